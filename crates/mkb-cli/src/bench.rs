@@ -91,6 +91,7 @@ fn generate_ground_truth(n: usize) -> Vec<GroundTruthDoc> {
             valid_until,
             temporal_precision: Some(TemporalPrecision::Day),
             occurred_at: None,
+            occurred_until: None,
         };
 
         let id = Document::generate_id(doc_type, &title, i as u32);
@@ -0,0 +1,90 @@
+//! Live vault values for shell completion.
+//!
+//! The scripts from `mkb completions <shell> --dynamic` call back into
+//! `mkb completions --complete-ids <prefix>` and friends on each tab press.
+//! Each [`CompletionSource`] answers one of those flags.
+
+use std::path::{Path, PathBuf};
+
+use mkb_index::IndexManager;
+use mkb_vault::Vault;
+
+/// Environment variable naming the vault completions read from.
+pub const VAULT_ENV: &str = "MKB_VAULT";
+
+/// Something that can suggest values for a partially typed argument.
+pub trait CompletionSource {
+    /// Values starting with `prefix`, sorted and without duplicates.
+    fn complete(&self, prefix: &str) -> Vec<String>;
+}
+
+/// IDs of indexed documents.
+pub struct DocumentIdSource<'a> {
+    pub index: &'a IndexManager,
+}
+
+/// Names of saved views.
+pub struct ViewNameSource<'a> {
+    pub vault: &'a Vault,
+}
+
+/// Document types present in the index.
+pub struct DocTypeSource<'a> {
+    pub index: &'a IndexManager,
+}
+
+/// Tags used by indexed documents.
+pub struct TagSource<'a> {
+    pub index: &'a IndexManager,
+}
+
+impl CompletionSource for DocumentIdSource<'_> {
+    fn complete(&self, prefix: &str) -> Vec<String> {
+        let docs = self.index.query_all().unwrap_or_default();
+        matching(docs.into_iter().map(|d| d.id), prefix)
+    }
+}
+
+impl CompletionSource for ViewNameSource<'_> {
+    fn complete(&self, prefix: &str) -> Vec<String> {
+        matching(self.vault.list_views().unwrap_or_default(), prefix)
+    }
+}
+
+impl CompletionSource for DocTypeSource<'_> {
+    fn complete(&self, prefix: &str) -> Vec<String> {
+        let docs = self.index.query_all().unwrap_or_default();
+        matching(docs.into_iter().map(|d| d.doc_type), prefix)
+    }
+}
+
+impl CompletionSource for TagSource<'_> {
+    fn complete(&self, prefix: &str) -> Vec<String> {
+        let docs = self.index.query_all().unwrap_or_default();
+        matching(docs.into_iter().flat_map(|d| d.tags), prefix)
+    }
+}
+
+fn matching(values: impl IntoIterator<Item = String>, prefix: &str) -> Vec<String> {
+    let mut values: Vec<String> = values
+        .into_iter()
+        .filter(|v| v.starts_with(prefix))
+        .collect();
+    values.sort();
+    values.dedup();
+    values
+}
+
+/// The vault completions read from: `explicit` if given, else `$MKB_VAULT`,
+/// else the nearest directory at or above `cwd` holding a `.mkb` folder.
+pub fn find_vault(explicit: Option<&Path>, cwd: &Path) -> Option<PathBuf> {
+    if let Some(path) = explicit {
+        return Some(path.to_path_buf());
+    }
+    if let Some(path) = std::env::var_os(VAULT_ENV).filter(|p| !p.is_empty()) {
+        return Some(PathBuf::from(path));
+    }
+    cwd.ancestors()
+        .find(|dir| dir.join(".mkb").is_dir())
+        .map(Path::to_path_buf)
+}
@@ -1,23 +1,39 @@
 //! MKB CLI — Markdown Knowledge Base for LLMs
 //!
-//! Commands: init, add, query, search, edit, rm, link, schema, gc, stats, status, ingest
+//! Commands: init, add, query, search, edit, append, rm, link, index, schema, gc, stats, status,
+//! ingest, lint-queries
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+mod completion;
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use clap::{CommandFactory, Parser};
+use indicatif::{ProgressBar, ProgressStyle};
 
-use mkb_core::document::Document;
+use mkb_core::document::{Document, ProvenanceRecord, ProvenanceType};
+use mkb_core::error::{EmbeddingError, MkbError};
 use mkb_core::frontmatter;
 use mkb_core::link::Link;
 use mkb_core::schema;
-use mkb_core::temporal::{DecayProfile, RawTemporalInput, TemporalPrecision};
-use mkb_index::IndexManager;
-use mkb_query::{compile, execute, format_results, OutputFormat};
-use mkb_vault::Vault;
+use mkb_core::temporal::{ParsedDateTime, RawTemporalInput, TemporalPrecision};
+use mkb_index::{
+    ConflictStrategy, EmbeddingProvider, IndexManager, MockEmbeddingProvider, RebuildStats,
+    SyncOutcome, TemporalInterpolator,
+};
+use mkb_parser::ast::{CompOp, Predicate, Value as MkqlValue, WhereClause};
+use mkb_query::{
+    compile_with_options, execute_streaming_with_provider, format_atom, format_results,
+    write_results_streaming, CompileOptions, CsvOpts, FeedOpts, OutputFormat, QueryResult,
+};
+use mkb_vault::importers::obsidian::ObsidianImporter;
+use mkb_vault::lock::VaultLock;
+use mkb_vault::watcher::{VaultWatcher, DEFAULT_DEBOUNCE};
+use mkb_vault::{AppendStyle, Vault, VaultHealthReport, VaultIssueKind};
 
 #[derive(Parser)]
 #[command(name = "mkb")]
@@ -47,15 +63,16 @@ enum Commands {
         #[arg(long)]
         title: String,
 
-        /// When this information was observed (ISO 8601 datetime)
-        #[arg(long)]
-        observed_at: DateTime<Utc>,
+        /// When this information was observed (RFC 3339, `YYYY-MM-DD HH:MM:SS` or `YYYY-MM-DD`)
+        #[arg(long, value_parser = parse_datetime_arg)]
+        observed_at: ParsedDateTime,
 
         /// When this information expires (computed from decay profile if omitted)
-        #[arg(long)]
-        valid_until: Option<DateTime<Utc>>,
+        #[arg(long, value_parser = parse_datetime_arg)]
+        valid_until: Option<ParsedDateTime>,
 
-        /// Temporal precision (exact, day, week, month, quarter, approximate, inferred)
+        /// Temporal precision (exact, day, week, month, quarter, approximate,
+        /// inferred); a bare `--observed-at` date is at best `day`
         #[arg(long, default_value = "day")]
         precision: String,
 
@@ -71,6 +88,11 @@ enum Commands {
         #[arg(long)]
         from_file: Option<PathBuf>,
 
+        /// How to generate the ID: counter, or content-hash (stable across
+        /// re-adds, which update the existing document)
+        #[arg(long, default_value = "counter")]
+        id_strategy: String,
+
         /// Vault directory (defaults to current directory)
         #[arg(long, default_value = ".")]
         vault: PathBuf,
@@ -90,10 +112,39 @@ enum Commands {
         #[arg(long)]
         search: Option<String>,
 
-        /// Output format: json, table, markdown, context
+        /// Output format: json, yaml, table, markdown, context, atom, pivot,
+        /// csv, tsv
         #[arg(long, short, default_value = "json")]
         format: String,
 
+        /// Template file for `--format context`, with `{{field}}` placeholders
+        #[arg(long)]
+        template: Option<PathBuf>,
+
+        /// Base URL of document links in `--format atom` feeds
+        #[arg(long)]
+        feed_base_url: Option<String>,
+
+        /// Field delimiter for `--format csv` (default `,`)
+        #[arg(long)]
+        delimiter: Option<char>,
+
+        /// Quote character for `--format csv`/`tsv` (default `"`)
+        #[arg(long)]
+        quote: Option<char>,
+
+        /// Include the `body` column in `--format csv`/`tsv` output
+        #[arg(long)]
+        include_body: bool,
+
+        /// Keep line breaks in quoted csv/tsv fields instead of writing `\n`
+        #[arg(long)]
+        multiline_body: bool,
+
+        /// Start csv/tsv output with a UTF-8 byte order mark, for Excel
+        #[arg(long)]
+        bom: bool,
+
         /// Save this query as a named view
         #[arg(long)]
         save: Option<String>,
@@ -102,6 +153,42 @@ enum Commands {
         #[arg(long)]
         view: Option<String>,
 
+        /// Print the query plan instead of executing the query
+        #[arg(long)]
+        explain: bool,
+
+        /// Print the query in canonical form instead of executing it
+        #[arg(long, conflicts_with = "explain")]
+        normalize: bool,
+
+        /// Print SQLite's query plan (which indexes it uses) instead of
+        /// executing the query
+        #[arg(long, conflicts_with_all = ["explain", "normalize"])]
+        explain_plan: bool,
+
+        /// Reject WHERE fields that are not in the schema for the queried type
+        #[arg(long)]
+        strict: bool,
+
+        /// Show the then-current version of each document at this time
+        /// (RFC 3339, `YYYY-MM-DD HH:MM:SS` or `YYYY-MM-DD`)
+        #[arg(long, value_parser = parse_datetime_arg, conflicts_with_all = ["mkql", "search", "view", "save", "explain"])]
+        as_of: Option<ParsedDateTime>,
+
+        /// Only documents with this provenance type: manual, import, llm,
+        /// api or watcher. Needs an MKQL query, --view or --doc-type
+        #[arg(long, value_parser = parse_provenance_type, conflicts_with_all = ["as_of", "search"])]
+        provenance_type: Option<ProvenanceType>,
+
+        /// Embedding provider for `NEAR()` and `SIMILARITY()` text: mock or
+        /// openai
+        #[arg(long, default_value = "mock")]
+        embedding_provider: String,
+
+        /// OpenAI API key (defaults to $OPENAI_API_KEY)
+        #[arg(long)]
+        openai_key: Option<String>,
+
         /// Vault directory (defaults to current directory)
         #[arg(long, default_value = ".")]
         vault: PathBuf,
@@ -125,6 +212,14 @@ enum Commands {
         #[arg(long)]
         embedding: Option<String>,
 
+        /// Embedding provider for the query text: mock or openai
+        #[arg(long, default_value = "mock")]
+        embedding_provider: String,
+
+        /// OpenAI API key (defaults to $OPENAI_API_KEY)
+        #[arg(long)]
+        openai_key: Option<String>,
+
         /// Maximum results to return
         #[arg(long, default_value = "10")]
         limit: usize,
@@ -156,6 +251,27 @@ enum Commands {
         vault: PathBuf,
     },
 
+    /// Append a timestamped entry to a document's body
+    Append {
+        /// Document ID (e.g., proj-alpha-001)
+        id: String,
+
+        /// Text to append
+        text: String,
+
+        /// Separator between the existing body and the new entry
+        #[arg(long, default_value = "\n\n")]
+        separator: String,
+
+        /// Don't start the entry with a timestamp heading
+        #[arg(long)]
+        no_timestamp: bool,
+
+        /// Vault directory (defaults to current directory)
+        #[arg(long, default_value = ".")]
+        vault: PathBuf,
+    },
+
     /// Remove a document (soft delete to archive)
     Rm {
         /// Document ID (e.g., proj-alpha-001)
@@ -170,12 +286,58 @@ enum Commands {
         vault: PathBuf,
     },
 
+    /// Restore an archived document
+    Restore {
+        /// Document ID (e.g., proj-alpha-001)
+        id: String,
+
+        /// Document type
+        #[arg(long)]
+        doc_type: String,
+
+        /// Vault directory (defaults to current directory)
+        #[arg(long, default_value = ".")]
+        vault: PathBuf,
+    },
+
+    /// Merge another vault's documents and index into this one
+    Merge {
+        /// Vault to merge from
+        other_vault: PathBuf,
+
+        /// Documents in both vaults: skip (keep ours), newer (take the later
+        /// _modified_at), or fail (abort without changes)
+        #[arg(long, default_value = "skip")]
+        conflict: String,
+
+        /// Vault directory (defaults to current directory)
+        #[arg(long, default_value = ".")]
+        vault: PathBuf,
+    },
+
     /// Manage links between documents
     Link {
         #[command(subcommand)]
         action: LinkAction,
     },
 
+    /// Manage the search index
+    Index {
+        #[command(subcommand)]
+        action: IndexAction,
+    },
+
+    /// Re-index vault files changed since they were last indexed
+    Reindex {
+        /// Rebuild the whole index from scratch instead
+        #[arg(long)]
+        full: bool,
+
+        /// Vault directory (defaults to current directory)
+        #[arg(long, default_value = ".")]
+        vault: PathBuf,
+    },
+
     /// Manage document schemas
     Schema {
         #[command(subcommand)]
@@ -184,6 +346,11 @@ enum Commands {
 
     /// Garbage collect: sweep stale documents
     Gc {
+        /// Decay every indexed confidence to its value now under the vault's
+        /// decay model
+        #[arg(long)]
+        recalculate_confidence: bool,
+
         /// Vault directory (defaults to current directory)
         #[arg(long, default_value = ".")]
         vault: PathBuf,
@@ -191,6 +358,11 @@ enum Commands {
 
     /// Vault statistics
     Stats {
+        /// Show the slowest and most frequent recorded MKQL queries instead
+        /// (enable recording with `mkb config query-history on`)
+        #[arg(long)]
+        query_history: bool,
+
         /// Vault directory (defaults to current directory)
         #[arg(long, default_value = ".")]
         vault: PathBuf,
@@ -201,10 +373,62 @@ enum Commands {
         /// Vault directory (defaults to current directory)
         #[arg(long, default_value = ".")]
         vault: PathBuf,
+
+        /// Count links whose target document does not exist
+        #[arg(long)]
+        check_links: bool,
+
+        /// Delete dangling links and report what was removed
+        #[arg(long, requires = "check_links")]
+        fix: bool,
+
+        /// Break down stored embeddings by model and count documents without one
+        #[arg(long)]
+        embeddings: bool,
+
+        /// List the document IDs that are only in the vault, only in the
+        /// index, or edited since they were indexed
+        #[arg(long)]
+        diff: bool,
+    },
+
+    /// Check vault files for parse errors, invalid temporal fields, ID
+    /// collisions and broken supersession chains
+    Validate {
+        /// Move unparseable files to the rejection log
+        #[arg(long)]
+        fix: bool,
+
+        /// Vault directory (defaults to current directory)
+        #[arg(long, default_value = ".")]
+        vault: PathBuf,
+    },
+
+    /// Serve query results over HTTP on localhost
+    Serve {
+        /// Serve Atom feeds of query results at `/feed?q=<mkql>`
+        #[arg(long)]
+        feed: bool,
+
+        /// Port to listen on
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+
+        /// Base URL of document links in feed entries
+        #[arg(long)]
+        feed_base_url: Option<String>,
+
+        /// Vault directory (defaults to current directory)
+        #[arg(long, default_value = ".")]
+        vault: PathBuf,
     },
 
     /// Start MCP (Model Context Protocol) server on stdio
     Mcp {
+        /// Keep the index in sync with vault file changes while serving
+        #[arg(long)]
+        watch: bool,
+
         /// Vault directory (defaults to current directory)
         #[arg(long, default_value = ".")]
         vault: PathBuf,
@@ -217,7 +441,7 @@ enum Commands {
         center: Option<String>,
 
         /// Document type to visualize (all documents of this type)
-        #[arg(long, name = "type")]
+        #[arg(long = "type")]
         doc_type: Option<String>,
 
         /// Traversal depth (hops from center, default 2)
@@ -233,6 +457,47 @@ enum Commands {
         vault: PathBuf,
     },
 
+    /// Print a document's version history, oldest first
+    Chain {
+        /// Any document ID in the chain
+        id: String,
+
+        /// Vault directory (defaults to current directory)
+        #[arg(long, default_value = ".")]
+        vault: PathBuf,
+    },
+
+    /// Attach a note or flag to a document in the index, leaving its file
+    /// untouched. Match annotated documents with `WHERE ANNOTATED('type')`
+    Annotate {
+        /// Document ID
+        id: String,
+
+        /// Annotation type, e.g. reviewed or needs-update
+        #[arg(long = "type", value_name = "TYPE", required_unless_present_any = ["list", "delete"])]
+        annotation_type: Option<String>,
+
+        /// Free-form note
+        #[arg(long, default_value = "")]
+        note: String,
+
+        /// Who added the annotation
+        #[arg(long, default_value = "cli")]
+        by: String,
+
+        /// List the document's annotations instead of adding one
+        #[arg(long, conflicts_with_all = ["annotation_type", "delete"])]
+        list: bool,
+
+        /// Remove the document's annotation with this ID
+        #[arg(long, value_name = "ANNOTATION_ID", conflicts_with = "annotation_type")]
+        delete: Option<i64>,
+
+        /// Vault directory (defaults to current directory)
+        #[arg(long, default_value = ".")]
+        vault: PathBuf,
+    },
+
     /// Manage saved views (named MKQL queries)
     View {
         #[command(subcommand)]
@@ -241,15 +506,57 @@ enum Commands {
 
     /// Watch vault for changes and auto-reindex
     Watch {
+        /// Quiet period in milliseconds that ends a batch of changes
+        /// (default 500)
+        #[arg(long, value_name = "MS")]
+        debounce_ms: Option<u64>,
+
         /// Vault directory (defaults to current directory)
         #[arg(long, default_value = ".")]
         vault: PathBuf,
     },
 
     /// Generate shell completions
+    #[command(hide = true, after_help = COMPLETIONS_INSTALL_HELP)]
     Completions {
         /// Shell to generate completions for (bash, zsh, fish, powershell)
-        shell: clap_complete::Shell,
+        #[arg(required_unless_present_any = ["complete_types", "complete_views", "complete_ids", "complete_tags"])]
+        shell: Option<clap_complete::Shell>,
+
+        /// Only complete commands and flags, not document IDs, types, views
+        /// and tags from the live vault
+        #[arg(long = "static")]
+        static_only: bool,
+
+        /// Complete values from the live vault (the default; kept for
+        /// existing setups)
+        #[arg(long, hide = true, conflicts_with = "static_only")]
+        dynamic: bool,
+
+        /// Print indexed document types starting with PREFIX, one per line
+        /// (used by completion scripts)
+        #[arg(long, hide = true, alias = "list-types", value_name = "PREFIX", num_args = 0..=1, default_missing_value = "")]
+        complete_types: Option<String>,
+
+        /// Print saved view names starting with PREFIX, one per line (used by
+        /// completion scripts)
+        #[arg(long, hide = true, alias = "list-views", value_name = "PREFIX", num_args = 0..=1, default_missing_value = "")]
+        complete_views: Option<String>,
+
+        /// Print indexed document IDs starting with PREFIX, one per line (used
+        /// by completion scripts)
+        #[arg(long, hide = true, alias = "list-ids", value_name = "PREFIX", num_args = 0..=1, default_missing_value = "")]
+        complete_ids: Option<String>,
+
+        /// Print tags starting with PREFIX, one per line (used by completion
+        /// scripts)
+        #[arg(long, hide = true, value_name = "PREFIX", num_args = 0..=1, default_missing_value = "")]
+        complete_tags: Option<String>,
+
+        /// Vault directory (defaults to $MKB_VAULT, then the nearest vault at or
+        /// above the current directory)
+        #[arg(long)]
+        vault: Option<PathBuf>,
     },
 
     /// Ingest files into the vault
@@ -261,6 +568,89 @@ enum Commands {
         #[arg(long, default_value = "document")]
         doc_type: String,
 
+        /// How to generate IDs for files without frontmatter: counter, or
+        /// content-hash (re-ingesting an unchanged file updates it in place)
+        #[arg(long, default_value = "counter")]
+        id_strategy: String,
+
+        /// Read each file as several `---` separated documents
+        #[arg(long)]
+        multi: bool,
+
+        /// Import a directory on N worker threads (0 picks one per CPU)
+        #[arg(long, value_name = "N", conflicts_with = "multi")]
+        parallel: Option<usize>,
+
+        /// Embed ingested documents with this provider: mock or openai
+        #[arg(long)]
+        embedding_provider: Option<String>,
+
+        /// OpenAI API key (defaults to $OPENAI_API_KEY)
+        #[arg(long)]
+        openai_key: Option<String>,
+
+        /// Vault directory (defaults to current directory)
+        #[arg(long, default_value = ".")]
+        vault: PathBuf,
+    },
+
+    /// Import notes from another tool's vault
+    Import {
+        /// Directory to import from
+        path: PathBuf,
+
+        /// Source format (obsidian)
+        #[arg(long = "from")]
+        from: String,
+
+        /// Document type for imported documents
+        #[arg(long, default_value = "document")]
+        doc_type: String,
+
+        /// Vault directory (defaults to current directory)
+        #[arg(long, default_value = ".")]
+        vault: PathBuf,
+    },
+
+    /// Export every vault document to a file
+    Export {
+        /// Output format: jsonl
+        #[arg(long, default_value = "jsonl")]
+        format: String,
+
+        /// File to write
+        #[arg(long)]
+        output: PathBuf,
+
+        /// Leave document bodies out of the export
+        #[arg(long)]
+        no_body: bool,
+
+        /// Vault directory (defaults to current directory)
+        #[arg(long, default_value = ".")]
+        vault: PathBuf,
+    },
+
+    /// Check every query in a directory of .mkql files
+    LintQueries {
+        /// Directory to scan (recursively) for .mkql files
+        dir: PathBuf,
+
+        /// Vault whose query settings and schemas apply (defaults to current
+        /// directory; outside a vault the defaults are used)
+        #[arg(long, default_value = ".")]
+        vault: PathBuf,
+    },
+
+    /// Show or change a vault setting (query-history, future-in-current,
+    /// future-in-fresh: on|off)
+    Config {
+        /// Setting name
+        key: String,
+
+        /// New value; omit to print the current one
+        value: Option<String>,
+
         /// Vault directory (defaults to current directory)
         #[arg(long, default_value = ".")]
         vault: PathBuf,
@@ -308,15 +698,94 @@ enum SchemaAction {
     /// List all available schemas
     List,
 
-    /// Validate a document against its schema
+    /// Validate a document, or with --all every document, against its schema
     Validate {
         /// Document ID
-        id: String,
+        #[arg(required_unless_present = "all", conflicts_with = "all")]
+        id: Option<String>,
 
         /// Document type
+        #[arg(long, required_unless_present = "all", conflicts_with = "all")]
+        doc_type: Option<String>,
+
+        /// Validate every document in the vault and exit non-zero on failures
+        #[arg(long)]
+        all: bool,
+
+        /// With --all, treat warnings as errors
+        #[arg(long, requires = "all")]
+        strict: bool,
+
+        /// Vault directory (defaults to current directory)
+        #[arg(long, default_value = ".")]
+        vault: PathBuf,
+    },
+
+    /// Export schemas to another schema language
+    Export {
+        /// Document type to export
+        #[arg(long, required_unless_present = "all", conflicts_with = "all")]
+        doc_type: Option<String>,
+
+        /// Export every built-in schema
+        #[arg(long)]
+        all: bool,
+
+        /// Output format: jsonschema
+        #[arg(long, default_value = "jsonschema")]
+        format: String,
+
+        /// Write one `<type>.schema.json` file per schema here instead of stdout
+        #[arg(long)]
+        output_dir: Option<PathBuf>,
+    },
+
+    /// Report changes between two schema versions that break existing documents
+    Diff {
+        /// Schema YAML file for the current version
+        #[arg(long)]
+        old: PathBuf,
+
+        /// Schema YAML file for the proposed version
         #[arg(long)]
+        new: PathBuf,
+    },
+
+    /// Infer a schema from a type's documents and save it as
+    /// `.mkb/schemas/<type>-inferred.yaml` for review
+    Infer {
+        /// Document type to sample
+        #[arg(long, alias = "type")]
         doc_type: String,
 
+        /// Number of documents to sample
+        #[arg(long, default_value_t = 100)]
+        sample: usize,
+
+        /// Vault directory (defaults to current directory)
+        #[arg(long, default_value = ".")]
+        vault: PathBuf,
+    },
+}
+
+#[derive(clap::Subcommand)]
+enum IndexAction {
+    /// Rebuild the index from vault files
+    Rebuild {
+        /// Vault directory (defaults to current directory)
+        #[arg(long, default_value = ".")]
+        vault: PathBuf,
+    },
+
+    /// Remove index entries whose vault files no longer exist
+    Prune {
+        /// Vault directory (defaults to current directory)
+        #[arg(long, default_value = ".")]
+        vault: PathBuf,
+    },
+
+    /// Regenerate the full-text search index from indexed documents
+    RepairFts {
         /// Vault directory (defaults to current directory)
         #[arg(long, default_value = ".")]
         vault: PathBuf,
@@ -354,7 +823,7 @@ enum ViewAction {
         /// View name
         name: String,
 
-        /// Output format: json, table, markdown
+        /// Output format: json, yaml, table, markdown
         #[arg(long, short, default_value = "json")]
         format: String,
 
@@ -388,6 +857,7 @@ fn main() -> Result<()> {
             body,
             tags,
             from_file,
+            id_strategy,
             vault,
         }) => {
             if let Some(file_path) = from_file {
@@ -398,10 +868,11 @@ fn main() -> Result<()> {
                     &doc_type,
                     &title,
                     observed_at,
-                    valid_until,
+                    valid_until.map(|v| v.utc),
                     &precision,
                     &body,
                     tags.as_deref(),
+                    parse_id_strategy(&id_strategy)?,
                 )
             }
         }
@@ -413,14 +884,78 @@ fn main() -> Result<()> {
             vault,
             save,
             view,
+            explain,
+            normalize,
+            explain_plan,
+            strict,
+            as_of,
+            template,
+            feed_base_url,
+            delimiter,
+            quote,
+            include_body,
+            multiline_body,
+            bom,
+            provenance_type,
+            embedding_provider,
+            openai_key,
         }) => {
+            if let Some(at) = as_of {
+                return cmd_query_as_of(&vault, at.utc, doc_type.as_deref());
+            }
+            // --explain flag: print the plan without touching the index
+            if explain {
+                let mkql_str = mkql
+                    .as_deref()
+                    .ok_or_else(|| anyhow::anyhow!("--explain requires an MKQL query string"))?;
+                return cmd_query_explain(&vault, mkql_str, &format);
+            }
+            if explain_plan {
+                let mkql_str = mkql.as_deref().ok_or_else(|| {
+                    anyhow::anyhow!("--explain-plan requires an MKQL query string")
+                })?;
+                return cmd_query_explain_plan(&vault, mkql_str, &format);
+            }
+            if normalize {
+                let mkql_str = mkql
+                    .as_deref()
+                    .ok_or_else(|| anyhow::anyhow!("--normalize requires an MKQL query string"))?;
+                let ast = mkb_parser::parse_mkql(mkql_str)
+                    .map_err(|e| anyhow::anyhow!("Parse error: {e}"))?;
+                println!("{ast}");
+                return Ok(());
+            }
+            let output_format = with_feed_base_url(
+                with_template(parse_format(&format)?, template.as_deref())?,
+                feed_base_url,
+            )?;
+            let output_format = with_csv_opts(
+                output_format,
+                CsvFlags {
+                    delimiter,
+                    quote,
+                    include_body,
+                    multiline_body,
+                    bom,
+                },
+            )?;
+            let embedder = parse_embedding_provider(&embedding_provider, openai_key)?;
             // --view flag: load saved view and run it
             if let Some(view_name) = view {
                 let v = Vault::open(&vault).context("Failed to open vault")?;
                 let saved = v
                     .load_view(&view_name)
                     .map_err(|e| anyhow::anyhow!("{e}"))?;
-                return cmd_query(&vault, Some(&saved.query), None, None, &format);
+                return cmd_query(
+                    &vault,
+                    Some(&saved.query),
+                    None,
+                    None,
+                    output_format,
+                    strict,
+                    provenance_type,
+                    &embedder,
+                );
             }
             // --save flag: save the query as a view, then run it
             if let Some(save_name) = save {
@@ -439,12 +974,23 @@ fn main() -> Result<()> {
                     anyhow::bail!("--save requires an MKQL query string");
                 }
             }
+            // --provenance-type filters MKQL; a bare --doc-type becomes a query
+            let mkql = match (mkql, provenance_type, &doc_type) {
+                (None, Some(_), Some(dtype)) => Some(format!("SELECT * FROM {dtype}")),
+                (None, Some(_), None) => {
+                    anyhow::bail!("--provenance-type needs an MKQL query, --view or --doc-type")
+                }
+                (mkql, _, _) => mkql,
+            };
             cmd_query(
                 &vault,
                 mkql.as_deref(),
                 doc_type.as_deref(),
                 search.as_deref(),
-                &format,
+                output_format,
+                strict,
+                provenance_type,
+                &embedder,
             )
         }
         Some(Commands::Search {
@@ -452,6 +998,8 @@ fn main() -> Result<()> {
             format,
             semantic,
             embedding,
+            embedding_provider,
+            openai_key,
             limit,
             vault,
         }) => {
@@ -460,6 +1008,7 @@ fn main() -> Result<()> {
                     &vault,
                     query.as_deref(),
                     embedding.as_deref(),
+                    &parse_embedding_provider(&embedding_provider, openai_key)?,
                     limit,
                     &format,
                 )
@@ -475,11 +1024,36 @@ fn main() -> Result<()> {
             body,
             vault,
         }) => cmd_edit(&vault, &id, &set, title.as_deref(), body.as_deref()),
+        Some(Commands::Append {
+            id,
+            text,
+            separator,
+            no_timestamp,
+            vault,
+        }) => cmd_append(
+            &vault,
+            &id,
+            &text,
+            AppendStyle {
+                separator,
+                timestamp_header: !no_timestamp,
+            },
+        ),
         Some(Commands::Rm {
             id,
             doc_type,
             vault,
         }) => cmd_rm(&vault, &doc_type, &id),
+        Some(Commands::Restore {
+            id,
+            doc_type,
+            vault,
+        }) => cmd_restore(&vault, &doc_type, &id),
+        Some(Commands::Merge {
+            other_vault,
+            conflict,
+            vault,
+        }) => cmd_merge(&vault, &other_vault, parse_conflict_strategy(&conflict)?),
         Some(Commands::Link { action }) => match action {
             LinkAction::Create {
                 source,
@@ -489,15 +1063,59 @@ fn main() -> Result<()> {
             } => cmd_link_create(&vault, &source, &rel, &target),
             LinkAction::List { id, reverse, vault } => cmd_link_list(&vault, &id, reverse),
         },
+        Some(Commands::Index { action }) => match action {
+            IndexAction::Rebuild { vault } => cmd_index_rebuild(&vault),
+            IndexAction::Prune { vault } => cmd_index_prune(&vault),
+            IndexAction::RepairFts { vault } => cmd_index_repair_fts(&vault),
+        },
+        Some(Commands::Reindex { full, vault }) => {
+            if full {
+                cmd_index_rebuild(&vault)
+            } else {
+                cmd_reindex(&vault)
+            }
+        }
         Some(Commands::Schema { action }) => match action {
             SchemaAction::List => cmd_schema_list(),
             SchemaAction::Validate {
                 id,
                 doc_type,
+                all,
+                strict,
                 vault,
-            } => cmd_schema_validate(&vault, &doc_type, &id),
+            } => match (id, doc_type) {
+                (Some(id), Some(doc_type)) if !all => cmd_schema_validate(&vault, &doc_type, &id),
+                _ => cmd_schema_validate_all(&vault, strict),
+            },
+            SchemaAction::Export {
+                doc_type,
+                all: _,
+                format,
+                output_dir,
+            } => cmd_schema_export(doc_type.as_deref(), &format, output_dir.as_deref()),
+            SchemaAction::Diff { old, new } => cmd_schema_diff(&old, &new),
+            SchemaAction::Infer {
+                doc_type,
+                sample,
+                vault,
+            } => cmd_schema_infer(&vault, &doc_type, sample),
+        },
+        Some(Commands::Mcp { vault, watch }) => cmd_mcp(&vault, watch),
+        Some(Commands::Chain { id, vault }) => cmd_chain(&vault, &id),
+        Some(Commands::Annotate {
+            id,
+            annotation_type,
+            note,
+            by,
+            list,
+            delete,
+            vault,
+        }) => match (annotation_type, delete) {
+            (Some(annotation_type), _) => cmd_annotate(&vault, &id, &annotation_type, &note, &by),
+            (None, Some(annotation_id)) => cmd_annotate_delete(&vault, &id, annotation_id),
+            (None, None) if list => cmd_annotate_list(&vault, &id),
+            (None, None) => anyhow::bail!("--type, --list or --delete is required"),
         },
-        Some(Commands::Mcp { vault }) => cmd_mcp(&vault),
         Some(Commands::Graph {
             center,
             doc_type,
@@ -526,21 +1144,113 @@ fn main() -> Result<()> {
             } => cmd_view_run(&vault, &name, &format),
             ViewAction::Delete { name, vault } => cmd_view_delete(&vault, &name),
         },
-        Some(Commands::Gc { vault }) => cmd_gc(&vault),
-        Some(Commands::Stats { vault }) => cmd_stats(&vault),
-        Some(Commands::Status { vault }) => cmd_status(&vault),
-        Some(Commands::Watch { vault }) => cmd_watch(&vault),
-        Some(Commands::Completions { shell }) => {
-            let mut cmd = Cli::command();
-            clap_complete::generate(shell, &mut cmd, "mkb", &mut std::io::stdout());
-            Ok(())
+        Some(Commands::Gc {
+            recalculate_confidence,
+            vault,
+        }) => cmd_gc(&vault, recalculate_confidence),
+        Some(Commands::Stats {
+            query_history,
+            vault,
+        }) => {
+            if query_history {
+                cmd_stats_query_history(&vault)
+            } else {
+                cmd_stats(&vault)
+            }
+        }
+        Some(Commands::Status {
+            vault,
+            check_links,
+            fix,
+            embeddings,
+            diff,
+        }) => cmd_status(&vault, check_links, fix, embeddings, diff),
+        Some(Commands::Validate { fix, vault }) => cmd_validate(&vault, fix),
+        Some(Commands::Serve {
+            feed,
+            port,
+            feed_base_url,
+            vault,
+        }) => cmd_serve(&vault, feed, port, feed_base_url),
+        Some(Commands::Watch { vault, debounce_ms }) => cmd_watch(
+            &vault,
+            debounce_ms.map_or(DEFAULT_DEBOUNCE, Duration::from_millis),
+        ),
+        Some(Commands::Completions {
+            shell,
+            static_only,
+            dynamic: _,
+            complete_types,
+            complete_views,
+            complete_ids,
+            complete_tags,
+            vault,
+        }) => {
+            let request = if let Some(prefix) = complete_types {
+                Some((CompletionKind::Types, prefix))
+            } else if let Some(prefix) = complete_views {
+                Some((CompletionKind::Views, prefix))
+            } else if let Some(prefix) = complete_ids {
+                Some((CompletionKind::Ids, prefix))
+            } else {
+                complete_tags.map(|prefix| (CompletionKind::Tags, prefix))
+            };
+            match request {
+                Some((kind, prefix)) => {
+                    cmd_completion_values(vault.as_deref(), kind, &prefix);
+                    Ok(())
+                }
+                None => cmd_completions(shell.context("A shell is required")?, !static_only),
+            }
         }
         Some(Commands::Ingest {
             path,
             doc_type,
+            id_strategy,
+            multi,
+            parallel,
+            embedding_provider,
+            openai_key,
             vault,
-        }) => cmd_ingest(&vault, &path, &doc_type),
-        None => {
+        }) => {
+            let embedder = embedding_provider
+                .map(|name| parse_embedding_provider(&name, openai_key))
+                .transpose()?;
+            let id_strategy = parse_id_strategy(&id_strategy)?;
+            match parallel {
+                Some(parallelism) => cmd_ingest_parallel(
+                    &vault,
+                    &path,
+                    &doc_type,
+                    id_strategy,
+                    parallelism,
+                    embedder.as_ref(),
+                ),
+                None => cmd_ingest(
+                    &vault,
+                    &path,
+                    &doc_type,
+                    id_strategy,
+                    multi,
+                    embedder.as_ref(),
+                ),
+            }
+        }
+        Some(Commands::Import {
+            path,
+            from,
+            doc_type,
+            vault,
+        }) => cmd_import(&vault, &path, &from, &doc_type),
+        Some(Commands::Export {
+            format,
+            output,
+            no_body,
+            vault,
+        }) => cmd_export(&vault, &format, &output, !no_body),
+        Some(Commands::LintQueries { dir, vault }) => cmd_lint_queries(&dir, &vault),
+        Some(Commands::Config { key, value, vault }) => cmd_config(&vault, &key, value.as_deref()),
+        None => {
             println!(
                 "MKB v{} — Markdown Knowledge Base for LLMs",
                 env!("CARGO_PKG_VERSION")
@@ -572,26 +1282,31 @@ fn cmd_add(
     vault_path: &Path,
     doc_type: &str,
     title: &str,
-    observed_at: DateTime<Utc>,
+    observed_at: ParsedDateTime,
     valid_until: Option<DateTime<Utc>>,
     precision: &str,
     body: &str,
     tags: Option<&str>,
+    id_strategy: IdStrategy,
 ) -> Result<()> {
     let vault = Vault::open(vault_path).context("Failed to open vault")?;
-    let index = open_index(vault_path)?;
+    let _lock = lock_vault(&vault)?;
+    let mut index = open_index(vault_path)?;
 
-    let temporal_precision = parse_precision(precision)?;
-    let profile = DecayProfile::default_profile();
+    // A bare date can't claim a time of day
+    let temporal_precision = observed_at.coerce_precision(parse_precision(precision)?);
+    let profile = vault
+        .load_decay_profile()
+        .context("Failed to load decay model")?;
 
-    let counter = mkb_vault::next_counter(vault_path, doc_type, &mkb_vault::slugify(title));
-    let id = Document::generate_id(doc_type, title, counter);
+    let id = id_strategy.generate(vault_path, doc_type, &observed_at.utc, title);
 
     let input = RawTemporalInput {
-        observed_at: Some(observed_at),
+        observed_at: Some(observed_at.utc),
         valid_until,
         temporal_precision: Some(temporal_precision),
         occurred_at: None,
+        occurred_until: None,
     };
 
     let mut doc = Document::new(id, doc_type.to_string(), title.to_string(), input, &profile)
@@ -601,16 +1316,16 @@ fn cmd_add(
     if let Some(tags_str) = tags {
         doc.tags = tags_str.split(',').map(|s| s.trim().to_string()).collect();
     }
+    doc.provenance = Some(cli_provenance(ProvenanceType::Manual, None));
 
-    let path = vault.create(&doc).context("Failed to create document")?;
-    index
-        .index_document(&doc)
-        .context("Failed to index document")?;
+    let (path, updated) = upsert_document(&vault, &mut doc)?;
+    index_with_links(&mut index, &doc)?;
 
     let output = serde_json::json!({
         "id": doc.id,
         "type": doc.doc_type,
         "title": doc.title,
+        "updated": updated,
         "path": path.display().to_string(),
         "observed_at": doc.temporal.observed_at.to_rfc3339(),
         "valid_until": doc.temporal.valid_until.to_rfc3339(),
@@ -621,49 +1336,129 @@ fn cmd_add(
 
 fn cmd_add_from_file(vault_path: &Path, file_path: &Path) -> Result<()> {
     let vault = Vault::open(vault_path).context("Failed to open vault")?;
-    let index = open_index(vault_path)?;
+    let _lock = lock_vault(&vault)?;
+    let mut index = open_index(vault_path)?;
 
     let content = fs::read_to_string(file_path)
         .with_context(|| format!("Failed to read file: {}", file_path.display()))?;
 
-    let doc = frontmatter::parse_document(&content).context("Failed to parse frontmatter")?;
+    let docs = frontmatter::parse_documents(&content).context("Failed to parse frontmatter")?;
 
-    let path = vault.create(&doc).context("Failed to create document")?;
-    index
-        .index_document(&doc)
-        .context("Failed to index document")?;
+    let mut created = Vec::with_capacity(docs.len());
+    for mut doc in docs {
+        doc.provenance
+            .get_or_insert_with(|| cli_provenance(ProvenanceType::Manual, Some(file_path)));
+        let path = match vault.create(&doc) {
+            Ok(path) => path,
+            Err(MkbError::DuplicateDocument { id }) => {
+                anyhow::bail!("Document already exists: {id}. Use `mkb edit` to change it.")
+            }
+            Err(e) => return Err(e).context("Failed to create document"),
+        };
+        index_with_links(&mut index, &doc)?;
+        created.push(serde_json::json!({
+            "id": doc.id,
+            "type": doc.doc_type,
+            "title": doc.title,
+            "path": path.display().to_string(),
+        }));
+    }
 
-    let output = serde_json::json!({
-        "id": doc.id,
-        "type": doc.doc_type,
-        "title": doc.title,
-        "path": path.display().to_string(),
-    });
+    // A single document prints as an object, a batch file as an array
+    let output = if created.len() == 1 {
+        created.remove(0)
+    } else {
+        serde_json::Value::Array(created)
+    };
     println!("{}", serde_json::to_string_pretty(&output)?);
     Ok(())
 }
 
 // === Query ===
 
+/// Compile options from the vault's `.mkb/query.yaml`; `strict` also checks
+/// fields against the vault's schemas.
+fn compile_options(vault_path: &Path, strict: bool) -> Result<CompileOptions> {
+    let vault = Vault::open(vault_path).context("Failed to open vault")?;
+    let settings = vault
+        .load_query_settings()
+        .context("Failed to load query settings")?;
+    let mut options = CompileOptions::from(settings);
+    if strict {
+        let registry = vault.schema_registry().context("Failed to load schemas")?;
+        options.validate_fields = true;
+        options.schemas = registry.iter().cloned().collect();
+    }
+    Ok(options)
+}
+
+/// [`compile_options`] for commands that also work outside a vault: with no
+/// `.mkb` directory at `vault_path`, the defaults apply.
+fn compile_options_if_vault(vault_path: &Path, strict: bool) -> Result<CompileOptions> {
+    if vault_path.join(".mkb").is_dir() {
+        compile_options(vault_path, strict)
+    } else {
+        Ok(CompileOptions::default())
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn cmd_query(
     vault_path: &Path,
     mkql: Option<&str>,
     doc_type: Option<&str>,
     search: Option<&str>,
-    format: &str,
+    output_format: OutputFormat,
+    strict: bool,
+    provenance_type: Option<ProvenanceType>,
+    embedder: &Embedder,
 ) -> Result<()> {
     let index = open_index(vault_path)?;
 
     if let Some(mkql_str) = mkql {
         // Full MKQL query execution
-        let ast =
+        let start = Instant::now();
+        let mut ast =
             mkb_parser::parse_mkql(mkql_str).map_err(|e| anyhow::anyhow!("Parse error: {e}"))?;
-        let compiled = compile(&ast).map_err(|e| anyhow::anyhow!("Compile error: {e}"))?;
-        let result =
-            execute(&index, &compiled).map_err(|e| anyhow::anyhow!("Execution error: {e}"))?;
-
-        let output_format = parse_format(format)?;
-        println!("{}", format_results(&result, output_format));
+        if let Some(provenance_type) = provenance_type {
+            ast.and_where(WhereClause::Predicate(Predicate::Comparison {
+                field: "provenance_type".to_string(),
+                op: CompOp::Eq,
+                value: MkqlValue::String(provenance_type.as_str().to_string()),
+            }));
+        }
+        let compiled = compile_with_options(&ast, compile_options(vault_path, strict)?)
+            .map_err(|e| anyhow::anyhow!("Compile error: {e}"))?;
+        if compiled.uses_semantic || compiled.similarity_order.is_some() {
+            check_embedding_model(&index, embedder)?;
+        }
+        let mut stream = execute_streaming_with_provider(&index, &compiled, embedder)
+            .map_err(|e| anyhow::anyhow!("Execution error: {e}"))?;
+
+        // Small results are formatted as a whole; past the threshold, rows
+        // are printed as they are read
+        let mut head = Vec::new();
+        for row in stream.by_ref() {
+            head.push(row.map_err(|e| anyhow::anyhow!("Execution error: {e}"))?);
+            if head.len() > STREAMING_THRESHOLD {
+                let mut out = std::io::stdout().lock();
+                let total = write_results_streaming(&mut out, head, stream, output_format)
+                    .map_err(|e| anyhow::anyhow!("Execution error: {e}"))?;
+                return record_query(&index, mkql_str, start, total);
+            }
+        }
+        let total = head.len();
+        let result = QueryResult { rows: head, total };
+        // CSV ends every record with a newline; a blank line after it
+        // would read as an empty record
+        if matches!(output_format, OutputFormat::Csv(_)) {
+            print!("{}", format_results(&result, output_format));
+        } else {
+            println!("{}", format_results(&result, output_format));
+        }
+        // Release the read statement before writing to the history
+        drop(stream);
+        record_query(&index, mkql_str, start, total)?;
     } else if let Some(query) = search {
         let results = index.search_fts(query).context("FTS search failed")?;
         let json: Vec<serde_json::Value> = results
@@ -674,18 +1469,72 @@ fn cmd_query(
                     "type": r.doc_type,
                     "title": r.title,
                     "rank": r.rank,
+                    "tags": r.tags,
                 })
             })
             .collect();
         println!("{}", serde_json::to_string_pretty(&json)?);
     } else if let Some(dtype) = doc_type {
         let results = index.query_by_type(dtype).context("Query by type failed")?;
-        print_indexed_docs(&results)?;
+        print_indexed_docs_as(&results, &output_format)?;
     } else {
         let results = index.query_all().context("Query all failed")?;
-        print_indexed_docs(&results)?;
+        print_indexed_docs_as(&results, &output_format)?;
+    }
+
+    Ok(())
+}
+
+/// Add a finished MKQL query to the query history, if recording is on.
+fn record_query(index: &IndexManager, mkql: &str, start: Instant, total: usize) -> Result<()> {
+    index
+        .record_query(mkql, start.elapsed(), total)
+        .context("Failed to record query history")
+}
+
+/// Reconstruct the vault at `at`, optionally limited to one document type.
+fn cmd_query_as_of(vault_path: &Path, at: DateTime<Utc>, doc_type: Option<&str>) -> Result<()> {
+    let index = open_index(vault_path)?;
+    let mut results =
+        TemporalInterpolator::snapshot(&index, at).context("Snapshot query failed")?;
+    if let Some(dtype) = doc_type {
+        results.retain(|doc| doc.doc_type == dtype);
+    }
+    print_indexed_docs(&results)
+}
+
+fn cmd_query_explain(vault_path: &Path, mkql: &str, format: &str) -> Result<()> {
+    let ast = mkb_parser::parse_mkql(mkql).map_err(|e| anyhow::anyhow!("Parse error: {e}"))?;
+    let compiled = compile_with_options(&ast, compile_options_if_vault(vault_path, false)?)
+        .map_err(|e| anyhow::anyhow!("Compile error: {e}"))?;
+    let plan = mkb_query::explain(&compiled);
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&plan)?);
+    } else {
+        print!("{}", plan.to_text());
     }
+    Ok(())
+}
 
+fn cmd_query_explain_plan(vault_path: &Path, mkql: &str, format: &str) -> Result<()> {
+    let ast = mkb_parser::parse_mkql(mkql).map_err(|e| anyhow::anyhow!("Parse error: {e}"))?;
+    let compiled = compile_with_options(&ast, compile_options(vault_path, false)?)
+        .map_err(|e| anyhow::anyhow!("Compile error: {e}"))?;
+    let index = open_index(vault_path)?;
+    let plan = mkb_query::explain_plan(&index, &compiled).context("EXPLAIN QUERY PLAN failed")?;
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&plan)?);
+    } else {
+        // Rows come parent first; indent each under the step it belongs to
+        let mut depths: HashMap<i64, usize> = HashMap::new();
+        for row in &plan {
+            let depth = depths.get(&row.order).map_or(0, |d| d + 1);
+            depths.insert(row.selectid, depth);
+            println!("{}{}", "  ".repeat(depth), row.detail);
+        }
+    }
     Ok(())
 }
 
@@ -706,6 +1555,7 @@ fn cmd_search(vault_path: &Path, query: &str, format: &str) -> Result<()> {
                         "type": r.doc_type,
                         "title": r.title,
                         "rank": r.rank,
+                        "tags": r.tags,
                     })
                 })
                 .collect();
@@ -734,6 +1584,7 @@ fn cmd_search(vault_path: &Path, query: &str, format: &str) -> Result<()> {
                         "type": r.doc_type,
                         "title": r.title,
                         "rank": r.rank,
+                        "tags": r.tags,
                     })
                 })
                 .collect();
@@ -743,12 +1594,85 @@ fn cmd_search(vault_path: &Path, query: &str, format: &str) -> Result<()> {
     Ok(())
 }
 
+// === Embeddings ===
+
+/// Embedding provider selected with `--embedding-provider`.
+enum Embedder {
+    Mock(MockEmbeddingProvider),
+    #[cfg(feature = "openai")]
+    OpenAi(mkb_index::OpenAIEmbeddingProvider),
+}
+
+impl EmbeddingProvider for Embedder {
+    fn model(&self) -> &str {
+        match self {
+            Self::Mock(provider) => provider.model(),
+            #[cfg(feature = "openai")]
+            Self::OpenAi(provider) => provider.model(),
+        }
+    }
+
+    async fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>, EmbeddingError> {
+        match self {
+            Self::Mock(provider) => provider.embed(texts).await,
+            #[cfg(feature = "openai")]
+            Self::OpenAi(provider) => provider.embed(texts).await,
+        }
+    }
+}
+
+fn parse_embedding_provider(name: &str, openai_key: Option<String>) -> Result<Embedder> {
+    match name {
+        "mock" => Ok(Embedder::Mock(MockEmbeddingProvider)),
+        #[cfg(feature = "openai")]
+        "openai" => {
+            let key = openai_key
+                .or_else(|| std::env::var("OPENAI_API_KEY").ok())
+                .context("--embedding-provider openai needs --openai-key or $OPENAI_API_KEY")?;
+            Ok(Embedder::OpenAi(mkb_index::OpenAIEmbeddingProvider::new(
+                key,
+            )))
+        }
+        #[cfg(not(feature = "openai"))]
+        "openai" => {
+            let _ = openai_key;
+            anyhow::bail!("mkb was built without the `openai` feature")
+        }
+        other => anyhow::bail!("Unknown embedding provider '{other}'. Use: mock, openai"),
+    }
+}
+
+/// Embed `texts` on a single-threaded runtime.
+#[tokio::main(flavor = "current_thread")]
+async fn embed_texts(embedder: &Embedder, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+    embedder
+        .embed(texts)
+        .await
+        .context("Failed to generate embeddings")
+}
+
+/// Fail if the index holds embeddings from a model other than `embedder`'s,
+/// since vectors from different models are not comparable.
+fn check_embedding_model(index: &IndexManager, embedder: &Embedder) -> Result<()> {
+    let models = index.embedding_models()?;
+    if !models.is_empty() && !models.iter().any(|(m, _)| m == embedder.model()) {
+        let stored: Vec<&str> = models.iter().map(|(m, _)| m.as_str()).collect();
+        anyhow::bail!(
+            "Index embeddings were generated by {}, not '{}'. Pass a matching --embedding-provider.",
+            stored.join(", "),
+            embedder.model()
+        );
+    }
+    Ok(())
+}
+
 // === Semantic Search ===
 
 fn cmd_search_semantic(
     vault_path: &Path,
     query: Option<&str>,
     embedding_json: Option<&str>,
+    embedder: &Embedder,
     limit: usize,
     format: &str,
 ) -> Result<()> {
@@ -758,7 +1682,8 @@ fn cmd_search_semantic(
         serde_json::from_str(json_str)
             .context("Invalid embedding JSON (expected array of floats)")?
     } else if let Some(q) = query {
-        mkb_index::mock_embedding(q)
+        check_embedding_model(&index, embedder)?;
+        embed_texts(embedder, &[q])?.remove(0)
     } else {
         anyhow::bail!("Semantic search requires either a query string or --embedding vector");
     };
@@ -827,6 +1752,7 @@ fn cmd_edit(
     new_body: Option<&str>,
 ) -> Result<()> {
     let vault = Vault::open(vault_path).context("Failed to open vault")?;
+    let _lock = lock_vault(&vault)?;
     let index = open_index(vault_path)?;
 
     // Find the document type by searching the index
@@ -873,10 +1799,42 @@ fn cmd_edit(
     Ok(())
 }
 
+// === Append ===
+
+fn cmd_append(vault_path: &Path, id: &str, text: &str, style: AppendStyle) -> Result<()> {
+    let vault = Vault::open(vault_path)
+        .context("Failed to open vault")?
+        .with_append_style(style);
+    let _lock = lock_vault(&vault)?;
+    let index = open_index(vault_path)?;
+
+    let all = index.query_all().context("Failed to query index")?;
+    let indexed = all
+        .iter()
+        .find(|d| d.id == id)
+        .ok_or_else(|| anyhow::anyhow!("Document not found: {id}"))?;
+
+    let doc = vault
+        .append_body(&indexed.doc_type, id, text)
+        .context("Failed to append to document")?;
+    index
+        .index_document(&doc)
+        .context("Failed to re-index document")?;
+
+    let output = serde_json::json!({
+        "id": doc.id,
+        "path": vault.document_path(&doc.doc_type, &doc.id).display().to_string(),
+        "modified_at": doc.modified_at.to_rfc3339(),
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
 // === Rm ===
 
 fn cmd_rm(vault_path: &Path, doc_type: &str, id: &str) -> Result<()> {
     let vault = Vault::open(vault_path).context("Failed to open vault")?;
+    let _lock = lock_vault(&vault)?;
     let index = open_index(vault_path)?;
 
     let archive_path = vault
@@ -894,6 +1852,95 @@ fn cmd_rm(vault_path: &Path, doc_type: &str, id: &str) -> Result<()> {
     Ok(())
 }
 
+fn cmd_restore(vault_path: &Path, doc_type: &str, id: &str) -> Result<()> {
+    let vault = Vault::open(vault_path).context("Failed to open vault")?;
+    let _lock = lock_vault(&vault)?;
+    let index = open_index(vault_path)?;
+
+    let path = vault
+        .restore_from_archive(doc_type, id)
+        .context("Failed to restore document")?;
+    let doc = vault
+        .read(doc_type, id)
+        .context("Failed to read restored document")?;
+    index
+        .index_document(&doc)
+        .context("Failed to index document")?;
+
+    let output = serde_json::json!({
+        "id": id,
+        "restored_to": path.display().to_string(),
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+// === Merge ===
+
+fn cmd_merge(vault_path: &Path, other_path: &Path, conflict: ConflictStrategy) -> Result<()> {
+    let vault = Vault::open(vault_path).context("Failed to open vault")?;
+    let other = Vault::open(other_path).context("Failed to open vault to merge from")?;
+    let _lock = lock_vault(&vault)?;
+    let index = open_index(vault_path)?;
+    let other_index = open_index(other_path)?;
+
+    let stats = index
+        .merge_from(&other_index, conflict)
+        .context("Failed to merge index")?;
+
+    // Copy the files the index merge took, by the same rule
+    let mut copied = 0;
+    for source in other.list_documents().context("Failed to list documents")? {
+        let relative = source.strip_prefix(other.root()).unwrap_or(&source);
+        let dest = vault.root().join(relative);
+        let take = if dest.exists() {
+            conflict == ConflictStrategy::OverwriteWithNewer
+                && match (read_modified_at(&source), read_modified_at(&dest)) {
+                    (Some(theirs), Some(ours)) => theirs > ours,
+                    (Some(_), None) => true,
+                    _ => false,
+                }
+        } else {
+            true
+        };
+        if take {
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(&source, &dest)
+                .with_context(|| format!("Failed to copy {}", source.display()))?;
+            copied += 1;
+        }
+    }
+
+    let output = serde_json::json!({
+        "inserted": stats.inserted,
+        "skipped": stats.skipped,
+        "overwritten": stats.overwritten,
+        "failed": stats.failed,
+        "files_copied": copied,
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+/// The `_modified_at` of a document file, if it parses.
+fn read_modified_at(path: &Path) -> Option<DateTime<Utc>> {
+    let content = fs::read_to_string(path).ok()?;
+    frontmatter::parse_document(&content)
+        .ok()
+        .map(|doc| doc.modified_at)
+}
+
+fn parse_conflict_strategy(s: &str) -> Result<ConflictStrategy> {
+    match s.to_lowercase().as_str() {
+        "skip" => Ok(ConflictStrategy::SkipExisting),
+        "newer" => Ok(ConflictStrategy::OverwriteWithNewer),
+        "fail" => Ok(ConflictStrategy::FailOnConflict),
+        other => anyhow::bail!("Unknown conflict strategy '{other}'. Valid: skip, newer, fail"),
+    }
+}
+
 // === Link ===
 
 fn cmd_link_create(vault_path: &Path, source: &str, rel: &str, target: &str) -> Result<()> {
@@ -924,19 +1971,72 @@ fn cmd_link_create(vault_path: &Path, source: &str, rel: &str, target: &str) ->
         .chain(std::iter::once(link))
         .collect();
 
+    let warnings = check_link_constraints(vault_path, &index, source, &new_links)?;
+
     index
         .store_links(source, &new_links)
         .context("Failed to store link")?;
 
-    let output = serde_json::json!({
+    let mut output = serde_json::json!({
         "source": source,
         "rel": rel,
         "target": target,
     });
+    if !warnings.is_empty() {
+        output["warnings"] = serde_json::json!(warnings);
+    }
     println!("{}", serde_json::to_string_pretty(&output)?);
     Ok(())
 }
 
+/// Validate `source`'s full link set against its schema's link constraints.
+/// Fails on errors and returns the warnings. Sources that are not indexed or
+/// have no schema are not checked.
+fn check_link_constraints(
+    vault_path: &Path,
+    index: &IndexManager,
+    source: &str,
+    links: &[Link],
+) -> Result<Vec<String>> {
+    let Some(source_doc) = index
+        .query_by_id(source)
+        .context("Failed to look up source document")?
+    else {
+        return Ok(Vec::new());
+    };
+    let vault = Vault::open(vault_path).context("Failed to open vault")?;
+    let registry = vault.schema_registry().context("Failed to load schemas")?;
+    let Some(schema_def) = registry.get(&source_doc.doc_type) else {
+        return Ok(Vec::new());
+    };
+
+    let mut target_types = HashMap::new();
+    for link in links {
+        if let Some(doc) = index
+            .query_by_id(&link.target)
+            .context("Failed to look up link target")?
+        {
+            target_types.insert(link.target.clone(), doc.doc_type);
+        }
+    }
+
+    match schema_def
+        .validate_links(links, |id| target_types.get(id).cloned())
+        .into_result(&source_doc.doc_type)
+    {
+        Ok(warnings) => Ok(warnings),
+        Err(MkbError::SchemaViolation { errors, .. }) => {
+            let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+            anyhow::bail!(
+                "Link violates schema '{}': {}",
+                schema_def.name,
+                errors.join("; ")
+            )
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
 fn cmd_link_list(vault_path: &Path, id: &str, reverse: bool) -> Result<()> {
     let index = open_index(vault_path)?;
 
@@ -1030,89 +2130,728 @@ fn cmd_schema_validate(vault_path: &Path, doc_type: &str, id: &str) -> Result<()
     Ok(())
 }
 
-// === GC ===
-
-fn cmd_gc(vault_path: &Path) -> Result<()> {
-    let index = open_index(vault_path)?;
-
-    let now = Utc::now().to_rfc3339();
-    let stale_ids = index
-        .staleness_sweep(&now)
-        .context("Failed to run staleness sweep")?;
-
+fn cmd_schema_validate_all(vault_path: &Path, strict: bool) -> Result<()> {
+    let vault = Vault::open(vault_path).context("Failed to open vault")?;
+    let registry = vault.schema_registry().context("Failed to load schemas")?;
+    let schemas: Vec<schema::SchemaDefinition> = registry.iter().cloned().collect();
+
+    let report = vault
+        .validate_all_against_schemas(&schemas)
+        .context("Failed to validate vault")?;
+    let errors: Vec<serde_json::Value> = report
+        .documents
+        .iter()
+        .map(|d| {
+            serde_json::json!({
+                "id": d.id,
+                "doc_type": d.doc_type,
+                "errors": d.errors,
+                "warnings": d.warnings,
+            })
+        })
+        .collect();
+    let invalid = report.invalid(strict);
     let output = serde_json::json!({
-        "swept_at": now,
-        "stale_count": stale_ids.len(),
-        "stale_ids": stale_ids,
+        "valid": report.valid(strict),
+        "invalid": invalid,
+        "warnings": report.with_warnings(),
+        "errors": errors,
     });
     println!("{}", serde_json::to_string_pretty(&output)?);
+
+    if invalid > 0 {
+        anyhow::bail!(
+            "{invalid} of {} documents failed schema validation",
+            report.checked
+        );
+    }
     Ok(())
 }
 
-// === Stats ===
+fn cmd_schema_export(
+    doc_type: Option<&str>,
+    format: &str,
+    output_dir: Option<&Path>,
+) -> Result<()> {
+    if format != "jsonschema" {
+        anyhow::bail!("Unknown schema export format '{format}'. Valid: jsonschema");
+    }
 
-fn cmd_stats(vault_path: &Path) -> Result<()> {
-    let vault = Vault::open(vault_path).context("Failed to open vault")?;
-    let index = open_index(vault_path)?;
+    let schemas: Vec<schema::SchemaDefinition> = match doc_type {
+        Some(dtype) => {
+            let found = schema::built_in_schemas()
+                .into_iter()
+                .find(|s| s.name == dtype)
+                .with_context(|| format!("No schema defined for type '{dtype}'"))?;
+            vec![found]
+        }
+        None => schema::built_in_schemas(),
+    };
 
-    let doc_count = index.count().context("Failed to count documents")?;
-    let files = vault.list_documents().unwrap_or_default();
+    let Some(dir) = output_dir else {
+        let exported: Vec<serde_json::Value> = schemas
+            .iter()
+            .map(schema::SchemaDefinition::to_json_schema)
+            .collect();
+        let output = match (doc_type, exported.as_slice()) {
+            (Some(_), [single]) => single.clone(),
+            _ => serde_json::Value::Array(exported),
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    };
 
-    // Count by type
-    let all_docs = index.query_all().unwrap_or_default();
-    let mut type_counts: HashMap<String, usize> = HashMap::new();
-    for doc in &all_docs {
-        *type_counts.entry(doc.doc_type.clone()).or_insert(0) += 1;
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let mut written = Vec::new();
+    for schema_def in &schemas {
+        let path = dir.join(format!("{}.schema.json", schema_def.name));
+        let json = serde_json::to_string_pretty(&schema_def.to_json_schema())?;
+        fs::write(&path, json + "\n")
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        written.push(path.display().to_string());
     }
 
     let output = serde_json::json!({
-        "vault_root": vault.root().display().to_string(),
-        "indexed_documents": doc_count,
-        "vault_files": files.len(),
-        "by_type": type_counts,
+        "exported": written.len(),
+        "files": written,
     });
     println!("{}", serde_json::to_string_pretty(&output)?);
     Ok(())
 }
 
-// === Status ===
+fn read_schema_file(path: &Path) -> Result<schema::SchemaDefinition> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let def: schema::SchemaDefinition = serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse schema {}", path.display()))?;
+    def.resolve(&schema::built_in_schemas())
+        .with_context(|| format!("Failed to resolve schema {}", path.display()))
+}
+
+fn cmd_schema_diff(old_path: &Path, new_path: &Path) -> Result<()> {
+    let old = read_schema_file(old_path)?;
+    let new = read_schema_file(new_path)?;
+
+    let changes = schema::SchemaRegistry::detect_breaking_changes(&old, &new);
+    let fatal = changes
+        .iter()
+        .filter(|c| c.severity == schema::BreakingChangeSeverity::Fatal)
+        .count();
+    let migration = schema::SchemaMigration::fill_defaults(&old, &new);
+
+    let output = serde_json::json!({
+        "schema": new.name,
+        "old_version": old.version,
+        "new_version": new.version,
+        "version_bumped": new.version > old.version,
+        "compatible": fatal == 0,
+        "changes": changes,
+        "migration_defaults": migration.set_defaults,
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
 
-fn cmd_status(vault_path: &Path) -> Result<()> {
+fn cmd_schema_infer(vault_path: &Path, doc_type: &str, sample: usize) -> Result<()> {
     let vault = Vault::open(vault_path).context("Failed to open vault")?;
     let index = open_index(vault_path)?;
 
-    let doc_count = index.count().context("Failed to count documents")?;
-    let rejection_count = vault.rejection_count().unwrap_or(0);
-    let files = vault.list_documents().unwrap_or_default();
-
-    // Index health: compare file count with indexed count
-    let index_synced = files.len() as u64 == doc_count;
+    let docs = index
+        .query_by_type(doc_type)
+        .context("Query by type failed")?
+        .into_iter()
+        .take(sample)
+        .map(|indexed| {
+            vault
+                .read(doc_type, &indexed.id)
+                .with_context(|| format!("Failed to read {}", indexed.id))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if docs.is_empty() {
+        anyhow::bail!("No documents of type '{doc_type}' to infer a schema from");
+    }
 
-    let now = Utc::now().to_rfc3339();
-    let stale_count = index.staleness_sweep(&now).unwrap_or_default().len();
+    let inferred = schema::SchemaDefinition::infer_from_documents(doc_type, &docs);
+    let path = vault.inferred_schema_path(doc_type);
+    fs::create_dir_all(vault.schemas_dir()).context("Failed to create schema directory")?;
+    // Fields come out of a HashMap; sort them so the draft reads well
+    let mut yaml = serde_yaml::to_value(&inferred)?;
+    if let Some(fields) = yaml.get_mut("fields").and_then(|f| f.as_mapping_mut()) {
+        let mut entries: Vec<_> = std::mem::take(fields).into_iter().collect();
+        entries.sort_by(|a, b| a.0.as_str().cmp(&b.0.as_str()));
+        fields.extend(entries);
+    }
+    fs::write(&path, serde_yaml::to_string(&yaml)?)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
 
+    let mut fields: Vec<serde_json::Value> = inferred
+        .fields
+        .iter()
+        .map(|(name, def)| {
+            serde_json::json!({
+                "name": name,
+                "type": def.field_type,
+                "required": def.required,
+                "values": def.values,
+            })
+        })
+        .collect();
+    fields.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
     let output = serde_json::json!({
-        "vault_root": vault.root().display().to_string(),
-        "indexed_documents": doc_count,
-        "vault_files": files.len(),
-        "index_synced": index_synced,
-        "rejection_count": rejection_count,
-        "stale_documents": stale_count,
+        "doc_type": doc_type,
+        "sampled": docs.len(),
+        "path": path.display().to_string(),
+        "fields": fields,
     });
     println!("{}", serde_json::to_string_pretty(&output)?);
     Ok(())
 }
 
-// === Ingest ===
+// === GC ===
 
-fn cmd_ingest(vault_path: &Path, input_path: &Path, doc_type: &str) -> Result<()> {
+fn cmd_gc(vault_path: &Path, recalculate_confidence: bool) -> Result<()> {
     let vault = Vault::open(vault_path).context("Failed to open vault")?;
+    let _lock = lock_vault(&vault)?;
     let index = open_index(vault_path)?;
 
-    let paths: Vec<PathBuf> = if input_path.is_dir() {
-        // Collect all .md files from directory
-        fs::read_dir(input_path)
-            .context("Failed to read directory")?
+    let interrupted = vault.remove_interrupted_writes();
+    let now = Utc::now();
+    let stale_ids = index
+        .staleness_sweep(&now.to_rfc3339())
+        .context("Failed to run staleness sweep")?;
+    let recalculated = if recalculate_confidence {
+        let profile = vault
+            .load_decay_profile()
+            .context("Failed to load decay model")?;
+        Some(
+            index
+                .recalculate_all_confidence(&profile, now)
+                .context("Failed to recalculate confidence")?,
+        )
+    } else {
+        None
+    };
+    index.checkpoint().context("Failed to checkpoint index")?;
+
+    let mut output = serde_json::json!({
+        "swept_at": now.to_rfc3339(),
+        "stale_count": stale_ids.len(),
+        "stale_ids": stale_ids,
+        "interrupted_writes_removed": interrupted.len(),
+    });
+    if let Some(count) = recalculated {
+        output["confidence_updated"] = serde_json::json!(count);
+    }
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+// === Index ===
+
+fn cmd_index_rebuild(vault_path: &Path) -> Result<()> {
+    let vault = Vault::open(vault_path).context("Failed to open vault")?;
+
+    let bar = rebuild_progress_bar("Rebuilding index")?;
+    let stats = IndexManager::rebuild_from_vault(&vault, |done, total| {
+        bar.set_length(total as u64);
+        bar.set_position(done as u64);
+    })
+    .context("Failed to rebuild index")?;
+    bar.finish_and_clear();
+
+    print_rebuild_stats(&stats)
+}
+
+fn cmd_reindex(vault_path: &Path) -> Result<()> {
+    let vault = Vault::open(vault_path).context("Failed to open vault")?;
+    let index = open_index(vault_path)?;
+
+    let bar = rebuild_progress_bar("Reindexing")?;
+    let stats = index
+        .reindex_incremental(&vault, |done, total| {
+            bar.set_length(total as u64);
+            bar.set_position(done as u64);
+        })
+        .context("Failed to reindex vault")?;
+    bar.finish_and_clear();
+
+    print_rebuild_stats(&stats)
+}
+
+fn rebuild_progress_bar(label: &str) -> Result<ProgressBar> {
+    let bar = ProgressBar::new(0);
+    bar.set_style(
+        ProgressStyle::with_template(&format!("{label} {{bar:40}} {{pos}}/{{len}}"))
+            .context("Invalid progress template")?,
+    );
+    Ok(bar)
+}
+
+fn print_rebuild_stats(stats: &RebuildStats) -> Result<()> {
+    let output = serde_json::json!({
+        "indexed": stats.indexed,
+        "skipped": stats.skipped,
+        "failed": stats.failed,
+        "elapsed_ms": stats.elapsed.as_millis(),
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+fn cmd_index_prune(vault_path: &Path) -> Result<()> {
+    let vault = Vault::open(vault_path).context("Failed to open vault")?;
+    let index = open_index(vault_path)?;
+
+    let pruned = index
+        .prune_orphans(&vault)
+        .context("Failed to prune orphaned index entries")?;
+
+    let output = serde_json::json!({ "pruned": pruned });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+fn cmd_index_repair_fts(vault_path: &Path) -> Result<()> {
+    let index = open_index(vault_path)?;
+
+    let before = index
+        .fts_integrity_check()
+        .context("Failed to check full-text index")?;
+    index
+        .rebuild_fts()
+        .context("Failed to rebuild full-text index")?;
+    let after = index
+        .fts_integrity_check()
+        .context("Failed to check full-text index")?;
+
+    let output = serde_json::json!({
+        "consistent_before": before.ok,
+        "problem": before.message,
+        "consistent_after": after.ok,
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+// === Stats ===
+
+fn cmd_stats(vault_path: &Path) -> Result<()> {
+    let vault = Vault::open(vault_path).context("Failed to open vault")?;
+    let index = open_index(vault_path)?;
+
+    let doc_count = index.count().context("Failed to count documents")?;
+    let files = vault.list_documents().unwrap_or_default();
+
+    // Count by type
+    let all_docs = index.query_all().unwrap_or_default();
+    let mut type_counts: BTreeMap<String, usize> = BTreeMap::new();
+    for doc in &all_docs {
+        *type_counts.entry(doc.doc_type.clone()).or_insert(0) += 1;
+    }
+
+    let newest_per_type: BTreeMap<String, serde_json::Value> = index
+        .query_newest_per_type()
+        .context("Failed to query newest documents")?
+        .into_iter()
+        .map(|(doc_type, doc)| {
+            let row = serde_json::json!({
+                "id": doc.id,
+                "title": doc.title,
+                "observed_at": doc.observed_at,
+            });
+            (doc_type, row)
+        })
+        .collect();
+
+    let output = serde_json::json!({
+        "vault_root": vault.root().display().to_string(),
+        "indexed_documents": doc_count,
+        "vault_files": files.len(),
+        "by_type": type_counts,
+        "newest_per_type": newest_per_type,
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+/// The slowest and most frequent queries recorded in the query history.
+fn cmd_stats_query_history(vault_path: &Path) -> Result<()> {
+    let index = open_index(vault_path)?;
+    let slowest: Vec<serde_json::Value> = index
+        .slowest_queries(10)
+        .context("Failed to read query history")?
+        .into_iter()
+        .map(|q| {
+            serde_json::json!({
+                "mkql": q.mkql,
+                "executed_at": q.executed_at,
+                "duration_ms": q.duration_ms,
+                "result_count": q.result_count,
+            })
+        })
+        .collect();
+    let most_frequent: Vec<serde_json::Value> = index
+        .frequent_queries(10)
+        .context("Failed to read query history")?
+        .into_iter()
+        .map(|q| {
+            serde_json::json!({
+                "mkql": q.mkql,
+                "executions": q.executions,
+                "p50_ms": q.p50_ms,
+                "p99_ms": q.p99_ms,
+            })
+        })
+        .collect();
+
+    let output = serde_json::json!({
+        "recording": index.query_history_enabled().context("Failed to read settings")?,
+        "slowest": slowest,
+        "most_frequent": most_frequent,
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+// === Config ===
+
+fn cmd_config(vault_path: &Path, key: &str, value: Option<&str>) -> Result<()> {
+    if !matches!(
+        key,
+        "query-history" | "future-in-current" | "future-in-fresh"
+    ) {
+        anyhow::bail!(
+            "Unknown setting: {key} (expected query-history, future-in-current or future-in-fresh)"
+        );
+    }
+    let enabled = value.map(|v| parse_switch(key, v)).transpose()?;
+    let current = if key == "query-history" {
+        let index = open_index(vault_path)?;
+        if let Some(enabled) = enabled {
+            index
+                .set_query_history_enabled(enabled)
+                .context("Failed to update settings")?;
+        }
+        index
+            .query_history_enabled()
+            .context("Failed to read settings")?
+    } else {
+        let vault = Vault::open(vault_path).context("Failed to open vault")?;
+        let mut settings = vault
+            .load_query_settings()
+            .context("Failed to load query settings")?;
+        let setting = if key == "future-in-current" {
+            &mut settings.future_in_current
+        } else {
+            &mut settings.future_in_fresh
+        };
+        if let Some(enabled) = enabled {
+            *setting = enabled;
+        }
+        let current = *setting;
+        if enabled.is_some() {
+            vault
+                .save_query_settings(&settings)
+                .context("Failed to update settings")?;
+        }
+        current
+    };
+    println!("{key} = {}", if current { "on" } else { "off" });
+    Ok(())
+}
+
+/// Parse an on/off setting value.
+fn parse_switch(key: &str, value: &str) -> Result<bool> {
+    match value {
+        "on" | "true" => Ok(true),
+        "off" | "false" => Ok(false),
+        other => anyhow::bail!("Invalid value for {key}: {other} (expected on or off)"),
+    }
+}
+
+// === Status ===
+
+fn cmd_status(
+    vault_path: &Path,
+    check_links: bool,
+    fix: bool,
+    embeddings: bool,
+    diff: bool,
+) -> Result<()> {
+    let vault = Vault::open(vault_path).context("Failed to open vault")?;
+    let index = open_index(vault_path)?;
+
+    let doc_count = index.count().context("Failed to count documents")?;
+    let rejection_count = vault.rejection_count().unwrap_or(0);
+    let archived_count = vault.archived_count().unwrap_or(0);
+    let files = vault.list_documents().unwrap_or_default();
+
+    // Index health: compare file count with indexed count
+    let index_synced = files.len() as u64 == doc_count;
+
+    let now = Utc::now().to_rfc3339();
+    let stale_count = index.staleness_sweep(&now).unwrap_or_default().len();
+
+    let mut output = serde_json::json!({
+        "vault_root": vault.root().display().to_string(),
+        "indexed_documents": doc_count,
+        "vault_files": files.len(),
+        "index_synced": index_synced,
+        "rejection_count": rejection_count,
+        "archived_documents": archived_count,
+        "stale_documents": stale_count,
+    });
+
+    if check_links {
+        let dangling = if fix {
+            index.remove_dangling_links()
+        } else {
+            index.detect_dangling_links()
+        }
+        .context("Failed to check links")?;
+        output["dangling_links"] = serde_json::json!(dangling.len());
+        if fix {
+            output["removed_links"] = dangling
+                .iter()
+                .map(|l| {
+                    serde_json::json!({
+                        "source_id": l.source_id,
+                        "target_id": l.target_id,
+                        "rel": l.rel,
+                    })
+                })
+                .collect();
+        }
+    }
+    if embeddings {
+        let models = index
+            .get_embedding_model_stats()
+            .context("Failed to read embedding stats")?;
+        let missing = index
+            .documents_without_embedding()
+            .context("Failed to find documents without embeddings")?;
+        output["embeddings"] = serde_json::json!({
+            "models": models
+                .iter()
+                .map(|m| {
+                    serde_json::json!({
+                        "model": m.model,
+                        "count": m.count,
+                        "oldest_created_at": m.oldest_created_at,
+                        "newest_created_at": m.newest_created_at,
+                    })
+                })
+                .collect::<Vec<_>>(),
+            "without_embedding": missing.len(),
+        });
+    }
+    if diff {
+        let diff = index
+            .diff_against_vault(&vault)
+            .context("Failed to compare vault with index")?;
+        output["diff"] = serde_json::json!({
+            "in_vault_not_index": diff.in_vault_not_index,
+            "in_index_not_vault": diff.in_index_not_vault,
+            "modified_since_index": diff.modified_since_index,
+        });
+    }
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+fn cmd_validate(vault_path: &Path, fix: bool) -> Result<()> {
+    let vault = Vault::open(vault_path).context("Failed to open vault")?;
+    let _lock = fix.then(|| lock_vault(&vault)).transpose()?;
+    let validate = || -> Result<VaultHealthReport> {
+        let mut report = vault.validate().context("Failed to validate vault")?;
+        if vault_path.join(".mkb/index/mkb.db").exists() {
+            let index = open_index(vault_path)?;
+            report.check_index_count(index.count().context("Failed to count documents")?);
+        }
+        Ok(report)
+    };
+
+    let mut report = validate()?;
+    let mut rejected = Vec::new();
+    let mut removed = Vec::new();
+    if fix {
+        for issue in &report.issues {
+            if let (VaultIssueKind::ParseError, Some(path)) = (issue.kind, &issue.path) {
+                vault
+                    .reject_file(path, &issue.description)
+                    .with_context(|| format!("Failed to reject {}", path.display()))?;
+                rejected.push(path.display().to_string());
+            }
+        }
+        removed = vault.remove_interrupted_writes();
+        if !rejected.is_empty() || !removed.is_empty() {
+            report = validate()?;
+        }
+    }
+
+    let issues: Vec<serde_json::Value> = report
+        .issues
+        .iter()
+        .map(|issue| {
+            serde_json::json!({
+                "severity": issue.severity.to_string(),
+                "kind": format!("{:?}", issue.kind),
+                "description": issue.description,
+                "path": issue.path.as_ref().map(|p| p.display().to_string()),
+            })
+        })
+        .collect();
+    let mut output = serde_json::json!({
+        "healthy": report.healthy,
+        "documents": report.documents,
+        "issues": issues,
+    });
+    if fix {
+        output["rejected"] = serde_json::json!(rejected);
+        output["removed"] = serde_json::json!(removed
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect::<Vec<_>>());
+    }
+    println!("{}", serde_json::to_string_pretty(&output)?);
+
+    if !report.healthy {
+        anyhow::bail!("Vault has errors");
+    }
+    Ok(())
+}
+
+// === Lint queries ===
+
+/// Parse, validate and compile every query in every `.mkql` file under `dir`.
+///
+/// Queries are separated by `;`; `--` starts a comment that runs to the end
+/// of the line. Failures are reported as `file:line: error` on stderr.
+fn cmd_lint_queries(dir: &Path, vault_path: &Path) -> Result<()> {
+    let options = compile_options_if_vault(vault_path, true)?;
+    let mut files = Vec::new();
+    collect_mkql_files(dir, &mut files)?;
+    files.sort();
+
+    let mut query_count = 0;
+    let mut errors = Vec::new();
+
+    for file in &files {
+        let content = fs::read_to_string(file)
+            .with_context(|| format!("Failed to read {}", file.display()))?;
+
+        for (line, query) in split_mkql_queries(&content) {
+            query_count += 1;
+            if let Err(e) = lint_query(&query, &options) {
+                eprintln!("{}:{line}: {e}\n    {query}", file.display());
+                errors.push(serde_json::json!({
+                    "file": file.display().to_string(),
+                    "line": line,
+                    "query": query,
+                    "error": e,
+                }));
+            }
+        }
+    }
+
+    let output = serde_json::json!({
+        "files": files.len(),
+        "queries": query_count,
+        "errors": errors,
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+
+    if !errors.is_empty() {
+        anyhow::bail!("{} of {} queries failed lint", errors.len(), query_count);
+    }
+    Ok(())
+}
+
+fn lint_query(mkql: &str, options: &CompileOptions) -> std::result::Result<(), String> {
+    let ast = mkb_parser::parse_mkql(mkql).map_err(|e| format!("Parse error: {e}"))?;
+    mkb_query::validate_identifiers(&ast, &options.schemas)
+        .map_err(|e| format!("Invalid identifier: {e}"))?;
+    compile_with_options(&ast, options.clone()).map_err(|e| format!("Compile error: {e}"))?;
+    Ok(())
+}
+
+fn collect_mkql_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in
+        fs::read_dir(dir).with_context(|| format!("Failed to read directory {}", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_mkql_files(&path, files)?;
+        } else if path.extension().and_then(|e| e.to_str()) == Some("mkql") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Split `.mkql` file content into `(start_line, query)` pairs.
+///
+/// `;` and `--` inside single-quoted strings are left alone.
+fn split_mkql_queries(content: &str) -> Vec<(usize, String)> {
+    let mut queries = Vec::new();
+    let mut current = String::new();
+    let mut start_line = 0;
+    let mut line = 1;
+    let mut in_string = false;
+    let mut chars = content.chars().peekable();
+
+    let mut flush = |current: &mut String, start_line: usize| {
+        let query = current.trim();
+        if !query.is_empty() {
+            queries.push((start_line, query.to_string()));
+        }
+        current.clear();
+    };
+
+    while let Some(c) = chars.next() {
+        match c {
+            '-' if !in_string && chars.peek() == Some(&'-') => {
+                while chars.next_if(|&next| next != '\n').is_some() {}
+                continue;
+            }
+            ';' if !in_string => {
+                flush(&mut current, start_line);
+                continue;
+            }
+            '\'' => in_string = !in_string,
+            _ => {}
+        }
+        if current.trim().is_empty() && !c.is_whitespace() {
+            start_line = line;
+        }
+        if c == '\n' {
+            line += 1;
+        }
+        current.push(c);
+    }
+    flush(&mut current, start_line);
+
+    queries
+}
+
+// === Ingest ===
+
+fn cmd_ingest(
+    vault_path: &Path,
+    input_path: &Path,
+    doc_type: &str,
+    id_strategy: IdStrategy,
+    multi: bool,
+    embedder: Option<&Embedder>,
+) -> Result<()> {
+    let vault = Vault::open(vault_path).context("Failed to open vault")?;
+    let _lock = lock_vault(&vault)?;
+    let index = open_index(vault_path)?;
+
+    let paths: Vec<PathBuf> = if input_path.is_dir() {
+        // Collect all .md files from directory
+        fs::read_dir(input_path)
+            .context("Failed to read directory")?
             .filter_map(|entry| {
                 let entry = entry.ok()?;
                 let path = entry.path();
@@ -1129,6 +2868,7 @@ fn cmd_ingest(vault_path: &Path, input_path: &Path, doc_type: &str) -> Result<()
 
     let mut ingested = Vec::new();
     let mut rejected = Vec::new();
+    let mut to_embed: Vec<(String, String)> = Vec::new();
 
     for file_path in &paths {
         let content = match fs::read_to_string(file_path) {
@@ -1142,12 +2882,71 @@ fn cmd_ingest(vault_path: &Path, input_path: &Path, doc_type: &str) -> Result<()
             }
         };
 
-        match ingest_single_file(&vault, &index, vault_path, &content, doc_type) {
+        if multi {
+            let docs = match frontmatter::parse_documents(&content) {
+                Ok(docs) => docs,
+                Err(e) => {
+                    rejected.push(serde_json::json!({
+                        "file": file_path.display().to_string(),
+                        "error": e.to_string(),
+                    }));
+                    continue;
+                }
+            };
+            for doc in docs {
+                let mut doc = doc.sanitize(Document::MAX_BODY_BYTES, Document::MAX_FIELD_BYTES);
+                doc.provenance
+                    .get_or_insert_with(|| cli_provenance(ProvenanceType::Import, Some(file_path)));
+                let stored = vault
+                    .create(&doc)
+                    .context("Failed to create document")
+                    .and_then(|_| {
+                        index
+                            .index_document(&doc)
+                            .context("Failed to index document")
+                    });
+                match stored {
+                    Ok(()) => {
+                        ingested.push(serde_json::json!({
+                            "file": file_path.display().to_string(),
+                            "id": doc.id,
+                        }));
+                        to_embed.push((doc.id, doc.body));
+                    }
+                    Err(e) => rejected.push(serde_json::json!({
+                        "file": file_path.display().to_string(),
+                        "id": doc.id,
+                        "error": format!("{e:#}"),
+                    })),
+                }
+            }
+            continue;
+        }
+
+        // Content-hash IDs need an observation time that survives re-ingest
+        let observed_at = match id_strategy {
+            IdStrategy::Counter => Utc::now(),
+            IdStrategy::ContentHash => fs::metadata(file_path)
+                .and_then(|m| m.modified())
+                .map_or_else(|_| Utc::now(), DateTime::<Utc>::from),
+        };
+
+        match ingest_single_file(
+            &vault,
+            &index,
+            vault_path,
+            file_path,
+            &content,
+            doc_type,
+            id_strategy,
+            observed_at,
+        ) {
             Ok(doc_id) => {
                 ingested.push(serde_json::json!({
                     "file": file_path.display().to_string(),
                     "id": doc_id,
                 }));
+                to_embed.push((doc_id, content));
             }
             Err(e) => {
                 // Write to rejection log
@@ -1164,9 +2963,12 @@ fn cmd_ingest(vault_path: &Path, input_path: &Path, doc_type: &str) -> Result<()
         }
     }
 
+    let embedded = embed_ingested(&index, embedder, &to_embed)?;
+
     let output = serde_json::json!({
         "ingested": ingested.len(),
         "rejected": rejected.len(),
+        "embedded": embedded,
         "files": ingested,
         "errors": rejected,
     });
@@ -1174,15 +2976,107 @@ fn cmd_ingest(vault_path: &Path, input_path: &Path, doc_type: &str) -> Result<()
     Ok(())
 }
 
+/// Ingest a directory with [`Vault::import_directory_with`]: files are
+/// parsed on `parallelism` threads while this thread indexes the results
+/// in one transaction.
+fn cmd_ingest_parallel(
+    vault_path: &Path,
+    input_path: &Path,
+    doc_type: &str,
+    id_strategy: IdStrategy,
+    parallelism: usize,
+    embedder: Option<&Embedder>,
+) -> Result<()> {
+    if !input_path.is_dir() {
+        anyhow::bail!("--parallel needs a directory: {}", input_path.display());
+    }
+    if id_strategy != IdStrategy::Counter {
+        anyhow::bail!("--parallel only supports --id-strategy counter");
+    }
+
+    let vault = Vault::open(vault_path).context("Failed to open vault")?;
+    let _lock = lock_vault(&vault)?;
+    let index = open_index(vault_path)?;
+
+    let mut ingested = Vec::new();
+    let mut to_embed: Vec<(String, String)> = Vec::new();
+    let stats = index
+        .with_transaction(|index| {
+            vault.import_directory_with(input_path, doc_type, parallelism, |file, doc| {
+                index.index_document(doc)?;
+                ingested.push(serde_json::json!({
+                    "file": file.display().to_string(),
+                    "id": doc.id,
+                }));
+                to_embed.push((doc.id.clone(), doc.body.clone()));
+                Ok(())
+            })
+        })
+        .context("Import failed")?;
+
+    let embedded = embed_ingested(&index, embedder, &to_embed)?;
+
+    let rejected: Vec<serde_json::Value> = stats
+        .rejections
+        .iter()
+        .map(|r| {
+            serde_json::json!({
+                "file": r.path.display().to_string(),
+                "error": r.error,
+            })
+        })
+        .collect();
+    let output = serde_json::json!({
+        "ingested": stats.imported,
+        "rejected": stats.rejected,
+        "embedded": embedded,
+        "elapsed_secs": stats.elapsed.as_secs_f64(),
+        "throughput_docs_per_sec": stats.throughput_docs_per_sec,
+        "files": ingested,
+        "errors": rejected,
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+/// Embed and store each `(doc_id, text)` with `embedder`, if given.
+/// Returns how many embeddings were stored.
+fn embed_ingested(
+    index: &IndexManager,
+    embedder: Option<&Embedder>,
+    to_embed: &[(String, String)],
+) -> Result<usize> {
+    let mut embedded = 0;
+    if let Some(embedder) = embedder.filter(|_| !to_embed.is_empty()) {
+        let texts: Vec<&str> = to_embed.iter().map(|(_, text)| text.as_str()).collect();
+        let vectors = embed_texts(embedder, &texts)?;
+        for ((doc_id, _), vector) in to_embed.iter().zip(&vectors) {
+            index
+                .store_embedding(doc_id, vector, embedder.model())
+                .context("Failed to store embedding")?;
+            embedded += 1;
+        }
+    }
+    Ok(embedded)
+}
+
+#[allow(clippy::too_many_arguments)]
 fn ingest_single_file(
     vault: &Vault,
     index: &IndexManager,
     vault_path: &Path,
+    file_path: &Path,
     content: &str,
     default_doc_type: &str,
+    id_strategy: IdStrategy,
+    observed_at: DateTime<Utc>,
 ) -> Result<String> {
+    let provenance = cli_provenance(ProvenanceType::Import, Some(file_path));
+
     // Try to parse as frontmatter document first
     if let Ok(doc) = frontmatter::parse_document(content) {
+        let mut doc = doc.sanitize(Document::MAX_BODY_BYTES, Document::MAX_FIELD_BYTES);
+        doc.provenance.get_or_insert(provenance);
         let doc_id = doc.id.clone();
         vault.create(&doc).context("Failed to create document")?;
         index
@@ -1199,24 +3093,27 @@ fn ingest_single_file(
         .map(|l| l.trim_start_matches("# ").to_string())
         .unwrap_or_else(|| "Untitled".to_string());
 
-    let profile = DecayProfile::default_profile();
-    let counter =
-        mkb_vault::next_counter(vault_path, default_doc_type, &mkb_vault::slugify(&title));
-    let id = Document::generate_id(default_doc_type, &title, counter);
+    let profile = vault
+        .load_decay_profile()
+        .context("Failed to load decay model")?;
+    let id = id_strategy.generate(vault_path, default_doc_type, &observed_at, &title);
 
     let input = RawTemporalInput {
-        observed_at: Some(Utc::now()),
+        observed_at: Some(observed_at),
         valid_until: None,
         temporal_precision: Some(TemporalPrecision::Day),
         occurred_at: None,
+        occurred_until: None,
     };
 
     let mut doc = Document::new(id, default_doc_type.to_string(), title, input, &profile)
         .context("Temporal gate rejected document")?;
     doc.body = content.to_string();
+    doc.provenance = Some(provenance);
+    let mut doc = doc.sanitize(Document::MAX_BODY_BYTES, Document::MAX_FIELD_BYTES);
 
     let doc_id = doc.id.clone();
-    vault.create(&doc).context("Failed to create document")?;
+    upsert_document(vault, &mut doc)?;
     index
         .index_document(&doc)
         .context("Failed to index document")?;
@@ -1224,56 +3121,289 @@ fn ingest_single_file(
     Ok(doc_id)
 }
 
-// === Watch ===
+/// Provenance of a document `mkb` writes, naming the file it was read
+/// from, if any.
+fn cli_provenance(source_type: ProvenanceType, file_path: Option<&Path>) -> ProvenanceRecord {
+    ProvenanceRecord {
+        original_filename: file_path
+            .and_then(Path::file_name)
+            .map(|name| name.to_string_lossy().into_owned()),
+        ..ProvenanceRecord::new(source_type, "cli")
+    }
+}
 
-fn cmd_watch(vault_path: &Path) -> Result<()> {
-    let _vault = Vault::open(vault_path).context("Failed to open vault")?;
-    let index = open_index(vault_path)?;
+// === Import ===
 
+fn cmd_import(vault_path: &Path, source: &Path, from: &str, doc_type: &str) -> Result<()> {
+    if from != "obsidian" {
+        anyhow::bail!("Unknown import source '{from}'. Valid: obsidian");
+    }
+    if !source.is_dir() {
+        anyhow::bail!("Not a directory: {}", source.display());
+    }
+
+    let vault = Vault::open(vault_path).context("Failed to open vault")?;
+    let _lock = lock_vault(&vault)?;
+    let mut index = open_index(vault_path)?;
+
+    let report = ObsidianImporter::new(source)
+        .with_doc_type(doc_type)
+        .import(&vault)
+        .context("Import failed")?;
+
+    for doc in &report.documents {
+        index_with_links(&mut index, doc)?;
+    }
+
+    let output = serde_json::json!({
+        "created": report.documents.len(),
+        "links_resolved": report.links_resolved,
+        "links_dangling": report.dangling_links.len(),
+        "documents": report.documents.iter().map(|d| serde_json::json!({
+            "id": d.id,
+            "source": d.provenance.as_ref().and_then(|p| p.source_uri.as_deref()),
+        })).collect::<Vec<_>>(),
+        "dangling": report.dangling_links.iter().map(|d| serde_json::json!({
+            "source": d.source_id,
+            "target": d.target,
+        })).collect::<Vec<_>>(),
+        "skipped": report.skipped.iter().map(|s| serde_json::json!({
+            "file": s.path.display().to_string(),
+            "error": s.error,
+        })).collect::<Vec<_>>(),
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+// === Export ===
+
+fn cmd_export(vault_path: &Path, format: &str, output: &Path, include_body: bool) -> Result<()> {
+    if format != "jsonl" {
+        anyhow::bail!("Unknown export format '{format}'. Valid: jsonl");
+    }
+    let vault = Vault::open(vault_path).context("Failed to open vault")?;
+    let file = fs::File::create(output)
+        .with_context(|| format!("Failed to create {}", output.display()))?;
+
+    let stats = vault
+        .export_jsonl(std::io::BufWriter::new(file), include_body)
+        .context("Export failed")?;
+
+    let result = serde_json::json!({
+        "output": output.display().to_string(),
+        "exported": stats.exported,
+        "failed": stats.failed,
+        "elapsed_secs": stats.elapsed.as_secs_f64(),
+    });
+    println!("{}", serde_json::to_string_pretty(&result)?);
+    Ok(())
+}
+
+// === Watch ===
+
+fn cmd_watch(vault_path: &Path, debounce: Duration) -> Result<()> {
+    let (root, watcher, mut index) = start_index_sync(vault_path, debounce)?;
     eprintln!(
         "Watching vault at {} for changes (Ctrl+C to stop)...",
-        vault_path.canonicalize()?.display()
+        root.display()
     );
+    run_index_sync(&root, &watcher, &mut index)
+}
 
-    let watcher = mkb_vault::watcher::VaultWatcher::start(vault_path)
-        .context("Failed to start file watcher")?;
+/// Open the vault's index and start watching its files for changes.
+fn start_index_sync(
+    vault_path: &Path,
+    debounce: Duration,
+) -> Result<(PathBuf, VaultWatcher, IndexManager)> {
+    let _vault = Vault::open(vault_path).context("Failed to open vault")?;
+    let index = open_index(vault_path)?;
+    let root = vault_path.canonicalize()?;
+    let watcher = VaultWatcher::start(&root)
+        .context("Failed to start file watcher")?
+        .with_debounce(debounce);
+    Ok((root, watcher, index))
+}
 
+/// Apply batches of file changes to the index forever, reporting each on stderr.
+fn run_index_sync(root: &Path, watcher: &VaultWatcher, index: &mut IndexManager) -> ! {
     loop {
-        if let Some(event) = watcher.recv_timeout(std::time::Duration::from_millis(500)) {
-            match event {
-                mkb_vault::watcher::VaultEvent::Changed(path) => match fs::read_to_string(&path) {
-                    Ok(content) => match frontmatter::parse_document(&content) {
-                        Ok(doc) => match index.index_document(&doc) {
-                            Ok(()) => eprintln!("  indexed: {} ({})", doc.id, doc.title),
-                            Err(e) => eprintln!("  index error: {e}"),
-                        },
-                        Err(e) => eprintln!("  parse error for {}: {e}", path.display()),
-                    },
-                    Err(e) => eprintln!("  read error for {}: {e}", path.display()),
-                },
-                mkb_vault::watcher::VaultEvent::Removed(path) => {
-                    let id = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
-                    if !id.is_empty() {
-                        match index.remove_document(id) {
-                            Ok(()) => eprintln!("  removed: {id}"),
-                            Err(e) => eprintln!("  remove error: {e}"),
+        let batch = watcher.next_batch(Duration::from_secs(1));
+        if batch.is_empty() && !batch.overflowed {
+            continue;
+        }
+        match index.apply_batch_event(root, &batch) {
+            Ok(report) => {
+                if let Some(stats) = report.rebuilt {
+                    eprintln!(
+                        "  too many changes at once; reindexed vault ({} indexed)",
+                        stats.indexed
+                    );
+                }
+                for outcome in report.outcomes {
+                    match outcome {
+                        SyncOutcome::Indexed { id, title } => {
+                            eprintln!("  indexed: {id} ({title})");
                         }
+                        SyncOutcome::Removed { id } => eprintln!("  removed: {id}"),
                     }
                 }
+                for (path, e) in report.failed {
+                    eprintln!("  sync error for {}: {e}", path.display());
+                }
+            }
+            Err(e) => eprintln!("  sync error for {} files: {e}", batch.len()),
+        }
+    }
+}
+
+// === Completions ===
+
+const COMPLETIONS_INSTALL_HELP: &str = "\
+Installation:
+  bash:        echo 'source <(mkb completions bash)' >> ~/.bashrc
+  zsh:         echo 'source <(mkb completions zsh)' >> ~/.zshrc
+  fish:        mkb completions fish > ~/.config/fish/completions/mkb.fish
+  powershell:  mkb completions powershell | Out-String | Invoke-Expression
+
+Bash, zsh and fish also complete --doc-type, --view, --tags and the ID
+argument of `mkb rm` / `mkb edit` from the vault named by $MKB_VAULT, or
+else the nearest vault at or above the current directory. Pass --static to
+leave those out.";
+
+/// Dynamic value completion layered over clap's static bash script.
+const BASH_DYNAMIC_COMPLETIONS: &str = r#"
+_mkb_dynamic() {
+    local cur="${COMP_WORDS[COMP_CWORD]}" prev="${COMP_WORDS[COMP_CWORD-1]}" values=""
+    case "$prev" in
+        --doc-type|--type) values="$(mkb completions --complete-types="$cur" 2>/dev/null)" ;;
+        --view) values="$(mkb completions --complete-views="$cur" 2>/dev/null)" ;;
+        --tags) values="$(mkb completions --complete-tags="$cur" 2>/dev/null)" ;;
+        *)
+            if [[ $COMP_CWORD -eq 2 && ( "${COMP_WORDS[1]}" == rm || "${COMP_WORDS[1]}" == edit ) && "$cur" != -* ]]; then
+                values="$(mkb completions --complete-ids="$cur" 2>/dev/null)"
+            else
+                _mkb "$@"
+                return
+            fi
+            ;;
+    esac
+    local IFS=$'\n'
+    COMPREPLY=($values)
+}
+
+complete -F _mkb_dynamic -o bashdefault -o default mkb
+"#;
+
+/// Dynamic value completion layered over clap's static zsh script.
+const ZSH_DYNAMIC_COMPLETIONS: &str = r#"
+_mkb_dynamic() {
+    local cur=${words[CURRENT]}
+    case "${words[CURRENT-1]}" in
+        --doc-type|--type) compadd -- ${(f)"$(mkb completions --complete-types="$cur" 2>/dev/null)"}; return ;;
+        --view) compadd -- ${(f)"$(mkb completions --complete-views="$cur" 2>/dev/null)"}; return ;;
+        --tags) compadd -- ${(f)"$(mkb completions --complete-tags="$cur" 2>/dev/null)"}; return ;;
+    esac
+    if (( CURRENT == 3 )) && [[ ${words[2]} == (rm|edit) && $cur != -* ]]; then
+        compadd -- ${(f)"$(mkb completions --complete-ids="$cur" 2>/dev/null)"}
+        return
+    fi
+    _mkb "$@"
+}
+
+compdef _mkb_dynamic mkb
+"#;
+
+/// Dynamic value completion added to clap's static fish script.
+const FISH_DYNAMIC_COMPLETIONS: &str = r#"
+complete -c mkb -l doc-type -x -a "(mkb completions --complete-types=(commandline -ct) 2>/dev/null)"
+complete -c mkb -n "__fish_seen_subcommand_from graph" -l type -x -a "(mkb completions --complete-types=(commandline -ct) 2>/dev/null)"
+complete -c mkb -l view -x -a "(mkb completions --complete-views=(commandline -ct) 2>/dev/null)"
+complete -c mkb -l tags -x -a "(mkb completions --complete-tags=(commandline -ct) 2>/dev/null)"
+complete -c mkb -n "__fish_seen_subcommand_from rm edit" -f -a "(mkb completions --complete-ids=(commandline -ct) 2>/dev/null)"
+"#;
+
+fn cmd_completions(shell: clap_complete::Shell, dynamic: bool) -> Result<()> {
+    use clap_complete::Shell;
+
+    let mut cmd = Cli::command();
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut cmd, "mkb", &mut script);
+    let mut script = String::from_utf8(script).context("Completion script is not UTF-8")?;
+
+    if dynamic {
+        match shell {
+            Shell::Bash => script.push_str(BASH_DYNAMIC_COMPLETIONS),
+            Shell::Zsh => script.push_str(ZSH_DYNAMIC_COMPLETIONS),
+            Shell::Fish => script.push_str(FISH_DYNAMIC_COMPLETIONS),
+            _ => {}
+        }
+    }
+
+    print!("{script}");
+    Ok(())
+}
+
+/// Which vault values `mkb completions --complete-*` prints.
+#[derive(Clone, Copy)]
+enum CompletionKind {
+    Types,
+    Views,
+    Ids,
+    Tags,
+}
+
+/// Print completion candidates starting with `prefix`, one per line. Runs on
+/// every tab press, so a missing vault or index prints nothing rather than
+/// an error.
+fn cmd_completion_values(vault_path: Option<&Path>, kind: CompletionKind, prefix: &str) {
+    use completion::{
+        CompletionSource, DocTypeSource, DocumentIdSource, TagSource, ViewNameSource,
+    };
+
+    let Some(vault_path) = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| completion::find_vault(vault_path, &cwd))
+    else {
+        return;
+    };
+    let Ok(vault) = Vault::open(&vault_path) else {
+        return;
+    };
+
+    let values = match kind {
+        CompletionKind::Views => ViewNameSource { vault: &vault }.complete(prefix),
+        CompletionKind::Types | CompletionKind::Ids | CompletionKind::Tags => {
+            let Ok(index) = open_index(&vault_path) else {
+                return;
+            };
+            let index = &index;
+            match kind {
+                CompletionKind::Types => DocTypeSource { index }.complete(prefix),
+                CompletionKind::Ids => DocumentIdSource { index }.complete(prefix),
+                _ => TagSource { index }.complete(prefix),
             }
         }
+    };
+    for value in values {
+        println!("{value}");
     }
 }
 
 // === MCP ===
 
 #[tokio::main]
-async fn cmd_mcp(vault_path: &Path) -> Result<()> {
+async fn cmd_mcp(vault_path: &Path, watch: bool) -> Result<()> {
     use rmcp::ServiceExt;
 
     // Validate vault exists
     let _vault = Vault::open(vault_path).context("Failed to open vault")?;
 
+    if watch {
+        let (root, watcher, mut index) = start_index_sync(vault_path, DEFAULT_DEBOUNCE)?;
+        std::thread::spawn(move || run_index_sync(&root, &watcher, &mut index));
+    }
+
     let service = mkb_mcp::tools::MkbMcpService::new(vault_path.to_path_buf());
     let server = service
         .serve(rmcp::transport::stdio())
@@ -1283,6 +3413,80 @@ async fn cmd_mcp(vault_path: &Path) -> Result<()> {
     Ok(())
 }
 
+// === HTTP feeds ===
+
+#[derive(serde::Deserialize)]
+struct FeedParams {
+    q: String,
+}
+
+type FeedState = std::sync::Arc<(PathBuf, FeedOpts)>;
+
+#[tokio::main]
+async fn cmd_serve(
+    vault_path: &Path,
+    feed: bool,
+    port: u16,
+    feed_base_url: Option<String>,
+) -> Result<()> {
+    if !feed {
+        anyhow::bail!("Nothing to serve; pass --feed");
+    }
+    Vault::open(vault_path).context("Failed to open vault")?;
+
+    let opts = FeedOpts {
+        base_url: feed_base_url,
+        ..FeedOpts::default()
+    };
+    let app = axum::Router::new()
+        .route("/feed", axum::routing::get(serve_feed))
+        .with_state(FeedState::new((vault_path.to_path_buf(), opts)));
+
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", port))
+        .await
+        .with_context(|| format!("Failed to listen on port {port}"))?;
+    eprintln!("Serving feeds at http://127.0.0.1:{port}/feed?q=<mkql>");
+    axum::serve(listener, app)
+        .await
+        .context("HTTP server error")
+}
+
+/// `GET /feed?q=<mkql>`: the query's results as an Atom feed, or a 400
+/// with the error if the query fails.
+async fn serve_feed(
+    axum::extract::State(state): axum::extract::State<FeedState>,
+    axum::extract::Query(params): axum::extract::Query<FeedParams>,
+) -> axum::response::Response {
+    use axum::http::{header, StatusCode};
+    use axum::response::IntoResponse;
+
+    let feed = tokio::task::spawn_blocking(move || {
+        let (vault_path, opts) = &*state;
+        feed_xml(vault_path, &params.q, opts)
+    })
+    .await;
+    match feed {
+        Ok(Ok(xml)) => (
+            [(header::CONTENT_TYPE, "application/atom+xml; charset=utf-8")],
+            xml,
+        )
+            .into_response(),
+        Ok(Err(e)) => (StatusCode::BAD_REQUEST, format!("{e:#}")).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// Run `mkql` against the vault's index and render the rows as Atom.
+fn feed_xml(vault_path: &Path, mkql: &str, opts: &FeedOpts) -> Result<String> {
+    let index = open_index(vault_path)?;
+    let ast = mkb_parser::parse_mkql(mkql).map_err(|e| anyhow::anyhow!("Parse error: {e}"))?;
+    let compiled = compile_with_options(&ast, compile_options(vault_path, false)?)
+        .map_err(|e| anyhow::anyhow!("Compile error: {e}"))?;
+    let result = mkb_query::execute(&index, &compiled)
+        .map_err(|e| anyhow::anyhow!("Execution error: {e}"))?;
+    Ok(format_atom(&result, opts))
+}
+
 // === Graph ===
 
 fn cmd_graph(
@@ -1298,16 +3502,16 @@ fn cmd_graph(
         mkb_query::graph::GraphBuilder::from_center(&index, center_id, depth)
             .map_err(|e| anyhow::anyhow!("{e}"))?
     } else if let Some(dtype) = doc_type {
-        mkb_query::graph::GraphBuilder::from_type(&index, dtype)
+        mkb_query::graph::GraphBuilder::from_type(&index, dtype, depth)
             .map_err(|e| anyhow::anyhow!("{e}"))?
     } else {
         anyhow::bail!("Specify --center <ID> or --type <TYPE> for graph visualization");
     };
 
     match format {
-        "dot" => println!("{}", mkb_query::graph::GraphBuilder::format_dot(&graph)),
-        "mermaid" => println!("{}", mkb_query::graph::GraphBuilder::format_mermaid(&graph)),
-        "json" => println!("{}", mkb_query::graph::GraphBuilder::format_json(&graph)),
+        "dot" => println!("{}", graph.to_dot()),
+        "mermaid" => println!("{}", graph.to_mermaid()),
+        "json" => println!("{}", graph.to_json()),
         other => anyhow::bail!(
             "Unknown graph format '{}'. Valid: dot, mermaid, json",
             other
@@ -1317,6 +3521,110 @@ fn cmd_graph(
     Ok(())
 }
 
+// === Chain ===
+
+fn cmd_annotate(
+    vault_path: &Path,
+    id: &str,
+    annotation_type: &str,
+    note: &str,
+    by: &str,
+) -> Result<()> {
+    let index = open_index(vault_path)?;
+    let annotation_id = index
+        .add_annotation(id, annotation_type, note, by)
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    let added = index
+        .get_annotations(id)
+        .context("Failed to read annotations")?
+        .into_iter()
+        .find(|a| a.id == annotation_id)
+        .context("Annotation vanished after it was added")?;
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&annotation_json(&added))?
+    );
+    Ok(())
+}
+
+fn cmd_annotate_list(vault_path: &Path, id: &str) -> Result<()> {
+    let index = open_index(vault_path)?;
+    let annotations: Vec<serde_json::Value> = index
+        .get_annotations(id)
+        .context("Failed to read annotations")?
+        .iter()
+        .map(annotation_json)
+        .collect();
+    println!("{}", serde_json::to_string_pretty(&annotations)?);
+    Ok(())
+}
+
+fn cmd_annotate_delete(vault_path: &Path, id: &str, annotation_id: i64) -> Result<()> {
+    let index = open_index(vault_path)?;
+    let belongs = index
+        .get_annotations(id)
+        .context("Failed to read annotations")?
+        .iter()
+        .any(|a| a.id == annotation_id);
+    if !belongs {
+        anyhow::bail!("Document {id} has no annotation with ID {annotation_id}");
+    }
+    index
+        .delete_annotation(annotation_id)
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+
+    let output = serde_json::json!({
+        "id": annotation_id,
+        "doc_id": id,
+        "deleted": true,
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+fn annotation_json(annotation: &mkb_index::Annotation) -> serde_json::Value {
+    serde_json::json!({
+        "id": annotation.id,
+        "doc_id": annotation.doc_id,
+        "annotation_type": annotation.annotation_type,
+        "content": annotation.content,
+        "created_at": annotation.created_at,
+        "created_by": annotation.created_by,
+    })
+}
+
+fn cmd_chain(vault_path: &Path, id: &str) -> Result<()> {
+    let index = open_index(vault_path)?;
+
+    let mut versions = index
+        .supersession_chain(id)
+        .context("Failed to follow supersedes")?;
+    if versions.is_empty() {
+        anyhow::bail!("Document not found: {id}");
+    }
+    let later = index
+        .superseded_by_chain(id)
+        .context("Failed to follow superseded_by")?;
+    versions.extend(later.into_iter().skip(1));
+
+    let output: Vec<serde_json::Value> = versions
+        .iter()
+        .enumerate()
+        .map(|(i, doc)| {
+            serde_json::json!({
+                "version": i + 1,
+                "id": doc.id,
+                "title": doc.title,
+                "observed_at": doc.observed_at,
+                "confidence": doc.confidence,
+                "requested": doc.id == id,
+            })
+        })
+        .collect();
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
 // === View ===
 
 fn cmd_view_save(
@@ -1374,7 +3682,16 @@ fn cmd_view_run(vault_path: &Path, name: &str, format: &str) -> Result<()> {
 
     let view = vault.load_view(name).map_err(|e| anyhow::anyhow!("{e}"))?;
 
-    cmd_query(vault_path, Some(&view.query), None, None, format)
+    cmd_query(
+        vault_path,
+        Some(&view.query),
+        None,
+        None,
+        parse_format(format)?,
+        false,
+        None,
+        &Embedder::Mock(MockEmbeddingProvider),
+    )
 }
 
 fn cmd_view_delete(vault_path: &Path, name: &str) -> Result<()> {
@@ -1396,7 +3713,94 @@ fn cmd_view_delete(vault_path: &Path, name: &str) -> Result<()> {
 
 fn open_index(vault_path: &Path) -> Result<IndexManager> {
     let index_path = vault_path.join(".mkb").join("index").join("mkb.db");
-    IndexManager::open(&index_path).context("Failed to open index")
+    match IndexManager::open(&index_path) {
+        Ok(index) => Ok(index),
+        Err(MkbError::IndexCorrupted { reason }) => anyhow::bail!(
+            "Index is corrupted ({reason}). Run `mkb index rebuild` to rebuild it from vault files."
+        ),
+        Err(e) => Err(e).context("Failed to open index"),
+    }
+}
+
+/// Result count above which `mkb query` streams rows instead of collecting them.
+const STREAMING_THRESHOLD: usize = 10_000;
+
+/// How long write commands wait for another `mkb` process to release the vault.
+const VAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn lock_vault(vault: &Vault) -> Result<VaultLock> {
+    vault
+        .try_lock(VAULT_LOCK_TIMEOUT)
+        .context("Failed to lock vault")
+}
+
+fn parse_provenance_type(s: &str) -> Result<ProvenanceType, String> {
+    ProvenanceType::from_name(&s.to_lowercase()).ok_or_else(|| {
+        format!("Unknown provenance type '{s}'. Valid: manual, import, llm, api, watcher")
+    })
+}
+
+fn parse_datetime_arg(s: &str) -> Result<ParsedDateTime, String> {
+    mkb_core::temporal::parse_datetime(s).map_err(|e| e.to_string())
+}
+
+/// How `mkb add` and `mkb ingest` generate IDs for new documents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IdStrategy {
+    /// `<type>-<slug>-<NNN>`, numbered after existing documents.
+    Counter,
+    /// `<type>-<slug>-<hash>` from [`Document::generate_content_id`].
+    ContentHash,
+}
+
+impl IdStrategy {
+    fn generate(
+        self,
+        vault_path: &Path,
+        doc_type: &str,
+        observed_at: &DateTime<Utc>,
+        title: &str,
+    ) -> String {
+        match self {
+            Self::Counter => {
+                let counter =
+                    mkb_vault::next_counter(vault_path, doc_type, &mkb_vault::slugify(title));
+                Document::generate_id(doc_type, title, counter)
+            }
+            Self::ContentHash => Document::generate_content_id(doc_type, observed_at, title),
+        }
+    }
+}
+
+fn parse_id_strategy(s: &str) -> Result<IdStrategy> {
+    match s.to_lowercase().as_str() {
+        "counter" => Ok(IdStrategy::Counter),
+        "content-hash" => Ok(IdStrategy::ContentHash),
+        other => anyhow::bail!("Unknown ID strategy '{other}'. Valid: counter, content-hash"),
+    }
+}
+
+/// Index `doc` and its links together, so a failure leaves neither behind.
+fn index_with_links(index: &mut IndexManager, doc: &Document) -> Result<()> {
+    index
+        .transaction(|index| {
+            index.index_document(doc)?;
+            index.store_links(&doc.id, &doc.links)
+        })
+        .context("Failed to index document")
+}
+
+/// Write a new document, or update it in place when one with the same ID
+/// exists (only content-hash IDs repeat). Returns the path and whether an
+/// existing document was updated.
+fn upsert_document(vault: &Vault, doc: &mut Document) -> Result<(PathBuf, bool)> {
+    if vault.document_path(&doc.doc_type, &doc.id).exists() {
+        let path = vault.update(doc).context("Failed to update document")?;
+        Ok((path, true))
+    } else {
+        let path = vault.create(doc).context("Failed to create document")?;
+        Ok((path, false))
+    }
 }
 
 fn parse_precision(s: &str) -> Result<TemporalPrecision> {
@@ -1418,9 +3822,77 @@ fn parse_precision(s: &str) -> Result<TemporalPrecision> {
 fn parse_format(s: &str) -> Result<OutputFormat> {
     match s.to_lowercase().as_str() {
         "json" => Ok(OutputFormat::Json),
+        "yaml" | "yml" => Ok(OutputFormat::Yaml),
         "table" => Ok(OutputFormat::Table),
         "markdown" | "md" => Ok(OutputFormat::Markdown),
-        other => anyhow::bail!("Unknown format '{}'. Valid: json, table, markdown", other),
+        "context" => Ok(OutputFormat::Context { template: None }),
+        "atom" => Ok(OutputFormat::Atom(FeedOpts::default())),
+        "pivot" => Ok(OutputFormat::PivotTable),
+        "csv" => Ok(OutputFormat::Csv(CsvOpts::default())),
+        "tsv" => Ok(OutputFormat::Csv(CsvOpts::tsv())),
+        other => anyhow::bail!(
+            "Unknown format '{}'. Valid: json, yaml, table, markdown, context, atom, pivot, csv, tsv",
+            other
+        ),
+    }
+}
+
+/// Load `--template` into a context format; other formats don't take one.
+fn with_template(format: OutputFormat, template: Option<&Path>) -> Result<OutputFormat> {
+    match (format, template) {
+        (OutputFormat::Context { .. }, Some(path)) => {
+            let template = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read template {}", path.display()))?;
+            Ok(OutputFormat::Context {
+                template: Some(template),
+            })
+        }
+        (_, Some(_)) => anyhow::bail!("--template requires --format context"),
+        (format, None) => Ok(format),
+    }
+}
+
+/// Set `--feed-base-url` on an atom format; other formats don't take one.
+fn with_feed_base_url(format: OutputFormat, base_url: Option<String>) -> Result<OutputFormat> {
+    match (format, base_url) {
+        (OutputFormat::Atom(opts), base_url @ Some(_)) => {
+            Ok(OutputFormat::Atom(FeedOpts { base_url, ..opts }))
+        }
+        (_, Some(_)) => anyhow::bail!("--feed-base-url requires --format atom"),
+        (format, None) => Ok(format),
+    }
+}
+
+/// `mkb query` flags that only apply to csv/tsv output.
+struct CsvFlags {
+    delimiter: Option<char>,
+    quote: Option<char>,
+    include_body: bool,
+    multiline_body: bool,
+    bom: bool,
+}
+
+/// Apply [`CsvFlags`] to a csv format; other formats don't take them.
+fn with_csv_opts(format: OutputFormat, flags: CsvFlags) -> Result<OutputFormat> {
+    match format {
+        OutputFormat::Csv(opts) => Ok(OutputFormat::Csv(CsvOpts {
+            delimiter: flags.delimiter.unwrap_or(opts.delimiter),
+            quote: flags.quote.unwrap_or(opts.quote),
+            include_body: flags.include_body,
+            multiline_body: flags.multiline_body,
+            bom: flags.bom,
+        })),
+        _ if flags.delimiter.is_some()
+            || flags.quote.is_some()
+            || flags.include_body
+            || flags.multiline_body
+            || flags.bom =>
+        {
+            anyhow::bail!(
+                "--delimiter, --quote, --include-body, --multiline-body and --bom require --format csv or tsv"
+            )
+        }
+        format => Ok(format),
     }
 }
 
@@ -1435,9 +3907,36 @@ fn print_indexed_docs(results: &[mkb_index::IndexedDocument]) -> Result<()> {
                 "observed_at": r.observed_at,
                 "valid_until": r.valid_until,
                 "confidence": r.confidence,
+                "tags": r.tags,
+                "modified_at": r.modified_at,
+                "age_label": r.age_label,
             })
         })
         .collect();
     println!("{}", serde_json::to_string_pretty(&json)?);
     Ok(())
 }
+
+/// Print indexed documents as a table with their age in place of
+/// `observed_at` for `--format table`, and as JSON otherwise.
+fn print_indexed_docs_as(
+    results: &[mkb_index::IndexedDocument],
+    format: &OutputFormat,
+) -> Result<()> {
+    if !matches!(format, OutputFormat::Table) {
+        return print_indexed_docs(results);
+    }
+    if results.is_empty() {
+        println!("(no results)");
+        return Ok(());
+    }
+    println!("{:<30} {:<15} {:<30} {:<20}", "ID", "TYPE", "TITLE", "AGE");
+    println!("{}", "-".repeat(98));
+    for r in results {
+        println!(
+            "{:<30} {:<15} {:<30} {:<20}",
+            r.id, r.doc_type, r.title, r.age_label
+        );
+    }
+    Ok(())
+}
@@ -97,6 +97,137 @@ fn e2e_add_rejects_without_observed_at() {
     assert!(!output.status.success());
 }
 
+#[test]
+fn e2e_add_accepts_offset_and_date_only_observed_at() {
+    let dir = init_vault();
+    let add = |observed: &str| {
+        mkb_in(dir.path())
+            .args([
+                "add",
+                "--doc-type",
+                "project",
+                "--title",
+                "Dated",
+                "--observed-at",
+                observed,
+            ])
+            .output()
+            .unwrap()
+    };
+
+    let output = add("2025-06-15T10:00:00+02:00");
+    assert!(output.status.success());
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["observed_at"], "2025-06-15T08:00:00+00:00");
+
+    let output = add("2025-06-15");
+    assert!(output.status.success());
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["observed_at"], "2025-06-15T00:00:00+00:00");
+
+    let output = add("June 15th");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("invalid datetime"));
+}
+
+#[test]
+fn e2e_add_enforces_vault_id_pattern() {
+    let dir = init_vault();
+    std::fs::write(dir.path().join(".mkb/ids.yaml"), "pattern: '^prj-'\n").unwrap();
+
+    let output = mkb_in(dir.path())
+        .args([
+            "add",
+            "--doc-type",
+            "project",
+            "--title",
+            "Alpha",
+            "--observed-at",
+            "2025-06-15T10:00:00Z",
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Document ID 'proj-alpha-001' does not match required pattern '^prj-'"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn e2e_add_date_only_observed_at_caps_precision_at_day() {
+    let dir = init_vault();
+    let precision_of = |observed: &str| {
+        let output = mkb_in(dir.path())
+            .args([
+                "add",
+                "--doc-type",
+                "project",
+                "--title",
+                "Dated",
+                "--observed-at",
+                observed,
+                "--precision",
+                "exact",
+            ])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let content =
+            std::fs::read_to_string(dir.path().join(result["path"].as_str().unwrap())).unwrap();
+        content
+            .lines()
+            .find_map(|l| l.strip_prefix("temporal_precision: "))
+            .unwrap()
+            .to_string()
+    };
+
+    assert_eq!(precision_of("2025-06-15"), "day");
+    assert_eq!(precision_of("2025-06-15T10:00:00Z"), "exact");
+}
+
+#[test]
+fn e2e_concurrent_adds_get_distinct_ids() {
+    let dir = init_vault();
+    let children: Vec<_> = (0..4)
+        .map(|_| {
+            mkb_in(dir.path())
+                .args([
+                    "add",
+                    "--doc-type",
+                    "project",
+                    "--title",
+                    "Race",
+                    "--observed-at",
+                    "2025-02-10T00:00:00Z",
+                ])
+                .stdout(std::process::Stdio::piped())
+                .spawn()
+                .unwrap()
+        })
+        .collect();
+
+    let mut ids: Vec<String> = children
+        .into_iter()
+        .map(|child| {
+            let output = child.wait_with_output().unwrap();
+            assert!(output.status.success());
+            let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+            result["id"].as_str().unwrap().to_string()
+        })
+        .collect();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), 4);
+    // Every writer released the lock and cleared its owner record
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join(".mkb/vault.lock")).unwrap(),
+        ""
+    );
+}
+
 #[test]
 fn e2e_add_from_file() {
     let dir = init_vault();
@@ -115,6 +246,10 @@ confidence: 0.95
 tags:
   - rust
   - test
+links:
+  - rel: owner
+    target: people/jane-smith
+    observed_at: "2025-02-10T00:00:00Z"
 ---
 # Test Project
 
@@ -146,6 +281,129 @@ This is a test project document.
     let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
     assert_eq!(result["id"], "proj-test-001");
     assert_eq!(result["title"], "Test Project");
+
+    // Frontmatter links are indexed with the document
+    let output = mkb_in(dir.path())
+        .args(["link", "list", "proj-test-001"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("people/jane-smith"));
+
+    let output = mkb_in(dir.path())
+        .args([
+            "add",
+            "--doc-type",
+            "project",
+            "--title",
+            "ignored",
+            "--observed-at",
+            "2025-02-10T00:00:00Z",
+            "--from-file",
+            file_path.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Document already exists: proj-test-001"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn e2e_provenance_recorded_and_filterable() {
+    let dir = init_vault();
+    let added = add_project(dir.path(), "Manual Project");
+    let content =
+        std::fs::read_to_string(dir.path().join(added["path"].as_str().unwrap())).unwrap();
+    assert!(content.contains("source_type: manual"), "{content}");
+    assert!(content.contains("ingested_via: cli"), "{content}");
+
+    let file_path = dir.path().join("generated.md");
+    std::fs::write(
+        &file_path,
+        "---\nid: proj-generated-001\ntype: project\ntitle: Generated\n\
+         observed_at: \"2025-02-10T00:00:00Z\"\nvalid_until: \"2025-08-10T00:00:00Z\"\n\
+         temporal_precision: day\n_created_at: \"2025-02-10T00:00:00Z\"\n\
+         _modified_at: \"2025-02-10T00:00:00Z\"\nprovenance:\n  source_type: llm_generated\n\
+         \x20 ingested_via: mcp\n---\nBody\n",
+    )
+    .unwrap();
+    let output = mkb_in(dir.path())
+        .args([
+            "add",
+            "--doc-type",
+            "project",
+            "--title",
+            "ignored",
+            "--observed-at",
+            "2025-02-10T00:00:00Z",
+            "--from-file",
+            file_path.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "add from-file failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let ids = |args: &[&str]| -> Vec<String> {
+        let output = mkb_in(dir.path()).arg("query").args(args).output().unwrap();
+        assert!(
+            output.status.success(),
+            "query failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        result["rows"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|row| row["fields"]["id"].as_str().unwrap().to_string())
+            .collect()
+    };
+    assert_eq!(
+        ids(&["SELECT id FROM project", "--provenance-type", "llm"]),
+        vec!["proj-generated-001"]
+    );
+    assert_eq!(
+        ids(&["--doc-type", "project", "--provenance-type", "manual"]),
+        vec![added["id"].as_str().unwrap()]
+    );
+
+    let output = mkb_in(dir.path())
+        .args([
+            "query",
+            "--provenance-type",
+            "robot",
+            "SELECT * FROM project",
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn e2e_corrupted_index_suggests_rebuild() {
+    let dir = init_vault();
+    std::fs::write(
+        dir.path().join(".mkb/index/mkb.db"),
+        "not a database".repeat(100),
+    )
+    .unwrap();
+
+    let output = mkb_in(dir.path())
+        .args(["query", "SELECT * FROM project"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Index is corrupted"), "{stderr}");
+    assert!(stderr.contains("mkb index rebuild"), "{stderr}");
 }
 
 // === T-300.3: Query ===
@@ -171,6 +429,27 @@ fn e2e_query_with_mkql() {
     assert!(stdout.contains("Beta Project"));
 }
 
+#[test]
+fn e2e_query_strict_rejects_unknown_fields() {
+    let dir = init_vault();
+    add_project(dir.path(), "Alpha Project");
+
+    let output = mkb_in(dir.path())
+        .args([
+            "query",
+            "SELECT * FROM project WHERE stauts = 'active'",
+            "--strict",
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Unknown field 'stauts'") && stderr.contains("status"),
+        "{stderr}"
+    );
+}
+
 #[test]
 fn e2e_query_with_format_flag() {
     let dir = init_vault();
@@ -193,232 +472,2233 @@ fn e2e_query_with_format_flag() {
     assert!(output.status.success());
     let stdout = String::from_utf8_lossy(&output.stdout);
     assert!(stdout.contains("|")); // Markdown table pipes
-}
-
-#[test]
-fn e2e_query_pipe_to_stdout() {
-    let dir = init_vault();
-    add_project(dir.path(), "Alpha Project");
 
-    let output = mkb_in(dir.path())
-        .args(["query", "--doc-type", "project"])
-        .output()
-        .unwrap();
-    assert!(output.status.success());
-    // Should produce valid JSON to stdout
-    let parsed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
-    assert!(parsed.is_array());
-}
-
-// === T-300.4: Search ===
-
-#[test]
-fn e2e_search_fulltext() {
-    let dir = init_vault();
-
-    // Add documents with different bodies
+    // YAML format
     let output = mkb_in(dir.path())
         .args([
-            "add",
-            "--doc-type",
-            "project",
-            "--title",
-            "ML Project",
-            "--observed-at",
-            "2025-02-10T00:00:00Z",
-            "--body",
-            "This project uses machine learning and neural networks",
+            "query",
+            "SELECT title, body FROM project",
+            "--format",
+            "yaml",
         ])
         .output()
         .unwrap();
     assert!(output.status.success());
-
-    let output = mkb_in(dir.path())
-        .args(["search", "machine learning"])
-        .output()
-        .unwrap();
-    assert!(output.status.success());
     let stdout = String::from_utf8_lossy(&output.stdout);
-    assert!(stdout.contains("ML Project"));
+    assert!(
+        stdout.contains("title: Alpha Project\nbody: |-\n"),
+        "{stdout}"
+    );
 }
 
-// === T-300.5: Edit + Rm ===
-
 #[test]
-fn e2e_edit_updates_fields() {
+fn e2e_query_pivot_format() {
     let dir = init_vault();
-    let added = add_project(dir.path(), "Original Title");
-    let doc_id = added["id"].as_str().unwrap();
+    add_project(dir.path(), "Alpha Project");
+    add_project(dir.path(), "Beta Project");
 
     let output = mkb_in(dir.path())
-        .args(["edit", doc_id, "--title", "Updated Title"])
+        .args([
+            "query",
+            "SELECT PIVOT doc_type BY month(observed_at) COUNT(*)",
+            "--format",
+            "pivot",
+        ])
         .output()
         .unwrap();
     assert!(
         output.status.success(),
-        "edit failed: {}",
+        "pivot query failed: {}",
         String::from_utf8_lossy(&output.stderr)
     );
-
-    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
-    assert_eq!(result["title"], "Updated Title");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout.trim_end(),
+        "        | 2025-02\n--------+--------\nproject |       2"
+    );
 }
 
 #[test]
-fn e2e_rm_soft_delete() {
+fn e2e_query_atom_format() {
     let dir = init_vault();
-    let added = add_project(dir.path(), "To Delete");
-    let doc_id = added["id"].as_str().unwrap();
+    let alpha = add_project(dir.path(), "Alpha Project");
+    add_project(dir.path(), "Beta Project");
 
     let output = mkb_in(dir.path())
-        .args(["rm", doc_id, "--doc-type", "project"])
+        .args([
+            "query",
+            "SELECT * FROM project",
+            "--format",
+            "atom",
+            "--feed-base-url",
+            "https://kb.example.com/docs",
+        ])
         .output()
         .unwrap();
     assert!(
         output.status.success(),
-        "rm failed: {}",
+        "query failed: {}",
         String::from_utf8_lossy(&output.stderr)
     );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("<?xml"), "{stdout}");
+    assert_eq!(stdout.matches("<entry>").count(), 2);
+    assert!(stdout.contains(&format!(
+        "<id>https://kb.example.com/docs/{}</id>",
+        alpha["id"].as_str().unwrap()
+    )));
+    assert!(stdout.contains("<category term=\"project\"/>"));
 
-    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
-    assert!(result["archived_to"].as_str().unwrap().contains("archive"));
+    let output = mkb_in(dir.path())
+        .args([
+            "query",
+            "SELECT * FROM project",
+            "--feed-base-url",
+            "https://kb.example.com",
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
 }
 
-// === T-300.6: Link ===
-
 #[test]
-fn e2e_link_create_and_list() {
+fn e2e_query_csv_format() {
     let dir = init_vault();
-    let alpha = add_project(dir.path(), "Alpha");
-    let beta = add_project(dir.path(), "Beta");
-    let alpha_id = alpha["id"].as_str().unwrap();
-    let beta_id = beta["id"].as_str().unwrap();
+    add_project(dir.path(), "Alpha, the first");
 
-    // Create link
     let output = mkb_in(dir.path())
         .args([
-            "link",
-            "create",
-            "--source",
-            alpha_id,
-            "--rel",
-            "depends_on",
-            "--target",
-            beta_id,
+            "query",
+            "SELECT title, doc_type FROM project",
+            "--format",
+            "csv",
+            "--delimiter",
+            ";",
+            "--bom",
         ])
         .output()
         .unwrap();
     assert!(
         output.status.success(),
-        "link create failed: {}",
+        "query failed: {}",
         String::from_utf8_lossy(&output.stderr)
     );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout, "\u{feff}doc_type;title\nproject;Alpha, the first\n");
 
-    // List forward links
     let output = mkb_in(dir.path())
-        .args(["link", "list", alpha_id])
+        .args(["query", "SELECT title FROM project", "--format", "tsv"])
         .output()
         .unwrap();
-    assert!(output.status.success());
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    assert!(stdout.contains("depends_on"));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "title\nAlpha, the first\n"
+    );
 
-    // List reverse links
     let output = mkb_in(dir.path())
-        .args(["link", "list", beta_id, "--reverse"])
+        .args(["query", "SELECT * FROM project", "--bom"])
         .output()
         .unwrap();
-    assert!(output.status.success());
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    assert!(stdout.contains("depends_on"));
+    assert!(!output.status.success());
 }
 
-// === T-300.7: Schema ===
-
 #[test]
-fn e2e_schema_list() {
-    let output = mkb().arg("schema").arg("list").output().unwrap();
-    assert!(output.status.success());
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    assert!(stdout.contains("project"));
-    assert!(stdout.contains("meeting"));
-    assert!(stdout.contains("decision"));
-    assert!(stdout.contains("signal"));
-}
+fn e2e_serve_feed_over_http() {
+    use std::io::{Read, Write};
 
-// === T-300.8: GC ===
+    let dir = init_vault();
+    add_project(dir.path(), "Alpha Project");
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut server = mkb_in(dir.path())
+        .args(["serve", "--feed", "--port", &port.to_string()])
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let get = |path: &str| -> Option<String> {
+        let mut stream = std::net::TcpStream::connect(("127.0.0.1", port)).ok()?;
+        write!(
+            stream,
+            "GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n"
+        )
+        .ok()?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).ok()?;
+        Some(response)
+    };
+    let mut response = None;
+    for _ in 0..100 {
+        response = get("/feed?q=SELECT%20*%20FROM%20project");
+        if response.is_some() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    let bad = get("/feed?q=SELEKT");
+    server.kill().unwrap();
+    server.wait().unwrap();
+
+    let response = response.expect("server did not start");
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    assert!(response.contains("application/atom+xml"), "{response}");
+    assert_eq!(response.matches("<entry>").count(), 1);
+    assert!(bad.unwrap().starts_with("HTTP/1.1 400"));
+}
 
 #[test]
-fn e2e_gc_sweep() {
+fn e2e_query_context_format_with_template() {
     let dir = init_vault();
-    add_project(dir.path(), "Test Project");
+    let created = add_project(dir.path(), "Alpha Project");
 
-    let output = mkb_in(dir.path()).args(["gc"]).output().unwrap();
+    let output = mkb_in(dir.path())
+        .args(["query", "SELECT * FROM project", "--format", "context"])
+        .output()
+        .unwrap();
     assert!(
         output.status.success(),
-        "gc failed: {}",
+        "query failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.starts_with("## Alpha Project (project, observed 2025-02-10"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("Body of Alpha Project\n\n---\n"),
+        "{stdout}"
+    );
+
+    let template = dir.path().join("row.tmpl");
+    std::fs::write(&template, "* {{id}}: {{ title }}\n").unwrap();
+    let output = mkb_in(dir.path())
+        .args([
+            "query",
+            "SELECT * FROM project",
+            "--format",
+            "context",
+            "--template",
+        ])
+        .arg(&template)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("* {}: Alpha Project\n\n", created["id"].as_str().unwrap())
+    );
+
+    let output = mkb_in(dir.path())
+        .args(["query", "SELECT * FROM project", "--template"])
+        .arg(&template)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn e2e_query_pipe_to_stdout() {
+    let dir = init_vault();
+    add_project(dir.path(), "Alpha Project");
+
+    let output = mkb_in(dir.path())
+        .args(["query", "--doc-type", "project"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    // Should produce valid JSON to stdout
+    let parsed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(parsed.is_array());
+    assert!(parsed[0]["age_label"].as_str().unwrap().ends_with("ago"));
+}
+
+#[test]
+fn e2e_query_by_type_table_shows_age() {
+    let dir = init_vault();
+    add_project(dir.path(), "Alpha Project");
+
+    let output = mkb_in(dir.path())
+        .args(["query", "--doc-type", "project", "--format", "table"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.lines().next().unwrap().contains("AGE"), "{stdout}");
+    assert!(stdout.contains("Alpha Project"), "{stdout}");
+    assert!(stdout.contains(" ago"), "{stdout}");
+    assert!(!stdout.contains("2025-02-10"), "{stdout}");
+}
+
+#[test]
+fn e2e_query_as_of_reconstructs_past_state() {
+    let dir = init_vault();
+    add_project(dir.path(), "Alpha Project");
+
+    let snapshot = |args: &[&str]| {
+        let output = mkb_in(dir.path())
+            .args(["query", "--as-of"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "query failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+
+    assert_eq!(snapshot(&["2025-01-01"]), serde_json::json!([]));
+    let later = snapshot(&["2025-03-01", "--doc-type", "project"]);
+    assert_eq!(later[0]["title"], "Alpha Project");
+    assert_eq!(
+        snapshot(&["2025-03-01", "--doc-type", "meeting"]),
+        serde_json::json!([])
+    );
+
+    let output = mkb_in(dir.path())
+        .args(["query", "SELECT * FROM project", "--as-of", "2025-03-01"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn e2e_query_explain_prints_plan() {
+    // No vault needed: explain never opens the index
+    let dir = TempDir::new().unwrap();
+
+    let output = mkb_in(dir.path())
+        .args([
+            "query",
+            "--explain",
+            "SELECT * FROM project WHERE status = 'active'",
+        ])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "explain failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let plan: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(plan["sql"].as_str().unwrap().contains("d.status = ?2"));
+    assert_eq!(plan["params"].as_array().unwrap().len(), 2);
+    assert_eq!(plan["uses_fts"], false);
+}
+
+#[test]
+fn e2e_query_explain_uses_vault_query_settings() {
+    let dir = init_vault();
+    let explain_sql = || -> String {
+        let output = mkb_in(dir.path())
+            .args([
+                "query",
+                "--explain",
+                "SELECT * FROM project WHERE CURRENT()",
+            ])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let plan: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        plan["sql"].as_str().unwrap().to_string()
+    };
+
+    let before = explain_sql();
+    let output = mkb_in(dir.path())
+        .args(["config", "future-in-current", "off"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_ne!(explain_sql(), before);
+}
+
+#[test]
+fn e2e_query_normalize_prints_canonical_form() {
+    let dir = TempDir::new().unwrap();
+
+    let output = mkb_in(dir.path())
+        .args([
+            "query",
+            "--normalize",
+            "select  *  from  project  where  (status='active' and x in (3, 1)) or y=1",
+        ])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "normalize failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim_end(),
+        "SELECT * FROM project WHERE status = 'active' AND x IN (1, 3) OR y = 1"
+    );
+}
+
+#[test]
+fn e2e_query_explain_plan_shows_index_use() {
+    let dir = init_vault();
+    add_project(dir.path(), "Alpha Project");
+
+    let output = mkb_in(dir.path())
+        .args([
+            "query",
+            "--explain-plan",
+            "SELECT * FROM project WHERE title = 'Alpha Project'",
+        ])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "explain-plan failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let plan: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(
+        plan.iter().any(|row| row["detail"]
+            .as_str()
+            .unwrap()
+            .contains("idx_documents_type")),
+        "{plan:?}"
+    );
+
+    let output = mkb_in(dir.path())
+        .args([
+            "query",
+            "--explain-plan",
+            "SELECT * FROM project",
+            "--format",
+            "table",
+        ])
+        .output()
+        .unwrap();
+    let text = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        text.contains("SEARCH d USING INDEX idx_documents_type"),
+        "{text}"
+    );
+}
+
+// === T-300.4: Search ===
+
+#[test]
+fn e2e_search_fulltext() {
+    let dir = init_vault();
+
+    // Add documents with different bodies
+    let output = mkb_in(dir.path())
+        .args([
+            "add",
+            "--doc-type",
+            "project",
+            "--title",
+            "ML Project",
+            "--observed-at",
+            "2025-02-10T00:00:00Z",
+            "--body",
+            "This project uses machine learning and neural networks",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let output = mkb_in(dir.path())
+        .args(["search", "machine learning"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("ML Project"));
+}
+
+// === T-300.5: Edit + Rm ===
+
+#[test]
+fn e2e_edit_updates_fields() {
+    let dir = init_vault();
+    let added = add_project(dir.path(), "Original Title");
+    let doc_id = added["id"].as_str().unwrap();
+
+    let output = mkb_in(dir.path())
+        .args(["edit", doc_id, "--title", "Updated Title"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "edit failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["title"], "Updated Title");
+}
+
+#[test]
+fn e2e_append_adds_entry_after_existing_body() {
+    let dir = init_vault();
+    let added = add_project(dir.path(), "Journal");
+    let doc_id = added["id"].as_str().unwrap();
+
+    let output = mkb_in(dir.path())
+        .args(["append", doc_id, "Shipped the parser."])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "append failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let content =
+        std::fs::read_to_string(dir.path().join(result["path"].as_str().unwrap())).unwrap();
+    let original = content.find("Body of Journal").unwrap();
+    let appended = content.find("Shipped the parser.").unwrap();
+    assert!(original < appended);
+
+    // The appended text is searchable straight away
+    let output = mkb_in(dir.path())
+        .args(["search", "parser"])
+        .output()
+        .unwrap();
+    let hits: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(hits[0]["id"], doc_id);
+}
+
+#[test]
+fn e2e_rm_soft_delete() {
+    let dir = init_vault();
+    let added = add_project(dir.path(), "To Delete");
+    let doc_id = added["id"].as_str().unwrap();
+
+    let output = mkb_in(dir.path())
+        .args(["rm", doc_id, "--doc-type", "project"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "rm failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(result["archived_to"].as_str().unwrap().contains("archive"));
+}
+
+#[test]
+fn e2e_restore_brings_back_archived_document() {
+    let dir = init_vault();
+    let added = add_project(dir.path(), "Restore Me");
+    let doc_id = added["id"].as_str().unwrap();
+
+    let rm = mkb_in(dir.path())
+        .args(["rm", doc_id, "--doc-type", "project"])
+        .output()
+        .unwrap();
+    assert!(rm.status.success());
+
+    let status = mkb_in(dir.path()).args(["status"]).output().unwrap();
+    let status: serde_json::Value = serde_json::from_slice(&status.stdout).unwrap();
+    assert_eq!(status["archived_documents"], 1);
+
+    let output = mkb_in(dir.path())
+        .args(["restore", doc_id, "--doc-type", "project"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "restore failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(result["restored_to"]
+        .as_str()
+        .unwrap()
+        .ends_with(&format!("{doc_id}.md")));
+
+    let search = mkb_in(dir.path())
+        .args(["search", "Restore"])
+        .output()
+        .unwrap();
+    let hits: Vec<serde_json::Value> = serde_json::from_slice(&search.stdout).unwrap();
+    assert_eq!(hits[0]["id"], doc_id);
+
+    let again = mkb_in(dir.path())
+        .args(["restore", doc_id, "--doc-type", "project"])
+        .output()
+        .unwrap();
+    assert!(!again.status.success());
+}
+
+#[test]
+fn e2e_merge_copies_documents_from_other_vault() {
+    let ours = init_vault();
+    let theirs = init_vault();
+    let shared = add_project(ours.path(), "Shared")["id"].clone();
+    assert_eq!(add_project(theirs.path(), "Shared")["id"], shared);
+    let only_theirs = add_project(theirs.path(), "Theirs Only")["id"].clone();
+
+    let merge = |conflict: &str| {
+        mkb_in(ours.path())
+            .args([
+                "merge",
+                theirs.path().to_str().unwrap(),
+                "--conflict",
+                conflict,
+            ])
+            .output()
+            .unwrap()
+    };
+
+    let failed = merge("fail");
+    assert!(!failed.status.success());
+    assert!(String::from_utf8_lossy(&failed.stderr).contains("conflict"));
+
+    let output = merge("skip");
+    assert!(
+        output.status.success(),
+        "merge failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["inserted"], 1);
+    assert_eq!(result["skipped"], 1);
+    assert_eq!(result["files_copied"], 1);
+
+    let only_theirs = only_theirs.as_str().unwrap();
+    assert!(ours
+        .path()
+        .join("projects")
+        .join(format!("{only_theirs}.md"))
+        .exists());
+    let search = mkb_in(ours.path())
+        .args(["search", "Theirs"])
+        .output()
+        .unwrap();
+    let hits: Vec<serde_json::Value> = serde_json::from_slice(&search.stdout).unwrap();
+    assert_eq!(hits[0]["id"], only_theirs);
+}
+
+// === T-300.6: Link ===
+
+#[test]
+fn e2e_link_create_and_list() {
+    let dir = init_vault();
+    let alpha = add_project(dir.path(), "Alpha");
+    let beta = add_project(dir.path(), "Beta");
+    let alpha_id = alpha["id"].as_str().unwrap();
+    let beta_id = beta["id"].as_str().unwrap();
+
+    // Create link
+    let output = mkb_in(dir.path())
+        .args([
+            "link",
+            "create",
+            "--source",
+            alpha_id,
+            "--rel",
+            "depends_on",
+            "--target",
+            beta_id,
+        ])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "link create failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // List forward links
+    let output = mkb_in(dir.path())
+        .args(["link", "list", alpha_id])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("depends_on"));
+
+    // List reverse links
+    let output = mkb_in(dir.path())
+        .args(["link", "list", beta_id, "--reverse"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("depends_on"));
+}
+
+#[test]
+fn e2e_link_create_enforces_schema_link_constraints() {
+    let dir = init_vault();
+    let alpha = add_project(dir.path(), "Alpha")["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let beta = add_project(dir.path(), "Beta")["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let mut people = Vec::new();
+    for name in ["Jane", "Joe"] {
+        let output = mkb_in(dir.path())
+            .args([
+                "add",
+                "--doc-type",
+                "person",
+                "--title",
+                name,
+                "--observed-at",
+                "2025-02-10T00:00:00Z",
+            ])
+            .output()
+            .unwrap();
+        let doc: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        people.push(doc["id"].as_str().unwrap().to_string());
+    }
+    let link_owner = |target: &str| {
+        mkb_in(dir.path())
+            .args([
+                "link", "create", "--source", &alpha, "--rel", "owner", "--target", target,
+            ])
+            .output()
+            .unwrap()
+    };
+
+    let output = link_owner(&beta);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("must target a 'person' document"),
+        "{stderr}"
+    );
+
+    let output = link_owner(&people[0]);
+    assert!(
+        output.status.success(),
+        "link create failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let output = link_owner(&people[1]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("too many 'owner' links"), "{stderr}");
+
+    let output = mkb_in(dir.path())
+        .args(["link", "list", &alpha])
+        .output()
+        .unwrap();
+    let links: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(links.as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn e2e_graph_by_type_mermaid() {
+    let dir = init_vault();
+    let alpha = add_project(dir.path(), "Alpha");
+    let beta = add_project(dir.path(), "Beta");
+    let alpha_id = alpha["id"].as_str().unwrap();
+    let beta_id = beta["id"].as_str().unwrap();
+
+    let output = mkb_in(dir.path())
+        .args([
+            "link",
+            "create",
+            "--source",
+            alpha_id,
+            "--rel",
+            "depends_on",
+            "--target",
+            beta_id,
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let output = mkb_in(dir.path())
+        .args([
+            "graph", "--type", "project", "--depth", "2", "--format", "mermaid",
+        ])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "graph failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("graph LR"));
+    assert!(stdout.contains("[\"Alpha (project)\"]"));
+    assert!(stdout.contains("[\"Beta (project)\"]"));
+    assert!(stdout.contains("-->|\"depends_on\"|"));
+    // Dashed IDs are rewritten into Mermaid-safe identifiers
+    assert!(!stdout.contains(&format!("{alpha_id} -->")));
+}
+
+// === T-300.7: Schema ===
+
+#[test]
+fn e2e_schema_list() {
+    let output = mkb().arg("schema").arg("list").output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("project"));
+    assert!(stdout.contains("meeting"));
+    assert!(stdout.contains("decision"));
+    assert!(stdout.contains("signal"));
+}
+
+#[test]
+fn e2e_schema_validate_all_reports_violations() {
+    let dir = init_vault();
+    let alpha = add_project(dir.path(), "Alpha");
+    let beta = add_project(dir.path(), "Beta");
+    for (doc, status) in [(&alpha, "active"), (&beta, "bogus")] {
+        let id = doc["id"].as_str().unwrap();
+        let output = mkb_in(dir.path())
+            .args(["edit", id, "--set", &format!("status={status}")])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+    }
+
+    let output = mkb_in(dir.path())
+        .args(["schema", "validate", "--all"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["valid"], 1);
+    assert_eq!(report["invalid"], 1);
+    assert_eq!(report["warnings"], 0);
+    assert_eq!(report["errors"][0]["id"], beta["id"]);
+    assert_eq!(report["errors"][0]["doc_type"], "project");
+
+    let beta_id = beta["id"].as_str().unwrap();
+    let output = mkb_in(dir.path())
+        .args(["edit", beta_id, "--set", "status=paused"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let output = mkb_in(dir.path())
+        .args(["schema", "validate", "--all", "--strict"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "validate failed: {}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
+
+#[test]
+fn e2e_schema_export_jsonschema() {
+    let output = mkb()
+        .args([
+            "schema",
+            "export",
+            "--doc-type",
+            "project",
+            "--format",
+            "jsonschema",
+        ])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "schema export failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let schema: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        schema["$schema"],
+        "https://json-schema.org/draft/2020-12/schema"
+    );
+    assert_eq!(schema["$id"], "urn:mkb:schema:project");
+    assert_eq!(schema["required"], serde_json::json!(["status"]));
+}
+
+#[test]
+fn e2e_schema_export_all_to_directory() {
+    let dir = TempDir::new().unwrap();
+    let out_dir = dir.path().join(".mkb/schemas/generated");
+
+    let output = mkb()
+        .args(["schema", "export", "--all", "--output-dir"])
+        .arg(&out_dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "schema export failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["exported"], 4);
+    for name in ["project", "meeting", "decision", "signal"] {
+        let content = std::fs::read_to_string(out_dir.join(format!("{name}.schema.json"))).unwrap();
+        let schema: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(schema["title"], name);
+    }
+}
+
+#[test]
+fn e2e_schema_infer_writes_draft_schema() {
+    let dir = init_vault();
+    for (n, status) in ["active", "active", "paused"].iter().enumerate() {
+        let file_path = dir.path().join(format!("p{n}.md"));
+        std::fs::write(
+            &file_path,
+            format!(
+                "---\nid: proj-infer-00{n}\ntype: project\ntitle: Infer {n}\n\
+                 observed_at: \"2025-02-10T00:00:00Z\"\nvalid_until: \"2025-08-10T00:00:00Z\"\n\
+                 temporal_precision: day\n_created_at: \"2025-02-10T00:00:00Z\"\n\
+                 _modified_at: \"2025-02-10T00:00:00Z\"\nfields:\n  status: {status}\n  priority: {n}\n---\nBody\n"
+            ),
+        )
+        .unwrap();
+        let output = mkb_in(dir.path())
+            .args([
+                "add",
+                "--doc-type",
+                "project",
+                "--title",
+                "ignored",
+                "--observed-at",
+                "2025-02-10T00:00:00Z",
+                "--from-file",
+                file_path.to_str().unwrap(),
+            ])
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "add failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let output = mkb_in(dir.path())
+        .args(["schema", "infer", "--type", "project", "--sample", "100"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "schema infer failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["sampled"], 3);
+    assert_eq!(
+        result["fields"],
+        serde_json::json!([
+            {"name": "priority", "type": "integer", "required": true, "values": null},
+            {"name": "status", "type": "enum", "required": true, "values": ["active", "paused"]},
+        ])
+    );
+    let draft =
+        std::fs::read_to_string(dir.path().join(".mkb/schemas/project-inferred.yaml")).unwrap();
+    assert!(draft.starts_with("name: project\n"), "{draft}");
+
+    let output = mkb_in(dir.path())
+        .args(["schema", "infer", "--type", "meeting"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn e2e_schema_diff_reports_breaking_changes() {
+    let dir = TempDir::new().unwrap();
+    let old = dir.path().join("v1.yaml");
+    let new = dir.path().join("v2.yaml");
+    std::fs::write(
+        &old,
+        "name: task\nversion: 1\nfields:\n  status:\n    type: enum\n    values: [open, blocked, done]\n  notes:\n    type: string\n",
+    )
+    .unwrap();
+    std::fs::write(
+        &new,
+        "name: task\nversion: 2\nfields:\n  status:\n    type: enum\n    values: [open, done]\n  priority:\n    type: integer\n    required: true\n    default: 3\n",
+    )
+    .unwrap();
+
+    let output = mkb()
+        .args(["schema", "diff", "--old"])
+        .arg(&old)
+        .arg("--new")
+        .arg(&new)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "schema diff failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["version_bumped"], true);
+    assert_eq!(result["compatible"], false);
+    let kinds: Vec<(&str, &str, &str)> = result["changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| {
+            (
+                c["field"].as_str().unwrap(),
+                c["kind"].as_str().unwrap(),
+                c["severity"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            ("notes", "removed_field", "warning"),
+            ("priority", "new_required_field", "warning"),
+            ("status", "enum_values_removed", "fatal"),
+        ]
+    );
+    assert_eq!(result["migration_defaults"]["priority"], 3);
+}
+
+// === T-300.8: GC ===
+
+#[test]
+fn e2e_gc_sweep() {
+    let dir = init_vault();
+    add_project(dir.path(), "Test Project");
+
+    let output = mkb_in(dir.path()).args(["gc"]).output().unwrap();
+    assert!(
+        output.status.success(),
+        "gc failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(result["swept_at"].as_str().is_some());
+    assert!(result["stale_count"].is_number());
+    assert!(result.get("confidence_updated").is_none());
+}
+
+#[test]
+fn e2e_gc_removes_interrupted_writes_only() {
+    let dir = init_vault();
+    add_project(dir.path(), "Test Project");
+    let torn = dir.path().join("projects/proj-torn-001.md.tmp");
+    let own = dir.path().join("projects/scratch.tmp");
+    std::fs::write(&torn, "---\nid: proj-to").unwrap();
+    std::fs::write(&own, "keep me").unwrap();
+
+    // Opening the vault for a read leaves both alone
+    mkb_in(dir.path()).args(["status"]).output().unwrap();
+    assert!(torn.exists());
+
+    let output = mkb_in(dir.path()).args(["gc"]).output().unwrap();
+    assert!(
+        output.status.success(),
+        "gc failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["interrupted_writes_removed"], 1);
+    assert!(!torn.exists());
+    assert!(own.exists());
+}
+
+#[test]
+fn e2e_gc_recalculate_confidence() {
+    let dir = init_vault();
+    add_project(dir.path(), "Old Project");
+
+    let output = mkb_in(dir.path())
+        .args(["gc", "--recalculate-confidence"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "gc failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["confidence_updated"], 1);
+
+    let effective = || -> f64 {
+        let output = mkb_in(dir.path())
+            .args([
+                "query",
+                "SELECT confidence, effective_confidence FROM project",
+                "--format",
+                "json",
+            ])
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "query failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(result["rows"][0]["fields"]["confidence"], 0.95);
+        result["rows"][0]["fields"]["effective_confidence"]
+            .as_f64()
+            .unwrap()
+    };
+    let first = effective();
+    assert!(first < 0.95);
+
+    // The raw confidence is the base every time, so runs don't compound
+    let output = mkb_in(dir.path())
+        .args(["gc", "--recalculate-confidence"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!((effective() - first).abs() < 1e-6);
+}
+
+// === Index rebuild ===
+
+#[test]
+fn e2e_index_rebuild_restores_deleted_index() {
+    let dir = init_vault();
+    add_project(dir.path(), "Alpha");
+    add_project(dir.path(), "Beta");
+
+    let index_path = dir.path().join(".mkb").join("index").join("mkb.db");
+    std::fs::remove_file(&index_path).unwrap();
+
+    let output = mkb_in(dir.path())
+        .args(["index", "rebuild"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "rebuild failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["indexed"], 2);
+    assert_eq!(result["failed"], 0);
+
+    let output = mkb_in(dir.path()).args(["stats"]).output().unwrap();
+    let stats: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(stats["indexed_documents"], 2);
+}
+
+#[test]
+fn e2e_reindex_restores_missing_documents_once() {
+    let dir = init_vault();
+    add_project(dir.path(), "Alpha");
+    add_project(dir.path(), "Beta");
+
+    let index_path = dir.path().join(".mkb").join("index").join("mkb.db");
+    std::fs::remove_file(&index_path).unwrap();
+
+    let reindex = || {
+        let output = mkb_in(dir.path()).args(["reindex"]).output().unwrap();
+        assert!(
+            output.status.success(),
+            "reindex failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+    assert_eq!(reindex()["indexed"], 2);
+    assert_eq!(reindex()["indexed"], 0);
+
+    let output = mkb_in(dir.path()).args(["stats"]).output().unwrap();
+    let stats: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(stats["indexed_documents"], 2);
+}
+
+#[test]
+fn e2e_reindex_full_handles_empty_vault() {
+    let dir = init_vault();
+
+    let output = mkb_in(dir.path())
+        .args(["reindex", "--full"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "reindex --full failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["indexed"], 0);
+    assert_eq!(result["failed"], 0);
+}
+
+#[test]
+fn e2e_watch_reindexes_edited_files() {
+    let dir = init_vault();
+    let alpha = add_project(dir.path(), "Alpha");
+    let path = dir
+        .path()
+        .join("projects")
+        .join(format!("{}.md", alpha["id"].as_str().unwrap()));
+
+    let mut watch = mkb_in(dir.path())
+        .arg("watch")
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(500));
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    std::fs::write(&path, content.replace("Body of Alpha", "Edited by hand")).unwrap();
+
+    let found = (0..30).any(|_| {
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        let output = mkb_in(dir.path())
+            .args(["search", "Edited"])
+            .output()
+            .unwrap();
+        let results: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        !results.as_array().unwrap().is_empty()
+    });
+    watch.kill().unwrap();
+    watch.wait().unwrap();
+    assert!(found, "watch did not reindex the edited file");
+}
+
+#[test]
+fn e2e_index_prune_drops_deleted_files() {
+    let dir = init_vault();
+    let alpha = add_project(dir.path(), "Alpha");
+    add_project(dir.path(), "Beta");
+
+    let alpha_id = alpha["id"].as_str().unwrap();
+    std::fs::remove_file(dir.path().join("projects").join(format!("{alpha_id}.md"))).unwrap();
+
+    let output = mkb_in(dir.path())
+        .args(["index", "prune"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "prune failed: {}",
         String::from_utf8_lossy(&output.stderr)
     );
-
     let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
-    assert!(result["swept_at"].as_str().is_some());
-    assert!(result["stale_count"].is_number());
+    assert_eq!(result["pruned"], 1);
+
+    let output = mkb_in(dir.path()).args(["stats"]).output().unwrap();
+    let stats: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(stats["indexed_documents"], 1);
+}
+
+#[test]
+fn e2e_index_repair_fts_keeps_search_working() {
+    let dir = init_vault();
+    add_project(dir.path(), "Alpha");
+
+    let output = mkb_in(dir.path())
+        .args(["index", "repair-fts"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "repair-fts failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["consistent_before"], true);
+    assert_eq!(result["consistent_after"], true);
+
+    let output = mkb_in(dir.path())
+        .args(["search", "Alpha"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Alpha"));
+}
+
+// === T-300.9: Stats ===
+
+#[test]
+fn e2e_stats_shows_vault_summary() {
+    let dir = init_vault();
+    add_project(dir.path(), "Alpha");
+    add_project(dir.path(), "Beta");
+
+    let output = mkb_in(dir.path()).args(["stats"]).output().unwrap();
+    assert!(output.status.success());
+
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["indexed_documents"], 2);
+    assert!(result["by_type"]["project"].as_u64().unwrap() >= 2);
+    assert!(result["newest_per_type"]["project"]["id"].is_string());
+}
+
+#[test]
+fn e2e_stats_query_history_records_only_when_enabled() {
+    let dir = init_vault();
+    add_project(dir.path(), "Alpha");
+    let run_query = || {
+        let output = mkb_in(dir.path())
+            .args(["query", "SELECT * FROM project"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+    };
+    let history = || -> serde_json::Value {
+        let output = mkb_in(dir.path())
+            .args(["stats", "--query-history"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        serde_json::from_slice(&output.stdout).unwrap()
+    };
+
+    run_query();
+    let result = history();
+    assert_eq!(result["recording"], false);
+    assert_eq!(result["slowest"].as_array().unwrap().len(), 0);
+
+    let output = mkb_in(dir.path())
+        .args(["config", "query-history", "on"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        "query-history = on"
+    );
+    run_query();
+    run_query();
+
+    let result = history();
+    assert_eq!(result["recording"], true);
+    assert_eq!(result["slowest"].as_array().unwrap().len(), 2);
+    assert_eq!(result["slowest"][0]["result_count"], 1);
+    let frequent = result["most_frequent"].as_array().unwrap();
+    assert_eq!(frequent.len(), 1);
+    assert_eq!(frequent[0]["mkql"], "SELECT * FROM project");
+    assert_eq!(frequent[0]["executions"], 2);
+
+    let output = mkb_in(dir.path())
+        .args(["config", "query-history", "maybe"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn e2e_config_future_in_current_changes_current_results() {
+    let dir = init_vault();
+    let output = mkb_in(dir.path())
+        .args([
+            "add",
+            "--doc-type",
+            "project",
+            "--title",
+            "Planned",
+            "--observed-at",
+            "2999-01-01T00:00:00Z",
+        ])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "add failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let current = || -> String {
+        let output = mkb_in(dir.path())
+            .args(["query", "SELECT * FROM project WHERE CURRENT()"])
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "query failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    assert!(current().contains("Planned"));
+
+    let output = mkb_in(dir.path())
+        .args(["config", "future-in-current", "off"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        "future-in-current = off"
+    );
+    assert!(dir.path().join(".mkb/query.yaml").exists());
+    assert!(!current().contains("Planned"));
+
+    let output = mkb_in(dir.path())
+        .args(["config", "future-in-fresh"])
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        "future-in-fresh = off"
+    );
+}
+
+// === T-300.10: Status ===
+
+#[test]
+fn e2e_status_shows_health() {
+    let dir = init_vault();
+    add_project(dir.path(), "Test");
+
+    let output = mkb_in(dir.path()).args(["status"]).output().unwrap();
+    assert!(output.status.success());
+
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["indexed_documents"], 1);
+    assert!(result["rejection_count"].is_number());
+    assert!(result["index_synced"].is_boolean());
+}
+
+#[test]
+fn e2e_status_embeddings_breaks_down_by_model() {
+    let dir = init_vault();
+    add_project(dir.path(), "Unembedded");
+    let file_path = dir.path().join("vectors.md");
+    std::fs::write(&file_path, "# Vector Notes\n\nEmbedded on ingest.\n").unwrap();
+    let output = mkb_in(dir.path())
+        .args([
+            "ingest",
+            file_path.to_str().unwrap(),
+            "--embedding-provider",
+            "mock",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let output = mkb_in(dir.path()).args(["status"]).output().unwrap();
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(result.get("embeddings").is_none());
+
+    let output = mkb_in(dir.path())
+        .args(["status", "--embeddings"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let models = result["embeddings"]["models"].as_array().unwrap();
+    assert_eq!(models.len(), 1);
+    assert_eq!(models[0]["model"], "mock");
+    assert_eq!(models[0]["count"], 1);
+    assert!(models[0]["oldest_created_at"].is_string());
+    assert_eq!(result["embeddings"]["without_embedding"], 1);
+}
+
+#[test]
+fn e2e_status_diff_lists_divergent_documents() {
+    let dir = init_vault();
+    let alpha = add_project(dir.path(), "Alpha");
+    let beta = add_project(dir.path(), "Beta");
+    let projects = dir.path().join("projects");
+    let alpha_file = projects.join(format!("{}.md", alpha["id"].as_str().unwrap()));
+    std::fs::copy(&alpha_file, projects.join("proj-copy-001.md")).unwrap();
+    std::fs::remove_file(projects.join(format!("{}.md", beta["id"].as_str().unwrap()))).unwrap();
+
+    let output = mkb_in(dir.path())
+        .args(["status", "--diff"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "status failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        result["diff"]["in_vault_not_index"],
+        serde_json::json!(["proj-copy-001"])
+    );
+    assert_eq!(
+        result["diff"]["in_index_not_vault"],
+        serde_json::json!([beta["id"]])
+    );
+    assert_eq!(
+        result["diff"]["modified_since_index"],
+        serde_json::json!([])
+    );
+}
+
+#[test]
+fn e2e_status_check_links_reports_and_fixes_dangling() {
+    let dir = init_vault();
+    let alpha = add_project(dir.path(), "Alpha");
+    let alpha_id = alpha["id"].as_str().unwrap();
+
+    let output = mkb_in(dir.path())
+        .args([
+            "link",
+            "create",
+            "--source",
+            alpha_id,
+            "--rel",
+            "owner",
+            "--target",
+            "people/nobody",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let output = mkb_in(dir.path())
+        .args(["status", "--check-links"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["dangling_links"], 1);
+    assert!(result.get("removed_links").is_none());
+
+    let output = mkb_in(dir.path())
+        .args(["status", "--check-links", "--fix"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["removed_links"][0]["target_id"], "people/nobody");
+
+    let output = mkb_in(dir.path())
+        .args(["status", "--check-links"])
+        .output()
+        .unwrap();
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["dangling_links"], 0);
+}
+
+#[test]
+fn e2e_validate_reports_and_rejects_unparseable_files() {
+    let dir = init_vault();
+    add_project(dir.path(), "Alpha");
+
+    let output = mkb_in(dir.path()).args(["validate"]).output().unwrap();
+    assert!(output.status.success());
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["healthy"], true);
+    assert_eq!(result["issues"].as_array().unwrap().len(), 0);
+
+    let garbage = dir.path().join("projects").join("garbage.md");
+    std::fs::write(&garbage, "no frontmatter here").unwrap();
+
+    let output = mkb_in(dir.path()).args(["validate"]).output().unwrap();
+    assert!(!output.status.success());
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["healthy"], false);
+    assert_eq!(result["issues"][0]["kind"], "ParseError");
+    assert_eq!(result["issues"][0]["severity"], "error");
+    assert!(garbage.exists());
+
+    let output = mkb_in(dir.path())
+        .args(["validate", "--fix"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["healthy"], true);
+    assert_eq!(result["rejected"].as_array().unwrap().len(), 1);
+    assert!(!garbage.exists());
+}
+
+// === T-300.11: Ingest ===
+
+#[test]
+fn e2e_ingest_file() {
+    let dir = init_vault();
+
+    // Create a plain markdown file (no frontmatter)
+    let md_content = "# My Notes\n\nSome important notes about the project.\n";
+    let file_path = dir.path().join("notes.md");
+    std::fs::write(&file_path, md_content).unwrap();
+
+    let output = mkb_in(dir.path())
+        .args(["ingest", file_path.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "ingest failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["ingested"], 1);
+    assert_eq!(result["rejected"], 0);
 }
 
-// === T-300.9: Stats ===
+#[test]
+fn e2e_ingest_parallel_directory() {
+    let dir = init_vault();
+    let notes = dir.path().join("notes");
+    std::fs::create_dir(&notes).unwrap();
+    for i in 0..100 {
+        std::fs::write(
+            notes.join(format!("note-{i:03}.md")),
+            format!("# Daily Note\n\nEntry {i}\n"),
+        )
+        .unwrap();
+    }
+
+    let output = mkb_in(dir.path())
+        .args(["ingest", notes.to_str().unwrap(), "--parallel", "4"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "ingest failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["ingested"], 100, "{result}");
+    assert_eq!(result["rejected"], 0);
+    assert!(result["throughput_docs_per_sec"].as_f64().unwrap() > 0.0);
+
+    let output = mkb_in(dir.path()).args(["stats"]).output().unwrap();
+    let stats: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(stats["indexed_documents"], 100);
+}
 
 #[test]
-fn e2e_stats_shows_vault_summary() {
+fn e2e_ingest_multi_document_file() {
     let dir = init_vault();
-    add_project(dir.path(), "Alpha");
-    add_project(dir.path(), "Beta");
+    let header = "_created_at: 2025-02-10T00:00:00Z\n_modified_at: 2025-02-10T00:00:00Z\n\
+                  observed_at: 2025-02-10T00:00:00Z\nvalid_until: 2025-08-10T00:00:00Z\n\
+                  temporal_precision: day\n";
+    let content = format!(
+        "---\nid: proj-a-001\ntype: project\ntitle: A\n{header}---\nBody A\n\
+         ---\n# Loose Note\nNo frontmatter here.\n\
+         ---\ntype: project\ntitle: C\n{header}---\nBody C\n"
+    );
+    let file_path = dir.path().join("batch.md");
+    std::fs::write(&file_path, content).unwrap();
+
+    let output = mkb_in(dir.path())
+        .args(["ingest", file_path.to_str().unwrap(), "--multi"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "ingest failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["ingested"], 3, "{result}");
+    assert_eq!(result["files"][0]["id"], "proj-a-001");
+    assert!(result["files"][1]["id"]
+        .as_str()
+        .unwrap()
+        .starts_with("docu-loose-note-"));
 
     let output = mkb_in(dir.path()).args(["stats"]).output().unwrap();
+    let stats: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(stats["indexed_documents"], 3);
+}
+
+#[test]
+fn e2e_ingest_embeds_with_provider_for_semantic_search() {
+    let dir = init_vault();
+    let md_content = "# Vector Notes\n\nEmbeddings for the search index.\n";
+    let file_path = dir.path().join("vectors.md");
+    std::fs::write(&file_path, md_content).unwrap();
+    add_project(dir.path(), "Unembedded");
+
+    let output = mkb_in(dir.path())
+        .args([
+            "ingest",
+            file_path.to_str().unwrap(),
+            "--embedding-provider",
+            "mock",
+        ])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "ingest failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["embedded"], 1);
+    let id = result["files"][0]["id"].as_str().unwrap().to_string();
+
+    let output = mkb_in(dir.path())
+        .args(["search", "--semantic", md_content])
+        .output()
+        .unwrap();
     assert!(output.status.success());
+    let hits: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let hits = hits.as_array().unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0]["id"], id.as_str());
 
+    let output = mkb_in(dir.path())
+        .args(["search", "--semantic", "x", "--embedding-provider", "bogus"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown embedding provider"));
+}
+
+#[test]
+fn e2e_query_near_embeds_with_provider() {
+    let dir = init_vault();
+    let md_content = "# Vector Notes\n\nEmbeddings for the search index.\n";
+    let file_path = dir.path().join("vectors.md");
+    std::fs::write(&file_path, md_content).unwrap();
+    let output = mkb_in(dir.path())
+        .args([
+            "ingest",
+            file_path.to_str().unwrap(),
+            "--embedding-provider",
+            "mock",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
     let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
-    assert_eq!(result["indexed_documents"], 2);
-    assert!(result["by_type"]["project"].as_u64().unwrap() >= 2);
+    let id = result["files"][0]["id"].as_str().unwrap().to_string();
+    let mkql = format!("SELECT id FROM document WHERE NEAR('{md_content}', 0.9)");
+
+    let output = mkb_in(dir.path())
+        .args(["query", &mkql, "--embedding-provider", "mock"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "query failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["total"], 1);
+    assert_eq!(result["rows"][0]["fields"]["id"], id.as_str());
+
+    let output = mkb_in(dir.path())
+        .args(["query", &mkql, "--embedding-provider", "bogus"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown embedding provider"));
 }
 
-// === T-300.10: Status ===
+#[test]
+fn e2e_ingest_content_hash_is_idempotent() {
+    let dir = init_vault();
+    let file_path = dir.path().join("notes.md");
+    std::fs::write(&file_path, "# My Notes\n\nFirst draft.\n").unwrap();
+
+    let ingest = || {
+        let output = mkb_in(dir.path())
+            .args([
+                "ingest",
+                file_path.to_str().unwrap(),
+                "--id-strategy",
+                "content-hash",
+            ])
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "ingest failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(result["ingested"], 1, "{result}");
+        result["files"][0]["id"].as_str().unwrap().to_string()
+    };
+
+    let first = ingest();
+    let second = ingest();
+    assert_eq!(first, second);
+
+    let output = mkb_in(dir.path()).args(["stats"]).output().unwrap();
+    let stats: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(stats["indexed_documents"], 1);
+}
 
 #[test]
-fn e2e_status_shows_health() {
+fn e2e_add_content_hash_upserts() {
     let dir = init_vault();
-    add_project(dir.path(), "Test");
+    let add = |body: &str| {
+        let output = mkb_in(dir.path())
+            .args([
+                "add",
+                "--doc-type",
+                "project",
+                "--title",
+                "Alpha",
+                "--observed-at",
+                "2025-02-10T00:00:00Z",
+                "--body",
+                body,
+                "--id-strategy",
+                "content-hash",
+            ])
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "add failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
 
-    let output = mkb_in(dir.path()).args(["status"]).output().unwrap();
-    assert!(output.status.success());
+    let first = add("v1");
+    let second = add("v2");
+    assert_eq!(first["id"], second["id"]);
+    assert_eq!(first["updated"], false);
+    assert_eq!(second["updated"], true);
+
+    let output = mkb_in(dir.path()).args(["search", "v2"]).output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Alpha"));
+
+    let output = mkb_in(dir.path())
+        .args(["add", "--doc-type", "project", "--title", "Beta"])
+        .args(["--observed-at", "2025-02-10", "--id-strategy", "uuid"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn e2e_import_obsidian_stores_links() {
+    let dir = init_vault();
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../fixtures/obsidian");
+
+    let output = mkb_in(dir.path())
+        .args(["import", "--from", "obsidian", fixture.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "import failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["created"], 4);
+    assert_eq!(report["links_resolved"], 6);
+    assert_eq!(report["links_dangling"], 2);
+
+    let home = report["documents"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|d| d["source"] == "obsidian:Home.md")
+        .unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let output = mkb_in(dir.path())
+        .args(["link", "list", &home, "--reverse"])
+        .output()
+        .unwrap();
+    let links: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(links.as_array().unwrap().len(), 2);
+
+    let output = mkb_in(dir.path())
+        .args(["import", "--from", "notion", fixture.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+// === Annotations ===
+
+#[test]
+fn e2e_annotate_adds_lists_queries_and_deletes() {
+    let dir = init_vault();
+    let alpha = add_project(dir.path(), "Alpha Project");
+    add_project(dir.path(), "Beta Project");
+    let id = alpha["id"].as_str().unwrap();
+    let file = dir.path().join(alpha["path"].as_str().unwrap());
+    let before = std::fs::read_to_string(&file).unwrap();
+
+    let output = mkb_in(dir.path())
+        .args([
+            "annotate",
+            id,
+            "--type",
+            "reviewed",
+            "--note",
+            "Verified by Q2 review",
+        ])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "annotate failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let added: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(added["doc_id"], id);
+    assert_eq!(added["content"], "Verified by Q2 review");
+    assert_eq!(added["created_by"], "cli");
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), before);
 
+    // Each command reopens the index, so this also checks persistence
+    let output = mkb_in(dir.path())
+        .args([
+            "query",
+            "SELECT id FROM project WHERE ANNOTATED('reviewed')",
+        ])
+        .output()
+        .unwrap();
     let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
-    assert_eq!(result["indexed_documents"], 1);
-    assert!(result["rejection_count"].is_number());
-    assert!(result["index_synced"].is_boolean());
+    assert_eq!(result["total"], 1);
+    assert_eq!(result["rows"][0]["fields"]["id"], id);
+
+    let output = mkb_in(dir.path())
+        .args(["annotate", id, "--list"])
+        .output()
+        .unwrap();
+    let listed: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0]["annotation_type"], "reviewed");
+
+    let annotation_id = added["id"].to_string();
+    let output = mkb_in(dir.path())
+        .args(["annotate", id, "--delete", &annotation_id])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let output = mkb_in(dir.path())
+        .args(["annotate", id, "--delete", &annotation_id])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+
+    let output = mkb_in(dir.path())
+        .args(["annotate", "proj-missing-001", "--type", "reviewed"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
 }
 
-// === T-300.11: Ingest ===
+// === Chain ===
 
 #[test]
-fn e2e_ingest_file() {
+fn e2e_chain_prints_version_history() {
     let dir = init_vault();
+    let ids: Vec<String> = ["Plan v1", "Plan v2", "Plan v3"]
+        .iter()
+        .map(|title| {
+            add_project(dir.path(), title)["id"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        })
+        .collect();
+    for (i, id) in ids.iter().enumerate() {
+        let path = dir.path().join("projects").join(format!("{id}.md"));
+        let mut extra = String::new();
+        if i > 0 {
+            extra.push_str(&format!("supersedes: {}\n", ids[i - 1]));
+        }
+        if let Some(next) = ids.get(i + 1) {
+            extra.push_str(&format!("superseded_by: {next}\n"));
+        }
+        let content = std::fs::read_to_string(&path).unwrap();
+        let content = content.replacen("confidence:", &format!("{extra}confidence:"), 1);
+        std::fs::write(&path, content).unwrap();
+    }
+    let output = mkb_in(dir.path())
+        .args(["index", "rebuild"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
 
-    // Create a plain markdown file (no frontmatter)
-    let md_content = "# My Notes\n\nSome important notes about the project.\n";
-    let file_path = dir.path().join("notes.md");
-    std::fs::write(&file_path, md_content).unwrap();
+    let output = mkb_in(dir.path())
+        .args(["chain", &ids[1]])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "chain failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let versions: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).unwrap();
+    let chain: Vec<&str> = versions.iter().map(|v| v["id"].as_str().unwrap()).collect();
+    assert_eq!(chain, ids);
+    assert_eq!(versions[1]["requested"], true);
+    assert_eq!(versions[2]["version"], 3);
 
     let output = mkb_in(dir.path())
-        .args(["ingest", file_path.to_str().unwrap()])
+        .args(["chain", "proj-nope-001"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+// === Export ===
+
+#[test]
+fn e2e_export_jsonl() {
+    let dir = init_vault();
+    let first = add_project(dir.path(), "Alpha");
+    add_project(dir.path(), "Beta");
+    let out = dir.path().join("vault.jsonl");
+
+    let output = mkb_in(dir.path())
+        .args(["export", "--format", "jsonl", "--output"])
+        .arg(&out)
         .output()
         .unwrap();
     assert!(
         output.status.success(),
-        "ingest failed: {}",
+        "export failed: {}",
         String::from_utf8_lossy(&output.stderr)
     );
+    let stats: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(stats["exported"], 2);
+    assert_eq!(stats["failed"], 0);
+
+    let content = std::fs::read_to_string(&out).unwrap();
+    let records: Vec<serde_json::Value> = content
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(records.len(), 2);
+    let alpha = records.iter().find(|r| r["id"] == first["id"]).unwrap();
+    assert_eq!(alpha["title"], "Alpha");
+    assert_eq!(alpha["body"], "Body of Alpha\n");
+    assert_eq!(alpha["tags"], serde_json::json!(["rust", "test"]));
+
+    let output = mkb_in(dir.path())
+        .args(["export", "--no-body", "--output"])
+        .arg(&out)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let content = std::fs::read_to_string(&out).unwrap();
+    assert!(content
+        .lines()
+        .all(|l| serde_json::from_str::<serde_json::Value>(l).unwrap()["body"].is_null()));
+
+    let output = mkb_in(dir.path())
+        .args(["export", "--format", "csv", "--output"])
+        .arg(&out)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+// === Lint queries ===
+
+#[test]
+fn e2e_lint_queries_reports_bad_query() {
+    let dir = TempDir::new().unwrap();
+    std::fs::write(
+        dir.path().join("good.mkql"),
+        "-- active work; keep in sync with the dashboard\n\
+         SELECT title FROM project WHERE title LIKE 'a;b%';\n\
+         SELECT * FROM meeting WHERE FRESH('7d') ORDER BY observed_at DESC\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("bad.mkql"),
+        "SELECT * FROM project;\n\nSELECT * FROM project\nWHERE status = 'active';\n",
+    )
+    .unwrap();
+
+    let output = mkb()
+        .args(["lint-queries", dir.path().to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("bad.mkql:3: Invalid identifier: Unknown field 'status'"));
+    assert!(!stderr.contains("good.mkql"));
+
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["files"], 2);
+    assert_eq!(result["queries"], 4);
+    assert_eq!(result["errors"].as_array().unwrap().len(), 1);
+    assert_eq!(
+        result["errors"][0]["query"],
+        "SELECT * FROM project\nWHERE status = 'active'"
+    );
+}
+
+#[test]
+fn e2e_lint_queries_checks_fields_against_vault_schemas() {
+    let dir = init_vault();
+    let queries = dir.path().join("queries");
+    std::fs::create_dir(&queries).unwrap();
+    std::fs::write(
+        queries.join("views.mkql"),
+        "SELECT title, status FROM project WHERE status = 'active';\n\
+         SELECT * FROM project ORDER BY stauts\n",
+    )
+    .unwrap();
+
+    let output = mkb_in(dir.path())
+        .args(["lint-queries", "queries"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
 
     let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
-    assert_eq!(result["ingested"], 1);
-    assert_eq!(result["rejected"], 0);
+    assert_eq!(result["queries"], 2);
+    let errors = result["errors"].as_array().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0]["line"], 2);
+    assert!(errors[0]["error"]
+        .as_str()
+        .unwrap()
+        .contains("Unknown field 'stauts' for type 'project' (did you mean: status?)"));
+}
+
+#[test]
+fn e2e_lint_queries_passes_valid_files() {
+    let dir = TempDir::new().unwrap();
+    std::fs::write(
+        dir.path().join("ok.mkql"),
+        "SELECT * FROM project WHERE CURRENT();\n",
+    )
+    .unwrap();
+
+    let output = mkb()
+        .args(["lint-queries", dir.path().to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "lint failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+// === Completions ===
+
+#[test]
+fn e2e_completions_scripts_include_flags() {
+    for shell in ["bash", "zsh", "fish", "powershell"] {
+        let output = mkb()
+            .args(["completions", shell, "--static"])
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{shell} completions failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let script = String::from_utf8_lossy(&output.stdout);
+        assert!(!script.is_empty(), "{shell} script is empty");
+        assert!(
+            script.contains("doc-type"),
+            "{shell} script lacks --doc-type"
+        );
+        assert!(script.contains("vault"), "{shell} script lacks --vault");
+        assert!(
+            !script.contains("mkb completions --complete-"),
+            "{shell} script is dynamic"
+        );
+    }
+
+    // Dynamic completion is the default; --dynamic is still accepted
+    for args in [
+        ["completions", "bash"].as_slice(),
+        &["completions", "zsh"],
+        &["completions", "fish"],
+        &["completions", "bash", "--dynamic"],
+    ] {
+        let shell = args[1];
+        let output = mkb().args(args).output().unwrap();
+        let script = String::from_utf8_lossy(&output.stdout);
+        for flag in ["--complete-types", "--complete-ids", "--complete-tags"] {
+            assert!(
+                script.contains(&format!("mkb completions {flag}=")),
+                "{shell} script lacks {flag}"
+            );
+        }
+    }
+    let output = mkb().args(["completions", "fish"]).output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout)
+        .contains("-a \"(mkb completions --complete-types=(commandline -ct) 2>/dev/null)\""));
+}
+
+#[test]
+fn e2e_completions_filter_vault_values_by_prefix() {
+    let dir = init_vault();
+    let alpha = add_project(dir.path(), "Alpha");
+    let beta = add_project(dir.path(), "Beta");
+    for name in ["active", "archived", "blocked"] {
+        let output = mkb_in(dir.path())
+            .args(["view", "save", name, "SELECT * FROM project"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+    }
+
+    let complete = |cwd: &Path, args: &[&str]| {
+        let output = mkb_in(cwd)
+            .env_remove("MKB_VAULT")
+            .arg("completions")
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    let (alpha, beta) = (alpha["id"].as_str().unwrap(), beta["id"].as_str().unwrap());
+
+    assert_eq!(
+        complete(dir.path(), &["--complete-ids"]),
+        format!("{alpha}\n{beta}\n")
+    );
+    assert_eq!(
+        complete(dir.path(), &["--complete-ids", "proj-b"]),
+        format!("{beta}\n")
+    );
+    assert_eq!(complete(dir.path(), &["--complete-ids=zzz"]), "");
+    assert_eq!(
+        complete(dir.path(), &["--complete-views", "a"]),
+        "active\narchived\n"
+    );
+    assert_eq!(complete(dir.path(), &["--complete-types=pro"]), "project\n");
+    assert_eq!(complete(dir.path(), &["--complete-tags", "r"]), "rust\n");
+    assert_eq!(complete(dir.path(), &["--complete-tags"]), "rust\ntest\n");
+    // The old list flags still work for scripts generated before prefixes
+    assert_eq!(complete(dir.path(), &["--list-types"]), "project\n");
+
+    // Found by walking up from a subdirectory
+    let nested = dir.path().join("notes").join("deep");
+    std::fs::create_dir_all(&nested).unwrap();
+    assert_eq!(complete(&nested, &["--complete-types"]), "project\n");
+
+    // Outside a vault the completion modes print nothing instead of failing
+    let empty = TempDir::new().unwrap();
+    assert_eq!(complete(empty.path(), &["--complete-types"]), "");
+
+    // $MKB_VAULT wins over the current directory
+    let output = mkb_in(empty.path())
+        .env("MKB_VAULT", dir.path())
+        .args(["completions", "--complete-views", "b"])
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "blocked\n");
+}
+
+#[test]
+fn e2e_completions_hidden_from_help() {
+    let output = mkb().arg("--help").output().unwrap();
+    let help = String::from_utf8_lossy(&output.stdout);
+    assert!(!help.contains("completions"));
+
+    let output = mkb().args(["completions", "--help"]).output().unwrap();
+    let help = String::from_utf8_lossy(&output.stdout);
+    assert!(help.contains("source <(mkb completions bash)"));
 }
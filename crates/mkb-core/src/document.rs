@@ -1,15 +1,22 @@
 //! Document type — the central knowledge unit in MKB.
 
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 
-use crate::error::TemporalError;
+use crate::error::{MkbError, TemporalError};
 use crate::link::Link;
 use crate::temporal::{DecayProfile, RawTemporalInput, TemporalFields, TemporalGate};
 
 /// A knowledge unit in the vault. Every document is a markdown file
 /// with YAML frontmatter containing structured metadata.
+///
+/// Equality and hashing look at `id` only: two versions of the same
+/// document compare equal, so a `HashSet<Document>` holds one entry per ID.
+/// Ordering is by `id` too, so it agrees with equality and is safe for
+/// `BTreeSet` and `BTreeMap`; sort by recency with [`Document::cmp_recency`].
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Document {
     // === Identity ===
@@ -30,13 +37,24 @@ pub struct Document {
 
     // === Provenance ===
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub source: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub source_hash: Option<String>,
     #[serde(default = "default_confidence")]
     pub confidence: f64,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub provenance: Option<String>,
+    /// Whether `confidence` was set explicitly (see
+    /// [`Document::set_confidence`]) rather than derived from the temporal
+    /// precision. Not written to frontmatter.
+    #[serde(skip)]
+    pub confidence_override: bool,
+    /// Where the document came from and how it entered the vault. Older
+    /// files kept the origin in a top-level `source` key, which
+    /// [`parse_document`](crate::frontmatter::parse_document) moves to
+    /// `provenance.source_uri`.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_provenance",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub provenance: Option<ProvenanceRecord>,
 
     // === Supersession ===
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -65,9 +83,218 @@ fn default_confidence() -> f64 {
     1.0
 }
 
+/// How a document's content was produced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProvenanceType {
+    /// Written by a person, e.g. with `mkb add`.
+    Manual,
+    /// Converted from files or another tool's notes.
+    Import,
+    /// Written by a language model, e.g. through the MCP server.
+    #[serde(alias = "llm")]
+    LlmGenerated,
+    /// Created by a program through the Python or HTTP API.
+    #[serde(alias = "api")]
+    ApiIngestion,
+    /// Picked up by the vault file watcher.
+    Watcher,
+}
+
+impl ProvenanceType {
+    /// The serialized name, as stored in frontmatter and the index.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Manual => "manual",
+            Self::Import => "import",
+            Self::LlmGenerated => "llm_generated",
+            Self::ApiIngestion => "api_ingestion",
+            Self::Watcher => "watcher",
+        }
+    }
+
+    /// The type with this serialized name, also accepting the short forms
+    /// `llm` and `api`.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "manual" => Some(Self::Manual),
+            "import" => Some(Self::Import),
+            "llm_generated" | "llm" => Some(Self::LlmGenerated),
+            "api_ingestion" | "api" => Some(Self::ApiIngestion),
+            "watcher" => Some(Self::Watcher),
+            _ => None,
+        }
+    }
+}
+
+/// Structured provenance of a document, for audit trails.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProvenanceRecord {
+    pub source_type: ProvenanceType,
+    /// URL or path of the original content, if it has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_uri: Option<String>,
+    /// Who ingested the document, e.g. a user or agent name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ingested_by: Option<String>,
+    /// The interface that wrote the document: `cli`, `python`, `mcp`, ...
+    pub ingested_via: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_filename: Option<String>,
+    /// Processing steps applied to the content on the way in, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transformation_log: Vec<String>,
+}
+
+impl ProvenanceRecord {
+    /// A record with only the type and ingestion interface set.
+    #[must_use]
+    pub fn new(source_type: ProvenanceType, ingested_via: impl Into<String>) -> Self {
+        Self {
+            source_type,
+            source_uri: None,
+            ingested_by: None,
+            ingested_via: ingested_via.into(),
+            original_filename: None,
+            transformation_log: Vec::new(),
+        }
+    }
+}
+
+/// Read `provenance` as a [`ProvenanceRecord`], or as the free-form string
+/// older vaults stored. A string that names a [`ProvenanceType`] becomes
+/// that type; any other string is kept as `ingested_via` of a `Manual`
+/// record.
+pub(crate) fn deserialize_provenance<'de, D: Deserializer<'de>>(
+    d: D,
+) -> Result<Option<ProvenanceRecord>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Record(ProvenanceRecord),
+        Legacy(String),
+    }
+
+    Ok(Option::<Repr>::deserialize(d)?.map(|repr| match repr {
+        Repr::Record(record) => record,
+        Repr::Legacy(text) => ProvenanceRecord::new(
+            ProvenanceType::from_name(&text).unwrap_or(ProvenanceType::Manual),
+            text,
+        ),
+    }))
+}
+
+/// `text` without null bytes, in Unicode NFC.
+fn clean_text(text: &str) -> String {
+    use unicode_normalization::UnicodeNormalization;
+
+    text.chars().filter(|&c| c != '\0').nfc().collect()
+}
+
+/// `text` cut to at most `max` bytes without splitting a character.
+fn truncate_bytes(mut text: String, max: usize) -> String {
+    if text.len() > max {
+        let mut end = max;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+    text
+}
+
+/// [`clean_text`] and [`truncate_bytes`] applied to every string in `value`.
+fn clean_json(value: serde_json::Value, max: usize) -> serde_json::Value {
+    use serde_json::Value;
+
+    match value {
+        Value::String(s) => Value::String(truncate_bytes(clean_text(&s), max)),
+        Value::Array(items) => {
+            Value::Array(items.into_iter().map(|v| clean_json(v, max)).collect())
+        }
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(k, v)| (clean_text(&k), clean_json(v, max)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+/// Whether `source` is a `scheme://...` URL or a plain file path: no
+/// control characters, no markup, and no whitespace in a URL.
+fn is_url_or_path(source: &str) -> bool {
+    if source.trim().is_empty()
+        || source
+            .chars()
+            .any(|c| c.is_control() || matches!(c, '<' | '>' | '"'))
+    {
+        return false;
+    }
+    match source.split_once("://") {
+        Some((scheme, rest)) => {
+            scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '.' | '-'))
+                && !rest.is_empty()
+                && !rest.chars().any(char::is_whitespace)
+        }
+        None => true,
+    }
+}
+
+impl PartialEq for Document {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Document {}
+
+impl Hash for Document {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl PartialOrd for Document {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Document {
+    /// By `id`, matching [`PartialEq`].
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.id.cmp(&other.id)
+    }
+}
+
 impl Document {
+    /// Newest `observed_at` first, ties broken by `id` ascending: a
+    /// deterministic recency order for `sort_by(Document::cmp_recency)`.
+    ///
+    /// Unlike [`Ord`], this tells apart two versions of one document
+    /// observed at different times.
+    #[must_use]
+    pub fn cmp_recency(&self, other: &Self) -> Ordering {
+        other
+            .temporal
+            .observed_at
+            .cmp(&self.temporal.observed_at)
+            .then_with(|| self.id.cmp(&other.id))
+    }
+
     /// Create a new document with temporal gate validation.
     ///
+    /// Confidence starts at the precision's
+    /// [`confidence_weight`](crate::temporal::TemporalPrecision::confidence_weight):
+    /// a document observed on an inferred date is trusted less than one
+    /// with an exact timestamp.
+    ///
     /// # Errors
     ///
     /// Returns [`TemporalError`] if temporal fields fail validation
@@ -81,6 +308,7 @@ impl Document {
     ) -> Result<Self, TemporalError> {
         let now = Utc::now();
         let temporal = TemporalGate::validate(&temporal_input, decay_profile)?;
+        let confidence = default_confidence() * temporal.temporal_precision.confidence_weight();
 
         Ok(Self {
             id,
@@ -89,9 +317,9 @@ impl Document {
             created_at: now,
             modified_at: now,
             temporal,
-            source: None,
             source_hash: None,
-            confidence: 1.0,
+            confidence,
+            confidence_override: false,
             provenance: None,
             supersedes: None,
             superseded_by: None,
@@ -103,12 +331,153 @@ impl Document {
         })
     }
 
+    /// Set a confidence chosen by the caller, replacing the precision-based
+    /// default.
+    pub fn set_confidence(&mut self, confidence: f64) {
+        self.confidence = confidence;
+        self.confidence_override = true;
+    }
+
+    /// Body limit the ingestion paths pass to [`sanitize`](Self::sanitize).
+    pub const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+    /// Field limit the ingestion paths pass to [`sanitize`](Self::sanitize).
+    pub const MAX_FIELD_BYTES: usize = 4096;
+
+    /// A copy with untrusted (e.g. LLM-generated) content made safe to store.
+    ///
+    /// Every string field has null bytes removed and is normalized to NFC.
+    /// `body` is cut to `max_body_bytes`, `title` to 512 bytes, and
+    /// `provenance` strings and string values in `fields` to
+    /// `max_field_bytes`, always at a character boundary. At most 20 tags
+    /// are kept, each cut to 64 bytes; tags left empty are dropped. A
+    /// provenance `source_uri` that is neither a URL nor a file path becomes
+    /// `None`. Temporal fields are untouched.
+    #[must_use]
+    pub fn sanitize(&self, max_body_bytes: usize, max_field_bytes: usize) -> Document {
+        let mut doc = self.clone();
+
+        doc.id = clean_text(&doc.id);
+        doc.doc_type = clean_text(&doc.doc_type);
+        doc.title = truncate_bytes(clean_text(&doc.title), 512);
+        doc.body = truncate_bytes(clean_text(&doc.body), max_body_bytes);
+        for text in [
+            &mut doc.source_hash,
+            &mut doc.supersedes,
+            &mut doc.superseded_by,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            *text = clean_text(text);
+        }
+        if let Some(record) = &mut doc.provenance {
+            record.source_uri = record
+                .source_uri
+                .take()
+                .map(|s| clean_text(&s))
+                .filter(|s| is_url_or_path(s));
+            for text in [&mut record.ingested_by, &mut record.original_filename]
+                .into_iter()
+                .flatten()
+                .chain([&mut record.ingested_via])
+                .chain(&mut record.transformation_log)
+            {
+                *text = truncate_bytes(clean_text(text), max_field_bytes);
+            }
+        }
+        doc.fields = doc
+            .fields
+            .into_iter()
+            .map(|(k, v)| (clean_text(&k), clean_json(v, max_field_bytes)))
+            .collect();
+        doc.tags = doc
+            .tags
+            .iter()
+            .map(|t| truncate_bytes(clean_text(t), 64))
+            .filter(|t| !t.is_empty())
+            .take(20)
+            .collect();
+        for link in &mut doc.links {
+            link.rel = clean_text(&link.rel);
+            link.target = clean_text(&link.target);
+        }
+
+        doc
+    }
+
+    /// Whether the document's validity window has closed by `now`.
+    #[must_use]
+    pub fn is_stale(&self, now: DateTime<Utc>) -> bool {
+        self.temporal.valid_until < now
+    }
+
+    /// Check that an ID is safe to use as a vault file name.
+    ///
+    /// Rejects empty IDs and IDs containing path separators, whitespace or
+    /// `..`, any of which could move the file out of its type directory or
+    /// collide with another document.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Validation`] describing the first problem found.
+    pub fn validate_id(id: &str) -> Result<(), MkbError> {
+        let problem = if id.is_empty() {
+            Some("must not be empty")
+        } else if id.contains(['/', '\\']) {
+            Some("must not contain path separators")
+        } else if id.chars().any(char::is_whitespace) {
+            Some("must not contain whitespace")
+        } else if id.contains("..") {
+            Some("must not contain '..'")
+        } else {
+            None
+        };
+
+        match problem {
+            Some(reason) => Err(MkbError::Validation(format!(
+                "Invalid document ID '{id}': {reason}"
+            ))),
+            None => Ok(()),
+        }
+    }
+
     /// Generate a document ID from type and title.
     ///
     /// Format: `<type>-<slug>-<counter>`
     /// Example: `proj-alpha-001`
     #[must_use]
     pub fn generate_id(doc_type: &str, title: &str, counter: u32) -> String {
+        format!("{}-{counter:03}", Self::id_stem(doc_type, title))
+    }
+
+    /// Generate a stable document ID from type, observation time and title.
+    ///
+    /// Format: `<type>-<slug>-<hash>`, where `<hash>` is the first 8 hex
+    /// characters of a SHA-256 over the three inputs. The same inputs always
+    /// give the same ID, so re-importing a document updates it instead of
+    /// creating a copy.
+    /// Example: `proj-alpha-3f9a1c0e`
+    #[must_use]
+    pub fn generate_content_id(doc_type: &str, observed_at: &DateTime<Utc>, title: &str) -> String {
+        use sha2::{Digest, Sha256};
+
+        let mut hasher = Sha256::new();
+        for part in [doc_type, &observed_at.to_rfc3339(), title] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        let hash: String = hasher.finalize()[..4]
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        format!("{}-{hash}", Self::id_stem(doc_type, title))
+    }
+
+    /// `<type>-<slug>`: the first four characters of the type and up to
+    /// three words of the title.
+    #[must_use]
+    pub fn id_stem(doc_type: &str, title: &str) -> String {
         let type_prefix = &doc_type[..doc_type.len().min(4)];
         let slug: String = title
             .to_lowercase()
@@ -121,7 +490,7 @@ impl Document {
             .collect::<Vec<_>>()
             .join("-");
         let slug = &slug[..slug.len().min(30)];
-        format!("{type_prefix}-{slug}-{counter:03}")
+        format!("{type_prefix}-{slug}")
     }
 }
 
@@ -129,6 +498,7 @@ impl Document {
 mod tests {
     use super::*;
     use crate::temporal::TemporalPrecision;
+    use crate::testutil::assert_doc_eq;
     use chrono::TimeZone;
 
     fn utc(y: i32, m: u32, d: u32) -> DateTime<Utc> {
@@ -165,6 +535,7 @@ mod tests {
             valid_until: None,
             temporal_precision: Some(TemporalPrecision::Day),
             occurred_at: None,
+            occurred_until: None,
         };
         let profile = DecayProfile::default_profile();
 
@@ -182,7 +553,34 @@ mod tests {
         assert_eq!(doc.title, "Alpha Project");
         assert_eq!(doc.temporal.observed_at, utc(2025, 2, 10));
         assert_eq!(doc.temporal.temporal_precision, TemporalPrecision::Day);
-        assert!((doc.confidence - 1.0).abs() < f64::EPSILON);
+        assert!((doc.confidence - 0.95).abs() < f64::EPSILON);
+        assert!(!doc.confidence_override);
+    }
+
+    #[test]
+    fn document_confidence_defaults_to_precision_weight() {
+        let doc_with = |precision| {
+            Document::new(
+                "proj-alpha-001".to_string(),
+                "project".to_string(),
+                "Alpha Project".to_string(),
+                RawTemporalInput {
+                    observed_at: Some(utc(2025, 2, 10)),
+                    temporal_precision: Some(precision),
+                    ..Default::default()
+                },
+                &DecayProfile::default_profile(),
+            )
+            .unwrap()
+        };
+
+        assert!((doc_with(TemporalPrecision::Exact).confidence - 1.0).abs() < f64::EPSILON);
+        let mut inferred = doc_with(TemporalPrecision::Inferred);
+        assert!((inferred.confidence - 0.40).abs() < f64::EPSILON);
+
+        inferred.set_confidence(1.0);
+        assert!((inferred.confidence - 1.0).abs() < f64::EPSILON);
+        assert!(inferred.confidence_override);
     }
 
     #[test]
@@ -192,6 +590,7 @@ mod tests {
             valid_until: Some(utc(2025, 8, 10)),
             temporal_precision: Some(TemporalPrecision::Day),
             occurred_at: None,
+            occurred_until: None,
         };
         let profile = DecayProfile::default_profile();
 
@@ -219,6 +618,7 @@ mod tests {
             valid_until: Some(utc(2025, 8, 10)),
             temporal_precision: Some(TemporalPrecision::Exact),
             occurred_at: Some(utc(2025, 2, 9)),
+            occurred_until: None,
         };
         let profile = DecayProfile::default_profile();
 
@@ -231,25 +631,206 @@ mod tests {
         )
         .unwrap();
         doc.tags = vec!["rust".to_string(), "ai".to_string()];
-        doc.source = Some("manual".to_string());
         doc.confidence = 0.95;
+        doc.provenance = Some(ProvenanceRecord {
+            source_uri: Some("https://example.com/alpha".to_string()),
+            ingested_by: Some("jane".to_string()),
+            original_filename: Some("alpha.md".to_string()),
+            transformation_log: vec!["html-to-markdown".to_string()],
+            ..ProvenanceRecord::new(ProvenanceType::Import, "cli")
+        });
 
         let yaml = serde_yaml::to_string(&doc).expect("serialize");
+        assert!(yaml.contains("source_type: import"));
         let back: Document = serde_yaml::from_str(&yaml).expect("deserialize");
 
-        assert_eq!(doc.id, back.id);
-        assert_eq!(doc.doc_type, back.doc_type);
-        assert_eq!(doc.title, back.title);
-        assert_eq!(doc.temporal.observed_at, back.temporal.observed_at);
-        assert_eq!(doc.temporal.valid_until, back.temporal.valid_until);
+        assert_doc_eq!(doc, back);
+    }
+
+    #[test]
+    fn legacy_string_provenance_still_parses() {
+        let yaml = |provenance: &str| {
+            format!(
+                "id: a\ntype: note\ntitle: A\n_created_at: 2025-02-10T00:00:00Z\n\
+                 _modified_at: 2025-02-10T00:00:00Z\nobserved_at: 2025-02-10T00:00:00Z\n\
+                 valid_until: 2025-08-10T00:00:00Z\ntemporal_precision: day\n\
+                 provenance: {provenance}\n"
+            )
+        };
+
+        let doc: Document = serde_yaml::from_str(&yaml("llm")).unwrap();
+        let record = doc.provenance.unwrap();
+        assert_eq!(record.source_type, ProvenanceType::LlmGenerated);
+        assert_eq!(record.ingested_via, "llm");
+
+        let doc: Document = serde_yaml::from_str(&yaml("meeting notes")).unwrap();
         assert_eq!(
-            doc.temporal.temporal_precision,
-            back.temporal.temporal_precision
+            doc.provenance.unwrap(),
+            ProvenanceRecord::new(ProvenanceType::Manual, "meeting notes")
         );
-        assert_eq!(doc.temporal.occurred_at, back.temporal.occurred_at);
-        assert_eq!(doc.tags, back.tags);
-        assert_eq!(doc.source, back.source);
-        assert!((doc.confidence - back.confidence).abs() < f64::EPSILON);
+    }
+
+    fn observed(id: &str, observed_at: DateTime<Utc>) -> Document {
+        Document::new(
+            id.to_string(),
+            "project".to_string(),
+            "Alpha Project".to_string(),
+            RawTemporalInput {
+                observed_at: Some(observed_at),
+                valid_until: Some(utc(2025, 6, 1)),
+                ..Default::default()
+            },
+            &DecayProfile::default_profile(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn documents_compare_and_hash_by_id() {
+        let doc = observed("proj-alpha-001", utc(2025, 2, 10));
+        let mut edited = observed("proj-alpha-001", utc(2025, 3, 1));
+        edited.title = "Alpha Project v2".to_string();
+
+        assert_eq!(doc, edited);
+        assert_eq!(doc.cmp(&edited), Ordering::Equal);
+        assert_ne!(doc, observed("proj-alpha-002", utc(2025, 2, 10)));
+
+        let set: std::collections::HashSet<_> = [doc, edited].into_iter().collect();
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn documents_sort_newest_first_then_by_id() {
+        let mut docs = [
+            observed("b", utc(2025, 1, 1)),
+            observed("c", utc(2025, 3, 1)),
+            observed("a", utc(2025, 1, 1)),
+        ];
+        docs.sort_by(Document::cmp_recency);
+
+        let ids: Vec<_> = docs.iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(ids, ["c", "a", "b"]);
+    }
+
+    #[test]
+    fn ordering_is_total_across_versions_of_one_id() {
+        // Two versions of x around y: ordering by recency would put y
+        // between two documents that compare equal
+        let newer = observed("x", utc(2025, 3, 1));
+        let other = observed("y", utc(2025, 2, 1));
+        let older = observed("x", utc(2025, 1, 1));
+        assert_eq!(newer.cmp(&older), Ordering::Equal);
+        assert_eq!(newer.cmp(&other), older.cmp(&other));
+
+        let set: std::collections::BTreeSet<_> = [newer, other, older].into_iter().collect();
+        let ids: Vec<_> = set.iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(ids, ["x", "y"]);
+    }
+
+    #[test]
+    fn is_stale_once_valid_until_has_passed() {
+        let doc = observed("proj-alpha-001", utc(2025, 2, 10));
+        assert!(!doc.is_stale(utc(2025, 5, 31)));
+        assert!(!doc.is_stale(utc(2025, 6, 1)));
+        assert!(doc.is_stale(utc(2025, 6, 2)));
+    }
+
+    #[test]
+    fn sanitize_truncates_body_and_title_at_char_boundaries() {
+        let mut doc = observed("proj-alpha-001", utc(2025, 2, 10));
+        doc.body = "é".repeat(10);
+        doc.title = "ü".repeat(300);
+
+        let clean = doc.sanitize(5, 64);
+        assert_eq!(clean.body, "éé");
+        assert_eq!(clean.title.len(), 512);
+        assert!(clean.title.chars().all(|c| c == 'ü'));
+        assert_eq!(doc.body.len(), 20, "original must be untouched");
+    }
+
+    #[test]
+    fn sanitize_strips_null_bytes_from_strings() {
+        let mut doc = observed("proj-alpha-001", utc(2025, 2, 10));
+        doc.title = "Al\0pha".to_string();
+        doc.body = "bo\0dy".to_string();
+        let mut record = ProvenanceRecord::new(ProvenanceType::LlmGenerated, "mc\0p");
+        record.transformation_log.push("sum\0marized".to_string());
+        doc.provenance = Some(record);
+        doc.fields.insert(
+            "status".to_string(),
+            serde_json::json!({ "nested": ["a\0b"] }),
+        );
+
+        let clean = doc.sanitize(1024, 1024);
+        assert_eq!(clean.title, "Alpha");
+        assert_eq!(clean.body, "body");
+        let record = clean.provenance.unwrap();
+        assert_eq!(record.ingested_via, "mcp");
+        assert_eq!(record.transformation_log, vec!["summarized"]);
+        assert_eq!(
+            clean.fields["status"],
+            serde_json::json!({ "nested": ["ab"] })
+        );
+    }
+
+    #[test]
+    fn sanitize_normalizes_to_nfc() {
+        let mut doc = observed("proj-alpha-001", utc(2025, 2, 10));
+        doc.title = "Cafe\u{301}".to_string();
+        doc.tags = vec!["re\u{301}sume\u{301}".to_string()];
+
+        let clean = doc.sanitize(1024, 1024);
+        assert_eq!(clean.title, "Caf\u{e9}");
+        assert_eq!(clean.tags, ["r\u{e9}sum\u{e9}"]);
+    }
+
+    #[test]
+    fn sanitize_limits_tags_and_field_values() {
+        let mut doc = observed("proj-alpha-001", utc(2025, 2, 10));
+        doc.tags = (0..30).map(|i| format!("tag-{i}")).collect();
+        doc.tags[0] = "x".repeat(100);
+        doc.tags[1] = "\0".to_string();
+        doc.fields
+            .insert("notes".to_string(), serde_json::json!("y".repeat(100)));
+
+        let clean = doc.sanitize(1024, 10);
+        assert_eq!(clean.tags.len(), 20);
+        assert_eq!(clean.tags[0], "x".repeat(64));
+        assert_eq!(clean.tags[1], "tag-2");
+        assert_eq!(clean.fields["notes"], serde_json::json!("y".repeat(10)));
+    }
+
+    #[test]
+    fn sanitize_keeps_only_url_or_path_sources() {
+        let mut doc = observed("proj-alpha-001", utc(2025, 2, 10));
+        for (source, kept) in [
+            ("https://example.com/a?b=c", true),
+            ("notes/meeting.md", true),
+            ("/var/data/export.csv", true),
+            ("<script>alert(1)</script>", false),
+            ("https://exa mple.com", false),
+            ("://missing-scheme", false),
+            ("line\nbreak", false),
+            ("   ", false),
+        ] {
+            doc.provenance = Some(ProvenanceRecord {
+                source_uri: Some(source.to_string()),
+                ..ProvenanceRecord::new(ProvenanceType::Manual, "cli")
+            });
+            let clean = doc.sanitize(1024, 1024).provenance.unwrap();
+            assert_eq!(clean.source_uri.is_some(), kept, "{source:?}");
+        }
+    }
+
+    #[test]
+    fn sanitized_document_passes_temporal_gate() {
+        let mut doc = observed("proj-alpha-001", utc(2025, 2, 10));
+        doc.title = "\0".repeat(1000);
+        doc.body = "x".repeat(1000);
+
+        let clean = doc.sanitize(10, 10);
+        assert!(TemporalGate::validate_fields(&clean.temporal).is_ok());
+        assert_eq!(clean.temporal, doc.temporal);
     }
 
     #[test]
@@ -260,4 +841,57 @@ mod tests {
         let id = Document::generate_id("meeting", "Sprint Review Q4", 42);
         assert_eq!(id, "meet-sprint-review-q4-042");
     }
+
+    #[test]
+    fn generate_content_id_is_stable_and_truncated() {
+        let observed = utc(2025, 2, 10);
+        let id = Document::generate_content_id("project", &observed, "Alpha Project");
+        assert_eq!(
+            id,
+            Document::generate_content_id("project", &observed, "Alpha Project")
+        );
+        assert!(id.starts_with("proj-alpha-project-"));
+
+        let hash = id.rsplit('-').next().unwrap();
+        assert_eq!(hash.len(), 8);
+        assert!(hash.chars().all(|c| c.is_ascii_hexdigit()));
+        assert!(Document::validate_id(&id).is_ok());
+
+        assert_ne!(
+            id,
+            Document::generate_content_id("project", &observed, "Alpha Project v2")
+        );
+        assert_ne!(
+            id,
+            Document::generate_content_id("project", &utc(2025, 2, 11), "Alpha Project")
+        );
+        assert_ne!(
+            id,
+            Document::generate_content_id("proj", &observed, "Alpha Project")
+        );
+    }
+
+    #[test]
+    fn validate_id_accepts_generated_ids() {
+        assert!(Document::validate_id("proj-alpha-project-001").is_ok());
+        assert!(Document::validate_id("meet_2025.02.10").is_ok());
+    }
+
+    #[test]
+    fn validate_id_rejects_unsafe_ids() {
+        for id in [
+            "",
+            "proj/alpha",
+            "..\\etc",
+            "proj alpha",
+            "proj\talpha",
+            "proj..001",
+        ] {
+            let err = Document::validate_id(id).unwrap_err();
+            assert!(
+                matches!(err, MkbError::Validation(ref msg) if msg.contains("Invalid document ID")),
+                "{id:?} accepted or wrong error: {err}"
+            );
+        }
+    }
 }
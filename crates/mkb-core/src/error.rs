@@ -1,5 +1,7 @@
 //! Error types for MKB.
 
+use std::path::PathBuf;
+
 use thiserror::Error;
 
 /// Top-level result type for MKB operations.
@@ -14,6 +16,25 @@ pub enum MkbError {
     #[error("schema error: {0}")]
     Schema(#[from] SchemaError),
 
+    #[error("document not found: {id} ({})", path.display())]
+    DocumentNotFound { id: String, path: PathBuf },
+
+    #[error("document already exists: {id}")]
+    DuplicateDocument { id: String },
+
+    #[error("index corrupted: {reason}")]
+    IndexCorrupted { reason: String },
+
+    #[error(
+        "document of type '{doc_type}' violates its schema: {}",
+        errors.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ")
+    )]
+    SchemaViolation {
+        doc_type: String,
+        errors: Vec<SchemaError>,
+    },
+
+    /// Vault failures not covered by a more specific variant.
     #[error("vault error: {0}")]
     Vault(String),
 
@@ -31,6 +52,9 @@ pub enum MkbError {
 
     #[error("serialization error: {0}")]
     Serialization(String),
+
+    #[error("validation error: {0}")]
+    Validation(String),
 }
 
 /// Errors related to temporal grounding and decay.
@@ -56,6 +80,41 @@ pub enum TemporalError {
         observed_at: String,
         occurred_at: String,
     },
+
+    #[error("occurred_until ({occurred_until}) requires occurred_at")]
+    OccurredUntilWithoutOccurredAt { occurred_until: String },
+
+    #[error("occurred_until ({occurred_until}) cannot be before occurred_at ({occurred_at})")]
+    OccurredUntilBeforeOccurredAt {
+        occurred_at: String,
+        occurred_until: String,
+    },
+
+    #[error("occurred_until ({occurred_until}) should not be after observed_at ({observed_at})")]
+    OccurredUntilAfterObservedAt {
+        observed_at: String,
+        occurred_until: String,
+    },
+
+    #[error(
+        "invalid datetime '{0}': expected RFC 3339 (2025-06-15T10:00:00+02:00), \
+         'YYYY-MM-DD HH:MM:SS' or 'YYYY-MM-DD'"
+    )]
+    InvalidDateTime(String),
+}
+
+/// Why [`FuzzyDateTimeParser`](crate::temporal::FuzzyDateTimeParser) could
+/// not read a date expression.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum FuzzyParseError {
+    #[error(
+        "relative date '{0}' cannot be resolved without knowing when it was written; \
+         use an absolute date such as 2025-06-15"
+    )]
+    RelativeNotSupported(String),
+
+    #[error("unrecognized date '{0}': expected ISO 8601, 'YYYY-QN', 'YYYY' or 'Month YYYY'")]
+    Unrecognized(String),
 }
 
 /// Errors related to schema validation.
@@ -83,6 +142,39 @@ pub enum SchemaError {
 
     #[error("schema parse error: {0}")]
     ParseError(String),
+
+    #[error("schema '{schema}' extends unknown schema '{parent}'")]
+    MissingSchema { schema: String, parent: String },
+
+    #[error("circular schema inheritance: {}", chain.join(" -> "))]
+    CircularInheritance { chain: Vec<String> },
+
+    #[error("link '{rel}' must target a '{expected}' document, but '{target}' is a '{actual}'")]
+    LinkTargetType {
+        rel: String,
+        target: String,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("too many '{rel}' links: at most {max} allowed, found {count}")]
+    TooManyLinks { rel: String, max: u32, count: usize },
+
+    #[error("field '{field}' violates constraint: {message}")]
+    ConstraintViolation { field: String, message: String },
+}
+
+/// Errors from generating embeddings.
+#[derive(Debug, Error)]
+pub enum EmbeddingError {
+    #[error("embedding request failed: {0}")]
+    Request(String),
+
+    #[error("unexpected embedding response: {0}")]
+    Response(String),
+
+    #[error("embedding dimension mismatch: expected {expected}, got {actual}")]
+    Dimension { expected: usize, actual: usize },
 }
 
 #[cfg(test)]
@@ -103,4 +195,25 @@ mod tests {
         assert!(msg.contains("title"));
         assert!(msg.contains("project"));
     }
+
+    #[test]
+    fn schema_violation_lists_every_error() {
+        let err = MkbError::SchemaViolation {
+            doc_type: "project".to_string(),
+            errors: vec![
+                SchemaError::MissingRequiredField {
+                    doc_type: "project".to_string(),
+                    field: "status".to_string(),
+                },
+                SchemaError::ConstraintViolation {
+                    field: "priority".to_string(),
+                    message: "must be at most 5".to_string(),
+                },
+            ],
+        };
+        let msg = err.to_string();
+        assert!(msg.contains("'project'"));
+        assert!(msg.contains("'status'"));
+        assert!(msg.contains("at most 5"));
+    }
 }
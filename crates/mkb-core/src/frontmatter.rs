@@ -13,9 +13,66 @@
 //!
 //! ## Body content here
 //! ```
+//!
+//! Files record the layout they were written with under `_mkb_version`
+//! (see [`FrontmatterVersion`]); files without it predate versioning.
+
+use std::fmt;
+
+use crate::document::{deserialize_provenance, Document, ProvenanceRecord, ProvenanceType};
+use crate::error::{FuzzyParseError, MkbError};
+use crate::temporal::{DecayProfile, FuzzyDateTimeParser, RawTemporalInput, TemporalPrecision};
+use chrono::Utc;
+
+/// Frontmatter key holding the [`FrontmatterVersion`] a file was written with.
+pub const VERSION_KEY: &str = "_mkb_version";
+
+/// Layout of a document file's frontmatter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrontmatterVersion {
+    /// Written before files were versioned: the V1 layout without a
+    /// `_mkb_version` key.
+    Unversioned,
+    /// `_mkb_version: 1`.
+    V1,
+}
+
+impl FrontmatterVersion {
+    /// The version [`write_document`] produces.
+    pub const CURRENT: Self = Self::V1;
+
+    /// The `_mkb_version` value written for this version, if any.
+    #[must_use]
+    pub fn number(self) -> Option<u64> {
+        match self {
+            Self::Unversioned => None,
+            Self::V1 => Some(1),
+        }
+    }
 
-use crate::document::Document;
-use crate::error::MkbError;
+    /// The version a file's content was written with.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Parse`] if frontmatter is missing, or
+    /// [`MkbError::Serialization`] if it is malformed or names a version
+    /// this build does not know.
+    pub fn detect(content: &str) -> Result<Self, MkbError> {
+        let (yaml, _) = split_frontmatter(content)?;
+        let mut value: serde_yaml::Value =
+            serde_yaml::from_str(yaml).map_err(|e| MkbError::Serialization(e.to_string()))?;
+        take_version(&mut value)
+    }
+}
+
+impl fmt::Display for FrontmatterVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.number() {
+            Some(n) => write!(f, "v{n}"),
+            None => f.write_str("unversioned"),
+        }
+    }
+}
 
 /// Parse a markdown file into frontmatter YAML and body content.
 ///
@@ -48,36 +105,378 @@ pub fn split_frontmatter(content: &str) -> Result<(&str, &str), MkbError> {
     // Skip the newline after closing ---
     let body = rest.strip_prefix('\n').unwrap_or(rest);
     let body = body.strip_prefix('\r').unwrap_or(body);
+    // ...and the blank line `write_document` puts before the body
+    let body = body
+        .strip_prefix("\r\n")
+        .or_else(|| body.strip_prefix('\n'))
+        .unwrap_or(body);
 
     Ok((yaml, body))
 }
 
 /// Parse a markdown file with YAML frontmatter into a [`Document`].
 ///
+/// Temporal fields accept anything [`FuzzyDateTimeParser`] does, so
+/// `observed_at: 2025 Q1` is read as the first day of the quarter. The
+/// declared `temporal_precision` is lowered to what the `observed_at`
+/// notation supports: a bare date can't be `exact`, a quarter can't be
+/// finer than `quarter`.
+///
 /// # Errors
 ///
 /// Returns [`MkbError::Parse`] if frontmatter is missing or malformed.
-/// Returns [`MkbError::Serialization`] if YAML cannot be deserialized.
+/// Returns [`MkbError::Serialization`] if YAML cannot be deserialized or a
+/// temporal field holds a relative date such as `last Tuesday`.
 pub fn parse_document(content: &str) -> Result<Document, MkbError> {
     let (yaml, body) = split_frontmatter(content)?;
+    let value: serde_yaml::Value =
+        serde_yaml::from_str(yaml).map_err(|e| MkbError::Serialization(e.to_string()))?;
+    document_from_yaml(value, body)
+}
+
+/// Parse a file holding several `---` separated documents.
+///
+/// The content is split on lines consisting of `---`. A segment whose first
+/// line looks like a YAML key (`id: ...`) is frontmatter, and the segment
+/// after it is its body unless that is frontmatter too. Any other segment is
+/// a body-only document with generated metadata: type `document`, the first
+/// `# ` heading as title, observed now at day precision.
+///
+/// Frontmatter without an `id` gets one generated from its type, title and
+/// position in the file, so parsing the same file twice gives the same IDs.
+/// A frontmatter segment that fails to parse is logged and skipped; the
+/// other documents are still returned. A file with a single frontmatter
+/// document parses the same as [`parse_document`], except that a `---` line
+/// in its body starts a new document.
+///
+/// # Errors
+///
+/// Returns the error of the last failed segment if no segment parsed.
+pub fn parse_documents(content: &str) -> Result<Vec<Document>, MkbError> {
+    let segments = split_segments(content);
+
+    let mut docs = Vec::new();
+    let mut last_error = None;
+    let mut i = 0;
+    while i < segments.len() {
+        let segment = segments[i];
+        let position = i;
+        i += 1;
+
+        if !looks_like_frontmatter(segment) {
+            match body_only_document(segment, position) {
+                Ok(doc) => docs.push(doc),
+                Err(e) => last_error = Some(e),
+            }
+            continue;
+        }
+
+        let body = match segments.get(i) {
+            Some(next) if !looks_like_frontmatter(next) => {
+                i += 1;
+                next.trim_start_matches(['\r', '\n'])
+            }
+            _ => "",
+        };
+        let parsed = serde_yaml::from_str(segment)
+            .map_err(|e| MkbError::Serialization(e.to_string()))
+            .and_then(|mut value: serde_yaml::Value| {
+                insert_missing_id(&mut value, segment, position);
+                document_from_yaml(value, body)
+            });
+        match parsed {
+            Ok(doc) => docs.push(doc),
+            Err(e) => {
+                tracing::warn!(
+                    "Skipping document {} of multi-document file: {e}",
+                    position + 1
+                );
+                last_error = Some(e);
+            }
+        }
+    }
+
+    match last_error {
+        Some(e) if docs.is_empty() => Err(e),
+        _ => Ok(docs),
+    }
+}
+
+/// Split content on `---` lines, dropping blank segments such as the one
+/// before a leading `---` or after a trailing one.
+fn split_segments(content: &str) -> Vec<&str> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        if line.trim_end_matches(['\r', '\n']) == "---" {
+            segments.push(&content[start..offset]);
+            start = offset + line.len();
+        }
+        offset += line.len();
+    }
+    segments.push(&content[start..]);
+    segments.retain(|s| !s.trim().is_empty());
+    segments
+}
+
+/// Whether a segment's first non-blank line is a `key:` mapping entry.
+fn looks_like_frontmatter(segment: &str) -> bool {
+    let Some(line) = segment.lines().find(|l| !l.trim().is_empty()) else {
+        return false;
+    };
+    let Some((key, _)) = line.split_once(':') else {
+        return false;
+    };
+    !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Give a frontmatter mapping without `id` one derived from its content.
+fn insert_missing_id(value: &mut serde_yaml::Value, segment: &str, position: usize) {
+    let Some(mapping) = value.as_mapping_mut() else {
+        return;
+    };
+    if mapping.contains_key("id") {
+        return;
+    }
+    let field = |key| mapping.get(key).and_then(serde_yaml::Value::as_str);
+    let doc_type = field("type").unwrap_or("document").to_string();
+    let title = field("title").unwrap_or("Untitled").to_string();
+    mapping.insert(
+        serde_yaml::Value::from("id"),
+        serde_yaml::Value::from(segment_id(&doc_type, &title, segment, position)),
+    );
+}
+
+/// A document built from a segment with no frontmatter.
+fn body_only_document(segment: &str, position: usize) -> Result<Document, MkbError> {
+    let body = segment.trim_start_matches(['\r', '\n']);
+    let title = body
+        .lines()
+        .find(|l| l.starts_with("# "))
+        .map_or("Untitled", |l| l.trim_start_matches("# "));
+    let doc_type = "document";
+    let input = RawTemporalInput {
+        observed_at: Some(Utc::now()),
+        valid_until: None,
+        temporal_precision: Some(TemporalPrecision::Day),
+        occurred_at: None,
+        occurred_until: None,
+    };
+
+    let mut doc = Document::new(
+        segment_id(doc_type, title, segment, position),
+        doc_type.to_string(),
+        title.to_string(),
+        input,
+        &DecayProfile::default_profile(),
+    )?;
+    doc.body = body.to_string();
+    Ok(doc)
+}
+
+/// `<type>-<slug>-<hash>`, hashing the segment and its position so that
+/// identical segments in one file still get distinct IDs.
+fn segment_id(doc_type: &str, title: &str, segment: &str, position: usize) -> String {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    hasher.update(position.to_le_bytes());
+    hasher.update(segment.as_bytes());
+    let hash: String = hasher.finalize()[..4]
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    format!("{}-{hash}", Document::id_stem(doc_type, title))
+}
+
+/// Deserialize a frontmatter mapping with the parser for its
+/// `_mkb_version` and attach `body`.
+fn document_from_yaml(mut value: serde_yaml::Value, body: &str) -> Result<Document, MkbError> {
+    match take_version(&mut value)? {
+        // Unversioned files already use the V1 layout
+        FrontmatterVersion::Unversioned | FrontmatterVersion::V1 => document_from_v1(value, body),
+    }
+}
+
+/// Remove `_mkb_version` from a frontmatter mapping and return the version
+/// it names.
+fn take_version(value: &mut serde_yaml::Value) -> Result<FrontmatterVersion, MkbError> {
+    let Some(raw) = value
+        .as_mapping_mut()
+        .and_then(|mapping| mapping.remove(VERSION_KEY))
+    else {
+        return Ok(FrontmatterVersion::Unversioned);
+    };
+    match raw.as_u64() {
+        Some(1) => Ok(FrontmatterVersion::V1),
+        _ => Err(MkbError::Serialization(format!(
+            "unsupported {VERSION_KEY}: {}",
+            serde_yaml::to_string(&raw).unwrap_or_default().trim_end()
+        ))),
+    }
+}
+
+/// Deserialize a V1 frontmatter mapping and attach `body`.
+fn document_from_v1(mut value: serde_yaml::Value, body: &str) -> Result<Document, MkbError> {
+    let observed_precision = normalize_fuzzy_dates(&mut value)?;
+    migrate_legacy_source(&mut value)?;
 
     let mut doc: Document =
-        serde_yaml::from_str(yaml).map_err(|e| MkbError::Serialization(e.to_string()))?;
+        serde_yaml::from_value(value).map_err(|e| MkbError::Serialization(e.to_string()))?;
+    if let Some(precision) = observed_precision {
+        doc.temporal.temporal_precision = doc.temporal.temporal_precision.max(precision);
+    }
     doc.body = body.to_string();
 
     Ok(doc)
 }
 
-/// Write a [`Document`] as a markdown file with YAML frontmatter.
+/// Move the top-level `source` older files carry into
+/// `provenance.source_uri`.
+///
+/// A file without `provenance` gets a `Manual` record ingested via
+/// `unknown`. A `source_uri` already in the record wins over `source`.
+fn migrate_legacy_source(value: &mut serde_yaml::Value) -> Result<(), MkbError> {
+    #[derive(serde::Deserialize)]
+    struct Provenance(
+        #[serde(deserialize_with = "deserialize_provenance")] Option<ProvenanceRecord>,
+    );
+
+    let Some(mapping) = value.as_mapping_mut() else {
+        return Ok(());
+    };
+    let Some(source) = mapping.remove("source") else {
+        return Ok(());
+    };
+    let Some(source) = source.as_str().map(str::to_string) else {
+        return Ok(());
+    };
+
+    let existing = match mapping.remove("provenance") {
+        Some(raw) => {
+            serde_yaml::from_value::<Provenance>(raw)
+                .map_err(|e| MkbError::Serialization(format!("provenance: {e}")))?
+                .0
+        }
+        None => None,
+    };
+    let mut record =
+        existing.unwrap_or_else(|| ProvenanceRecord::new(ProvenanceType::Manual, "unknown"));
+    record.source_uri.get_or_insert(source);
+    mapping.insert(
+        serde_yaml::Value::from("provenance"),
+        serde_yaml::to_value(record).map_err(|e| MkbError::Serialization(e.to_string()))?,
+    );
+    Ok(())
+}
+
+/// Rewrite the temporal fields of a frontmatter mapping as RFC 3339 and
+/// return the precision `observed_at` was written with.
+///
+/// Values no notation matches are left alone for deserialization to report.
+fn normalize_fuzzy_dates(
+    value: &mut serde_yaml::Value,
+) -> Result<Option<TemporalPrecision>, MkbError> {
+    let Some(mapping) = value.as_mapping_mut() else {
+        return Ok(None);
+    };
+
+    let mut observed_precision = None;
+    for key in [
+        "observed_at",
+        "valid_until",
+        "occurred_at",
+        "occurred_until",
+    ] {
+        // YAML reads a bare `2025` as a number
+        let raw = match mapping.get(key) {
+            Some(serde_yaml::Value::String(s)) => s.clone(),
+            Some(serde_yaml::Value::Number(n)) => n.to_string(),
+            _ => continue,
+        };
+        let parsed = match FuzzyDateTimeParser::parse(&raw) {
+            Ok(parsed) => parsed,
+            Err(e @ FuzzyParseError::RelativeNotSupported(_)) => {
+                return Err(MkbError::Serialization(format!("{key}: {e}")));
+            }
+            Err(FuzzyParseError::Unrecognized(_)) => continue,
+        };
+        mapping.insert(
+            serde_yaml::Value::from(key),
+            serde_yaml::Value::from(parsed.utc.to_rfc3339()),
+        );
+        if key == "observed_at" {
+            observed_precision = Some(parsed.precision);
+        }
+    }
+
+    Ok(observed_precision)
+}
+
+/// Write a [`Document`] as a markdown file in the current
+/// [`FrontmatterVersion`] (see [`Document::to_frontmatter_v1`]).
 ///
 /// # Errors
 ///
-/// Returns [`MkbError::Serialization`] if the document cannot be serialized.
+/// Returns the errors of [`Document::to_frontmatter_v1`].
 pub fn write_document(doc: &Document) -> Result<String, MkbError> {
+    doc.to_frontmatter_v1()
+}
+
+impl Document {
+    /// Write this document as a markdown file with YAML frontmatter in the
+    /// given layout.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`to_frontmatter_v1`](Self::to_frontmatter_v1).
+    pub fn to_frontmatter_string(&self, version: FrontmatterVersion) -> Result<String, MkbError> {
+        render_frontmatter(self, version.number())
+    }
+
+    /// Write this document as a markdown file with V1 YAML frontmatter,
+    /// starting with `_mkb_version: 1`.
+    ///
+    /// A body that does not end in a newline gets one; everything else
+    /// survives a round trip through [`parse_document`] unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Serialization`] if the document cannot be
+    /// serialized, or if it could not be read back as written: a body
+    /// starting with `---` or containing a null byte, or a tag containing a
+    /// comma (the index stores tags comma-separated).
+    pub fn to_frontmatter_v1(&self) -> Result<String, MkbError> {
+        render_frontmatter(self, FrontmatterVersion::V1.number())
+    }
+}
+
+/// The V1 file layout, tagged with `version` when given.
+fn render_frontmatter(doc: &Document, version: Option<u64>) -> Result<String, MkbError> {
+    if doc.body.starts_with("---") {
+        return Err(MkbError::Serialization(
+            "body must not start with a '---' delimiter".to_string(),
+        ));
+    }
+    if doc.body.contains('\0') {
+        return Err(MkbError::Serialization(
+            "body must not contain null bytes".to_string(),
+        ));
+    }
+    if let Some(tag) = doc.tags.iter().find(|t| t.contains(',')) {
+        return Err(MkbError::Serialization(format!(
+            "tag '{tag}' must not contain a comma"
+        )));
+    }
+
     let yaml = serde_yaml::to_string(doc).map_err(|e| MkbError::Serialization(e.to_string()))?;
 
-    let mut output = String::with_capacity(yaml.len() + doc.body.len() + 10);
+    let mut output = String::with_capacity(yaml.len() + doc.body.len() + 32);
     output.push_str("---\n");
+    if let Some(version) = version {
+        output.push_str(&format!("{VERSION_KEY}: {version}\n"));
+    }
     output.push_str(&yaml);
     output.push_str("---\n");
     if !doc.body.is_empty() {
@@ -94,8 +493,7 @@ pub fn write_document(doc: &Document) -> Result<String, MkbError> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::temporal::{DecayProfile, RawTemporalInput, TemporalPrecision};
-    use chrono::{TimeZone, Utc};
+    use chrono::TimeZone;
 
     fn utc(y: i32, m: u32, d: u32) -> chrono::DateTime<Utc> {
         Utc.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap()
@@ -161,6 +559,7 @@ This is the Alpha project.
             valid_until: Some(utc(2025, 8, 10)),
             temporal_precision: Some(TemporalPrecision::Day),
             occurred_at: None,
+            occurred_until: None,
         };
         let profile = DecayProfile::default_profile();
 
@@ -183,6 +582,126 @@ This is the Alpha project.
         // Should have closing ---
         let parts: Vec<&str> = output.split("---\n").collect();
         assert!(parts.len() >= 3, "Should have opening and closing ---");
+        assert!(!output.contains("occurred_until"));
+    }
+
+    #[test]
+    fn occurred_range_roundtrips_through_frontmatter() {
+        let input = RawTemporalInput {
+            observed_at: Some(utc(2025, 1, 10)),
+            occurred_at: Some(utc(2025, 1, 5)),
+            occurred_until: Some(utc(2025, 1, 8)),
+            ..Default::default()
+        };
+        let doc = Document::new(
+            "event-conf-001".to_string(),
+            "event".to_string(),
+            "Conference".to_string(),
+            input,
+            &DecayProfile::default_profile(),
+        )
+        .unwrap();
+
+        let written = write_document(&doc).unwrap();
+        assert!(written.contains("occurred_until:"));
+        let parsed = parse_document(&written).unwrap();
+        assert_eq!(parsed.temporal.occurred_at, Some(utc(2025, 1, 5)));
+        assert_eq!(parsed.temporal.occurred_until, Some(utc(2025, 1, 8)));
+    }
+
+    #[test]
+    fn parses_offset_and_date_only_timestamps() {
+        let content = r#"---
+id: "test-001"
+type: project
+title: "Test"
+observed_at: 2025-02-10
+valid_until: "2025-08-10T12:00:00-05:00"
+temporal_precision: exact
+_created_at: "2025-02-10T00:00:00Z"
+_modified_at: "2025-02-10T00:00:00Z"
+confidence: 1.0
+---
+
+Body here.
+"#;
+
+        let doc = parse_document(content).unwrap();
+        assert_eq!(doc.temporal.observed_at, utc(2025, 2, 10));
+        assert_eq!(
+            doc.temporal.valid_until,
+            Utc.with_ymd_and_hms(2025, 8, 10, 17, 0, 0).unwrap()
+        );
+        assert_eq!(doc.temporal.temporal_precision, TemporalPrecision::Day);
+
+        let garbled = content.replace("observed_at: 2025-02-10", "observed_at: last tuesday");
+        assert!(parse_document(&garbled).is_err());
+    }
+
+    #[test]
+    fn parse_document_moves_legacy_source_into_provenance() {
+        let with = |extra: &str| {
+            format!(
+                "---\nid: test-001\ntype: project\ntitle: Test\n\
+                 observed_at: \"2025-02-10T00:00:00Z\"\nvalid_until: \"2026-01-01T00:00:00Z\"\n\
+                 temporal_precision: day\n_created_at: \"2025-02-10T00:00:00Z\"\n\
+                 _modified_at: \"2025-02-10T00:00:00Z\"\n{extra}---\n"
+            )
+        };
+
+        let doc = parse_document(&with("source: notes/standup.md\n")).unwrap();
+        let record = doc.provenance.unwrap();
+        assert_eq!(record.source_type, ProvenanceType::Manual);
+        assert_eq!(record.ingested_via, "unknown");
+        assert_eq!(record.source_uri.as_deref(), Some("notes/standup.md"));
+
+        // A legacy string provenance keeps its meaning
+        let doc = parse_document(&with("source: notes/standup.md\nprovenance: llm\n")).unwrap();
+        let record = doc.provenance.unwrap();
+        assert_eq!(record.source_type, ProvenanceType::LlmGenerated);
+        assert_eq!(record.source_uri.as_deref(), Some("notes/standup.md"));
+
+        let doc = parse_document(&with(
+            "source: old.md\nprovenance:\n  source_type: import\n  ingested_via: cli\n  \
+             source_uri: https://example.com/a\n",
+        ))
+        .unwrap();
+        let record = doc.provenance.unwrap();
+        assert_eq!(record.source_type, ProvenanceType::Import);
+        assert_eq!(record.source_uri.as_deref(), Some("https://example.com/a"));
+
+        // Written back, the origin lives only in the provenance block
+        let mut written = parse_document(&with("source: notes/standup.md\n")).unwrap();
+        written.body = "Body\n".to_string();
+        let content = write_document(&written).unwrap();
+        assert!(!content.contains("\nsource:"));
+        assert!(content.contains("source_uri: notes/standup.md"));
+    }
+
+    #[test]
+    fn parse_document_falls_back_to_fuzzy_dates() {
+        let with_observed = |observed: &str| {
+            format!(
+                "---\nid: test-001\ntype: project\ntitle: Test\nobserved_at: {observed}\n\
+                 valid_until: \"2026-01-01T00:00:00Z\"\ntemporal_precision: exact\n\
+                 _created_at: \"2025-02-10T00:00:00Z\"\n_modified_at: \"2025-02-10T00:00:00Z\"\n\
+                 confidence: 1.0\n---\n"
+            )
+        };
+
+        for (observed, at, precision) in [
+            ("2025 Q2", utc(2025, 4, 1), TemporalPrecision::Quarter),
+            ("\"2025-Q1\"", utc(2025, 1, 1), TemporalPrecision::Quarter),
+            ("2025", utc(2025, 1, 1), TemporalPrecision::Approximate),
+            ("June 2025", utc(2025, 6, 1), TemporalPrecision::Month),
+        ] {
+            let doc = parse_document(&with_observed(observed)).unwrap();
+            assert_eq!(doc.temporal.observed_at, at, "{observed}");
+            assert_eq!(doc.temporal.temporal_precision, precision, "{observed}");
+        }
+
+        let err = parse_document(&with_observed("last Tuesday")).unwrap_err();
+        assert!(err.to_string().contains("relative date"), "{err}");
     }
 
     #[test]
@@ -213,6 +732,7 @@ Body here.
             valid_until: Some(utc(2025, 8, 10)),
             temporal_precision: Some(TemporalPrecision::Day),
             occurred_at: None,
+            occurred_until: None,
         };
         let profile = DecayProfile::default_profile();
 
@@ -238,5 +758,211 @@ Body here.
         assert_eq!(doc.tags, parsed.tags);
         assert!(parsed.body.contains("## Test Body"));
         assert!(parsed.body.contains("Some content here."));
+        // Repeated write/parse cycles leave the body unchanged
+        assert_eq!(parsed.body, doc.body);
+        assert_eq!(write_document(&parsed).unwrap(), written);
+    }
+
+    #[test]
+    fn v1_output_is_tagged_and_detected() {
+        let doc = Document::new(
+            "proj-alpha-001".to_string(),
+            "project".to_string(),
+            "Alpha Project".to_string(),
+            RawTemporalInput {
+                observed_at: Some(utc(2025, 2, 10)),
+                ..Default::default()
+            },
+            &DecayProfile::default_profile(),
+        )
+        .unwrap();
+
+        let v1 = doc.to_frontmatter_v1().unwrap();
+        assert!(v1.starts_with("---\n_mkb_version: 1\nid: proj-alpha-001\n"));
+        assert_eq!(write_document(&doc).unwrap(), v1);
+        assert_eq!(
+            FrontmatterVersion::detect(&v1).unwrap(),
+            FrontmatterVersion::V1
+        );
+
+        let unversioned = doc
+            .to_frontmatter_string(FrontmatterVersion::Unversioned)
+            .unwrap();
+        assert!(!unversioned.contains(VERSION_KEY));
+        assert_eq!(
+            FrontmatterVersion::detect(&unversioned).unwrap(),
+            FrontmatterVersion::Unversioned
+        );
+        // Both layouts read back to the same document, without the tag
+        let parsed = parse_document(&unversioned).unwrap();
+        assert_eq!(parsed.to_frontmatter_v1().unwrap(), v1);
+        assert!(!parsed.fields.contains_key(VERSION_KEY));
+    }
+
+    #[test]
+    fn unknown_version_is_rejected() {
+        let content = "---
+_mkb_version: 7
+id: proj-alpha-001
+type: project
+title: Alpha
+_created_at: 2025-02-10T00:00:00Z
+_modified_at: 2025-02-10T00:00:00Z
+observed_at: 2025-02-10T00:00:00Z
+valid_until: 2025-08-10T00:00:00Z
+temporal_precision: day
+---
+";
+        let err = parse_document(content).unwrap_err();
+        assert!(
+            err.to_string().contains("unsupported _mkb_version: 7"),
+            "{err}"
+        );
+        assert!(FrontmatterVersion::detect(content).is_err());
+    }
+
+    #[test]
+    fn write_rejects_documents_that_would_not_read_back() {
+        let doc = || {
+            Document::new(
+                "proj-edge-001".to_string(),
+                "project".to_string(),
+                "Edge".to_string(),
+                RawTemporalInput {
+                    observed_at: Some(utc(2025, 2, 10)),
+                    ..Default::default()
+                },
+                &DecayProfile::default_profile(),
+            )
+            .unwrap()
+        };
+
+        let mut delimiter_body = doc();
+        delimiter_body.body = "---\nnot: frontmatter\n".to_string();
+        let mut null_body = doc();
+        null_body.body = "before\0after\n".to_string();
+        let mut comma_tag = doc();
+        comma_tag.tags = vec!["rust, ai".to_string()];
+
+        for bad in [delimiter_body, null_body, comma_tag] {
+            assert!(matches!(
+                write_document(&bad),
+                Err(MkbError::Serialization(_))
+            ));
+        }
+    }
+
+    const MULTI_HEADER: &str = "_created_at: 2025-02-10T00:00:00Z
+_modified_at: 2025-02-10T00:00:00Z
+observed_at: 2025-02-10T00:00:00Z
+valid_until: 2025-08-10T00:00:00Z
+temporal_precision: day
+";
+
+    fn multi_frontmatter(id: &str, title: &str) -> String {
+        format!("id: {id}\ntype: project\ntitle: {title}\n{MULTI_HEADER}")
+    }
+
+    #[test]
+    fn parse_documents_single_document_matches_parse_document() {
+        let content = format!("---\n{}---\n\n## Body\n", multi_frontmatter("proj-a", "A"));
+        let docs = parse_documents(&content).unwrap();
+        let single = parse_document(&content).unwrap();
+        assert_eq!(docs.len(), 1);
+        assert_eq!(docs[0].id, "proj-a");
+        assert_eq!(docs[0].body, single.body);
+    }
+
+    #[test]
+    fn parse_documents_reads_three_documents() {
+        let content = format!(
+            "---\n{}---\nBody A\n---\n{}---\nBody B\n---\n{}---\n",
+            multi_frontmatter("proj-a", "A"),
+            multi_frontmatter("proj-b", "B"),
+            multi_frontmatter("proj-c", "C"),
+        );
+        let docs = parse_documents(&content).unwrap();
+        let ids: Vec<_> = docs.iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(ids, ["proj-a", "proj-b", "proj-c"]);
+        assert_eq!(docs[0].body, "Body A\n");
+        assert_eq!(docs[1].body, "Body B\n");
+        assert!(docs[2].body.is_empty());
+    }
+
+    #[test]
+    fn parse_documents_generates_missing_ids() {
+        let content = format!(
+            "---\ntype: project\ntitle: Alpha\n{MULTI_HEADER}---\n---\ntype: project\ntitle: Alpha\n{MULTI_HEADER}---\n"
+        );
+        let docs = parse_documents(&content).unwrap();
+        assert_eq!(docs.len(), 2);
+        assert!(docs[0].id.starts_with("proj-alpha-"));
+        assert_ne!(docs[0].id, docs[1].id);
+        assert_eq!(parse_documents(&content).unwrap()[0].id, docs[0].id);
+    }
+
+    #[test]
+    fn parse_documents_gives_body_only_segments_metadata() {
+        let content = format!(
+            "# First note\nplain text\n---\n{}---\nBody A\n---\nno heading here\n",
+            multi_frontmatter("proj-a", "A"),
+        );
+        let docs = parse_documents(&content).unwrap();
+        assert_eq!(docs.len(), 3);
+        assert_eq!(docs[0].doc_type, "document");
+        assert_eq!(docs[0].title, "First note");
+        assert!(docs[0].id.starts_with("docu-first-note-"));
+        assert_eq!(docs[0].body, "# First note\nplain text\n");
+        assert_eq!(docs[1].id, "proj-a");
+        assert_eq!(docs[1].body, "Body A\n");
+        assert_eq!(docs[2].title, "Untitled");
+        assert_eq!(docs[2].temporal.temporal_precision, TemporalPrecision::Day);
+    }
+
+    #[test]
+    fn parse_documents_skips_malformed_segment() {
+        let content = format!(
+            "---\n{}---\nBody A\n---\nid: broken\ntitle: [unclosed\n---\nBroken body\n---\n{}---\nBody C\n",
+            multi_frontmatter("proj-a", "A"),
+            multi_frontmatter("proj-c", "C"),
+        );
+        let docs = parse_documents(&content).unwrap();
+        let ids: Vec<_> = docs.iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(ids, ["proj-a", "proj-c"]);
+        assert_eq!(docs[1].body, "Body C\n");
+    }
+
+    #[test]
+    fn parse_documents_fails_when_nothing_parses() {
+        let content = "---\nid: broken\ntitle: [unclosed\n---\n";
+        assert!(matches!(
+            parse_documents(content),
+            Err(MkbError::Serialization(_))
+        ));
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    use super::*;
+    use crate::testutil::doc_diff;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn write_then_parse_is_identity(doc in any::<Document>()) {
+            let written = write_document(&doc).unwrap();
+            let parsed = parse_document(&written).unwrap();
+            let diff = doc_diff(&parsed, &doc);
+            prop_assert!(diff.is_empty(), "documents differ:\n  {}", diff.join("\n  "));
+        }
+
+        #[test]
+        fn unversioned_write_then_parse_is_identity(doc in any::<Document>()) {
+            let written = doc.to_frontmatter_string(FrontmatterVersion::Unversioned).unwrap();
+            let parsed = parse_document(&written).unwrap();
+            let diff = doc_diff(&parsed, &doc);
+            prop_assert!(diff.is_empty(), "documents differ:\n  {}", diff.join("\n  "));
+        }
     }
 }
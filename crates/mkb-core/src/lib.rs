@@ -3,11 +3,12 @@
 //! Core types, schemas, and temporal model for the MKB knowledge base.
 //!
 //! This crate defines the foundational types used across all other MKB crates:
-//! - [`Document`] — the central knowledge unit
+//! - [`Document`] — the central knowledge unit, with its [`ProvenanceRecord`]
 //! - Temporal types ([`TemporalFields`], [`TemporalPrecision`], [`TemporalGate`])
 //! - [`RawTemporalInput`] — pre-validation temporal input
 //! - [`DecayProfile`] — configurable decay for `valid_until` computation
-//! - [`Link`] — typed relationships between documents
+//! - [`Link`] — typed relationships between documents, and [`LinkGraph`]
+//!   for whole-graph algorithms over them
 //! - [`schema::SchemaDefinition`] — document type contracts
 //! - Error hierarchy ([`MkbError`], [`error::TemporalError`], [`error::SchemaError`])
 //! - Frontmatter parsing ([`frontmatter`])
@@ -20,9 +21,12 @@ pub mod schema;
 pub mod temporal;
 pub mod view;
 
-pub use document::Document;
+#[cfg(test)]
+mod testutil;
+
+pub use document::{Document, ProvenanceRecord, ProvenanceType};
 pub use error::{MkbError, Result};
-pub use link::Link;
+pub use link::{Link, LinkEdge, LinkGraph};
 pub use temporal::{
     DecayModel, DecayProfile, RawTemporalInput, TemporalFields, TemporalGate, TemporalPrecision,
 };
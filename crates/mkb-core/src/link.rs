@@ -1,10 +1,28 @@
-//! Link type — typed relationships between documents.
+//! Link type — typed relationships between documents — and [`LinkGraph`],
+//! an in-memory view of every link for whole-graph algorithms.
+//!
+//! Links come from a document's frontmatter `links:` array and from
+//! `[[target]]` WikiLinks in its body ([`extract_wikilinks`]).
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
+use std::sync::LazyLock;
 
 use chrono::{DateTime, Utc};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 
+use crate::document::Document;
+
+/// Relationship type given to links converted from `[[WikiLink]]`s.
+pub const WIKILINK_REL: &str = "links_to";
+
+/// `[[Target]]`, `[[Target#Heading]]`, `[[Target|Label]]` and `![[Embed]]`.
+static WIKILINK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"!?\[\[([^\]|#]*)(?:#[^\]|]*)?(?:\|[^\]]*)?\]\]").unwrap());
+
 /// A typed, timestamped relationship between two documents.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Link {
     /// Relationship type (e.g., "owner", "blocked_by", "has_signal").
     pub rel: String,
@@ -20,10 +38,426 @@ pub struct Link {
     pub metadata: Option<serde_json::Value>,
 }
 
+/// WikiLink targets in `body`, in order. Embedded attachments such as
+/// `![[diagram.png]]` are not notes and are left out.
+#[must_use]
+pub fn extract_wikilinks(body: &str) -> Vec<String> {
+    WIKILINK
+        .captures_iter(body)
+        .filter_map(|caps| {
+            let target = caps[1].trim();
+            let target = target.strip_suffix(".md").unwrap_or(target);
+            let is_attachment = Path::new(target)
+                .extension()
+                .is_some_and(|ext| ext.to_str().is_some_and(|e| !e.contains(' ')));
+            (!target.is_empty() && !is_attachment).then(|| target.to_string())
+        })
+        .collect()
+}
+
+/// Every link `doc` declares: its frontmatter links, then one
+/// [`WIKILINK_REL`] link per distinct `[[target_id]]` in its body.
+///
+/// Body links are dated by the document's `observed_at`. A WikiLink to a
+/// target already linked with [`WIKILINK_REL`] in frontmatter is skipped.
+#[must_use]
+pub fn document_links(doc: &Document) -> Vec<Link> {
+    let mut links = doc.links.clone();
+    let mut seen: HashSet<String> = links
+        .iter()
+        .filter(|l| l.rel == WIKILINK_REL)
+        .map(|l| l.target.clone())
+        .collect();
+    for target in extract_wikilinks(&doc.body) {
+        if seen.insert(target.clone()) {
+            links.push(Link {
+                rel: WIKILINK_REL.to_string(),
+                target,
+                observed_at: doc.temporal.observed_at,
+                metadata: None,
+            });
+        }
+    }
+    links
+}
+
+/// A directed edge of a [`LinkGraph`], as stored in the index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkEdge {
+    pub source: String,
+    pub target: String,
+    pub rel: String,
+    /// RFC 3339 timestamp, kept as stored.
+    pub observed_at: String,
+}
+
+/// Every link held in memory as adjacency lists.
+///
+/// Nodes are document IDs; a link target that is not a known document is
+/// still a node. Parallel edges (several `rel`s between the same pair) are
+/// kept, and each node's edges stay in insertion order.
+#[derive(Debug, Clone, Default)]
+pub struct LinkGraph {
+    ids: Vec<String>,
+    index_of: HashMap<String, usize>,
+    edges: Vec<LinkEdge>,
+    outgoing: Vec<Vec<usize>>,
+    incoming: Vec<Vec<usize>>,
+}
+
+impl LinkGraph {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a graph from edges alone; nodes are their endpoints.
+    pub fn from_edges(edges: impl IntoIterator<Item = LinkEdge>) -> Self {
+        let mut graph = Self::new();
+        for edge in edges {
+            graph.add_edge(edge);
+        }
+        graph
+    }
+
+    /// Add a node with no edges. Adding an existing node is a no-op.
+    pub fn add_node(&mut self, id: &str) {
+        self.node(id);
+    }
+
+    /// Add an edge, adding its endpoints as nodes if needed.
+    pub fn add_edge(&mut self, edge: LinkEdge) {
+        let source = self.node(&edge.source);
+        let target = self.node(&edge.target);
+        self.outgoing[source].push(self.edges.len());
+        self.incoming[target].push(self.edges.len());
+        self.edges.push(edge);
+    }
+
+    fn node(&mut self, id: &str) -> usize {
+        if let Some(&n) = self.index_of.get(id) {
+            return n;
+        }
+        let n = self.ids.len();
+        self.ids.push(id.to_string());
+        self.index_of.insert(id.to_string(), n);
+        self.outgoing.push(Vec::new());
+        self.incoming.push(Vec::new());
+        n
+    }
+
+    #[must_use]
+    pub fn node_count(&self) -> usize {
+        self.ids.len()
+    }
+
+    #[must_use]
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Node IDs in the order they were added.
+    pub fn nodes(&self) -> impl Iterator<Item = &str> {
+        self.ids.iter().map(String::as_str)
+    }
+
+    /// Every edge in the order it was added.
+    #[must_use]
+    pub fn edges(&self) -> &[LinkEdge] {
+        &self.edges
+    }
+
+    #[must_use]
+    pub fn contains(&self, id: &str) -> bool {
+        self.index_of.contains_key(id)
+    }
+
+    /// Edges leaving `id`; empty for an unknown node.
+    pub fn outgoing(&self, id: &str) -> impl Iterator<Item = &LinkEdge> {
+        self.adjacent(&self.outgoing, id)
+    }
+
+    /// Edges arriving at `id`; empty for an unknown node.
+    pub fn incoming(&self, id: &str) -> impl Iterator<Item = &LinkEdge> {
+        self.adjacent(&self.incoming, id)
+    }
+
+    fn adjacent<'a>(
+        &'a self,
+        lists: &'a [Vec<usize>],
+        id: &str,
+    ) -> impl Iterator<Item = &'a LinkEdge> {
+        self.index_of
+            .get(id)
+            .map(|&n| lists[n].as_slice())
+            .unwrap_or_default()
+            .iter()
+            .map(|&e| &self.edges[e])
+    }
+
+    /// Whether the directed graph has a cycle, self-links included.
+    ///
+    /// Uses Kahn's algorithm: a cycle exists exactly when repeatedly
+    /// removing nodes with no incoming edges cannot remove them all.
+    #[must_use]
+    pub fn has_cycle(&self) -> bool {
+        let mut in_degree: Vec<usize> = self.incoming.iter().map(Vec::len).collect();
+        let mut ready: Vec<usize> = (0..self.ids.len()).filter(|&n| in_degree[n] == 0).collect();
+        let mut removed = 0;
+        while let Some(n) = ready.pop() {
+            removed += 1;
+            for &e in &self.outgoing[n] {
+                let target = self.index_of[&self.edges[e].target];
+                in_degree[target] -= 1;
+                if in_degree[target] == 0 {
+                    ready.push(target);
+                }
+            }
+        }
+        removed < self.ids.len()
+    }
+
+    /// Weakly connected components, ignoring edge direction.
+    ///
+    /// Each component is sorted, and components are ordered by their first
+    /// ID. An isolated node is a component of its own.
+    #[must_use]
+    pub fn connected_components(&self) -> Vec<Vec<String>> {
+        let mut parent: Vec<usize> = (0..self.ids.len()).collect();
+        for edge in &self.edges {
+            let a = find(&mut parent, self.index_of[&edge.source]);
+            let b = find(&mut parent, self.index_of[&edge.target]);
+            if a != b {
+                parent[a.max(b)] = a.min(b);
+            }
+        }
+
+        let mut groups: HashMap<usize, Vec<String>> = HashMap::new();
+        for (n, id) in self.ids.iter().enumerate() {
+            let root = find(&mut parent, n);
+            groups.entry(root).or_default().push(id.clone());
+        }
+        let mut components: Vec<Vec<String>> = groups
+            .into_values()
+            .map(|mut ids| {
+                ids.sort();
+                ids
+            })
+            .collect();
+        components.sort();
+        components
+    }
+
+    /// Fewest-hop path from `from` to `to` following edge direction (BFS),
+    /// including both ends.
+    ///
+    /// A node's path to itself is just that node. Returns `None` if either
+    /// node is unknown or `to` cannot be reached.
+    #[must_use]
+    pub fn shortest_path(&self, from: &str, to: &str) -> Option<Vec<String>> {
+        let start = *self.index_of.get(from)?;
+        let goal = *self.index_of.get(to)?;
+        let mut previous: HashMap<usize, usize> = HashMap::new();
+        let mut visited: HashSet<usize> = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(n) = queue.pop_front() {
+            if n == goal {
+                let mut path = vec![self.ids[n].clone()];
+                let mut current = n;
+                while let Some(&prev) = previous.get(&current) {
+                    path.push(self.ids[prev].clone());
+                    current = prev;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for &e in &self.outgoing[n] {
+                let target = self.index_of[&self.edges[e].target];
+                if visited.insert(target) {
+                    previous.insert(target, n);
+                    queue.push_back(target);
+                }
+            }
+        }
+        None
+    }
+
+    /// Nodes reachable from `id` following edge direction, within
+    /// `max_hops` hops if given.
+    ///
+    /// `id` itself is excluded unless a cycle leads back to it within the
+    /// hop limit. An unknown `id` reaches nothing.
+    #[must_use]
+    pub fn reachable_from(&self, id: &str, max_hops: Option<u32>) -> HashSet<String> {
+        let mut reached = HashSet::new();
+        let Some(&start) = self.index_of.get(id) else {
+            return reached;
+        };
+        let mut visited: HashSet<usize> = HashSet::new();
+        let mut queue = VecDeque::from([(start, 0u32)]);
+        while let Some((n, hops)) = queue.pop_front() {
+            if max_hops.is_some_and(|max| hops >= max) {
+                continue;
+            }
+            for &e in &self.outgoing[n] {
+                let target = self.index_of[&self.edges[e].target];
+                if visited.insert(target) {
+                    reached.insert(self.ids[target].clone());
+                    queue.push_back((target, hops + 1));
+                }
+            }
+        }
+        reached
+    }
+}
+
+/// Union-find root of `n`, halving the path on the way.
+fn find(parent: &mut [usize], mut n: usize) -> usize {
+    while parent[n] != n {
+        parent[n] = parent[parent[n]];
+        n = parent[n];
+    }
+    n
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn edge(source: &str, target: &str) -> LinkEdge {
+        LinkEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            rel: "related".to_string(),
+            observed_at: "2025-02-10T00:00:00+00:00".to_string(),
+        }
+    }
+
+    fn graph(edges: &[(&str, &str)]) -> LinkGraph {
+        LinkGraph::from_edges(edges.iter().map(|(s, t)| edge(s, t)))
+    }
+
+    fn ids(ids: &[&str]) -> HashSet<String> {
+        ids.iter().map(|id| (*id).to_string()).collect()
+    }
+
+    #[test]
+    fn cycle_is_detected() {
+        let acyclic = graph(&[("a", "b"), ("b", "c"), ("a", "c")]);
+        assert!(!acyclic.has_cycle());
+
+        let cyclic = graph(&[("a", "b"), ("b", "c"), ("c", "a"), ("c", "d")]);
+        assert!(cyclic.has_cycle());
+
+        assert!(graph(&[("a", "a")]).has_cycle());
+    }
+
+    #[test]
+    fn disconnected_components_are_counted() {
+        let mut g = graph(&[("a", "b"), ("c", "b"), ("d", "e")]);
+        g.add_node("f");
+        assert_eq!(
+            g.connected_components(),
+            vec![
+                vec!["a".to_string(), "b".to_string(), "c".to_string()],
+                vec!["d".to_string(), "e".to_string()],
+                vec!["f".to_string()],
+            ]
+        );
+        assert!(LinkGraph::new().connected_components().is_empty());
+    }
+
+    #[test]
+    fn shortest_path_follows_direction() {
+        let g = graph(&[("a", "b"), ("b", "c"), ("c", "d"), ("a", "d"), ("x", "y")]);
+        assert_eq!(
+            g.shortest_path("a", "d"),
+            Some(vec!["a".to_string(), "d".to_string()])
+        );
+        assert_eq!(
+            g.shortest_path("b", "d"),
+            Some(vec!["b".to_string(), "c".to_string(), "d".to_string()])
+        );
+        assert_eq!(g.shortest_path("a", "a"), Some(vec!["a".to_string()]));
+        assert_eq!(g.shortest_path("d", "a"), None);
+        assert_eq!(g.shortest_path("a", "y"), None);
+        assert_eq!(g.shortest_path("a", "missing"), None);
+    }
+
+    #[test]
+    fn reachable_from_respects_hop_limit() {
+        let g = graph(&[("a", "b"), ("b", "c"), ("c", "a"), ("c", "d")]);
+        assert_eq!(g.reachable_from("a", Some(1)), ids(&["b"]));
+        assert_eq!(g.reachable_from("a", Some(2)), ids(&["b", "c"]));
+        assert_eq!(g.reachable_from("a", None), ids(&["a", "b", "c", "d"]));
+        assert!(g.reachable_from("d", None).is_empty());
+        assert!(g.reachable_from("missing", None).is_empty());
+    }
+
+    #[test]
+    fn adjacency_keeps_parallel_edges() {
+        let mut g = graph(&[("a", "b")]);
+        g.add_edge(LinkEdge {
+            rel: "owner".to_string(),
+            ..edge("a", "b")
+        });
+        assert_eq!(g.node_count(), 2);
+        assert_eq!(g.edge_count(), 2);
+        let rels: Vec<&str> = g.outgoing("a").map(|e| e.rel.as_str()).collect();
+        assert_eq!(rels, vec!["related", "owner"]);
+        assert_eq!(g.incoming("b").count(), 2);
+        assert_eq!(g.outgoing("missing").count(), 0);
+    }
+
+    #[test]
+    fn extract_wikilinks_handles_headings_labels_and_embeds() {
+        let body = "[[A]] [[B#Intro]] [[C|label]] ![[D]] ![[img.png]] [[E.md]] [[]]";
+        assert_eq!(extract_wikilinks(body), ["A", "B", "C", "D", "E"]);
+    }
+
+    #[test]
+    fn document_links_adds_body_wikilinks_once() {
+        use crate::temporal::{DecayProfile, RawTemporalInput};
+        use chrono::TimeZone;
+
+        let observed_at = Utc.with_ymd_and_hms(2025, 2, 10, 0, 0, 0).unwrap();
+        let input = RawTemporalInput {
+            observed_at: Some(observed_at),
+            ..RawTemporalInput::default()
+        };
+        let mut doc = Document::new(
+            "proj-alpha-001".to_string(),
+            "project".to_string(),
+            "Alpha".to_string(),
+            input,
+            &DecayProfile::default_profile(),
+        )
+        .unwrap();
+        doc.body =
+            "See [[proj-beta-001]], [[pers-jane-001|Jane]] and [[proj-beta-001]].".to_string();
+        doc.links.push(Link {
+            rel: "owner".to_string(),
+            target: "pers-jane-001".to_string(),
+            observed_at,
+            metadata: None,
+        });
+
+        let links = document_links(&doc);
+        let pairs: Vec<(&str, &str)> = links
+            .iter()
+            .map(|l| (l.rel.as_str(), l.target.as_str()))
+            .collect();
+        assert_eq!(
+            pairs,
+            [
+                ("owner", "pers-jane-001"),
+                (WIKILINK_REL, "proj-beta-001"),
+                (WIKILINK_REL, "pers-jane-001"),
+            ]
+        );
+        assert!(links.iter().all(|l| l.observed_at == observed_at));
+    }
+
     #[test]
     fn link_serialization_roundtrip() {
         let link = Link {
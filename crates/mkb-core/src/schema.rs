@@ -1,9 +1,11 @@
 //! Schema definition types and validation engine for MKB document type contracts.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use crate::error::SchemaError;
+use crate::document::Document;
+use crate::error::{MkbError, SchemaError};
+use crate::link::Link;
 
 /// A schema definition that describes the frontmatter contract for a document type.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,12 +21,29 @@ pub struct SchemaDefinition {
     pub fields: HashMap<String, FieldDef>,
     #[serde(default)]
     pub validation: Vec<ValidationRule>,
+    /// Constraints on outgoing links, keyed by `rel`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub link_constraints: HashMap<String, LinkConstraint>,
 }
 
 fn default_version() -> u32 {
     1
 }
 
+/// What a schema allows for outgoing links with one `rel`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkConstraint {
+    /// Document type every target must have.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_type: Option<String>,
+    /// Whether a document should have at least one such link.
+    #[serde(default)]
+    pub required: bool,
+    /// Most links with this `rel` a document may have.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_count: Option<u32>,
+}
+
 /// Definition of a single field in a schema.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FieldDef {
@@ -48,6 +67,65 @@ pub struct FieldDef {
     pub ref_type: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Bounds and pattern a present value must satisfy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub constraints: Option<FieldConstraints>,
+}
+
+/// Value constraints on a [`FieldDef`], checked after its type.
+///
+/// `min`/`max` bound `integer` and `float` values, inclusive.
+/// `min_length`/`max_length` bound `string` values in characters, and
+/// `pattern` is a regex every string value must match somewhere; anchor it
+/// with `^...$` to match the whole value.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FieldConstraints {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+}
+
+impl FieldConstraints {
+    /// Describe every way `value` breaks these constraints.
+    fn violations(&self, field_type: &FieldType, value: &serde_json::Value) -> Vec<String> {
+        let mut violations = Vec::new();
+        if matches!(field_type, FieldType::Integer | FieldType::Float) {
+            if let Some(n) = value.as_f64() {
+                if let Some(min) = self.min.filter(|min| n < *min) {
+                    violations.push(format!("{n} is less than the minimum {min}"));
+                }
+                if let Some(max) = self.max.filter(|max| n > *max) {
+                    violations.push(format!("{n} is greater than the maximum {max}"));
+                }
+            }
+        }
+        if let Some(text) = value.as_str() {
+            if *field_type == FieldType::String {
+                let length = text.chars().count();
+                if let Some(min) = self.min_length.filter(|min| length < *min) {
+                    violations.push(format!("length {length} is less than the minimum {min}"));
+                }
+                if let Some(max) = self.max_length.filter(|max| length > *max) {
+                    violations.push(format!("length {length} is greater than the maximum {max}"));
+                }
+            }
+            if let Some(ref pattern) = self.pattern {
+                match regex::Regex::new(pattern) {
+                    Ok(re) if re.is_match(text) => {}
+                    Ok(_) => violations.push(format!("'{text}' does not match /{pattern}/")),
+                    Err(e) => violations.push(format!("invalid pattern /{pattern}/: {e}")),
+                }
+            }
+        }
+        violations
+    }
 }
 
 /// Supported field types in MKB schemas.
@@ -107,6 +185,23 @@ impl ValidationResult {
     pub fn is_valid(&self) -> bool {
         self.errors.is_empty()
     }
+
+    /// The warnings if validation passed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::SchemaViolation`] carrying every error if any were
+    /// found.
+    pub fn into_result(self, doc_type: &str) -> Result<Vec<String>, MkbError> {
+        if self.errors.is_empty() {
+            Ok(self.warnings)
+        } else {
+            Err(MkbError::SchemaViolation {
+                doc_type: doc_type.to_string(),
+                errors: self.errors,
+            })
+        }
+    }
 }
 
 impl SchemaDefinition {
@@ -116,6 +211,7 @@ impl SchemaDefinition {
     /// - All required fields are present
     /// - Field types match (basic type checking on JSON values)
     /// - Enum values are in the allowed set
+    /// - Correctly typed values satisfy the field's [`FieldConstraints`]
     pub fn validate(
         &self,
         doc_type: &str,
@@ -135,6 +231,16 @@ impl SchemaDefinition {
                     // Type check
                     if let Some(err) = check_field_type(field_name, &field_def.field_type, value) {
                         result.errors.push(err);
+                    } else if let Some(ref constraints) = field_def.constraints {
+                        result.errors.extend(
+                            constraints
+                                .violations(&field_def.field_type, value)
+                                .into_iter()
+                                .map(|message| SchemaError::ConstraintViolation {
+                                    field: field_name.clone(),
+                                    message,
+                                }),
+                        );
                     }
 
                     // Enum value check
@@ -158,6 +264,304 @@ impl SchemaDefinition {
 
         result
     }
+
+    /// Validate a document's complete set of outgoing links against
+    /// [`link_constraints`](Self::link_constraints).
+    ///
+    /// `target_type` maps a target ID to its document type, or `None` if the
+    /// target is unknown. Wrong target types and exceeded `max_count`s are
+    /// errors. Missing required links and targets whose type is unknown are
+    /// warnings, since links are often added one at a time.
+    pub fn validate_links(
+        &self,
+        links: &[Link],
+        target_type: impl Fn(&str) -> Option<String>,
+    ) -> ValidationResult {
+        let mut result = ValidationResult::default();
+
+        let mut rels: Vec<(&String, &LinkConstraint)> = self.link_constraints.iter().collect();
+        rels.sort_by_key(|(rel, _)| *rel);
+        for (rel, constraint) in rels {
+            let matching: Vec<&Link> = links.iter().filter(|l| &l.rel == rel).collect();
+
+            if constraint.required && matching.is_empty() {
+                result
+                    .warnings
+                    .push(format!("missing required link '{rel}'"));
+            }
+            if let Some(max) = constraint.max_count {
+                if matching.len() > max as usize {
+                    result.errors.push(SchemaError::TooManyLinks {
+                        rel: rel.clone(),
+                        max,
+                        count: matching.len(),
+                    });
+                }
+            }
+            let Some(ref expected) = constraint.target_type else {
+                continue;
+            };
+            for link in matching {
+                match target_type(&link.target) {
+                    Some(actual) if &actual != expected => {
+                        result.errors.push(SchemaError::LinkTargetType {
+                            rel: rel.clone(),
+                            target: link.target.clone(),
+                            expected: expected.clone(),
+                            actual,
+                        });
+                    }
+                    Some(_) => {}
+                    None => result.warnings.push(format!(
+                        "link '{rel}' target '{}' is not indexed, so its type was not checked",
+                        link.target
+                    )),
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Merge inherited fields and rules into this schema by following
+    /// `extends` through `schemas`.
+    ///
+    /// Fields defined closer to this schema win on name collision.
+    /// Validation rules are this schema's own, followed by each ancestor's.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SchemaError::MissingSchema`] if a parent is not in
+    /// `schemas`, or [`SchemaError::CircularInheritance`] if the chain
+    /// loops back on itself.
+    pub fn resolve(&self, schemas: &[SchemaDefinition]) -> Result<SchemaDefinition, SchemaError> {
+        let mut resolved = self.clone();
+        let mut chain = vec![self.name.clone()];
+        let mut current = self;
+
+        while let Some(ref parent_name) = current.extends {
+            if chain.contains(parent_name) {
+                chain.push(parent_name.clone());
+                return Err(SchemaError::CircularInheritance { chain });
+            }
+            let parent = schemas
+                .iter()
+                .find(|s| &s.name == parent_name)
+                .ok_or_else(|| SchemaError::MissingSchema {
+                    schema: current.name.clone(),
+                    parent: parent_name.clone(),
+                })?;
+
+            for (name, def) in &parent.fields {
+                resolved
+                    .fields
+                    .entry(name.clone())
+                    .or_insert_with(|| def.clone());
+            }
+            for (rel, constraint) in &parent.link_constraints {
+                resolved
+                    .link_constraints
+                    .entry(rel.clone())
+                    .or_insert_with(|| constraint.clone());
+            }
+            resolved
+                .validation
+                .extend(parent.validation.iter().cloned());
+
+            chain.push(parent_name.clone());
+            current = parent;
+        }
+
+        Ok(resolved)
+    }
+
+    /// Export this schema as a JSON Schema (draft 2020-12) document.
+    ///
+    /// The result describes a document's `fields` map, mirroring what
+    /// [`SchemaDefinition::validate`] checks: property types, enum values
+    /// and required fields. Custom validation rules are not exported.
+    #[must_use]
+    pub fn to_json_schema(&self) -> serde_json::Value {
+        let properties: serde_json::Map<String, serde_json::Value> = self
+            .fields
+            .iter()
+            .map(|(name, def)| (name.clone(), field_json_schema(def)))
+            .collect();
+
+        let mut required: Vec<&str> = self
+            .fields
+            .iter()
+            .filter(|(_, def)| def.required)
+            .map(|(name, _)| name.as_str())
+            .collect();
+        required.sort_unstable();
+
+        let mut schema = serde_json::json!({
+            "$schema": JSON_SCHEMA_DRAFT,
+            "$id": format!("urn:mkb:schema:{}", self.name),
+            "title": self.name,
+            "type": "object",
+            "properties": properties,
+            "required": required,
+        });
+        if let Some(ref description) = self.description {
+            schema["description"] = serde_json::json!(description);
+        }
+        schema
+    }
+}
+
+/// Share of sampled documents that must have a field for
+/// [`SchemaDefinition::infer_from_documents`] to mark it required.
+pub const INFER_REQUIRED_SHARE: f64 = 0.8;
+
+/// Inferred string fields with fewer distinct values than this become enums.
+pub const INFER_ENUM_MAX_VALUES: usize = 20;
+
+impl SchemaDefinition {
+    /// Infer a schema for `doc_type` from the `fields` of sample documents.
+    ///
+    /// Each field's type follows its JSON values: strings, integers, floats,
+    /// booleans, arrays (as `string[]`) and objects (as `map`). Integers mixed
+    /// with floats make `float`; any other mix makes `json`. A field is
+    /// required when more than [`INFER_REQUIRED_SHARE`] of the samples have a
+    /// non-null value for it. A string field whose values repeat, with fewer
+    /// than [`INFER_ENUM_MAX_VALUES`] distinct ones, becomes an enum of them.
+    #[must_use]
+    pub fn infer_from_documents(doc_type: &str, docs: &[Document]) -> SchemaDefinition {
+        let mut samples: BTreeMap<&str, Vec<&serde_json::Value>> = BTreeMap::new();
+        for doc in docs {
+            for (name, value) in &doc.fields {
+                if !value.is_null() {
+                    samples.entry(name).or_default().push(value);
+                }
+            }
+        }
+
+        let fields = samples
+            .into_iter()
+            .map(|(name, values)| (name.to_string(), infer_field(&values, docs.len())))
+            .collect();
+        SchemaDefinition {
+            name: doc_type.to_string(),
+            version: default_version(),
+            extends: None,
+            description: Some(format!("Inferred from {} documents", docs.len())),
+            fields,
+            validation: Vec::new(),
+            link_constraints: HashMap::new(),
+        }
+    }
+}
+
+/// Infer one field from its non-null `values` across `total` documents.
+fn infer_field(values: &[&serde_json::Value], total: usize) -> FieldDef {
+    let value_type = |value: &serde_json::Value| match value {
+        serde_json::Value::Bool(_) => FieldType::Boolean,
+        serde_json::Value::Number(n) if n.is_f64() => FieldType::Float,
+        serde_json::Value::Number(_) => FieldType::Integer,
+        serde_json::Value::Array(_) => FieldType::StringArray,
+        serde_json::Value::Object(_) => FieldType::Map,
+        _ => FieldType::String,
+    };
+    let mut field_type = value_type(values[0]);
+    for value in &values[1..] {
+        field_type = match (field_type, value_type(value)) {
+            (a, b) if a == b => a,
+            (FieldType::Integer | FieldType::Float, FieldType::Integer | FieldType::Float) => {
+                FieldType::Float
+            }
+            _ => FieldType::Json,
+        };
+    }
+
+    let mut enum_values = None;
+    if field_type == FieldType::String {
+        let distinct: std::collections::BTreeSet<&str> =
+            values.iter().filter_map(|v| v.as_str()).collect();
+        if distinct.len() < INFER_ENUM_MAX_VALUES && distinct.len() < values.len() {
+            field_type = FieldType::Enum;
+            enum_values = Some(distinct.into_iter().map(str::to_string).collect());
+        }
+    }
+
+    FieldDef {
+        field_type,
+        required: values.len() as f64 > total as f64 * INFER_REQUIRED_SHARE,
+        indexed: false,
+        searchable: false,
+        unique: false,
+        default: None,
+        values: enum_values,
+        ref_type: None,
+        description: None,
+        constraints: None,
+    }
+}
+
+/// Meta-schema URI emitted by [`SchemaDefinition::to_json_schema`].
+pub const JSON_SCHEMA_DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Map a single field definition to its JSON Schema property.
+fn field_json_schema(def: &FieldDef) -> serde_json::Value {
+    let mut prop = match def.field_type {
+        FieldType::String | FieldType::Duration | FieldType::Ref => {
+            serde_json::json!({"type": "string"})
+        }
+        FieldType::Date => serde_json::json!({"type": "string", "format": "date"}),
+        FieldType::Datetime => serde_json::json!({"type": "string", "format": "date-time"}),
+        FieldType::Integer => serde_json::json!({"type": "integer"}),
+        FieldType::Float => serde_json::json!({"type": "number"}),
+        FieldType::Boolean => serde_json::json!({"type": "boolean"}),
+        FieldType::Enum => match def.values {
+            Some(ref values) => serde_json::json!({"type": "string", "enum": values}),
+            None => serde_json::json!({"type": "string"}),
+        },
+        FieldType::RefArray | FieldType::StringArray => {
+            serde_json::json!({"type": "array", "items": {"type": "string"}})
+        }
+        FieldType::Map | FieldType::Json => serde_json::json!({"type": "object"}),
+    };
+
+    if let Some(ref description) = def.description {
+        prop["description"] = serde_json::json!(description);
+    }
+    if let Some(ref default) = def.default {
+        prop["default"] = default.clone();
+    }
+    if let Some(ref c) = def.constraints {
+        let numeric = matches!(def.field_type, FieldType::Integer | FieldType::Float);
+        let string = def.field_type == FieldType::String;
+        let keywords = [
+            (
+                "minimum",
+                c.min.filter(|_| numeric).map(|v| serde_json::json!(v)),
+            ),
+            (
+                "maximum",
+                c.max.filter(|_| numeric).map(|v| serde_json::json!(v)),
+            ),
+            (
+                "minLength",
+                c.min_length
+                    .filter(|_| string)
+                    .map(|v| serde_json::json!(v)),
+            ),
+            (
+                "maxLength",
+                c.max_length
+                    .filter(|_| string)
+                    .map(|v| serde_json::json!(v)),
+            ),
+            ("pattern", c.pattern.as_ref().map(|v| serde_json::json!(v))),
+        ];
+        for (keyword, value) in keywords {
+            if let Some(value) = value {
+                prop[keyword] = value;
+            }
+        }
+    }
+    prop
 }
 
 /// Check if a JSON value matches the expected field type.
@@ -201,14 +605,223 @@ fn json_type_name(value: &serde_json::Value) -> &'static str {
     }
 }
 
-/// Built-in schemas for common document types.
+/// Built-in and user schemas keyed by type name, with their versions.
+///
+/// User schemas replace built-ins of the same name, matching
+/// `Vault::load_schemas`.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaRegistry {
+    schemas: BTreeMap<String, SchemaDefinition>,
+}
+
+/// How disruptive a [`BreakingChange`] is for existing documents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BreakingChangeSeverity {
+    /// Existing documents may fail validation and need manual edits.
+    Fatal,
+    /// Existing documents stay valid or can be fixed by a [`SchemaMigration`].
+    Warning,
+}
+
+/// What changed about a field between two schema versions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BreakingChangeKind {
+    /// The field is required now but was optional or absent before.
+    NewRequiredField,
+    /// The field no longer exists in the schema.
+    RemovedField,
+    /// The field's type changed.
+    TypeChanged { from: FieldType, to: FieldType },
+    /// Values were dropped from an enum field's allowed set.
+    EnumValuesRemoved { values: Vec<String> },
+}
+
+/// A change between two versions of a schema that affects existing documents.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BreakingChange {
+    pub field: String,
+    #[serde(flatten)]
+    pub kind: BreakingChangeKind,
+    pub severity: BreakingChangeSeverity,
+}
+
+/// Bulk edit applied to every document of one type after a schema change.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SchemaMigration {
+    pub doc_type: String,
+    /// Values written to documents that lack the field; present values are
+    /// left alone.
+    #[serde(default)]
+    pub set_defaults: BTreeMap<String, serde_json::Value>,
+}
+
+/// Outcome of applying a [`SchemaMigration`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MigrationReport {
+    /// Documents of the migrated type that were examined.
+    pub scanned: usize,
+    /// IDs of documents that were rewritten.
+    pub updated: Vec<String>,
+}
+
+impl SchemaRegistry {
+    /// Build a registry from the built-in schemas plus `user` schemas.
+    #[must_use]
+    pub fn new(user: Vec<SchemaDefinition>) -> Self {
+        let mut schemas: BTreeMap<String, SchemaDefinition> = built_in_schemas()
+            .into_iter()
+            .map(|s| (s.name.clone(), s))
+            .collect();
+        schemas.extend(user.into_iter().map(|s| (s.name.clone(), s)));
+        Self { schemas }
+    }
+
+    /// Look up a schema by type name.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&SchemaDefinition> {
+        self.schemas.get(name)
+    }
+
+    /// The registered version of a schema, if the type is known.
+    #[must_use]
+    pub fn version(&self, name: &str) -> Option<u32> {
+        self.schemas.get(name).map(|s| s.version)
+    }
+
+    /// Registered schemas in name order.
+    pub fn iter(&self) -> impl Iterator<Item = &SchemaDefinition> {
+        self.schemas.values()
+    }
+
+    /// Compare `candidate` with the registered schema of the same name.
+    ///
+    /// Returns `None` for a type the registry does not know.
+    #[must_use]
+    pub fn changes_for(&self, candidate: &SchemaDefinition) -> Option<Vec<BreakingChange>> {
+        self.get(&candidate.name)
+            .map(|current| Self::detect_breaking_changes(current, candidate))
+    }
+
+    /// List the changes from `old` to `new` that affect existing documents,
+    /// sorted by field name.
+    ///
+    /// A newly required field is only a warning when it has a default, since
+    /// [`SchemaMigration::fill_defaults`] can backfill it. Removed fields are
+    /// warnings because leftover values no longer fail validation. Type
+    /// changes and removed enum values are fatal.
+    #[must_use]
+    pub fn detect_breaking_changes(
+        old: &SchemaDefinition,
+        new: &SchemaDefinition,
+    ) -> Vec<BreakingChange> {
+        let mut changes = Vec::new();
+
+        for (field, new_def) in &new.fields {
+            let old_def = old.fields.get(field);
+
+            if new_def.required && !old_def.is_some_and(|d| d.required) {
+                let severity = if new_def.default.is_some() {
+                    BreakingChangeSeverity::Warning
+                } else {
+                    BreakingChangeSeverity::Fatal
+                };
+                changes.push(BreakingChange {
+                    field: field.clone(),
+                    kind: BreakingChangeKind::NewRequiredField,
+                    severity,
+                });
+            }
+
+            let Some(old_def) = old_def else { continue };
+            if old_def.field_type != new_def.field_type {
+                changes.push(BreakingChange {
+                    field: field.clone(),
+                    kind: BreakingChangeKind::TypeChanged {
+                        from: old_def.field_type.clone(),
+                        to: new_def.field_type.clone(),
+                    },
+                    severity: BreakingChangeSeverity::Fatal,
+                });
+            } else if let (Some(old_values), Some(new_values)) = (&old_def.values, &new_def.values)
+            {
+                let removed: Vec<String> = old_values
+                    .iter()
+                    .filter(|v| !new_values.contains(v))
+                    .cloned()
+                    .collect();
+                if !removed.is_empty() {
+                    changes.push(BreakingChange {
+                        field: field.clone(),
+                        kind: BreakingChangeKind::EnumValuesRemoved { values: removed },
+                        severity: BreakingChangeSeverity::Fatal,
+                    });
+                }
+            }
+        }
+
+        for field in old.fields.keys() {
+            if !new.fields.contains_key(field) {
+                changes.push(BreakingChange {
+                    field: field.clone(),
+                    kind: BreakingChangeKind::RemovedField,
+                    severity: BreakingChangeSeverity::Warning,
+                });
+            }
+        }
+
+        changes.sort_by(|a, b| a.field.cmp(&b.field));
+        changes
+    }
+}
+
+impl SchemaMigration {
+    /// Backfill the defaults of fields that `new` requires and `old` did not.
+    ///
+    /// Newly required fields without a default are skipped; they show up as
+    /// fatal in [`SchemaRegistry::detect_breaking_changes`].
+    #[must_use]
+    pub fn fill_defaults(old: &SchemaDefinition, new: &SchemaDefinition) -> Self {
+        let set_defaults = new
+            .fields
+            .iter()
+            .filter(|(name, def)| {
+                def.required && !old.fields.get(*name).is_some_and(|d| d.required)
+            })
+            .filter_map(|(name, def)| def.default.clone().map(|v| (name.clone(), v)))
+            .collect();
+        Self {
+            doc_type: new.name.clone(),
+            set_defaults,
+        }
+    }
+
+    /// Add the missing defaults to `fields`. Returns whether anything changed.
+    pub fn apply(&self, fields: &mut HashMap<String, serde_json::Value>) -> bool {
+        let mut changed = false;
+        for (name, value) in &self.set_defaults {
+            if !fields.contains_key(name) {
+                fields.insert(name.clone(), value.clone());
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+/// Built-in schemas for common document types, with inheritance resolved.
 pub fn built_in_schemas() -> Vec<SchemaDefinition> {
-    vec![
+    let declared = vec![
         project_schema(),
         meeting_schema(),
         decision_schema(),
         signal_schema(),
-    ]
+    ];
+    declared
+        .iter()
+        .map(|s| s.resolve(&declared).expect("built-in schemas resolve"))
+        .collect()
 }
 
 /// Schema for "project" documents.
@@ -232,6 +845,7 @@ pub fn project_schema() -> SchemaDefinition {
             ]),
             ref_type: None,
             description: Some("Project status".to_string()),
+            constraints: None,
         },
     );
     fields.insert(
@@ -246,6 +860,36 @@ pub fn project_schema() -> SchemaDefinition {
             values: None,
             ref_type: Some("person".to_string()),
             description: Some("Project owner".to_string()),
+            constraints: None,
+        },
+    );
+    fields.insert(
+        "confidence".to_string(),
+        FieldDef {
+            field_type: FieldType::Float,
+            required: false,
+            indexed: false,
+            searchable: false,
+            unique: false,
+            default: None,
+            values: None,
+            ref_type: None,
+            description: Some("How far the project record can be trusted".to_string()),
+            constraints: Some(FieldConstraints {
+                min: Some(0.0),
+                max: Some(1.0),
+                ..FieldConstraints::default()
+            }),
+        },
+    );
+
+    let mut link_constraints = HashMap::new();
+    link_constraints.insert(
+        "owner".to_string(),
+        LinkConstraint {
+            target_type: Some("person".to_string()),
+            required: false,
+            max_count: Some(1),
         },
     );
 
@@ -256,6 +900,7 @@ pub fn project_schema() -> SchemaDefinition {
         description: Some("A project being tracked".to_string()),
         fields,
         validation: vec![],
+        link_constraints,
     }
 }
 
@@ -275,6 +920,7 @@ pub fn meeting_schema() -> SchemaDefinition {
             values: None,
             ref_type: None,
             description: Some("Meeting attendees".to_string()),
+            constraints: None,
         },
     );
 
@@ -285,10 +931,12 @@ pub fn meeting_schema() -> SchemaDefinition {
         description: Some("A meeting or discussion".to_string()),
         fields,
         validation: vec![],
+        link_constraints: HashMap::new(),
     }
 }
 
-/// Schema for "decision" documents.
+/// Schema for "decision" documents. Extends "project", so a resolved
+/// decision also carries `status` and `owner`.
 #[must_use]
 pub fn decision_schema() -> SchemaDefinition {
     let mut fields = HashMap::new();
@@ -304,6 +952,7 @@ pub fn decision_schema() -> SchemaDefinition {
             values: None,
             ref_type: None,
             description: Some("The decision that was made".to_string()),
+            constraints: None,
         },
     );
     fields.insert(
@@ -318,16 +967,18 @@ pub fn decision_schema() -> SchemaDefinition {
             values: None,
             ref_type: None,
             description: Some("Why this decision was made".to_string()),
+            constraints: None,
         },
     );
 
     SchemaDefinition {
         name: "decision".to_string(),
         version: 1,
-        extends: None,
+        extends: Some("project".to_string()),
         description: Some("A decision record".to_string()),
         fields,
         validation: vec![],
+        link_constraints: HashMap::new(),
     }
 }
 
@@ -351,6 +1002,7 @@ pub fn signal_schema() -> SchemaDefinition {
             ]),
             ref_type: None,
             description: Some("Signal sentiment".to_string()),
+            constraints: None,
         },
     );
 
@@ -361,6 +1013,7 @@ pub fn signal_schema() -> SchemaDefinition {
         description: Some("A signal or observation".to_string()),
         fields,
         validation: vec![],
+        link_constraints: HashMap::new(),
     }
 }
 
@@ -480,4 +1133,587 @@ mod tests {
         let result = schema.validate("decision", &fields);
         assert!(!result.is_valid());
     }
+
+    #[test]
+    fn validate_enforces_numeric_bounds() {
+        let schema = project_schema();
+        for (confidence, valid) in [(1.5, false), (-0.1, false), (0.0, true), (1.0, true)] {
+            let mut fields = HashMap::new();
+            fields.insert("status".to_string(), serde_json::json!("active"));
+            fields.insert("confidence".to_string(), serde_json::json!(confidence));
+
+            let result = schema.validate("project", &fields);
+            assert_eq!(result.is_valid(), valid, "confidence {confidence}");
+            if !valid {
+                assert!(matches!(
+                    &result.errors[..],
+                    [SchemaError::ConstraintViolation { field, .. }] if field == "confidence"
+                ));
+            }
+        }
+    }
+
+    fn constrained(constraints: FieldConstraints) -> SchemaDefinition {
+        let mut schema = inheriting("note", None, &[("title", FieldType::String)]);
+        schema.fields.get_mut("title").unwrap().constraints = Some(constraints);
+        schema
+    }
+
+    fn title_errors(schema: &SchemaDefinition, title: &str) -> Vec<SchemaError> {
+        let mut fields = HashMap::new();
+        fields.insert("title".to_string(), serde_json::json!(title));
+        schema.validate("note", &fields).errors
+    }
+
+    #[test]
+    fn validate_enforces_pattern() {
+        let schema = constrained(FieldConstraints {
+            pattern: Some("^[a-z]+-[a-z0-9-]+-[0-9]+$".to_string()),
+            ..FieldConstraints::default()
+        });
+
+        assert!(title_errors(&schema, "proj-alpha-001").is_empty());
+        let errors = title_errors(&schema, "Alpha Project");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].to_string().contains("does not match"));
+    }
+
+    #[test]
+    fn validate_enforces_length_bounds() {
+        let schema = constrained(FieldConstraints {
+            min_length: Some(2),
+            max_length: Some(4),
+            ..FieldConstraints::default()
+        });
+
+        assert!(title_errors(&schema, "café").is_empty());
+        assert_eq!(title_errors(&schema, "a").len(), 1);
+        assert_eq!(title_errors(&schema, "abcde").len(), 1);
+    }
+
+    #[test]
+    fn validate_reports_invalid_pattern() {
+        let schema = constrained(FieldConstraints {
+            pattern: Some("(".to_string()),
+            ..FieldConstraints::default()
+        });
+        let errors = title_errors(&schema, "anything");
+        assert!(errors[0].to_string().contains("invalid pattern"));
+    }
+
+    #[test]
+    fn field_constraints_parse_from_yaml() {
+        let schema: SchemaDefinition = serde_yaml::from_str(
+            "name: note\nfields:\n  score:\n    type: integer\n    constraints:\n      min: 1\n      max: 5\n",
+        )
+        .unwrap();
+        let constraints = schema.fields["score"].constraints.as_ref().unwrap();
+        assert_eq!(constraints.min, Some(1.0));
+        assert_eq!(constraints.max, Some(5.0));
+        assert_eq!(constraints.pattern, None);
+    }
+
+    #[test]
+    fn to_json_schema_maps_field_types() {
+        let schema = project_schema().to_json_schema();
+
+        assert_eq!(schema["$schema"], JSON_SCHEMA_DRAFT);
+        assert_eq!(schema["$id"], "urn:mkb:schema:project");
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["required"], serde_json::json!(["status"]));
+        assert_eq!(
+            schema["properties"]["status"]["enum"],
+            serde_json::json!(["active", "paused", "completed", "cancelled"])
+        );
+        assert_eq!(schema["properties"]["status"]["default"], "active");
+        assert_eq!(schema["properties"]["owner"]["type"], "string");
+        assert_eq!(schema["properties"]["confidence"]["minimum"], 0.0);
+        assert_eq!(schema["properties"]["confidence"]["maximum"], 1.0);
+
+        let attendees = &meeting_schema().to_json_schema()["properties"]["attendees"];
+        assert_eq!(
+            *attendees,
+            serde_json::json!({
+                "type": "array",
+                "items": {"type": "string"},
+                "description": "Meeting attendees",
+            })
+        );
+    }
+
+    #[test]
+    fn json_schema_is_valid_draft_2020_12() {
+        for schema in built_in_schemas() {
+            let json_schema = schema.to_json_schema();
+            assert!(
+                jsonschema::draft202012::meta::is_valid(&json_schema),
+                "{} schema is not valid draft 2020-12",
+                schema.name
+            );
+        }
+    }
+
+    fn sample_doc(n: usize, fields: serde_json::Value) -> Document {
+        use crate::temporal::{DecayProfile, RawTemporalInput};
+
+        let mut doc = Document::new(
+            format!("proj-sample-{n:03}"),
+            "project".to_string(),
+            format!("Sample {n}"),
+            RawTemporalInput {
+                observed_at: Some(chrono::Utc::now()),
+                ..Default::default()
+            },
+            &DecayProfile::default_profile(),
+        )
+        .unwrap();
+        doc.fields = serde_json::from_value(fields).unwrap();
+        doc
+    }
+
+    #[test]
+    fn infer_from_documents_types_and_requires_fields() {
+        let docs: Vec<Document> = (0..10)
+            .map(|n| {
+                let mut fields = serde_json::json!({
+                    "status": if n % 2 == 0 { "active" } else { "paused" },
+                    "priority": n,
+                    "score": if n == 0 { serde_json::json!(1) } else { serde_json::json!(0.5) },
+                    "labels": ["a"],
+                    "archived": false,
+                    "note": format!("note {n}"),
+                });
+                // Present in 8 of 10 documents: not more than 80%
+                if n < 8 {
+                    fields["owner"] = serde_json::json!("pers-jane-001");
+                }
+                if n == 0 {
+                    fields["extra"] = serde_json::json!("x");
+                }
+                sample_doc(n, fields)
+            })
+            .collect();
+
+        let schema = SchemaDefinition::infer_from_documents("project", &docs);
+        assert_eq!(schema.name, "project");
+        let field = |name: &str| &schema.fields[name];
+
+        assert!(field("status").required);
+        assert_eq!(field("status").field_type, FieldType::Enum);
+        assert_eq!(
+            field("status").values,
+            Some(vec!["active".to_string(), "paused".to_string()])
+        );
+        assert_eq!(field("priority").field_type, FieldType::Integer);
+        assert_eq!(field("score").field_type, FieldType::Float);
+        assert_eq!(field("labels").field_type, FieldType::StringArray);
+        assert_eq!(field("archived").field_type, FieldType::Boolean);
+        // Every value distinct: free text, not an enum
+        assert_eq!(field("note").field_type, FieldType::String);
+        assert!(field("note").values.is_none());
+        assert!(!field("owner").required);
+        assert!(!field("extra").required);
+
+        // The inferred schema accepts the documents it came from
+        for doc in &docs {
+            assert!(schema.validate("project", &doc.fields).is_valid());
+        }
+    }
+
+    #[test]
+    fn infer_from_documents_falls_back_to_json_for_mixed_types() {
+        let docs = vec![
+            sample_doc(1, serde_json::json!({"ref": "abc"})),
+            sample_doc(2, serde_json::json!({"ref": 42})),
+        ];
+        let schema = SchemaDefinition::infer_from_documents("project", &docs);
+        assert_eq!(schema.fields["ref"].field_type, FieldType::Json);
+        assert!(schema.fields["ref"].required);
+    }
+
+    #[test]
+    fn document_roundtrips_through_json_schema() {
+        use crate::document::Document;
+        use crate::frontmatter::{parse_document, write_document};
+        use crate::temporal::{DecayProfile, RawTemporalInput};
+
+        let mut doc = Document::new(
+            "proj-alpha-001".to_string(),
+            "project".to_string(),
+            "Alpha".to_string(),
+            RawTemporalInput {
+                observed_at: Some(chrono::Utc::now()),
+                ..Default::default()
+            },
+            &DecayProfile::default_profile(),
+        )
+        .unwrap();
+        doc.fields
+            .insert("status".to_string(), serde_json::json!("active"));
+        doc.fields
+            .insert("owner".to_string(), serde_json::json!("pers-jane-001"));
+
+        let reparsed = parse_document(&write_document(&doc).unwrap()).unwrap();
+        let fields = serde_json::to_value(&reparsed.fields).unwrap();
+
+        let validator = jsonschema::validator_for(&project_schema().to_json_schema()).unwrap();
+        assert!(validator.is_valid(&fields));
+        assert!(!validator.is_valid(&serde_json::json!({"status": "archived"})));
+        assert!(!validator.is_valid(&serde_json::json!({"owner": "pers-jane-001"})));
+    }
+
+    fn inheriting(
+        name: &str,
+        extends: Option<&str>,
+        fields: &[(&str, FieldType)],
+    ) -> SchemaDefinition {
+        let fields = fields
+            .iter()
+            .map(|(field, field_type)| {
+                let def = FieldDef {
+                    field_type: field_type.clone(),
+                    required: false,
+                    indexed: false,
+                    searchable: false,
+                    unique: false,
+                    default: None,
+                    values: None,
+                    ref_type: None,
+                    description: None,
+                    constraints: None,
+                };
+                ((*field).to_string(), def)
+            })
+            .collect();
+        SchemaDefinition {
+            name: name.to_string(),
+            version: 1,
+            extends: extends.map(str::to_string),
+            description: None,
+            fields,
+            validation: vec![ValidationRule {
+                rule: format!("{name}_rule"),
+                message: format!("{name} rule"),
+                severity: ValidationSeverity::Warning,
+            }],
+            link_constraints: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn resolve_child_field_overrides_parent() {
+        let parent = inheriting(
+            "base",
+            None,
+            &[("status", FieldType::String), ("owner", FieldType::Ref)],
+        );
+        let child = inheriting("task", Some("base"), &[("status", FieldType::Enum)]);
+
+        let resolved = child.resolve(&[parent, child.clone()]).unwrap();
+        assert_eq!(resolved.fields["status"].field_type, FieldType::Enum);
+        assert_eq!(resolved.fields["owner"].field_type, FieldType::Ref);
+        let rules: Vec<&str> = resolved
+            .validation
+            .iter()
+            .map(|r| r.rule.as_str())
+            .collect();
+        assert_eq!(rules, ["task_rule", "base_rule"]);
+    }
+
+    #[test]
+    fn resolve_follows_multi_level_chain() {
+        let schemas = [
+            inheriting("a", Some("b"), &[("x", FieldType::Integer)]),
+            inheriting(
+                "b",
+                Some("c"),
+                &[("x", FieldType::Float), ("y", FieldType::Boolean)],
+            ),
+            inheriting(
+                "c",
+                None,
+                &[("y", FieldType::String), ("z", FieldType::Date)],
+            ),
+        ];
+
+        let resolved = schemas[0].resolve(&schemas).unwrap();
+        assert_eq!(resolved.fields.len(), 3);
+        assert_eq!(resolved.fields["x"].field_type, FieldType::Integer);
+        assert_eq!(resolved.fields["y"].field_type, FieldType::Boolean);
+        assert_eq!(resolved.fields["z"].field_type, FieldType::Date);
+        let rules: Vec<&str> = resolved
+            .validation
+            .iter()
+            .map(|r| r.rule.as_str())
+            .collect();
+        assert_eq!(rules, ["a_rule", "b_rule", "c_rule"]);
+    }
+
+    #[test]
+    fn resolve_missing_parent_errors() {
+        let child = inheriting("task", Some("nope"), &[]);
+        let err = child.resolve(std::slice::from_ref(&child)).unwrap_err();
+        assert!(matches!(
+            err,
+            SchemaError::MissingSchema { ref schema, ref parent } if schema == "task" && parent == "nope"
+        ));
+    }
+
+    #[test]
+    fn resolve_circular_inheritance_errors() {
+        let schemas = [
+            inheriting("a", Some("b"), &[]),
+            inheriting("b", Some("a"), &[]),
+        ];
+        let err = schemas[0].resolve(&schemas).unwrap_err();
+        assert!(matches!(
+            err,
+            SchemaError::CircularInheritance { ref chain } if chain == &["a", "b", "a"]
+        ));
+        assert_eq!(err.to_string(), "circular schema inheritance: a -> b -> a");
+    }
+
+    #[test]
+    fn built_in_decision_inherits_project_fields() {
+        let decision = built_in_schemas()
+            .into_iter()
+            .find(|s| s.name == "decision")
+            .unwrap();
+        assert!(decision.fields.contains_key("decision"));
+        assert!(decision.fields.contains_key("status"));
+        assert!(decision.fields.contains_key("owner"));
+    }
+
+    fn required(
+        mut def: SchemaDefinition,
+        field: &str,
+        default: Option<serde_json::Value>,
+    ) -> SchemaDefinition {
+        let f = def.fields.get_mut(field).unwrap();
+        f.required = true;
+        f.default = default;
+        def
+    }
+
+    #[test]
+    fn detect_breaking_changes_classifies_each_kind() {
+        let mut old = inheriting(
+            "task",
+            None,
+            &[
+                ("status", FieldType::Enum),
+                ("owner", FieldType::Ref),
+                ("legacy", FieldType::String),
+            ],
+        );
+        old.fields.get_mut("status").unwrap().values =
+            Some(vec!["open".into(), "blocked".into(), "done".into()]);
+        let mut new = inheriting(
+            "task",
+            None,
+            &[
+                ("status", FieldType::Enum),
+                ("owner", FieldType::String),
+                ("priority", FieldType::Integer),
+                ("team", FieldType::String),
+            ],
+        );
+        new.fields.get_mut("status").unwrap().values = Some(vec!["open".into(), "done".into()]);
+        let new = required(new, "priority", Some(serde_json::json!(3)));
+        let new = required(new, "team", None);
+
+        let changes = SchemaRegistry::detect_breaking_changes(&old, &new);
+        assert_eq!(
+            changes,
+            vec![
+                BreakingChange {
+                    field: "legacy".into(),
+                    kind: BreakingChangeKind::RemovedField,
+                    severity: BreakingChangeSeverity::Warning,
+                },
+                BreakingChange {
+                    field: "owner".into(),
+                    kind: BreakingChangeKind::TypeChanged {
+                        from: FieldType::Ref,
+                        to: FieldType::String,
+                    },
+                    severity: BreakingChangeSeverity::Fatal,
+                },
+                BreakingChange {
+                    field: "priority".into(),
+                    kind: BreakingChangeKind::NewRequiredField,
+                    severity: BreakingChangeSeverity::Warning,
+                },
+                BreakingChange {
+                    field: "status".into(),
+                    kind: BreakingChangeKind::EnumValuesRemoved {
+                        values: vec!["blocked".into()],
+                    },
+                    severity: BreakingChangeSeverity::Fatal,
+                },
+                BreakingChange {
+                    field: "team".into(),
+                    kind: BreakingChangeKind::NewRequiredField,
+                    severity: BreakingChangeSeverity::Fatal,
+                },
+            ]
+        );
+        assert!(SchemaRegistry::detect_breaking_changes(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn fill_defaults_backfills_only_missing_fields() {
+        let old = inheriting("task", None, &[("title", FieldType::String)]);
+        let new = inheriting(
+            "task",
+            None,
+            &[
+                ("title", FieldType::String),
+                ("priority", FieldType::Integer),
+            ],
+        );
+        let new = required(new, "priority", Some(serde_json::json!(3)));
+
+        let migration = SchemaMigration::fill_defaults(&old, &new);
+        assert_eq!(migration.doc_type, "task");
+        assert_eq!(migration.set_defaults["priority"], serde_json::json!(3));
+
+        let mut missing = HashMap::new();
+        assert!(migration.apply(&mut missing));
+        assert_eq!(missing["priority"], serde_json::json!(3));
+        let mut present = HashMap::from([("priority".to_string(), serde_json::json!(1))]);
+        assert!(!migration.apply(&mut present));
+        assert_eq!(present["priority"], serde_json::json!(1));
+    }
+
+    #[test]
+    fn registry_user_schema_replaces_built_in() {
+        let mut project = project_schema();
+        project.version = 2;
+        project.fields.remove("owner");
+        let registry = SchemaRegistry::new(vec![project.clone()]);
+
+        assert_eq!(registry.version("project"), Some(2));
+        assert_eq!(registry.version("meeting"), Some(1));
+        assert_eq!(registry.version("nope"), None);
+        assert!(registry.iter().any(|s| s.name == "signal"));
+
+        let mut candidate = project;
+        candidate.fields.remove("status");
+        let changes = registry.changes_for(&candidate).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].field, "status");
+        assert!(registry
+            .changes_for(&inheriting("nope", None, &[]))
+            .is_none());
+    }
+
+    #[test]
+    fn breaking_change_serializes_flat() {
+        let change = BreakingChange {
+            field: "owner".into(),
+            kind: BreakingChangeKind::TypeChanged {
+                from: FieldType::Ref,
+                to: FieldType::StringArray,
+            },
+            severity: BreakingChangeSeverity::Fatal,
+        };
+        assert_eq!(
+            serde_json::to_value(&change).unwrap(),
+            serde_json::json!({
+                "field": "owner",
+                "kind": "type_changed",
+                "from": "ref",
+                "to": "string[]",
+                "severity": "fatal",
+            })
+        );
+    }
+
+    fn link(rel: &str, target: &str) -> Link {
+        Link {
+            rel: rel.to_string(),
+            target: target.to_string(),
+            observed_at: chrono::Utc::now(),
+            metadata: None,
+        }
+    }
+
+    fn doc_types(id: &str) -> Option<String> {
+        match id {
+            "pers-jane-001" | "pers-joe-001" => Some("person".to_string()),
+            "meet-sync-001" => Some("meeting".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn validate_links_rejects_wrong_target_type() {
+        let result = project_schema().validate_links(&[link("owner", "meet-sync-001")], doc_types);
+        assert!(!result.is_valid());
+        assert_eq!(
+            result.errors[0].to_string(),
+            "link 'owner' must target a 'person' document, but 'meet-sync-001' is a 'meeting'"
+        );
+
+        let ok = project_schema().validate_links(&[link("owner", "pers-jane-001")], doc_types);
+        assert!(ok.is_valid());
+        assert!(ok.warnings.is_empty());
+
+        let unknown = project_schema().validate_links(&[link("owner", "people/nobody")], doc_types);
+        assert!(unknown.is_valid());
+        assert_eq!(unknown.warnings.len(), 1);
+    }
+
+    #[test]
+    fn validate_links_warns_on_missing_required_link() {
+        let mut schema = inheriting("task", None, &[]);
+        schema.link_constraints.insert(
+            "assignee".to_string(),
+            LinkConstraint {
+                target_type: None,
+                required: true,
+                max_count: None,
+            },
+        );
+
+        let result = schema.validate_links(&[link("depends_on", "task-x-001")], doc_types);
+        assert!(result.is_valid());
+        assert_eq!(result.warnings, ["missing required link 'assignee'"]);
+        assert!(schema
+            .validate_links(&[link("assignee", "pers-jane-001")], doc_types)
+            .warnings
+            .is_empty());
+    }
+
+    #[test]
+    fn validate_links_rejects_exceeding_max_count() {
+        let links = [
+            link("owner", "pers-jane-001"),
+            link("owner", "pers-joe-001"),
+        ];
+        let result = project_schema().validate_links(&links, doc_types);
+        assert!(matches!(
+            result.errors.as_slice(),
+            [SchemaError::TooManyLinks { rel, max: 1, count: 2 }] if rel == "owner"
+        ));
+    }
+
+    #[test]
+    fn link_constraints_parse_and_inherit() {
+        let parent: SchemaDefinition = serde_yaml::from_str(
+            "name: base\nlink_constraints:\n  owner:\n    target_type: person\n    max_count: 1\n",
+        )
+        .unwrap();
+        let child = inheriting("task", Some("base"), &[]);
+        let resolved = child.resolve(&[parent, child.clone()]).unwrap();
+        assert_eq!(
+            resolved.link_constraints["owner"],
+            LinkConstraint {
+                target_type: Some("person".to_string()),
+                required: false,
+                max_count: Some(1),
+            }
+        );
+    }
 }
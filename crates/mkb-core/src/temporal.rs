@@ -2,10 +2,10 @@
 //!
 //! Core invariant: **No information enters the vault without `observed_at`.**
 
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::error::TemporalError;
+use crate::error::{FuzzyParseError, TemporalError};
 
 /// Precision level of a temporal observation.
 #[derive(
@@ -23,13 +23,331 @@ pub enum TemporalPrecision {
     Inferred,
 }
 
+impl TemporalPrecision {
+    /// How much to trust a document's content given how precisely its
+    /// observation time is known, from `1.0` for `Exact` down to `0.40` for
+    /// `Inferred`. Used as the default confidence of new documents.
+    #[must_use]
+    pub fn confidence_weight(self) -> f64 {
+        match self {
+            Self::Exact => 1.0,
+            Self::Day => 0.95,
+            Self::Week => 0.85,
+            Self::Month => 0.75,
+            Self::Quarter => 0.65,
+            Self::Approximate => 0.55,
+            Self::Inferred => 0.40,
+        }
+    }
+
+    /// The precision with this serialized name (`"day"`, `"week"`, ...).
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "exact" => Some(Self::Exact),
+            "day" => Some(Self::Day),
+            "week" => Some(Self::Week),
+            "month" => Some(Self::Month),
+            "quarter" => Some(Self::Quarter),
+            "approximate" => Some(Self::Approximate),
+            "inferred" => Some(Self::Inferred),
+            _ => None,
+        }
+    }
+
+    /// How long ago `observed_at` was at `now`, in words no finer than this
+    /// precision: a `Week` observation from six days ago is "less than 1
+    /// week ago", not "6 days ago".
+    ///
+    /// The count is whole units, rounded down. Once an age reaches one of a
+    /// coarser unit it is shown in that unit, so thirteen months is "1 year
+    /// ago". Observations after `now` are "in the future".
+    #[must_use]
+    pub fn age_label(self, observed_at: DateTime<Utc>, now: DateTime<Utc>) -> String {
+        let age = now.signed_duration_since(observed_at);
+        if age < Duration::zero() {
+            return "in the future".to_string();
+        }
+
+        let finest = AgeUnit::finest_for(self);
+        // Quarters are only counted for quarter-precision observations
+        let unit = AgeUnit::ALL
+            .into_iter()
+            .rev()
+            .find(|u| {
+                *u >= finest
+                    && (*u != AgeUnit::Quarter || finest == AgeUnit::Quarter)
+                    && (*u == finest || age >= u.length())
+            })
+            .unwrap_or(finest);
+        let count = age.num_seconds() / unit.length().num_seconds();
+        match count {
+            0 => format!("less than 1 {}", unit.name()),
+            1 => format!("1 {} ago", unit.name()),
+            n => format!("{n} {}s ago", unit.name()),
+        }
+    }
+}
+
+/// Units [`TemporalPrecision::age_label`] counts in, finest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum AgeUnit {
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl AgeUnit {
+    const ALL: [Self; 7] = [
+        Self::Minute,
+        Self::Hour,
+        Self::Day,
+        Self::Week,
+        Self::Month,
+        Self::Quarter,
+        Self::Year,
+    ];
+
+    /// Ages are never shown finer than the observation was known; an
+    /// approximate or inferred date only supports years.
+    fn finest_for(precision: TemporalPrecision) -> Self {
+        match precision {
+            TemporalPrecision::Exact => Self::Minute,
+            TemporalPrecision::Day => Self::Day,
+            TemporalPrecision::Week => Self::Week,
+            TemporalPrecision::Month => Self::Month,
+            TemporalPrecision::Quarter => Self::Quarter,
+            TemporalPrecision::Approximate | TemporalPrecision::Inferred => Self::Year,
+        }
+    }
+
+    fn length(self) -> Duration {
+        match self {
+            Self::Minute => Duration::minutes(1),
+            Self::Hour => Duration::hours(1),
+            Self::Day => Duration::days(1),
+            Self::Week => Duration::weeks(1),
+            Self::Month => Duration::days(30),
+            Self::Quarter => Duration::days(91),
+            Self::Year => Duration::days(365),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Minute => "minute",
+            Self::Hour => "hour",
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+            Self::Quarter => "quarter",
+            Self::Year => "year",
+        }
+    }
+}
+
+/// A user-supplied timestamp normalized to UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParsedDateTime {
+    pub utc: DateTime<Utc>,
+    /// Offset the input was written in, in minutes east of UTC. Inputs
+    /// without an offset are read as UTC and report `0`.
+    pub offset_minutes: i32,
+    /// The input was a bare date, read as midnight UTC.
+    pub date_only: bool,
+}
+
+impl ParsedDateTime {
+    /// Precision that can honestly be claimed for this timestamp.
+    ///
+    /// A bare date says nothing about the time of day, so `Exact` is
+    /// coerced to `Day`; coarser precisions are kept.
+    #[must_use]
+    pub fn coerce_precision(&self, precision: TemporalPrecision) -> TemporalPrecision {
+        if self.date_only {
+            precision.max(TemporalPrecision::Day)
+        } else {
+            precision
+        }
+    }
+}
+
+/// Parse a timestamp as users tend to write it.
+///
+/// Accepts, in order:
+/// 1. RFC 3339 with any offset (`2025-06-15T10:00:00+02:00`), converted to UTC
+/// 2. `YYYY-MM-DD HH:MM:SS` or `YYYY-MM-DDTHH:MM:SS` without an offset, read as UTC
+/// 3. `YYYY-MM-DD`, read as midnight UTC
+///
+/// # Errors
+///
+/// Returns [`TemporalError::InvalidDateTime`] if no format matches.
+pub fn parse_datetime(s: &str) -> Result<ParsedDateTime, TemporalError> {
+    let s = s.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Ok(ParsedDateTime {
+            utc: dt.with_timezone(&Utc),
+            offset_minutes: dt.offset().local_minus_utc() / 60,
+            date_only: false,
+        });
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(s, format) {
+            return Ok(ParsedDateTime {
+                utc: naive.and_utc(),
+                offset_minutes: 0,
+                date_only: false,
+            });
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Ok(ParsedDateTime {
+            utc: date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc(),
+            offset_minutes: 0,
+            date_only: true,
+        });
+    }
+    Err(TemporalError::InvalidDateTime(s.to_string()))
+}
+
+/// A date expression resolved by [`FuzzyDateTimeParser`], with the precision
+/// its notation supports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FuzzyDateTime {
+    pub utc: DateTime<Utc>,
+    pub precision: TemporalPrecision,
+}
+
+/// Words that mark a date as relative to an unknown "now".
+const RELATIVE_WORDS: &[&str] = &[
+    "ago",
+    "last",
+    "next",
+    "this",
+    "now",
+    "today",
+    "yesterday",
+    "tomorrow",
+    "recently",
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+/// Reads the looser date notations LLMs tend to write into frontmatter.
+///
+/// Tries, in order:
+/// 1. anything [`parse_datetime`] accepts (`Exact`, or `Day` for bare dates)
+/// 2. quarters: `2025-Q1`, `2025 Q1` or `Q1 2025`, read as the first day of
+///    the quarter (`Quarter`)
+/// 3. a bare year `2025`, read as January 1 (`Approximate`)
+/// 4. month and year: `June 2025` or `Jun 2025`, read as the first of the
+///    month (`Month`)
+pub struct FuzzyDateTimeParser;
+
+impl FuzzyDateTimeParser {
+    /// Resolve `s` to a UTC timestamp and the precision it supports.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FuzzyParseError::RelativeNotSupported`] for expressions like
+    /// `last Tuesday` or `3 days ago`, and [`FuzzyParseError::Unrecognized`]
+    /// for anything else no notation matches.
+    pub fn parse(s: &str) -> Result<FuzzyDateTime, FuzzyParseError> {
+        let s = s.trim();
+        if let Ok(parsed) = parse_datetime(s) {
+            let precision = if parsed.date_only {
+                TemporalPrecision::Day
+            } else {
+                TemporalPrecision::Exact
+            };
+            return Ok(FuzzyDateTime {
+                utc: parsed.utc,
+                precision,
+            });
+        }
+
+        let first_of = |year: i32, month: u32, precision| {
+            NaiveDate::from_ymd_opt(year, month, 1).map(|date| FuzzyDateTime {
+                utc: date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc(),
+                precision,
+            })
+        };
+        let tokens: Vec<String> = s
+            .split(|c: char| c == '-' || c.is_whitespace())
+            .filter(|t| !t.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+
+        let resolved = match tokens.as_slice() {
+            [year, quarter] | [quarter, year] if quarter.starts_with('q') => {
+                match (parse_year(year), quarter[1..].parse::<u32>()) {
+                    (Some(year), Ok(q @ 1..=4)) => {
+                        first_of(year, (q - 1) * 3 + 1, TemporalPrecision::Quarter)
+                    }
+                    _ => None,
+                }
+            }
+            [year] => parse_year(year).and_then(|y| first_of(y, 1, TemporalPrecision::Approximate)),
+            [month, year] => NaiveDate::parse_from_str(&format!("1 {month} {year}"), "%d %B %Y")
+                .ok()
+                .filter(|_| parse_year(year).is_some())
+                .and_then(|date| first_of(date.year(), date.month(), TemporalPrecision::Month)),
+            _ => None,
+        };
+        if let Some(resolved) = resolved {
+            return Ok(resolved);
+        }
+
+        if tokens
+            .iter()
+            .any(|t| RELATIVE_WORDS.contains(&t.trim_end_matches(['.', ','])))
+        {
+            return Err(FuzzyParseError::RelativeNotSupported(s.to_string()));
+        }
+        Err(FuzzyParseError::Unrecognized(s.to_string()))
+    }
+}
+
+/// A four-digit year.
+fn parse_year(token: &str) -> Option<i32> {
+    if token.len() == 4 && token.bytes().all(|b| b.is_ascii_digit()) {
+        token.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// Serde adapter reading datetimes through [`parse_datetime`].
+mod lenient_datetime {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Deserializer};
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<DateTime<Utc>, D::Error> {
+        let raw = String::deserialize(d)?;
+        super::parse_datetime(&raw)
+            .map(|p| p.utc)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
 /// Mandatory temporal fields present on every document.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TemporalFields {
     /// When this information was true/observed. **MANDATORY.**
+    #[serde(deserialize_with = "lenient_datetime::deserialize")]
     pub observed_at: DateTime<Utc>,
 
     /// When this information expires. **MANDATORY** (computed if not provided).
+    #[serde(deserialize_with = "lenient_datetime::deserialize")]
     pub valid_until: DateTime<Utc>,
 
     /// How precise the temporal grounding is. **MANDATORY.**
@@ -39,6 +357,51 @@ pub struct TemporalFields {
     /// When the described event actually happened (if different from observed_at).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub occurred_at: Option<DateTime<Utc>>,
+
+    /// When an event spanning a range ended. `None` means `occurred_at` is a
+    /// single point in time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub occurred_until: Option<DateTime<Utc>>,
+
+    /// Decay already accrued when the document was observed, as a factor on
+    /// the raw confidence. Computed by [`TemporalGate::validate`] from the
+    /// gap between `occurred_at` and `observed_at`.
+    #[serde(default = "unit_factor", skip_serializing_if = "is_unit_factor")]
+    pub confidence_factor: f64,
+}
+
+fn unit_factor() -> f64 {
+    1.0
+}
+
+fn is_unit_factor(factor: &f64) -> bool {
+    (factor - 1.0).abs() < f64::EPSILON
+}
+
+impl TemporalFields {
+    /// Confidence after decay since `observed_at`.
+    ///
+    /// Computes `raw × confidence_factor × factor(age)`, where `factor` comes
+    /// from the profile's [`DecayModel`]. Unlike
+    /// [`DecayModel::effective_confidence`], no precision penalty is applied.
+    /// Observations in the future of `now` are not decayed.
+    #[must_use]
+    pub fn effective_confidence(
+        &self,
+        raw_confidence: f64,
+        decay_profile: &DecayProfile,
+        now: DateTime<Utc>,
+    ) -> f64 {
+        let age = now.signed_duration_since(self.observed_at);
+        raw_confidence * self.confidence_factor * decay_profile.model.confidence_factor(age)
+    }
+
+    /// How long ago this was observed, in units of its precision (see
+    /// [`TemporalPrecision::age_label`]), e.g. "3 days ago".
+    #[must_use]
+    pub fn age_in_precision_units(&self, now: DateTime<Utc>) -> String {
+        self.temporal_precision.age_label(self.observed_at, now)
+    }
 }
 
 /// Raw temporal input before gate validation.
@@ -49,74 +412,176 @@ pub struct RawTemporalInput {
     pub valid_until: Option<DateTime<Utc>>,
     pub temporal_precision: Option<TemporalPrecision>,
     pub occurred_at: Option<DateTime<Utc>>,
+    pub occurred_until: Option<DateTime<Utc>>,
 }
 
 /// Decay profile for computing `valid_until` when not explicitly provided.
 #[derive(Debug, Clone, PartialEq)]
 pub struct DecayProfile {
-    /// Base half-life duration for this document type.
-    pub half_life: Duration,
+    /// How confidence falls off with age for this document type.
+    pub model: DecayModel,
 }
 
 impl DecayProfile {
-    /// Create a new decay profile with the given half-life.
+    /// Create an exponential decay profile with the given half-life.
     #[must_use]
     pub fn new(half_life: Duration) -> Self {
-        Self { half_life }
+        Self::with_model(DecayModel::with_half_life(half_life))
+    }
+
+    /// Create a decay profile around an explicit [`DecayModel`].
+    #[must_use]
+    pub fn with_model(model: DecayModel) -> Self {
+        Self { model }
     }
 
     /// Default decay profile: 90-day half-life.
     #[must_use]
     pub fn default_profile() -> Self {
-        Self {
-            half_life: Duration::days(90),
-        }
+        Self::new(Duration::days(90))
     }
 
     /// Project status decays in 14 days.
     #[must_use]
     pub fn project_status() -> Self {
-        Self {
-            half_life: Duration::days(14),
-        }
+        Self::new(Duration::days(14))
     }
 
     /// Decisions never decay (very long half-life).
     #[must_use]
     pub fn decision() -> Self {
-        Self {
-            half_life: Duration::days(365 * 100),
-        }
+        Self::new(Duration::days(365 * 100))
     }
 
     /// Signals decay in 7 days.
     #[must_use]
     pub fn signal() -> Self {
-        Self {
-            half_life: Duration::days(7),
-        }
+        Self::new(Duration::days(7))
+    }
+
+    /// Age at which this profile's confidence factor has halved.
+    #[must_use]
+    pub fn half_life(&self) -> Duration {
+        self.model.half_life()
     }
 
-    /// Compute valid_until from observed_at using this profile.
-    /// Uses 2x half-life as the default validity window.
+    /// Compute valid_until from observed_at using this profile's model.
+    /// See [`DecayModel::validity_window`]. Saturates at the latest
+    /// representable time.
     #[must_use]
     pub fn compute_valid_until(&self, observed_at: DateTime<Utc>) -> DateTime<Utc> {
-        observed_at + self.half_life * 2
+        observed_at
+            .checked_add_signed(self.model.validity_window())
+            .unwrap_or(DateTime::<Utc>::MAX_UTC)
     }
 }
 
-/// The DecayModel computes effective confidence of a document over time.
+/// Validity window for documents that never decay.
+const NEVER_DECAYS_WINDOW: Duration = Duration::days(365 * 100);
+
+/// How the confidence of a document falls off over time.
+///
+/// The model yields a factor in `[0.0, 1.0]` for a given age, and the
+/// validity window used when `valid_until` is not provided. Effective
+/// confidence is:
+///
+/// `C(t) = C₀ × factor(t) × precision_penalty`
 ///
-/// Formula: `C(t) = C₀ × 0.5^(t / half_life) × precision_penalty`
+/// Models are read from `.mkb/decay.yaml`, tagged by `kind`:
 ///
-/// Where:
-/// - `C₀` is the initial confidence (typically 1.0)
-/// - `t` is elapsed time since `observed_at`
-/// - `half_life` comes from the decay profile
-/// - `precision_penalty` reduces confidence for lower-precision timestamps
-pub struct DecayModel;
+/// ```yaml
+/// model:
+///   kind: step_function
+///   steps:
+///     - { after_days: 7, factor: 0.8 }
+///     - { after_days: 30, factor: 0.2 }
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "DecayModelSpec")]
+pub enum DecayModel {
+    /// Falls in a straight line, reaching 0.5 at `half_life` and 0 at twice
+    /// that. Valid for two half-lives.
+    Linear { half_life: Duration },
+    /// `e^(−lambda × age_in_days)`. Valid until the factor drops to 0.25,
+    /// which is two half-lives.
+    Exponential { lambda: f64 },
+    /// Holds the factor of the latest step whose age has been reached, and
+    /// 1.0 before the first. Valid until the last step.
+    StepFunction { steps: Vec<(Duration, f64)> },
+    /// Always 1.0. Valid for a hundred years.
+    Never,
+}
 
 impl DecayModel {
+    /// Exponential decay that halves every `half_life`.
+    #[must_use]
+    pub fn with_half_life(half_life: Duration) -> Self {
+        Self::Exponential {
+            lambda: std::f64::consts::LN_2 / duration_days(half_life),
+        }
+    }
+
+    /// The confidence factor at `age`. Negative ages are not decayed.
+    #[must_use]
+    pub fn confidence_factor(&self, age: Duration) -> f64 {
+        if age < Duration::zero() {
+            return 1.0;
+        }
+        match self {
+            Self::Linear { half_life } => {
+                if *half_life <= Duration::zero() {
+                    return 1.0;
+                }
+                (1.0 - duration_days(age) / (2.0 * duration_days(*half_life))).max(0.0)
+            }
+            Self::Exponential { lambda } => (-lambda * duration_days(age)).exp().min(1.0),
+            Self::StepFunction { steps } => steps
+                .iter()
+                .filter(|(after, _)| *after <= age)
+                .max_by_key(|(after, _)| *after)
+                .map_or(1.0, |(_, factor)| *factor),
+            Self::Never => 1.0,
+        }
+    }
+
+    /// How long after `observed_at` a document stays valid when no
+    /// `valid_until` is given.
+    #[must_use]
+    pub fn validity_window(&self) -> Duration {
+        match self {
+            Self::Linear { half_life } => *half_life * 2,
+            Self::Exponential { lambda } if *lambda > 0.0 => {
+                days_to_duration(2.0 * std::f64::consts::LN_2 / lambda)
+            }
+            Self::StepFunction { steps } if !steps.is_empty() => steps
+                .iter()
+                .map(|(after, _)| *after)
+                .max()
+                .unwrap_or(NEVER_DECAYS_WINDOW),
+            Self::Exponential { .. } | Self::StepFunction { .. } | Self::Never => {
+                NEVER_DECAYS_WINDOW
+            }
+        }
+    }
+
+    /// The first age at which the factor is at most 0.5.
+    #[must_use]
+    pub fn half_life(&self) -> Duration {
+        match self {
+            Self::Linear { half_life } => *half_life,
+            Self::Exponential { lambda } if *lambda > 0.0 => {
+                days_to_duration(std::f64::consts::LN_2 / lambda)
+            }
+            Self::StepFunction { steps } => steps
+                .iter()
+                .filter(|(_, factor)| *factor <= 0.5)
+                .map(|(after, _)| *after)
+                .min()
+                .unwrap_or(NEVER_DECAYS_WINDOW),
+            Self::Exponential { .. } | Self::Never => NEVER_DECAYS_WINDOW,
+        }
+    }
+
     /// Compute the effective confidence of a document at a given time.
     ///
     /// Returns a value in `[0.0, 1.0]` representing how much to trust this document.
@@ -129,15 +594,11 @@ impl DecayModel {
         precision: TemporalPrecision,
     ) -> f64 {
         let elapsed = at_time.signed_duration_since(observed_at);
-        let elapsed_secs = elapsed.num_seconds() as f64;
-        let half_life_secs = profile.half_life.num_seconds() as f64;
-
-        if half_life_secs <= 0.0 || elapsed_secs < 0.0 {
+        if elapsed < Duration::zero() {
             return initial_confidence;
         }
 
-        // Exponential decay: C(t) = C₀ × 0.5^(t / half_life)
-        let decay_factor = (0.5_f64).powf(elapsed_secs / half_life_secs);
+        let decay_factor = profile.model.confidence_factor(elapsed);
 
         // Precision penalty: lower precision = faster effective decay
         let precision_penalty = Self::precision_multiplier(precision);
@@ -168,6 +629,68 @@ impl DecayModel {
     }
 }
 
+/// On-disk form of a [`DecayModel`], with ages in days.
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum DecayModelSpec {
+    Linear { half_life_days: f64 },
+    Exponential { lambda: f64 },
+    StepFunction { steps: Vec<DecayStepSpec> },
+    Never,
+}
+
+#[derive(Deserialize)]
+struct DecayStepSpec {
+    after_days: f64,
+    factor: f64,
+}
+
+impl TryFrom<DecayModelSpec> for DecayModel {
+    type Error = String;
+
+    fn try_from(spec: DecayModelSpec) -> Result<Self, Self::Error> {
+        match spec {
+            DecayModelSpec::Linear { half_life_days } if half_life_days > 0.0 => Ok(Self::Linear {
+                half_life: days_to_duration(half_life_days),
+            }),
+            DecayModelSpec::Linear { .. } => Err("half_life_days must be positive".into()),
+            DecayModelSpec::Exponential { lambda } if lambda > 0.0 => {
+                Ok(Self::Exponential { lambda })
+            }
+            DecayModelSpec::Exponential { .. } => Err("lambda must be positive".into()),
+            DecayModelSpec::StepFunction { steps } => steps
+                .into_iter()
+                .map(|step| {
+                    if step.after_days < 0.0 {
+                        Err("step after_days must not be negative".to_string())
+                    } else if !(0.0..=1.0).contains(&step.factor) {
+                        Err("step factor must be between 0 and 1".to_string())
+                    } else {
+                        Ok((days_to_duration(step.after_days), step.factor))
+                    }
+                })
+                .collect::<Result<_, _>>()
+                .map(|steps| Self::StepFunction { steps }),
+            DecayModelSpec::Never => Ok(Self::Never),
+        }
+    }
+}
+
+fn duration_days(d: Duration) -> f64 {
+    d.num_seconds() as f64 / 86_400.0
+}
+
+/// Whole seconds closest to `days`, so round trips through `f64` land on
+/// the original duration. Spans longer than [`NEVER_DECAYS_WINDOW`] (such as
+/// the half-life of a tiny `lambda`) are clamped to it.
+fn days_to_duration(days: f64) -> Duration {
+    let seconds = (days * 86_400.0).round();
+    if seconds.is_nan() || seconds >= NEVER_DECAYS_WINDOW.num_seconds() as f64 {
+        return NEVER_DECAYS_WINDOW;
+    }
+    Duration::try_seconds(seconds as i64).unwrap_or(NEVER_DECAYS_WINDOW)
+}
+
 /// The Temporal Gate — validates all temporal invariants before a document
 /// enters the vault.
 ///
@@ -184,12 +707,16 @@ impl TemporalGate {
     ///
     /// If `valid_until` is not provided, it is computed from the decay profile.
     /// If `temporal_precision` is not provided, it defaults to `Inferred`.
+    /// `confidence_factor` is the profile's decay between `occurred_at` and
+    /// `observed_at`.
     ///
     /// # Errors
     ///
     /// Returns [`TemporalError::MissingObservedAt`] if `observed_at` is `None`.
     /// Returns [`TemporalError::ValidUntilBeforeObservedAt`] if `valid_until < observed_at`.
     /// Returns [`TemporalError::OccurredAtAfterObservedAt`] if `occurred_at > observed_at`.
+    /// Returns an `OccurredUntil*` error unless
+    /// `occurred_at <= occurred_until <= observed_at`.
     pub fn validate(
         input: &RawTemporalInput,
         decay_profile: &DecayProfile,
@@ -225,22 +752,34 @@ impl TemporalGate {
             }
         }
 
+        // T6: occurred_at <= occurred_until <= observed_at
+        Self::validate_occurred_range(input.occurred_at, input.occurred_until, observed_at)?;
+
+        // Information observed after the event has already aged
+        let confidence_factor = decay_profile
+            .model
+            .confidence_factor(observed_at - input.occurred_at.unwrap_or(observed_at));
+
         Ok(TemporalFields {
             observed_at,
             valid_until,
             temporal_precision,
             occurred_at: input.occurred_at,
+            occurred_until: input.occurred_until,
+            confidence_factor,
         })
     }
 
     /// Validate already-constructed [`TemporalFields`] (e.g., from deserialization).
     ///
-    /// Checks T4 and T5 invariants. T1-T3 are guaranteed by the type.
+    /// Checks T4-T6 invariants. T1-T3 are guaranteed by the type.
     ///
     /// # Errors
     ///
     /// Returns [`TemporalError::ValidUntilBeforeObservedAt`] if `valid_until < observed_at`.
     /// Returns [`TemporalError::OccurredAtAfterObservedAt`] if `occurred_at > observed_at`.
+    /// Returns an `OccurredUntil*` error unless
+    /// `occurred_at <= occurred_until <= observed_at`.
     pub fn validate_fields(fields: &TemporalFields) -> Result<(), TemporalError> {
         // T4: valid_until >= observed_at
         if fields.valid_until < fields.observed_at {
@@ -260,6 +799,39 @@ impl TemporalGate {
             }
         }
 
+        // T6: occurred_at <= occurred_until <= observed_at
+        Self::validate_occurred_range(
+            fields.occurred_at,
+            fields.occurred_until,
+            fields.observed_at,
+        )
+    }
+
+    fn validate_occurred_range(
+        occurred_at: Option<DateTime<Utc>>,
+        occurred_until: Option<DateTime<Utc>>,
+        observed_at: DateTime<Utc>,
+    ) -> Result<(), TemporalError> {
+        let Some(occurred_until) = occurred_until else {
+            return Ok(());
+        };
+        let Some(occurred_at) = occurred_at else {
+            return Err(TemporalError::OccurredUntilWithoutOccurredAt {
+                occurred_until: occurred_until.to_rfc3339(),
+            });
+        };
+        if occurred_until < occurred_at {
+            return Err(TemporalError::OccurredUntilBeforeOccurredAt {
+                occurred_at: occurred_at.to_rfc3339(),
+                occurred_until: occurred_until.to_rfc3339(),
+            });
+        }
+        if occurred_until > observed_at {
+            return Err(TemporalError::OccurredUntilAfterObservedAt {
+                observed_at: observed_at.to_rfc3339(),
+                occurred_until: occurred_until.to_rfc3339(),
+            });
+        }
         Ok(())
     }
 }
@@ -273,6 +845,206 @@ mod tests {
         Utc.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap()
     }
 
+    // === age label tests ===
+
+    fn fields_at(observed_at: DateTime<Utc>, precision: TemporalPrecision) -> TemporalFields {
+        TemporalFields {
+            observed_at,
+            valid_until: observed_at + Duration::days(180),
+            temporal_precision: precision,
+            occurred_at: None,
+            occurred_until: None,
+            confidence_factor: 1.0,
+        }
+    }
+
+    #[test]
+    fn age_label_counts_in_precision_units() {
+        let now = utc(2025, 3, 1);
+        let cases = [
+            (utc(2025, 2, 28), TemporalPrecision::Day, "1 day ago"),
+            (utc(2025, 2, 26), TemporalPrecision::Day, "3 days ago"),
+            (
+                utc(2025, 2, 23),
+                TemporalPrecision::Week,
+                "less than 1 week",
+            ),
+            (utc(2025, 2, 21), TemporalPrecision::Week, "1 week ago"),
+            (utc(2024, 2, 1), TemporalPrecision::Month, "1 year ago"),
+            (utc(2025, 1, 1), TemporalPrecision::Month, "1 month ago"),
+            (utc(2024, 9, 1), TemporalPrecision::Quarter, "1 quarter ago"),
+            (utc(2024, 9, 1), TemporalPrecision::Month, "6 months ago"),
+            (
+                utc(2025, 1, 1),
+                TemporalPrecision::Approximate,
+                "less than 1 year",
+            ),
+            (utc(2025, 3, 2), TemporalPrecision::Day, "in the future"),
+        ];
+        for (observed_at, precision, expected) in cases {
+            assert_eq!(
+                fields_at(observed_at, precision).age_in_precision_units(now),
+                expected,
+                "{observed_at} at {precision:?}"
+            );
+        }
+
+        let exact = Utc.with_ymd_and_hms(2025, 2, 28, 21, 0, 0).unwrap();
+        assert_eq!(
+            TemporalPrecision::Exact.age_label(exact, now),
+            "3 hours ago"
+        );
+    }
+
+    #[test]
+    fn precision_names_round_trip() {
+        for precision in [
+            TemporalPrecision::Exact,
+            TemporalPrecision::Day,
+            TemporalPrecision::Week,
+            TemporalPrecision::Month,
+            TemporalPrecision::Quarter,
+            TemporalPrecision::Approximate,
+            TemporalPrecision::Inferred,
+        ] {
+            let name = serde_json::to_value(precision).unwrap();
+            assert_eq!(
+                TemporalPrecision::from_name(name.as_str().unwrap()),
+                Some(precision)
+            );
+        }
+        assert_eq!(TemporalPrecision::from_name("fortnight"), None);
+    }
+
+    // === parse_datetime tests ===
+
+    #[test]
+    fn parse_datetime_converts_offsets_to_utc() {
+        let cases = [
+            ("2025-06-15T10:00:00Z", 0, 10),
+            ("2025-06-15T10:00:00+00:00", 0, 10),
+            ("2025-06-15T10:00:00+02:00", 120, 8),
+            ("2025-06-15T10:00:00-05:00", -300, 15),
+        ];
+        for (input, offset, hour) in cases {
+            let parsed = parse_datetime(input).unwrap();
+            assert_eq!(parsed.offset_minutes, offset, "{input}");
+            assert_eq!(
+                parsed.utc,
+                Utc.with_ymd_and_hms(2025, 6, 15, hour, 0, 0).unwrap()
+            );
+            assert!(!parsed.date_only);
+        }
+    }
+
+    #[test]
+    fn parse_datetime_accepts_naive_formats() {
+        let spaced = parse_datetime("2025-06-15 10:30:00").unwrap();
+        assert_eq!(
+            spaced.utc,
+            Utc.with_ymd_and_hms(2025, 6, 15, 10, 30, 0).unwrap()
+        );
+        assert_eq!(parse_datetime("2025-06-15T10:30:00").unwrap(), spaced);
+
+        let date = parse_datetime("2025-06-15").unwrap();
+        assert_eq!(date.utc, utc(2025, 6, 15));
+        assert!(date.date_only);
+        assert_eq!(
+            date.coerce_precision(TemporalPrecision::Exact),
+            TemporalPrecision::Day
+        );
+        assert_eq!(
+            date.coerce_precision(TemporalPrecision::Month),
+            TemporalPrecision::Month
+        );
+        assert_eq!(
+            spaced.coerce_precision(TemporalPrecision::Exact),
+            TemporalPrecision::Exact
+        );
+    }
+
+    #[test]
+    fn parse_datetime_rejects_garbage() {
+        for input in [
+            "",
+            "yesterday",
+            "2025-13-01",
+            "15/06/2025",
+            "2025-06-15T25:00:00Z",
+        ] {
+            assert!(
+                matches!(
+                    parse_datetime(input),
+                    Err(TemporalError::InvalidDateTime(_))
+                ),
+                "{input}"
+            );
+        }
+    }
+
+    // === FuzzyDateTimeParser tests ===
+
+    #[test]
+    fn fuzzy_parser_prefers_strict_formats() {
+        let exact = FuzzyDateTimeParser::parse("2025-06-15T10:00:00+02:00").unwrap();
+        assert_eq!(
+            exact.utc,
+            Utc.with_ymd_and_hms(2025, 6, 15, 8, 0, 0).unwrap()
+        );
+        assert_eq!(exact.precision, TemporalPrecision::Exact);
+
+        let day = FuzzyDateTimeParser::parse("2025-06-15").unwrap();
+        assert_eq!(day.utc, utc(2025, 6, 15));
+        assert_eq!(day.precision, TemporalPrecision::Day);
+    }
+
+    #[test]
+    fn fuzzy_parser_reads_quarters() {
+        for input in ["2025-Q1", "2025 Q1", "Q1 2025", "2025-q1"] {
+            let parsed = FuzzyDateTimeParser::parse(input).unwrap();
+            assert_eq!(parsed.utc, utc(2025, 1, 1), "{input}");
+            assert_eq!(parsed.precision, TemporalPrecision::Quarter);
+        }
+        assert_eq!(
+            FuzzyDateTimeParser::parse("2024-Q4").unwrap().utc,
+            utc(2024, 10, 1)
+        );
+        assert!(FuzzyDateTimeParser::parse("2025-Q5").is_err());
+    }
+
+    #[test]
+    fn fuzzy_parser_reads_bare_years() {
+        let parsed = FuzzyDateTimeParser::parse("2025").unwrap();
+        assert_eq!(parsed.utc, utc(2025, 1, 1));
+        assert_eq!(parsed.precision, TemporalPrecision::Approximate);
+        assert!(FuzzyDateTimeParser::parse("25").is_err());
+    }
+
+    #[test]
+    fn fuzzy_parser_reads_month_and_year() {
+        for input in ["June 2025", "jun 2025", "JUNE 2025"] {
+            let parsed = FuzzyDateTimeParser::parse(input).unwrap();
+            assert_eq!(parsed.utc, utc(2025, 6, 1), "{input}");
+            assert_eq!(parsed.precision, TemporalPrecision::Month);
+        }
+        assert!(FuzzyDateTimeParser::parse("Smarch 2025").is_err());
+    }
+
+    #[test]
+    fn fuzzy_parser_rejects_relative_expressions() {
+        for input in ["last Tuesday", "3 days ago", "yesterday", "next month"] {
+            assert_eq!(
+                FuzzyDateTimeParser::parse(input),
+                Err(FuzzyParseError::RelativeNotSupported(input.to_string())),
+                "{input}"
+            );
+        }
+        assert_eq!(
+            FuzzyDateTimeParser::parse("sometime"),
+            Err(FuzzyParseError::Unrecognized("sometime".to_string()))
+        );
+    }
+
     // === TemporalPrecision tests ===
 
     #[test]
@@ -311,6 +1083,7 @@ mod tests {
             valid_until: Some(utc(2025, 8, 10)),
             temporal_precision: Some(TemporalPrecision::Day),
             occurred_at: None,
+            occurred_until: None,
         };
         let profile = DecayProfile::default_profile();
 
@@ -330,6 +1103,7 @@ mod tests {
             valid_until: None, // not provided — gate should compute
             temporal_precision: None,
             occurred_at: None,
+            occurred_until: None,
         };
         let profile = DecayProfile::default_profile(); // 90-day half-life, 180-day validity
 
@@ -349,6 +1123,7 @@ mod tests {
             valid_until: None,
             temporal_precision: None, // not provided
             occurred_at: None,
+            occurred_until: None,
         };
         let profile = DecayProfile::default_profile();
 
@@ -382,6 +1157,7 @@ mod tests {
             valid_until: Some(utc(2025, 1, 1)), // before observed_at!
             temporal_precision: Some(TemporalPrecision::Day),
             occurred_at: None,
+            occurred_until: None,
         };
         let profile = DecayProfile::default_profile();
 
@@ -399,6 +1175,7 @@ mod tests {
             valid_until: None,
             temporal_precision: None,
             occurred_at: Some(utc(2025, 6, 1)), // after observed_at!
+            occurred_until: None,
         };
         let profile = DecayProfile::default_profile();
 
@@ -416,12 +1193,76 @@ mod tests {
             valid_until: None,
             temporal_precision: None,
             occurred_at: Some(utc(2025, 1, 1)), // before observed_at, valid
+            occurred_until: None,
         };
         let profile = DecayProfile::default_profile();
 
         let result = TemporalGate::validate(&input, &profile);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap().occurred_at, Some(utc(2025, 1, 1)));
+        let fields = result.unwrap();
+        assert_eq!(fields.occurred_at, Some(utc(2025, 1, 1)));
+        // No occurred_until: occurred_at stays a single point in time
+        assert_eq!(fields.occurred_until, None);
+    }
+
+    #[test]
+    fn gate_accepts_valid_occurred_range() {
+        let input = RawTemporalInput {
+            observed_at: Some(utc(2025, 1, 10)),
+            occurred_at: Some(utc(2025, 1, 5)),
+            occurred_until: Some(utc(2025, 1, 8)),
+            ..Default::default()
+        };
+        let fields = TemporalGate::validate(&input, &DecayProfile::default_profile()).unwrap();
+        assert_eq!(fields.occurred_at, Some(utc(2025, 1, 5)));
+        assert_eq!(fields.occurred_until, Some(utc(2025, 1, 8)));
+        assert!(TemporalGate::validate_fields(&fields).is_ok());
+
+        // A range ending exactly at observed_at is allowed
+        let input = RawTemporalInput {
+            occurred_until: Some(utc(2025, 1, 10)),
+            ..input
+        };
+        assert!(TemporalGate::validate(&input, &DecayProfile::default_profile()).is_ok());
+    }
+
+    #[test]
+    fn gate_rejects_occurred_range_spanning_observed_at() {
+        let input = RawTemporalInput {
+            observed_at: Some(utc(2025, 1, 6)),
+            occurred_at: Some(utc(2025, 1, 5)),
+            occurred_until: Some(utc(2025, 1, 8)), // event still running when observed
+            ..Default::default()
+        };
+        let err = TemporalGate::validate(&input, &DecayProfile::default_profile()).unwrap_err();
+        assert!(matches!(
+            err,
+            TemporalError::OccurredUntilAfterObservedAt { .. }
+        ));
+    }
+
+    #[test]
+    fn gate_rejects_inverted_or_open_start_occurred_range() {
+        let profile = DecayProfile::default_profile();
+        let inverted = RawTemporalInput {
+            observed_at: Some(utc(2025, 1, 10)),
+            occurred_at: Some(utc(2025, 1, 8)),
+            occurred_until: Some(utc(2025, 1, 5)),
+            ..Default::default()
+        };
+        assert!(matches!(
+            TemporalGate::validate(&inverted, &profile).unwrap_err(),
+            TemporalError::OccurredUntilBeforeOccurredAt { .. }
+        ));
+
+        let no_start = RawTemporalInput {
+            occurred_at: None,
+            ..inverted
+        };
+        assert!(matches!(
+            TemporalGate::validate(&no_start, &profile).unwrap_err(),
+            TemporalError::OccurredUntilWithoutOccurredAt { .. }
+        ));
     }
 
     #[test]
@@ -431,6 +1272,8 @@ mod tests {
             valid_until: utc(2025, 1, 1), // invalid!
             temporal_precision: TemporalPrecision::Day,
             occurred_at: None,
+            occurred_until: None,
+            confidence_factor: 1.0,
         };
 
         let result = TemporalGate::validate_fields(&fields);
@@ -442,7 +1285,7 @@ mod tests {
     #[test]
     fn project_status_decays_in_14_days() {
         let profile = DecayProfile::project_status();
-        assert_eq!(profile.half_life, Duration::days(14));
+        assert_eq!(profile.half_life(), Duration::days(14));
 
         let observed = utc(2025, 1, 1);
         let valid_until = profile.compute_valid_until(observed);
@@ -452,13 +1295,209 @@ mod tests {
     #[test]
     fn decision_never_decays() {
         let profile = DecayProfile::decision();
-        assert!(profile.half_life > Duration::days(365 * 50));
+        assert!(profile.half_life() > Duration::days(365 * 50));
     }
 
     #[test]
     fn signal_decays_in_7_days() {
         let profile = DecayProfile::signal();
-        assert_eq!(profile.half_life, Duration::days(7));
+        assert_eq!(profile.half_life(), Duration::days(7));
+    }
+
+    // === DecayModel variants ===
+
+    fn model_from_yaml(yaml: &str) -> DecayModel {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn linear_model_reaches_zero_at_valid_until() {
+        let model = model_from_yaml("kind: linear\nhalf_life_days: 10");
+        assert_eq!(
+            model,
+            DecayModel::Linear {
+                half_life: Duration::days(10)
+            }
+        );
+
+        let profile = DecayProfile::with_model(model);
+        let observed = utc(2025, 1, 1);
+        assert_eq!(
+            profile.compute_valid_until(observed),
+            observed + Duration::days(20)
+        );
+        let factor = |days| profile.model.confidence_factor(Duration::days(days));
+        assert!((factor(5) - 0.75).abs() < 1e-9);
+        assert!((factor(10) - 0.5).abs() < 1e-9);
+        assert!(factor(20).abs() < 1e-9);
+        assert!(factor(40).abs() < 1e-9);
+    }
+
+    #[test]
+    fn exponential_model_is_valid_for_two_half_lives() {
+        let model = model_from_yaml("kind: exponential\nlambda: 0.1");
+        let profile = DecayProfile::with_model(model);
+        let observed = utc(2025, 1, 1);
+
+        let window = (2.0 * std::f64::consts::LN_2 / 0.1 * 86_400.0).round() as i64;
+        assert_eq!(
+            profile.compute_valid_until(observed),
+            observed + Duration::seconds(window)
+        );
+        let at_ten = profile.model.confidence_factor(Duration::days(10));
+        assert!((at_ten - (-1.0_f64).exp()).abs() < 1e-9);
+
+        // The built-in profiles keep their exact windows
+        assert_eq!(
+            DecayProfile::default_profile().compute_valid_until(observed),
+            observed + Duration::days(180)
+        );
+    }
+
+    #[test]
+    fn step_function_model_holds_each_step() {
+        let model = model_from_yaml(
+            "kind: step_function\nsteps:\n  - { after_days: 30, factor: 0.2 }\n  - { after_days: 7, factor: 0.8 }",
+        );
+        let profile = DecayProfile::with_model(model);
+        let observed = utc(2025, 1, 1);
+
+        assert_eq!(
+            profile.compute_valid_until(observed),
+            observed + Duration::days(30)
+        );
+        assert_eq!(profile.half_life(), Duration::days(30));
+        let factor = |days| profile.model.confidence_factor(Duration::days(days));
+        assert!((factor(0) - 1.0).abs() < f64::EPSILON);
+        assert!((factor(7) - 0.8).abs() < f64::EPSILON);
+        assert!((factor(29) - 0.8).abs() < f64::EPSILON);
+        assert!((factor(30) - 0.2).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn never_model_does_not_decay() {
+        let profile = DecayProfile::with_model(model_from_yaml("kind: never"));
+        let observed = utc(2025, 1, 1);
+
+        assert_eq!(
+            profile.compute_valid_until(observed),
+            observed + Duration::days(365 * 100)
+        );
+        let factor = profile.model.confidence_factor(Duration::days(365 * 50));
+        assert!((factor - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn tiny_rates_and_huge_spans_clamp_to_the_never_window() {
+        let observed = utc(2025, 1, 1);
+        for yaml in [
+            "kind: exponential\nlambda: 1e-12",
+            "kind: exponential\nlambda: 1e-9",
+            "kind: linear\nhalf_life_days: 1e15",
+            "kind: linear\nhalf_life_days: .inf",
+            "kind: step_function\nsteps:\n  - { after_days: 1e15, factor: 0.5 }",
+        ] {
+            let profile = DecayProfile::with_model(model_from_yaml(yaml));
+            assert!(profile.half_life() <= NEVER_DECAYS_WINDOW, "{yaml}");
+            assert!(
+                profile.model.validity_window() <= NEVER_DECAYS_WINDOW * 2,
+                "{yaml}"
+            );
+            let fields = TemporalGate::validate(
+                &RawTemporalInput {
+                    observed_at: Some(observed),
+                    ..Default::default()
+                },
+                &profile,
+            )
+            .unwrap();
+            assert!(fields.valid_until > observed, "{yaml}");
+        }
+
+        let profile = DecayProfile::with_model(model_from_yaml("kind: never"));
+        assert_eq!(
+            profile.compute_valid_until(DateTime::<Utc>::MAX_UTC),
+            DateTime::<Utc>::MAX_UTC
+        );
+    }
+
+    #[test]
+    fn decay_model_rejects_invalid_parameters() {
+        for yaml in [
+            "kind: linear\nhalf_life_days: 0",
+            "kind: exponential\nlambda: -1",
+            "kind: step_function\nsteps:\n  - { after_days: 7, factor: 1.5 }",
+            "kind: sigmoid",
+        ] {
+            assert!(
+                serde_yaml::from_str::<DecayModel>(yaml).is_err(),
+                "accepted {yaml}"
+            );
+        }
+    }
+
+    #[test]
+    fn gate_stores_decay_accrued_before_observation() {
+        let profile = DecayProfile::with_model(DecayModel::Linear {
+            half_life: Duration::days(10),
+        });
+        let input = RawTemporalInput {
+            observed_at: Some(utc(2025, 1, 11)),
+            occurred_at: Some(utc(2025, 1, 6)),
+            ..Default::default()
+        };
+
+        let fields = TemporalGate::validate(&input, &profile).unwrap();
+        assert!((fields.confidence_factor - 0.75).abs() < 1e-9);
+        let now = fields.effective_confidence(0.8, &profile, utc(2025, 1, 11));
+        assert!((now - 0.6).abs() < 1e-9);
+
+        let fresh = RawTemporalInput {
+            occurred_at: None,
+            ..input
+        };
+        let fields = TemporalGate::validate(&fresh, &profile).unwrap();
+        assert!((fields.confidence_factor - 1.0).abs() < f64::EPSILON);
+    }
+
+    // === TemporalFields::effective_confidence ===
+
+    fn fields_observed(at: DateTime<Utc>) -> TemporalFields {
+        TemporalFields {
+            observed_at: at,
+            valid_until: at + Duration::days(180),
+            temporal_precision: TemporalPrecision::Day,
+            occurred_at: None,
+            occurred_until: None,
+            confidence_factor: 1.0,
+        }
+    }
+
+    #[test]
+    fn effective_confidence_halves_each_half_life() {
+        let fields = fields_observed(utc(2025, 1, 1));
+        let profile = DecayProfile::default_profile();
+
+        let one = fields.effective_confidence(0.9, &profile, utc(2025, 1, 1) + Duration::days(90));
+        let two = fields.effective_confidence(0.9, &profile, utc(2025, 1, 1) + Duration::days(180));
+        assert!((one - 0.45).abs() < 1e-9, "got {one}");
+        assert!((two - 0.225).abs() < 1e-9, "got {two}");
+    }
+
+    #[test]
+    fn effective_confidence_favours_recent_observations() {
+        let profile = DecayProfile::default_profile();
+        let now = utc(2025, 7, 1);
+        let old = fields_observed(utc(2025, 1, 1)).effective_confidence(0.9, &profile, now);
+        let recent = fields_observed(utc(2025, 6, 30)).effective_confidence(0.9, &profile, now);
+        assert!(recent > old);
+    }
+
+    #[test]
+    fn effective_confidence_ignores_future_observations() {
+        let fields = fields_observed(utc(2025, 6, 1));
+        let conf = fields.effective_confidence(0.8, &DecayProfile::signal(), utc(2025, 1, 1));
+        assert!((conf - 0.8).abs() < f64::EPSILON);
     }
 
     // === DecayModel tests (T-110.1) ===
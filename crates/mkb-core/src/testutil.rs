@@ -0,0 +1,245 @@
+//! Proptest strategies for core types.
+//!
+//! Generated documents are the ones [`write_document`] promises to round-trip:
+//! bodies are empty or end in a newline, never start with `---` and hold no
+//! null bytes, and tags contain no commas.
+//!
+//! [`write_document`]: crate::frontmatter::write_document
+//!
+//! [`Document`] equality only compares IDs, so tests that care about content
+//! use [`assert_doc_eq!`] or [`doc_diff`] instead of `assert_eq!`.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, TimeZone, Utc};
+use proptest::collection::{hash_map, vec};
+use proptest::option;
+use proptest::prelude::*;
+
+use crate::document::{Document, ProvenanceRecord, ProvenanceType};
+use crate::link::Link;
+use crate::temporal::{TemporalFields, TemporalPrecision};
+
+/// Short text mixing YAML-significant characters, quotes and non-ASCII.
+const TEXT: &str = "[a-zA-Z0-9 :#\"'\\-_.,!?%&*|>{}\\[\\]éü日本🚀]{0,24}";
+
+/// Markdown-ish body lines, including headings, lists and `---` rules.
+const BODY_LINE: &str = "(#{0,3} |- |---|> )?[a-zA-Z0-9 :#\"'\\-_.,!?éü日本🚀]{0,40}";
+
+/// Whole-second timestamps between 2000 and 2100.
+fn arb_datetime() -> impl Strategy<Value = DateTime<Utc>> {
+    (946_684_800i64..4_102_444_800).prop_map(|secs| Utc.timestamp_opt(secs, 0).unwrap())
+}
+
+fn arb_precision() -> impl Strategy<Value = TemporalPrecision> {
+    prop_oneof![
+        Just(TemporalPrecision::Exact),
+        Just(TemporalPrecision::Day),
+        Just(TemporalPrecision::Week),
+        Just(TemporalPrecision::Month),
+        Just(TemporalPrecision::Quarter),
+        Just(TemporalPrecision::Approximate),
+        Just(TemporalPrecision::Inferred),
+    ]
+}
+
+fn arb_provenance() -> impl Strategy<Value = ProvenanceRecord> {
+    let source_type = prop_oneof![
+        Just(ProvenanceType::Manual),
+        Just(ProvenanceType::Import),
+        Just(ProvenanceType::LlmGenerated),
+        Just(ProvenanceType::ApiIngestion),
+        Just(ProvenanceType::Watcher),
+    ];
+    (
+        source_type,
+        option::of(TEXT),
+        option::of(TEXT),
+        TEXT,
+        option::of(TEXT),
+        vec(TEXT, 0..3),
+    )
+        .prop_map(
+            |(
+                source_type,
+                source_uri,
+                ingested_by,
+                ingested_via,
+                original_filename,
+                transformation_log,
+            )| ProvenanceRecord {
+                source_type,
+                source_uri,
+                ingested_by,
+                ingested_via,
+                original_filename,
+                transformation_log,
+            },
+        )
+}
+
+fn arb_temporal() -> impl Strategy<Value = TemporalFields> {
+    (
+        arb_datetime(),
+        arb_datetime(),
+        arb_precision(),
+        option::of(arb_datetime()),
+        option::of(arb_datetime()),
+    )
+        .prop_map(
+            |(observed_at, valid_until, temporal_precision, occurred_at, occurred_until)| {
+                TemporalFields {
+                    observed_at,
+                    valid_until,
+                    temporal_precision,
+                    occurred_at,
+                    occurred_until,
+                    confidence_factor: 1.0,
+                }
+            },
+        )
+}
+
+fn arb_json_value() -> impl Strategy<Value = serde_json::Value> {
+    prop_oneof![
+        TEXT.prop_map(serde_json::Value::from),
+        any::<i64>().prop_map(serde_json::Value::from),
+        any::<bool>().prop_map(serde_json::Value::from),
+    ]
+}
+
+fn arb_link() -> impl Strategy<Value = Link> {
+    (
+        "[a-z_]{1,12}",
+        "[a-z0-9/\\-]{1,24}",
+        arb_datetime(),
+        option::of(hash_map("[a-z_]{1,8}", arb_json_value(), 0..3)),
+    )
+        .prop_map(|(rel, target, observed_at, metadata)| Link {
+            rel,
+            target,
+            observed_at,
+            metadata: metadata.map(|m| serde_json::Value::Object(m.into_iter().collect())),
+        })
+}
+
+fn arb_body() -> impl Strategy<Value = String> {
+    vec(BODY_LINE, 0..6).prop_map(|lines| {
+        let body: String = lines.iter().map(|line| format!("{line}\n")).collect();
+        if body.starts_with("---") {
+            format!("text\n{body}")
+        } else {
+            body
+        }
+    })
+}
+
+fn arb_fields() -> impl Strategy<Value = HashMap<String, serde_json::Value>> {
+    hash_map("[a-z][a-z_]{0,11}", arb_json_value(), 0..4)
+}
+
+impl Arbitrary for Document {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        let identity = ("[a-z0-9\\-]{1,24}", "[a-z_]{1,12}", TEXT);
+        let system = (arb_datetime(), arb_datetime(), arb_temporal());
+        let provenance = (
+            option::of("[0-9a-f]{64}"),
+            0.0..=1.0f64,
+            option::of(arb_provenance()),
+        );
+        let supersession = (
+            option::of("[a-z0-9\\-]{1,24}"),
+            option::of("[a-z0-9\\-]{1,24}"),
+            option::of(arb_datetime()),
+        );
+        let content = (
+            arb_fields(),
+            vec("[a-zA-Z0-9 \\-_éü日本]{1,16}", 0..4),
+            vec(arb_link(), 0..3),
+            arb_body(),
+        );
+
+        (identity, system, provenance, supersession, content)
+            .prop_map(
+                |(
+                    (id, doc_type, title),
+                    (created_at, modified_at, temporal),
+                    (source_hash, confidence, provenance),
+                    (supersedes, superseded_by, superseded_at),
+                    (fields, tags, links, body),
+                )| Document {
+                    id,
+                    doc_type,
+                    title,
+                    created_at,
+                    modified_at,
+                    temporal,
+                    source_hash,
+                    confidence,
+                    confidence_override: false,
+                    provenance,
+                    supersedes,
+                    superseded_by,
+                    superseded_at,
+                    fields,
+                    tags,
+                    links,
+                    body,
+                },
+            )
+            .boxed()
+    }
+}
+
+/// Every field that differs between two documents, as `field: left != right`.
+pub fn doc_diff(left: &Document, right: &Document) -> Vec<String> {
+    let mut diff = Vec::new();
+    macro_rules! compare {
+        ($($field:ident),+) => {$(
+            if left.$field != right.$field {
+                diff.push(format!(
+                    "{}: {:?} != {:?}",
+                    stringify!($field),
+                    left.$field,
+                    right.$field
+                ));
+            }
+        )+};
+    }
+    compare!(
+        id,
+        doc_type,
+        title,
+        created_at,
+        modified_at,
+        temporal,
+        source_hash,
+        confidence,
+        confidence_override,
+        provenance,
+        supersedes,
+        superseded_by,
+        superseded_at,
+        fields,
+        tags,
+        links,
+        body
+    );
+    diff
+}
+
+/// Assert two documents match field by field, listing every difference.
+macro_rules! assert_doc_eq {
+    ($left:expr, $right:expr $(,)?) => {{
+        let diff = $crate::testutil::doc_diff(&$left, &$right);
+        assert!(
+            diff.is_empty(),
+            "documents differ:\n  {}",
+            diff.join("\n  ")
+        );
+    }};
+}
+pub(crate) use assert_doc_eq;
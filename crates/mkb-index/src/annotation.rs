@@ -0,0 +1,279 @@
+//! Notes and flags attached to indexed documents.
+//!
+//! Annotations such as `reviewed` or `needs-update` live only in the index
+//! database, so marking a document never touches its markdown file. They
+//! are deleted with their document and carried over when the index is
+//! rebuilt.
+
+use std::path::Path;
+
+use rusqlite::{params, Connection};
+
+use mkb_core::error::MkbError;
+
+use crate::IndexManager;
+
+/// One annotation on a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    pub id: i64,
+    pub doc_id: String,
+    /// The flag the annotation sets, e.g. `reviewed`; what
+    /// `ANNOTATED('reviewed')` matches.
+    pub annotation_type: String,
+    /// Free-form note; may be empty.
+    pub content: String,
+    /// When the annotation was added (RFC 3339).
+    pub created_at: String,
+    pub created_by: String,
+}
+
+impl IndexManager {
+    /// Attach an annotation to an indexed document and return its ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if `doc_id` is not indexed or the
+    /// annotation cannot be written.
+    pub fn add_annotation(
+        &self,
+        doc_id: &str,
+        annotation_type: &str,
+        content: &str,
+        created_by: &str,
+    ) -> Result<i64, MkbError> {
+        if self.query_by_id(doc_id)?.is_none() {
+            return Err(MkbError::Index(format!(
+                "Cannot annotate '{doc_id}': document is not indexed"
+            )));
+        }
+        self.conn
+            .execute(
+                "INSERT INTO annotations (doc_id, annotation_type, content, created_at, created_by)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    doc_id,
+                    annotation_type,
+                    content,
+                    chrono::Utc::now().to_rfc3339(),
+                    created_by
+                ],
+            )
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// The annotations on `doc_id`, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the query fails.
+    pub fn get_annotations(&self, doc_id: &str) -> Result<Vec<Annotation>, MkbError> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, doc_id, annotation_type, content, created_at, created_by
+                 FROM annotations WHERE doc_id = ?1 ORDER BY id",
+            )
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        let rows = stmt
+            .query_map(params![doc_id], |row| {
+                Ok(Annotation {
+                    id: row.get(0)?,
+                    doc_id: row.get(1)?,
+                    annotation_type: row.get(2)?,
+                    content: row.get(3)?,
+                    created_at: row.get(4)?,
+                    created_by: row.get(5)?,
+                })
+            })
+            .map_err(|e| MkbError::Index(e.to_string()))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        Ok(rows)
+    }
+
+    /// Remove the annotation with ID `id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if there is no such annotation or the
+    /// delete fails.
+    pub fn delete_annotation(&self, id: i64) -> Result<(), MkbError> {
+        let deleted = self
+            .conn
+            .execute("DELETE FROM annotations WHERE id = ?1", params![id])
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        if deleted == 0 {
+            return Err(MkbError::Index(format!("No annotation with ID {id}")));
+        }
+        Ok(())
+    }
+}
+
+/// Copy the annotations in the index at `live` into the freshly rebuilt one
+/// at `rebuilt`, keeping their IDs. Annotations on documents the rebuild did
+/// not index are dropped.
+///
+/// A live index that is missing or unreadable has nothing to carry over.
+pub(crate) fn carry_annotations(rebuilt: &Path, live: &Path) -> Result<(), MkbError> {
+    if !live.exists() {
+        return Ok(());
+    }
+    let conn = Connection::open(rebuilt).map_err(|e| MkbError::Index(e.to_string()))?;
+    let carried = conn
+        .execute(
+            "ATTACH DATABASE ?1 AS live",
+            params![live.to_string_lossy()],
+        )
+        .and_then(|_| {
+            conn.execute(
+                "INSERT INTO main.annotations
+                     (id, doc_id, annotation_type, content, created_at, created_by)
+                 SELECT id, doc_id, annotation_type, content, created_at, created_by
+                 FROM live.annotations
+                 WHERE doc_id IN (SELECT id FROM main.documents)",
+                [],
+            )
+        });
+    if let Err(e) = carried {
+        tracing::warn!("Annotations not carried over from {}: {e}", live.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::make_doc;
+    use mkb_vault::Vault;
+
+    #[test]
+    fn annotations_are_listed_in_order_and_deletable() {
+        let index = IndexManager::in_memory().unwrap();
+        index
+            .index_document(&make_doc("proj-alpha-001", "project", "Alpha", "body"))
+            .unwrap();
+
+        let reviewed = index
+            .add_annotation("proj-alpha-001", "reviewed", "Verified by Q2 review", "ana")
+            .unwrap();
+        let flagged = index
+            .add_annotation("proj-alpha-001", "needs-update", "", "ana")
+            .unwrap();
+
+        let annotations = index.get_annotations("proj-alpha-001").unwrap();
+        assert_eq!(annotations.len(), 2);
+        assert_eq!(annotations[0].id, reviewed);
+        assert_eq!(annotations[0].annotation_type, "reviewed");
+        assert_eq!(annotations[0].content, "Verified by Q2 review");
+        assert_eq!(annotations[0].created_by, "ana");
+        assert!(chrono::DateTime::parse_from_rfc3339(&annotations[0].created_at).is_ok());
+
+        index.delete_annotation(flagged).unwrap();
+        assert_eq!(index.get_annotations("proj-alpha-001").unwrap().len(), 1);
+        assert!(matches!(
+            index.delete_annotation(flagged),
+            Err(MkbError::Index(_))
+        ));
+    }
+
+    #[test]
+    fn annotating_an_unindexed_document_fails() {
+        let index = IndexManager::in_memory().unwrap();
+        let err = index
+            .add_annotation("proj-missing-001", "reviewed", "", "ana")
+            .unwrap_err();
+        assert!(err.to_string().contains("proj-missing-001"), "{err}");
+    }
+
+    #[test]
+    fn annotations_persist_across_reopen() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("mkb.db");
+        {
+            let index = IndexManager::open(&path).unwrap();
+            index
+                .index_document(&make_doc("proj-alpha-001", "project", "Alpha", "body"))
+                .unwrap();
+            index
+                .add_annotation("proj-alpha-001", "reviewed", "ok", "ana")
+                .unwrap();
+        }
+
+        let reopened = IndexManager::open(&path).unwrap();
+        let annotations = reopened.get_annotations("proj-alpha-001").unwrap();
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(annotations[0].annotation_type, "reviewed");
+    }
+
+    #[test]
+    fn annotations_go_with_their_document_but_survive_reindexing() {
+        let index = IndexManager::in_memory().unwrap();
+        let doc = make_doc("proj-alpha-001", "project", "Alpha", "body");
+        index.index_document(&doc).unwrap();
+        index
+            .add_annotation("proj-alpha-001", "reviewed", "", "ana")
+            .unwrap();
+
+        index.index_document(&doc).unwrap();
+        assert_eq!(index.get_annotations("proj-alpha-001").unwrap().len(), 1);
+
+        index.remove_document("proj-alpha-001").unwrap();
+        index.index_document(&doc).unwrap();
+        assert!(index.get_annotations("proj-alpha-001").unwrap().is_empty());
+    }
+
+    #[test]
+    fn pruned_documents_lose_their_annotations() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        let index = IndexManager::in_memory().unwrap();
+        index
+            .index_document(&make_doc("proj-gone-001", "project", "Gone", "body"))
+            .unwrap();
+        index
+            .add_annotation("proj-gone-001", "reviewed", "", "ana")
+            .unwrap();
+
+        assert_eq!(index.prune_orphans(&vault).unwrap(), 1);
+        let remaining: i64 = index
+            .conn
+            .query_row("SELECT COUNT(*) FROM annotations", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(remaining, 0);
+    }
+
+    #[test]
+    fn rebuild_keeps_annotations_of_documents_still_in_the_vault() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        let index_path = dir.path().join(".mkb").join("index").join("mkb.db");
+        vault
+            .create(&make_doc("proj-alpha-001", "project", "Alpha", "body"))
+            .unwrap();
+        {
+            let index = IndexManager::open(&index_path).unwrap();
+            index
+                .index_document(&make_doc("proj-alpha-001", "project", "Alpha", "body"))
+                .unwrap();
+            index
+                .index_document(&make_doc("proj-gone-001", "project", "Gone", "body"))
+                .unwrap();
+            index
+                .add_annotation("proj-alpha-001", "reviewed", "ok", "ana")
+                .unwrap();
+            index
+                .add_annotation("proj-gone-001", "reviewed", "", "ana")
+                .unwrap();
+        }
+
+        IndexManager::rebuild_from_vault(&vault, |_, _| {}).unwrap();
+
+        let index = IndexManager::open(&index_path).unwrap();
+        let annotations = index.get_annotations("proj-alpha-001").unwrap();
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(annotations[0].content, "ok");
+        assert!(index.get_annotations("proj-gone-001").unwrap().is_empty());
+    }
+}
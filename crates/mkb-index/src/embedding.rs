@@ -0,0 +1,197 @@
+//! Embedding providers for semantic search.
+//!
+//! [`EmbeddingProvider`] turns text into vectors of [`EMBEDDING_DIM`]
+//! floats. Each provider names the model it uses, which is stored next to
+//! every embedding so vectors from different models are never compared.
+
+use std::future::Future;
+
+use mkb_core::error::EmbeddingError;
+
+use crate::EMBEDDING_DIM;
+
+/// Generates embeddings for batches of text.
+pub trait EmbeddingProvider {
+    /// Model name recorded alongside each stored embedding.
+    fn model(&self) -> &str;
+
+    /// Embed `texts`, returning one vector per input in the same order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EmbeddingError`] if the embeddings cannot be produced.
+    fn embed(
+        &self,
+        texts: &[&str],
+    ) -> impl Future<Output = Result<Vec<Vec<f32>>, EmbeddingError>> + Send;
+}
+
+/// Generate a deterministic mock embedding from text using SHA-256.
+///
+/// This is the Rust port of `MockEmbeddingBackend.generate()` from Python.
+/// Produces the same deterministic vector for the same input text, suitable
+/// for testing without API calls.
+///
+/// Component `i` is the first four bytes of `SHA-256("{text}-{i}")` read as
+/// a little-endian `f32`, scaled into `[-1, 1]`. The vector is then
+/// normalized to unit length. Similar texts do not get similar vectors.
+#[must_use]
+pub fn mock_embedding(text: &str) -> Vec<f32> {
+    use sha2::{Digest, Sha256};
+
+    let mut vec = Vec::with_capacity(EMBEDDING_DIM);
+    for i in 0..EMBEDDING_DIM {
+        let mut hasher = Sha256::new();
+        hasher.update(format!("{text}-{i}").as_bytes());
+        let hash = hasher.finalize();
+        // Interpret first 4 bytes as f32
+        let val = f32::from_le_bytes([hash[0], hash[1], hash[2], hash[3]]);
+        // Clamp to [-1, 1]. NaN maps to 1.0, as Python's max(-1, min(1, nan)) does.
+        let val = if val.is_nan() {
+            1.0
+        } else {
+            (val.clamp(-1.0e38, 1.0e38) / 1.0e38).clamp(-1.0, 1.0)
+        };
+        vec.push(val);
+    }
+    // Normalize
+    let norm: f32 = vec.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for v in &mut vec {
+            *v /= norm;
+        }
+    }
+    vec
+}
+
+/// Offline provider backed by [`mock_embedding`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MockEmbeddingProvider;
+
+impl EmbeddingProvider for MockEmbeddingProvider {
+    fn model(&self) -> &str {
+        "mock"
+    }
+
+    async fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>, EmbeddingError> {
+        Ok(texts.iter().map(|t| mock_embedding(t)).collect())
+    }
+}
+
+/// Provider calling OpenAI's `/v1/embeddings` endpoint.
+///
+/// The HTTP request is blocking, so run it where blocking the current
+/// thread is acceptable.
+#[cfg(feature = "openai")]
+#[derive(Debug, Clone)]
+pub struct OpenAIEmbeddingProvider {
+    api_key: String,
+    model: String,
+    endpoint: String,
+}
+
+#[cfg(feature = "openai")]
+impl OpenAIEmbeddingProvider {
+    /// Default model, whose vectors have [`EMBEDDING_DIM`] dimensions.
+    pub const DEFAULT_MODEL: &'static str = "text-embedding-3-small";
+
+    /// Provider for `text-embedding-3-small` on the public API.
+    #[must_use]
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            api_key: api_key.into(),
+            model: Self::DEFAULT_MODEL.to_string(),
+            endpoint: "https://api.openai.com/v1/embeddings".to_string(),
+        }
+    }
+
+    /// Send requests to `endpoint` instead, e.g. a proxy or compatible server.
+    #[must_use]
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into();
+        self
+    }
+}
+
+#[cfg(feature = "openai")]
+impl EmbeddingProvider for OpenAIEmbeddingProvider {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>, EmbeddingError> {
+        #[derive(serde::Deserialize)]
+        struct Response {
+            data: Vec<Item>,
+        }
+        #[derive(serde::Deserialize)]
+        struct Item {
+            index: usize,
+            embedding: Vec<f32>,
+        }
+
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let body = serde_json::json!({ "model": self.model, "input": texts });
+        let mut response: Response = ureq::post(&self.endpoint)
+            .header("Authorization", &format!("Bearer {}", self.api_key))
+            .send_json(&body)
+            .map_err(|e| EmbeddingError::Request(e.to_string()))?
+            .body_mut()
+            .read_json()
+            .map_err(|e| EmbeddingError::Response(e.to_string()))?;
+
+        if response.data.len() != texts.len() {
+            return Err(EmbeddingError::Response(format!(
+                "expected {} embeddings, got {}",
+                texts.len(),
+                response.data.len()
+            )));
+        }
+        response.data.sort_by_key(|item| item.index);
+        response
+            .data
+            .into_iter()
+            .map(|item| {
+                if item.embedding.len() == EMBEDDING_DIM {
+                    Ok(item.embedding)
+                } else {
+                    Err(EmbeddingError::Dimension {
+                        expected: EMBEDDING_DIM,
+                        actual: item.embedding.len(),
+                    })
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn mock_provider_embeds_each_text_in_order() {
+        let provider = MockEmbeddingProvider;
+        let vectors = provider.embed(&["alpha", "beta"]).await.unwrap();
+
+        assert_eq!(provider.model(), "mock");
+        assert_eq!(
+            vectors,
+            vec![mock_embedding("alpha"), mock_embedding("beta")]
+        );
+        assert!(vectors.iter().all(|v| v.len() == EMBEDDING_DIM));
+    }
+
+    #[test]
+    fn mock_embedding_is_deterministic_and_unit_length() {
+        let a = mock_embedding("machine learning");
+        assert_eq!(a, mock_embedding("machine learning"));
+        assert_ne!(a, mock_embedding("gardening"));
+
+        let norm: f32 = a.iter().map(|v| v * v).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-4, "norm {norm}");
+    }
+}
@@ -0,0 +1,257 @@
+//! Opt-in log of executed MKQL queries.
+//!
+//! Recording is off by default so ordinary queries pay no write cost. The
+//! switch and the log both live in the index database, so
+//! `mkb index rebuild` clears them.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use rusqlite::{params, OptionalExtension};
+
+use mkb_core::error::MkbError;
+
+use crate::IndexManager;
+
+/// `index_settings` key holding the query history switch.
+const QUERY_HISTORY_SETTING: &str = "query_history";
+
+/// One recorded query execution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryHistoryRow {
+    pub id: i64,
+    pub mkql: String,
+    /// When the query ran (RFC 3339).
+    pub executed_at: String,
+    pub duration_ms: f64,
+    pub result_count: u64,
+}
+
+/// Latency of every recorded execution of one MKQL string.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryPatternStat {
+    pub mkql: String,
+    pub executions: u64,
+    pub p50_ms: f64,
+    pub p99_ms: f64,
+}
+
+impl IndexManager {
+    /// Whether [`record_query`](Self::record_query) writes to the history.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the setting cannot be read.
+    pub fn query_history_enabled(&self) -> Result<bool, MkbError> {
+        let value: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT value FROM index_settings WHERE key = ?1",
+                params![QUERY_HISTORY_SETTING],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        Ok(value.as_deref() == Some("on"))
+    }
+
+    /// Turn query history recording on or off. Existing entries are kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the setting cannot be written.
+    pub fn set_query_history_enabled(&self, enabled: bool) -> Result<(), MkbError> {
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO index_settings (key, value) VALUES (?1, ?2)",
+                params![QUERY_HISTORY_SETTING, if enabled { "on" } else { "off" }],
+            )
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Log one execution of `mkql`. Does nothing unless query history is
+    /// enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the entry cannot be written.
+    pub fn record_query(
+        &self,
+        mkql: &str,
+        duration: Duration,
+        count: usize,
+    ) -> Result<(), MkbError> {
+        if !self.query_history_enabled()? {
+            return Ok(());
+        }
+        self.conn
+            .execute(
+                "INSERT INTO query_history (mkql, executed_at, duration_ms, result_count)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    mkql,
+                    chrono::Utc::now().to_rfc3339(),
+                    duration.as_secs_f64() * 1000.0,
+                    count as i64
+                ],
+            )
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        Ok(())
+    }
+
+    /// The `limit` most recent recorded queries, newest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the query fails.
+    pub fn query_history(&self, limit: usize) -> Result<Vec<QueryHistoryRow>, MkbError> {
+        self.history_rows("ORDER BY id DESC", limit)
+    }
+
+    /// The `limit` slowest recorded executions, slowest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the query fails.
+    pub fn slowest_queries(&self, limit: usize) -> Result<Vec<QueryHistoryRow>, MkbError> {
+        self.history_rows("ORDER BY duration_ms DESC, id DESC", limit)
+    }
+
+    /// The `limit` most often recorded MKQL strings with their p50 and p99
+    /// latencies, most frequent first.
+    ///
+    /// Percentiles use the nearest-rank method over every recorded
+    /// execution of the string.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the query fails.
+    pub fn frequent_queries(&self, limit: usize) -> Result<Vec<QueryPatternStat>, MkbError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT mkql, duration_ms FROM query_history ORDER BY mkql, duration_ms")
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        let mut durations: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
+            })
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        for row in rows {
+            let (mkql, ms) = row.map_err(|e| MkbError::Index(e.to_string()))?;
+            durations.entry(mkql).or_default().push(ms);
+        }
+
+        let mut stats: Vec<QueryPatternStat> = durations
+            .into_iter()
+            .map(|(mkql, sorted)| QueryPatternStat {
+                executions: sorted.len() as u64,
+                p50_ms: percentile(&sorted, 0.50),
+                p99_ms: percentile(&sorted, 0.99),
+                mkql,
+            })
+            .collect();
+        // Stable, so ties stay in MKQL order
+        stats.sort_by_key(|s| std::cmp::Reverse(s.executions));
+        stats.truncate(limit);
+        Ok(stats)
+    }
+
+    fn history_rows(&self, order: &str, limit: usize) -> Result<Vec<QueryHistoryRow>, MkbError> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT id, mkql, executed_at, duration_ms, result_count
+                 FROM query_history {order} LIMIT ?1"
+            ))
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        let rows = stmt
+            .query_map(params![limit as i64], |row| {
+                Ok(QueryHistoryRow {
+                    id: row.get(0)?,
+                    mkql: row.get(1)?,
+                    executed_at: row.get(2)?,
+                    duration_ms: row.get(3)?,
+                    result_count: row.get::<_, i64>(4)? as u64,
+                })
+            })
+            .map_err(|e| MkbError::Index(e.to_string()))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        Ok(rows)
+    }
+}
+
+/// Nearest-rank percentile of ascending, non-empty `sorted`.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_query_is_off_by_default() {
+        let index = IndexManager::in_memory().unwrap();
+        assert!(!index.query_history_enabled().unwrap());
+
+        index
+            .record_query("SELECT * FROM project", Duration::from_millis(5), 3)
+            .unwrap();
+        assert!(index.query_history(10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn query_history_lists_newest_first() {
+        let index = IndexManager::in_memory().unwrap();
+        index.set_query_history_enabled(true).unwrap();
+        index
+            .record_query("SELECT * FROM project", Duration::from_millis(5), 3)
+            .unwrap();
+        index
+            .record_query("SELECT * FROM meeting", Duration::from_millis(12), 1)
+            .unwrap();
+
+        let history = index.query_history(10).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].mkql, "SELECT * FROM meeting");
+        assert_eq!(history[0].result_count, 1);
+        assert!((history[0].duration_ms - 12.0).abs() < 1e-9);
+        assert_eq!(index.query_history(1).unwrap().len(), 1);
+
+        index.set_query_history_enabled(false).unwrap();
+        index
+            .record_query("SELECT * FROM person", Duration::from_millis(1), 0)
+            .unwrap();
+        assert_eq!(index.query_history(10).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn slowest_and_frequent_queries() {
+        let index = IndexManager::in_memory().unwrap();
+        index.set_query_history_enabled(true).unwrap();
+        for ms in [10, 20, 30, 40] {
+            index
+                .record_query("SELECT * FROM project", Duration::from_millis(ms), 1)
+                .unwrap();
+        }
+        index
+            .record_query("SELECT * FROM meeting", Duration::from_millis(100), 1)
+            .unwrap();
+
+        let slowest = index.slowest_queries(2).unwrap();
+        assert_eq!(slowest[0].mkql, "SELECT * FROM meeting");
+        assert!((slowest[1].duration_ms - 40.0).abs() < 1e-9);
+
+        let frequent = index.frequent_queries(10).unwrap();
+        assert_eq!(frequent.len(), 2);
+        assert_eq!(frequent[0].mkql, "SELECT * FROM project");
+        assert_eq!(frequent[0].executions, 4);
+        assert!((frequent[0].p50_ms - 20.0).abs() < 1e-9);
+        assert!((frequent[0].p99_ms - 40.0).abs() < 1e-9);
+        assert_eq!(index.frequent_queries(1).unwrap().len(), 1);
+    }
+}
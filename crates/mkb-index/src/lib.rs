@@ -7,19 +7,45 @@
 //! - FTS5 virtual table for full-text content search
 //! - Temporal columns for time-based queries
 
-use std::path::Path;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use rusqlite::ffi::sqlite3_auto_extension;
-use rusqlite::{params, types::Value as SqlValue, Connection};
+use rusqlite::{params, types::Value as SqlValue, Connection, OptionalExtension};
+use serde::Serialize;
 use sqlite_vec::sqlite3_vec_init;
 use zerocopy::IntoBytes;
 
 use mkb_core::document::Document;
 use mkb_core::error::MkbError;
+use mkb_core::frontmatter;
+use mkb_core::link::{self, LinkEdge, LinkGraph};
+use mkb_core::schema::{MigrationReport, SchemaMigration};
+use mkb_core::temporal::TemporalPrecision;
+use mkb_vault::watcher::{BatchFileEvent, VaultEvent};
+use mkb_vault::Vault;
+
+mod annotation;
+mod embedding;
+mod history;
+mod snapshot;
+mod stream;
+
+pub use annotation::Annotation;
+#[cfg(feature = "openai")]
+pub use embedding::OpenAIEmbeddingProvider;
+pub use embedding::{mock_embedding, EmbeddingProvider, MockEmbeddingProvider};
+pub use history::{QueryHistoryRow, QueryPatternStat};
+pub use snapshot::TemporalInterpolator;
+pub use stream::{SqlRow, SqlRowStream};
 
 /// Embedding dimension for text-embedding-3-small (OpenAI).
 pub const EMBEDDING_DIM: usize = 1536;
 
+type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
 /// Register sqlite-vec extension globally. Safe to call multiple times.
 fn ensure_vec_extension() {
     use std::sync::Once;
@@ -30,6 +56,118 @@ fn ensure_vec_extension() {
     });
 }
 
+/// Reject confidence bounds outside `[0.0, 1.0]` (including NaN).
+fn check_confidence(value: f64) -> Result<(), MkbError> {
+    if (0.0..=1.0).contains(&value) {
+        Ok(())
+    } else {
+        Err(MkbError::Index("confidence out of range".to_string()))
+    }
+}
+
+/// Split a stored comma-separated tag column into trimmed, non-empty tags.
+pub fn parse_tags(stored: &str) -> Vec<String> {
+    stored
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Read a nullable tag column from `row` as a parsed tag list.
+fn tags_column(row: &rusqlite::Row<'_>, idx: usize) -> rusqlite::Result<Vec<String>> {
+    Ok(row
+        .get::<_, Option<String>>(idx)?
+        .map(|s| parse_tags(&s))
+        .unwrap_or_default())
+}
+
+/// The age label of the `observed_at` and `temporal_precision` columns at
+/// `observed_idx` and `precision_idx`, as of now.
+fn age_label_column(
+    row: &rusqlite::Row<'_>,
+    observed_idx: usize,
+    precision_idx: usize,
+) -> rusqlite::Result<String> {
+    let observed_at: String = row.get(observed_idx)?;
+    let precision: String = row.get(precision_idx)?;
+    let Ok(observed_at) = chrono::DateTime::parse_from_rfc3339(&observed_at) else {
+        return Ok(String::new());
+    };
+    let precision = TemporalPrecision::from_name(&precision).unwrap_or_default();
+    Ok(precision.age_label(observed_at.with_timezone(&chrono::Utc), chrono::Utc::now()))
+}
+
+/// A file's modification time in nanoseconds since the Unix epoch, if the
+/// filesystem reports one.
+fn file_mtime(path: &Path) -> Option<i64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    let since_epoch = modified.duration_since(std::time::UNIX_EPOCH).ok()?;
+    i64::try_from(since_epoch.as_nanos()).ok()
+}
+
+/// `file` relative to the vault `root`, as stored in `indexed_files`.
+fn relative_path(root: &Path, file: &Path) -> String {
+    file.strip_prefix(root)
+        .unwrap_or(file)
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// What happened to a single file during a rebuild or reindex.
+enum FileOutcome {
+    Indexed,
+    Skipped,
+    Failed,
+}
+
+/// Columns of the `documents` table, in the order [`IndexManager::merge_from`]
+/// copies them.
+const DOCUMENT_COLUMNS: &str = "id, doc_type, title, observed_at, valid_until, \
+    temporal_precision, occurred_at, occurred_until, created_at, modified_at, confidence, \
+    source, provenance_type, supersedes, superseded_by, tags, body, effective_confidence";
+
+/// A `documents` row as [`IndexManager::merge_from`] copies it.
+struct DocumentRow {
+    id: SqlValue,
+    modified_at: SqlValue,
+    /// Every column, in [`DOCUMENT_COLUMNS`] order.
+    values: Vec<SqlValue>,
+}
+
+/// Whether the RFC 3339 timestamp `candidate` is later than `current`.
+/// A timestamp that does not parse is older than any that does.
+fn is_newer(candidate: &str, current: &str) -> bool {
+    let parse = |s| chrono::DateTime::parse_from_rfc3339(s).ok();
+    match (parse(candidate), parse(current)) {
+        (Some(candidate), Some(current)) => candidate > current,
+        (Some(_), None) => true,
+        _ => false,
+    }
+}
+
+/// Map a SQLite error to [`MkbError::IndexCorrupted`] when the file is
+/// damaged or not a database at all, and to [`MkbError::Index`] otherwise.
+fn classify_open_error(e: rusqlite::Error, context: &str) -> MkbError {
+    match &e {
+        rusqlite::Error::SqliteFailure(err, _)
+            if matches!(
+                err.code,
+                rusqlite::ErrorCode::DatabaseCorrupt | rusqlite::ErrorCode::NotADatabase
+            ) =>
+        {
+            MkbError::IndexCorrupted {
+                reason: e.to_string(),
+            }
+        }
+        _ => MkbError::Index(format!("{context}: {e}")),
+    }
+}
+
+/// How long a connection waits on a lock held by another writer.
+pub const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// The IndexManager manages the SQLite index database.
 pub struct IndexManager {
     conn: Connection,
@@ -38,13 +176,27 @@ pub struct IndexManager {
 impl IndexManager {
     /// Open or create an index database at the given path.
     ///
+    /// The database runs in WAL mode, so readers in other processes (CLI
+    /// commands, the MCP server) are not blocked by a writer. A writer
+    /// waits up to [`BUSY_TIMEOUT`] for another writer to finish.
+    ///
     /// # Errors
     ///
-    /// Returns [`MkbError::Index`] if the database cannot be opened.
+    /// Returns [`MkbError::IndexCorrupted`] if the file is damaged or is not
+    /// a SQLite database, or [`MkbError::Index`] if it cannot be opened.
     pub fn open(path: &Path) -> Result<Self, MkbError> {
         ensure_vec_extension();
         let conn = Connection::open(path).map_err(|e| MkbError::Index(e.to_string()))?;
+        conn.busy_timeout(BUSY_TIMEOUT)
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        conn.execute_batch(
+            "PRAGMA journal_mode=WAL;
+             PRAGMA synchronous=NORMAL;
+             PRAGMA wal_autocheckpoint=1000;",
+        )
+        .map_err(|e| classify_open_error(e, "Failed to enable WAL"))?;
         let mgr = Self { conn };
+        mgr.register_functions()?;
         mgr.create_schema()?;
         Ok(mgr)
     }
@@ -58,10 +210,517 @@ impl IndexManager {
         ensure_vec_extension();
         let conn = Connection::open_in_memory().map_err(|e| MkbError::Index(e.to_string()))?;
         let mgr = Self { conn };
+        mgr.register_functions()?;
         mgr.create_schema()?;
         Ok(mgr)
     }
 
+    /// Rebuild the vault's index from scratch by re-reading every document file.
+    ///
+    /// The new index is built in a temporary file next to the live one and
+    /// copied into it only on success, so a failed rebuild leaves the old
+    /// index untouched. The copy goes through SQLite's backup API, so the
+    /// live database's WAL stays consistent and connections already open on
+    /// it see the rebuilt contents. `progress` is called with
+    /// `(completed, total)` after each file. Embeddings are not stored in
+    /// vault files and must be regenerated after a rebuild; annotations on
+    /// documents still in the vault are carried over.
+    ///
+    /// Files that cannot be read count as `failed`; files that are not valid
+    /// MKB documents count as `skipped`. Neither aborts the rebuild.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the new index cannot be written, or
+    /// [`MkbError::Io`] if the vault cannot be scanned or the swap fails.
+    pub fn rebuild_from_vault(
+        vault: &Vault,
+        progress: impl Fn(usize, usize) + Send,
+    ) -> Result<RebuildStats, MkbError> {
+        let start = Instant::now();
+        let index_dir = vault.root().join(".mkb").join("index");
+        fs::create_dir_all(&index_dir)?;
+        let live_path = index_dir.join("mkb.db");
+        let tmp_path = index_dir.join("mkb.db.rebuild");
+
+        // A leftover from an interrupted rebuild is never valid
+        if tmp_path.exists() {
+            fs::remove_file(&tmp_path)?;
+        }
+
+        let files = vault.list_documents()?;
+        match Self::populate(&tmp_path, vault.root(), &files, &progress) {
+            Ok((indexed, skipped, failed)) => {
+                annotation::carry_annotations(&tmp_path, &live_path)?;
+                Self::install_rebuilt(&tmp_path, &live_path)?;
+                Ok(RebuildStats {
+                    indexed,
+                    skipped,
+                    failed,
+                    elapsed: start.elapsed(),
+                })
+            }
+            Err(e) => {
+                let _ = fs::remove_file(&tmp_path);
+                Err(e)
+            }
+        }
+    }
+
+    /// Replace the contents of the database at `live` with the one at
+    /// `rebuilt`, then delete `rebuilt`.
+    ///
+    /// If the live database is corrupted, it and its WAL sidecars are
+    /// deleted and `rebuilt` is renamed into place.
+    fn install_rebuilt(rebuilt: &Path, live: &Path) -> Result<(), MkbError> {
+        use rusqlite::backup::{Backup, StepResult};
+
+        let copied = (|| {
+            let src = Connection::open(rebuilt)?;
+            let mut dst = Connection::open(live)?;
+            dst.busy_timeout(BUSY_TIMEOUT)?;
+            // One step copies every page under a single write lock
+            let step = Backup::new(&src, &mut dst)?.step(-1)?;
+            Ok(step)
+        })()
+        .map_err(|e| classify_open_error(e, "Failed to replace index"));
+        let result = match copied {
+            Ok(StepResult::Done) => Ok(()),
+            Ok(_) => Err(MkbError::Index(
+                "Failed to replace index: database is busy".to_string(),
+            )),
+            Err(MkbError::IndexCorrupted { reason }) => {
+                tracing::warn!("Replacing corrupted index {}: {reason}", live.display());
+                for suffix in ["", "-wal", "-shm"] {
+                    let mut path = live.as_os_str().to_owned();
+                    path.push(suffix);
+                    match fs::remove_file(PathBuf::from(path)) {
+                        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                        _ => {}
+                    }
+                }
+                return Ok(fs::rename(rebuilt, live)?);
+            }
+            Err(e) => Err(e),
+        };
+        fs::remove_file(rebuilt)?;
+        result
+    }
+
+    /// Index `files` into a fresh database at `path` in one transaction.
+    /// Returns `(indexed, skipped, failed)`; the connection is closed on return.
+    fn populate(
+        path: &Path,
+        root: &Path,
+        files: &[PathBuf],
+        progress: &impl Fn(usize, usize),
+    ) -> Result<(usize, usize, usize), MkbError> {
+        Self::open(path)?.with_transaction(|fresh| {
+            let (mut indexed, mut skipped, mut failed) = (0, 0, 0);
+            for (i, file) in files.iter().enumerate() {
+                match fresh.index_file(root, file)? {
+                    FileOutcome::Indexed => indexed += 1,
+                    FileOutcome::Skipped => skipped += 1,
+                    FileOutcome::Failed => failed += 1,
+                }
+                progress(i + 1, files.len());
+            }
+            Ok((indexed, skipped, failed))
+        })
+    }
+
+    /// Re-index only the vault files that changed since they were last indexed.
+    ///
+    /// A file is re-read when its modification time differs from the one
+    /// recorded in `indexed_files`, when it has never been recorded, or when
+    /// its document is missing from the index. `progress` is called with
+    /// `(completed, total)` after each file. All changes are made in one
+    /// transaction, so a failure leaves the index as it was.
+    ///
+    /// Files whose documents were indexed outside a rebuild (e.g. by
+    /// `mkb add`) are not yet recorded and are re-read once.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if a file cannot be indexed, or
+    /// [`MkbError::Io`] if the vault cannot be scanned.
+    pub fn reindex_incremental(
+        &self,
+        vault: &Vault,
+        progress: impl Fn(usize, usize),
+    ) -> Result<RebuildStats, MkbError> {
+        let start = Instant::now();
+        let files = vault.list_documents()?;
+
+        let (indexed, skipped, failed) = self.with_transaction(|index| {
+            let (mut indexed, mut skipped, mut failed) = (0, 0, 0);
+            for (i, file) in files.iter().enumerate() {
+                if index.is_stale(vault.root(), file)? {
+                    match index.index_file(vault.root(), file)? {
+                        FileOutcome::Indexed => indexed += 1,
+                        FileOutcome::Skipped => skipped += 1,
+                        FileOutcome::Failed => failed += 1,
+                    }
+                }
+                progress(i + 1, files.len());
+            }
+            Ok((indexed, skipped, failed))
+        })?;
+        Ok(RebuildStats {
+            indexed,
+            skipped,
+            failed,
+            elapsed: start.elapsed(),
+        })
+    }
+
+    /// Whether `file` changed, or lost its document, since it was last indexed.
+    fn is_stale(&self, root: &Path, file: &Path) -> Result<bool, MkbError> {
+        let Some(mtime) = file_mtime(file) else {
+            return Ok(true);
+        };
+        let recorded: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT f.modified_at FROM indexed_files f
+                 JOIN documents d ON d.id = f.doc_id
+                 WHERE f.path = ?1",
+                params![relative_path(root, file)],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        Ok(recorded != Some(mtime))
+    }
+
+    /// Parse `file` and index its document and links, recording the file's
+    /// modification time in `indexed_files`.
+    fn index_file(&self, root: &Path, file: &Path) -> Result<FileOutcome, MkbError> {
+        let Ok(content) = fs::read_to_string(file) else {
+            return Ok(FileOutcome::Failed);
+        };
+        let Ok(doc) = frontmatter::parse_document(&content) else {
+            return Ok(FileOutcome::Skipped);
+        };
+        self.index_parsed_file(root, file, &doc)?;
+        Ok(FileOutcome::Indexed)
+    }
+
+    fn index_parsed_file(&self, root: &Path, file: &Path, doc: &Document) -> Result<(), MkbError> {
+        self.index_document(doc)?;
+        self.store_links(&doc.id, &doc.links)?;
+        if let Some(mtime) = file_mtime(file) {
+            self.conn
+                .execute(
+                    "INSERT OR REPLACE INTO indexed_files (path, doc_id, modified_at)
+                     VALUES (?1, ?2, ?3)",
+                    params![relative_path(root, file), doc.id, mtime],
+                )
+                .map_err(|e| MkbError::Index(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Bring the index in line with one event from a
+    /// [`VaultWatcher`](mkb_vault::watcher::VaultWatcher) on `vault_root`.
+    ///
+    /// A changed file is parsed and indexed with its links. A removed file
+    /// drops the document named by its file stem, together with its links
+    /// and embedding.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Io`] if a changed file cannot be read, a parse
+    /// error if it is not a valid MKB document, and [`MkbError::Index`] if
+    /// the index cannot be updated.
+    pub fn apply_vault_event(
+        &self,
+        vault_root: &Path,
+        event: &VaultEvent,
+    ) -> Result<SyncOutcome, MkbError> {
+        match event {
+            VaultEvent::Changed(path) => {
+                let doc = frontmatter::parse_document(&fs::read_to_string(path)?)?;
+                self.index_parsed_file(vault_root, path, &doc)?;
+                Ok(SyncOutcome::Indexed {
+                    id: doc.id,
+                    title: doc.title,
+                })
+            }
+            VaultEvent::Removed(path) => {
+                let id = path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or_default()
+                    .to_string();
+                self.purge_document(&id)?;
+                self.conn
+                    .execute(
+                        "DELETE FROM indexed_files WHERE path = ?1",
+                        params![relative_path(vault_root, path)],
+                    )
+                    .map_err(|e| MkbError::Index(e.to_string()))?;
+                Ok(SyncOutcome::Removed { id })
+            }
+        }
+    }
+
+    /// Bring the index in line with one
+    /// [`VaultWatcher::next_batch`](mkb_vault::watcher::VaultWatcher::next_batch)
+    /// batch on `vault_root`, in one transaction.
+    ///
+    /// Each file is applied as by [`apply_vault_event`](Self::apply_vault_event).
+    /// Files that cannot be read or parsed are reported in
+    /// [`BatchSyncReport::failed`] without holding back the rest. An
+    /// overflowed batch is not applied file by file: the vault is reindexed
+    /// incrementally and documents whose files are gone are pruned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the index cannot be updated; none of
+    /// the batch is then applied.
+    pub fn apply_batch_event(
+        &mut self,
+        vault_root: &Path,
+        batch: &BatchFileEvent,
+    ) -> Result<BatchSyncReport, MkbError> {
+        if batch.overflowed {
+            let vault = Vault::open(vault_root)?;
+            let stats = self.reindex_incremental(&vault, |_, _| {})?;
+            self.prune_orphans(&vault)?;
+            return Ok(BatchSyncReport {
+                rebuilt: Some(stats),
+                ..BatchSyncReport::default()
+            });
+        }
+        self.transaction(|index| {
+            let mut report = BatchSyncReport::default();
+            for event in batch.events() {
+                match index.apply_vault_event(vault_root, &event) {
+                    Ok(outcome) => report.outcomes.push(outcome),
+                    Err(e @ MkbError::Index(_)) => return Err(e),
+                    Err(e) => report
+                        .failed
+                        .push((event.path().to_path_buf(), e.to_string())),
+                }
+            }
+            Ok(report)
+        })
+    }
+
+    /// Copy the write-ahead log into the database file and truncate it.
+    ///
+    /// SQLite checkpoints on its own every 1000 pages; this forces one, e.g.
+    /// during `mkb gc`. If readers still need part of the log, the checkpoint
+    /// stops short and the rest is copied on a later one.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the checkpoint fails.
+    pub fn checkpoint(&self) -> Result<(), MkbError> {
+        let busy: i64 = self
+            .conn
+            .query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| row.get(0))
+            .map_err(|e| MkbError::Index(format!("WAL checkpoint failed: {e}")))?;
+        if busy != 0 {
+            tracing::debug!("WAL checkpoint incomplete: log still in use by readers");
+        }
+        Ok(())
+    }
+
+    /// Remove indexed documents whose vault files no longer exist, along
+    /// with their embeddings and outgoing links. Returns the number pruned.
+    ///
+    /// Catches files deleted outside MKB (e.g. `git rm`) without a full
+    /// [`rebuild_from_vault`](Self::rebuild_from_vault).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if a query or delete fails; no rows are
+    /// removed in that case.
+    pub fn prune_orphans(&self, vault: &Vault) -> Result<usize, MkbError> {
+        let orphans: Vec<String> = self
+            .query_all()?
+            .into_iter()
+            .filter(|doc| !vault.document_path(&doc.doc_type, &doc.id).exists())
+            .map(|doc| doc.id)
+            .collect();
+        if orphans.is_empty() {
+            return Ok(0);
+        }
+
+        self.with_transaction(|index| orphans.iter().try_for_each(|id| index.purge_document(id)))?;
+        Ok(orphans.len())
+    }
+
+    /// Remove a document along with its embedding and outgoing links.
+    fn purge_document(&self, id: &str) -> Result<(), MkbError> {
+        self.conn
+            .execute("DELETE FROM links WHERE source_id = ?1", params![id])
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        self.remove_embedding(id)?;
+        self.remove_document(id)
+    }
+
+    /// Compare the vault's files with the indexed documents.
+    ///
+    /// Vault IDs come from file names, so no file is parsed. A file counts
+    /// as modified when its modification time is more than
+    /// [`MODIFIED_SLACK`] past its document's indexed `modified_at`, which
+    /// catches edits made outside MKB since the document was indexed. All
+    /// three lists are sorted.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Io`] if the vault cannot be scanned, or
+    /// [`MkbError::Index`] if the index cannot be read.
+    pub fn diff_against_vault(&self, vault: &Vault) -> Result<VaultDiff, MkbError> {
+        let mut files: BTreeMap<String, PathBuf> = BTreeMap::new();
+        for file in vault.list_documents()? {
+            if let Some(id) = file.file_stem().and_then(|stem| stem.to_str()) {
+                files.insert(id.to_string(), file.clone());
+            }
+        }
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, modified_at FROM documents ORDER BY id")
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        let indexed: BTreeMap<String, String> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| MkbError::Index(e.to_string()))?
+            .collect::<std::result::Result<_, _>>()
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+
+        let mut diff = VaultDiff::default();
+        for (id, file) in &files {
+            let Some(modified_at) = indexed.get(id) else {
+                diff.in_vault_not_index.push(id.clone());
+                continue;
+            };
+            let file_time = fs::metadata(file)
+                .and_then(|meta| meta.modified())
+                .map(chrono::DateTime::<chrono::Utc>::from);
+            let index_time = chrono::DateTime::parse_from_rfc3339(modified_at);
+            if let (Ok(file_time), Ok(index_time)) = (file_time, index_time) {
+                if file_time - index_time.with_timezone(&chrono::Utc) > MODIFIED_SLACK {
+                    diff.modified_since_index.push(id.clone());
+                }
+            }
+        }
+        diff.in_index_not_vault = indexed
+            .into_keys()
+            .filter(|id| !files.contains_key(id))
+            .collect();
+        Ok(diff)
+    }
+
+    /// Apply a schema migration to every indexed document of
+    /// `migration.doc_type`.
+    ///
+    /// Custom fields live only in the vault files, so each changed document
+    /// is rewritten there and then reindexed. Documents that already have
+    /// every defaulted field are left untouched.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Vault`] or [`MkbError::Io`] if a document cannot
+    /// be read or written, and [`MkbError::Index`] if reindexing fails.
+    /// Documents migrated before the failure keep their changes.
+    pub fn migrate_schema(
+        &self,
+        vault: &Vault,
+        migration: &SchemaMigration,
+    ) -> Result<MigrationReport, MkbError> {
+        let mut report = MigrationReport::default();
+        for indexed in self.query_by_type(&migration.doc_type)? {
+            report.scanned += 1;
+            let mut doc = vault.read(&indexed.doc_type, &indexed.id)?;
+            if !migration.apply(&mut doc.fields) {
+                continue;
+            }
+            vault.update(&mut doc)?;
+            self.index_document(&doc)?;
+            report.updated.push(doc.id);
+        }
+        Ok(report)
+    }
+
+    /// Regenerate the full-text index from the `documents` table.
+    ///
+    /// Repairs an FTS index that has drifted from its content table without
+    /// re-reading the vault.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the rebuild fails.
+    pub fn rebuild_fts(&self) -> Result<(), MkbError> {
+        self.conn
+            .execute(
+                "INSERT INTO documents_fts(documents_fts) VALUES ('rebuild')",
+                [],
+            )
+            .map_err(|e| MkbError::Index(format!("FTS rebuild failed: {e}")))?;
+        Ok(())
+    }
+
+    /// Check that the full-text index matches the `documents` table.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the check cannot run. A mismatch is
+    /// reported in the result, not as an error.
+    pub fn fts_integrity_check(&self) -> Result<FtsIntegrityResult, MkbError> {
+        // rank = 1 also compares the index against the external content table
+        let outcome = self.conn.execute(
+            "INSERT INTO documents_fts(documents_fts, rank) VALUES ('integrity-check', 1)",
+            [],
+        );
+        match outcome {
+            Ok(_) => Ok(FtsIntegrityResult {
+                ok: true,
+                message: None,
+            }),
+            Err(rusqlite::Error::SqliteFailure(e, message))
+                if e.code == rusqlite::ErrorCode::DatabaseCorrupt =>
+            {
+                Ok(FtsIntegrityResult {
+                    ok: false,
+                    message: Some(message.unwrap_or_else(|| e.to_string())),
+                })
+            }
+            Err(e) => Err(MkbError::Index(format!("FTS integrity check failed: {e}"))),
+        }
+    }
+
+    /// Register scalar SQL functions the bundled SQLite lacks.
+    ///
+    /// - `exp(x)` backs effective-confidence decay in both
+    ///   [`query_by_effective_confidence_range`](Self::query_by_effective_confidence_range)
+    ///   and compiled MKQL `EFF_CONFIDENCE()` predicates.
+    /// - `regexp(pattern, value)` makes `value REGEXP pattern` work, for
+    ///   MKQL `REGEX` predicates. NULL values never match.
+    fn register_functions(&self) -> Result<(), MkbError> {
+        use rusqlite::functions::FunctionFlags;
+
+        let flags = FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC;
+
+        self.conn
+            .create_scalar_function("exp", 1, flags, |ctx| Ok(ctx.get::<f64>(0)?.exp()))
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+
+        self.conn
+            .create_scalar_function("regexp", 2, flags, |ctx| {
+                // Compiled once per statement and cached as auxiliary data
+                let re = ctx.get_or_create_aux(0, |vr| -> Result<_, BoxError> {
+                    Ok(regex::Regex::new(vr.as_str()?)?)
+                })?;
+                let value: Option<String> = ctx.get(1)?;
+                Ok(value.is_some_and(|v| re.is_match(&v)))
+            })
+            .map_err(|e| MkbError::Index(e.to_string()))
+    }
+
     /// Create the index schema (documents table + FTS5 virtual table).
     fn create_schema(&self) -> Result<(), MkbError> {
         self.conn
@@ -75,14 +734,17 @@ impl IndexManager {
                 valid_until TEXT NOT NULL,
                 temporal_precision TEXT NOT NULL,
                 occurred_at TEXT,
+                occurred_until TEXT,
                 created_at TEXT NOT NULL,
                 modified_at TEXT NOT NULL,
                 confidence REAL NOT NULL DEFAULT 1.0,
                 source TEXT,
+                provenance_type TEXT,
                 supersedes TEXT,
                 superseded_by TEXT,
                 tags TEXT,
-                body TEXT NOT NULL DEFAULT ''
+                body TEXT NOT NULL DEFAULT '',
+                effective_confidence REAL
             );
 
             CREATE VIRTUAL TABLE IF NOT EXISTS documents_fts USING fts5(
@@ -110,9 +772,9 @@ impl IndexManager {
                 VALUES (new.rowid, new.title, new.body, new.tags);
             END;
 
-            CREATE INDEX IF NOT EXISTS idx_documents_type ON documents(doc_type);
             CREATE INDEX IF NOT EXISTS idx_documents_observed_at ON documents(observed_at);
             CREATE INDEX IF NOT EXISTS idx_documents_valid_until ON documents(valid_until);
+            CREATE INDEX IF NOT EXISTS idx_documents_confidence ON documents(confidence);
 
             CREATE TABLE IF NOT EXISTS links (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -135,10 +797,99 @@ impl IndexManager {
                 created_at TEXT NOT NULL DEFAULT (datetime('now')),
                 FOREIGN KEY (id) REFERENCES documents(id) ON DELETE CASCADE
             );
+
+            CREATE TABLE IF NOT EXISTS indexed_files (
+                path TEXT PRIMARY KEY,
+                doc_id TEXT NOT NULL,
+                modified_at INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS vec_meta (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS index_settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS query_history (
+                id INTEGER PRIMARY KEY,
+                mkql TEXT NOT NULL,
+                executed_at TEXT NOT NULL,
+                duration_ms REAL NOT NULL,
+                result_count INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS annotations (
+                id INTEGER PRIMARY KEY,
+                doc_id TEXT NOT NULL,
+                annotation_type TEXT NOT NULL,
+                content TEXT NOT NULL DEFAULT '',
+                created_at TEXT NOT NULL,
+                created_by TEXT NOT NULL,
+                FOREIGN KEY (doc_id) REFERENCES documents(id)
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_annotations_doc ON annotations(doc_id, annotation_type);
+
+            -- Foreign keys are not enforced, so annotations are removed here.
+            -- INSERT OR REPLACE does not fire delete triggers, so reindexing
+            -- a document keeps them.
+            CREATE TRIGGER IF NOT EXISTS documents_ad_annotations AFTER DELETE ON documents BEGIN
+                DELETE FROM annotations WHERE doc_id = old.id;
+            END;
             ",
             )
             .map_err(|e| MkbError::Index(e.to_string()))?;
 
+        // Indexes created before these columns existed lack them
+        for (column, sql_type) in [
+            ("occurred_until", "TEXT"),
+            ("provenance_type", "TEXT"),
+            ("effective_confidence", "REAL"),
+        ] {
+            let has_column: bool = self
+                .conn
+                .query_row(
+                    "SELECT COUNT(*) > 0 FROM pragma_table_info('documents') WHERE name = ?1",
+                    params![column],
+                    |row| row.get(0),
+                )
+                .map_err(|e| MkbError::Index(e.to_string()))?;
+            if !has_column {
+                self.conn
+                    .execute_batch(&format!(
+                        "ALTER TABLE documents ADD COLUMN {column} {sql_type};"
+                    ))
+                    .map_err(|e| MkbError::Index(e.to_string()))?;
+            }
+        }
+        // The type index also covers the end of each document's occurred
+        // range, which AS_OF_RANGE compares; the expression must match the
+        // compiled SQL for SQLite to use it. Older indexes hold doc_type only.
+        let type_index: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT sql FROM sqlite_master WHERE name = 'idx_documents_type'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        if !type_index.is_some_and(|sql| sql.contains("occurred_until")) {
+            self.conn
+                .execute_batch(
+                    "DROP INDEX IF EXISTS idx_documents_occurred_until;
+                     DROP INDEX IF EXISTS idx_documents_type;
+                     CREATE INDEX idx_documents_type ON documents(
+                         doc_type, COALESCE(occurred_until, occurred_at, observed_at)
+                     );",
+                )
+                .map_err(|e| MkbError::Index(e.to_string()))?;
+        }
+
         // Create virtual vec0 table for vector search (sqlite-vec).
         // This is idempotent — sqlite-vec handles IF NOT EXISTS internally.
         self.conn
@@ -165,9 +916,10 @@ impl IndexManager {
             .execute(
                 "INSERT OR REPLACE INTO documents
                 (id, doc_type, title, observed_at, valid_until, temporal_precision,
-                 occurred_at, created_at, modified_at, confidence, source,
-                 supersedes, superseded_by, tags, body)
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+                 occurred_at, occurred_until, created_at, modified_at, confidence, source,
+                 provenance_type, supersedes, superseded_by, tags, body)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16,
+                        ?17)",
                 params![
                     doc.id,
                     doc.doc_type,
@@ -176,10 +928,14 @@ impl IndexManager {
                     doc.temporal.valid_until.to_rfc3339(),
                     format!("{:?}", doc.temporal.temporal_precision).to_lowercase(),
                     doc.temporal.occurred_at.map(|d| d.to_rfc3339()),
+                    doc.temporal.occurred_until.map(|d| d.to_rfc3339()),
                     doc.created_at.to_rfc3339(),
                     doc.modified_at.to_rfc3339(),
                     doc.confidence,
-                    doc.source,
+                    doc.provenance
+                        .as_ref()
+                        .and_then(|p| p.source_uri.as_deref()),
+                    doc.provenance.as_ref().map(|p| p.source_type.as_str()),
                     doc.supersedes,
                     doc.superseded_by,
                     tags_str,
@@ -191,7 +947,7 @@ impl IndexManager {
         Ok(())
     }
 
-    /// Remove a document from the index.
+    /// Remove a document from the index, along with its annotations.
     ///
     /// # Errors
     ///
@@ -211,15 +967,35 @@ impl IndexManager {
     ///
     /// Returns [`MkbError::Index`] if the query fails.
     pub fn search_fts(&self, query: &str) -> Result<Vec<SearchResult>, MkbError> {
+        self.search_fts_inner(query, false)
+    }
+
+    /// Like [`search_fts`](Self::search_fts), also extracting a snippet of
+    /// about 32 tokens around the match in each body, with matched terms
+    /// wrapped in `<b>`/`</b>`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the query fails.
+    pub fn search_fts_with_snippets(&self, query: &str) -> Result<Vec<SearchResult>, MkbError> {
+        self.search_fts_inner(query, true)
+    }
+
+    fn search_fts_inner(&self, query: &str, snippets: bool) -> Result<Vec<SearchResult>, MkbError> {
+        let snippet_sql = if snippets {
+            "snippet(documents_fts, 1, '<b>', '</b>', '...', 32)"
+        } else {
+            "NULL"
+        };
         let mut stmt = self
             .conn
-            .prepare(
-                "SELECT d.id, d.title, d.doc_type, rank
+            .prepare(&format!(
+                "SELECT d.id, d.title, d.doc_type, rank, d.tags, {snippet_sql}
                  FROM documents_fts f
                  JOIN documents d ON d.rowid = f.rowid
                  WHERE documents_fts MATCH ?1
-                 ORDER BY rank",
-            )
+                 ORDER BY rank"
+            ))
             .map_err(|e| MkbError::Index(e.to_string()))?;
 
         let results = stmt
@@ -229,6 +1005,8 @@ impl IndexManager {
                     title: row.get(1)?,
                     doc_type: row.get(2)?,
                     rank: row.get(3)?,
+                    tags: tags_column(row, 4)?,
+                    snippet: row.get(5)?,
                 })
             })
             .map_err(|e| MkbError::Index(e.to_string()))?
@@ -250,7 +1028,8 @@ impl IndexManager {
     /// Returns [`MkbError::Index`] if the query fails or document not found.
     pub fn query_by_id(&self, id: &str) -> Result<Option<IndexedDocument>, MkbError> {
         let result = self.conn.query_row(
-            "SELECT id, doc_type, title, observed_at, valid_until, confidence
+            "SELECT id, doc_type, title, observed_at, valid_until, confidence, tags, modified_at,
+                 temporal_precision
                  FROM documents WHERE id = ?1",
             params![id],
             |row| {
@@ -261,6 +1040,9 @@ impl IndexManager {
                     observed_at: row.get(3)?,
                     valid_until: row.get(4)?,
                     confidence: row.get(5)?,
+                    tags: tags_column(row, 6)?,
+                    modified_at: row.get(7)?,
+                    age_label: age_label_column(row, 3, 8)?,
                 })
             },
         );
@@ -271,6 +1053,81 @@ impl IndexManager {
         }
     }
 
+    /// The `supersedes` and `superseded_by` IDs recorded for a document.
+    ///
+    /// Both are `None` if the document is not indexed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the query fails.
+    pub fn query_supersession(&self, id: &str) -> Result<SupersessionLinks, MkbError> {
+        self.conn
+            .query_row(
+                "SELECT supersedes, superseded_by FROM documents WHERE id = ?1",
+                params![id],
+                |row| {
+                    Ok(SupersessionLinks {
+                        supersedes: row.get(0)?,
+                        superseded_by: row.get(1)?,
+                    })
+                },
+            )
+            .optional()
+            .map(Option::unwrap_or_default)
+            .map_err(|e| MkbError::Index(e.to_string()))
+    }
+
+    /// Earlier versions of a document, oldest first, ending with `id`.
+    ///
+    /// Follows `supersedes` back to the original. The walk stops early at a
+    /// version that is not indexed. Returns an empty list if `id` itself is
+    /// not indexed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Vault`] if the chain loops back on itself, or
+    /// [`MkbError::Index`] if a query fails.
+    pub fn supersession_chain(&self, id: &str) -> Result<Vec<IndexedDocument>, MkbError> {
+        let mut chain = self.walk_supersession(id, |links| links.supersedes)?;
+        chain.reverse();
+        Ok(chain)
+    }
+
+    /// Later versions of a document, starting with `id` and ending with the
+    /// latest.
+    ///
+    /// Follows `superseded_by` forward; otherwise behaves like
+    /// [`supersession_chain`](Self::supersession_chain).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Vault`] if the chain loops back on itself, or
+    /// [`MkbError::Index`] if a query fails.
+    pub fn superseded_by_chain(&self, id: &str) -> Result<Vec<IndexedDocument>, MkbError> {
+        self.walk_supersession(id, |links| links.superseded_by)
+    }
+
+    /// Collect documents from `id` following `next` until it runs out.
+    fn walk_supersession(
+        &self,
+        id: &str,
+        next: impl Fn(SupersessionLinks) -> Option<String>,
+    ) -> Result<Vec<IndexedDocument>, MkbError> {
+        let mut chain: Vec<IndexedDocument> = Vec::new();
+        let mut current = Some(id.to_string());
+        while let Some(id) = current {
+            if chain.iter().any(|d| d.id == id) {
+                return Err(MkbError::Vault("circular supersession".to_string()));
+            }
+            let Some(doc) = self.query_by_id(&id)? else {
+                break;
+            };
+            current = next(self.query_supersession(&id)?);
+            chain.push(doc);
+        }
+        Ok(chain)
+    }
+
     /// Query documents by type.
     ///
     /// # Errors
@@ -280,7 +1137,8 @@ impl IndexManager {
         let mut stmt = self
             .conn
             .prepare(
-                "SELECT id, doc_type, title, observed_at, valid_until, confidence
+                "SELECT id, doc_type, title, observed_at, valid_until, confidence, tags, modified_at,
+                 temporal_precision
                  FROM documents
                  WHERE doc_type = ?1
                  ORDER BY observed_at DESC",
@@ -296,6 +1154,9 @@ impl IndexManager {
                     observed_at: row.get(3)?,
                     valid_until: row.get(4)?,
                     confidence: row.get(5)?,
+                    tags: tags_column(row, 6)?,
+                    modified_at: row.get(7)?,
+                    age_label: age_label_column(row, 3, 8)?,
                 })
             })
             .map_err(|e| MkbError::Index(e.to_string()))?
@@ -314,7 +1175,8 @@ impl IndexManager {
         let mut stmt = self
             .conn
             .prepare(
-                "SELECT id, doc_type, title, observed_at, valid_until, confidence
+                "SELECT id, doc_type, title, observed_at, valid_until, confidence, tags, modified_at,
+                 temporal_precision
                  FROM documents
                  ORDER BY observed_at DESC",
             )
@@ -329,6 +1191,9 @@ impl IndexManager {
                     observed_at: row.get(3)?,
                     valid_until: row.get(4)?,
                     confidence: row.get(5)?,
+                    tags: tags_column(row, 6)?,
+                    modified_at: row.get(7)?,
+                    age_label: age_label_column(row, 3, 8)?,
                 })
             })
             .map_err(|e| MkbError::Index(e.to_string()))?
@@ -338,23 +1203,68 @@ impl IndexManager {
         Ok(results)
     }
 
-    /// Store links for a document. Replaces any existing links for the source.
+    /// The most recently observed document of each type, keyed by type.
+    ///
+    /// Ties on `observed_at` go to the alphabetically first ID.
     ///
     /// # Errors
     ///
-    /// Returns [`MkbError::Index`] if the insert fails.
-    pub fn store_links(
-        &self,
-        source_id: &str,
-        links: &[mkb_core::link::Link],
-    ) -> Result<(), MkbError> {
-        // Remove existing links for this source
-        self.conn
-            .execute("DELETE FROM links WHERE source_id = ?1", params![source_id])
+    /// Returns [`MkbError::Index`] if the query fails.
+    pub fn query_newest_per_type(&self) -> Result<HashMap<String, IndexedDocument>, MkbError> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, doc_type, title, observed_at, valid_until, confidence, tags, modified_at,
+                 temporal_precision
+                 FROM (
+                     SELECT *, ROW_NUMBER() OVER (
+                         PARTITION BY doc_type ORDER BY observed_at DESC, id ASC
+                     ) AS rank
+                     FROM documents
+                 )
+                 WHERE rank = 1",
+            )
             .map_err(|e| MkbError::Index(e.to_string()))?;
 
-        for link in links {
-            self.conn
+        let results = stmt
+            .query_map([], |row| {
+                Ok(IndexedDocument {
+                    id: row.get(0)?,
+                    doc_type: row.get(1)?,
+                    title: row.get(2)?,
+                    observed_at: row.get(3)?,
+                    valid_until: row.get(4)?,
+                    confidence: row.get(5)?,
+                    tags: tags_column(row, 6)?,
+                    modified_at: row.get(7)?,
+                    age_label: age_label_column(row, 3, 8)?,
+                })
+            })
+            .map_err(|e| MkbError::Index(e.to_string()))?
+            .map(|doc| doc.map(|doc| (doc.doc_type.clone(), doc)))
+            .collect::<std::result::Result<HashMap<_, _>, _>>()
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+
+        Ok(results)
+    }
+
+    /// Store links for a document. Replaces any existing links for the source.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the insert fails.
+    pub fn store_links(
+        &self,
+        source_id: &str,
+        links: &[mkb_core::link::Link],
+    ) -> Result<(), MkbError> {
+        // Remove existing links for this source
+        self.conn
+            .execute("DELETE FROM links WHERE source_id = ?1", params![source_id])
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+
+        for link in links {
+            self.conn
                 .execute(
                     "INSERT INTO links (source_id, target_id, rel, observed_at, metadata)
                      VALUES (?1, ?2, ?3, ?4, ?5)",
@@ -435,6 +1345,141 @@ impl IndexManager {
         Ok(results)
     }
 
+    /// Load every indexed document and link into a [`LinkGraph`].
+    ///
+    /// Documents without links are isolated nodes; dangling link targets
+    /// are nodes too. Each node's edges are ordered by `rel`, then
+    /// `observed_at`, like [`Self::query_forward_links`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if a query fails.
+    pub fn link_graph(&self) -> Result<LinkGraph, MkbError> {
+        let mut graph = LinkGraph::new();
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id FROM documents ORDER BY id")
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        let ids = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        for id in ids {
+            graph.add_node(&id.map_err(|e| MkbError::Index(e.to_string()))?);
+        }
+
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT source_id, target_id, rel, observed_at FROM links
+                 ORDER BY rel, observed_at, source_id, target_id",
+            )
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        let edges = stmt
+            .query_map([], |row| {
+                Ok(LinkEdge {
+                    source: row.get(0)?,
+                    target: row.get(1)?,
+                    rel: row.get(2)?,
+                    observed_at: row.get(3)?,
+                })
+            })
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        for edge in edges {
+            graph.add_edge(edge.map_err(|e| MkbError::Index(e.to_string()))?);
+        }
+
+        Ok(graph)
+    }
+
+    /// Find links whose target is not an indexed document.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the query fails.
+    pub fn detect_dangling_links(&self) -> Result<Vec<DanglingLink>, MkbError> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT l.source_id, l.target_id, l.rel FROM links l
+                 LEFT JOIN documents d ON d.id = l.target_id
+                 WHERE d.id IS NULL
+                 ORDER BY l.source_id, l.target_id, l.rel",
+            )
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+
+        let results = stmt
+            .query_map([], |row| {
+                Ok(DanglingLink {
+                    source_id: row.get(0)?,
+                    target_id: row.get(1)?,
+                    rel: row.get(2)?,
+                })
+            })
+            .map_err(|e| MkbError::Index(e.to_string()))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+
+        Ok(results)
+    }
+
+    /// Delete every dangling link row, returning the links removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the query or delete fails.
+    pub fn remove_dangling_links(&self) -> Result<Vec<DanglingLink>, MkbError> {
+        let dangling = self.detect_dangling_links()?;
+        self.conn
+            .execute(
+                "DELETE FROM links WHERE target_id NOT IN (SELECT id FROM documents)",
+                [],
+            )
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        Ok(dangling)
+    }
+
+    /// Replace every stored link with the links declared in `vault`'s files.
+    ///
+    /// Each file contributes its frontmatter `links:` and its body's
+    /// `[[target_id]]` WikiLinks ([`link::document_links`]). Files that
+    /// cannot be read or parsed contribute nothing. All changes are made in
+    /// one transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Io`] if the vault cannot be scanned, or
+    /// [`MkbError::Index`] if the links table cannot be rewritten.
+    pub fn rebuild_links(&self, vault: &Vault) -> Result<LinkRebuildStats, MkbError> {
+        let files = vault.list_documents()?;
+        let rebuilt = self.with_transaction(|index| index.replace_links(&files))?;
+
+        Ok(LinkRebuildStats {
+            rebuilt,
+            dangling: self.detect_dangling_links()?.len(),
+        })
+    }
+
+    fn replace_links(&self, files: &[PathBuf]) -> Result<usize, MkbError> {
+        self.conn
+            .execute("DELETE FROM links", [])
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+
+        let mut rebuilt = 0;
+        for file in files {
+            let Ok(content) = fs::read_to_string(file) else {
+                continue;
+            };
+            let Ok(doc) = frontmatter::parse_document(&content) else {
+                continue;
+            };
+            let links = link::document_links(&doc);
+            self.store_links(&doc.id, &links)?;
+            rebuilt += links.len();
+        }
+        Ok(rebuilt)
+    }
+
     /// Query documents by observed_at range.
     ///
     /// # Errors
@@ -448,7 +1493,8 @@ impl IndexManager {
         let mut stmt = self
             .conn
             .prepare(
-                "SELECT id, doc_type, title, observed_at, valid_until, confidence
+                "SELECT id, doc_type, title, observed_at, valid_until, confidence, tags, modified_at,
+                 temporal_precision
                  FROM documents
                  WHERE observed_at >= ?1 AND observed_at <= ?2
                  ORDER BY observed_at DESC",
@@ -464,6 +1510,9 @@ impl IndexManager {
                     observed_at: row.get(3)?,
                     valid_until: row.get(4)?,
                     confidence: row.get(5)?,
+                    tags: tags_column(row, 6)?,
+                    modified_at: row.get(7)?,
+                    age_label: age_label_column(row, 3, 8)?,
                 })
             })
             .map_err(|e| MkbError::Index(e.to_string()))?
@@ -482,7 +1531,8 @@ impl IndexManager {
         let mut stmt = self
             .conn
             .prepare(
-                "SELECT id, doc_type, title, observed_at, valid_until, confidence
+                "SELECT id, doc_type, title, observed_at, valid_until, confidence, tags, modified_at,
+                 temporal_precision
                  FROM documents
                  WHERE superseded_by IS NULL
                    AND valid_until >= ?1
@@ -499,6 +1549,159 @@ impl IndexManager {
                     observed_at: row.get(3)?,
                     valid_until: row.get(4)?,
                     confidence: row.get(5)?,
+                    tags: tags_column(row, 6)?,
+                    modified_at: row.get(7)?,
+                    age_label: age_label_column(row, 3, 8)?,
+                })
+            })
+            .map_err(|e| MkbError::Index(e.to_string()))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+
+        Ok(results)
+    }
+
+    /// Query documents whose decayed confidence at `now` lies in `[min, max]`.
+    ///
+    /// Effective confidence is computed in SQLite as
+    /// `confidence × e^(−ln 2 × age / half_life)`, with age measured from the
+    /// stored `observed_at` and the half-life taken from `profile`. Results
+    /// are ordered by effective confidence, highest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the query fails.
+    pub fn query_by_effective_confidence_range(
+        &self,
+        min: f64,
+        max: f64,
+        now: &str,
+        profile: &mkb_core::temporal::DecayProfile,
+    ) -> Result<Vec<IndexedDocument>, MkbError> {
+        let half_life_days = profile.half_life().num_seconds() as f64 / 86_400.0;
+
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, doc_type, title, observed_at, valid_until, confidence, tags, modified_at,
+                 temporal_precision
+                 FROM (
+                     SELECT *, confidence * exp(
+                         -0.6931471805599453
+                         * max(0.0, julianday(?3) - julianday(observed_at)) / ?4
+                     ) AS eff_confidence
+                     FROM documents
+                 )
+                 WHERE eff_confidence >= ?1 AND eff_confidence <= ?2
+                 ORDER BY eff_confidence DESC",
+            )
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+
+        let results = stmt
+            .query_map(params![min, max, now, half_life_days], |row| {
+                Ok(IndexedDocument {
+                    id: row.get(0)?,
+                    doc_type: row.get(1)?,
+                    title: row.get(2)?,
+                    observed_at: row.get(3)?,
+                    valid_until: row.get(4)?,
+                    confidence: row.get(5)?,
+                    tags: tags_column(row, 6)?,
+                    modified_at: row.get(7)?,
+                    age_label: age_label_column(row, 3, 8)?,
+                })
+            })
+            .map_err(|e| MkbError::Index(e.to_string()))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+
+        Ok(results)
+    }
+
+    /// Query documents whose stored confidence lies in `[min, max]`,
+    /// highest confidence first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if either bound is outside `[0.0, 1.0]`
+    /// or the query fails.
+    pub fn query_by_confidence_range(
+        &self,
+        min: f64,
+        max: f64,
+    ) -> Result<Vec<IndexedDocument>, MkbError> {
+        check_confidence(min)?;
+        check_confidence(max)?;
+
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, doc_type, title, observed_at, valid_until, confidence, tags, modified_at,
+                 temporal_precision
+                 FROM documents
+                 WHERE confidence >= ?1 AND confidence <= ?2
+                 ORDER BY confidence DESC, observed_at DESC",
+            )
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+
+        let results = stmt
+            .query_map(params![min, max], |row| {
+                Ok(IndexedDocument {
+                    id: row.get(0)?,
+                    doc_type: row.get(1)?,
+                    title: row.get(2)?,
+                    observed_at: row.get(3)?,
+                    valid_until: row.get(4)?,
+                    confidence: row.get(5)?,
+                    tags: tags_column(row, 6)?,
+                    modified_at: row.get(7)?,
+                    age_label: age_label_column(row, 3, 8)?,
+                })
+            })
+            .map_err(|e| MkbError::Index(e.to_string()))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+
+        Ok(results)
+    }
+
+    /// Query documents of one type with confidence of at least `min`,
+    /// highest confidence first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if `min` is outside `[0.0, 1.0]` or the
+    /// query fails.
+    pub fn query_by_type_and_confidence(
+        &self,
+        doc_type: &str,
+        min: f64,
+    ) -> Result<Vec<IndexedDocument>, MkbError> {
+        check_confidence(min)?;
+
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, doc_type, title, observed_at, valid_until, confidence, tags, modified_at,
+                 temporal_precision
+                 FROM documents
+                 WHERE doc_type = ?1 AND confidence >= ?2
+                 ORDER BY confidence DESC, observed_at DESC",
+            )
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+
+        let results = stmt
+            .query_map(params![doc_type, min], |row| {
+                Ok(IndexedDocument {
+                    id: row.get(0)?,
+                    doc_type: row.get(1)?,
+                    title: row.get(2)?,
+                    observed_at: row.get(3)?,
+                    valid_until: row.get(4)?,
+                    confidence: row.get(5)?,
+                    tags: tags_column(row, 6)?,
+                    modified_at: row.get(7)?,
+                    age_label: age_label_column(row, 3, 8)?,
                 })
             })
             .map_err(|e| MkbError::Index(e.to_string()))?
@@ -514,18 +1717,36 @@ impl IndexManager {
     ///
     /// Returns [`MkbError::Index`] if the query fails.
     pub fn staleness_sweep(&self, at_time: &str) -> Result<Vec<String>, MkbError> {
+        self.staleness_sweep_with_recency(at_time, None)
+    }
+
+    /// Like [`staleness_sweep`](Self::staleness_sweep), but with
+    /// `edited_within` set, an expired document whose file was modified less
+    /// than that long before `at_time` is not reported: someone is still
+    /// maintaining it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the query fails.
+    pub fn staleness_sweep_with_recency(
+        &self,
+        at_time: &str,
+        edited_within: Option<Duration>,
+    ) -> Result<Vec<String>, MkbError> {
+        let window_days = edited_within.map(|d| d.as_secs_f64() / 86_400.0);
         let mut stmt = self
             .conn
             .prepare(
                 "SELECT id FROM documents
                  WHERE valid_until < ?1
                    AND superseded_by IS NULL
+                   AND (?2 IS NULL OR julianday(modified_at) < julianday(?1) - ?2)
                  ORDER BY valid_until ASC",
             )
             .map_err(|e| MkbError::Index(e.to_string()))?;
 
         let results = stmt
-            .query_map(params![at_time], |row| row.get(0))
+            .query_map(params![at_time, window_days], |row| row.get(0))
             .map_err(|e| MkbError::Index(e.to_string()))?
             .collect::<std::result::Result<Vec<String>, _>>()
             .map_err(|e| MkbError::Index(e.to_string()))?;
@@ -533,6 +1754,44 @@ impl IndexManager {
         Ok(results)
     }
 
+    /// Count documents per `observed_at` bucket.
+    ///
+    /// Bins are returned in chronological order; labels are chosen so that
+    /// lexical order matches chronological order (`2025-W07`, `2025-Q1`, ...).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the query fails or a stored
+    /// `observed_at` is not valid RFC 3339.
+    pub fn observed_at_histogram(
+        &self,
+        bucket: HistogramBucket,
+    ) -> Result<Vec<HistogramBin>, MkbError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT observed_at FROM documents")
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+
+        let stamps = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| MkbError::Index(e.to_string()))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+
+        let mut counts = std::collections::BTreeMap::new();
+        for stamp in stamps {
+            let at = chrono::DateTime::parse_from_rfc3339(&stamp)
+                .map_err(|e| MkbError::Index(format!("Invalid observed_at '{stamp}': {e}")))?
+                .with_timezone(&chrono::Utc);
+            *counts.entry(bucket.label(&at)).or_insert(0u64) += 1;
+        }
+
+        Ok(counts
+            .into_iter()
+            .map(|(label, count)| HistogramBin { label, count })
+            .collect())
+    }
+
     /// Execute a raw SQL query with parameters, returning rows as JSON-like maps.
     ///
     /// Used by the query engine to execute compiled MKQL queries.
@@ -540,49 +1799,55 @@ impl IndexManager {
     /// # Errors
     ///
     /// Returns [`MkbError::Index`] if the query fails.
-    pub fn execute_sql(
+    pub fn execute_sql(&self, sql: &str, params: &[SqlValue]) -> Result<Vec<SqlRow>, MkbError> {
+        self.stream_sql(sql, params)?.collect()
+    }
+
+    /// Start a raw SQL query, returning an iterator that reads one row from
+    /// SQLite per step.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the statement cannot be prepared or
+    /// bound. Errors while stepping are yielded by the iterator.
+    pub fn stream_sql(&self, sql: &str, params: &[SqlValue]) -> Result<SqlRowStream<'_>, MkbError> {
+        let stmt = self
+            .conn
+            .prepare(sql)
+            .map_err(|e| MkbError::Index(format!("SQL prepare error: {e}")))?;
+        SqlRowStream::start(stmt, params)
+    }
+
+    /// SQLite's plan for `sql`, from `EXPLAIN QUERY PLAN`, without running it.
+    ///
+    /// Shows whether a query scans a whole table or searches one of the
+    /// index's B-tree indexes. `params` only need to match `sql`'s
+    /// placeholders in number; their values do not affect the plan.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the statement cannot be prepared.
+    pub fn explain_query(
         &self,
         sql: &str,
         params: &[SqlValue],
-    ) -> Result<Vec<std::collections::HashMap<String, serde_json::Value>>, MkbError> {
+    ) -> Result<Vec<QueryPlanRow>, MkbError> {
+        let index_err = |e: rusqlite::Error| MkbError::Index(format!("EXPLAIN QUERY PLAN: {e}"));
         let mut stmt = self
             .conn
-            .prepare(sql)
-            .map_err(|e| MkbError::Index(format!("SQL prepare error: {e}")))?;
-
-        let column_count = stmt.column_count();
-        let column_names: Vec<String> = (0..column_count)
-            .map(|i| stmt.column_name(i).unwrap_or("?").to_string())
-            .collect();
-
-        let param_refs: Vec<&dyn rusqlite::types::ToSql> = params
-            .iter()
-            .map(|v| v as &dyn rusqlite::types::ToSql)
-            .collect();
-
+            .prepare(&format!("EXPLAIN QUERY PLAN {sql}"))
+            .map_err(index_err)?;
         let rows = stmt
-            .query_map(param_refs.as_slice(), |row| {
-                let mut map = std::collections::HashMap::new();
-                for (i, name) in column_names.iter().enumerate() {
-                    let value: SqlValue = row.get(i)?;
-                    let json_val = match value {
-                        SqlValue::Null => serde_json::Value::Null,
-                        SqlValue::Integer(n) => serde_json::json!(n),
-                        SqlValue::Real(f) => serde_json::json!(f),
-                        SqlValue::Text(s) => serde_json::json!(s),
-                        SqlValue::Blob(b) => {
-                            serde_json::json!(format!("<blob:{} bytes>", b.len()))
-                        }
-                    };
-                    map.insert(name.clone(), json_val);
-                }
-                Ok(map)
+            .query_map(rusqlite::params_from_iter(params), |row| {
+                Ok(QueryPlanRow {
+                    selectid: row.get(0)?,
+                    order: row.get(1)?,
+                    from: row.get(2)?,
+                    detail: row.get(3)?,
+                })
             })
-            .map_err(|e| MkbError::Index(format!("SQL query error: {e}")))?
-            .collect::<std::result::Result<Vec<_>, _>>()
-            .map_err(|e| MkbError::Index(format!("SQL row error: {e}")))?;
-
-        Ok(rows)
+            .map_err(index_err)?;
+        rows.collect::<Result<_, _>>().map_err(index_err)
     }
 
     // === Vector / Embedding Operations ===
@@ -625,12 +1890,39 @@ impl IndexManager {
             )
             .map_err(|e| MkbError::Index(format!("Vec index insert failed: {e}")))?;
 
+        // Record what the vectors look like so search can diagnose mismatches
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO vec_meta (key, value)
+                 VALUES ('dimension', ?1), ('model', ?2)",
+                params![embedding.len().to_string(), model],
+            )
+            .map_err(|e| MkbError::Index(format!("Vec meta update failed: {e}")))?;
+
         Ok(())
     }
 
-    /// Search for similar documents using vector similarity (KNN).
+    /// Dimension of the embeddings stored in this index, from `vec_meta`.
     ///
-    /// Returns document IDs with their distance scores, ordered by similarity.
+    /// Returns `None` if no embedding has been stored yet.
+    #[must_use]
+    pub fn embedding_dimension(&self) -> Option<usize> {
+        self.vec_meta("dimension")?.parse().ok()
+    }
+
+    fn vec_meta(&self, key: &str) -> Option<String> {
+        self.conn
+            .query_row(
+                "SELECT value FROM vec_meta WHERE key = ?1",
+                params![key],
+                |row| row.get(0),
+            )
+            .ok()
+    }
+
+    /// Search for similar documents using vector similarity (KNN).
+    ///
+    /// Returns document IDs with their distance scores, ordered by similarity.
     ///
     /// # Errors
     ///
@@ -640,13 +1932,7 @@ impl IndexManager {
         query_embedding: &[f32],
         limit: usize,
     ) -> Result<Vec<VectorSearchResult>, MkbError> {
-        if query_embedding.len() != EMBEDDING_DIM {
-            return Err(MkbError::Index(format!(
-                "Query embedding dimension mismatch: expected {EMBEDDING_DIM}, got {}",
-                query_embedding.len()
-            )));
-        }
-
+        self.check_query_dimension(query_embedding)?;
         let blob = query_embedding.as_bytes();
 
         let mut stmt = self
@@ -677,6 +1963,121 @@ impl IndexManager {
         Ok(results)
     }
 
+    /// Like [`search_semantic`](Self::search_semantic), restricted to one
+    /// document type.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the query fails.
+    pub fn search_semantic_by_type(
+        &self,
+        query_embedding: &[f32],
+        doc_type: &str,
+        limit: usize,
+    ) -> Result<Vec<VectorSearchResult>, MkbError> {
+        self.search_semantic_by_types(query_embedding, &[doc_type], limit)
+    }
+
+    /// Like [`search_semantic`](Self::search_semantic), restricted to
+    /// documents of any of `doc_types`.
+    ///
+    /// The `vec0` KNN query cannot filter on joined columns, so this ranks
+    /// every embedding of the requested types by exact L2 distance instead.
+    /// No result slots go to other types. An empty `doc_types` matches
+    /// nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the query fails.
+    pub fn search_semantic_by_types(
+        &self,
+        query_embedding: &[f32],
+        doc_types: &[&str],
+        limit: usize,
+    ) -> Result<Vec<VectorSearchResult>, MkbError> {
+        self.check_query_dimension(query_embedding)?;
+        if doc_types.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let placeholders: Vec<String> = (0..doc_types.len())
+            .map(|i| format!("?{}", i + 3))
+            .collect();
+        let sql = format!(
+            "SELECT v.id, vec_distance_l2(v.embedding, ?1) AS distance, d.title, d.doc_type
+             FROM vec_documents v
+             JOIN documents d ON d.id = v.id
+             WHERE d.doc_type IN ({})
+             ORDER BY distance
+             LIMIT ?2",
+            placeholders.join(", ")
+        );
+        let mut values = vec![
+            SqlValue::Blob(query_embedding.as_bytes().to_vec()),
+            SqlValue::Integer(limit as i64),
+        ];
+        values.extend(doc_types.iter().map(|t| SqlValue::Text((*t).to_string())));
+
+        let mut stmt = self
+            .conn
+            .prepare(&sql)
+            .map_err(|e| MkbError::Index(format!("Vec search prepare failed: {e}")))?;
+        let results = stmt
+            .query_map(rusqlite::params_from_iter(values), |row| {
+                Ok(VectorSearchResult {
+                    id: row.get(0)?,
+                    distance: row.get::<_, Option<f64>>(1)?.unwrap_or(0.0),
+                    title: row.get(2)?,
+                    doc_type: row.get(3)?,
+                })
+            })
+            .map_err(|e| MkbError::Index(format!("Vec search query failed: {e}")))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| MkbError::Index(format!("Vec search row failed: {e}")))?;
+
+        Ok(results)
+    }
+
+    /// Reject a query embedding whose length differs from the index's.
+    fn check_query_dimension(&self, query_embedding: &[f32]) -> Result<(), MkbError> {
+        if query_embedding.len() == EMBEDDING_DIM {
+            return Ok(());
+        }
+        let stored = self.embedding_dimension().unwrap_or(EMBEDDING_DIM);
+        let model = self
+            .vec_meta("model")
+            .map_or_else(String::new, |m| format!(" from model '{m}'"));
+        Err(MkbError::Index(format!(
+            "Query embedding dimension mismatch: index stores {stored}-dim vectors{model}, \
+             query has {} dims. The query was likely embedded with a different model.",
+            query_embedding.len()
+        )))
+    }
+
+    /// Fetch the stored embedding for a document, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the query fails.
+    pub fn get_embedding(&self, doc_id: &str) -> Result<Option<Vec<f32>>, MkbError> {
+        let blob: Option<Vec<u8>> = self
+            .conn
+            .query_row(
+                "SELECT embedding FROM document_embeddings WHERE id = ?1",
+                params![doc_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+
+        Ok(blob.map(|bytes| {
+            bytes
+                .chunks_exact(4)
+                .map(|c| f32::from_ne_bytes([c[0], c[1], c[2], c[3]]))
+                .collect()
+        }))
+    }
+
     /// Check if a document has an embedding stored.
     ///
     /// # Errors
@@ -727,637 +2128,2925 @@ impl IndexManager {
         Ok(count as u64)
     }
 
-    /// Get count of indexed documents.
+    /// Models that generated the stored embeddings, with how many each
+    /// produced, sorted by model name. More than one entry means the vault
+    /// mixes vectors that cannot be compared with each other.
     ///
     /// # Errors
     ///
     /// Returns [`MkbError::Index`] if the query fails.
-    pub fn count(&self) -> Result<u64, MkbError> {
-        let count: i64 = self
+    pub fn embedding_models(&self) -> Result<Vec<(String, u64)>, MkbError> {
+        let mut stmt = self
             .conn
-            .query_row("SELECT COUNT(*) FROM documents", [], |row| row.get(0))
+            .prepare(
+                "SELECT model, COUNT(*) FROM document_embeddings
+                 GROUP BY model ORDER BY model",
+            )
             .map_err(|e| MkbError::Index(e.to_string()))?;
-        Ok(count as u64)
+        let models = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as u64)))
+            .map_err(|e| MkbError::Index(e.to_string()))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        Ok(models)
     }
-}
-
-/// A search result from FTS5 full-text search.
-#[derive(Debug, Clone)]
-pub struct SearchResult {
-    pub id: String,
-    pub title: String,
-    pub doc_type: String,
-    pub rank: f64,
-}
 
-/// A link as stored in the index.
-#[derive(Debug, Clone)]
-pub struct IndexedLink {
-    pub source_id: String,
-    pub target_id: String,
-    pub rel: String,
-    pub observed_at: String,
-}
+    /// Per-model breakdown of the stored embeddings, sorted by model name,
+    /// with when each model's oldest and newest vectors were written. Use it
+    /// to find what still needs re-embedding after switching models.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the query fails.
+    pub fn get_embedding_model_stats(&self) -> Result<Vec<EmbeddingModelStat>, MkbError> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT model, COUNT(*), MIN(created_at), MAX(created_at)
+                 FROM document_embeddings
+                 GROUP BY model ORDER BY model",
+            )
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        let stats = stmt
+            .query_map([], |row| {
+                Ok(EmbeddingModelStat {
+                    model: row.get(0)?,
+                    count: row.get::<_, i64>(1)? as u64,
+                    oldest_created_at: row.get(2)?,
+                    newest_created_at: row.get(3)?,
+                })
+            })
+            .map_err(|e| MkbError::Index(e.to_string()))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        Ok(stats)
+    }
 
-/// A vector search result with distance score.
-#[derive(Debug, Clone)]
-pub struct VectorSearchResult {
-    pub id: String,
-    pub distance: f64,
-    pub title: String,
-    pub doc_type: String,
-}
+    /// Documents with no stored embedding, newest observation first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the query fails.
+    pub fn documents_without_embedding(&self) -> Result<Vec<IndexedDocument>, MkbError> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT d.id, d.doc_type, d.title, d.observed_at, d.valid_until, d.confidence,
+                        d.tags, d.modified_at, d.temporal_precision
+                 FROM documents d
+                 WHERE NOT EXISTS (SELECT 1 FROM document_embeddings e WHERE e.id = d.id)
+                 ORDER BY d.observed_at DESC, d.id",
+            )
+            .map_err(|e| MkbError::Index(e.to_string()))?;
 
-/// A document as stored in the index.
-#[derive(Debug, Clone)]
-pub struct IndexedDocument {
-    pub id: String,
-    pub doc_type: String,
-    pub title: String,
-    pub observed_at: String,
-    pub valid_until: String,
-    pub confidence: f64,
-}
+        let results = stmt
+            .query_map([], |row| {
+                Ok(IndexedDocument {
+                    id: row.get(0)?,
+                    doc_type: row.get(1)?,
+                    title: row.get(2)?,
+                    observed_at: row.get(3)?,
+                    valid_until: row.get(4)?,
+                    confidence: row.get(5)?,
+                    tags: tags_column(row, 6)?,
+                    modified_at: row.get(7)?,
+                    age_label: age_label_column(row, 3, 8)?,
+                })
+            })
+            .map_err(|e| MkbError::Index(e.to_string()))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| MkbError::Index(e.to_string()))?;
 
-/// Generate a deterministic mock embedding from text using SHA-256.
-///
-/// This is the Rust port of `MockEmbeddingBackend.generate()` from Python.
-/// Produces the same deterministic vector for the same input text, suitable
-/// for testing without API calls.
-#[must_use]
-pub fn mock_embedding(text: &str) -> Vec<f32> {
-    use sha2::{Digest, Sha256};
-
-    let mut vec = Vec::with_capacity(EMBEDDING_DIM);
-    for i in 0..EMBEDDING_DIM {
-        let mut hasher = Sha256::new();
-        hasher.update(format!("{text}-{i}").as_bytes());
-        let hash = hasher.finalize();
-        // Interpret first 4 bytes as f32
-        let val = f32::from_le_bytes([hash[0], hash[1], hash[2], hash[3]]);
-        // Clamp to [-1, 1]
-        let val = val.clamp(-1.0e38, 1.0e38) / 1.0e38;
-        let val = val.clamp(-1.0, 1.0);
-        vec.push(val);
-    }
-    // Normalize
-    let norm: f32 = vec.iter().map(|v| v * v).sum::<f32>().sqrt();
-    if norm > 0.0 {
-        for v in &mut vec {
-            *v /= norm;
-        }
+        Ok(results)
     }
-    vec
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use chrono::{DateTime, TimeZone, Utc};
-    use mkb_core::temporal::{DecayProfile, RawTemporalInput, TemporalPrecision};
 
-    fn utc(y: i32, m: u32, d: u32) -> DateTime<Utc> {
-        Utc.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap()
+    /// Copy the documents, links and embeddings of `source` into this index.
+    ///
+    /// A document only in `source` is inserted with its links and embedding.
+    /// A document in both is resolved by `conflict`; an overwritten document
+    /// replaces its links, and its embedding is replaced or dropped to match
+    /// `source`. A document whose row cannot be written counts as `failed`
+    /// and does not stop the merge. The merge runs in one transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if either database cannot be read, or on
+    /// the first conflict under [`ConflictStrategy::FailOnConflict`]; the
+    /// index is then left as it was.
+    pub fn merge_from(
+        &self,
+        source: &IndexManager,
+        conflict: ConflictStrategy,
+    ) -> Result<MergeStats, MkbError> {
+        let rows = source.document_rows()?;
+        self.with_transaction(|index| index.merge_rows(source, &rows, conflict))
     }
 
-    fn make_doc(id: &str, doc_type: &str, title: &str, body: &str) -> Document {
-        let input = RawTemporalInput {
-            observed_at: Some(utc(2025, 2, 10)),
-            valid_until: Some(utc(2025, 8, 10)),
-            temporal_precision: Some(TemporalPrecision::Day),
-            occurred_at: None,
-        };
-        let profile = DecayProfile::default_profile();
-        let mut doc = Document::new(
-            id.to_string(),
-            doc_type.to_string(),
-            title.to_string(),
-            input,
-            &profile,
-        )
-        .unwrap();
-        doc.body = body.to_string();
-        doc
+    /// Every `documents` row, as raw values.
+    fn document_rows(&self) -> Result<Vec<DocumentRow>, MkbError> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT id, modified_at, {DOCUMENT_COLUMNS} FROM documents ORDER BY id"
+            ))
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        let column_count = stmt.column_count();
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(DocumentRow {
+                    id: row.get(0)?,
+                    modified_at: row.get(1)?,
+                    values: (2..column_count)
+                        .map(|i| row.get::<_, SqlValue>(i))
+                        .collect::<rusqlite::Result<_>>()?,
+                })
+            })
+            .map_err(|e| MkbError::Index(e.to_string()))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        Ok(rows)
     }
 
-    #[test]
-    fn creates_schema_on_init() {
-        let mgr = IndexManager::in_memory().unwrap();
-        // Schema exists — can count without error
-        let count = mgr.count().unwrap();
-        assert_eq!(count, 0);
+    fn merge_rows(
+        &self,
+        source: &IndexManager,
+        rows: &[DocumentRow],
+        conflict: ConflictStrategy,
+    ) -> Result<MergeStats, MkbError> {
+        let mut stats = MergeStats::default();
+        for row in rows {
+            let (SqlValue::Text(id), SqlValue::Text(modified_at)) = (&row.id, &row.modified_at)
+            else {
+                stats.failed += 1;
+                continue;
+            };
+
+            let existing: Option<String> = self
+                .conn
+                .query_row(
+                    "SELECT modified_at FROM documents WHERE id = ?1",
+                    params![id],
+                    |r| r.get(0),
+                )
+                .optional()
+                .map_err(|e| MkbError::Index(e.to_string()))?;
+            if let Some(existing) = &existing {
+                match conflict {
+                    ConflictStrategy::SkipExisting => {
+                        stats.skipped += 1;
+                        continue;
+                    }
+                    ConflictStrategy::OverwriteWithNewer => {
+                        if !is_newer(modified_at, existing) {
+                            stats.skipped += 1;
+                            continue;
+                        }
+                    }
+                    ConflictStrategy::FailOnConflict => {
+                        return Err(MkbError::Index(format!(
+                            "Merge conflict: document '{id}' exists in both indexes"
+                        )));
+                    }
+                }
+            }
+
+            let placeholders: Vec<String> =
+                (1..=row.values.len()).map(|i| format!("?{i}")).collect();
+            let inserted = self.conn.execute(
+                &format!(
+                    "INSERT OR REPLACE INTO documents ({DOCUMENT_COLUMNS}) VALUES ({})",
+                    placeholders.join(", ")
+                ),
+                rusqlite::params_from_iter(&row.values),
+            );
+            if inserted.is_err() {
+                stats.failed += 1;
+                continue;
+            }
+            self.copy_links(source, id)?;
+            self.copy_embedding(source, id)?;
+
+            if existing.is_some() {
+                stats.overwritten += 1;
+            } else {
+                stats.inserted += 1;
+            }
+        }
+        Ok(stats)
     }
 
-    #[test]
-    fn index_document_stores_all_frontmatter_fields() {
-        let mgr = IndexManager::in_memory().unwrap();
-
-        let mut doc = make_doc("proj-alpha-001", "project", "Alpha Project", "Some body");
-        doc.tags = vec!["rust".to_string(), "ai".to_string()];
-        doc.confidence = 0.95;
-        doc.source = Some("manual".to_string());
-
-        mgr.index_document(&doc).unwrap();
-
-        assert_eq!(mgr.count().unwrap(), 1);
+    /// Replace the links from `id` with those `source` holds.
+    fn copy_links(&self, source: &IndexManager, id: &str) -> Result<(), MkbError> {
+        let mut stmt = source
+            .conn
+            .prepare(
+                "SELECT target_id, rel, observed_at, metadata FROM links
+                 WHERE source_id = ?1 ORDER BY id",
+            )
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        let links = stmt
+            .query_map(params![id], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                ))
+            })
+            .map_err(|e| MkbError::Index(e.to_string()))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| MkbError::Index(e.to_string()))?;
 
-        let results = mgr.query_by_type("project").unwrap();
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].id, "proj-alpha-001");
-        assert_eq!(results[0].title, "Alpha Project");
-        assert!((results[0].confidence - 0.95).abs() < f64::EPSILON);
+        self.conn
+            .execute("DELETE FROM links WHERE source_id = ?1", params![id])
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        for (target, rel, observed_at, metadata) in links {
+            self.conn
+                .execute(
+                    "INSERT INTO links (source_id, target_id, rel, observed_at, metadata)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![id, target, rel, observed_at, metadata],
+                )
+                .map_err(|e| MkbError::Index(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Replace the embedding of `id` with the one `source` holds, or remove
+    /// it if `source` has none.
+    fn copy_embedding(&self, source: &IndexManager, id: &str) -> Result<(), MkbError> {
+        let embedding: Option<(Vec<u8>, String, String)> = source
+            .conn
+            .query_row(
+                "SELECT embedding, model, created_at FROM document_embeddings WHERE id = ?1",
+                params![id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+
+        // vec0 does not support INSERT OR REPLACE on an existing key
+        self.remove_embedding(id)?;
+        let Some((blob, model, created_at)) = embedding else {
+            return Ok(());
+        };
+        self.conn
+            .execute(
+                "INSERT INTO document_embeddings (id, embedding, model, created_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![id, blob, model, created_at],
+            )
+            .map_err(|e| MkbError::Index(format!("Store embedding failed: {e}")))?;
+        self.conn
+            .execute(
+                "INSERT INTO vec_documents (id, embedding) VALUES (?1, ?2)",
+                params![id, blob],
+            )
+            .map_err(|e| MkbError::Index(format!("Vec index insert failed: {e}")))?;
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO vec_meta (key, value)
+                 VALUES ('dimension', ?1), ('model', ?2)",
+                params![(blob.len() / 4).to_string(), model],
+            )
+            .map_err(|e| MkbError::Index(format!("Vec meta update failed: {e}")))?;
+        Ok(())
+    }
+
+    /// Set the stored confidence of each `(id, confidence)` pair in one
+    /// transaction. Returns the number of rows updated; IDs that are not
+    /// indexed are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if a confidence is outside `[0.0, 1.0]`
+    /// or an update fails. Either way no update is applied.
+    pub fn bulk_update_confidence(&self, updates: &[(String, f64)]) -> Result<usize, MkbError> {
+        if updates.is_empty() {
+            return Ok(0);
+        }
+        self.with_transaction(|index| index.apply_confidence_updates(updates))
+    }
+
+    fn apply_confidence_updates(&self, updates: &[(String, f64)]) -> Result<usize, MkbError> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("UPDATE documents SET confidence = ?2 WHERE id = ?1")
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        let mut updated = 0;
+        for (id, confidence) in updates {
+            check_confidence(*confidence)?;
+            updated += stmt
+                .execute(params![id, confidence])
+                .map_err(|e| MkbError::Index(e.to_string()))?;
+        }
+        Ok(updated)
+    }
+
+    /// Store every document's effective confidence at `now` under `profile`
+    /// in the `effective_confidence` column.
+    ///
+    /// Each value is computed from the raw `confidence`, as indexed from the
+    /// vault, with the profile's decay for the document's age since
+    /// `observed_at` and its precision penalty (see
+    /// [`DecayModel::effective_confidence`](mkb_core::temporal::DecayModel::effective_confidence)).
+    /// The raw confidence is left alone, so running this again at the same
+    /// `now` changes nothing. Reindexing a document clears its value.
+    /// Returns the number of documents whose effective confidence changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if reading or updating the index fails.
+    pub fn recalculate_all_confidence(
+        &self,
+        profile: &mkb_core::temporal::DecayProfile,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<usize, MkbError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, observed_at, temporal_precision, confidence FROM documents")
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, f64>(3)?,
+                ))
+            })
+            .map_err(|e| MkbError::Index(e.to_string()))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+
+        let values: Vec<(String, f64)> = rows
+            .into_iter()
+            .filter_map(|(id, observed_at, precision, confidence)| {
+                let observed_at = chrono::DateTime::parse_from_rfc3339(&observed_at).ok()?;
+                let precision = TemporalPrecision::from_name(&precision)?;
+                let effective = mkb_core::temporal::DecayModel::effective_confidence(
+                    confidence,
+                    observed_at.with_timezone(&chrono::Utc),
+                    now,
+                    profile,
+                    precision,
+                );
+                Some((id, effective))
+            })
+            .collect();
+
+        self.with_transaction(|index| index.store_effective_confidence(&values))
+    }
+
+    fn store_effective_confidence(&self, values: &[(String, f64)]) -> Result<usize, MkbError> {
+        let mut stmt = self
+            .conn
+            .prepare_cached(
+                "UPDATE documents SET effective_confidence = ?2
+                 WHERE id = ?1 AND effective_confidence IS NOT ?2",
+            )
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        let mut updated = 0;
+        for (id, effective) in values {
+            updated += stmt
+                .execute(params![id, effective])
+                .map_err(|e| MkbError::Index(e.to_string()))?;
+        }
+        Ok(updated)
+    }
+
+    /// Begin an explicit transaction. Subsequent writes are held until
+    /// [`commit_transaction`](Self::commit_transaction) or discarded by
+    /// [`rollback_transaction`](Self::rollback_transaction).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if a transaction is already open.
+    pub fn begin_transaction(&self) -> Result<(), MkbError> {
+        self.conn
+            .execute_batch("BEGIN IMMEDIATE")
+            .map_err(|e| MkbError::Index(e.to_string()))
+    }
+
+    /// Commit the open transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if no transaction is open or the commit fails.
+    pub fn commit_transaction(&self) -> Result<(), MkbError> {
+        self.conn
+            .execute_batch("COMMIT")
+            .map_err(|e| MkbError::Index(e.to_string()))
+    }
+
+    /// Roll back the open transaction, discarding all writes since
+    /// [`begin_transaction`](Self::begin_transaction).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if no transaction is open.
+    pub fn rollback_transaction(&self) -> Result<(), MkbError> {
+        self.conn
+            .execute_batch("ROLLBACK")
+            .map_err(|e| MkbError::Index(e.to_string()))
+    }
+
+    /// Run `f` inside a transaction: its writes are committed together if
+    /// it returns `Ok`, and rolled back together if it returns `Err`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error from `f`, or [`MkbError::Index`] if a transaction
+    /// is already open or the commit fails.
+    pub fn transaction<F, T>(&mut self, f: F) -> Result<T, MkbError>
+    where
+        F: FnOnce(&mut IndexManager) -> Result<T, MkbError>,
+    {
+        self.begin_transaction()?;
+        let result = f(self);
+        self.finish_transaction(result)
+    }
+
+    /// [`transaction`](Self::transaction) for callers that only hold a
+    /// shared reference.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error from `f`, or [`MkbError::Index`] if a transaction
+    /// is already open or the commit fails.
+    pub fn with_transaction<F, T>(&self, f: F) -> Result<T, MkbError>
+    where
+        F: FnOnce(&IndexManager) -> Result<T, MkbError>,
+    {
+        self.begin_transaction()?;
+        let result = f(self);
+        self.finish_transaction(result)
+    }
+
+    /// Commit the open transaction if `result` is `Ok`, otherwise (or if the
+    /// commit fails) roll it back.
+    fn finish_transaction<T>(&self, result: Result<T, MkbError>) -> Result<T, MkbError> {
+        let result = result.and_then(|value| self.commit_transaction().map(|()| value));
+        if result.is_err() {
+            let _ = self.rollback_transaction();
+        }
+        result
+    }
+
+    /// Apply `ops` in order in one transaction. Returns the number of
+    /// operations applied.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the first operation that fails; none of the
+    /// operations are then applied.
+    pub fn execute_batch(&mut self, ops: Vec<IndexOp>) -> Result<usize, MkbError> {
+        self.transaction(|index| {
+            let count = ops.len();
+            for op in ops {
+                index.apply_op(op)?;
+            }
+            Ok(count)
+        })
+    }
+
+    fn apply_op(&self, op: IndexOp) -> Result<(), MkbError> {
+        match op {
+            IndexOp::IndexDocument(doc) => self.index_document(&doc),
+            IndexOp::RemoveDocument(id) => self.remove_document(&id),
+            IndexOp::StoreLinks { source_id, links } => self.store_links(&source_id, &links),
+            IndexOp::StoreEmbedding {
+                doc_id,
+                embedding,
+                model,
+            } => self.store_embedding(&doc_id, &embedding, &model),
+            IndexOp::RemoveEmbedding(doc_id) => self.remove_embedding(&doc_id),
+            IndexOp::UpdateConfidence { id, confidence } => self
+                .apply_confidence_updates(&[(id, confidence)])
+                .map(|_| ()),
+        }
+    }
+
+    /// Get count of indexed documents.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the query fails.
+    pub fn count(&self) -> Result<u64, MkbError> {
+        let count: i64 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM documents", [], |row| row.get(0))
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+        Ok(count as u64)
+    }
+}
+
+/// One write applied by [`IndexManager::execute_batch`].
+#[derive(Debug, Clone)]
+pub enum IndexOp {
+    /// [`IndexManager::index_document`].
+    IndexDocument(Box<Document>),
+    /// [`IndexManager::remove_document`].
+    RemoveDocument(String),
+    /// [`IndexManager::store_links`].
+    StoreLinks {
+        source_id: String,
+        links: Vec<mkb_core::link::Link>,
+    },
+    /// [`IndexManager::store_embedding`].
+    StoreEmbedding {
+        doc_id: String,
+        embedding: Vec<f32>,
+        model: String,
+    },
+    /// [`IndexManager::remove_embedding`].
+    RemoveEmbedding(String),
+    /// Set one document's stored confidence, as
+    /// [`IndexManager::bulk_update_confidence`] does.
+    UpdateConfidence { id: String, confidence: f64 },
+}
+
+/// A search result from FTS5 full-text search.
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    pub id: String,
+    pub title: String,
+    pub doc_type: String,
+    pub rank: f64,
+    pub tags: Vec<String>,
+    /// Body excerpt around the match, from
+    /// [`IndexManager::search_fts_with_snippets`].
+    pub snippet: Option<String>,
+}
+
+/// Outcome of [`IndexManager::fts_integrity_check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FtsIntegrityResult {
+    /// Whether the full-text index matches the `documents` table.
+    pub ok: bool,
+    /// SQLite's description of the mismatch, when not `ok`.
+    pub message: Option<String>,
+}
+
+/// A link as stored in the index.
+#[derive(Debug, Clone)]
+pub struct IndexedLink {
+    pub source_id: String,
+    pub target_id: String,
+    pub rel: String,
+    pub observed_at: String,
+}
+
+/// A link whose target document is not in the index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DanglingLink {
+    pub source_id: String,
+    pub target_id: String,
+    pub rel: String,
+}
+
+/// Outcome of [`IndexManager::rebuild_links`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinkRebuildStats {
+    /// Links written to the links table.
+    pub rebuilt: usize,
+    /// Of those, links whose target is not an indexed document.
+    pub dangling: usize,
+}
+
+/// Embeddings written by one model, from
+/// [`IndexManager::get_embedding_model_stats`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddingModelStat {
+    pub model: String,
+    pub count: u64,
+    /// `created_at` of the model's oldest embedding (SQLite `datetime('now')` format).
+    pub oldest_created_at: String,
+    /// `created_at` of the model's newest embedding.
+    pub newest_created_at: String,
+}
+
+/// A vector search result with distance score.
+#[derive(Debug, Clone)]
+pub struct VectorSearchResult {
+    pub id: String,
+    pub distance: f64,
+    pub title: String,
+    pub doc_type: String,
+}
+
+/// A document as stored in the index.
+#[derive(Debug, Clone)]
+pub struct IndexedDocument {
+    pub id: String,
+    pub doc_type: String,
+    pub title: String,
+    pub observed_at: String,
+    pub valid_until: String,
+    pub confidence: f64,
+    /// Tags split from the stored comma-separated column, trimmed, empties dropped.
+    pub tags: Vec<String>,
+    /// When the document file was last edited (`_modified_at`).
+    pub modified_at: String,
+    /// How long ago it was observed, in units of its temporal precision
+    /// ("3 days ago"), as of the query.
+    pub age_label: String,
+}
+
+/// A document's place in a supersession chain.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SupersessionLinks {
+    /// The earlier version this document replaces.
+    pub supersedes: Option<String>,
+    /// The later version that replaces this document.
+    pub superseded_by: Option<String>,
+}
+
+/// One row of [`IndexManager::explain_query`]: the four columns of
+/// `EXPLAIN QUERY PLAN`, in order.
+///
+/// SQLite 3.24 and later call the first three `id`, `parent` and `notused`;
+/// the older names are kept here. Rows form a tree through `order`, which
+/// holds the `selectid` of the parent step.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct QueryPlanRow {
+    pub selectid: i64,
+    pub order: i64,
+    pub from: i64,
+    /// What SQLite does at this step, e.g.
+    /// `SEARCH d USING INDEX idx_documents_type (doc_type=?)`.
+    pub detail: String,
+}
+
+/// What [`IndexManager::apply_vault_event`] did to the index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncOutcome {
+    /// A document was (re)indexed.
+    Indexed { id: String, title: String },
+    /// A document was removed.
+    Removed { id: String },
+}
+
+/// What [`IndexManager::apply_batch_event`] did with one batch.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BatchSyncReport {
+    /// Per-file changes, in batch order.
+    pub outcomes: Vec<SyncOutcome>,
+    /// Files that could not be read or parsed, with the reason.
+    pub failed: Vec<(PathBuf, String)>,
+    /// Set when an overflowed batch was handled by reindexing the vault.
+    pub rebuilt: Option<RebuildStats>,
+}
+
+/// Outcome of [`IndexManager::rebuild_from_vault`] and
+/// [`IndexManager::reindex_incremental`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RebuildStats {
+    pub indexed: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub elapsed: Duration,
+}
+
+/// How far a file's modification time may run past its document's
+/// `modified_at` before [`IndexManager::diff_against_vault`] reports it.
+/// MKB stamps `modified_at` just before writing the file.
+pub const MODIFIED_SLACK: chrono::TimeDelta = chrono::TimeDelta::seconds(1);
+
+/// Where a vault and its index disagree, by document ID; see
+/// [`IndexManager::diff_against_vault`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VaultDiff {
+    /// Vault files with no indexed document.
+    pub in_vault_not_index: Vec<String>,
+    /// Indexed documents with no vault file.
+    pub in_index_not_vault: Vec<String>,
+    /// Vault files changed since their document was indexed.
+    pub modified_since_index: Vec<String>,
+}
+
+impl VaultDiff {
+    /// Whether the vault and index agree.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.in_vault_not_index.is_empty()
+            && self.in_index_not_vault.is_empty()
+            && self.modified_since_index.is_empty()
+    }
+}
+
+/// How [`IndexManager::merge_from`] treats a document present in both
+/// indexes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictStrategy {
+    /// Keep this index's version.
+    SkipExisting,
+    /// Take the source's version if its `modified_at` is later.
+    OverwriteWithNewer,
+    /// Abort the merge and roll back.
+    FailOnConflict,
+}
+
+/// Outcome of [`IndexManager::merge_from`], counted in documents.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeStats {
+    pub inserted: usize,
+    pub skipped: usize,
+    pub overwritten: usize,
+    pub failed: usize,
+}
+
+/// How weeks are numbered in week-bucketed histograms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeekMode {
+    /// ISO 8601: weeks start Monday, week 1 contains the first Thursday.
+    /// Labelled with the ISO week-year, e.g. `2025-W01`.
+    Iso,
+    /// US: weeks start Sunday, days before the first Sunday are week 00.
+    /// Labelled e.g. `2025-U00`.
+    Us,
+}
+
+/// Bucket granularity for [`IndexManager::observed_at_histogram`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistogramBucket {
+    Day,
+    Week(WeekMode),
+    Month,
+    Quarter,
+    Year,
+}
+
+impl HistogramBucket {
+    /// Lexically sortable label for the bucket containing `at`.
+    #[must_use]
+    pub fn label(&self, at: &chrono::DateTime<chrono::Utc>) -> String {
+        use chrono::Datelike;
+
+        match self {
+            Self::Day => at.format("%Y-%m-%d").to_string(),
+            Self::Week(WeekMode::Iso) => {
+                let week = at.iso_week();
+                format!("{:04}-W{:02}", week.year(), week.week())
+            }
+            Self::Week(WeekMode::Us) => at.format("%Y-U%U").to_string(),
+            Self::Month => at.format("%Y-%m").to_string(),
+            Self::Quarter => format!("{:04}-Q{}", at.year(), at.month0() / 3 + 1),
+            Self::Year => format!("{:04}", at.year()),
+        }
+    }
+}
+
+/// A single histogram bucket and its document count.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistogramBin {
+    pub label: String,
+    pub count: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, TimeZone, Utc};
+    use mkb_core::temporal::{DecayModel, DecayProfile, RawTemporalInput, TemporalPrecision};
+
+    fn utc(y: i32, m: u32, d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap()
+    }
+
+    pub(crate) fn make_doc(id: &str, doc_type: &str, title: &str, body: &str) -> Document {
+        let input = RawTemporalInput {
+            observed_at: Some(utc(2025, 2, 10)),
+            valid_until: Some(utc(2025, 8, 10)),
+            temporal_precision: Some(TemporalPrecision::Day),
+            occurred_at: None,
+            occurred_until: None,
+        };
+        let profile = DecayProfile::default_profile();
+        let mut doc = Document::new(
+            id.to_string(),
+            doc_type.to_string(),
+            title.to_string(),
+            input,
+            &profile,
+        )
+        .unwrap();
+        doc.body = body.to_string();
+        doc
+    }
+
+    #[test]
+    fn explain_query_shows_scans_and_index_use() {
+        let mgr = IndexManager::in_memory().unwrap();
+        let details = |sql: &str| -> Vec<String> {
+            mgr.explain_query(sql, &[SqlValue::Text("x".to_string())])
+                .unwrap()
+                .into_iter()
+                .map(|row| row.detail)
+                .collect()
+        };
+
+        let scan = details("SELECT id FROM documents d WHERE d.title = ?1");
+        assert!(scan.iter().any(|d| d.starts_with("SCAN")), "{scan:?}");
+
+        let search = details("SELECT id FROM documents d WHERE d.doc_type = ?1");
+        assert!(
+            search.iter().any(|d| d.contains("idx_documents_type")),
+            "{search:?}"
+        );
+
+        assert!(matches!(
+            mgr.explain_query("SELECT nope FROM nowhere", &[]),
+            Err(MkbError::Index(_))
+        ));
+    }
+
+    #[test]
+    fn stream_sql_steps_rows_lazily() {
+        use rusqlite::functions::FunctionFlags;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let mgr = IndexManager::in_memory().unwrap();
+        for i in 0..50 {
+            let id = format!("proj-{i:03}");
+            mgr.index_document(&make_doc(&id, "project", &id, "body"))
+                .unwrap();
+        }
+
+        // Count how many rows SQLite actually evaluates
+        let evaluated = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&evaluated);
+        mgr.conn
+            .create_scalar_function("probe", 1, FunctionFlags::SQLITE_UTF8, move |ctx| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                ctx.get::<String>(0)
+            })
+            .unwrap();
+
+        // No ORDER BY: a sort would have to evaluate every row first
+        let first: Vec<SqlRow> = mgr
+            .stream_sql("SELECT probe(id) AS id FROM documents", &[])
+            .unwrap()
+            .take(3)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(first.len(), 3);
+        assert_eq!(evaluated.load(Ordering::SeqCst), 3);
+
+        let all = mgr
+            .execute_sql("SELECT probe(id) AS id FROM documents", &[])
+            .unwrap();
+        assert_eq!(all.len(), 50);
+    }
+
+    #[test]
+    fn open_reports_garbage_file_as_corrupted() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("mkb.db");
+        fs::write(
+            &path,
+            "this is not a sqlite database, just some text".repeat(100),
+        )
+        .unwrap();
+        assert!(matches!(
+            IndexManager::open(&path),
+            Err(MkbError::IndexCorrupted { .. })
+        ));
+    }
+
+    #[test]
+    fn open_uses_wal_for_concurrent_reader_and_writer() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("mkb.db");
+        let mgr = IndexManager::open(&path).unwrap();
+        let mode: String = mgr
+            .conn
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(mode, "wal");
+
+        let writer_path = path.clone();
+        let writer = std::thread::spawn(move || {
+            let index = IndexManager::open(&writer_path).unwrap();
+            for i in 0..200 {
+                let id = format!("proj-{i:03}");
+                index
+                    .index_document(&make_doc(&id, "project", &id, "body"))
+                    .unwrap();
+            }
+        });
+        let reader = std::thread::spawn(move || {
+            let index = IndexManager::open(&path).unwrap();
+            let mut last = 0;
+            for _ in 0..200 {
+                let count = index.count().unwrap();
+                assert!(count >= last);
+                last = count;
+            }
+        });
+        writer.join().unwrap();
+        reader.join().unwrap();
+
+        assert_eq!(mgr.count().unwrap(), 200);
+        mgr.checkpoint().unwrap();
+        let wal = dir.path().join("mkb.db-wal");
+        assert_eq!(fs::metadata(wal).map(|m| m.len()).unwrap_or(0), 0);
+    }
+
+    #[test]
+    fn search_fts_with_snippets_highlights_match() {
+        let mgr = IndexManager::in_memory().unwrap();
+        let body = "Kickoff notes. The team agreed to migrate the billing service \
+                    to Rust before the end of the quarter.";
+        mgr.index_document(&make_doc("proj-a", "project", "Alpha", body))
+            .unwrap();
+
+        let hits = mgr.search_fts_with_snippets("billing").unwrap();
+        assert_eq!(hits.len(), 1);
+        let snippet = hits[0].snippet.as_deref().unwrap();
+        assert!(snippet.contains("<b>billing</b>"), "snippet: {snippet}");
+
+        assert!(mgr.search_fts("billing").unwrap()[0].snippet.is_none());
+    }
+
+    #[test]
+    fn rebuild_fts_repairs_deleted_fts_row() {
+        let mgr = IndexManager::in_memory().unwrap();
+        mgr.index_document(&make_doc("proj-a", "project", "Alpha", "rust compiler"))
+            .unwrap();
+        mgr.index_document(&make_doc("proj-b", "project", "Beta", "python scripts"))
+            .unwrap();
+        assert!(mgr.fts_integrity_check().unwrap().ok);
+
+        mgr.conn
+            .execute(
+                "DELETE FROM documents_fts WHERE rowid = \
+                 (SELECT rowid FROM documents WHERE id = 'proj-a')",
+                [],
+            )
+            .unwrap();
+        assert!(mgr.search_fts("rust").unwrap().is_empty());
+        let check = mgr.fts_integrity_check().unwrap();
+        assert!(!check.ok);
+        assert!(check.message.is_some());
+
+        mgr.rebuild_fts().unwrap();
+        assert_eq!(
+            mgr.fts_integrity_check().unwrap(),
+            FtsIntegrityResult {
+                ok: true,
+                message: None,
+            }
+        );
+        let hits = mgr.search_fts("rust").unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].id, "proj-a");
+        assert_eq!(mgr.search_fts("python").unwrap()[0].id, "proj-b");
+    }
+
+    #[test]
+    fn creates_schema_on_init() {
+        let mgr = IndexManager::in_memory().unwrap();
+        // Schema exists — can count without error
+        let count = mgr.count().unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn open_adds_new_columns_to_older_indexes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("mkb.db");
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE documents (
+                    id TEXT PRIMARY KEY, doc_type TEXT NOT NULL, title TEXT NOT NULL,
+                    observed_at TEXT NOT NULL, valid_until TEXT NOT NULL,
+                    temporal_precision TEXT NOT NULL, occurred_at TEXT,
+                    created_at TEXT NOT NULL, modified_at TEXT NOT NULL,
+                    confidence REAL NOT NULL DEFAULT 1.0, source TEXT, supersedes TEXT,
+                    superseded_by TEXT, tags TEXT, body TEXT NOT NULL DEFAULT ''
+                );
+                CREATE INDEX idx_documents_type ON documents(doc_type);",
+            )
+            .unwrap();
+
+        let mgr = IndexManager::open(&path).unwrap();
+        let type_index = mgr
+            .execute_sql(
+                "SELECT sql FROM sqlite_master WHERE name = 'idx_documents_type'",
+                &[],
+            )
+            .unwrap();
+        assert!(
+            type_index[0]["sql"]
+                .as_str()
+                .unwrap()
+                .contains("COALESCE(occurred_until, occurred_at, observed_at)"),
+            "{type_index:?}"
+        );
+        let mut doc = make_doc("event-conf-001", "event", "Conference", "");
+        doc.temporal.occurred_at = Some(utc(2025, 1, 5));
+        doc.temporal.occurred_until = Some(utc(2025, 1, 8));
+        mgr.index_document(&doc).unwrap();
+
+        let rows = mgr
+            .execute_sql("SELECT occurred_until, provenance_type FROM documents", &[])
+            .unwrap();
+        assert_eq!(rows[0]["occurred_until"], "2025-01-08T00:00:00+00:00");
+        assert!(rows[0]["provenance_type"].is_null());
+
+        // Reopening is a no-op
+        drop(mgr);
+        IndexManager::open(&path).unwrap();
+    }
+
+    #[test]
+    fn index_document_stores_all_frontmatter_fields() {
+        let mgr = IndexManager::in_memory().unwrap();
+
+        let mut doc = make_doc("proj-alpha-001", "project", "Alpha Project", "Some body");
+        doc.tags = vec!["rust".to_string(), "ai".to_string()];
+        doc.confidence = 0.95;
+        doc.provenance = Some(mkb_core::ProvenanceRecord {
+            source_uri: Some("notes/alpha.md".to_string()),
+            ..mkb_core::ProvenanceRecord::new(mkb_core::ProvenanceType::LlmGenerated, "mcp")
+        });
+
+        mgr.index_document(&doc).unwrap();
+
+        assert_eq!(mgr.count().unwrap(), 1);
+        let rows = mgr
+            .execute_sql("SELECT source, provenance_type FROM documents", &[])
+            .unwrap();
+        assert_eq!(rows[0]["source"], "notes/alpha.md");
+        assert_eq!(rows[0]["provenance_type"], "llm_generated");
+
+        let results = mgr.query_by_type("project").unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "proj-alpha-001");
+        assert_eq!(results[0].title, "Alpha Project");
+        assert!((results[0].confidence - 0.95).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn fts_indexes_title_and_body() {
+        let mgr = IndexManager::in_memory().unwrap();
+
+        mgr.index_document(&make_doc(
+            "proj-alpha-001",
+            "project",
+            "Alpha Project",
+            "This project uses Rust and machine learning.",
+        ))
+        .unwrap();
+
+        mgr.index_document(&make_doc(
+            "proj-beta-001",
+            "project",
+            "Beta Project",
+            "A Python data pipeline for analytics.",
+        ))
+        .unwrap();
+
+        // Search in body
+        let results = mgr.search_fts("machine learning").unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "proj-alpha-001");
+
+        // Search in title
+        let results = mgr.search_fts("Beta").unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "proj-beta-001");
+    }
+
+    #[test]
+    fn fts_search_returns_ranked_results() {
+        let mgr = IndexManager::in_memory().unwrap();
+
+        mgr.index_document(&make_doc(
+            "d1",
+            "project",
+            "Rust Project",
+            "Rust is great for systems programming with Rust tools.",
+        ))
+        .unwrap();
+
+        mgr.index_document(&make_doc(
+            "d2",
+            "project",
+            "Python Project",
+            "Python is great. Also mentions Rust once.",
+        ))
+        .unwrap();
+
+        let results = mgr.search_fts("Rust").unwrap();
+        assert_eq!(results.len(), 2);
+        // d1 should rank higher (more mentions of "Rust")
+        assert_eq!(results[0].id, "d1");
+    }
+
+    #[test]
+    fn remove_document_deletes_from_index() {
+        let mgr = IndexManager::in_memory().unwrap();
+
+        mgr.index_document(&make_doc("d1", "project", "Alpha", "body"))
+            .unwrap();
+        assert_eq!(mgr.count().unwrap(), 1);
+
+        mgr.remove_document("d1").unwrap();
+        assert_eq!(mgr.count().unwrap(), 0);
+    }
+
+    #[test]
+    fn query_all_returns_all_documents() {
+        let mgr = IndexManager::in_memory().unwrap();
+
+        mgr.index_document(&make_doc("d1", "project", "Alpha", "body1"))
+            .unwrap();
+        mgr.index_document(&make_doc("d2", "meeting", "Sprint Review", "body2"))
+            .unwrap();
+
+        let all = mgr.query_all().unwrap();
+        assert_eq!(all.len(), 2);
+    }
+
+    #[test]
+    fn query_newest_per_type_picks_latest_observed() {
+        let mgr = IndexManager::in_memory().unwrap();
+        assert!(mgr.query_newest_per_type().unwrap().is_empty());
+
+        let observed = |id: &str, doc_type: &str, day: u32| {
+            let mut doc = make_doc(id, doc_type, id, "");
+            doc.temporal.observed_at = utc(2025, 2, day);
+            doc
+        };
+        mgr.index_document(&observed("p-old", "project", 1))
+            .unwrap();
+        mgr.index_document(&observed("p-new", "project", 20))
+            .unwrap();
+        mgr.index_document(&observed("p-mid", "project", 10))
+            .unwrap();
+        mgr.index_document(&observed("m-b", "meeting", 5)).unwrap();
+        mgr.index_document(&observed("m-a", "meeting", 5)).unwrap();
+
+        let newest = mgr.query_newest_per_type().unwrap();
+        assert_eq!(newest.len(), 2);
+        assert_eq!(newest["project"].id, "p-new");
+        assert_eq!(newest["meeting"].id, "m-a");
+    }
+
+    #[test]
+    fn index_document_upserts_on_duplicate_id() {
+        let mgr = IndexManager::in_memory().unwrap();
+
+        mgr.index_document(&make_doc("d1", "project", "Original", "body"))
+            .unwrap();
+        mgr.index_document(&make_doc("d1", "project", "Updated", "new body"))
+            .unwrap();
+
+        assert_eq!(mgr.count().unwrap(), 1);
+        let results = mgr.query_by_type("project").unwrap();
+        assert_eq!(results[0].title, "Updated");
+    }
+
+    // === T-110.3 tests: link indexing ===
+
+    #[test]
+    fn link_creation_with_timestamp() {
+        let link = mkb_core::link::Link {
+            rel: "owner".to_string(),
+            target: "people/jane-smith".to_string(),
+            observed_at: utc(2025, 2, 10),
+            metadata: None,
+        };
+        assert_eq!(link.rel, "owner");
+        assert_eq!(link.observed_at, utc(2025, 2, 10));
+    }
+
+    #[test]
+    fn store_and_retrieve_links() {
+        let mgr = IndexManager::in_memory().unwrap();
+        let doc = make_doc("proj-alpha-001", "project", "Alpha", "body");
+        mgr.index_document(&doc).unwrap();
+
+        let links = vec![
+            mkb_core::link::Link {
+                rel: "owner".to_string(),
+                target: "people/jane-smith".to_string(),
+                observed_at: utc(2025, 2, 10),
+                metadata: None,
+            },
+            mkb_core::link::Link {
+                rel: "blocked_by".to_string(),
+                target: "proj-beta-001".to_string(),
+                observed_at: utc(2025, 2, 10),
+                metadata: None,
+            },
+        ];
+
+        mgr.store_links("proj-alpha-001", &links).unwrap();
+        let forward = mgr.query_forward_links("proj-alpha-001").unwrap();
+        assert_eq!(forward.len(), 2);
+    }
+
+    #[test]
+    fn link_graph_loads_documents_and_links() {
+        let mgr = IndexManager::in_memory().unwrap();
+        for (id, title) in [
+            ("proj-alpha-001", "Alpha"),
+            ("proj-beta-001", "Beta"),
+            ("proj-gamma-001", "Gamma"),
+        ] {
+            mgr.index_document(&make_doc(id, "project", title, "body"))
+                .unwrap();
+        }
+        let link = |rel: &str, target: &str| mkb_core::link::Link {
+            rel: rel.to_string(),
+            target: target.to_string(),
+            observed_at: utc(2025, 2, 10),
+            metadata: None,
+        };
+        mgr.store_links(
+            "proj-alpha-001",
+            &[
+                link("depends_on", "proj-beta-001"),
+                link("owner", "people/ghost"),
+            ],
+        )
+        .unwrap();
+
+        let graph = mgr.link_graph().unwrap();
+        assert_eq!(graph.node_count(), 4);
+        assert_eq!(graph.edge_count(), 2);
+        assert!(!graph.has_cycle());
+        assert_eq!(graph.connected_components().len(), 2);
+        assert_eq!(
+            graph.shortest_path("proj-alpha-001", "proj-beta-001"),
+            Some(vec![
+                "proj-alpha-001".to_string(),
+                "proj-beta-001".to_string()
+            ])
+        );
+        assert_eq!(
+            graph.shortest_path("proj-alpha-001", "proj-gamma-001"),
+            None
+        );
+    }
+
+    #[test]
+    fn detect_and_remove_dangling_links() {
+        let mgr = IndexManager::in_memory().unwrap();
+        mgr.index_document(&make_doc("proj-alpha-001", "project", "Alpha", "body"))
+            .unwrap();
+        mgr.index_document(&make_doc("proj-beta-001", "project", "Beta", "body"))
+            .unwrap();
+
+        let link = |rel: &str, target: &str| mkb_core::link::Link {
+            rel: rel.to_string(),
+            target: target.to_string(),
+            observed_at: utc(2025, 2, 10),
+            metadata: None,
+        };
+        mgr.store_links(
+            "proj-alpha-001",
+            &[
+                link("blocked_by", "proj-beta-001"),
+                link("owner", "people/ghost"),
+                link("depends_on", "proj-missing-001"),
+            ],
+        )
+        .unwrap();
+
+        let dangling = mgr.detect_dangling_links().unwrap();
+        assert_eq!(
+            dangling,
+            vec![
+                DanglingLink {
+                    source_id: "proj-alpha-001".to_string(),
+                    target_id: "people/ghost".to_string(),
+                    rel: "owner".to_string(),
+                },
+                DanglingLink {
+                    source_id: "proj-alpha-001".to_string(),
+                    target_id: "proj-missing-001".to_string(),
+                    rel: "depends_on".to_string(),
+                },
+            ]
+        );
+
+        assert_eq!(mgr.remove_dangling_links().unwrap(), dangling);
+        assert!(mgr.detect_dangling_links().unwrap().is_empty());
+        let forward = mgr.query_forward_links("proj-alpha-001").unwrap();
+        assert_eq!(forward.len(), 1);
+        assert_eq!(forward[0].target_id, "proj-beta-001");
+    }
+
+    #[test]
+    fn query_forward_links() {
+        let mgr = IndexManager::in_memory().unwrap();
+        let doc = make_doc("proj-alpha-001", "project", "Alpha", "body");
+        mgr.index_document(&doc).unwrap();
+
+        let links = vec![mkb_core::link::Link {
+            rel: "owner".to_string(),
+            target: "people/jane-smith".to_string(),
+            observed_at: utc(2025, 2, 10),
+            metadata: None,
+        }];
+        mgr.store_links("proj-alpha-001", &links).unwrap();
+
+        let forward = mgr.query_forward_links("proj-alpha-001").unwrap();
+        assert_eq!(forward.len(), 1);
+        assert_eq!(forward[0].target_id, "people/jane-smith");
+        assert_eq!(forward[0].rel, "owner");
+    }
+
+    #[test]
+    fn query_reverse_links() {
+        let mgr = IndexManager::in_memory().unwrap();
+
+        let doc1 = make_doc("proj-alpha-001", "project", "Alpha", "body");
+        mgr.index_document(&doc1).unwrap();
+        let doc2 = make_doc("proj-beta-001", "project", "Beta", "body");
+        mgr.index_document(&doc2).unwrap();
+
+        // Both projects link to same person
+        mgr.store_links(
+            "proj-alpha-001",
+            &[mkb_core::link::Link {
+                rel: "owner".to_string(),
+                target: "people/jane-smith".to_string(),
+                observed_at: utc(2025, 2, 10),
+                metadata: None,
+            }],
+        )
+        .unwrap();
+        mgr.store_links(
+            "proj-beta-001",
+            &[mkb_core::link::Link {
+                rel: "owner".to_string(),
+                target: "people/jane-smith".to_string(),
+                observed_at: utc(2025, 2, 10),
+                metadata: None,
+            }],
+        )
+        .unwrap();
+
+        let reverse = mgr.query_reverse_links("people/jane-smith").unwrap();
+        assert_eq!(reverse.len(), 2);
+        let sources: Vec<&str> = reverse.iter().map(|l| l.source_id.as_str()).collect();
+        assert!(sources.contains(&"proj-alpha-001"));
+        assert!(sources.contains(&"proj-beta-001"));
+    }
+
+    // === T-110.4 tests: temporal queries ===
+
+    #[test]
+    fn query_by_observed_at_range() {
+        let mgr = IndexManager::in_memory().unwrap();
+
+        // Doc observed in January
+        let d1 = make_doc("d1", "project", "January Doc", "body1");
+        mgr.index_document(&d1).unwrap();
+
+        // Doc observed in March (create with different observed_at)
+        let input = RawTemporalInput {
+            observed_at: Some(utc(2025, 3, 15)),
+            valid_until: Some(utc(2025, 9, 15)),
+            temporal_precision: Some(TemporalPrecision::Day),
+            occurred_at: None,
+            occurred_until: None,
+        };
+        let profile = DecayProfile::default_profile();
+        let mut d2 = Document::new(
+            "d2".into(),
+            "project".into(),
+            "March Doc".into(),
+            input,
+            &profile,
+        )
+        .unwrap();
+        d2.body = "body2".into();
+        mgr.index_document(&d2).unwrap();
+
+        // Query range that only includes February (from Feb 1 to Feb 28)
+        let results = mgr
+            .query_by_observed_at_range("2025-02-01T00:00:00+00:00", "2025-02-28T23:59:59+00:00")
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "d1");
+
+        // Query range that includes both
+        let results = mgr
+            .query_by_observed_at_range("2025-01-01T00:00:00+00:00", "2025-12-31T23:59:59+00:00")
+            .unwrap();
+        assert_eq!(results.len(), 2);
+    }
+
+    #[test]
+    fn query_current_documents() {
+        let mgr = IndexManager::in_memory().unwrap();
+
+        // Active document (valid until Aug 2025)
+        let d1 = make_doc("d1", "project", "Active", "body1");
+        mgr.index_document(&d1).unwrap();
+
+        // Expired document (valid until Jan 2025, before our query time)
+        let input = RawTemporalInput {
+            observed_at: Some(utc(2024, 6, 1)),
+            valid_until: Some(utc(2025, 1, 1)),
+            temporal_precision: Some(TemporalPrecision::Day),
+            occurred_at: None,
+            occurred_until: None,
+        };
+        let profile = DecayProfile::default_profile();
+        let mut d2 = Document::new(
+            "d2".into(),
+            "project".into(),
+            "Expired".into(),
+            input,
+            &profile,
+        )
+        .unwrap();
+        d2.body = "body2".into();
+        mgr.index_document(&d2).unwrap();
+
+        // Superseded document
+        let mut d3 = make_doc("d3", "project", "Superseded", "body3");
+        d3.superseded_by = Some("d1".to_string());
+        mgr.index_document(&d3).unwrap();
+
+        // Query current at Feb 2025: should only return d1
+        let current = mgr
+            .query_current_documents("2025-02-15T00:00:00+00:00")
+            .unwrap();
+        assert_eq!(current.len(), 1);
+        assert_eq!(current[0].id, "d1");
+    }
+
+    #[test]
+    fn query_with_effective_confidence() {
+        let mgr = IndexManager::in_memory().unwrap();
+
+        // High-confidence recent doc
+        let mut d1 = make_doc("d1", "project", "Recent", "body1");
+        d1.confidence = 0.95;
+        mgr.index_document(&d1).unwrap();
+
+        // Low-confidence old doc
+        let input = RawTemporalInput {
+            observed_at: Some(utc(2024, 1, 1)),
+            valid_until: Some(utc(2026, 1, 1)),
+            temporal_precision: Some(TemporalPrecision::Day),
+            occurred_at: None,
+            occurred_until: None,
+        };
+        let profile = DecayProfile::default_profile();
+        let mut d2 =
+            Document::new("d2".into(), "project".into(), "Old".into(), input, &profile).unwrap();
+        d2.body = "body2".into();
+        d2.confidence = 0.5;
+        mgr.index_document(&d2).unwrap();
+
+        // Query all and check confidence values are retrievable
+        let all = mgr.query_all().unwrap();
+        assert_eq!(all.len(), 2);
+
+        let recent = all.iter().find(|d| d.id == "d1").unwrap();
+        assert!((recent.confidence - 0.95).abs() < f64::EPSILON);
+
+        let old = all.iter().find(|d| d.id == "d2").unwrap();
+        assert!((old.confidence - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn staleness_sweep_marks_expired() {
+        let mgr = IndexManager::in_memory().unwrap();
+
+        // Doc valid until June 2025
+        let d1 = make_doc("d1", "project", "Valid", "body1");
+        mgr.index_document(&d1).unwrap();
+
+        // Doc valid until Jan 2025 (expired)
+        let input = RawTemporalInput {
+            observed_at: Some(utc(2024, 6, 1)),
+            valid_until: Some(utc(2025, 1, 1)),
+            temporal_precision: Some(TemporalPrecision::Day),
+            occurred_at: None,
+            occurred_until: None,
+        };
+        let profile = DecayProfile::default_profile();
+        let mut d2 = Document::new(
+            "d2".into(),
+            "project".into(),
+            "Expired".into(),
+            input,
+            &profile,
+        )
+        .unwrap();
+        d2.body = "body2".into();
+        mgr.index_document(&d2).unwrap();
+
+        // Sweep at Feb 2025
+        let stale = mgr.staleness_sweep("2025-02-15T00:00:00+00:00").unwrap();
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0], "d2");
+    }
+
+    #[test]
+    fn staleness_sweep_with_recency_skips_recently_edited() {
+        let mgr = IndexManager::in_memory().unwrap();
+        for (id, modified_at) in [("edited", utc(2025, 2, 10)), ("idle", utc(2024, 6, 1))] {
+            let mut doc = doc_observed_at(id, utc(2024, 6, 1));
+            doc.temporal.valid_until = utc(2025, 1, 1);
+            doc.modified_at = modified_at;
+            mgr.index_document(&doc).unwrap();
+        }
+
+        let at = "2025-02-15T00:00:00+00:00";
+        assert_eq!(mgr.staleness_sweep(at).unwrap().len(), 2);
+        let week = Duration::from_secs(7 * 86_400);
+        assert_eq!(
+            mgr.staleness_sweep_with_recency(at, Some(week)).unwrap(),
+            ["idle"]
+        );
+    }
+
+    // === T-410.2 tests: sqlite-vec vector operations ===
+
+    /// Generate a deterministic test embedding from a seed string.
+    fn test_embedding(seed: &str) -> Vec<f32> {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+        let mut vec = vec![0.0f32; EMBEDDING_DIM];
+        for (i, v) in vec.iter_mut().enumerate() {
+            let mut h = DefaultHasher::new();
+            seed.hash(&mut h);
+            i.hash(&mut h);
+            *v = (h.finish() as f32 / u64::MAX as f32) * 2.0 - 1.0;
+        }
+        // Normalize to unit vector
+        let norm: f32 = vec.iter().map(|x| x * x).sum::<f32>().sqrt();
+        for v in &mut vec {
+            *v /= norm;
+        }
+        vec
     }
 
     #[test]
-    fn fts_indexes_title_and_body() {
+    fn store_and_query_embedding() {
         let mgr = IndexManager::in_memory().unwrap();
 
-        mgr.index_document(&make_doc(
-            "proj-alpha-001",
-            "project",
-            "Alpha Project",
-            "This project uses Rust and machine learning.",
-        ))
-        .unwrap();
+        let doc = make_doc("d1", "project", "Alpha", "body");
+        mgr.index_document(&doc).unwrap();
 
-        mgr.index_document(&make_doc(
-            "proj-beta-001",
-            "project",
-            "Beta Project",
-            "A Python data pipeline for analytics.",
-        ))
-        .unwrap();
+        let emb = test_embedding("alpha");
+        mgr.store_embedding("d1", &emb, "test-model").unwrap();
 
-        // Search in body
-        let results = mgr.search_fts("machine learning").unwrap();
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].id, "proj-alpha-001");
+        assert!(mgr.has_embedding("d1").unwrap());
+        assert!(!mgr.has_embedding("d2").unwrap());
+        assert_eq!(mgr.embedding_count().unwrap(), 1);
+    }
 
-        // Search in title
-        let results = mgr.search_fts("Beta").unwrap();
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].id, "proj-beta-001");
+    #[test]
+    fn semantic_search_returns_similar_documents() {
+        let mgr = IndexManager::in_memory().unwrap();
+
+        // Create 3 documents with different embeddings
+        for (id, doc_type, title) in &[
+            ("d1", "project", "Alpha Project"),
+            ("d2", "project", "Beta Project"),
+            ("d3", "meeting", "Standup Meeting"),
+        ] {
+            let doc = make_doc(id, doc_type, title, "body");
+            mgr.index_document(&doc).unwrap();
+            mgr.store_embedding(id, &test_embedding(id), "test-model")
+                .unwrap();
+        }
+
+        // Query with the same embedding as d1 — should return d1 first
+        let results = mgr.search_semantic(&test_embedding("d1"), 3).unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].id, "d1");
+        assert!(results[0].distance < results[1].distance);
+    }
+
+    #[test]
+    fn semantic_search_by_type_filters_before_ranking() {
+        let mgr = IndexManager::in_memory().unwrap();
+        for (id, doc_type) in [
+            ("d1", "project"),
+            ("d2", "meeting"),
+            ("d3", "meeting"),
+            ("d4", "decision"),
+        ] {
+            mgr.index_document(&make_doc(id, doc_type, id, "body"))
+                .unwrap();
+            mgr.store_embedding(id, &test_embedding(id), "test-model")
+                .unwrap();
+        }
+        let query = test_embedding("d1");
+
+        // d1 would take the only KNN slot; the type filter must skip it
+        let meetings = mgr.search_semantic_by_type(&query, "meeting", 1).unwrap();
+        assert_eq!(meetings.len(), 1);
+        assert_eq!(meetings[0].doc_type, "meeting");
+
+        let all = mgr.search_semantic(&query, 4).unwrap();
+        let expected: Vec<&str> = all
+            .iter()
+            .filter(|r| r.doc_type != "project")
+            .map(|r| r.id.as_str())
+            .collect();
+        let multi = mgr
+            .search_semantic_by_types(&query, &["meeting", "decision"], 10)
+            .unwrap();
+        assert_eq!(
+            multi.iter().map(|r| r.id.as_str()).collect::<Vec<_>>(),
+            expected
+        );
+        for (m, a) in multi
+            .iter()
+            .zip(all.iter().filter(|r| r.doc_type != "project"))
+        {
+            assert!((m.distance - a.distance).abs() < 1e-4);
+        }
+
+        assert!(mgr
+            .search_semantic_by_types(&query, &[], 10)
+            .unwrap()
+            .is_empty());
+        assert!(mgr
+            .search_semantic_by_type(&[0.0; 3], "meeting", 1)
+            .unwrap_err()
+            .to_string()
+            .contains("dimension mismatch"));
+    }
+
+    #[test]
+    fn embedding_dimension_mismatch_rejected() {
+        let mgr = IndexManager::in_memory().unwrap();
+
+        let doc = make_doc("d1", "project", "Alpha", "body");
+        mgr.index_document(&doc).unwrap();
+
+        let wrong_dim = vec![0.0f32; 768]; // Wrong dimension
+        let result = mgr.store_embedding("d1", &wrong_dim, "test-model");
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("dimension mismatch"));
+    }
+
+    #[test]
+    fn search_with_mismatched_dimension_names_both_dimensions() {
+        let mgr = IndexManager::in_memory().unwrap();
+        mgr.index_document(&make_doc("d1", "project", "Alpha", "body"))
+            .unwrap();
+        assert_eq!(mgr.embedding_dimension(), None);
+
+        mgr.store_embedding("d1", &test_embedding("d1"), "text-embedding-3-small")
+            .unwrap();
+        assert_eq!(mgr.embedding_dimension(), Some(EMBEDDING_DIM));
+
+        let err = mgr
+            .search_semantic(&vec![0.1f32; 768], 5)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("1536-dim"), "got: {err}");
+        assert!(err.contains("768 dims"), "got: {err}");
+        assert!(err.contains("text-embedding-3-small"), "got: {err}");
+    }
+
+    #[test]
+    fn mock_embedding_is_finite() {
+        for text in ["machine learning", "gardening", "d1", ""] {
+            assert!(mock_embedding(text).iter().all(|v| v.is_finite()));
+        }
+    }
+
+    #[test]
+    fn get_embedding_round_trips() {
+        let mgr = IndexManager::in_memory().unwrap();
+        mgr.index_document(&make_doc("d1", "project", "Alpha", "body"))
+            .unwrap();
+        assert_eq!(mgr.get_embedding("d1").unwrap(), None);
+
+        let emb = test_embedding("d1");
+        mgr.store_embedding("d1", &emb, "test-model").unwrap();
+        assert_eq!(mgr.get_embedding("d1").unwrap(), Some(emb));
+    }
+
+    #[test]
+    fn embedding_models_counts_each_model() {
+        let mgr = IndexManager::in_memory().unwrap();
+        assert!(mgr.embedding_models().unwrap().is_empty());
+
+        for (id, model) in [
+            ("d1", "mock"),
+            ("d2", "text-embedding-3-small"),
+            ("d3", "mock"),
+        ] {
+            mgr.index_document(&make_doc(id, "project", id, "body"))
+                .unwrap();
+            mgr.store_embedding(id, &test_embedding(id), model).unwrap();
+        }
+
+        assert_eq!(
+            mgr.embedding_models().unwrap(),
+            vec![
+                ("mock".to_string(), 2),
+                ("text-embedding-3-small".to_string(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn embedding_model_stats_and_missing_documents() {
+        let mgr = IndexManager::in_memory().unwrap();
+        assert!(mgr.get_embedding_model_stats().unwrap().is_empty());
+
+        for (id, model) in [
+            ("d1", Some("mock")),
+            ("d2", Some("text-embedding-3-small")),
+            ("d3", Some("mock")),
+            ("d4", None),
+            ("d5", Some("mock")),
+        ] {
+            mgr.index_document(&make_doc(id, "project", id, "body"))
+                .unwrap();
+            if let Some(model) = model {
+                mgr.store_embedding(id, &test_embedding(id), model).unwrap();
+            }
+        }
+        mgr.remove_embedding("d5").unwrap();
+        // Pin timestamps so oldest and newest differ
+        mgr.conn
+            .execute_batch(
+                "UPDATE document_embeddings SET created_at = '2025-01-01 00:00:00' WHERE id = 'd1';
+                 UPDATE document_embeddings SET created_at = '2025-03-01 00:00:00' WHERE id = 'd3';",
+            )
+            .unwrap();
+
+        let stats = mgr.get_embedding_model_stats().unwrap();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(
+            stats[0],
+            EmbeddingModelStat {
+                model: "mock".to_string(),
+                count: 2,
+                oldest_created_at: "2025-01-01 00:00:00".to_string(),
+                newest_created_at: "2025-03-01 00:00:00".to_string(),
+            }
+        );
+        assert_eq!(stats[1].model, "text-embedding-3-small");
+        assert_eq!(stats[1].count, 1);
+        assert_eq!(stats[1].oldest_created_at, stats[1].newest_created_at);
+
+        let missing: Vec<String> = mgr
+            .documents_without_embedding()
+            .unwrap()
+            .into_iter()
+            .map(|d| d.id)
+            .collect();
+        assert_eq!(missing, vec!["d4", "d5"]);
+    }
+
+    /// A target with `shared` (modified 2025-02-10) and a source with a
+    /// newer `shared` plus `only-source`, each with a link and embedding.
+    fn merge_fixture() -> (IndexManager, IndexManager) {
+        let link = |target: &str| mkb_core::link::Link {
+            rel: "related".to_string(),
+            target: target.to_string(),
+            observed_at: utc(2025, 2, 10),
+            metadata: None,
+        };
+
+        let target = IndexManager::in_memory().unwrap();
+        let mut shared = make_doc("shared", "project", "Target version", "old");
+        shared.modified_at = utc(2025, 2, 10);
+        target.index_document(&shared).unwrap();
+        target.store_links("shared", &[link("old-target")]).unwrap();
+        target
+            .store_embedding("shared", &test_embedding("old"), "mock")
+            .unwrap();
+
+        let source = IndexManager::in_memory().unwrap();
+        let mut newer = make_doc("shared", "project", "Source version", "new");
+        newer.modified_at = utc(2025, 3, 1);
+        source.index_document(&newer).unwrap();
+        source.store_links("shared", &[link("new-target")]).unwrap();
+        source
+            .store_embedding("shared", &test_embedding("new"), "mock")
+            .unwrap();
+        source
+            .index_document(&make_doc("only-source", "project", "Fresh", "body"))
+            .unwrap();
+        source
+            .store_links("only-source", &[link("shared")])
+            .unwrap();
+        source
+            .store_embedding("only-source", &test_embedding("fresh"), "mock")
+            .unwrap();
+
+        (target, source)
+    }
+
+    #[test]
+    fn merge_from_skip_existing_keeps_target_version() {
+        let (target, source) = merge_fixture();
+        let stats = target
+            .merge_from(&source, ConflictStrategy::SkipExisting)
+            .unwrap();
+        assert_eq!(
+            stats,
+            MergeStats {
+                inserted: 1,
+                skipped: 1,
+                overwritten: 0,
+                failed: 0
+            }
+        );
+
+        let shared = target.query_by_id("shared").unwrap().unwrap();
+        assert_eq!(shared.title, "Target version");
+        assert_eq!(
+            target.query_forward_links("shared").unwrap()[0].target_id,
+            "old-target"
+        );
+        assert_eq!(
+            target.get_embedding("shared").unwrap(),
+            Some(test_embedding("old"))
+        );
+
+        // The inserted document brings its links, embedding and FTS entry
+        assert_eq!(target.count().unwrap(), 2);
+        assert_eq!(target.query_forward_links("only-source").unwrap().len(), 1);
+        assert_eq!(
+            target.get_embedding("only-source").unwrap(),
+            Some(test_embedding("fresh"))
+        );
+        assert_eq!(target.search_fts("Fresh").unwrap()[0].id, "only-source");
+    }
+
+    #[test]
+    fn merge_from_overwrite_with_newer_compares_modified_at() {
+        let (target, source) = merge_fixture();
+        let stats = target
+            .merge_from(&source, ConflictStrategy::OverwriteWithNewer)
+            .unwrap();
+        assert_eq!((stats.inserted, stats.overwritten), (1, 1));
+
+        let shared = target.query_by_id("shared").unwrap().unwrap();
+        assert_eq!(shared.title, "Source version");
+        let links = target.query_forward_links("shared").unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].target_id, "new-target");
+        assert_eq!(
+            target.get_embedding("shared").unwrap(),
+            Some(test_embedding("new"))
+        );
+        assert!(target.search_fts("Target").unwrap().is_empty());
+
+        // Merging back the other way leaves the newer version alone
+        let stats = source
+            .merge_from(&target, ConflictStrategy::OverwriteWithNewer)
+            .unwrap();
+        assert_eq!((stats.skipped, stats.overwritten), (2, 0));
+        let shared = source.query_by_id("shared").unwrap().unwrap();
+        assert_eq!(shared.title, "Source version");
+    }
+
+    #[test]
+    fn merge_from_fail_on_conflict_rolls_back() {
+        let (target, source) = merge_fixture();
+        let err = target
+            .merge_from(&source, ConflictStrategy::FailOnConflict)
+            .unwrap_err();
+        assert!(err.to_string().contains("'shared'"), "{err}");
+
+        // `only-source` sorts after `shared`, but nothing may be left half-merged
+        assert_eq!(target.count().unwrap(), 1);
+        let shared = target.query_by_id("shared").unwrap().unwrap();
+        assert_eq!(shared.title, "Target version");
+        assert_eq!(target.embedding_count().unwrap(), 1);
+
+        // Without a conflict the same strategy merges normally
+        let empty = IndexManager::in_memory().unwrap();
+        let stats = empty
+            .merge_from(&source, ConflictStrategy::FailOnConflict)
+            .unwrap();
+        assert_eq!(stats.inserted, 2);
+    }
+
+    #[test]
+    fn remove_embedding_works() {
+        let mgr = IndexManager::in_memory().unwrap();
+
+        let doc = make_doc("d1", "project", "Alpha", "body");
+        mgr.index_document(&doc).unwrap();
+        mgr.store_embedding("d1", &test_embedding("d1"), "test-model")
+            .unwrap();
+
+        assert!(mgr.has_embedding("d1").unwrap());
+        mgr.remove_embedding("d1").unwrap();
+        assert!(!mgr.has_embedding("d1").unwrap());
+        assert_eq!(mgr.embedding_count().unwrap(), 0);
+    }
+
+    #[test]
+    fn persist_and_reload_index() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db_path = dir.path().join("test.db");
+
+        // Create and populate
+        {
+            let mgr = IndexManager::open(&db_path).unwrap();
+            let doc = make_doc("d1", "project", "Alpha", "body");
+            mgr.index_document(&doc).unwrap();
+            mgr.store_embedding("d1", &test_embedding("d1"), "test-model")
+                .unwrap();
+        }
+
+        // Reopen and verify
+        {
+            let mgr = IndexManager::open(&db_path).unwrap();
+            assert_eq!(mgr.count().unwrap(), 1);
+            assert!(mgr.has_embedding("d1").unwrap());
+
+            let results = mgr.search_semantic(&test_embedding("d1"), 1).unwrap();
+            assert_eq!(results.len(), 1);
+            assert_eq!(results[0].id, "d1");
+        }
+    }
+
+    #[test]
+    fn full_rebuild_matches_incremental_index() {
+        // Build incrementally
+        let incremental = IndexManager::in_memory().unwrap();
+        let docs = vec![
+            make_doc("d1", "project", "Alpha", "body1"),
+            make_doc("d2", "project", "Beta", "body2"),
+            make_doc("d3", "meeting", "Sprint", "body3"),
+        ];
+
+        for doc in &docs {
+            incremental.index_document(doc).unwrap();
+        }
+
+        // Build from scratch (simulates full rebuild)
+        let rebuilt = IndexManager::in_memory().unwrap();
+        for doc in &docs {
+            rebuilt.index_document(doc).unwrap();
+        }
+
+        // Both should have same count and same query results
+        assert_eq!(incremental.count().unwrap(), rebuilt.count().unwrap());
+
+        let inc_projects = incremental.query_by_type("project").unwrap();
+        let reb_projects = rebuilt.query_by_type("project").unwrap();
+        assert_eq!(inc_projects.len(), reb_projects.len());
+
+        for (a, b) in inc_projects.iter().zip(reb_projects.iter()) {
+            assert_eq!(a.id, b.id);
+            assert_eq!(a.title, b.title);
+        }
     }
 
     #[test]
-    fn fts_search_returns_ranked_results() {
+    fn rollback_discards_writes_since_begin() {
         let mgr = IndexManager::in_memory().unwrap();
+        mgr.index_document(&make_doc("d1", "project", "Alpha", ""))
+            .unwrap();
 
-        mgr.index_document(&make_doc(
-            "d1",
-            "project",
-            "Rust Project",
-            "Rust is great for systems programming with Rust tools.",
-        ))
-        .unwrap();
-
-        mgr.index_document(&make_doc(
-            "d2",
-            "project",
-            "Python Project",
-            "Python is great. Also mentions Rust once.",
-        ))
-        .unwrap();
+        mgr.begin_transaction().unwrap();
+        mgr.index_document(&make_doc("d2", "project", "Beta", ""))
+            .unwrap();
+        mgr.remove_document("d1").unwrap();
+        mgr.rollback_transaction().unwrap();
 
-        let results = mgr.search_fts("Rust").unwrap();
-        assert_eq!(results.len(), 2);
-        // d1 should rank higher (more mentions of "Rust")
-        assert_eq!(results[0].id, "d1");
+        assert_eq!(mgr.count().unwrap(), 1);
+        assert!(mgr.query_by_id("d1").unwrap().is_some());
+        assert!(mgr.query_by_id("d2").unwrap().is_none());
     }
 
     #[test]
-    fn remove_document_deletes_from_index() {
+    fn commit_persists_transaction_writes() {
         let mgr = IndexManager::in_memory().unwrap();
+        mgr.begin_transaction().unwrap();
+        mgr.index_document(&make_doc("d1", "project", "Alpha", ""))
+            .unwrap();
+        mgr.commit_transaction().unwrap();
 
-        mgr.index_document(&make_doc("d1", "project", "Alpha", "body"))
+        assert_eq!(mgr.count().unwrap(), 1);
+        assert!(mgr.commit_transaction().is_err());
+    }
+
+    #[test]
+    fn transaction_commits_on_ok_and_rolls_back_on_err() {
+        let mut mgr = IndexManager::in_memory().unwrap();
+        let doc = make_doc("d1", "project", "Alpha", "");
+        let ids = mgr
+            .transaction(|index| {
+                index.index_document(&doc)?;
+                index.store_links(&doc.id, &[])?;
+                Ok(doc.id.clone())
+            })
             .unwrap();
+        assert_eq!(ids, "d1");
         assert_eq!(mgr.count().unwrap(), 1);
 
-        mgr.remove_document("d1").unwrap();
-        assert_eq!(mgr.count().unwrap(), 0);
+        let result: Result<(), MkbError> = mgr.transaction(|index| {
+            index.remove_document("d1")?;
+            Err(MkbError::Index("boom".to_string()))
+        });
+        assert!(result.is_err());
+        assert!(mgr.query_by_id("d1").unwrap().is_some());
     }
 
     #[test]
-    fn query_all_returns_all_documents() {
+    fn with_transaction_rolls_back_on_err_and_closes_the_transaction() {
         let mgr = IndexManager::in_memory().unwrap();
+        let result: Result<(), MkbError> = mgr.with_transaction(|index| {
+            index.index_document(&make_doc("d1", "project", "Alpha", ""))?;
+            Err(MkbError::Index("boom".to_string()))
+        });
+        assert!(result.is_err());
+        assert_eq!(mgr.count().unwrap(), 0);
 
-        mgr.index_document(&make_doc("d1", "project", "Alpha", "body1"))
-            .unwrap();
-        mgr.index_document(&make_doc("d2", "meeting", "Sprint Review", "body2"))
+        // Nesting fails on BEGIN and leaves the outer transaction usable
+        let count = mgr
+            .with_transaction(|index| {
+                index.index_document(&make_doc("d2", "project", "Beta", ""))?;
+                assert!(index.with_transaction(|_| Ok(())).is_err());
+                index.count()
+            })
             .unwrap();
-
-        let all = mgr.query_all().unwrap();
-        assert_eq!(all.len(), 2);
+        assert_eq!(count, 1);
+        assert!(mgr.commit_transaction().is_err());
     }
 
     #[test]
-    fn index_document_upserts_on_duplicate_id() {
-        let mgr = IndexManager::in_memory().unwrap();
-
-        mgr.index_document(&make_doc("d1", "project", "Original", "body"))
+    fn execute_batch_applies_every_op() {
+        let mut mgr = IndexManager::in_memory().unwrap();
+        mgr.index_document(&make_doc("old", "project", "Old", ""))
             .unwrap();
-        mgr.index_document(&make_doc("d1", "project", "Updated", "new body"))
+
+        let applied = mgr
+            .execute_batch(vec![
+                IndexOp::IndexDocument(Box::new(make_doc("d1", "project", "Alpha", ""))),
+                IndexOp::StoreLinks {
+                    source_id: "d1".to_string(),
+                    links: vec![mkb_core::link::Link {
+                        rel: "owner".to_string(),
+                        target: "people/jane".to_string(),
+                        observed_at: utc(2025, 2, 10),
+                        metadata: None,
+                    }],
+                },
+                IndexOp::StoreEmbedding {
+                    doc_id: "d1".to_string(),
+                    embedding: mock_embedding("Alpha"),
+                    model: "mock".to_string(),
+                },
+                IndexOp::UpdateConfidence {
+                    id: "d1".to_string(),
+                    confidence: 0.5,
+                },
+                IndexOp::RemoveDocument("old".to_string()),
+            ])
             .unwrap();
 
+        assert_eq!(applied, 5);
         assert_eq!(mgr.count().unwrap(), 1);
-        let results = mgr.query_by_type("project").unwrap();
-        assert_eq!(results[0].title, "Updated");
-    }
-
-    // === T-110.3 tests: link indexing ===
-
-    #[test]
-    fn link_creation_with_timestamp() {
-        let link = mkb_core::link::Link {
-            rel: "owner".to_string(),
-            target: "people/jane-smith".to_string(),
-            observed_at: utc(2025, 2, 10),
-            metadata: None,
-        };
-        assert_eq!(link.rel, "owner");
-        assert_eq!(link.observed_at, utc(2025, 2, 10));
+        assert_eq!(mgr.query_by_id("d1").unwrap().unwrap().confidence, 0.5);
+        assert_eq!(mgr.query_forward_links("d1").unwrap().len(), 1);
+        assert!(mgr.has_embedding("d1").unwrap());
     }
 
     #[test]
-    fn store_and_retrieve_links() {
-        let mgr = IndexManager::in_memory().unwrap();
-        let doc = make_doc("proj-alpha-001", "project", "Alpha", "body");
-        mgr.index_document(&doc).unwrap();
-
-        let links = vec![
-            mkb_core::link::Link {
-                rel: "owner".to_string(),
-                target: "people/jane-smith".to_string(),
-                observed_at: utc(2025, 2, 10),
-                metadata: None,
-            },
-            mkb_core::link::Link {
-                rel: "blocked_by".to_string(),
-                target: "proj-beta-001".to_string(),
-                observed_at: utc(2025, 2, 10),
-                metadata: None,
+    fn execute_batch_rolls_back_when_a_later_op_fails() {
+        let mut mgr = IndexManager::in_memory().unwrap();
+
+        let result = mgr.execute_batch(vec![
+            IndexOp::IndexDocument(Box::new(make_doc("d1", "project", "Alpha", ""))),
+            IndexOp::StoreEmbedding {
+                doc_id: "d1".to_string(),
+                embedding: vec![0.0; 3],
+                model: "mock".to_string(),
             },
-        ];
+        ]);
 
-        mgr.store_links("proj-alpha-001", &links).unwrap();
-        let forward = mgr.query_forward_links("proj-alpha-001").unwrap();
-        assert_eq!(forward.len(), 2);
+        assert!(result.is_err());
+        assert_eq!(mgr.count().unwrap(), 0);
+        // The connection is usable again after the rollback
+        assert_eq!(mgr.execute_batch(Vec::new()).unwrap(), 0);
     }
 
     #[test]
-    fn query_forward_links() {
+    fn bulk_update_confidence_updates_only_listed_rows() {
         let mgr = IndexManager::in_memory().unwrap();
-        let doc = make_doc("proj-alpha-001", "project", "Alpha", "body");
-        mgr.index_document(&doc).unwrap();
-
-        let links = vec![mkb_core::link::Link {
-            rel: "owner".to_string(),
-            target: "people/jane-smith".to_string(),
-            observed_at: utc(2025, 2, 10),
-            metadata: None,
-        }];
-        mgr.store_links("proj-alpha-001", &links).unwrap();
+        for id in ["d1", "d2", "d3"] {
+            mgr.index_document(&make_doc(id, "project", id, ""))
+                .unwrap();
+        }
+        let before = mgr.query_by_id("d3").unwrap().unwrap().confidence;
+
+        let updated = mgr
+            .bulk_update_confidence(&[
+                ("d1".to_string(), 0.25),
+                ("d2".to_string(), 0.5),
+                ("missing".to_string(), 0.75),
+            ])
+            .unwrap();
+        assert_eq!(updated, 2);
+        assert_eq!(mgr.query_by_id("d1").unwrap().unwrap().confidence, 0.25);
+        assert_eq!(mgr.query_by_id("d2").unwrap().unwrap().confidence, 0.5);
+        assert_eq!(mgr.query_by_id("d3").unwrap().unwrap().confidence, before);
 
-        let forward = mgr.query_forward_links("proj-alpha-001").unwrap();
-        assert_eq!(forward.len(), 1);
-        assert_eq!(forward[0].target_id, "people/jane-smith");
-        assert_eq!(forward[0].rel, "owner");
+        assert_eq!(mgr.bulk_update_confidence(&[]).unwrap(), 0);
     }
 
     #[test]
-    fn query_reverse_links() {
+    fn bulk_update_confidence_rolls_back_on_failure() {
         let mgr = IndexManager::in_memory().unwrap();
+        mgr.index_document(&make_doc("d1", "project", "Alpha", ""))
+            .unwrap();
+        mgr.index_document(&make_doc("d2", "project", "Beta", ""))
+            .unwrap();
+        let before = mgr.query_by_id("d1").unwrap().unwrap().confidence;
 
-        let doc1 = make_doc("proj-alpha-001", "project", "Alpha", "body");
-        mgr.index_document(&doc1).unwrap();
-        let doc2 = make_doc("proj-beta-001", "project", "Beta", "body");
-        mgr.index_document(&doc2).unwrap();
-
-        // Both projects link to same person
-        mgr.store_links(
-            "proj-alpha-001",
-            &[mkb_core::link::Link {
-                rel: "owner".to_string(),
-                target: "people/jane-smith".to_string(),
-                observed_at: utc(2025, 2, 10),
-                metadata: None,
-            }],
-        )
-        .unwrap();
-        mgr.store_links(
-            "proj-beta-001",
-            &[mkb_core::link::Link {
-                rel: "owner".to_string(),
-                target: "people/jane-smith".to_string(),
-                observed_at: utc(2025, 2, 10),
-                metadata: None,
-            }],
-        )
-        .unwrap();
+        let result =
+            mgr.bulk_update_confidence(&[("d1".to_string(), 0.25), ("d2".to_string(), 1.5)]);
+        assert!(result.is_err());
+        assert_eq!(mgr.query_by_id("d1").unwrap().unwrap().confidence, before);
 
-        let reverse = mgr.query_reverse_links("people/jane-smith").unwrap();
-        assert_eq!(reverse.len(), 2);
-        let sources: Vec<&str> = reverse.iter().map(|l| l.source_id.as_str()).collect();
-        assert!(sources.contains(&"proj-alpha-001"));
-        assert!(sources.contains(&"proj-beta-001"));
+        // The connection is usable again after the rollback
+        assert_eq!(
+            mgr.bulk_update_confidence(&[("d2".to_string(), 0.5)])
+                .unwrap(),
+            1
+        );
     }
 
-    // === T-110.4 tests: temporal queries ===
-
     #[test]
-    fn query_by_observed_at_range() {
+    fn recalculate_all_confidence_applies_decay() {
         let mgr = IndexManager::in_memory().unwrap();
+        let profile = DecayProfile::default_profile();
+        let now = utc(2025, 6, 1);
+        let mut old = doc_observed_at("old", now - profile.half_life());
+        old.confidence = 0.8;
+        let fresh = doc_observed_at("fresh", now);
+        mgr.index_document(&old).unwrap();
+        mgr.index_document(&fresh).unwrap();
+        let fresh_before = mgr.query_by_id("fresh").unwrap().unwrap().confidence;
+        let effective = |id: &str| {
+            mgr.execute_sql(
+                &format!("SELECT effective_confidence FROM documents WHERE id = '{id}'"),
+                &[],
+            )
+            .unwrap()[0]["effective_confidence"]
+                .as_f64()
+                .unwrap()
+        };
 
-        // Doc observed in January
-        let d1 = make_doc("d1", "project", "January Doc", "body1");
-        mgr.index_document(&d1).unwrap();
+        assert_eq!(mgr.recalculate_all_confidence(&profile, now).unwrap(), 2);
+        let expected = 0.8
+            * profile.model.confidence_factor(profile.half_life())
+            * DecayModel::precision_multiplier(TemporalPrecision::Day);
+        assert!((effective("old") - expected).abs() < 1e-9);
+        assert!(
+            (effective("fresh")
+                - fresh_before * DecayModel::precision_multiplier(TemporalPrecision::Day))
+            .abs()
+                < 1e-9
+        );
+        // Raw confidences are untouched
+        assert_eq!(mgr.query_by_id("old").unwrap().unwrap().confidence, 0.8);
+        assert_eq!(
+            mgr.query_by_id("fresh").unwrap().unwrap().confidence,
+            fresh_before
+        );
 
-        // Doc observed in March (create with different observed_at)
+        // A second run at the same time is a no-op
+        let first = effective("old");
+        assert_eq!(mgr.recalculate_all_confidence(&profile, now).unwrap(), 0);
+        assert_eq!(effective("old"), first);
+
+        // Reindexing clears the stored value
+        mgr.index_document(&old).unwrap();
+        assert!(mgr
+            .execute_sql(
+                "SELECT effective_confidence FROM documents WHERE id = 'old'",
+                &[]
+            )
+            .unwrap()[0]["effective_confidence"]
+            .is_null());
+    }
+
+    fn doc_observed_at(id: &str, observed: DateTime<Utc>) -> Document {
         let input = RawTemporalInput {
-            observed_at: Some(utc(2025, 3, 15)),
-            valid_until: Some(utc(2025, 9, 15)),
+            observed_at: Some(observed),
+            valid_until: None,
             temporal_precision: Some(TemporalPrecision::Day),
             occurred_at: None,
+            occurred_until: None,
         };
-        let profile = DecayProfile::default_profile();
-        let mut d2 = Document::new(
-            "d2".into(),
-            "project".into(),
-            "March Doc".into(),
+        Document::new(
+            id.to_string(),
+            "project".to_string(),
+            id.to_string(),
             input,
-            &profile,
+            &DecayProfile::default_profile(),
         )
-        .unwrap();
-        d2.body = "body2".into();
-        mgr.index_document(&d2).unwrap();
-
-        // Query range that only includes February (from Feb 1 to Feb 28)
-        let results = mgr
-            .query_by_observed_at_range("2025-02-01T00:00:00+00:00", "2025-02-28T23:59:59+00:00")
-            .unwrap();
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].id, "d1");
+        .unwrap()
+    }
 
-        // Query range that includes both
-        let results = mgr
-            .query_by_observed_at_range("2025-01-01T00:00:00+00:00", "2025-12-31T23:59:59+00:00")
-            .unwrap();
-        assert_eq!(results.len(), 2);
+    #[test]
+    fn iso_week_label_uses_iso_week_year() {
+        // 2024-12-30 (Monday) belongs to ISO week 1 of 2025
+        assert_eq!(
+            HistogramBucket::Week(WeekMode::Iso).label(&utc(2024, 12, 30)),
+            "2025-W01"
+        );
+        // 2021-01-03 (Sunday) is still in ISO week 53 of 2020
+        assert_eq!(
+            HistogramBucket::Week(WeekMode::Iso).label(&utc(2021, 1, 3)),
+            "2020-W53"
+        );
+        // Same Sunday starts US week 01 of 2021
+        assert_eq!(
+            HistogramBucket::Week(WeekMode::Us).label(&utc(2021, 1, 3)),
+            "2021-U01"
+        );
     }
 
     #[test]
-    fn query_current_documents() {
+    fn quarter_histogram_sorts_across_year_boundary() {
         let mgr = IndexManager::in_memory().unwrap();
+        for (id, at) in [
+            ("a", utc(2025, 2, 10)),
+            ("b", utc(2024, 11, 5)),
+            ("c", utc(2025, 1, 1)),
+            ("d", utc(2024, 7, 1)),
+        ] {
+            mgr.index_document(&doc_observed_at(id, at)).unwrap();
+        }
 
-        // Active document (valid until Aug 2025)
-        let d1 = make_doc("d1", "project", "Active", "body1");
-        mgr.index_document(&d1).unwrap();
-
-        // Expired document (valid until Jan 2025, before our query time)
-        let input = RawTemporalInput {
-            observed_at: Some(utc(2024, 6, 1)),
-            valid_until: Some(utc(2025, 1, 1)),
-            temporal_precision: Some(TemporalPrecision::Day),
-            occurred_at: None,
-        };
-        let profile = DecayProfile::default_profile();
-        let mut d2 = Document::new(
-            "d2".into(),
-            "project".into(),
-            "Expired".into(),
-            input,
-            &profile,
-        )
-        .unwrap();
-        d2.body = "body2".into();
-        mgr.index_document(&d2).unwrap();
-
-        // Superseded document
-        let mut d3 = make_doc("d3", "project", "Superseded", "body3");
-        d3.superseded_by = Some("d1".to_string());
-        mgr.index_document(&d3).unwrap();
+        let bins = mgr.observed_at_histogram(HistogramBucket::Quarter).unwrap();
+        let labels: Vec<&str> = bins.iter().map(|b| b.label.as_str()).collect();
+        assert_eq!(labels, vec!["2024-Q3", "2024-Q4", "2025-Q1"]);
+        assert_eq!(bins[2].count, 2);
 
-        // Query current at Feb 2025: should only return d1
-        let current = mgr
-            .query_current_documents("2025-02-15T00:00:00+00:00")
-            .unwrap();
-        assert_eq!(current.len(), 1);
-        assert_eq!(current[0].id, "d1");
+        let mut sorted = labels.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, labels);
     }
 
     #[test]
-    fn query_with_effective_confidence() {
+    fn effective_confidence_range_applies_decay() {
         let mgr = IndexManager::in_memory().unwrap();
+        let profile = DecayProfile::default_profile();
 
-        // High-confidence recent doc
-        let mut d1 = make_doc("d1", "project", "Recent", "body1");
-        d1.confidence = 0.95;
-        mgr.index_document(&d1).unwrap();
+        // Observed exactly one half-life before `now`: 0.9 decays to 0.45
+        let mut old = doc_observed_at("old", utc(2025, 1, 1));
+        old.confidence = 0.9;
+        mgr.index_document(&old).unwrap();
 
-        // Low-confidence old doc
-        let input = RawTemporalInput {
-            observed_at: Some(utc(2024, 1, 1)),
-            valid_until: Some(utc(2026, 1, 1)),
-            temporal_precision: Some(TemporalPrecision::Day),
-            occurred_at: None,
-        };
-        let profile = DecayProfile::default_profile();
-        let mut d2 =
-            Document::new("d2".into(), "project".into(), "Old".into(), input, &profile).unwrap();
-        d2.body = "body2".into();
-        d2.confidence = 0.5;
-        mgr.index_document(&d2).unwrap();
+        // Observed at `now`: no decay
+        let mut fresh = doc_observed_at("fresh", utc(2025, 4, 1));
+        fresh.confidence = 0.9;
+        mgr.index_document(&fresh).unwrap();
 
-        // Query all and check confidence values are retrievable
-        let all = mgr.query_all().unwrap();
-        assert_eq!(all.len(), 2);
+        let now = "2025-04-01T00:00:00+00:00";
+        let high = mgr
+            .query_by_effective_confidence_range(0.8, 1.0, now, &profile)
+            .unwrap();
+        assert_eq!(high.len(), 1);
+        assert_eq!(high[0].id, "fresh");
+
+        let low = mgr
+            .query_by_effective_confidence_range(0.4, 0.5, now, &profile)
+            .unwrap();
+        assert_eq!(low.len(), 1);
+        assert_eq!(low[0].id, "old");
+        // Raw confidence is returned unchanged
+        assert!((low[0].confidence - 0.9).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn confidence_range_includes_boundaries() {
+        let mgr = IndexManager::in_memory().unwrap();
+        for (id, doc_type, confidence) in [
+            ("zero", "project", 0.0),
+            ("mid", "meeting", 0.5),
+            ("one", "project", 1.0),
+        ] {
+            let mut doc = make_doc(id, doc_type, id, "");
+            doc.confidence = confidence;
+            mgr.index_document(&doc).unwrap();
+        }
 
-        let recent = all.iter().find(|d| d.id == "d1").unwrap();
-        assert!((recent.confidence - 0.95).abs() < f64::EPSILON);
+        let ids = |docs: Vec<IndexedDocument>| docs.into_iter().map(|d| d.id).collect::<Vec<_>>();
+        assert_eq!(
+            ids(mgr.query_by_confidence_range(0.0, 1.0).unwrap()),
+            vec!["one", "mid", "zero"]
+        );
+        assert_eq!(
+            ids(mgr.query_by_confidence_range(0.0, 0.0).unwrap()),
+            vec!["zero"]
+        );
+        assert_eq!(
+            ids(mgr.query_by_confidence_range(1.0, 1.0).unwrap()),
+            vec!["one"]
+        );
+        assert_eq!(
+            ids(mgr.query_by_confidence_range(0.5, 1.0).unwrap()),
+            vec!["one", "mid"]
+        );
 
-        let old = all.iter().find(|d| d.id == "d2").unwrap();
-        assert!((old.confidence - 0.5).abs() < f64::EPSILON);
+        assert_eq!(
+            ids(mgr.query_by_type_and_confidence("project", 0.0).unwrap()),
+            vec!["one", "zero"]
+        );
+        assert_eq!(
+            ids(mgr.query_by_type_and_confidence("project", 1.0).unwrap()),
+            vec!["one"]
+        );
     }
 
     #[test]
-    fn staleness_sweep_marks_expired() {
+    fn confidence_range_rejects_out_of_range_bounds() {
         let mgr = IndexManager::in_memory().unwrap();
 
-        // Doc valid until June 2025
-        let d1 = make_doc("d1", "project", "Valid", "body1");
-        mgr.index_document(&d1).unwrap();
+        for (min, max) in [(-0.1, 1.0), (0.0, 1.5), (f64::NAN, 1.0)] {
+            let err = mgr.query_by_confidence_range(min, max).unwrap_err();
+            assert!(matches!(err, MkbError::Index(ref m) if m == "confidence out of range"));
+        }
+        assert!(matches!(
+            mgr.query_by_type_and_confidence("project", 2.0),
+            Err(MkbError::Index(ref m)) if m == "confidence out of range"
+        ));
+    }
 
-        // Doc valid until Jan 2025 (expired)
-        let input = RawTemporalInput {
-            observed_at: Some(utc(2024, 6, 1)),
-            valid_until: Some(utc(2025, 1, 1)),
-            temporal_precision: Some(TemporalPrecision::Day),
-            occurred_at: None,
-        };
-        let profile = DecayProfile::default_profile();
-        let mut d2 = Document::new(
-            "d2".into(),
-            "project".into(),
-            "Expired".into(),
-            input,
-            &profile,
+    #[test]
+    fn rebuild_from_vault_replaces_index_and_reports_progress() {
+        use std::sync::Mutex;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        let index_path = dir.path().join(".mkb").join("index").join("mkb.db");
+
+        let mut alpha = make_doc("proj-alpha-001", "project", "Alpha", "body");
+        alpha.links.push(mkb_core::link::Link {
+            rel: "owner".to_string(),
+            target: "pers-jane-001".to_string(),
+            observed_at: utc(2025, 2, 10),
+            metadata: None,
+        });
+        vault.create(&alpha).unwrap();
+        vault
+            .create(&make_doc("proj-beta-001", "project", "Beta", "body"))
+            .unwrap();
+        fs::write(
+            dir.path().join("projects").join("notes.md"),
+            "no frontmatter",
         )
         .unwrap();
-        d2.body = "body2".into();
-        mgr.index_document(&d2).unwrap();
 
-        // Sweep at Feb 2025
-        let stale = mgr.staleness_sweep("2025-02-15T00:00:00+00:00").unwrap();
-        assert_eq!(stale.len(), 1);
-        assert_eq!(stale[0], "d2");
-    }
+        // Stale entry that the rebuild must drop
+        IndexManager::open(&index_path)
+            .unwrap()
+            .index_document(&make_doc("gone", "project", "Gone", ""))
+            .unwrap();
 
-    // === T-410.2 tests: sqlite-vec vector operations ===
+        let calls = Mutex::new(Vec::new());
+        let stats = IndexManager::rebuild_from_vault(&vault, |done, total| {
+            calls.lock().unwrap().push((done, total));
+        })
+        .unwrap();
 
-    /// Generate a deterministic test embedding from a seed string.
-    fn test_embedding(seed: &str) -> Vec<f32> {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-        let mut vec = vec![0.0f32; EMBEDDING_DIM];
-        for (i, v) in vec.iter_mut().enumerate() {
-            let mut h = DefaultHasher::new();
-            seed.hash(&mut h);
-            i.hash(&mut h);
-            *v = (h.finish() as f32 / u64::MAX as f32) * 2.0 - 1.0;
-        }
-        // Normalize to unit vector
-        let norm: f32 = vec.iter().map(|x| x * x).sum::<f32>().sqrt();
-        for v in &mut vec {
-            *v /= norm;
-        }
-        vec
+        assert_eq!(stats.indexed, 2);
+        assert_eq!(stats.skipped, 1);
+        assert_eq!(stats.failed, 0);
+        assert_eq!(*calls.lock().unwrap(), vec![(1, 3), (2, 3), (3, 3)]);
+
+        let index = IndexManager::open(&index_path).unwrap();
+        assert_eq!(index.count().unwrap(), 2);
+        assert!(index.query_by_id("gone").unwrap().is_none());
+        assert_eq!(
+            index.query_forward_links("proj-alpha-001").unwrap().len(),
+            1
+        );
+        assert!(!index_path.with_extension("db.rebuild").exists());
     }
 
     #[test]
-    fn store_and_query_embedding() {
-        let mgr = IndexManager::in_memory().unwrap();
+    fn rebuild_with_open_wal_connection_reopens_cleanly() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        let index_path = dir.path().join(".mkb").join("index").join("mkb.db");
+        vault
+            .create(&make_doc("proj-alpha-001", "project", "Alpha", "body"))
+            .unwrap();
 
-        let doc = make_doc("d1", "project", "Alpha", "body");
-        mgr.index_document(&doc).unwrap();
+        // An open connection with writes still in the WAL
+        let open = IndexManager::open(&index_path).unwrap();
+        open.index_document(&make_doc("gone", "project", "Gone", ""))
+            .unwrap();
+        assert!(index_path.with_extension("db-wal").exists());
+
+        IndexManager::rebuild_from_vault(&vault, |_, _| {}).unwrap();
+
+        assert!(open.query_by_id("gone").unwrap().is_none());
+        assert_eq!(open.count().unwrap(), 1);
+        drop(open);
+        let reopened = IndexManager::open(&index_path).unwrap();
+        assert_eq!(reopened.count().unwrap(), 1);
+        assert!(reopened.query_by_id("proj-alpha-001").unwrap().is_some());
+        assert_eq!(
+            reopened
+                .conn
+                .query_row("PRAGMA integrity_check", [], |r| r.get::<_, String>(0))
+                .unwrap(),
+            "ok"
+        );
+    }
 
-        let emb = test_embedding("alpha");
-        mgr.store_embedding("d1", &emb, "test-model").unwrap();
+    #[test]
+    fn rebuild_replaces_a_corrupted_index() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        let index_path = dir.path().join(".mkb").join("index").join("mkb.db");
+        vault
+            .create(&make_doc("proj-alpha-001", "project", "Alpha", "body"))
+            .unwrap();
+        fs::create_dir_all(index_path.parent().unwrap()).unwrap();
+        fs::write(&index_path, vec![0xAB; 8192]).unwrap();
 
-        assert!(mgr.has_embedding("d1").unwrap());
-        assert!(!mgr.has_embedding("d2").unwrap());
-        assert_eq!(mgr.embedding_count().unwrap(), 1);
+        IndexManager::rebuild_from_vault(&vault, |_, _| {}).unwrap();
+        assert_eq!(IndexManager::open(&index_path).unwrap().count().unwrap(), 1);
     }
 
     #[test]
-    fn semantic_search_returns_similar_documents() {
-        let mgr = IndexManager::in_memory().unwrap();
+    fn rebuild_links_reads_frontmatter_and_wikilinks() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
 
-        // Create 3 documents with different embeddings
-        for (id, doc_type, title) in &[
-            ("d1", "project", "Alpha Project"),
-            ("d2", "project", "Beta Project"),
-            ("d3", "meeting", "Standup Meeting"),
-        ] {
-            let doc = make_doc(id, doc_type, title, "body");
-            mgr.index_document(&doc).unwrap();
-            mgr.store_embedding(id, &test_embedding(id), "test-model")
-                .unwrap();
+        let mut alpha = make_doc(
+            "proj-alpha-001",
+            "project",
+            "Alpha",
+            "Blocked on [[proj-beta-001]]; see [[proj-ghost-001]].",
+        );
+        alpha.links.push(mkb_core::link::Link {
+            rel: "owner".to_string(),
+            target: "pers-jane-001".to_string(),
+            observed_at: utc(2025, 2, 10),
+            metadata: None,
+        });
+        let beta = make_doc(
+            "proj-beta-001",
+            "project",
+            "Beta",
+            "Back to [[proj-alpha-001]]",
+        );
+        let index = IndexManager::in_memory().unwrap();
+        for doc in [&alpha, &beta] {
+            vault.create(doc).unwrap();
+            index.index_document(doc).unwrap();
         }
+        // A link no vault file declares any more
+        index
+            .store_links("proj-beta-001", &[alpha.links[0].clone()])
+            .unwrap();
 
-        // Query with the same embedding as d1 — should return d1 first
-        let results = mgr.search_semantic(&test_embedding("d1"), 3).unwrap();
-        assert_eq!(results.len(), 3);
-        assert_eq!(results[0].id, "d1");
-        assert!(results[0].distance < results[1].distance);
+        let stats = index.rebuild_links(&vault).unwrap();
+        assert_eq!(
+            stats,
+            LinkRebuildStats {
+                rebuilt: 4,
+                dangling: 2
+            }
+        );
+        let beta_links = index.query_forward_links("proj-beta-001").unwrap();
+        assert_eq!(beta_links.len(), 1);
+        assert_eq!(beta_links[0].target_id, "proj-alpha-001");
+        assert_eq!(beta_links[0].rel, mkb_core::link::WIKILINK_REL);
+        assert!(index.link_graph().unwrap().has_cycle());
     }
 
     #[test]
-    fn embedding_dimension_mismatch_rejected() {
-        let mgr = IndexManager::in_memory().unwrap();
+    fn failed_rebuild_leaves_old_index_intact() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        vault
+            .create(&make_doc("proj-alpha-001", "project", "Alpha", ""))
+            .unwrap();
+        let index_dir = dir.path().join(".mkb").join("index");
+        IndexManager::open(&index_dir.join("mkb.db"))
+            .unwrap()
+            .index_document(&make_doc("old", "project", "Old", ""))
+            .unwrap();
 
-        let doc = make_doc("d1", "project", "Alpha", "body");
-        mgr.index_document(&doc).unwrap();
+        // A directory squatting on the temp path makes the rebuild fail
+        fs::create_dir_all(index_dir.join("mkb.db.rebuild").join("blocker")).unwrap();
+        assert!(IndexManager::rebuild_from_vault(&vault, |_, _| {}).is_err());
 
-        let wrong_dim = vec![0.0f32; 768]; // Wrong dimension
-        let result = mgr.store_embedding("d1", &wrong_dim, "test-model");
-        assert!(result.is_err());
-        assert!(result
-            .unwrap_err()
-            .to_string()
-            .contains("dimension mismatch"));
+        let index = IndexManager::open(&index_dir.join("mkb.db")).unwrap();
+        assert_eq!(index.count().unwrap(), 1);
+        assert!(index.query_by_id("old").unwrap().is_some());
     }
 
     #[test]
-    fn remove_embedding_works() {
-        let mgr = IndexManager::in_memory().unwrap();
+    fn reindex_incremental_restores_deleted_rows_and_skips_unchanged_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        for (id, title) in [("proj-alpha-001", "Alpha"), ("proj-beta-001", "Beta")] {
+            vault
+                .create(&make_doc(id, "project", title, "body"))
+                .unwrap();
+        }
+        IndexManager::rebuild_from_vault(&vault, |_, _| {}).unwrap();
+        let index =
+            IndexManager::open(&dir.path().join(".mkb").join("index").join("mkb.db")).unwrap();
+
+        let stats = index.reindex_incremental(&vault, |_, _| {}).unwrap();
+        assert_eq!(stats.indexed, 0);
+
+        index.remove_document("proj-beta-001").unwrap();
+        let stats = index.reindex_incremental(&vault, |_, _| {}).unwrap();
+        assert_eq!(stats.indexed, 1);
+        assert_eq!(index.count().unwrap(), 2);
+        assert!(index.query_by_id("proj-beta-001").unwrap().is_some());
+    }
 
-        let doc = make_doc("d1", "project", "Alpha", "body");
-        mgr.index_document(&doc).unwrap();
-        mgr.store_embedding("d1", &test_embedding("d1"), "test-model")
+    #[test]
+    fn reindex_incremental_picks_up_modified_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        let mut alpha = make_doc("proj-alpha-001", "project", "Alpha", "body");
+        let path = vault.create(&alpha).unwrap();
+        vault
+            .create(&make_doc("proj-beta-001", "project", "Beta", "body"))
             .unwrap();
+        let index = IndexManager::in_memory().unwrap();
+        assert_eq!(
+            index
+                .reindex_incremental(&vault, |_, _| {})
+                .unwrap()
+                .indexed,
+            2
+        );
 
-        assert!(mgr.has_embedding("d1").unwrap());
-        mgr.remove_embedding("d1").unwrap();
-        assert!(!mgr.has_embedding("d1").unwrap());
-        assert_eq!(mgr.embedding_count().unwrap(), 0);
+        alpha.title = "Alpha Renamed".to_string();
+        vault.update(&mut alpha).unwrap();
+        // Guard against coarse filesystem timestamps
+        let later = std::time::SystemTime::now() + Duration::from_secs(60);
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+
+        let stats = index.reindex_incremental(&vault, |_, _| {}).unwrap();
+        assert_eq!(stats.indexed, 1);
+        assert_eq!(
+            index.query_by_id("proj-alpha-001").unwrap().unwrap().title,
+            "Alpha Renamed"
+        );
     }
 
     #[test]
-    fn persist_and_reload_index() {
+    fn apply_vault_event_syncs_watched_changes_into_index() {
+        use mkb_vault::watcher::VaultWatcher;
+
         let dir = tempfile::TempDir::new().unwrap();
-        let db_path = dir.path().join("test.db");
+        let vault = Vault::init(dir.path()).unwrap();
+        let index = IndexManager::in_memory().unwrap();
+        let watcher = VaultWatcher::start(dir.path())
+            .unwrap()
+            .with_debounce(Duration::from_millis(200));
+        let sync = || {
+            watcher
+                .next_batch(Duration::from_secs(1))
+                .events()
+                .map(|event| index.apply_vault_event(dir.path(), &event).unwrap())
+                .collect::<Vec<_>>()
+        };
 
-        // Create and populate
-        {
-            let mgr = IndexManager::open(&db_path).unwrap();
-            let doc = make_doc("d1", "project", "Alpha", "body");
-            mgr.index_document(&doc).unwrap();
-            mgr.store_embedding("d1", &test_embedding("d1"), "test-model")
+        let path = vault
+            .create(&make_doc("proj-alpha-001", "project", "Alpha", "body"))
+            .unwrap();
+        assert_eq!(
+            sync(),
+            vec![SyncOutcome::Indexed {
+                id: "proj-alpha-001".to_string(),
+                title: "Alpha".to_string(),
+            }]
+        );
+        assert!(index.query_by_id("proj-alpha-001").unwrap().is_some());
+
+        fs::remove_file(&path).unwrap();
+        let outcomes = sync();
+        assert_eq!(
+            outcomes.last(),
+            Some(&SyncOutcome::Removed {
+                id: "proj-alpha-001".to_string()
+            })
+        );
+        assert_eq!(index.count().unwrap(), 0);
+    }
+
+    #[test]
+    fn apply_batch_event_indexes_a_burst_of_files_at_once() {
+        use mkb_vault::watcher::VaultWatcher;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        let mut index = IndexManager::in_memory().unwrap();
+        let watcher = VaultWatcher::start(dir.path())
+            .unwrap()
+            .with_debounce(Duration::from_millis(300));
+
+        for i in 0..100 {
+            let id = format!("proj-burst-{i:03}");
+            vault
+                .create(&make_doc(&id, "project", &format!("Burst {i}"), "body"))
                 .unwrap();
         }
 
-        // Reopen and verify
-        {
-            let mgr = IndexManager::open(&db_path).unwrap();
-            assert_eq!(mgr.count().unwrap(), 1);
-            assert!(mgr.has_embedding("d1").unwrap());
+        let batch = watcher.next_batch(Duration::from_secs(2));
+        assert_eq!(batch.len(), 100);
+        let report = index.apply_batch_event(dir.path(), &batch).unwrap();
+        assert_eq!(report.outcomes.len(), 100);
+        assert!(report.failed.is_empty() && report.rebuilt.is_none());
+        assert_eq!(index.count().unwrap(), 100);
+        assert!(watcher.next_batch(Duration::from_millis(200)).is_empty());
+    }
 
-            let results = mgr.search_semantic(&test_embedding("d1"), 1).unwrap();
-            assert_eq!(results.len(), 1);
-            assert_eq!(results[0].id, "d1");
+    #[test]
+    fn apply_batch_event_reports_bad_files_and_rescans_on_overflow() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        let mut index = IndexManager::in_memory().unwrap();
+
+        let good = vault
+            .create(&make_doc("proj-alpha-001", "project", "Alpha", "body"))
+            .unwrap();
+        let bad = good.with_file_name("broken.md");
+        fs::write(&bad, "no frontmatter").unwrap();
+        let batch = BatchFileEvent {
+            created: vec![bad.clone(), good.clone()],
+            ..BatchFileEvent::default()
+        };
+        let report = index.apply_batch_event(dir.path(), &batch).unwrap();
+        assert_eq!(report.outcomes.len(), 1);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].0, bad);
+        assert_eq!(index.count().unwrap(), 1);
+
+        // An overflowed batch rescans the vault instead of trusting its lists
+        fs::remove_file(&good).unwrap();
+        vault
+            .create(&make_doc("proj-beta-001", "project", "Beta", "body"))
+            .unwrap();
+        let overflowed = BatchFileEvent {
+            overflowed: true,
+            ..BatchFileEvent::default()
+        };
+        let report = index.apply_batch_event(dir.path(), &overflowed).unwrap();
+        assert!(report.rebuilt.is_some());
+        assert!(index.query_by_id("proj-alpha-001").unwrap().is_none());
+        assert!(index.query_by_id("proj-beta-001").unwrap().is_some());
+    }
+
+    #[test]
+    fn prune_orphans_removes_rows_for_deleted_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        let index = IndexManager::in_memory().unwrap();
+
+        let mut alpha = make_doc("proj-alpha-001", "project", "Alpha", "body");
+        alpha.links.push(mkb_core::link::Link {
+            rel: "depends_on".to_string(),
+            target: "proj-beta-001".to_string(),
+            observed_at: utc(2025, 2, 10),
+            metadata: None,
+        });
+        let beta = make_doc("proj-beta-001", "project", "Beta", "body");
+        for doc in [&alpha, &beta] {
+            vault.create(doc).unwrap();
+            index.index_document(doc).unwrap();
+            index.store_links(&doc.id, &doc.links).unwrap();
+            index
+                .store_embedding(&doc.id, &mock_embedding(&doc.title), "mock")
+                .unwrap();
         }
+
+        fs::remove_file(vault.document_path("project", "proj-alpha-001")).unwrap();
+
+        assert_eq!(index.prune_orphans(&vault).unwrap(), 1);
+        assert!(index.query_by_id("proj-alpha-001").unwrap().is_none());
+        assert!(!index.has_embedding("proj-alpha-001").unwrap());
+        assert!(index
+            .query_forward_links("proj-alpha-001")
+            .unwrap()
+            .is_empty());
+        assert!(index.search_fts("Alpha").unwrap().is_empty());
+
+        // The surviving document is untouched, and a second pass is a no-op
+        assert!(index.query_by_id("proj-beta-001").unwrap().is_some());
+        assert!(index.has_embedding("proj-beta-001").unwrap());
+        assert_eq!(index.prune_orphans(&vault).unwrap(), 0);
     }
 
     #[test]
-    fn full_rebuild_matches_incremental_index() {
-        // Build incrementally
-        let incremental = IndexManager::in_memory().unwrap();
-        let docs = vec![
-            make_doc("d1", "project", "Alpha", "body1"),
-            make_doc("d2", "project", "Beta", "body2"),
-            make_doc("d3", "meeting", "Sprint", "body3"),
-        ];
+    fn diff_against_vault_reports_each_kind_of_divergence() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        let index = IndexManager::in_memory().unwrap();
+
+        let synced = make_doc("proj-synced-001", "project", "Synced", "body");
+        let unindexed = make_doc("proj-unindexed-001", "project", "Unindexed", "body");
+        // Indexed with a modified_at long before its file was written
+        let mut edited = make_doc("proj-edited-001", "project", "Edited", "body");
+        edited.modified_at = utc(2025, 2, 10);
+        for doc in [&synced, &unindexed, &edited] {
+            vault.create(doc).unwrap();
+            index.index_document(doc).unwrap();
+        }
+        index.remove_document("proj-unindexed-001").unwrap();
+        index
+            .index_document(&make_doc("proj-gone-001", "project", "Gone", "body"))
+            .unwrap();
 
-        for doc in &docs {
-            incremental.index_document(doc).unwrap();
+        let diff = index.diff_against_vault(&vault).unwrap();
+        assert_eq!(diff.in_vault_not_index, vec!["proj-unindexed-001"]);
+        assert_eq!(diff.in_index_not_vault, vec!["proj-gone-001"]);
+        assert_eq!(diff.modified_since_index, vec!["proj-edited-001"]);
+        assert!(!diff.is_empty());
+
+        index.index_document(&unindexed).unwrap();
+        index.remove_document("proj-gone-001").unwrap();
+        let mut reindexed = edited.clone();
+        reindexed.modified_at = chrono::Utc::now();
+        index.index_document(&reindexed).unwrap();
+        assert!(index.diff_against_vault(&vault).unwrap().is_empty());
+    }
+
+    fn version(id: &str, supersedes: Option<&str>, superseded_by: Option<&str>) -> Document {
+        let mut doc = make_doc(id, "decision", id, "body");
+        doc.supersedes = supersedes.map(str::to_string);
+        doc.superseded_by = superseded_by.map(str::to_string);
+        doc
+    }
+
+    fn ids(docs: &[IndexedDocument]) -> Vec<&str> {
+        docs.iter().map(|d| d.id.as_str()).collect()
+    }
+
+    #[test]
+    fn snapshot_returns_the_version_current_at_the_time() {
+        let index = IndexManager::in_memory().unwrap();
+        let observed = |mut doc: Document, month: u32| {
+            doc.temporal.observed_at = utc(2025, month, 1);
+            doc.temporal.valid_until = utc(2025, 12, 31);
+            doc
+        };
+        for doc in [
+            observed(version("dec-v1-001", None, Some("dec-v2-001")), 1),
+            observed(version("dec-v2-001", Some("dec-v1-001"), None), 3),
+            observed(make_doc("proj-late-001", "project", "Late", ""), 5),
+        ] {
+            index.index_document(&doc).unwrap();
         }
 
-        // Build from scratch (simulates full rebuild)
-        let rebuilt = IndexManager::in_memory().unwrap();
-        for doc in &docs {
-            rebuilt.index_document(doc).unwrap();
+        let at = |month: u32| TemporalInterpolator::snapshot(&index, utc(2025, month, 15)).unwrap();
+        assert_eq!(ids(&at(2)), ["dec-v1-001"]);
+        assert_eq!(ids(&at(4)), ["dec-v2-001"]);
+        assert_eq!(ids(&at(6)), ["proj-late-001", "dec-v2-001"]);
+        assert!(TemporalInterpolator::snapshot(&index, utc(2024, 12, 1))
+            .unwrap()
+            .is_empty());
+        assert!(TemporalInterpolator::snapshot(&index, utc(2026, 2, 1))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn supersession_chain_of_single_document() {
+        let index = IndexManager::in_memory().unwrap();
+        index
+            .index_document(&version("dec-a-001", None, None))
+            .unwrap();
+
+        assert_eq!(
+            ids(&index.supersession_chain("dec-a-001").unwrap()),
+            ["dec-a-001"]
+        );
+        assert_eq!(
+            ids(&index.superseded_by_chain("dec-a-001").unwrap()),
+            ["dec-a-001"]
+        );
+        assert!(index
+            .supersession_chain("dec-missing-001")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn supersession_chain_of_three_versions() {
+        let index = IndexManager::in_memory().unwrap();
+        for doc in [
+            version("dec-v1-001", None, Some("dec-v2-001")),
+            version("dec-v2-001", Some("dec-v1-001"), Some("dec-v3-001")),
+            version("dec-v3-001", Some("dec-v2-001"), None),
+        ] {
+            index.index_document(&doc).unwrap();
         }
 
-        // Both should have same count and same query results
-        assert_eq!(incremental.count().unwrap(), rebuilt.count().unwrap());
+        assert_eq!(
+            ids(&index.supersession_chain("dec-v3-001").unwrap()),
+            ["dec-v1-001", "dec-v2-001", "dec-v3-001"]
+        );
+        assert_eq!(
+            ids(&index.supersession_chain("dec-v2-001").unwrap()),
+            ["dec-v1-001", "dec-v2-001"]
+        );
+        assert_eq!(
+            ids(&index.superseded_by_chain("dec-v1-001").unwrap()),
+            ["dec-v1-001", "dec-v2-001", "dec-v3-001"]
+        );
+        assert_eq!(
+            index.query_supersession("dec-v2-001").unwrap(),
+            SupersessionLinks {
+                supersedes: Some("dec-v1-001".to_string()),
+                superseded_by: Some("dec-v3-001".to_string()),
+            }
+        );
+    }
 
-        let inc_projects = incremental.query_by_type("project").unwrap();
-        let reb_projects = rebuilt.query_by_type("project").unwrap();
-        assert_eq!(inc_projects.len(), reb_projects.len());
+    #[test]
+    fn supersession_chain_detects_cycles() {
+        let index = IndexManager::in_memory().unwrap();
+        index
+            .index_document(&version("dec-a-001", Some("dec-b-001"), Some("dec-b-001")))
+            .unwrap();
+        index
+            .index_document(&version("dec-b-001", Some("dec-a-001"), Some("dec-a-001")))
+            .unwrap();
 
-        for (a, b) in inc_projects.iter().zip(reb_projects.iter()) {
-            assert_eq!(a.id, b.id);
-            assert_eq!(a.title, b.title);
+        for err in [
+            index.supersession_chain("dec-a-001").unwrap_err(),
+            index.superseded_by_chain("dec-b-001").unwrap_err(),
+        ] {
+            assert!(
+                matches!(err, MkbError::Vault(ref msg) if msg == "circular supersession"),
+                "{err}"
+            );
+        }
+    }
+
+    #[test]
+    fn migrate_schema_backfills_defaults_in_vault_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        let index = IndexManager::in_memory().unwrap();
+
+        let bare = make_doc("proj-alpha-001", "project", "Alpha", "body");
+        let mut set = make_doc("proj-beta-001", "project", "Beta", "body");
+        set.fields
+            .insert("priority".to_string(), serde_json::json!(1));
+        let other = make_doc("meet-sync-001", "meeting", "Sync", "body");
+        for doc in [&bare, &set, &other] {
+            vault.create(doc).unwrap();
+            index.index_document(doc).unwrap();
         }
+
+        let migration = SchemaMigration {
+            doc_type: "project".to_string(),
+            set_defaults: [("priority".to_string(), serde_json::json!(3))].into(),
+        };
+        let report = index.migrate_schema(&vault, &migration).unwrap();
+        assert_eq!(report.scanned, 2);
+        assert_eq!(report.updated, ["proj-alpha-001"]);
+
+        let alpha = vault.read("project", "proj-alpha-001").unwrap();
+        assert_eq!(alpha.fields["priority"], serde_json::json!(3));
+        let beta = vault.read("project", "proj-beta-001").unwrap();
+        assert_eq!(beta.fields["priority"], serde_json::json!(1));
+        assert!(!vault
+            .read("meeting", "meet-sync-001")
+            .unwrap()
+            .fields
+            .contains_key("priority"));
+
+        let again = index.migrate_schema(&vault, &migration).unwrap();
+        assert!(again.updated.is_empty());
     }
 }
@@ -0,0 +1,70 @@
+//! Point-in-time reconstruction of vault state.
+//!
+//! The index does not keep a history of edits, only the versions that exist
+//! as separate documents linked through `supersedes` / `superseded_by`. A
+//! snapshot at time `T` is therefore the set of versions that were already
+//! observed and still valid at `T`, minus any version whose successor had
+//! been observed by then.
+
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+
+use mkb_core::error::MkbError;
+
+use crate::{age_label_column, tags_column, IndexManager, IndexedDocument};
+
+/// Answers "what did the vault look like at time `T`?" from the index.
+pub struct TemporalInterpolator;
+
+impl TemporalInterpolator {
+    /// The then-current version of every document at `at`, most recently
+    /// observed first.
+    ///
+    /// A version counts when `observed_at <= at <= valid_until` and no
+    /// document superseding it had been observed by `at`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Index`] if the query fails.
+    pub fn snapshot(
+        index: &IndexManager,
+        at: DateTime<Utc>,
+    ) -> Result<Vec<IndexedDocument>, MkbError> {
+        let mut stmt = index
+            .conn
+            .prepare(
+                "SELECT d.id, d.doc_type, d.title, d.observed_at, d.valid_until, d.confidence, d.tags, d.modified_at,
+                 d.temporal_precision
+                 FROM documents d
+                 WHERE d.observed_at <= ?1
+                   AND d.valid_until >= ?1
+                   AND NOT EXISTS (
+                       SELECT 1 FROM documents s
+                       WHERE (s.supersedes = d.id OR s.id = d.superseded_by)
+                         AND s.observed_at <= ?1
+                   )
+                 ORDER BY d.observed_at DESC, d.id ASC",
+            )
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+
+        let results = stmt
+            .query_map(params![at.to_rfc3339()], |row| {
+                Ok(IndexedDocument {
+                    id: row.get(0)?,
+                    doc_type: row.get(1)?,
+                    title: row.get(2)?,
+                    observed_at: row.get(3)?,
+                    valid_until: row.get(4)?,
+                    confidence: row.get(5)?,
+                    tags: tags_column(row, 6)?,
+                    modified_at: row.get(7)?,
+                    age_label: age_label_column(row, 3, 8)?,
+                })
+            })
+            .map_err(|e| MkbError::Index(e.to_string()))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| MkbError::Index(e.to_string()))?;
+
+        Ok(results)
+    }
+}
@@ -0,0 +1,98 @@
+//! Lazily stepped SQL result rows.
+//!
+//! [`SqlRowStream`] keeps the prepared statement alive next to its open
+//! cursor, so rows are read from SQLite one at a time as the iterator is
+//! advanced instead of being collected up front.
+
+use std::collections::BTreeMap;
+
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{Rows, Statement};
+use self_cell::{self_cell, MutBorrow};
+
+use mkb_core::error::MkbError;
+
+/// One result row, keyed by column name.
+pub type SqlRow = BTreeMap<String, serde_json::Value>;
+
+type Cursor<'stmt> = Rows<'stmt>;
+
+self_cell!(
+    struct OpenStatement<'conn> {
+        owner: MutBorrow<Statement<'conn>>,
+
+        #[covariant]
+        dependent: Cursor,
+    }
+);
+
+/// Iterator over the rows of a running query. Borrows the index connection.
+pub struct SqlRowStream<'conn> {
+    columns: Vec<String>,
+    cursor: OpenStatement<'conn>,
+    done: bool,
+}
+
+impl<'conn> SqlRowStream<'conn> {
+    /// Bind `params` to `stmt` and start stepping it.
+    pub(crate) fn start(stmt: Statement<'conn>, params: &[SqlValue]) -> Result<Self, MkbError> {
+        let columns = stmt.column_names().into_iter().map(String::from).collect();
+        let cursor = OpenStatement::try_new(MutBorrow::new(stmt), |stmt| {
+            stmt.borrow_mut()
+                .query(rusqlite::params_from_iter(params.iter()))
+        })
+        .map_err(|e| MkbError::Index(format!("SQL query error: {e}")))?;
+        Ok(Self {
+            columns,
+            cursor,
+            done: false,
+        })
+    }
+
+    /// Column names, in select order.
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+}
+
+impl Iterator for SqlRowStream<'_> {
+    type Item = Result<SqlRow, MkbError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let columns = &self.columns;
+        let next = self.cursor.with_dependent_mut(|_, rows| match rows.next() {
+            Ok(Some(row)) => Some(row_to_map(row, columns)),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        });
+        match next {
+            Some(Ok(row)) => Some(Ok(row)),
+            Some(Err(e)) => {
+                self.done = true;
+                Some(Err(MkbError::Index(format!("SQL row error: {e}"))))
+            }
+            None => {
+                self.done = true;
+                None
+            }
+        }
+    }
+}
+
+fn row_to_map(row: &rusqlite::Row<'_>, columns: &[String]) -> rusqlite::Result<SqlRow> {
+    let mut map = BTreeMap::new();
+    for (i, name) in columns.iter().enumerate() {
+        let json_val = match row.get::<_, SqlValue>(i)? {
+            SqlValue::Null => serde_json::Value::Null,
+            SqlValue::Integer(n) => serde_json::json!(n),
+            SqlValue::Real(f) => serde_json::json!(f),
+            SqlValue::Text(s) => serde_json::json!(s),
+            SqlValue::Blob(b) => serde_json::json!(format!("<blob:{} bytes>", b.len())),
+        };
+        map.insert(name.clone(), json_val);
+    }
+    Ok(map)
+}
@@ -2,16 +2,23 @@
 //!
 //! MCP (Model Context Protocol) server for MKB vault.
 //!
-//! Exposes read-only vault operations as MCP tools:
+//! Exposes vault operations as MCP tools. All are read-only except
+//! `mkb_append_body`, which can only add to a document:
 //! - `mkb_query`: Execute MKQL queries
 //! - `mkb_search`: Full-text search (FTS5)
 //! - `mkb_search_semantic`: Vector similarity search
 //! - `mkb_get_document`: Read a document by type + ID
 //! - `mkb_list_types`: List available document types
 //! - `mkb_vault_status`: Vault health stats
+//! - `mkb_append_body`: Append a timestamped entry to a document body
 //!
 //! Also provides MCP resource templates:
 //! - `mkb://vault/{type}/{id}`: Read a document by type and ID
 //! - `mkb://query/{mkql}`: Execute an MKQL query (URL-encoded)
+//! - `mkb://search/{query}`: Full-text search (URL-encoded)
+//! - `mkb://semantic/{query_text}`: Semantic search (URL-encoded)
+//!
+//! Tool calls are rate limited per tool (see [`rate_limit`]).
 
+pub mod rate_limit;
 pub mod tools;
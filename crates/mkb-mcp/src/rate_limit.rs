@@ -0,0 +1,122 @@
+//! Token-bucket rate limiting for MCP tool calls.
+//!
+//! Guards the server against agents that call tools in a tight loop.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A token bucket allowing `requests_per_second` calls per second, with
+/// bursts of up to the same size.
+///
+/// Clones share one bucket, so a service cloned per connection still
+/// enforces a single limit.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    requests_per_second: u32,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    /// Create a limiter that starts full. A rate of 0 is treated as 1.
+    #[must_use]
+    pub fn new(requests_per_second: u32) -> Self {
+        let requests_per_second = requests_per_second.max(1);
+        Self {
+            requests_per_second,
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: f64::from(requests_per_second),
+                last_refill: Instant::now(),
+            })),
+        }
+    }
+
+    /// Configured sustained rate.
+    #[must_use]
+    pub fn requests_per_second(&self) -> u32 {
+        self.requests_per_second
+    }
+
+    /// Take one token if available.
+    ///
+    /// # Errors
+    ///
+    /// Returns how long to wait until a token is available when the bucket
+    /// is empty.
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        self.try_acquire_at(Instant::now())
+    }
+
+    fn try_acquire_at(&self, now: Instant) -> Result<(), Duration> {
+        let rate = f64::from(self.requests_per_second);
+        let mut bucket = self
+            .bucket
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        let elapsed = now.saturating_duration_since(bucket.last_refill);
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * rate).min(rate);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / rate))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allows_burst_then_rejects() {
+        let limiter = RateLimiter::new(20);
+        let now = Instant::now();
+
+        for _ in 0..20 {
+            assert!(limiter.try_acquire_at(now).is_ok());
+        }
+        let wait = limiter.try_acquire_at(now).unwrap_err();
+        assert_eq!(wait, Duration::from_millis(50));
+    }
+
+    #[test]
+    fn refills_over_time() {
+        let limiter = RateLimiter::new(5);
+        let start = Instant::now();
+        for _ in 0..5 {
+            limiter.try_acquire_at(start).unwrap();
+        }
+        assert!(limiter.try_acquire_at(start).is_err());
+
+        // One token every 200ms at 5 req/s
+        let later = start + Duration::from_millis(200);
+        assert!(limiter.try_acquire_at(later).is_ok());
+        assert!(limiter.try_acquire_at(later).is_err());
+
+        // Never refills past capacity
+        let much_later = start + Duration::from_secs(10);
+        for _ in 0..5 {
+            limiter.try_acquire_at(much_later).unwrap();
+        }
+        assert!(limiter.try_acquire_at(much_later).is_err());
+    }
+
+    #[test]
+    fn clones_share_a_bucket() {
+        let limiter = RateLimiter::new(1);
+        let clone = limiter.clone();
+        let now = Instant::now();
+
+        limiter.try_acquire_at(now).unwrap();
+        assert!(clone.try_acquire_at(now).is_err());
+    }
+}
@@ -1,39 +1,115 @@
-//! MCP tool definitions for MKB vault operations (read-only).
+//! MCP tool definitions for MKB vault operations (read-only, plus append).
 
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 use rmcp::{
     handler::server::{router::tool::ToolRouter, wrapper::Parameters},
     model::{
-        AnnotateAble, ListResourceTemplatesResult, PaginatedRequestParams, RawResourceTemplate,
-        ReadResourceRequestParams, ReadResourceResult, ResourceContents, ServerCapabilities,
-        ServerInfo,
+        AnnotateAble, ErrorCode, ListResourceTemplatesResult, PaginatedRequestParams,
+        RawResourceTemplate, ReadResourceRequestParams, ReadResourceResult, ResourceContents,
+        ServerCapabilities, ServerInfo,
     },
     service::RequestContext,
     tool, tool_handler, tool_router, ErrorData, RoleServer, ServerHandler,
 };
 use serde::Deserialize;
 
+use mkb_core::document::{ProvenanceRecord, ProvenanceType};
+use mkb_core::error::MkbError;
 use mkb_index::IndexManager;
+use mkb_parser::ast::MkqlQuery;
+use mkb_query::{CompileOptions, CompiledQuery, QueryCache};
 use mkb_vault::Vault;
 
-/// MKB MCP Server exposing read-only vault operations.
+use crate::rate_limit::RateLimiter;
+
+/// JSON-RPC error code returned when a tool's rate limit is exhausted.
+pub const RATE_LIMITED: ErrorCode = ErrorCode(-32000);
+
+/// Default requests per second for tools without a specific limit.
+pub const DEFAULT_TOOL_RATE_LIMIT: u32 = 50;
+
+/// Results returned by searches that don't set a limit.
+const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+/// Tools with a default limit below [`DEFAULT_TOOL_RATE_LIMIT`].
+const TOOL_RATE_LIMITS: &[(&str, u32)] = &[("mkb_query", 20), ("mkb_search_semantic", 5)];
+
+/// MKB MCP Server exposing read-only vault operations and body appends.
 #[derive(Debug, Clone)]
 pub struct MkbMcpService {
     /// Path to the vault directory.
     pub vault_path: PathBuf,
+    /// Rate limiter per tool name; tools without an entry are unlimited.
+    pub per_tool: HashMap<String, RateLimiter>,
+    /// Compiled MKQL shared by `mkb_query` and `mkb://query/` reads.
+    query_cache: QueryCache,
     tool_router: ToolRouter<Self>,
 }
 
 impl MkbMcpService {
-    /// Create a new MKB MCP server for the given vault path.
+    /// Create a new MKB MCP server for the given vault path, with default
+    /// per-tool rate limits.
     pub fn new(vault_path: PathBuf) -> Self {
+        let tool_router = Self::tool_router();
+        let per_tool = tool_router
+            .list_all()
+            .into_iter()
+            .map(|tool| {
+                let rps = TOOL_RATE_LIMITS
+                    .iter()
+                    .find(|(name, _)| *name == tool.name)
+                    .map_or(DEFAULT_TOOL_RATE_LIMIT, |(_, rps)| *rps);
+                (tool.name.to_string(), RateLimiter::new(rps))
+            })
+            .collect();
+
         Self {
             vault_path,
-            tool_router: Self::tool_router(),
+            per_tool,
+            query_cache: QueryCache::default(),
+            tool_router,
         }
     }
 
+    /// Override the rate limit for one tool.
+    #[must_use]
+    pub fn with_rate_limit(mut self, tool: &str, requests_per_second: u32) -> Self {
+        self.per_tool
+            .insert(tool.to_string(), RateLimiter::new(requests_per_second));
+        self
+    }
+
+    /// Remove all rate limits.
+    #[must_use]
+    pub fn without_rate_limits(mut self) -> Self {
+        self.per_tool.clear();
+        self
+    }
+
+    /// Take a token from `tool`'s bucket, or fail with a [`RATE_LIMITED`]
+    /// error carrying `retry_after_ms`.
+    fn check_rate_limit(&self, tool: &str) -> Result<(), ErrorData> {
+        let Some(limiter) = self.per_tool.get(tool) else {
+            return Ok(());
+        };
+        limiter.try_acquire().map_err(|wait| {
+            let retry_after_ms = wait.as_millis().max(1);
+            ErrorData::new(
+                RATE_LIMITED,
+                format!(
+                    "Rate limit exceeded for {tool} ({} requests/s); retry after {retry_after_ms}ms",
+                    limiter.requests_per_second()
+                ),
+                Some(serde_json::json!({
+                    "tool": tool,
+                    "retry_after_ms": retry_after_ms,
+                })),
+            )
+        })
+    }
+
     fn open_index(&self) -> Result<IndexManager, String> {
         let index_path = self.vault_path.join(".mkb").join("index").join("mkb.db");
         IndexManager::open(&index_path).map_err(|e| format!("Failed to open index: {e}"))
@@ -43,6 +119,19 @@ impl MkbMcpService {
         Vault::open(&self.vault_path).map_err(|e| format!("Failed to open vault: {e}"))
     }
 
+    /// Compile `ast` through the query cache under the vault's
+    /// `.mkb/query.yaml` settings, read on every call so `mkb config`
+    /// changes apply without a restart.
+    fn compile(&self, ast: &MkqlQuery) -> Result<CompiledQuery, String> {
+        let settings = self
+            .open_vault()?
+            .load_query_settings()
+            .map_err(|e| format!("Failed to load query settings: {e}"))?;
+        self.query_cache
+            .get_or_compile(ast, &CompileOptions::from(settings))
+            .map_err(|e| format!("Compile error: {e}"))
+    }
+
     fn handle_read_resource(&self, uri: &str) -> Result<ReadResourceResult, ErrorData> {
         // Parse mkb://vault/{type}/{id}
         if let Some(rest) = uri.strip_prefix("mkb://vault/") {
@@ -58,9 +147,12 @@ impl MkbMcpService {
             let vault = self
                 .open_vault()
                 .map_err(|e| ErrorData::internal_error(e, None))?;
-            let doc = vault
-                .read(doc_type, doc_id)
-                .map_err(|e| ErrorData::internal_error(format!("Document not found: {e}"), None))?;
+            let doc = vault.read(doc_type, doc_id).map_err(|e| match e {
+                MkbError::DocumentNotFound { id, .. } => {
+                    ErrorData::resource_not_found(format!("Document not found: {id}"), None)
+                }
+                e => ErrorData::internal_error(format!("Failed to read document: {e}"), None),
+            })?;
             let json = serde_json::json!({
                 "id": doc.id,
                 "type": doc.doc_type,
@@ -70,7 +162,7 @@ impl MkbMcpService {
                 "observed_at": doc.temporal.observed_at.to_rfc3339(),
                 "valid_until": doc.temporal.valid_until.to_rfc3339(),
                 "confidence": doc.confidence,
-                "source": doc.source,
+                "provenance": doc.provenance,
                 "fields": doc.fields,
             });
             let text = serde_json::to_string_pretty(&json).unwrap_or_else(|_| "{}".to_string());
@@ -89,8 +181,9 @@ impl MkbMcpService {
                 .map_err(|e| ErrorData::internal_error(e, None))?;
             let ast = mkb_parser::parse_mkql(&mkql)
                 .map_err(|e| ErrorData::invalid_params(format!("Parse error: {e}"), None))?;
-            let compiled = mkb_query::compile(&ast)
-                .map_err(|e| ErrorData::internal_error(format!("Compile error: {e}"), None))?;
+            let compiled = self
+                .compile(&ast)
+                .map_err(|e| ErrorData::internal_error(e, None))?;
             let result = mkb_query::execute(&index, &compiled)
                 .map_err(|e| ErrorData::internal_error(format!("Execution error: {e}"), None))?;
             let text = mkb_query::format_results(&result, mkb_query::OutputFormat::Json);
@@ -99,6 +192,38 @@ impl MkbMcpService {
             });
         }
 
+        // Parse mkb://search/{query}
+        if let Some(encoded) = uri.strip_prefix("mkb://search/") {
+            let query = decode_uri_segment(encoded)?;
+            let index = self
+                .open_index()
+                .map_err(|e| ErrorData::internal_error(e, None))?;
+            let results = index
+                .search_fts(&query)
+                .map_err(|e| ErrorData::invalid_params(format!("Search failed: {e}"), None))?;
+            let text = fts_hits_json(&results, DEFAULT_SEARCH_LIMIT);
+            return Ok(ReadResourceResult {
+                contents: vec![ResourceContents::text(text, uri)],
+            });
+        }
+
+        // Parse mkb://semantic/{query_text}
+        if let Some(encoded) = uri.strip_prefix("mkb://semantic/") {
+            let query = decode_uri_segment(encoded)?;
+            let index = self
+                .open_index()
+                .map_err(|e| ErrorData::internal_error(e, None))?;
+            let results = index
+                .search_semantic(&mkb_index::mock_embedding(&query), DEFAULT_SEARCH_LIMIT)
+                .map_err(|e| {
+                    ErrorData::internal_error(format!("Semantic search failed: {e}"), None)
+                })?;
+            let text = semantic_hits_json(&results);
+            return Ok(ReadResourceResult {
+                contents: vec![ResourceContents::text(text, uri)],
+            });
+        }
+
         Err(ErrorData::invalid_params(
             format!("Unknown resource URI scheme: {uri}"),
             None,
@@ -106,6 +231,51 @@ impl MkbMcpService {
     }
 }
 
+/// Percent-decode one URI path segment.
+fn decode_uri_segment(encoded: &str) -> Result<String, ErrorData> {
+    urlencoding::decode(encoded)
+        .map(|decoded| decoded.into_owned())
+        .map_err(|e| ErrorData::invalid_params(format!("Invalid URI encoding: {e}"), None))
+}
+
+/// Full-text search hits as a pretty JSON array, best first.
+fn fts_hits_json(results: &[mkb_index::SearchResult], limit: usize) -> String {
+    let json: Vec<serde_json::Value> = results
+        .iter()
+        .take(limit)
+        .map(|r| {
+            let mut hit = serde_json::json!({
+                "id": r.id,
+                "type": r.doc_type,
+                "title": r.title,
+                "rank": r.rank,
+                "tags": r.tags,
+            });
+            if let Some(ref snippet) = r.snippet {
+                hit["snippet"] = serde_json::json!(snippet);
+            }
+            hit
+        })
+        .collect();
+    serde_json::to_string_pretty(&json).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Semantic search hits as a pretty JSON array, nearest first.
+fn semantic_hits_json(results: &[mkb_index::VectorSearchResult]) -> String {
+    let json: Vec<serde_json::Value> = results
+        .iter()
+        .map(|r| {
+            serde_json::json!({
+                "id": r.id,
+                "type": r.doc_type,
+                "title": r.title,
+                "distance": r.distance,
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&json).unwrap_or_else(|_| "[]".to_string())
+}
+
 // === Tool request types ===
 
 /// Request for MKQL query execution.
@@ -113,6 +283,15 @@ impl MkbMcpService {
 pub struct QueryRequest {
     /// MKQL query string (e.g., "SELECT * FROM project WHERE CURRENT()")
     pub mkql: String,
+    /// Result format: json (default), yaml, or context for a prompt-ready
+    /// section per document
+    pub format: Option<String>,
+    /// Template for the context format, with `{{field}}` placeholders
+    /// (e.g. "## {{title}}\n{{body}}\n")
+    pub template: Option<String>,
+    /// Also return SQLite's query plan, as `{"results": ..., "plan": [...]}`
+    /// (default: false)
+    pub explain_plan: Option<bool>,
 }
 
 /// Request for full-text search.
@@ -122,6 +301,8 @@ pub struct SearchRequest {
     pub query: String,
     /// Maximum results to return (default: 10)
     pub limit: Option<usize>,
+    /// Include a highlighted body excerpt around each match (default: false)
+    pub include_snippets: Option<bool>,
 }
 
 /// Request for semantic (vector) search.
@@ -131,6 +312,8 @@ pub struct SemanticSearchRequest {
     pub query: String,
     /// Maximum results to return (default: 10)
     pub limit: Option<usize>,
+    /// Only return documents of this type (e.g., project, meeting)
+    pub doc_type: Option<String>,
 }
 
 /// Request to read a specific document.
@@ -142,95 +325,130 @@ pub struct GetDocumentRequest {
     pub id: String,
 }
 
+/// Request to append an entry to a document body.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct AppendBodyRequest {
+    /// Document type (e.g., project, meeting)
+    pub doc_type: String,
+    /// Document ID (e.g., proj-alpha-001)
+    pub id: String,
+    /// Text to append under a timestamp heading
+    pub text: String,
+}
+
 #[tool_router]
 impl MkbMcpService {
-    /// Execute an MKQL query and return JSON results.
+    /// Execute an MKQL query and return JSON, YAML or templated context.
     #[tool(
-        description = "Execute an MKQL (Markdown Knowledge Query Language) query and return JSON results"
+        description = "Execute an MKQL (Markdown Knowledge Query Language) query and return JSON (or YAML) results. Use format \"context\" for prompt-ready sections, optionally shaped by a {{field}} template"
     )]
-    fn mkb_query(&self, Parameters(req): Parameters<QueryRequest>) -> String {
+    fn mkb_query(&self, Parameters(req): Parameters<QueryRequest>) -> Result<String, ErrorData> {
+        self.check_rate_limit("mkb_query")?;
+        let format = match req.format.as_deref().unwrap_or("json") {
+            "json" => mkb_query::OutputFormat::Json,
+            "yaml" => mkb_query::OutputFormat::Yaml,
+            "context" => mkb_query::OutputFormat::Context {
+                template: req.template,
+            },
+            other => {
+                return Ok(format!(
+                    "{{\"error\": \"Unknown format: {other}. Valid: json, yaml, context\"}}"
+                ))
+            }
+        };
         let index = match self.open_index() {
             Ok(i) => i,
-            Err(e) => return format!("{{\"error\": \"{e}\"}}"),
+            Err(e) => return Ok(format!("{{\"error\": \"{e}\"}}")),
         };
         let ast = match mkb_parser::parse_mkql(&req.mkql) {
             Ok(a) => a,
-            Err(e) => return format!("{{\"error\": \"Parse error: {e}\"}}"),
+            Err(e) => return Ok(format!("{{\"error\": \"Parse error: {e}\"}}")),
         };
-        let compiled = match mkb_query::compile(&ast) {
+        let compiled = match self.compile(&ast) {
             Ok(c) => c,
-            Err(e) => return format!("{{\"error\": \"Compile error: {e}\"}}"),
+            Err(e) => return Ok(format!("{{\"error\": \"{e}\"}}")),
         };
-        match mkb_query::execute(&index, &compiled) {
-            Ok(result) => mkb_query::format_results(&result, mkb_query::OutputFormat::Json),
-            Err(e) => format!("{{\"error\": \"Execution error: {e}\"}}"),
+        let output = match mkb_query::execute(&index, &compiled) {
+            Ok(result) => mkb_query::format_results(&result, format),
+            Err(e) => return Ok(format!("{{\"error\": \"Execution error: {e}\"}}")),
+        };
+        if !req.explain_plan.unwrap_or(false) {
+            return Ok(output);
         }
+        let plan = match mkb_query::explain_plan(&index, &compiled) {
+            Ok(plan) => plan,
+            Err(e) => return Ok(format!("{{\"error\": \"Explain error: {e}\"}}")),
+        };
+        // JSON results nest as-is; YAML and context text become a string
+        let results = serde_json::from_str(&output).unwrap_or(serde_json::Value::String(output));
+        let json = serde_json::json!({ "results": results, "plan": plan });
+        Ok(serde_json::to_string_pretty(&json).unwrap_or_else(|_| "{}".to_string()))
     }
 
     /// Full-text search across all documents.
     #[tool(description = "Full-text search across all documents using FTS5")]
-    fn mkb_search(&self, Parameters(req): Parameters<SearchRequest>) -> String {
+    fn mkb_search(&self, Parameters(req): Parameters<SearchRequest>) -> Result<String, ErrorData> {
+        self.check_rate_limit("mkb_search")?;
         let index = match self.open_index() {
             Ok(i) => i,
-            Err(e) => return format!("{{\"error\": \"{e}\"}}"),
+            Err(e) => return Ok(format!("{{\"error\": \"{e}\"}}")),
         };
-        let results = match index.search_fts(&req.query) {
+        let results = if req.include_snippets.unwrap_or(false) {
+            index.search_fts_with_snippets(&req.query)
+        } else {
+            index.search_fts(&req.query)
+        };
+        let results = match results {
             Ok(r) => r,
-            Err(e) => return format!("{{\"error\": \"Search failed: {e}\"}}"),
+            Err(e) => return Ok(format!("{{\"error\": \"Search failed: {e}\"}}")),
         };
-        let limit = req.limit.unwrap_or(10);
-        let json: Vec<serde_json::Value> = results
-            .iter()
-            .take(limit)
-            .map(|r| {
-                serde_json::json!({
-                    "id": r.id,
-                    "type": r.doc_type,
-                    "title": r.title,
-                    "rank": r.rank,
-                })
-            })
-            .collect();
-        serde_json::to_string_pretty(&json).unwrap_or_else(|_| "[]".to_string())
+        Ok(fts_hits_json(
+            &results,
+            req.limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+        ))
     }
 
     /// Vector similarity search using embeddings.
     #[tool(description = "Vector similarity search using embeddings")]
-    fn mkb_search_semantic(&self, Parameters(req): Parameters<SemanticSearchRequest>) -> String {
+    fn mkb_search_semantic(
+        &self,
+        Parameters(req): Parameters<SemanticSearchRequest>,
+    ) -> Result<String, ErrorData> {
+        self.check_rate_limit("mkb_search_semantic")?;
         let index = match self.open_index() {
             Ok(i) => i,
-            Err(e) => return format!("{{\"error\": \"{e}\"}}"),
+            Err(e) => return Ok(format!("{{\"error\": \"{e}\"}}")),
         };
         let embedding = mkb_index::mock_embedding(&req.query);
-        let limit = req.limit.unwrap_or(10);
-        let results = match index.search_semantic(&embedding, limit) {
+        let limit = req.limit.unwrap_or(DEFAULT_SEARCH_LIMIT);
+        let results = match req.doc_type.as_deref() {
+            Some(doc_type) => index.search_semantic_by_type(&embedding, doc_type, limit),
+            None => index.search_semantic(&embedding, limit),
+        };
+        let results = match results {
             Ok(r) => r,
-            Err(e) => return format!("{{\"error\": \"Semantic search failed: {e}\"}}"),
+            Err(e) => return Ok(format!("{{\"error\": \"Semantic search failed: {e}\"}}")),
         };
-        let json: Vec<serde_json::Value> = results
-            .iter()
-            .map(|r| {
-                serde_json::json!({
-                    "id": r.id,
-                    "type": r.doc_type,
-                    "title": r.title,
-                    "distance": r.distance,
-                })
-            })
-            .collect();
-        serde_json::to_string_pretty(&json).unwrap_or_else(|_| "[]".to_string())
+        Ok(semantic_hits_json(&results))
     }
 
     /// Read a specific document by type and ID.
     #[tool(description = "Read a specific document by type and ID, returning its full content")]
-    fn mkb_get_document(&self, Parameters(req): Parameters<GetDocumentRequest>) -> String {
+    fn mkb_get_document(
+        &self,
+        Parameters(req): Parameters<GetDocumentRequest>,
+    ) -> Result<String, ErrorData> {
+        self.check_rate_limit("mkb_get_document")?;
         let vault = match self.open_vault() {
             Ok(v) => v,
-            Err(e) => return format!("{{\"error\": \"{e}\"}}"),
+            Err(e) => return Ok(format!("{{\"error\": \"{e}\"}}")),
         };
         let doc = match vault.read(&req.doc_type, &req.id) {
             Ok(d) => d,
-            Err(e) => return format!("{{\"error\": \"Document not found: {e}\"}}"),
+            Err(MkbError::DocumentNotFound { id, .. }) => {
+                return Ok(format!("{{\"error\": \"Document not found: {id}\"}}"))
+            }
+            Err(e) => return Ok(format!("{{\"error\": \"Failed to read document: {e}\"}}")),
         };
         let json = serde_json::json!({
             "id": doc.id,
@@ -241,24 +459,26 @@ impl MkbMcpService {
             "observed_at": doc.temporal.observed_at.to_rfc3339(),
             "valid_until": doc.temporal.valid_until.to_rfc3339(),
             "confidence": doc.confidence,
-            "source": doc.source,
+            "provenance": doc.provenance,
             "fields": doc.fields,
         });
-        serde_json::to_string_pretty(&json).unwrap_or_else(|_| "{}".to_string())
+        Ok(serde_json::to_string_pretty(&json).unwrap_or_else(|_| "{}".to_string()))
     }
 
     /// List all document types that have indexed documents.
     #[tool(description = "List all document types that have indexed documents")]
-    fn mkb_list_types(&self) -> String {
+    fn mkb_list_types(&self) -> Result<String, ErrorData> {
+        self.check_rate_limit("mkb_list_types")?;
         let index = match self.open_index() {
             Ok(i) => i,
-            Err(e) => return format!("{{\"error\": \"{e}\"}}"),
+            Err(e) => return Ok(format!("{{\"error\": \"{e}\"}}")),
         };
         let all = match index.query_all() {
             Ok(a) => a,
-            Err(e) => return format!("{{\"error\": \"Query failed: {e}\"}}"),
+            Err(e) => return Ok(format!("{{\"error\": \"Query failed: {e}\"}}")),
         };
-        let mut types: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+        let mut types: std::collections::BTreeMap<String, usize> =
+            std::collections::BTreeMap::new();
         for doc in &all {
             *types.entry(doc.doc_type.clone()).or_insert(0) += 1;
         }
@@ -266,25 +486,27 @@ impl MkbMcpService {
             .iter()
             .map(|(t, count)| serde_json::json!({"type": t, "count": count}))
             .collect();
-        serde_json::to_string_pretty(&json).unwrap_or_else(|_| "[]".to_string())
+        Ok(serde_json::to_string_pretty(&json).unwrap_or_else(|_| "[]".to_string()))
     }
 
     /// Get vault health status.
     #[tool(
         description = "Get vault health status including document count, index sync, and stale documents"
     )]
-    fn mkb_vault_status(&self) -> String {
+    fn mkb_vault_status(&self) -> Result<String, ErrorData> {
+        self.check_rate_limit("mkb_vault_status")?;
         let vault = match self.open_vault() {
             Ok(v) => v,
-            Err(e) => return format!("{{\"error\": \"{e}\"}}"),
+            Err(e) => return Ok(format!("{{\"error\": \"{e}\"}}")),
         };
         let index = match self.open_index() {
             Ok(i) => i,
-            Err(e) => return format!("{{\"error\": \"{e}\"}}"),
+            Err(e) => return Ok(format!("{{\"error\": \"{e}\"}}")),
         };
         let doc_count = index.count().unwrap_or(0);
         let files = vault.list_documents().unwrap_or_default();
         let rejection_count = vault.rejection_count().unwrap_or(0);
+        let archived_count = vault.archived_count().unwrap_or(0);
         let index_synced = files.len() as u64 == doc_count;
         let now = chrono::Utc::now().to_rfc3339();
         let stale_count = index.staleness_sweep(&now).unwrap_or_default().len();
@@ -295,9 +517,44 @@ impl MkbMcpService {
             "vault_files": files.len(),
             "index_synced": index_synced,
             "rejection_count": rejection_count,
+            "archived_documents": archived_count,
             "stale_documents": stale_count,
         });
-        serde_json::to_string_pretty(&json).unwrap_or_else(|_| "{}".to_string())
+        Ok(serde_json::to_string_pretty(&json).unwrap_or_else(|_| "{}".to_string()))
+    }
+
+    /// Append a timestamped entry to a document body.
+    #[tool(
+        description = "Append a timestamped entry to the end of a document's body without rewriting existing content"
+    )]
+    fn mkb_append_body(
+        &self,
+        Parameters(req): Parameters<AppendBodyRequest>,
+    ) -> Result<String, ErrorData> {
+        self.check_rate_limit("mkb_append_body")?;
+        let vault = match self.open_vault() {
+            Ok(v) => v,
+            Err(e) => return Ok(format!("{{\"error\": \"{e}\"}}")),
+        };
+        let index = match self.open_index() {
+            Ok(i) => i,
+            Err(e) => return Ok(format!("{{\"error\": \"{e}\"}}")),
+        };
+        let appender = ProvenanceRecord::new(ProvenanceType::LlmGenerated, "mcp");
+        let doc =
+            match vault.append_body_with_provenance(&req.doc_type, &req.id, &req.text, &appender) {
+                Ok(d) => d,
+                Err(e) => return Ok(format!("{{\"error\": \"Append failed: {e}\"}}")),
+            };
+        if let Err(e) = index.index_document(&doc) {
+            return Ok(format!("{{\"error\": \"Re-index failed: {e}\"}}"));
+        }
+        let json = serde_json::json!({
+            "id": doc.id,
+            "type": doc.doc_type,
+            "modified_at": doc.modified_at.to_rfc3339(),
+        });
+        Ok(serde_json::to_string_pretty(&json).unwrap_or_else(|_| "{}".to_string()))
     }
 }
 
@@ -307,7 +564,8 @@ impl ServerHandler for MkbMcpService {
         ServerInfo {
             instructions: Some(
                 "MKB (Markdown Knowledge Base) server. Query documents with MKQL, \
-                 search full-text or semantically, read documents, and check vault status."
+                 search full-text or semantically, read documents, append journal entries \
+                 to a document, and check vault status."
                     .to_string(),
             ),
             capabilities: ServerCapabilities::builder()
@@ -343,6 +601,28 @@ impl ServerHandler for MkbMcpService {
                 icons: None,
             }
             .no_annotation(),
+            RawResourceTemplate {
+                uri_template: "mkb://search/{query}".to_string(),
+                name: "Search".to_string(),
+                title: Some("Full-Text Search Results".to_string()),
+                description: Some(
+                    "Full-text search the vault and return the best matches as JSON".to_string(),
+                ),
+                mime_type: Some("application/json".to_string()),
+                icons: None,
+            }
+            .no_annotation(),
+            RawResourceTemplate {
+                uri_template: "mkb://semantic/{query_text}".to_string(),
+                name: "Semantic Search".to_string(),
+                title: Some("Semantic Search Results".to_string()),
+                description: Some(
+                    "Find the documents most similar to a text and return them as JSON".to_string(),
+                ),
+                mime_type: Some("application/json".to_string()),
+                icons: None,
+            }
+            .no_annotation(),
         ];
         std::future::ready(Ok(ListResourceTemplatesResult::with_all_items(templates)))
     }
@@ -413,6 +693,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn read_resource_missing_document_is_not_found() {
+        let (_vault_path, service, _dir) = setup_vault_with_doc();
+        let err = service
+            .handle_read_resource("mkb://vault/project/proj-missing-001")
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::RESOURCE_NOT_FOUND);
+        assert!(err.message.contains("proj-missing-001"));
+    }
+
     #[test]
     fn read_resource_query() {
         let (_vault_path, service, _dir) = setup_vault_with_doc();
@@ -428,6 +718,274 @@ mod tests {
         }
     }
 
+    fn resource_json(service: &MkbMcpService, uri: &str) -> Vec<serde_json::Value> {
+        let result = service.handle_read_resource(uri).unwrap();
+        match &result.contents[0] {
+            ResourceContents::TextResourceContents { text, .. } => {
+                serde_json::from_str(text).unwrap()
+            }
+            _ => panic!("Expected TextResourceContents"),
+        }
+    }
+
+    #[test]
+    fn read_resource_search_decodes_query() {
+        let (_vault_path, service, _dir) = setup_vault_with_doc();
+
+        // An encoded phrase query: quotes and a space
+        let hits = resource_json(&service, "mkb://search/%22Project%20details%22");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0]["id"], "proj-alpha-001");
+        assert_eq!(hits[0]["title"], "Alpha Project");
+        assert_eq!(hits[0]["type"], "project");
+        assert!(hits[0]["rank"].is_number());
+
+        // Multi-byte UTF-8; the tokenizer folds the accent away
+        let uri = format!("mkb://search/{}", urlencoding::encode("alpha AND détails"));
+        assert_eq!(resource_json(&service, &uri).len(), 1);
+        assert!(resource_json(&service, "mkb://search/nothing%20here").is_empty());
+    }
+
+    #[test]
+    fn read_resource_semantic_search() {
+        let (vault_path, service, _dir) = setup_vault_with_doc();
+        assert!(resource_json(&service, "mkb://semantic/Alpha%20Project").is_empty());
+
+        let index_path = vault_path.join(".mkb").join("index").join("mkb.db");
+        mkb_index::IndexManager::open(&index_path)
+            .unwrap()
+            .store_embedding(
+                "proj-alpha-001",
+                &mkb_index::mock_embedding("Alpha Project"),
+                "mock",
+            )
+            .unwrap();
+        let hits = resource_json(&service, "mkb://semantic/Alpha%20Project");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0]["id"], "proj-alpha-001");
+        assert!(hits[0]["distance"].as_f64().unwrap() < 1e-6);
+    }
+
+    #[test]
+    fn read_resource_search_rejects_bad_encoding() {
+        let (_vault_path, service, _dir) = setup_vault_with_doc();
+        let err = service
+            .handle_read_resource("mkb://search/%FF%FE")
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::INVALID_PARAMS);
+    }
+
+    #[test]
+    fn append_body_tool_appends_and_reindexes() {
+        let (vault_path, service, _dir) = setup_vault_with_doc();
+        let output = service
+            .mkb_append_body(Parameters(AppendBodyRequest {
+                doc_type: "project".to_string(),
+                id: "proj-alpha-001".to_string(),
+                text: "Kickoff moved to Friday.".to_string(),
+            }))
+            .unwrap();
+        assert!(output.contains("modified_at"), "output: {output}");
+
+        let doc = mkb_vault::Vault::open(&vault_path)
+            .unwrap()
+            .read("project", "proj-alpha-001")
+            .unwrap();
+        assert!(doc.body.starts_with("# Alpha\n\nProject details here."));
+        assert!(doc.body.trim_end().ends_with("Kickoff moved to Friday."));
+        let provenance = doc.provenance.unwrap();
+        assert_eq!(provenance.source_type, ProvenanceType::LlmGenerated);
+        assert_eq!(provenance.ingested_via, "mcp");
+        assert_eq!(provenance.transformation_log.len(), 1);
+        assert!(provenance.transformation_log[0].starts_with("appended via mcp at "));
+
+        let hits = service.open_index().unwrap().search_fts("Friday").unwrap();
+        assert_eq!(hits[0].id, "proj-alpha-001");
+    }
+
+    #[test]
+    fn query_tool_formats_yaml() {
+        let (_vault_path, service, _dir) = setup_vault_with_doc();
+        let query = |format: &str| {
+            service
+                .mkb_query(Parameters(QueryRequest {
+                    mkql: "SELECT id, body FROM project".to_string(),
+                    format: Some(format.to_string()),
+                    template: None,
+                    explain_plan: None,
+                }))
+                .unwrap()
+        };
+
+        let yaml = query("yaml");
+        assert!(
+            yaml.starts_with("id: proj-alpha-001\nbody: |-\n  # Alpha\n"),
+            "{yaml}"
+        );
+        assert!(query("xml").contains("Unknown format"));
+    }
+
+    #[test]
+    fn query_tool_follows_vault_query_settings() {
+        let (vault_path, service, _dir) = setup_vault_with_doc();
+        let vault = mkb_vault::Vault::open(&vault_path).unwrap();
+        let input = mkb_core::temporal::RawTemporalInput {
+            observed_at: Some(chrono::Utc::now() + chrono::Duration::days(30)),
+            ..Default::default()
+        };
+        let profile = mkb_core::temporal::DecayProfile::new(chrono::Duration::days(14));
+        let planned = mkb_core::Document::new(
+            "proj-planned-001".to_string(),
+            "project".to_string(),
+            "Planned Project".to_string(),
+            input,
+            &profile,
+        )
+        .unwrap();
+        vault.create(&planned).unwrap();
+        service
+            .open_index()
+            .unwrap()
+            .index_document(&planned)
+            .unwrap();
+
+        let query = || {
+            service
+                .mkb_query(Parameters(QueryRequest {
+                    mkql: "SELECT id FROM project WHERE CURRENT()".to_string(),
+                    format: None,
+                    template: None,
+                    explain_plan: None,
+                }))
+                .unwrap()
+        };
+        assert!(query().contains("proj-planned-001"));
+
+        vault
+            .save_query_settings(&mkb_vault::QuerySettings {
+                future_in_current: false,
+                ..mkb_vault::QuerySettings::default()
+            })
+            .unwrap();
+        let output = query();
+        assert!(output.contains("proj-alpha-001"), "{output}");
+        assert!(!output.contains("proj-planned-001"), "{output}");
+
+        let resource = service
+            .handle_read_resource("mkb://query/SELECT%20id%20FROM%20project%20WHERE%20CURRENT()")
+            .unwrap();
+        match &resource.contents[0] {
+            ResourceContents::TextResourceContents { text, .. } => {
+                assert!(!text.contains("proj-planned-001"), "{text}");
+            }
+            _ => panic!("Expected TextResourceContents"),
+        }
+    }
+
+    #[test]
+    fn query_tool_returns_plan_on_request() {
+        let (_vault_path, service, _dir) = setup_vault_with_doc();
+        let output = service
+            .mkb_query(Parameters(QueryRequest {
+                mkql: "SELECT id FROM project".to_string(),
+                format: None,
+                template: None,
+                explain_plan: Some(true),
+            }))
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(
+            json["results"]["rows"][0]["fields"]["id"], "proj-alpha-001",
+            "{output}"
+        );
+        let plan = json["plan"].as_array().unwrap();
+        for key in ["selectid", "order", "from", "detail"] {
+            assert!(plan[0].get(key).is_some(), "{output}");
+        }
+        assert!(
+            plan.iter().any(|row| row["detail"]
+                .as_str()
+                .unwrap()
+                .contains("idx_documents_type")),
+            "{output}"
+        );
+    }
+
+    #[test]
+    fn query_tool_formats_context() {
+        let (_vault_path, service, _dir) = setup_vault_with_doc();
+        let query = |template: Option<&str>| {
+            service
+                .mkb_query(Parameters(QueryRequest {
+                    mkql: "SELECT * FROM project".to_string(),
+                    format: Some("context".to_string()),
+                    template: template.map(str::to_string),
+                    explain_plan: None,
+                }))
+                .unwrap()
+        };
+
+        let context = query(None);
+        assert!(
+            context.starts_with("## Alpha Project (project, observed "),
+            "{context}"
+        );
+        assert!(context.ends_with("\n\n---\n"), "{context}");
+        assert_eq!(query(Some("{{id}}\n")), "proj-alpha-001\n");
+    }
+
+    #[test]
+    fn search_tool_includes_snippets_on_request() {
+        let (_vault_path, service, _dir) = setup_vault_with_doc();
+        let search = |include_snippets| {
+            let output = service
+                .mkb_search(Parameters(SearchRequest {
+                    query: "details".to_string(),
+                    limit: None,
+                    include_snippets,
+                }))
+                .unwrap();
+            let hits: Vec<serde_json::Value> = serde_json::from_str(&output).unwrap();
+            hits[0].clone()
+        };
+
+        let hit = search(Some(true));
+        let snippet = hit["snippet"].as_str().unwrap();
+        assert!(snippet.contains("<b>details</b>"), "snippet: {snippet}");
+        assert!(search(None).get("snippet").is_none());
+    }
+
+    #[test]
+    fn semantic_search_tool_filters_by_doc_type() {
+        let (vault_path, service, _dir) = setup_vault_with_doc();
+        let index_path = vault_path.join(".mkb").join("index").join("mkb.db");
+        let index = mkb_index::IndexManager::open(&index_path).unwrap();
+        index
+            .store_embedding(
+                "proj-alpha-001",
+                &mkb_index::mock_embedding("Alpha Project"),
+                "mock",
+            )
+            .unwrap();
+
+        let search = |doc_type: Option<&str>| {
+            let output = service
+                .mkb_search_semantic(Parameters(SemanticSearchRequest {
+                    query: "Alpha".to_string(),
+                    limit: None,
+                    doc_type: doc_type.map(str::to_string),
+                }))
+                .unwrap();
+            serde_json::from_str::<Vec<serde_json::Value>>(&output)
+                .unwrap()
+                .len()
+        };
+
+        assert_eq!(search(None), 1);
+        assert_eq!(search(Some("project")), 1);
+        assert_eq!(search(Some("meeting")), 0);
+    }
+
     #[test]
     fn read_resource_invalid_vault_uri() {
         let service = MkbMcpService::new(PathBuf::from("/tmp/nonexistent"));
@@ -441,4 +999,51 @@ mod tests {
         let result = service.handle_read_resource("https://example.com");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn default_rate_limits_per_tool() {
+        let service = MkbMcpService::new(PathBuf::from("/tmp/test"));
+        let rps = |tool: &str| service.per_tool[tool].requests_per_second();
+        assert_eq!(rps("mkb_query"), 20);
+        assert_eq!(rps("mkb_search_semantic"), 5);
+        assert_eq!(rps("mkb_search"), DEFAULT_TOOL_RATE_LIMIT);
+        assert_eq!(rps("mkb_vault_status"), DEFAULT_TOOL_RATE_LIMIT);
+    }
+
+    #[test]
+    fn twenty_first_query_in_a_second_is_rate_limited() {
+        let service = MkbMcpService::new(PathBuf::from("/tmp/nonexistent"));
+        for _ in 0..20 {
+            service.check_rate_limit("mkb_query").unwrap();
+        }
+
+        let err = service
+            .mkb_query(Parameters(QueryRequest {
+                mkql: "SELECT * FROM project".to_string(),
+                format: None,
+                template: None,
+                explain_plan: None,
+            }))
+            .unwrap_err();
+        assert_eq!(err.code, RATE_LIMITED);
+        assert_eq!(err.code.0, -32000);
+        let retry_after = err.data.unwrap()["retry_after_ms"].as_u64().unwrap();
+        assert!(retry_after > 0 && retry_after <= 50);
+
+        // Other tools have their own buckets
+        assert!(service.mkb_list_types().is_ok());
+    }
+
+    #[test]
+    fn rate_limits_are_configurable() {
+        let service =
+            MkbMcpService::new(PathBuf::from("/tmp/test")).with_rate_limit("mkb_search", 1);
+        service.check_rate_limit("mkb_search").unwrap();
+        assert!(service.check_rate_limit("mkb_search").is_err());
+
+        let unlimited = MkbMcpService::new(PathBuf::from("/tmp/test")).without_rate_limits();
+        for _ in 0..100 {
+            unlimited.check_rate_limit("mkb_query").unwrap();
+        }
+    }
 }
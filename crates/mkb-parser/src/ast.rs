@@ -11,9 +11,93 @@ pub struct MkqlQuery {
     pub select: SelectClause,
     pub from: String,
     pub where_clause: Option<WhereClause>,
+    /// `GROUP BY` fields, in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub group_by: Vec<String>,
+    /// `HAVING` condition on groups; only parsed after `GROUP BY`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub having: Option<HavingClause>,
     pub order_by: Option<Vec<OrderByItem>>,
     pub limit: Option<u64>,
     pub offset: Option<u64>,
+    /// Further SELECTs combined with this one, applied left to right.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub set_ops: Vec<SetOperation>,
+    /// `WITH name AS (...)` subqueries in scope for this query, in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ctes: Vec<CommonTableExpr>,
+    /// `SELECT PIVOT ...`; `select` is ignored when set. A pivot without
+    /// `FROM` has an empty `from` and spans every type.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pivot: Option<PivotClause>,
+}
+
+impl MkqlQuery {
+    /// Narrow this SELECT and each of its set operands to rows that also
+    /// match `clause`. `WITH` subqueries are left alone.
+    pub fn and_where(&mut self, clause: WhereClause) {
+        for set_op in &mut self.set_ops {
+            set_op.query.and_where(clause.clone());
+        }
+        self.where_clause = Some(match self.where_clause.take() {
+            Some(existing) => WhereClause::And(Box::new(existing), Box::new(clause)),
+            None => clause,
+        });
+    }
+}
+
+/// `PIVOT doc_type BY month(observed_at) COUNT(*)`: one row per
+/// `row_field` value, one column per `col_field` value, each cell the
+/// aggregate over the documents with both values.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PivotClause {
+    pub row_field: PivotKey,
+    pub col_field: PivotKey,
+    pub agg_func: AggregateFunction,
+    /// The aggregated field, or `*` for `COUNT(*)`.
+    pub agg_field: String,
+}
+
+/// A pivot axis: a field, optionally bucketed by date.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PivotKey {
+    pub field: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bucket: Option<DateBucket>,
+}
+
+/// Truncation applied to a date field used as a pivot axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DateBucket {
+    /// `month(field)`: `YYYY-MM`
+    Month,
+    /// `year(field)`: `YYYY`
+    Year,
+}
+
+/// A named subquery from a `WITH` clause, usable in `FROM` like a type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommonTableExpr {
+    pub name: String,
+    pub query: MkqlQuery,
+}
+
+/// `UNION | INTERSECT | EXCEPT` followed by another SELECT.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetOperation {
+    pub op: SetOp,
+    pub query: MkqlQuery,
+}
+
+/// A set operator combining two SELECT results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SetOp {
+    /// Rows of both sides, duplicates kept.
+    Union,
+    /// Rows present in both sides.
+    Intersect,
+    /// Rows of the left side that are not in the right.
+    Except,
 }
 
 /// The SELECT clause: which fields to return.
@@ -28,8 +112,41 @@ pub enum SelectClause {
 /// A single field in a SELECT clause.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SelectField {
+    /// The field, or `*` for `COUNT(*)`.
     pub name: String,
     pub alias: Option<String>,
+    /// Set for `COUNT(*)`, `AVG(confidence)`, etc.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aggregate: Option<AggregateFunction>,
+}
+
+/// An aggregate over the documents in a group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AggregateFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+/// The HAVING clause: a tree of aggregate predicates over each group.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum HavingClause {
+    Predicate(HavingPredicate),
+    And(Box<HavingClause>, Box<HavingClause>),
+    Or(Box<HavingClause>, Box<HavingClause>),
+    Not(Box<HavingClause>),
+}
+
+/// `COUNT(*) > 1`, `AVG(confidence) >= 0.8`, etc.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HavingPredicate {
+    pub function: AggregateFunction,
+    /// The aggregated field, or `*` for `COUNT(*)`.
+    pub field: String,
+    pub op: CompOp,
+    pub value: Value,
 }
 
 /// The WHERE clause: a tree of predicates.
@@ -54,14 +171,56 @@ pub enum Predicate {
     InList { field: String, values: Vec<Value> },
     /// `field LIKE 'pattern%'`
     Like { field: String, pattern: String },
+    /// `field REGEX '^Sprint [0-9]+'`
+    Regex { field: String, pattern: String },
     /// `BODY CONTAINS 'search term'`
     BodyContains { term: String },
+    /// `BODY MATCH_PHRASE 'deep learning'` — the words adjacent and in order
+    MatchPhrase {
+        target: PhraseTarget,
+        phrase: String,
+    },
+    /// `TAGS CONTAINS 'rust'` or `TAGS CONTAINS ANY ('rust', 'ai')` — whole-tag
+    /// membership; a single tag is parsed as [`TagMatch::All`]. `INCLUDES`
+    /// may stand in for `CONTAINS`, and `TAGS IN (...)` is `CONTAINS ANY`
+    TagsContain { tags: Vec<String>, mode: TagMatch },
+    /// `TAGS IS EMPTY` — the document has no tags
+    TagsEmpty,
     /// Temporal function predicates: `FRESH('7d')`, `CURRENT()`, etc.
     Temporal(TemporalFunction),
     /// `LINKED('rel', 'target')` or `LINKED(REVERSE, 'rel', 'source')`
     Linked(LinkedFunction),
+    /// `LINK_COUNT() > 5` or `REVERSE_LINK_COUNT('owner') >= 2` — number of
+    /// outgoing (or incoming) links, optionally of one relation type
+    LinkCount {
+        reverse: bool,
+        rel: Option<String>,
+        op: CompOp,
+        count: i64,
+    },
     /// `NEAR('query text', 0.8)` — vector similarity search with threshold
     Near { query: String, threshold: f64 },
+    /// `ANNOTATED('reviewed')` — the document has an index annotation of
+    /// this type
+    Annotated { annotation_type: String },
+}
+
+/// What [`Predicate::MatchPhrase`] searches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PhraseTarget {
+    /// Any indexed text, like `BODY CONTAINS`
+    Body,
+    /// The title only
+    Title,
+}
+
+/// How the tags in [`Predicate::TagsContain`] must match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TagMatch {
+    /// At least one of the tags is present
+    Any,
+    /// Every tag is present
+    All,
 }
 
 /// Comparison operators.
@@ -83,6 +242,34 @@ pub enum Value {
     Float(f64),
     Boolean(bool),
     Null,
+    /// `:name` placeholder, filled in when the query is executed.
+    Param(String),
+    /// `NOW() - 7d`, evaluated by the database at query time.
+    Date(DateExpr),
+}
+
+/// A point in time relative to now: `NOW()`, `NOW() - 30d`, `NOW() + 1mo - 1d`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DateExpr {
+    Now,
+    Add(Box<DateExpr>, DurationLiteral),
+    Sub(Box<DateExpr>, DurationLiteral),
+}
+
+/// An interval in date arithmetic, e.g. `7d`, `2w`, `1mo`, `1y`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DurationLiteral {
+    pub amount: u32,
+    pub unit: DurationUnit,
+}
+
+/// Calendar unit of a [`DurationLiteral`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DurationUnit {
+    Days,
+    Weeks,
+    Months,
+    Years,
 }
 
 /// Temporal function calls in WHERE clauses.
@@ -92,6 +279,8 @@ pub enum TemporalFunction {
     Fresh { duration: String },
     /// `STALE('30d')` — not observed within the given duration
     Stale { duration: String },
+    /// `CHANGED('7d')` — file edited (`_modified_at`) within the given duration
+    Changed { duration: String },
     /// `EXPIRED()` — past valid_until
     Expired,
     /// `CURRENT()` — not expired and not superseded
@@ -100,6 +289,13 @@ pub enum TemporalFunction {
     Latest,
     /// `AS_OF('2025-02-10T00:00:00Z')` — state at a given point in time
     AsOf { datetime: String },
+    /// `AS_OF_RANGE('2025-01-01', '2025-01-31')` — occurred range overlaps the window
+    AsOfRange { start: String, end: String },
+    /// `OVERLAPS('2025-01-01', '2025-03-31')` — validity window (`observed_at`
+    /// to `valid_until`) overlaps the given window
+    Overlaps { start: String, end: String },
+    /// `OVERLAPS_NOW()` — validity window contains now
+    OverlapsNow,
     /// `EFF_CONFIDENCE(> 0.5)` — effective confidence threshold
     EffConfidence { op: CompOp, threshold: f64 },
 }
@@ -111,15 +307,32 @@ pub enum LinkedFunction {
     Forward { rel: String, target: Option<String> },
     /// `LINKED(REVERSE, 'rel_type')` — reverse link exists with this rel
     Reverse { rel: String, source: Option<String> },
+    /// `LINKED_DEPTH('rel', 3, 'target')` or `LINKED_DEPTH_ANY('rel1|rel2', 3)` —
+    /// a chain of at most `max_depth` forward links, each matching one of the
+    /// `|`-separated relations in `rel_pattern`, ends at `target` (or anywhere)
+    Transitive {
+        rel_pattern: String,
+        max_depth: u32,
+        target: Option<String>,
+    },
 }
 
 /// An item in the ORDER BY clause.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct OrderByItem {
-    pub field: String,
+    pub key: OrderKey,
     pub direction: SortDirection,
 }
 
+/// What an ORDER BY item sorts on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderKey {
+    /// A document field, e.g. `observed_at`
+    Field(String),
+    /// `SIMILARITY('reference text')` — semantic similarity to the text
+    Similarity(String),
+}
+
 /// Sort direction for ORDER BY.
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum SortDirection {
@@ -141,18 +354,414 @@ impl std::fmt::Display for CompOp {
     }
 }
 
+impl std::fmt::Display for AggregateFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Count => write!(f, "COUNT"),
+            Self::Sum => write!(f, "SUM"),
+            Self::Avg => write!(f, "AVG"),
+            Self::Min => write!(f, "MIN"),
+            Self::Max => write!(f, "MAX"),
+        }
+    }
+}
+
 impl std::fmt::Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::String(s) => write!(f, "'{s}'"),
             Self::Integer(i) => write!(f, "{i}"),
-            Self::Float(fl) => write!(f, "{fl}"),
-            Self::Boolean(b) => write!(f, "{b}"),
+            Self::Float(fl) => write_float(f, *fl),
+            Self::Boolean(true) => write!(f, "TRUE"),
+            Self::Boolean(false) => write!(f, "FALSE"),
             Self::Null => write!(f, "NULL"),
+            Self::Param(name) => write!(f, ":{name}"),
+            Self::Date(expr) => write!(f, "{expr}"),
+        }
+    }
+}
+
+impl std::fmt::Display for DateExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Now => write!(f, "NOW()"),
+            Self::Add(base, d) => write!(f, "{base} + {d}"),
+            Self::Sub(base, d) => write!(f, "{base} - {d}"),
+        }
+    }
+}
+
+impl std::fmt::Display for DurationLiteral {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let unit = match self.unit {
+            DurationUnit::Days => "d",
+            DurationUnit::Weeks => "w",
+            DurationUnit::Months => "mo",
+            DurationUnit::Years => "y",
+        };
+        write!(f, "{}{unit}", self.amount)
+    }
+}
+
+/// Floats keep a decimal point so `1.0` does not read back as an integer.
+fn write_float(f: &mut std::fmt::Formatter<'_>, value: f64) -> std::fmt::Result {
+    if value.is_finite() && value.fract() == 0.0 {
+        write!(f, "{value:.1}")
+    } else {
+        write!(f, "{value}")
+    }
+}
+
+/// `'a', 'b', 'c'`, in alphabetical order.
+fn write_sorted_list<T: std::fmt::Display>(
+    f: &mut std::fmt::Formatter<'_>,
+    items: &[T],
+) -> std::fmt::Result {
+    let mut rendered: Vec<String> = items.iter().map(ToString::to_string).collect();
+    rendered.sort();
+    write!(f, "{}", rendered.join(", "))
+}
+
+/// One side of AND/OR or the operand of NOT, parenthesized only when the
+/// grammar would otherwise bind it differently.
+fn write_operand<T: std::fmt::Display>(
+    f: &mut std::fmt::Formatter<'_>,
+    operand: &T,
+    parens: bool,
+) -> std::fmt::Result {
+    if parens {
+        write!(f, "({operand})")
+    } else {
+        write!(f, "{operand}")
+    }
+}
+
+/// The canonical text of the query: uppercase keywords, single spaces,
+/// parentheses only where precedence needs them, and sorted `IN` and tag
+/// lists. Queries that differ only in spelling print the same, and the
+/// output parses back to an equivalent query.
+impl std::fmt::Display for MkqlQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.ctes.is_empty() {
+            write!(f, "WITH ")?;
+            for (i, cte) in self.ctes.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{} AS ({})", cte.name, cte.query)?;
+            }
+            write!(f, " ")?;
+        }
+        if let Some(pivot) = &self.pivot {
+            write!(f, "SELECT {pivot}")?;
+            if !self.from.is_empty() {
+                write!(f, " FROM {}", self.from)?;
+            }
+        } else {
+            write!(f, "SELECT {} FROM {}", self.select, self.from)?;
+        }
+        if let Some(where_clause) = &self.where_clause {
+            write!(f, " WHERE {where_clause}")?;
+        }
+        if !self.group_by.is_empty() {
+            write!(f, " GROUP BY {}", self.group_by.join(", "))?;
+        }
+        if let Some(having) = &self.having {
+            write!(f, " HAVING {having}")?;
+        }
+        if let Some(order_by) = &self.order_by {
+            let items: Vec<String> = order_by.iter().map(ToString::to_string).collect();
+            write!(f, " ORDER BY {}", items.join(", "))?;
+        }
+        if let Some(limit) = self.limit {
+            write!(f, " LIMIT {limit}")?;
+        }
+        if let Some(offset) = self.offset {
+            write!(f, " OFFSET {offset}")?;
+        }
+        for set_op in &self.set_ops {
+            write!(f, " {} {}", set_op.op, set_op.query)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for PivotClause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "PIVOT {} BY {} {}({})",
+            self.row_field, self.col_field, self.agg_func, self.agg_field
+        )
+    }
+}
+
+impl std::fmt::Display for PivotKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.bucket {
+            Some(bucket) => write!(f, "{bucket}({})", self.field),
+            None => write!(f, "{}", self.field),
+        }
+    }
+}
+
+impl std::fmt::Display for DateBucket {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Month => write!(f, "MONTH"),
+            Self::Year => write!(f, "YEAR"),
+        }
+    }
+}
+
+impl std::fmt::Display for SetOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Union => write!(f, "UNION"),
+            Self::Intersect => write!(f, "INTERSECT"),
+            Self::Except => write!(f, "EXCEPT"),
+        }
+    }
+}
+
+impl std::fmt::Display for SelectClause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Star => write!(f, "*"),
+            Self::Fields(fields) => {
+                let fields: Vec<String> = fields.iter().map(ToString::to_string).collect();
+                write!(f, "{}", fields.join(", "))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for SelectField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.aggregate {
+            Some(function) => write!(f, "{function}({})", self.name)?,
+            None => write!(f, "{}", self.name)?,
+        }
+        if let Some(alias) = &self.alias {
+            write!(f, " AS {alias}")?;
+        }
+        Ok(())
+    }
+}
+
+impl HavingClause {
+    /// How tightly this node binds: OR, then AND, then NOT, then a predicate.
+    fn precedence(&self) -> u8 {
+        match self {
+            Self::Or(..) => 0,
+            Self::And(..) => 1,
+            Self::Not(_) => 2,
+            Self::Predicate(_) => 3,
+        }
+    }
+}
+
+impl std::fmt::Display for HavingClause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Predicate(pred) => write!(f, "{pred}"),
+            Self::And(left, right) | Self::Or(left, right) => {
+                let op = if matches!(self, Self::And(..)) {
+                    "AND"
+                } else {
+                    "OR"
+                };
+                write_operand(f, left, left.precedence() < self.precedence())?;
+                write!(f, " {op} ")?;
+                write_operand(f, right, right.precedence() < self.precedence())
+            }
+            Self::Not(inner) => {
+                write!(f, "NOT ")?;
+                write_operand(f, inner, inner.precedence() < 3)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for HavingPredicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}({}) {} {}",
+            self.function, self.field, self.op, self.value
+        )
+    }
+}
+
+impl WhereClause {
+    /// How tightly this node binds: OR, then AND, then NOT, then a predicate.
+    fn precedence(&self) -> u8 {
+        match self {
+            Self::Or(..) => 0,
+            Self::And(..) => 1,
+            Self::Not(_) => 2,
+            Self::Predicate(_) => 3,
+        }
+    }
+}
+
+impl std::fmt::Display for WhereClause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Predicate(pred) => write!(f, "{pred}"),
+            Self::And(left, right) | Self::Or(left, right) => {
+                let op = if matches!(self, Self::And(..)) {
+                    "AND"
+                } else {
+                    "OR"
+                };
+                write_operand(f, left, left.precedence() < self.precedence())?;
+                write!(f, " {op} ")?;
+                write_operand(f, right, right.precedence() < self.precedence())
+            }
+            // NOT only takes an atom, so anything but a predicate is wrapped
+            Self::Not(inner) => {
+                write!(f, "NOT ")?;
+                write_operand(f, inner, inner.precedence() < 3)
+            }
         }
     }
 }
 
+impl std::fmt::Display for Predicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Comparison { field, op, value } => write!(f, "{field} {op} {value}"),
+            Self::InList { field, values } => {
+                write!(f, "{field} IN (")?;
+                write_sorted_list(f, values)?;
+                write!(f, ")")
+            }
+            Self::Like { field, pattern } => write!(f, "{field} LIKE '{pattern}'"),
+            Self::Regex { field, pattern } => write!(f, "{field} REGEX '{pattern}'"),
+            Self::BodyContains { term } => write!(f, "BODY CONTAINS '{term}'"),
+            Self::MatchPhrase { target, phrase } => write!(f, "{target} MATCH_PHRASE '{phrase}'"),
+            Self::TagsContain { tags, .. } if tags.len() == 1 => {
+                write!(f, "TAGS CONTAINS '{}'", tags[0])
+            }
+            Self::TagsContain { tags, mode } => {
+                let quoted: Vec<String> = tags.iter().map(|t| format!("'{t}'")).collect();
+                write!(f, "TAGS CONTAINS {mode} (")?;
+                write_sorted_list(f, &quoted)?;
+                write!(f, ")")
+            }
+            Self::TagsEmpty => write!(f, "TAGS IS EMPTY"),
+            Self::Temporal(function) => write!(f, "{function}"),
+            Self::Linked(function) => write!(f, "{function}"),
+            Self::LinkCount {
+                reverse,
+                rel,
+                op,
+                count,
+            } => {
+                let name = if *reverse {
+                    "REVERSE_LINK_COUNT"
+                } else {
+                    "LINK_COUNT"
+                };
+                match rel {
+                    Some(rel) => write!(f, "{name}('{rel}') {op} {count}"),
+                    None => write!(f, "{name}() {op} {count}"),
+                }
+            }
+            Self::Near { query, threshold } => {
+                write!(f, "NEAR('{query}', ")?;
+                write_float(f, *threshold)?;
+                write!(f, ")")
+            }
+            Self::Annotated { annotation_type } => write!(f, "ANNOTATED('{annotation_type}')"),
+        }
+    }
+}
+
+impl std::fmt::Display for PhraseTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Body => write!(f, "BODY"),
+            Self::Title => write!(f, "TITLE"),
+        }
+    }
+}
+
+impl std::fmt::Display for TagMatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Any => write!(f, "ANY"),
+            Self::All => write!(f, "ALL"),
+        }
+    }
+}
+
+impl std::fmt::Display for TemporalFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fresh { duration } => write!(f, "FRESH('{duration}')"),
+            Self::Stale { duration } => write!(f, "STALE('{duration}')"),
+            Self::Changed { duration } => write!(f, "CHANGED('{duration}')"),
+            Self::Expired => write!(f, "EXPIRED()"),
+            Self::Current => write!(f, "CURRENT()"),
+            Self::Latest => write!(f, "LATEST()"),
+            Self::AsOf { datetime } => write!(f, "AS_OF('{datetime}')"),
+            Self::AsOfRange { start, end } => write!(f, "AS_OF_RANGE('{start}', '{end}')"),
+            Self::Overlaps { start, end } => write!(f, "OVERLAPS('{start}', '{end}')"),
+            Self::OverlapsNow => write!(f, "OVERLAPS_NOW()"),
+            Self::EffConfidence { op, threshold } => {
+                write!(f, "EFF_CONFIDENCE({op} ")?;
+                write_float(f, *threshold)?;
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for LinkedFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Forward { rel, .. } => write!(f, "LINKED('{rel}'")?,
+            Self::Reverse { rel, .. } => write!(f, "LINKED(REVERSE, '{rel}'")?,
+            Self::Transitive {
+                rel_pattern,
+                max_depth,
+                ..
+            } => {
+                let name = if rel_pattern.contains('|') {
+                    "LINKED_DEPTH_ANY"
+                } else {
+                    "LINKED_DEPTH"
+                };
+                write!(f, "{name}('{rel_pattern}', {max_depth}")?;
+            }
+        }
+        let end = match self {
+            Self::Forward { target, .. } | Self::Transitive { target, .. } => target,
+            Self::Reverse { source, .. } => source,
+        };
+        match end {
+            Some(end) => write!(f, ", '{end}')"),
+            None => write!(f, ")"),
+        }
+    }
+}
+
+impl std::fmt::Display for OrderByItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.key {
+            OrderKey::Field(field) => write!(f, "{field}")?,
+            OrderKey::Similarity(text) => write!(f, "SIMILARITY('{text}')")?,
+        }
+        // ASC is the default and left implicit
+        if self.direction == SortDirection::Desc {
+            write!(f, " DESC")?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -163,9 +772,14 @@ mod tests {
             select: SelectClause::Star,
             from: "project".to_string(),
             where_clause: None,
+            group_by: Vec::new(),
+            having: None,
             order_by: None,
             limit: None,
             offset: None,
+            set_ops: Vec::new(),
+            ctes: Vec::new(),
+            pivot: None,
         };
 
         let json = serde_json::to_string(&query).expect("serialize");
@@ -173,6 +787,45 @@ mod tests {
         assert_eq!(query, back);
     }
 
+    #[test]
+    fn and_where_narrows_query_and_set_operands() {
+        let select = |from: &str, where_clause| MkqlQuery {
+            select: SelectClause::Star,
+            from: from.to_string(),
+            where_clause,
+            group_by: Vec::new(),
+            having: None,
+            order_by: None,
+            limit: None,
+            offset: None,
+            set_ops: Vec::new(),
+            ctes: Vec::new(),
+            pivot: None,
+        };
+        let current = WhereClause::Predicate(Predicate::Temporal(TemporalFunction::Current));
+        let filter = WhereClause::Predicate(Predicate::Comparison {
+            field: "provenance_type".to_string(),
+            op: CompOp::Eq,
+            value: Value::String("manual".to_string()),
+        });
+
+        let mut query = select("project", Some(current.clone()));
+        query.set_ops.push(SetOperation {
+            op: SetOp::Union,
+            query: select("meeting", None),
+        });
+        query.and_where(filter.clone());
+
+        assert_eq!(
+            query.where_clause,
+            Some(WhereClause::And(
+                Box::new(current),
+                Box::new(filter.clone())
+            ))
+        );
+        assert_eq!(query.set_ops[0].query.where_clause, Some(filter));
+    }
+
     #[test]
     fn ast_roundtrip_complex_query() {
         let query = MkqlQuery {
@@ -180,10 +833,12 @@ mod tests {
                 SelectField {
                     name: "title".to_string(),
                     alias: None,
+                    aggregate: None,
                 },
                 SelectField {
                     name: "status".to_string(),
                     alias: Some("s".to_string()),
+                    aggregate: None,
                 },
             ]),
             from: "project".to_string(),
@@ -197,12 +852,34 @@ mod tests {
                     TemporalFunction::Current,
                 ))),
             )),
+            group_by: Vec::new(),
+            having: None,
             order_by: Some(vec![OrderByItem {
-                field: "observed_at".to_string(),
+                key: OrderKey::Field("observed_at".to_string()),
                 direction: SortDirection::Desc,
             }]),
             limit: Some(10),
             offset: Some(0),
+            set_ops: vec![SetOperation {
+                op: SetOp::Except,
+                query: MkqlQuery {
+                    select: SelectClause::Star,
+                    from: "project".to_string(),
+                    where_clause: Some(WhereClause::Predicate(Predicate::Temporal(
+                        TemporalFunction::Expired,
+                    ))),
+                    group_by: Vec::new(),
+                    having: None,
+                    order_by: None,
+                    limit: None,
+                    offset: None,
+                    set_ops: Vec::new(),
+                    ctes: Vec::new(),
+                    pivot: None,
+                },
+            }],
+            ctes: Vec::new(),
+            pivot: None,
         };
 
         let json = serde_json::to_string(&query).expect("serialize");
@@ -22,8 +22,10 @@ use pest::Parser;
 use pest_derive::Parser;
 
 use ast::{
-    CompOp, LinkedFunction, MkqlQuery, OrderByItem, Predicate, SelectClause, SelectField,
-    SortDirection, TemporalFunction, Value, WhereClause,
+    AggregateFunction, CommonTableExpr, CompOp, DateBucket, DateExpr, DurationLiteral,
+    DurationUnit, HavingClause, HavingPredicate, LinkedFunction, MkqlQuery, OrderByItem, OrderKey,
+    PhraseTarget, PivotClause, PivotKey, Predicate, SelectClause, SelectField, SetOp, SetOperation,
+    SortDirection, TagMatch, TemporalFunction, Value, WhereClause,
 };
 
 #[derive(Parser)]
@@ -57,9 +59,90 @@ pub fn parse_mkql(input: &str) -> Result<MkqlQuery, ParseError> {
 }
 
 fn build_query(pair: pest::iterators::Pair<Rule>) -> Result<MkqlQuery, ParseError> {
+    let mut ctes = Vec::new();
+    let mut query = None;
+
+    for inner in pair.into_inner() {
+        match inner.as_rule() {
+            Rule::with_clause => {
+                for cte in inner.into_inner() {
+                    ctes.push(build_cte(cte)?);
+                }
+            }
+            Rule::compound_select => query = Some(build_compound_select(inner)?),
+            Rule::pivot_stmt => query = Some(build_pivot_stmt(inner)?),
+            _ => {}
+        }
+    }
+
+    let mut query = query.ok_or_else(|| ParseError::UnexpectedRule("empty query".to_string()))?;
+    query.ctes = ctes;
+    Ok(query)
+}
+
+fn build_compound_select(pair: pest::iterators::Pair<Rule>) -> Result<MkqlQuery, ParseError> {
+    let mut inners = pair.into_inner();
+    let first = inners
+        .next()
+        .ok_or_else(|| ParseError::UnexpectedRule("empty query".to_string()))?;
+    let mut query = build_select_stmt(first)?;
+
+    for inner in inners {
+        if inner.as_rule() == Rule::set_op_clause {
+            query.set_ops.push(build_set_op_clause(inner)?);
+        }
+    }
+
+    Ok(query)
+}
+
+fn build_cte(pair: pest::iterators::Pair<Rule>) -> Result<CommonTableExpr, ParseError> {
+    let mut inners = pair.into_inner();
+    let name = inners
+        .next()
+        .ok_or_else(|| ParseError::UnexpectedRule("missing WITH name".to_string()))?
+        .as_str()
+        .to_string();
+    let body = inners
+        .next()
+        .ok_or_else(|| ParseError::UnexpectedRule(format!("missing subquery for WITH '{name}'")))?;
+    Ok(CommonTableExpr {
+        name,
+        query: build_compound_select(body)?,
+    })
+}
+
+fn build_set_op_clause(pair: pest::iterators::Pair<Rule>) -> Result<SetOperation, ParseError> {
+    let mut inners = pair.into_inner();
+    let op_pair = inners
+        .next()
+        .and_then(|p| p.into_inner().next())
+        .ok_or_else(|| ParseError::UnexpectedRule("missing set operator".to_string()))?;
+    let op = match op_pair.as_rule() {
+        Rule::set_op_union => SetOp::Union,
+        Rule::set_op_intersect => SetOp::Intersect,
+        Rule::set_op_except => SetOp::Except,
+        other => {
+            return Err(ParseError::UnexpectedRule(format!(
+                "in set operator: {other:?}"
+            )))
+        }
+    };
+    let stmt = inners.next().ok_or_else(|| {
+        ParseError::UnexpectedRule("missing SELECT after set operator".to_string())
+    })?;
+    Ok(SetOperation {
+        op,
+        query: build_select_stmt(stmt)?,
+    })
+}
+
+fn build_select_stmt(pair: pest::iterators::Pair<Rule>) -> Result<MkqlQuery, ParseError> {
     let mut select = SelectClause::Star;
     let mut from = String::new();
     let mut where_clause = None;
+    let mut group_by = Vec::new();
+    let mut having = None;
     let mut order_by = None;
     let mut limit = None;
     let mut offset = None;
@@ -75,6 +158,12 @@ fn build_query(pair: pest::iterators::Pair<Rule>) -> Result<MkqlQuery, ParseErro
             Rule::where_clause => {
                 where_clause = Some(build_where_clause(inner)?);
             }
+            Rule::group_by_clause => {
+                group_by = inner.into_inner().map(|f| f.as_str().to_string()).collect();
+            }
+            Rule::having_clause => {
+                having = Some(build_having_clause(inner)?);
+            }
             Rule::order_by_clause => {
                 order_by = Some(build_order_by(inner)?);
             }
@@ -84,7 +173,6 @@ fn build_query(pair: pest::iterators::Pair<Rule>) -> Result<MkqlQuery, ParseErro
             Rule::offset_clause => {
                 offset = Some(build_offset(inner)?);
             }
-            Rule::EOI => {}
             _ => {}
         }
     }
@@ -93,12 +181,81 @@ fn build_query(pair: pest::iterators::Pair<Rule>) -> Result<MkqlQuery, ParseErro
         select,
         from,
         where_clause,
+        group_by,
+        having,
         order_by,
         limit,
         offset,
+        set_ops: Vec::new(),
+        ctes: Vec::new(),
+        pivot: None,
     })
 }
 
+fn build_pivot_stmt(pair: pest::iterators::Pair<Rule>) -> Result<MkqlQuery, ParseError> {
+    let mut pivot = None;
+    let mut from = String::new();
+    let mut where_clause = None;
+
+    for inner in pair.into_inner() {
+        match inner.as_rule() {
+            Rule::pivot_clause => pivot = Some(build_pivot_clause(inner)?),
+            Rule::from_clause => from = build_from_clause(inner),
+            Rule::where_clause => where_clause = Some(build_where_clause(inner)?),
+            _ => {}
+        }
+    }
+
+    Ok(MkqlQuery {
+        select: SelectClause::Star,
+        from,
+        where_clause,
+        group_by: Vec::new(),
+        having: None,
+        order_by: None,
+        limit: None,
+        offset: None,
+        set_ops: Vec::new(),
+        ctes: Vec::new(),
+        pivot,
+    })
+}
+
+fn build_pivot_clause(pair: pest::iterators::Pair<Rule>) -> Result<PivotClause, ParseError> {
+    let mut inners = pair.into_inner().filter(|p| p.as_rule() != Rule::kw_pivot);
+    let row_field = build_pivot_key(inners.next().unwrap());
+    let col_field = build_pivot_key(inners.next().unwrap());
+    let (agg_func, agg_field) = build_aggregate(inners.next().unwrap())?;
+    Ok(PivotClause {
+        row_field,
+        col_field,
+        agg_func,
+        agg_field,
+    })
+}
+
+/// `doc_type`, `month(observed_at)` or `year(observed_at)`.
+fn build_pivot_key(pair: pest::iterators::Pair<Rule>) -> PivotKey {
+    let mut inners = pair.into_inner();
+    let first = inners.next().unwrap();
+    if first.as_rule() == Rule::date_bucket {
+        let bucket = if first.as_str().eq_ignore_ascii_case("MONTH") {
+            DateBucket::Month
+        } else {
+            DateBucket::Year
+        };
+        PivotKey {
+            field: inners.next().unwrap().as_str().to_string(),
+            bucket: Some(bucket),
+        }
+    } else {
+        PivotKey {
+            field: first.as_str().to_string(),
+            bucket: None,
+        }
+    }
+}
+
 fn build_select_clause(pair: pest::iterators::Pair<Rule>) -> Result<SelectClause, ParseError> {
     let inner = pair
         .into_inner()
@@ -123,13 +280,42 @@ fn build_select_clause(pair: pest::iterators::Pair<Rule>) -> Result<SelectClause
 
 fn build_select_field(pair: pest::iterators::Pair<Rule>) -> Result<SelectField, ParseError> {
     let mut inners = pair.into_inner();
-    let name = inners
+    let first = inners
         .next()
-        .ok_or_else(|| ParseError::UnexpectedRule("missing field name".to_string()))?
-        .as_str()
-        .to_string();
+        .ok_or_else(|| ParseError::UnexpectedRule("missing field name".to_string()))?;
+    let (name, aggregate) = if first.as_rule() == Rule::aggregate {
+        let (function, field) = build_aggregate(first)?;
+        (field, Some(function))
+    } else {
+        (first.as_str().to_string(), None)
+    };
     let alias = inners.next().map(|a| a.as_str().to_string());
-    Ok(SelectField { name, alias })
+    Ok(SelectField {
+        name,
+        alias,
+        aggregate,
+    })
+}
+
+/// `COUNT(*)`, `AVG(confidence)`, ... as the function and its field (`*` for all).
+fn build_aggregate(
+    pair: pest::iterators::Pair<Rule>,
+) -> Result<(AggregateFunction, String), ParseError> {
+    let mut inners = pair.into_inner();
+    let function = match inners.next().unwrap().as_str().to_uppercase().as_str() {
+        "COUNT" => AggregateFunction::Count,
+        "SUM" => AggregateFunction::Sum,
+        "AVG" => AggregateFunction::Avg,
+        "MIN" => AggregateFunction::Min,
+        _ => AggregateFunction::Max,
+    };
+    let field = inners.next().unwrap().as_str().to_string();
+    if field == "*" && function != AggregateFunction::Count {
+        return Err(ParseError::Grammar(format!(
+            "{function}(*) is not supported; only COUNT takes '*'"
+        )));
+    }
+    Ok((function, field))
 }
 
 fn build_from_clause(pair: pest::iterators::Pair<Rule>) -> String {
@@ -178,21 +364,18 @@ fn build_and_expr(pair: pest::iterators::Pair<Rule>) -> Result<WhereClause, Pars
 }
 
 fn build_not_expr(pair: pest::iterators::Pair<Rule>) -> Result<WhereClause, ParseError> {
-    let mut inners = pair.into_inner().peekable();
+    let mut inners = pair.into_inner();
     let first = inners
-        .peek()
+        .next()
         .ok_or_else(|| ParseError::UnexpectedRule("empty not_expr".to_string()))?;
 
-    // Check if first child is an atom (no NOT keyword)
-    if first.as_rule() == Rule::atom {
-        let atom = inners.next().unwrap();
-        build_atom(atom)
+    if first.as_rule() == Rule::kw_not {
+        let atom = inners
+            .next()
+            .ok_or_else(|| ParseError::UnexpectedRule("NOT without operand".to_string()))?;
+        Ok(WhereClause::Not(Box::new(build_atom(atom)?)))
     } else {
-        // NOT prefix — skip the first child (which is consumed by the grammar rule) and get the atom
-        // In pest, `kw_not` is silent so we just get the atom
-        let atom = inners.next().unwrap();
-        let inner = build_atom(atom)?;
-        Ok(WhereClause::Not(Box::new(inner)))
+        build_atom(first)
     }
 }
 
@@ -215,10 +398,23 @@ fn build_atom(pair: pest::iterators::Pair<Rule>) -> Result<WhereClause, ParseErr
             let pred = build_like_pred(inner)?;
             Ok(WhereClause::Predicate(pred))
         }
+        Rule::regex_pred => {
+            let pred = build_regex_pred(inner)?;
+            Ok(WhereClause::Predicate(pred))
+        }
         Rule::body_contains_pred => {
             let pred = build_body_contains(inner)?;
             Ok(WhereClause::Predicate(pred))
         }
+        Rule::phrase_pred => {
+            let pred = build_phrase_pred(inner)?;
+            Ok(WhereClause::Predicate(pred))
+        }
+        Rule::tags_contains_pred | Rule::tags_in_pred => {
+            let pred = build_tags_contains(inner)?;
+            Ok(WhereClause::Predicate(pred))
+        }
+        Rule::tags_empty_pred => Ok(WhereClause::Predicate(Predicate::TagsEmpty)),
         Rule::temporal_fn => {
             let pred = build_temporal_fn(inner)?;
             Ok(WhereClause::Predicate(Predicate::Temporal(pred)))
@@ -231,6 +427,17 @@ fn build_atom(pair: pest::iterators::Pair<Rule>) -> Result<WhereClause, ParseErr
             let pred = build_near_fn(inner)?;
             Ok(WhereClause::Predicate(pred))
         }
+        Rule::link_count_fn => {
+            let pred = build_link_count_fn(inner)?;
+            Ok(WhereClause::Predicate(pred))
+        }
+        Rule::annotated_fn => {
+            let inner = inner.into_inner().next().unwrap();
+            let s = inner.as_str();
+            Ok(WhereClause::Predicate(Predicate::Annotated {
+                annotation_type: s[1..s.len() - 1].to_string(),
+            }))
+        }
         Rule::or_expr => build_or_expr(inner),
         _ => Err(ParseError::UnexpectedRule(format!(
             "in atom: {:?}",
@@ -239,6 +446,87 @@ fn build_atom(pair: pest::iterators::Pair<Rule>) -> Result<WhereClause, ParseErr
     }
 }
 
+fn build_having_clause(pair: pest::iterators::Pair<Rule>) -> Result<HavingClause, ParseError> {
+    let or_expr = pair
+        .into_inner()
+        .next()
+        .ok_or_else(|| ParseError::UnexpectedRule("empty having clause".to_string()))?;
+    build_having_or_expr(or_expr)
+}
+
+fn build_having_or_expr(pair: pest::iterators::Pair<Rule>) -> Result<HavingClause, ParseError> {
+    let mut inners = pair.into_inner();
+    let first = inners
+        .next()
+        .ok_or_else(|| ParseError::UnexpectedRule("empty having_or_expr".to_string()))?;
+    let mut result = build_having_and_expr(first)?;
+
+    for next in inners {
+        let right = build_having_and_expr(next)?;
+        result = HavingClause::Or(Box::new(result), Box::new(right));
+    }
+
+    Ok(result)
+}
+
+fn build_having_and_expr(pair: pest::iterators::Pair<Rule>) -> Result<HavingClause, ParseError> {
+    let mut inners = pair.into_inner();
+    let first = inners
+        .next()
+        .ok_or_else(|| ParseError::UnexpectedRule("empty having_and_expr".to_string()))?;
+    let mut result = build_having_not_expr(first)?;
+
+    for next in inners {
+        let right = build_having_not_expr(next)?;
+        result = HavingClause::And(Box::new(result), Box::new(right));
+    }
+
+    Ok(result)
+}
+
+fn build_having_not_expr(pair: pest::iterators::Pair<Rule>) -> Result<HavingClause, ParseError> {
+    let mut inners = pair.into_inner();
+    let first = inners
+        .next()
+        .ok_or_else(|| ParseError::UnexpectedRule("empty having_not_expr".to_string()))?;
+
+    if first.as_rule() == Rule::kw_not {
+        let atom = inners
+            .next()
+            .ok_or_else(|| ParseError::UnexpectedRule("NOT without operand".to_string()))?;
+        Ok(HavingClause::Not(Box::new(build_having_atom(atom)?)))
+    } else {
+        build_having_atom(first)
+    }
+}
+
+fn build_having_atom(pair: pest::iterators::Pair<Rule>) -> Result<HavingClause, ParseError> {
+    let inner = pair
+        .into_inner()
+        .next()
+        .ok_or_else(|| ParseError::UnexpectedRule("empty having atom".to_string()))?;
+
+    match inner.as_rule() {
+        Rule::having_pred => {
+            let mut inners = inner.into_inner();
+            let (function, field) = build_aggregate(inners.next().unwrap())?;
+            let op = build_comp_op(inners.next().unwrap())?;
+            let value = build_value(inners.next().unwrap())?;
+            Ok(HavingClause::Predicate(HavingPredicate {
+                function,
+                field,
+                op,
+                value,
+            }))
+        }
+        Rule::having_or_expr => build_having_or_expr(inner),
+        _ => Err(ParseError::UnexpectedRule(format!(
+            "in having atom: {:?}",
+            inner.as_rule()
+        ))),
+    }
+}
+
 fn build_comparison(pair: pest::iterators::Pair<Rule>) -> Result<Predicate, ParseError> {
     let mut inners = pair.into_inner();
     let field = inners.next().unwrap().as_str().to_string();
@@ -292,6 +580,8 @@ fn build_value(pair: pest::iterators::Pair<Rule>) -> Result<Value, ParseError> {
             Ok(Value::Boolean(b))
         }
         Rule::null_literal => Ok(Value::Null),
+        Rule::param_ref => Ok(Value::Param(inner.as_str()[1..].to_string())),
+        Rule::date_expr => Ok(Value::Date(build_date_expr(inner)?)),
         _ => Err(ParseError::UnexpectedRule(format!(
             "in value: {:?}",
             inner.as_rule()
@@ -299,6 +589,42 @@ fn build_value(pair: pest::iterators::Pair<Rule>) -> Result<Value, ParseError> {
     }
 }
 
+/// Fold `NOW() - 7d + 1w` left to right into nested [`DateExpr`]s.
+fn build_date_expr(pair: pest::iterators::Pair<Rule>) -> Result<DateExpr, ParseError> {
+    let mut expr = DateExpr::Now;
+    let mut inners = pair.into_inner();
+    while let Some(op) = inners.next() {
+        let interval = inners
+            .next()
+            .ok_or_else(|| ParseError::UnexpectedRule("date operator without interval".into()))?;
+        let duration = build_interval(interval.as_str())?;
+        expr = match op.as_str() {
+            "+" => DateExpr::Add(Box::new(expr), duration),
+            _ => DateExpr::Sub(Box::new(expr), duration),
+        };
+    }
+    Ok(expr)
+}
+
+fn build_interval(s: &str) -> Result<DurationLiteral, ParseError> {
+    let digits = s.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let unit = match &s[digits.len()..] {
+        "d" => DurationUnit::Days,
+        "w" => DurationUnit::Weeks,
+        "mo" => DurationUnit::Months,
+        "y" => DurationUnit::Years,
+        other => {
+            return Err(ParseError::UnexpectedRule(format!(
+                "unknown interval unit: {other}"
+            )))
+        }
+    };
+    let amount = digits
+        .parse()
+        .map_err(|e: std::num::ParseIntError| ParseError::Grammar(e.to_string()))?;
+    Ok(DurationLiteral { amount, unit })
+}
+
 fn build_in_pred(pair: pest::iterators::Pair<Rule>) -> Result<Predicate, ParseError> {
     let mut inners = pair.into_inner();
     let field = inners.next().unwrap().as_str().to_string();
@@ -318,6 +644,50 @@ fn build_like_pred(pair: pest::iterators::Pair<Rule>) -> Result<Predicate, Parse
     Ok(Predicate::Like { field, pattern })
 }
 
+fn build_regex_pred(pair: pest::iterators::Pair<Rule>) -> Result<Predicate, ParseError> {
+    let mut inners = pair.into_inner();
+    let field = inners.next().unwrap().as_str().to_string();
+    let pattern_raw = inners.next().unwrap().as_str();
+    let pattern = pattern_raw[1..pattern_raw.len() - 1].to_string();
+    Ok(Predicate::Regex { field, pattern })
+}
+
+fn build_link_count_fn(pair: pest::iterators::Pair<Rule>) -> Result<Predicate, ParseError> {
+    let mut reverse = false;
+    let mut rel = None;
+    let mut op = None;
+    let mut count = None;
+
+    for inner in pair.into_inner() {
+        match inner.as_rule() {
+            Rule::link_count_dir => {
+                reverse = inner.as_str().to_uppercase().starts_with("REVERSE");
+            }
+            Rule::string_literal => {
+                let s = inner.as_str();
+                rel = Some(s[1..s.len() - 1].to_string());
+            }
+            Rule::comp_op => op = Some(build_comp_op(inner)?),
+            Rule::integer_literal => {
+                let n: i64 = inner
+                    .as_str()
+                    .parse()
+                    .map_err(|e: std::num::ParseIntError| ParseError::Grammar(e.to_string()))?;
+                count = Some(n);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(Predicate::LinkCount {
+        reverse,
+        rel,
+        op: op.ok_or_else(|| ParseError::UnexpectedRule("link count missing operator".into()))?,
+        count: count
+            .ok_or_else(|| ParseError::UnexpectedRule("link count missing threshold".into()))?,
+    })
+}
+
 fn build_body_contains(pair: pest::iterators::Pair<Rule>) -> Result<Predicate, ParseError> {
     let inner = pair.into_inner().next().unwrap();
     let s = inner.as_str();
@@ -325,6 +695,36 @@ fn build_body_contains(pair: pest::iterators::Pair<Rule>) -> Result<Predicate, P
     Ok(Predicate::BodyContains { term })
 }
 
+fn build_phrase_pred(pair: pest::iterators::Pair<Rule>) -> Result<Predicate, ParseError> {
+    let mut inner = pair.into_inner();
+    let target = match inner.next().unwrap().as_str().to_uppercase().as_str() {
+        "TITLE" => PhraseTarget::Title,
+        _ => PhraseTarget::Body,
+    };
+    let s = inner.next().unwrap().as_str();
+    let phrase = s[1..s.len() - 1].to_string();
+    Ok(Predicate::MatchPhrase { target, phrase })
+}
+
+fn build_tags_contains(pair: pest::iterators::Pair<Rule>) -> Result<Predicate, ParseError> {
+    let unquote = |s: &str| s[1..s.len() - 1].to_string();
+    let mut mode = if pair.as_rule() == Rule::tags_in_pred {
+        TagMatch::Any
+    } else {
+        TagMatch::All
+    };
+    let mut tags = Vec::new();
+    for inner in pair.into_inner() {
+        match inner.as_rule() {
+            Rule::tag_match if inner.as_str().eq_ignore_ascii_case("ANY") => mode = TagMatch::Any,
+            Rule::tag_list => tags.extend(inner.into_inner().map(|t| unquote(t.as_str()))),
+            Rule::string_literal => tags.push(unquote(inner.as_str())),
+            _ => {}
+        }
+    }
+    Ok(Predicate::TagsContain { tags, mode })
+}
+
 fn build_temporal_fn(pair: pest::iterators::Pair<Rule>) -> Result<TemporalFunction, ParseError> {
     let inner = pair.into_inner().next().unwrap();
     match inner.as_rule() {
@@ -338,6 +738,11 @@ fn build_temporal_fn(pair: pest::iterators::Pair<Rule>) -> Result<TemporalFuncti
             let duration = s[1..s.len() - 1].to_string();
             Ok(TemporalFunction::Stale { duration })
         }
+        Rule::changed_fn => {
+            let s = inner.into_inner().next().unwrap().as_str();
+            let duration = s[1..s.len() - 1].to_string();
+            Ok(TemporalFunction::Changed { duration })
+        }
         Rule::expired_fn => Ok(TemporalFunction::Expired),
         Rule::current_fn => Ok(TemporalFunction::Current),
         Rule::latest_fn => Ok(TemporalFunction::Latest),
@@ -346,6 +751,25 @@ fn build_temporal_fn(pair: pest::iterators::Pair<Rule>) -> Result<TemporalFuncti
             let datetime = s[1..s.len() - 1].to_string();
             Ok(TemporalFunction::AsOf { datetime })
         }
+        Rule::as_of_range_fn => {
+            let mut bounds = inner.into_inner().map(|p| {
+                let s = p.as_str();
+                s[1..s.len() - 1].to_string()
+            });
+            let start = bounds.next().unwrap();
+            let end = bounds.next().unwrap();
+            Ok(TemporalFunction::AsOfRange { start, end })
+        }
+        Rule::overlaps_fn => {
+            let mut bounds = inner.into_inner().map(|p| {
+                let s = p.as_str();
+                s[1..s.len() - 1].to_string()
+            });
+            let start = bounds.next().unwrap();
+            let end = bounds.next().unwrap();
+            Ok(TemporalFunction::Overlaps { start, end })
+        }
+        Rule::overlaps_now_fn => Ok(TemporalFunction::OverlapsNow),
         Rule::eff_conf_fn => {
             let mut inners = inner.into_inner();
             let op = build_comp_op(inners.next().unwrap())?;
@@ -399,6 +823,32 @@ fn build_linked_fn(pair: pest::iterators::Pair<Rule>) -> Result<LinkedFunction,
             };
             Ok(LinkedFunction::Reverse { rel, source })
         }
+        Rule::linked_depth | Rule::linked_depth_any => {
+            let any = inner.as_rule() == Rule::linked_depth_any;
+            let mut parts = inner.into_inner();
+            let raw = parts.next().unwrap().as_str();
+            let rel_pattern = raw[1..raw.len() - 1].to_string();
+            if !any && rel_pattern.contains('|') {
+                return Err(ParseError::Grammar(format!(
+                    "LINKED_DEPTH takes a single relation; use LINKED_DEPTH_ANY for '{rel_pattern}'"
+                )));
+            }
+            let max_depth = parts
+                .next()
+                .unwrap()
+                .as_str()
+                .parse()
+                .map_err(|e: std::num::ParseIntError| ParseError::Grammar(e.to_string()))?;
+            let target = parts.next().map(|t| {
+                let raw = t.as_str();
+                raw[1..raw.len() - 1].to_string()
+            });
+            Ok(LinkedFunction::Transitive {
+                rel_pattern,
+                max_depth,
+                target,
+            })
+        }
         _ => Err(ParseError::UnexpectedRule(format!(
             "in linked_fn: {:?}",
             inner.as_rule()
@@ -423,7 +873,14 @@ fn build_order_by(pair: pest::iterators::Pair<Rule>) -> Result<Vec<OrderByItem>,
     pair.into_inner()
         .map(|item| {
             let mut inners = item.into_inner();
-            let field = inners.next().unwrap().as_str().to_string();
+            let key_pair = inners.next().unwrap();
+            let key = match key_pair.as_rule() {
+                Rule::similarity_expr => {
+                    let s = key_pair.into_inner().next().unwrap().as_str();
+                    OrderKey::Similarity(s[1..s.len() - 1].to_string())
+                }
+                _ => OrderKey::Field(key_pair.as_str().to_string()),
+            };
             let direction = match inners.next() {
                 Some(dir) => {
                     if dir.as_str().eq_ignore_ascii_case("desc") {
@@ -434,7 +891,7 @@ fn build_order_by(pair: pest::iterators::Pair<Rule>) -> Result<Vec<OrderByItem>,
                 }
                 None => SortDirection::Asc,
             };
-            Ok(OrderByItem { field, direction })
+            Ok(OrderByItem { key, direction })
         })
         .collect()
 }
@@ -545,6 +1002,75 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_named_params() {
+        let q =
+            parse_mkql("SELECT * FROM project WHERE confidence >= :min AND status IN (:a, 'x')")
+                .unwrap();
+        match &q.where_clause {
+            Some(WhereClause::And(left, right)) => {
+                assert!(matches!(
+                    left.as_ref(),
+                    WhereClause::Predicate(Predicate::Comparison { value: Value::Param(p), .. })
+                        if p == "min"
+                ));
+                assert!(matches!(
+                    right.as_ref(),
+                    WhereClause::Predicate(Predicate::InList { values, .. })
+                        if values[0] == Value::Param("a".to_string())
+                ));
+            }
+            other => panic!("expected AND, got {other:?}"),
+        }
+
+        assert!(parse_mkql("SELECT * FROM project WHERE status = : x").is_err());
+    }
+
+    #[test]
+    fn parse_date_arithmetic() {
+        let q = parse_mkql("SELECT * FROM project WHERE observed_at >= now() - 7d + 1mo").unwrap();
+        let expected = DateExpr::Add(
+            Box::new(DateExpr::Sub(
+                Box::new(DateExpr::Now),
+                DurationLiteral {
+                    amount: 7,
+                    unit: DurationUnit::Days,
+                },
+            )),
+            DurationLiteral {
+                amount: 1,
+                unit: DurationUnit::Months,
+            },
+        );
+        match &q.where_clause {
+            Some(WhereClause::Predicate(Predicate::Comparison { value, .. })) => {
+                assert_eq!(value, &Value::Date(expected));
+                assert_eq!(value.to_string(), "NOW() - 7d + 1mo");
+            }
+            other => panic!("expected comparison, got {other:?}"),
+        }
+
+        let q = parse_mkql("SELECT * FROM project WHERE valid_until < NOW()").unwrap();
+        assert!(matches!(
+            q.where_clause,
+            Some(WhereClause::Predicate(Predicate::Comparison {
+                value: Value::Date(DateExpr::Now),
+                ..
+            }))
+        ));
+
+        for bad in [
+            "NOW() - -1d",
+            "NOW() - 7",
+            "NOW() - 7h",
+            "NOW() - 7days",
+            "NOW - 7d",
+        ] {
+            let mkql = format!("SELECT * FROM project WHERE observed_at >= {bad}");
+            assert!(parse_mkql(&mkql).is_err(), "{bad} should not parse");
+        }
+    }
+
     #[test]
     fn parse_like_pattern() {
         let q = parse_mkql("SELECT * FROM project WHERE title LIKE 'Alpha%'").unwrap();
@@ -557,6 +1083,58 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_regex_pattern() {
+        let q = parse_mkql("SELECT * FROM meeting WHERE title REGEX '^Sprint [0-9]+'").unwrap();
+        match &q.where_clause {
+            Some(WhereClause::Predicate(Predicate::Regex { field, pattern })) => {
+                assert_eq!(field, "title");
+                assert_eq!(pattern, "^Sprint [0-9]+");
+            }
+            other => panic!("expected regex, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_link_count() {
+        let q = parse_mkql("SELECT * FROM project WHERE LINK_COUNT() > 5").unwrap();
+        assert_eq!(
+            q.where_clause,
+            Some(WhereClause::Predicate(Predicate::LinkCount {
+                reverse: false,
+                rel: None,
+                op: CompOp::Gt,
+                count: 5,
+            }))
+        );
+    }
+
+    #[test]
+    fn parse_reverse_link_count_with_rel() {
+        let q = parse_mkql(
+            "SELECT * FROM person WHERE reverse_link_count('owner') >= 2 AND status = 'active'",
+        )
+        .unwrap();
+        match &q.where_clause {
+            Some(WhereClause::And(left, _)) => assert_eq!(
+                **left,
+                WhereClause::Predicate(Predicate::LinkCount {
+                    reverse: true,
+                    rel: Some("owner".to_string()),
+                    op: CompOp::Gte,
+                    count: 2,
+                })
+            ),
+            other => panic!("expected AND with link count, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_link_count_requires_comparison() {
+        assert!(parse_mkql("SELECT * FROM project WHERE LINK_COUNT()").is_err());
+        assert!(parse_mkql("SELECT * FROM project WHERE LINK_COUNT() > 'many'").is_err());
+    }
+
     #[test]
     fn parse_and_or_combinations() {
         let q = parse_mkql("SELECT * FROM project WHERE status = 'active' AND confidence > 0.5")
@@ -568,6 +1146,126 @@ mod tests {
         assert!(matches!(q.where_clause, Some(WhereClause::Or(_, _))));
     }
 
+    #[test]
+    fn parse_tags_contains_single_any_and_all() {
+        let pred = |mkql: &str| match parse_mkql(mkql).unwrap().where_clause {
+            Some(WhereClause::Predicate(pred)) => pred,
+            other => panic!("expected a single predicate, got {other:?}"),
+        };
+
+        assert_eq!(
+            pred("SELECT * FROM project WHERE TAGS CONTAINS 'rust'"),
+            Predicate::TagsContain {
+                tags: vec!["rust".to_string()],
+                mode: TagMatch::All,
+            }
+        );
+        assert_eq!(
+            pred("SELECT * FROM project WHERE tags contains any ('rust', 'ai')"),
+            Predicate::TagsContain {
+                tags: vec!["rust".to_string(), "ai".to_string()],
+                mode: TagMatch::Any,
+            }
+        );
+        assert_eq!(
+            pred("SELECT * FROM project WHERE TAGS CONTAINS ALL ('rust', 'ai')"),
+            Predicate::TagsContain {
+                tags: vec!["rust".to_string(), "ai".to_string()],
+                mode: TagMatch::All,
+            }
+        );
+        assert_eq!(
+            pred("SELECT * FROM project WHERE TAGS IS EMPTY"),
+            Predicate::TagsEmpty
+        );
+    }
+
+    #[test]
+    fn parse_annotated() {
+        let q = parse_mkql("SELECT * FROM project WHERE annotated('reviewed') AND annotations = 1")
+            .unwrap();
+        match q.where_clause {
+            Some(WhereClause::And(left, right)) => {
+                assert_eq!(
+                    *left,
+                    WhereClause::Predicate(Predicate::Annotated {
+                        annotation_type: "reviewed".to_string()
+                    })
+                );
+                assert!(matches!(
+                    *right,
+                    WhereClause::Predicate(Predicate::Comparison { ref field, .. }) if field == "annotations"
+                ));
+            }
+            other => panic!("expected AND, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_tags_in_and_includes() {
+        let pred = |mkql: &str| match parse_mkql(mkql).unwrap().where_clause {
+            Some(WhereClause::Predicate(pred)) => pred,
+            other => panic!("expected a single predicate, got {other:?}"),
+        };
+        let tags = |names: &[&str]| names.iter().map(|t| (*t).to_string()).collect();
+
+        assert_eq!(
+            pred("SELECT * FROM project WHERE TAGS IN ('rust')"),
+            Predicate::TagsContain {
+                tags: tags(&["rust"]),
+                mode: TagMatch::Any,
+            }
+        );
+        assert_eq!(
+            pred("SELECT * FROM project WHERE tags in ('rust', 'machine learning')"),
+            Predicate::TagsContain {
+                tags: tags(&["rust", "machine learning"]),
+                mode: TagMatch::Any,
+            }
+        );
+        assert_eq!(
+            pred("SELECT * FROM project WHERE TAGS INCLUDES ALL ('rust', 'ai')"),
+            Predicate::TagsContain {
+                tags: tags(&["rust", "ai"]),
+                mode: TagMatch::All,
+            }
+        );
+        assert!(parse_mkql("SELECT * FROM project WHERE TAGS IN ()").is_err());
+        assert!(parse_mkql("SELECT * FROM project WHERE TAGS INCLUDES ALL ()").is_err());
+    }
+
+    #[test]
+    fn parse_tags_predicates_combine_with_and_or() {
+        let q = parse_mkql(
+            "SELECT * FROM project WHERE TAGS CONTAINS 'rust' AND (TAGS IS EMPTY OR tags = 'x')",
+        )
+        .unwrap();
+        match q.where_clause {
+            Some(WhereClause::And(left, right)) => {
+                assert!(matches!(
+                    *left,
+                    WhereClause::Predicate(Predicate::TagsContain { .. })
+                ));
+                assert!(matches!(*right, WhereClause::Or(_, _)));
+            }
+            other => panic!("expected AND, got {other:?}"),
+        }
+        assert!(parse_mkql("SELECT * FROM project WHERE TAGS CONTAINS ANY ()").is_err());
+    }
+
+    #[test]
+    fn parse_not_wraps_the_negated_atom() {
+        let q =
+            parse_mkql("SELECT * FROM project WHERE NOT status = 'done' AND notes = 'x'").unwrap();
+        match q.where_clause {
+            Some(WhereClause::And(left, right)) => {
+                assert!(matches!(*left, WhereClause::Not(_)));
+                assert!(matches!(*right, WhereClause::Predicate(_)));
+            }
+            other => panic!("expected AND, got {other:?}"),
+        }
+    }
+
     #[test]
     fn parse_body_contains() {
         let q = parse_mkql("SELECT * FROM meeting WHERE BODY CONTAINS 'machine learning'").unwrap();
@@ -579,6 +1277,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_match_phrase() {
+        let q =
+            parse_mkql("SELECT * FROM meeting WHERE BODY MATCH_PHRASE 'deep learning'").unwrap();
+        assert_eq!(
+            q.where_clause,
+            Some(WhereClause::Predicate(Predicate::MatchPhrase {
+                target: PhraseTarget::Body,
+                phrase: "deep learning".to_string(),
+            }))
+        );
+
+        let q =
+            parse_mkql("SELECT * FROM meeting WHERE title match_phrase 'Sprint Review'").unwrap();
+        assert!(matches!(
+            q.where_clause,
+            Some(WhereClause::Predicate(Predicate::MatchPhrase {
+                target: PhraseTarget::Title,
+                ..
+            }))
+        ));
+
+        // `title` is still an ordinary field elsewhere
+        let q = parse_mkql("SELECT * FROM meeting WHERE title = 'x'").unwrap();
+        assert!(matches!(
+            q.where_clause,
+            Some(WhereClause::Predicate(Predicate::Comparison { .. }))
+        ));
+    }
+
     // === T-200.3: Temporal functions ===
 
     #[test]
@@ -594,6 +1322,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_changed_duration() {
+        let q = parse_mkql("SELECT * FROM project WHERE changed('1d')").unwrap();
+        assert_eq!(
+            q.where_clause,
+            Some(WhereClause::Predicate(Predicate::Temporal(
+                TemporalFunction::Changed {
+                    duration: "1d".to_string()
+                }
+            )))
+        );
+    }
+
     #[test]
     fn parse_stale_and_expired() {
         let q = parse_mkql("SELECT * FROM project WHERE STALE('30d')").unwrap();
@@ -645,6 +1386,45 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_as_of_range() {
+        let q = parse_mkql("SELECT * FROM event WHERE AS_OF_RANGE('2025-01-01', '2025-01-07')")
+            .unwrap();
+        assert_eq!(
+            q.where_clause,
+            Some(WhereClause::Predicate(Predicate::Temporal(
+                TemporalFunction::AsOfRange {
+                    start: "2025-01-01".to_string(),
+                    end: "2025-01-07".to_string(),
+                }
+            )))
+        );
+        assert!(parse_mkql("SELECT * FROM event WHERE AS_OF_RANGE('2025-01-01')").is_err());
+    }
+
+    #[test]
+    fn parse_overlaps_and_overlaps_now() {
+        let q = parse_mkql(
+            "SELECT * FROM project WHERE OVERLAPS('2025-01-01', '2025-03-31') OR overlaps_now()",
+        )
+        .unwrap();
+        assert_eq!(
+            q.where_clause,
+            Some(WhereClause::Or(
+                Box::new(WhereClause::Predicate(Predicate::Temporal(
+                    TemporalFunction::Overlaps {
+                        start: "2025-01-01".to_string(),
+                        end: "2025-03-31".to_string(),
+                    }
+                ))),
+                Box::new(WhereClause::Predicate(Predicate::Temporal(
+                    TemporalFunction::OverlapsNow
+                ))),
+            ))
+        );
+        assert!(parse_mkql("SELECT * FROM project WHERE OVERLAPS('2025-01-01')").is_err());
+    }
+
     #[test]
     fn parse_eff_confidence() {
         let q = parse_mkql("SELECT * FROM project WHERE EFF_CONFIDENCE(> 0.5)").unwrap();
@@ -676,6 +1456,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_linked_depth_and_depth_any() {
+        let linked = |mkql: &str| match parse_mkql(mkql).unwrap().where_clause {
+            Some(WhereClause::Predicate(Predicate::Linked(lf))) => lf,
+            other => panic!("expected linked, got {other:?}"),
+        };
+
+        assert_eq!(
+            linked("SELECT * FROM project WHERE LINKED_DEPTH('blocked_by', 3, 'proj-alpha-001')"),
+            LinkedFunction::Transitive {
+                rel_pattern: "blocked_by".to_string(),
+                max_depth: 3,
+                target: Some("proj-alpha-001".to_string()),
+            }
+        );
+        assert_eq!(
+            linked("SELECT * FROM project WHERE linked_depth_any('blocked_by|depends_on', 2)"),
+            LinkedFunction::Transitive {
+                rel_pattern: "blocked_by|depends_on".to_string(),
+                max_depth: 2,
+                target: None,
+            }
+        );
+        assert!(parse_mkql("SELECT * FROM project WHERE LINKED_DEPTH('a|b', 2)").is_err());
+        assert!(parse_mkql("SELECT * FROM project WHERE LINKED_DEPTH('a', -1)").is_err());
+        assert!(parse_mkql("SELECT * FROM project WHERE LINKED_DEPTH('a')").is_err());
+    }
+
     #[test]
     fn parse_linked_reverse() {
         let q = parse_mkql("SELECT * FROM project WHERE LINKED(REVERSE, 'owner')").unwrap();
@@ -714,12 +1522,28 @@ mod tests {
         let q = parse_mkql("SELECT * FROM project ORDER BY observed_at DESC, title ASC").unwrap();
         let order = q.order_by.unwrap();
         assert_eq!(order.len(), 2);
-        assert_eq!(order[0].field, "observed_at");
+        assert_eq!(order[0].key, OrderKey::Field("observed_at".to_string()));
         assert_eq!(order[0].direction, SortDirection::Desc);
-        assert_eq!(order[1].field, "title");
+        assert_eq!(order[1].key, OrderKey::Field("title".to_string()));
         assert_eq!(order[1].direction, SortDirection::Asc);
     }
 
+    #[test]
+    fn parse_order_by_similarity() {
+        let q = parse_mkql(
+            "SELECT * FROM project ORDER BY SIMILARITY('machine learning') DESC, title LIMIT 5",
+        )
+        .unwrap();
+        let order = q.order_by.unwrap();
+        assert_eq!(
+            order[0].key,
+            OrderKey::Similarity("machine learning".to_string())
+        );
+        assert_eq!(order[0].direction, SortDirection::Desc);
+        assert_eq!(order[1].key, OrderKey::Field("title".to_string()));
+        assert_eq!(q.limit, Some(5));
+    }
+
     #[test]
     fn parse_limit_and_offset() {
         let q = parse_mkql("SELECT * FROM project LIMIT 10 OFFSET 20").unwrap();
@@ -727,6 +1551,159 @@ mod tests {
         assert_eq!(q.offset, Some(20));
     }
 
+    // === GROUP BY / HAVING ===
+
+    #[test]
+    fn parse_group_by_with_aggregates_and_having() {
+        let q = parse_mkql(
+            "SELECT doc_type, COUNT(*) AS n, avg(confidence) FROM project \
+             GROUP BY doc_type HAVING COUNT(*) > 1 AND NOT AVG(confidence) < 0.5 \
+             ORDER BY n DESC",
+        )
+        .unwrap();
+        assert_eq!(q.group_by, vec!["doc_type".to_string()]);
+        match &q.select {
+            SelectClause::Fields(fields) => {
+                assert_eq!(fields[0].aggregate, None);
+                assert_eq!(fields[1].name, "*");
+                assert_eq!(fields[1].aggregate, Some(AggregateFunction::Count));
+                assert_eq!(fields[1].alias, Some("n".to_string()));
+                assert_eq!(fields[2].name, "confidence");
+                assert_eq!(fields[2].aggregate, Some(AggregateFunction::Avg));
+            }
+            _ => panic!("expected Fields"),
+        }
+        assert_eq!(
+            q.having,
+            Some(HavingClause::And(
+                Box::new(HavingClause::Predicate(HavingPredicate {
+                    function: AggregateFunction::Count,
+                    field: "*".to_string(),
+                    op: CompOp::Gt,
+                    value: Value::Integer(1),
+                })),
+                Box::new(HavingClause::Not(Box::new(HavingClause::Predicate(
+                    HavingPredicate {
+                        function: AggregateFunction::Avg,
+                        field: "confidence".to_string(),
+                        op: CompOp::Lt,
+                        value: Value::Float(0.5),
+                    }
+                )))),
+            ))
+        );
+        assert!(q.order_by.is_some());
+    }
+
+    #[test]
+    fn parse_having_requires_group_by() {
+        assert!(parse_mkql("SELECT COUNT(*) FROM project HAVING COUNT(*) > 1").is_err());
+        assert!(parse_mkql("SELECT * FROM project GROUP BY status HAVING status = 'a'").is_err());
+        assert!(parse_mkql("SELECT SUM(*) FROM project GROUP BY status").is_err());
+    }
+
+    // === PIVOT ===
+
+    #[test]
+    fn parse_pivot_without_from() {
+        let q = parse_mkql("SELECT PIVOT doc_type BY month(observed_at) COUNT(*)").unwrap();
+        assert_eq!(q.from, "");
+        assert_eq!(
+            q.pivot,
+            Some(PivotClause {
+                row_field: PivotKey {
+                    field: "doc_type".to_string(),
+                    bucket: None,
+                },
+                col_field: PivotKey {
+                    field: "observed_at".to_string(),
+                    bucket: Some(DateBucket::Month),
+                },
+                agg_func: AggregateFunction::Count,
+                agg_field: "*".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_pivot_with_from_and_where() {
+        let q = parse_mkql(
+            "select pivot status by YEAR(observed_at) avg(confidence) from project where CURRENT()",
+        )
+        .unwrap();
+        assert_eq!(q.from, "project");
+        assert!(q.where_clause.is_some());
+        let pivot = q.pivot.unwrap();
+        assert_eq!(pivot.col_field.bucket, Some(DateBucket::Year));
+        assert_eq!(pivot.agg_func, AggregateFunction::Avg);
+        assert_eq!(pivot.agg_field, "confidence");
+
+        // A field named `pivot` is still an ordinary SELECT
+        let q = parse_mkql("SELECT pivot FROM project").unwrap();
+        assert!(q.pivot.is_none());
+        assert!(parse_mkql("SELECT PIVOT status BY source FROM project").is_err());
+    }
+
+    #[test]
+    fn parse_set_operations_left_to_right() {
+        let q = parse_mkql(
+            "SELECT * FROM project WHERE CURRENT() \
+             UNION SELECT * FROM decision WHERE CURRENT() \
+             except SELECT * FROM decision WHERE superseded_by != ''",
+        )
+        .unwrap();
+        assert_eq!(q.from, "project");
+        assert!(q.where_clause.is_some());
+        assert_eq!(q.set_ops.len(), 2);
+        assert_eq!(q.set_ops[0].op, SetOp::Union);
+        assert_eq!(q.set_ops[0].query.from, "decision");
+        assert_eq!(q.set_ops[1].op, SetOp::Except);
+        assert!(q.set_ops[1].query.set_ops.is_empty());
+    }
+
+    #[test]
+    fn parse_set_operation_after_limit() {
+        let q = parse_mkql(
+            "SELECT id FROM project ORDER BY title LIMIT 3 INTERSECT SELECT id FROM project",
+        )
+        .unwrap();
+        assert_eq!(q.limit, Some(3));
+        assert_eq!(q.set_ops[0].op, SetOp::Intersect);
+        assert!(parse_mkql("SELECT * FROM project UNION").is_err());
+    }
+
+    #[test]
+    fn parse_with_clause() {
+        let q = parse_mkql(
+            "WITH active AS (SELECT * FROM project WHERE status = 'active') \
+             SELECT * FROM active WHERE CURRENT()",
+        )
+        .unwrap();
+        assert_eq!(q.from, "active");
+        assert_eq!(q.ctes.len(), 1);
+        assert_eq!(q.ctes[0].name, "active");
+        assert_eq!(q.ctes[0].query.from, "project");
+        assert!(q.ctes[0].query.ctes.is_empty());
+    }
+
+    #[test]
+    fn parse_chained_ctes_with_set_ops() {
+        let q = parse_mkql(
+            "with a as (SELECT * FROM project UNION SELECT * FROM decision), \
+                  b AS (SELECT * FROM a WHERE CURRENT()) \
+             SELECT id FROM b EXCEPT SELECT id FROM a WHERE confidence < 0.5",
+        )
+        .unwrap();
+        let names: Vec<&str> = q.ctes.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["a", "b"]);
+        assert_eq!(q.ctes[0].query.set_ops[0].op, SetOp::Union);
+        assert_eq!(q.ctes[1].query.from, "a");
+        assert_eq!(q.set_ops[0].query.from, "a");
+
+        assert!(parse_mkql("WITH a AS SELECT * FROM project SELECT * FROM a").is_err());
+        assert!(parse_mkql("WITH a AS (SELECT * FROM project)").is_err());
+    }
+
     // === T-200.6: Parser error messages ===
 
     #[test]
@@ -805,6 +1782,87 @@ mod tests {
         assert_eq!(q.from, "project");
         assert!(q.where_clause.is_some());
     }
+
+    fn normalize(input: &str) -> String {
+        parse_mkql(input).unwrap().to_string()
+    }
+
+    #[test]
+    fn display_normalizes_spacing_and_case() {
+        assert_eq!(
+            normalize("SELECT  *  from  project  where  status='active'"),
+            "SELECT * FROM project WHERE status = 'active'"
+        );
+        assert_eq!(
+            normalize("select title,count(*) as n from project group by title having count(*)>1 order by n desc, title asc limit 5 offset 10"),
+            "SELECT title, COUNT(*) AS n FROM project GROUP BY title HAVING COUNT(*) > 1 ORDER BY n DESC, title LIMIT 5 OFFSET 10"
+        );
+    }
+
+    #[test]
+    fn display_keeps_only_needed_parens() {
+        assert_eq!(
+            normalize("SELECT * FROM t WHERE ((a = 1 AND b = 2)) OR (c = 3)"),
+            "SELECT * FROM t WHERE a = 1 AND b = 2 OR c = 3"
+        );
+        assert_eq!(
+            normalize("SELECT * FROM t WHERE a = 1 AND (b = 2 OR c = 3)"),
+            "SELECT * FROM t WHERE a = 1 AND (b = 2 OR c = 3)"
+        );
+        assert_eq!(
+            normalize("SELECT * FROM t WHERE NOT (a = 1 OR b = 2) AND NOT (c = 3)"),
+            "SELECT * FROM t WHERE NOT (a = 1 OR b = 2) AND NOT c = 3"
+        );
+
+        // Stripping parens must not change how the clause groups
+        for input in [
+            "SELECT * FROM t WHERE (a = 1 OR b = 2) AND (c = 3 OR d = 4)",
+            "SELECT * FROM t WHERE a = 1 OR (b = 2 AND NOT (c = 3 AND d = 4))",
+            "SELECT n FROM t GROUP BY n HAVING (COUNT(*) > 1 OR MAX(x) < 2) AND SUM(x) > 0",
+        ] {
+            let parsed = parse_mkql(input).unwrap();
+            assert_eq!(parse_mkql(&parsed.to_string()).unwrap(), parsed, "{input}");
+        }
+    }
+
+    #[test]
+    fn display_sorts_in_and_tag_lists() {
+        assert_eq!(
+            normalize("SELECT * FROM t WHERE status IN ('paused', 'active') AND tags in ('rust', 'ai')"),
+            "SELECT * FROM t WHERE status IN ('active', 'paused') AND TAGS CONTAINS ANY ('ai', 'rust')"
+        );
+        assert_eq!(
+            normalize("SELECT * FROM t WHERE status IN ('b','a')"),
+            normalize("SELECT * FROM t WHERE status IN ('a', 'b')")
+        );
+    }
+
+    #[test]
+    fn display_roundtrips_every_construct() {
+        for input in [
+            "WITH active AS (SELECT * FROM project WHERE status = 'active' UNION SELECT * FROM task) SELECT id FROM active",
+            "SELECT PIVOT doc_type BY MONTH(observed_at) COUNT(*) FROM signal WHERE CURRENT()",
+            "SELECT PIVOT source BY YEAR(observed_at) AVG(confidence)",
+            "SELECT * FROM t WHERE x = 1.0 AND y = -2 AND z = TRUE AND w = NULL AND v = :param",
+            "SELECT * FROM t WHERE observed_at > NOW() - 7d + 1mo",
+            "SELECT * FROM t WHERE title LIKE 'S%' AND title REGEX '^S' AND BODY CONTAINS 'x'",
+            "SELECT * FROM t WHERE TITLE MATCH_PHRASE 'a b' OR BODY MATCH_PHRASE 'c d'",
+            "SELECT * FROM t WHERE TAGS CONTAINS 'rust' AND TAGS CONTAINS ALL ('a', 'b') AND TAGS IS EMPTY",
+            "SELECT * FROM t WHERE FRESH('7d') AND STALE('30d') AND CHANGED('1d') AND EXPIRED() AND LATEST()",
+            "SELECT * FROM t WHERE AS_OF('2025-01-01') AND AS_OF_RANGE('2025-01-01', '2025-02-01')",
+            "SELECT * FROM t WHERE OVERLAPS('2025-01-01', '2025-02-01') AND OVERLAPS_NOW() AND EFF_CONFIDENCE(>= 1.0)",
+            "SELECT * FROM t WHERE LINKED('owner') AND LINKED('owner', 'p-1') AND LINKED(REVERSE, 'owner', 'p-2')",
+            "SELECT * FROM t WHERE LINKED_DEPTH('blocked_by', 3) AND LINKED_DEPTH_ANY('a|b', 2, 'p-1')",
+            "SELECT * FROM t WHERE LINK_COUNT() > 5 AND REVERSE_LINK_COUNT('owner') >= 2",
+            "SELECT * FROM t WHERE ANNOTATED('reviewed') AND NOT ANNOTATED('needs-update')",
+            "SELECT * FROM t WHERE NEAR('query', 0.8) ORDER BY SIMILARITY('query') DESC",
+            "SELECT * FROM a INTERSECT SELECT * FROM b EXCEPT SELECT * FROM c",
+        ] {
+            let parsed = parse_mkql(input).unwrap();
+            assert_eq!(parsed.to_string(), input);
+            assert_eq!(parse_mkql(&parsed.to_string()).unwrap(), parsed);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -845,6 +1903,12 @@ mod proptests {
             prop_assert!(result.is_ok(), "Valid query should parse: {query}, error: {:?}", result.err());
         }
 
+        #[test]
+        fn normalized_form_is_stable(query in valid_query_strategy()) {
+            let canonical = parse_mkql(&query).unwrap().to_string();
+            prop_assert_eq!(parse_mkql(&canonical).unwrap().to_string(), canonical);
+        }
+
         #[test]
         fn random_strings_dont_panic(s in "\\PC{0,100}") {
             // Should never panic — may return Ok or Err but must not crash
@@ -6,24 +6,78 @@
 //! All functions take vault_path as first argument (path-based API,
 //! no persistent handles across FFI boundary).
 
-use std::path::Path;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
 
-use pyo3::exceptions::PyValueError;
+use pyo3::exceptions::{PyImportError, PyRuntimeError, PyValueError};
 use pyo3::prelude::*;
-use pyo3::types::PyDict;
+use pyo3::types::{PyBool, PyDict, PyList};
 
 use chrono::{DateTime, Utc};
 
-use mkb_core::document::Document;
-use mkb_core::temporal::{DecayProfile, RawTemporalInput, TemporalGate, TemporalPrecision};
+use mkb_core::document::{Document, ProvenanceRecord, ProvenanceType};
+use mkb_core::error::MkbError;
+use mkb_core::link::Link;
+use mkb_core::temporal::{
+    self, DecayProfile, ParsedDateTime, RawTemporalInput, TemporalGate, TemporalPrecision,
+};
 use mkb_index::IndexManager;
+use mkb_query::{CompiledQuery, QueryStream};
 use mkb_vault::Vault;
 
+// === Exceptions ===
+
+pyo3::create_exception!(_mkb_core, DocumentNotFoundError, PyValueError);
+pyo3::create_exception!(_mkb_core, DuplicateDocumentError, PyValueError);
+pyo3::create_exception!(_mkb_core, SchemaViolationError, PyValueError);
+pyo3::create_exception!(_mkb_core, IndexCorruptedError, PyRuntimeError);
+
+/// Raise the exception matching a structured [`MkbError`] variant, or a
+/// plain `ValueError` prefixed with `context` for everything else.
+fn mkb_err(context: &str, e: MkbError) -> PyErr {
+    let message = format!("{context}: {e}");
+    match e {
+        MkbError::DocumentNotFound { .. } => DocumentNotFoundError::new_err(message),
+        MkbError::DuplicateDocument { .. } => DuplicateDocumentError::new_err(message),
+        MkbError::SchemaViolation { .. } => SchemaViolationError::new_err(message),
+        MkbError::IndexCorrupted { .. } => IndexCorruptedError::new_err(message),
+        _ => PyValueError::new_err(message),
+    }
+}
+
 // === Helpers ===
 
 fn open_index(vault_path: &Path) -> PyResult<IndexManager> {
     let index_path = vault_path.join(".mkb").join("index").join("mkb.db");
-    IndexManager::open(&index_path).map_err(|e| PyValueError::new_err(format!("Index error: {e}")))
+    IndexManager::open(&index_path).map_err(|e| mkb_err("Index error", e))
+}
+
+/// Compile options from the vault's `.mkb/query.yaml`; `strict` also checks
+/// fields against the vault's schemas.
+fn compile_options(vault_path: &str, strict: bool) -> PyResult<mkb_query::CompileOptions> {
+    let vault = Vault::open(Path::new(vault_path))
+        .map_err(|e| PyValueError::new_err(format!("Vault error: {e}")))?;
+    let settings = vault
+        .load_query_settings()
+        .map_err(|e| PyValueError::new_err(format!("Query settings error: {e}")))?;
+    let mut options = mkb_query::CompileOptions::from(settings);
+    if strict {
+        let registry = vault
+            .schema_registry()
+            .map_err(|e| PyValueError::new_err(format!("Schema error: {e}")))?;
+        options.validate_fields = true;
+        options.schemas = registry.iter().cloned().collect();
+    }
+    Ok(options)
+}
+
+/// Parse and compile `mkql` with the vault's [`compile_options`].
+fn compile_mkql(vault_path: &str, mkql: &str, strict: bool) -> PyResult<CompiledQuery> {
+    let ast = mkb_parser::parse_mkql(mkql)
+        .map_err(|e| PyValueError::new_err(format!("Parse error: {e}")))?;
+    mkb_query::compile_with_options(&ast, compile_options(vault_path, strict)?)
+        .map_err(|e| PyValueError::new_err(format!("Compile error: {e}")))
 }
 
 fn parse_precision(s: &str) -> PyResult<TemporalPrecision> {
@@ -39,31 +93,104 @@ fn parse_precision(s: &str) -> PyResult<TemporalPrecision> {
     }
 }
 
-fn parse_datetime(s: &str) -> PyResult<DateTime<Utc>> {
-    s.parse::<DateTime<Utc>>()
-        .map_err(|e| PyValueError::new_err(format!("Invalid datetime '{s}': {e}")))
+fn parse_datetime(s: &str) -> PyResult<ParsedDateTime> {
+    temporal::parse_datetime(s).map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// Convert `doc` to the dict shared with `mkb export`, computing
+/// `effective_confidence` under `vault`'s decay profile.
+fn doc_to_dict(py: Python<'_>, doc: &Document, vault: &Vault) -> PyResult<Py<PyDict>> {
+    let profile = vault
+        .load_decay_profile()
+        .map_err(|e| mkb_err("Decay profile error", e))?;
+    let record = mkb_vault::document_record(doc, &profile, Utc::now(), true);
+    let dict = PyDict::new(py);
+    for (key, value) in record.as_object().into_iter().flatten() {
+        dict.set_item(key, json_to_py(py, value)?)?;
+    }
+    Ok(dict.into())
 }
 
-fn doc_to_dict(py: Python<'_>, doc: &Document) -> PyResult<Py<PyDict>> {
+fn indexed_to_dict(py: Python<'_>, doc: &mkb_index::IndexedDocument) -> PyResult<Py<PyDict>> {
     let dict = PyDict::new(py);
     dict.set_item("id", &doc.id)?;
     dict.set_item("type", &doc.doc_type)?;
     dict.set_item("title", &doc.title)?;
-    dict.set_item("body", &doc.body)?;
-    dict.set_item("observed_at", doc.temporal.observed_at.to_rfc3339())?;
-    dict.set_item("valid_until", doc.temporal.valid_until.to_rfc3339())?;
-    dict.set_item(
-        "temporal_precision",
-        format!("{:?}", doc.temporal.temporal_precision).to_lowercase(),
-    )?;
+    dict.set_item("observed_at", &doc.observed_at)?;
+    dict.set_item("valid_until", &doc.valid_until)?;
     dict.set_item("confidence", doc.confidence)?;
-    dict.set_item("created_at", doc.created_at.to_rfc3339())?;
-    dict.set_item("modified_at", doc.modified_at.to_rfc3339())?;
     dict.set_item("tags", &doc.tags)?;
-    dict.set_item("source", &doc.source)?;
+    dict.set_item("modified_at", &doc.modified_at)?;
+    dict.set_item("age_label", &doc.age_label)?;
+    Ok(dict.into())
+}
+
+/// Map each document type to its most recently observed document.
+fn newest_per_type_dict(py: Python<'_>, index: &IndexManager) -> PyResult<Py<PyDict>> {
+    let newest = index
+        .query_newest_per_type()
+        .map_err(|e| PyValueError::new_err(format!("Query failed: {e}")))?;
+    let dict = PyDict::new(py);
+    for (doc_type, doc) in &newest {
+        dict.set_item(doc_type, indexed_to_dict(py, doc)?)?;
+    }
     Ok(dict.into())
 }
 
+/// Build a document through the temporal gate from Python-side arguments,
+/// sanitized with [`Document::sanitize`].
+#[allow(clippy::too_many_arguments)]
+fn build_document(
+    id: String,
+    doc_type: &str,
+    title: &str,
+    observed_at: &str,
+    body: &str,
+    tags: Option<Vec<String>>,
+    precision: &str,
+    valid_until: Option<&str>,
+    confidence: Option<f64>,
+) -> PyResult<Document> {
+    let observed = parse_datetime(observed_at)?;
+    let valid = valid_until.map(parse_datetime).transpose()?;
+    let prec = observed.coerce_precision(parse_precision(precision)?);
+    let profile = DecayProfile::default_profile();
+
+    let input = RawTemporalInput {
+        observed_at: Some(observed.utc),
+        valid_until: valid.map(|v| v.utc),
+        temporal_precision: Some(prec),
+        occurred_at: None,
+        occurred_until: None,
+    };
+
+    let mut doc = Document::new(id, doc_type.to_string(), title.to_string(), input, &profile)
+        .map_err(|e| PyValueError::new_err(format!("Temporal gate rejected: {e}")))?;
+
+    doc.body = body.to_string();
+    if let Some(t) = tags {
+        doc.tags = t;
+    }
+    if let Some(confidence) = confidence {
+        doc.set_confidence(check_confidence(confidence)?);
+    }
+    doc.provenance = Some(ProvenanceRecord::new(
+        ProvenanceType::ApiIngestion,
+        "python",
+    ));
+    Ok(doc.sanitize(Document::MAX_BODY_BYTES, Document::MAX_FIELD_BYTES))
+}
+
+fn check_confidence(confidence: f64) -> PyResult<f64> {
+    if (0.0..=1.0).contains(&confidence) {
+        Ok(confidence)
+    } else {
+        Err(PyValueError::new_err(format!(
+            "Confidence must be between 0.0 and 1.0, got {confidence}"
+        )))
+    }
+}
+
 // === Vault Operations (T-400.1) ===
 
 /// Initialize a new MKB vault at the given path.
@@ -84,8 +211,11 @@ fn init_vault(path: &str) -> PyResult<String> {
 }
 
 /// Create a new document in the vault.
+///
+/// Without `confidence`, the document's confidence is derived from
+/// `precision` (1.0 for `exact` down to 0.4 for `inferred`).
 #[pyfunction]
-#[pyo3(signature = (vault_path, doc_type, title, observed_at, body="", tags=None, precision="day", valid_until=None))]
+#[pyo3(signature = (vault_path, doc_type, title, observed_at, body="", tags=None, precision="day", valid_until=None, confidence=None))]
 #[allow(clippy::too_many_arguments)]
 fn create_document(
     py: Python<'_>,
@@ -97,43 +227,38 @@ fn create_document(
     tags: Option<Vec<String>>,
     precision: &str,
     valid_until: Option<&str>,
+    confidence: Option<f64>,
 ) -> PyResult<Py<PyDict>> {
     let vpath = Path::new(vault_path);
     let vault =
         Vault::open(vpath).map_err(|e| PyValueError::new_err(format!("Vault error: {e}")))?;
-    let index = open_index(vpath)?;
-
-    let observed = parse_datetime(observed_at)?;
-    let valid = valid_until.map(parse_datetime).transpose()?;
-    let prec = parse_precision(precision)?;
-    let profile = DecayProfile::default_profile();
+    let mut index = open_index(vpath)?;
 
     let counter = mkb_vault::next_counter(vpath, doc_type, &mkb_vault::slugify(title));
     let id = Document::generate_id(doc_type, title, counter);
-
-    let input = RawTemporalInput {
-        observed_at: Some(observed),
-        valid_until: valid,
-        temporal_precision: Some(prec),
-        occurred_at: None,
-    };
-
-    let mut doc = Document::new(id, doc_type.to_string(), title.to_string(), input, &profile)
-        .map_err(|e| PyValueError::new_err(format!("Temporal gate rejected: {e}")))?;
-
-    doc.body = body.to_string();
-    if let Some(t) = tags {
-        doc.tags = t;
-    }
+    let doc = build_document(
+        id,
+        doc_type,
+        title,
+        observed_at,
+        body,
+        tags,
+        precision,
+        valid_until,
+        confidence,
+    )?;
 
     let _path = vault
         .create(&doc)
-        .map_err(|e| PyValueError::new_err(format!("Create failed: {e}")))?;
+        .map_err(|e| mkb_err("Create failed", e))?;
     index
-        .index_document(&doc)
+        .transaction(|index| {
+            index.index_document(&doc)?;
+            index.store_links(&doc.id, &doc.links)
+        })
         .map_err(|e| PyValueError::new_err(format!("Index failed: {e}")))?;
 
-    doc_to_dict(py, &doc)
+    doc_to_dict(py, &doc, &vault)
 }
 
 /// Read a document from the vault.
@@ -150,9 +275,100 @@ fn read_document(
 
     let doc = vault
         .read(doc_type, id)
-        .map_err(|e| PyValueError::new_err(format!("Read failed: {e}")))?;
+        .map_err(|e| mkb_err("Read failed", e))?;
+
+    doc_to_dict(py, &doc, &vault)
+}
+
+/// Append an entry to a document's body and reindex it.
+#[pyfunction]
+#[pyo3(signature = (vault_path, doc_type, id, text, separator="\n\n", timestamp_header=true))]
+fn append_body(
+    py: Python<'_>,
+    vault_path: &str,
+    doc_type: &str,
+    id: &str,
+    text: &str,
+    separator: &str,
+    timestamp_header: bool,
+) -> PyResult<Py<PyDict>> {
+    let vpath = Path::new(vault_path);
+    let vault = Vault::open(vpath)
+        .map_err(|e| PyValueError::new_err(format!("Vault error: {e}")))?
+        .with_append_style(mkb_vault::AppendStyle {
+            separator: separator.to_string(),
+            timestamp_header,
+        });
+    let index = open_index(vpath)?;
+
+    let doc = vault
+        .append_body(doc_type, id, text)
+        .map_err(|e| mkb_err("Append failed", e))?;
+    index
+        .index_document(&doc)
+        .map_err(|e| PyValueError::new_err(format!("Index failed: {e}")))?;
 
-    doc_to_dict(py, &doc)
+    doc_to_dict(py, &doc, &vault)
+}
+
+/// Update an existing document and reindex it.
+///
+/// Only the arguments that are not `None` are applied; `fields` entries are
+/// merged into the existing fields. The ID, type and creation time never
+/// change.
+#[pyfunction]
+#[pyo3(signature = (vault_path, doc_type, id, title=None, body=None, tags=None, confidence=None, valid_until=None, fields=None))]
+#[allow(clippy::too_many_arguments)]
+fn update_document(
+    py: Python<'_>,
+    vault_path: &str,
+    doc_type: &str,
+    id: &str,
+    title: Option<&str>,
+    body: Option<&str>,
+    tags: Option<Vec<String>>,
+    confidence: Option<f64>,
+    valid_until: Option<&str>,
+    fields: Option<HashMap<String, Bound<'_, PyAny>>>,
+) -> PyResult<Py<PyDict>> {
+    let vpath = Path::new(vault_path);
+    let vault =
+        Vault::open(vpath).map_err(|e| PyValueError::new_err(format!("Vault error: {e}")))?;
+    let index = open_index(vpath)?;
+
+    let mut doc = vault
+        .read(doc_type, id)
+        .map_err(|e| mkb_err("Read failed", e))?;
+
+    if let Some(title) = title {
+        doc.title = title.to_string();
+    }
+    if let Some(body) = body {
+        doc.body = body.to_string();
+    }
+    if let Some(tags) = tags {
+        doc.tags = tags;
+    }
+    if let Some(confidence) = confidence {
+        doc.set_confidence(check_confidence(confidence)?);
+    }
+    if let Some(valid_until) = valid_until {
+        doc.temporal.valid_until = parse_datetime(valid_until)?.utc;
+    }
+    if let Some(fields) = fields {
+        for (key, value) in fields {
+            doc.fields.insert(key, scalar_to_json(&value)?);
+        }
+    }
+
+    vault
+        .update(&mut doc)
+        .map_err(|e| mkb_err("Update failed", e))?;
+    index
+        .index_document(&doc)
+        .map_err(|e| PyValueError::new_err(format!("Index failed: {e}")))?;
+
+    doc_to_dict(py, &doc, &vault)
 }
 
 /// Delete a document (soft delete to archive).
@@ -165,7 +381,7 @@ fn delete_document(vault_path: &str, doc_type: &str, id: &str) -> PyResult<Strin
 
     let archive_path = vault
         .delete(doc_type, id)
-        .map_err(|e| PyValueError::new_err(format!("Delete failed: {e}")))?;
+        .map_err(|e| mkb_err("Delete failed", e))?;
     index
         .remove_document(id)
         .map_err(|e| PyValueError::new_err(format!("Index removal failed: {e}")))?;
@@ -176,13 +392,25 @@ fn delete_document(vault_path: &str, doc_type: &str, id: &str) -> PyResult<Strin
 // === Index Operations (T-400.2) ===
 
 /// Search documents using full-text search.
+///
+/// With `include_snippets`, each hit carries a `snippet` of body text around
+/// the match, with matched terms wrapped in `<b>`/`</b>`.
 #[pyfunction]
-fn search_fts(py: Python<'_>, vault_path: &str, query: &str) -> PyResult<Vec<Py<PyDict>>> {
+#[pyo3(signature = (vault_path, query, include_snippets=false))]
+fn search_fts(
+    py: Python<'_>,
+    vault_path: &str,
+    query: &str,
+    include_snippets: bool,
+) -> PyResult<Vec<Py<PyDict>>> {
     let index = open_index(Path::new(vault_path))?;
 
-    let results = index
-        .search_fts(query)
-        .map_err(|e| PyValueError::new_err(format!("Search failed: {e}")))?;
+    let results = if include_snippets {
+        index.search_fts_with_snippets(query)
+    } else {
+        index.search_fts(query)
+    }
+    .map_err(|e| PyValueError::new_err(format!("Search failed: {e}")))?;
 
     results
         .iter()
@@ -192,36 +420,487 @@ fn search_fts(py: Python<'_>, vault_path: &str, query: &str) -> PyResult<Vec<Py<
             dict.set_item("title", &r.title)?;
             dict.set_item("type", &r.doc_type)?;
             dict.set_item("rank", r.rank)?;
+            dict.set_item("tags", &r.tags)?;
+            if include_snippets {
+                dict.set_item("snippet", &r.snippet)?;
+            }
             Ok(dict.into())
         })
         .collect()
 }
 
-/// Execute an MKQL query and return results as JSON string.
+/// Execute an MKQL query and return formatted results (`json`, `yaml`,
+/// `table`, `markdown` or `context`).
+///
+/// `template_str` replaces the default `context` template; its `{{field}}`
+/// placeholders are filled from each row. With `strict=True`, WHERE fields
+/// missing from the queried type's schema raise instead of matching nothing.
+#[pyfunction]
+#[pyo3(signature = (vault_path, mkql, format="json", template_str=None, strict=false))]
+fn query_mkql(
+    vault_path: &str,
+    mkql: &str,
+    format: &str,
+    template_str: Option<&str>,
+    strict: bool,
+) -> PyResult<String> {
+    let output_format = match (parse_format(format)?, template_str) {
+        (mkb_query::OutputFormat::Context { .. }, Some(template)) => {
+            mkb_query::OutputFormat::Context {
+                template: Some(template.to_string()),
+            }
+        }
+        (_, Some(_)) => {
+            return Err(PyValueError::new_err(
+                "template_str requires format=\"context\"",
+            ))
+        }
+        (output_format, None) => output_format,
+    };
+    let index = open_index(Path::new(vault_path))?;
+
+    let compiled = compile_mkql(vault_path, mkql, strict)?;
+    let start = std::time::Instant::now();
+    let result = mkb_query::execute(&index, &compiled)
+        .map_err(|e| PyValueError::new_err(format!("Execution error: {e}")))?;
+    index
+        .record_query(mkql, start.elapsed(), result.total)
+        .map_err(|e| PyRuntimeError::new_err(format!("Failed to record query: {e}")))?;
+
+    Ok(mkb_query::format_results(&result, output_format))
+}
+
+/// Execute an MKQL query with `:name` parameters bound from `params`.
 #[pyfunction]
-#[pyo3(signature = (vault_path, mkql, format="json"))]
-fn query_mkql(vault_path: &str, mkql: &str, format: &str) -> PyResult<String> {
+#[pyo3(signature = (vault_path, mkql, params, format="json"))]
+fn query_mkql_params(
+    vault_path: &str,
+    mkql: &str,
+    params: &Bound<'_, PyDict>,
+    format: &str,
+) -> PyResult<String> {
     let index = open_index(Path::new(vault_path))?;
 
+    let compiled = compile_mkql(vault_path, mkql, false)?;
+
+    let mut values: Vec<(String, serde_json::Value)> = Vec::with_capacity(params.len());
+    for (key, value) in params.iter() {
+        values.push((key.extract()?, scalar_to_json(&value)?));
+    }
+    let bound: HashMap<&str, serde_json::Value> = values
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.clone()))
+        .collect();
+
+    let result = mkb_query::execute_with_params(&index, &compiled, &bound)
+        .map_err(|e| PyValueError::new_err(format!("Execution error: {e}")))?;
+
+    Ok(mkb_query::format_results(&result, parse_format(format)?))
+}
+
+/// Execute an MKQL query and post-process its rows with `transforms`,
+/// applied in order. Each transform is a dict with a `type` of `sort`
+/// (`field`, optional `descending`), `dedup`, `confidence_filter` (`min`),
+/// `limit` (`limit`) or `project` (`fields`).
+#[pyfunction]
+#[pyo3(signature = (vault_path, mkql, transforms, format="json"))]
+fn query_mkql_with_transforms(
+    vault_path: &str,
+    mkql: &str,
+    transforms: Vec<Bound<'_, PyDict>>,
+    format: &str,
+) -> PyResult<String> {
+    let index = open_index(Path::new(vault_path))?;
+
+    let mut compiled = compile_mkql(vault_path, mkql, false)?;
+    for transform in &transforms {
+        let mut spec = serde_json::Map::new();
+        for (key, value) in transform.iter() {
+            let value = match value.extract::<Vec<String>>() {
+                Ok(items) => serde_json::json!(items),
+                Err(_) => scalar_to_json(&value)?,
+            };
+            spec.insert(key.extract()?, value);
+        }
+        let spec = mkb_query::TransformSpec::from_json(&serde_json::Value::Object(spec))
+            .map_err(PyValueError::new_err)?;
+        compiled.transformers.push(spec.build());
+    }
+
+    let result = mkb_query::execute(&index, &compiled)
+        .map_err(|e| PyValueError::new_err(format!("Execution error: {e}")))?;
+
+    Ok(mkb_query::format_results(&result, parse_format(format)?))
+}
+
+/// Turn recording of `query_mkql` calls into the query history on or off.
+#[pyfunction]
+fn set_query_history(vault_path: &str, enabled: bool) -> PyResult<()> {
+    open_index(Path::new(vault_path))?
+        .set_query_history_enabled(enabled)
+        .map_err(|e| PyRuntimeError::new_err(format!("Failed to update settings: {e}")))
+}
+
+/// The most recent recorded queries, newest first, as dicts with keys
+/// id, mkql, executed_at, duration_ms and result_count.
+#[pyfunction]
+#[pyo3(signature = (vault_path, limit=100))]
+fn get_query_history(py: Python<'_>, vault_path: &str, limit: usize) -> PyResult<Vec<Py<PyDict>>> {
+    let index = open_index(Path::new(vault_path))?;
+    let rows = index
+        .query_history(limit)
+        .map_err(|e| PyValueError::new_err(format!("Query failed: {e}")))?;
+    rows.into_iter()
+        .map(|row| {
+            let dict = PyDict::new(py);
+            dict.set_item("id", row.id)?;
+            dict.set_item("mkql", row.mkql)?;
+            dict.set_item("executed_at", row.executed_at)?;
+            dict.set_item("duration_ms", row.duration_ms)?;
+            dict.set_item("result_count", row.result_count)?;
+            Ok(dict.into())
+        })
+        .collect()
+}
+
+/// Attach an annotation (e.g. `reviewed`) to an indexed document and return
+/// its ID. Annotations live only in the index.
+#[pyfunction]
+#[pyo3(signature = (vault_path, doc_id, annotation_type, content="", created_by="python"))]
+fn add_annotation(
+    vault_path: &str,
+    doc_id: &str,
+    annotation_type: &str,
+    content: &str,
+    created_by: &str,
+) -> PyResult<i64> {
+    open_index(Path::new(vault_path))?
+        .add_annotation(doc_id, annotation_type, content, created_by)
+        .map_err(|e| mkb_err("Annotation failed", e))
+}
+
+/// The annotations on a document, oldest first, as dicts with keys id,
+/// doc_id, annotation_type, content, created_at and created_by.
+#[pyfunction]
+fn get_annotations(py: Python<'_>, vault_path: &str, doc_id: &str) -> PyResult<Vec<Py<PyDict>>> {
+    let index = open_index(Path::new(vault_path))?;
+    let annotations = index
+        .get_annotations(doc_id)
+        .map_err(|e| PyValueError::new_err(format!("Query failed: {e}")))?;
+    annotations
+        .into_iter()
+        .map(|a| {
+            let dict = PyDict::new(py);
+            dict.set_item("id", a.id)?;
+            dict.set_item("doc_id", a.doc_id)?;
+            dict.set_item("annotation_type", a.annotation_type)?;
+            dict.set_item("content", a.content)?;
+            dict.set_item("created_at", a.created_at)?;
+            dict.set_item("created_by", a.created_by)?;
+            Ok(dict.into())
+        })
+        .collect()
+}
+
+self_cell::self_cell!(
+    struct OpenQuery {
+        owner: IndexManager,
+
+        #[covariant]
+        dependent: QueryStream,
+    }
+);
+
+/// Iterator over MKQL result rows, read from the index one at a time.
+///
+/// Holds the index open until exhausted or garbage collected.
+#[pyclass(unsendable)]
+struct MkqlStream {
+    query: OpenQuery,
+}
+
+#[pymethods]
+impl MkqlStream {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<Py<PyDict>>> {
+        let Some(row) = self.query.with_dependent_mut(|_, stream| stream.next()) else {
+            return Ok(None);
+        };
+        let row = row.map_err(|e| PyValueError::new_err(format!("Execution error: {e}")))?;
+        let dict = PyDict::new(py);
+        for (key, value) in &row.fields {
+            dict.set_item(key, json_to_py(py, value)?)?;
+        }
+        Ok(Some(dict.into()))
+    }
+}
+
+/// Execute an MKQL query, returning an iterator that yields one dict per row.
+#[pyfunction]
+fn stream_mkql(vault_path: &str, mkql: &str) -> PyResult<MkqlStream> {
+    let index = open_index(Path::new(vault_path))?;
+
+    let compiled = compile_mkql(vault_path, mkql, false)?;
+    let query = OpenQuery::try_new(index, |index| {
+        mkb_query::execute_streaming(index, &compiled)
+    })
+    .map_err(|e| PyValueError::new_err(format!("Execution error: {e}")))?;
+
+    Ok(MkqlStream { query })
+}
+
+fn parse_format(format: &str) -> PyResult<mkb_query::OutputFormat> {
+    match format.to_lowercase().as_str() {
+        "json" => Ok(mkb_query::OutputFormat::Json),
+        "yaml" | "yml" => Ok(mkb_query::OutputFormat::Yaml),
+        "table" => Ok(mkb_query::OutputFormat::Table),
+        "markdown" | "md" => Ok(mkb_query::OutputFormat::Markdown),
+        "context" => Ok(mkb_query::OutputFormat::Context { template: None }),
+        "atom" => Ok(mkb_query::OutputFormat::Atom(mkb_query::FeedOpts::default())),
+        "pivot" => Ok(mkb_query::OutputFormat::PivotTable),
+        "csv" => Ok(mkb_query::OutputFormat::Csv(mkb_query::CsvOpts::default())),
+        "tsv" => Ok(mkb_query::OutputFormat::Csv(mkb_query::CsvOpts::tsv())),
+        other => Err(PyValueError::new_err(format!(
+            "Unknown format: {other}. Valid: json, yaml, table, markdown, context, atom, pivot, csv, tsv"
+        ))),
+    }
+}
+
+/// Convert a Python scalar (None, bool, int, float, str) to JSON.
+fn scalar_to_json(value: &Bound<'_, PyAny>) -> PyResult<serde_json::Value> {
+    if value.is_none() {
+        Ok(serde_json::Value::Null)
+    } else if value.is_instance_of::<PyBool>() {
+        Ok(serde_json::Value::Bool(value.extract()?))
+    } else if let Ok(i) = value.extract::<i64>() {
+        Ok(serde_json::json!(i))
+    } else if let Ok(f) = value.extract::<f64>() {
+        Ok(serde_json::json!(f))
+    } else if let Ok(s) = value.extract::<String>() {
+        Ok(serde_json::Value::String(s))
+    } else {
+        Err(PyValueError::new_err(format!(
+            "Unsupported parameter value: {}",
+            value.repr()?
+        )))
+    }
+}
+
+/// Run an MKQL query and pack the results into an LLM context string of at
+/// most `max_tokens` tokens, counted with `tokenizer` (`cl100k_base` or
+/// `whitespace`).
+///
+/// Expired and superseded documents are left out unless
+/// `exclude_expired` / `exclude_superseded` are false; included expired
+/// documents are marked `[EXPIRED]`.
+#[pyfunction]
+#[pyo3(signature = (vault_path, mkql, max_tokens=4000, tokenizer="cl100k_base", exclude_expired=true, exclude_superseded=true, min_confidence=0.0))]
+fn assemble_context(
+    vault_path: &str,
+    mkql: &str,
+    max_tokens: usize,
+    tokenizer: &str,
+    exclude_expired: bool,
+    exclude_superseded: bool,
+    min_confidence: f64,
+) -> PyResult<String> {
+    let tokenizer = mkb_query::tokenizer_by_name(tokenizer).ok_or_else(|| {
+        PyValueError::new_err(format!(
+            "Unknown tokenizer: {tokenizer}. Use: cl100k_base, whitespace"
+        ))
+    })?;
+    let index = open_index(Path::new(vault_path))?;
+
+    let compiled = compile_mkql(vault_path, mkql, false)?;
+    let result = mkb_query::execute(&index, &compiled)
+        .map_err(|e| PyValueError::new_err(format!("Execution error: {e}")))?;
+
+    let opts = mkb_query::ContextOpts {
+        max_tokens,
+        exclude_expired,
+        exclude_superseded,
+        min_confidence,
+        ..mkb_query::ContextOpts::default()
+    };
+    Ok(mkb_query::ContextAssembler::assemble(
+        &result, &opts, tokenizer,
+    ))
+}
+
+/// Explain an MKQL query plan without executing it. Returns JSON.
+#[pyfunction]
+fn explain_mkql(vault_path: &str, mkql: &str) -> PyResult<String> {
+    let compiled = compile_mkql(vault_path, mkql, false)?;
+    let plan = mkb_query::explain(&compiled);
+
+    serde_json::to_string_pretty(&plan)
+        .map_err(|e| PyValueError::new_err(format!("Serialization error: {e}")))
+}
+
+/// Run an MKQL query and return the rows as a `pyarrow.Table`.
+///
+/// Document columns are typed: `confidence` is `float64`, timestamps are
+/// `timestamp[us, tz=UTC]`, `tags` is `list<string>`, and the remaining
+/// columns are `string`. Raises `ImportError` if pyarrow is missing.
+#[pyfunction]
+fn query_to_arrow(py: Python<'_>, vault_path: &str, mkql: &str) -> PyResult<Py<PyAny>> {
+    let pa = py.import("pyarrow").map_err(|_| {
+        PyImportError::new_err(
+            "query_to_arrow requires pyarrow; install it with `pip install 'mkb[arrow]'`",
+        )
+    })?;
+
+    let index = open_index(Path::new(vault_path))?;
     let ast = mkb_parser::parse_mkql(mkql)
         .map_err(|e| PyValueError::new_err(format!("Parse error: {e}")))?;
-    let compiled = mkb_query::compile(&ast)
+    let compiled = mkb_query::compile_with_options(&ast, compile_options(vault_path, false)?)
         .map_err(|e| PyValueError::new_err(format!("Compile error: {e}")))?;
     let result = mkb_query::execute(&index, &compiled)
         .map_err(|e| PyValueError::new_err(format!("Execution error: {e}")))?;
 
-    let output_format = match format.to_lowercase().as_str() {
-        "json" => mkb_query::OutputFormat::Json,
-        "table" => mkb_query::OutputFormat::Table,
-        "markdown" | "md" => mkb_query::OutputFormat::Markdown,
-        other => {
-            return Err(PyValueError::new_err(format!(
-                "Unknown format: {other}. Valid: json, table, markdown"
-            )))
+    // (output name, column type) in SELECT order; a PIVOT has the row key,
+    // then one column per column key
+    let columns: Vec<(String, ArrowColumn)> = if ast.pivot.is_some() {
+        let keys: std::collections::BTreeSet<&String> = result
+            .rows
+            .iter()
+            .flat_map(|row| row.fields.keys())
+            .filter(|k| *k != mkb_query::PIVOT_ROW_COLUMN)
+            .collect();
+        std::iter::once((mkb_query::PIVOT_ROW_COLUMN.to_string(), ArrowColumn::Utf8))
+            .chain(keys.into_iter().map(|k| (k.clone(), ArrowColumn::Inferred)))
+            .collect()
+    } else {
+        match &ast.select {
+            mkb_parser::ast::SelectClause::Star => mkb_query::DOCUMENT_FIELDS
+                .iter()
+                .map(|f| ((*f).to_string(), ArrowColumn::for_field(f)))
+                .collect(),
+            mkb_parser::ast::SelectClause::Fields(fields) => fields
+                .iter()
+                .map(|f| {
+                    let kind = match f.aggregate {
+                        Some(_) => ArrowColumn::Inferred,
+                        None => ArrowColumn::for_field(&f.name),
+                    };
+                    (mkb_query::output_column(f), kind)
+                })
+                .collect(),
         }
     };
 
-    Ok(mkb_query::format_results(&result, output_format))
+    let arrays = PyList::empty(py);
+    let names = PyList::empty(py);
+    for (name, kind) in &columns {
+        let values = PyList::empty(py);
+        for row in &result.rows {
+            values.append(kind.to_py(py, row.fields.get(name))?)?;
+        }
+
+        let kwargs = PyDict::new(py);
+        if let Some(arrow_type) = kind.arrow_type(&pa)? {
+            kwargs.set_item("type", arrow_type)?;
+        }
+        arrays.append(pa.call_method("array", (values,), Some(&kwargs))?)?;
+        names.append(name)?;
+    }
+
+    let table = pa
+        .getattr("Table")?
+        .call_method1("from_arrays", (arrays, names))?;
+    Ok(table.unbind())
+}
+
+/// Arrow column type of a document field in [`query_to_arrow`].
+enum ArrowColumn {
+    Utf8,
+    Float64,
+    Timestamp,
+    Tags,
+    /// Not a document column; let pyarrow infer the type.
+    Inferred,
+}
+
+impl ArrowColumn {
+    fn for_field(field: &str) -> Self {
+        match field {
+            "confidence" => Self::Float64,
+            "observed_at" | "valid_until" | "occurred_at" | "occurred_until" | "created_at"
+            | "modified_at" => Self::Timestamp,
+            "tags" => Self::Tags,
+            f if mkb_query::DOCUMENT_FIELDS.contains(&f) => Self::Utf8,
+            _ => Self::Inferred,
+        }
+    }
+
+    fn arrow_type<'py>(&self, pa: &Bound<'py, PyModule>) -> PyResult<Option<Bound<'py, PyAny>>> {
+        Ok(Some(match self {
+            Self::Utf8 => pa.call_method0("string")?,
+            Self::Float64 => pa.call_method0("float64")?,
+            Self::Timestamp => pa.call_method(
+                "timestamp",
+                ("us",),
+                Some(&{
+                    let kwargs = PyDict::new(pa.py());
+                    kwargs.set_item("tz", "UTC")?;
+                    kwargs
+                }),
+            )?,
+            Self::Tags => pa.call_method1("list_", (pa.call_method0("string")?,))?,
+            Self::Inferred => return Ok(None),
+        }))
+    }
+
+    /// Convert a result cell to the Python value pyarrow expects for this
+    /// column: epoch microseconds for timestamps. Tags already arrive as
+    /// arrays and become lists.
+    fn to_py(&self, py: Python<'_>, value: Option<&serde_json::Value>) -> PyResult<Py<PyAny>> {
+        let value = value.unwrap_or(&serde_json::Value::Null);
+        match (self, value) {
+            (_, serde_json::Value::Null) => Ok(py.None()),
+            (Self::Timestamp, serde_json::Value::String(s)) => {
+                match DateTime::parse_from_rfc3339(s) {
+                    Ok(dt) => Ok(dt.timestamp_micros().into_pyobject(py)?.into_any().unbind()),
+                    Err(_) => Ok(py.None()),
+                }
+            }
+            (_, v) => json_to_py(py, v),
+        }
+    }
+}
+
+fn json_to_py(py: Python<'_>, value: &serde_json::Value) -> PyResult<Py<PyAny>> {
+    Ok(match value {
+        serde_json::Value::Null => py.None(),
+        serde_json::Value::Bool(b) => b.into_pyobject(py)?.to_owned().into_any().unbind(),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => i.into_pyobject(py)?.into_any().unbind(),
+            None => n
+                .as_f64()
+                .unwrap_or(f64::NAN)
+                .into_pyobject(py)?
+                .into_any()
+                .unbind(),
+        },
+        serde_json::Value::String(s) => s.into_pyobject(py)?.into_any().unbind(),
+        serde_json::Value::Array(items) => {
+            let list = PyList::empty(py);
+            for item in items {
+                list.append(json_to_py(py, item)?)?;
+            }
+            list.into_any().unbind()
+        }
+        serde_json::Value::Object(entries) => {
+            let dict = PyDict::new(py);
+            for (key, value) in entries {
+                dict.set_item(key, json_to_py(py, value)?)?;
+            }
+            dict.into_any().unbind()
+        }
+    })
 }
 
 /// Query all documents in the vault.
@@ -233,6 +912,39 @@ fn query_all(py: Python<'_>, vault_path: &str) -> PyResult<Vec<Py<PyDict>>> {
         .query_all()
         .map_err(|e| PyValueError::new_err(format!("Query failed: {e}")))?;
 
+    results.iter().map(|r| indexed_to_dict(py, r)).collect()
+}
+
+/// The most recently observed document of each type, keyed by type.
+#[pyfunction]
+fn get_newest_per_type(py: Python<'_>, vault_path: &str) -> PyResult<Py<PyDict>> {
+    let index = open_index(Path::new(vault_path))?;
+    newest_per_type_dict(py, &index)
+}
+
+/// The vault as it was at `datetime`: the then-current version of each
+/// document, most recently observed first.
+#[pyfunction]
+fn snapshot_at(py: Python<'_>, vault_path: &str, datetime: &str) -> PyResult<Vec<Py<PyDict>>> {
+    let index = open_index(Path::new(vault_path))?;
+    let at = parse_datetime(datetime)?.utc;
+
+    let results = mkb_index::TemporalInterpolator::snapshot(&index, at)
+        .map_err(|e| PyValueError::new_err(format!("Query failed: {e}")))?;
+
+    results.iter().map(|r| indexed_to_dict(py, r)).collect()
+}
+
+/// Earlier versions of a document via `supersedes`, oldest first, ending
+/// with `id`. Raises `ValueError` on circular supersession.
+#[pyfunction]
+fn get_supersession_chain(py: Python<'_>, vault_path: &str, id: &str) -> PyResult<Vec<Py<PyDict>>> {
+    let index = open_index(Path::new(vault_path))?;
+
+    let results = index
+        .supersession_chain(id)
+        .map_err(|e| PyValueError::new_err(format!("Chain failed: {e}")))?;
+
     results
         .iter()
         .map(|r| {
@@ -243,11 +955,39 @@ fn query_all(py: Python<'_>, vault_path: &str) -> PyResult<Vec<Py<PyDict>>> {
             dict.set_item("observed_at", &r.observed_at)?;
             dict.set_item("valid_until", &r.valid_until)?;
             dict.set_item("confidence", r.confidence)?;
+            dict.set_item("tags", &r.tags)?;
             Ok(dict.into())
         })
         .collect()
 }
 
+/// Relationship graph as JSON (`{"nodes": [...], "edges": [...]}`), built
+/// by following links `depth` hops from `center_id`, or from every document
+/// of `doc_type`. Exactly one seed must be given.
+#[pyfunction]
+#[pyo3(signature = (vault_path, center_id=None, doc_type=None, depth=2))]
+fn build_graph(
+    vault_path: &str,
+    center_id: Option<&str>,
+    doc_type: Option<&str>,
+    depth: u32,
+) -> PyResult<String> {
+    let index = open_index(Path::new(vault_path))?;
+
+    let graph = match (center_id, doc_type) {
+        (Some(id), None) => mkb_query::graph::GraphBuilder::from_center(&index, id, depth),
+        (None, Some(t)) => mkb_query::graph::GraphBuilder::from_type(&index, t, depth),
+        _ => {
+            return Err(PyValueError::new_err(
+                "Pass exactly one of center_id or doc_type",
+            ))
+        }
+    }
+    .map_err(|e| PyValueError::new_err(format!("Graph failed: {e}")))?;
+
+    Ok(graph.to_json())
+}
+
 /// Query documents by type.
 #[pyfunction]
 fn query_by_type(py: Python<'_>, vault_path: &str, doc_type: &str) -> PyResult<Vec<Py<PyDict>>> {
@@ -267,6 +1007,135 @@ fn query_by_type(py: Python<'_>, vault_path: &str, doc_type: &str) -> PyResult<V
             dict.set_item("observed_at", &r.observed_at)?;
             dict.set_item("valid_until", &r.valid_until)?;
             dict.set_item("confidence", r.confidence)?;
+            dict.set_item("tags", &r.tags)?;
+            Ok(dict.into())
+        })
+        .collect()
+}
+
+/// List links whose target document does not exist in the index.
+#[pyfunction]
+fn detect_dangling_links(py: Python<'_>, vault_path: &str) -> PyResult<Vec<Py<PyDict>>> {
+    let index = open_index(Path::new(vault_path))?;
+
+    let results = index
+        .detect_dangling_links()
+        .map_err(|e| PyValueError::new_err(format!("Query failed: {e}")))?;
+
+    results
+        .iter()
+        .map(|l| {
+            let dict = PyDict::new(py);
+            dict.set_item("source_id", &l.source_id)?;
+            dict.set_item("target_id", &l.target_id)?;
+            dict.set_item("rel", &l.rel)?;
+            Ok(dict.into())
+        })
+        .collect()
+}
+
+/// Re-read every vault file's frontmatter `links:` and body `[[target_id]]`
+/// WikiLinks and rebuild the links table from them.
+///
+/// Returns a dict with `rebuilt` (links written) and `dangling` (of those,
+/// links to documents not in the index).
+#[pyfunction]
+fn rebuild_links_from_vault(py: Python<'_>, vault_path: &str) -> PyResult<Py<PyDict>> {
+    let vault = open_vault(vault_path)?;
+    let index = open_index(Path::new(vault_path))?;
+
+    let stats = index
+        .rebuild_links(&vault)
+        .map_err(|e| PyValueError::new_err(format!("Link rebuild failed: {e}")))?;
+
+    let dict = PyDict::new(py);
+    dict.set_item("rebuilt", stats.rebuilt)?;
+    dict.set_item("dangling", stats.dangling)?;
+    Ok(dict.into())
+}
+
+/// Every indexed document and link as `{"nodes": [...], "edges": [...]}`,
+/// ready for `networkx` or D3.js.
+///
+/// Nodes are dicts with `id`, `type` and `title`; a dangling link target is
+/// a node whose `type` and `title` are `None`. Edges are dicts with
+/// `source`, `target`, `rel` and `observed_at`.
+#[pyfunction]
+fn get_link_graph(py: Python<'_>, vault_path: &str) -> PyResult<Py<PyDict>> {
+    let index = open_index(Path::new(vault_path))?;
+    let query_err = |e: MkbError| PyValueError::new_err(format!("Query failed: {e}"));
+
+    let graph = index.link_graph().map_err(query_err)?;
+    let docs: HashMap<String, mkb_index::IndexedDocument> = index
+        .query_all()
+        .map_err(query_err)?
+        .into_iter()
+        .map(|d| (d.id.clone(), d))
+        .collect();
+
+    let nodes = PyList::empty(py);
+    for id in graph.nodes() {
+        let node = PyDict::new(py);
+        node.set_item("id", id)?;
+        let doc = docs.get(id);
+        node.set_item("type", doc.map(|d| &d.doc_type))?;
+        node.set_item("title", doc.map(|d| &d.title))?;
+        nodes.append(node)?;
+    }
+
+    let edges = PyList::empty(py);
+    for edge in graph.edges() {
+        let dict = PyDict::new(py);
+        dict.set_item("source", &edge.source)?;
+        dict.set_item("target", &edge.target)?;
+        dict.set_item("rel", &edge.rel)?;
+        dict.set_item("observed_at", &edge.observed_at)?;
+        edges.append(dict)?;
+    }
+
+    let dict = PyDict::new(py);
+    dict.set_item("nodes", nodes)?;
+    dict.set_item("edges", edges)?;
+    Ok(dict.into())
+}
+
+/// Query documents by confidence, optionally restricted to one type.
+#[pyfunction]
+#[pyo3(signature = (vault_path, min_confidence, max_confidence=1.0, doc_type=None))]
+fn query_by_confidence(
+    py: Python<'_>,
+    vault_path: &str,
+    min_confidence: f64,
+    max_confidence: f64,
+    doc_type: Option<&str>,
+) -> PyResult<Vec<Py<PyDict>>> {
+    let index = open_index(Path::new(vault_path))?;
+
+    let results = match doc_type {
+        Some(_) if !(0.0..=1.0).contains(&max_confidence) => {
+            Err(MkbError::Index("confidence out of range".to_string()))
+        }
+        Some(t) => index
+            .query_by_type_and_confidence(t, min_confidence)
+            .map(|mut docs| {
+                docs.retain(|d| d.confidence <= max_confidence);
+                docs
+            }),
+        None => index.query_by_confidence_range(min_confidence, max_confidence),
+    }
+    .map_err(|e| PyValueError::new_err(format!("Query failed: {e}")))?;
+
+    results
+        .iter()
+        .map(|r| {
+            let dict = PyDict::new(py);
+            dict.set_item("id", &r.id)?;
+            dict.set_item("type", &r.doc_type)?;
+            dict.set_item("title", &r.title)?;
+            dict.set_item("observed_at", &r.observed_at)?;
+            dict.set_item("valid_until", &r.valid_until)?;
+            dict.set_item("confidence", r.confidence)?;
+            dict.set_item("tags", &r.tags)?;
             Ok(dict.into())
         })
         .collect()
@@ -275,7 +1144,8 @@ fn query_by_type(py: Python<'_>, vault_path: &str, doc_type: &str) -> PyResult<V
 // === Temporal Gate (T-400.3) ===
 
 /// Validate temporal fields without creating a document.
-/// Returns a dict with validation result.
+/// Returns a dict with validation result. When `observed_at` is given,
+/// `tz_offset_minutes` reports the offset it was written in.
 #[pyfunction]
 #[pyo3(signature = (observed_at=None, valid_until=None, precision="day"))]
 fn validate_temporal(
@@ -288,13 +1158,18 @@ fn validate_temporal(
 
     let obs = observed_at.map(parse_datetime).transpose()?;
     let valid = valid_until.map(parse_datetime).transpose()?;
-    let prec = parse_precision(precision)?;
+    let mut prec = parse_precision(precision)?;
+    if let Some(o) = obs {
+        prec = o.coerce_precision(prec);
+        dict.set_item("tz_offset_minutes", o.offset_minutes)?;
+    }
 
     let input = RawTemporalInput {
-        observed_at: obs,
-        valid_until: valid,
+        observed_at: obs.map(|o| o.utc),
+        valid_until: valid.map(|v| v.utc),
         temporal_precision: Some(prec),
         occurred_at: None,
+        occurred_until: None,
     };
     let profile = DecayProfile::default_profile();
 
@@ -317,6 +1192,40 @@ fn validate_temporal(
     Ok(dict.into())
 }
 
+/// Rebuild the index from vault files.
+///
+/// `progress`, if given, is called as `progress(completed, total)` after each
+/// file. Returns a dict with `indexed`, `skipped`, `failed` and `elapsed_secs`.
+#[pyfunction]
+#[pyo3(signature = (vault_path, progress=None))]
+fn rebuild_index(
+    py: Python<'_>,
+    vault_path: &str,
+    progress: Option<Py<PyAny>>,
+) -> PyResult<Py<PyDict>> {
+    let vault = Vault::open(Path::new(vault_path))
+        .map_err(|e| PyValueError::new_err(format!("Vault error: {e}")))?;
+
+    let stats = IndexManager::rebuild_from_vault(&vault, |done, total| {
+        if let Some(cb) = &progress {
+            Python::attach(|py| {
+                // A raising callback must not abort the rebuild mid-swap
+                if let Err(e) = cb.call1(py, (done, total)) {
+                    e.write_unraisable(py, None);
+                }
+            });
+        }
+    })
+    .map_err(|e| PyValueError::new_err(format!("Rebuild failed: {e}")))?;
+
+    let dict = PyDict::new(py);
+    dict.set_item("indexed", stats.indexed)?;
+    dict.set_item("skipped", stats.skipped)?;
+    dict.set_item("failed", stats.failed)?;
+    dict.set_item("elapsed_secs", stats.elapsed.as_secs_f64())?;
+    Ok(dict.into())
+}
+
 /// Get count of indexed documents.
 #[pyfunction]
 fn document_count(vault_path: &str) -> PyResult<u64> {
@@ -346,9 +1255,230 @@ fn vault_status(py: Python<'_>, vault_path: &str) -> PyResult<Py<PyDict>> {
     dict.set_item("vault_files", files.len())?;
     dict.set_item("index_synced", files.len() as u64 == doc_count)?;
     dict.set_item("rejection_count", rejection_count)?;
+    dict.set_item("archived_documents", vault.archived_count().unwrap_or(0))?;
+    dict.set_item("newest_per_type", newest_per_type_dict(py, &index)?)?;
     Ok(dict.into())
 }
 
+/// List the document IDs on which the vault and index disagree.
+///
+/// Returns a dict with `in_vault_not_index`, `in_index_not_vault` and
+/// `modified_since_index` lists (files edited since they were indexed).
+#[pyfunction]
+fn vault_diff(py: Python<'_>, vault_path: &str) -> PyResult<Py<PyDict>> {
+    let vpath = Path::new(vault_path);
+    let vault =
+        Vault::open(vpath).map_err(|e| PyValueError::new_err(format!("Vault error: {e}")))?;
+    let index = open_index(vpath)?;
+
+    let diff = index
+        .diff_against_vault(&vault)
+        .map_err(|e| PyRuntimeError::new_err(format!("Diff failed: {e}")))?;
+
+    let dict = PyDict::new(py);
+    dict.set_item("in_vault_not_index", diff.in_vault_not_index)?;
+    dict.set_item("in_index_not_vault", diff.in_index_not_vault)?;
+    dict.set_item("modified_since_index", diff.modified_since_index)?;
+    Ok(dict.into())
+}
+
+/// Validate every document against the schema for its type.
+///
+/// Returns a dict with `valid`, `invalid` and `warnings` counts and an
+/// `errors` list of `{id, doc_type, errors, warnings}` dicts. With `strict`,
+/// documents with warnings count as invalid.
+#[pyfunction]
+#[pyo3(signature = (vault_path, strict=false))]
+fn validate_all(py: Python<'_>, vault_path: &str, strict: bool) -> PyResult<Py<PyDict>> {
+    let vault = Vault::open(Path::new(vault_path))
+        .map_err(|e| PyValueError::new_err(format!("Vault error: {e}")))?;
+    let registry = vault
+        .schema_registry()
+        .map_err(|e| PyValueError::new_err(format!("Schema error: {e}")))?;
+    let schemas: Vec<_> = registry.iter().cloned().collect();
+    let report = vault
+        .validate_all_against_schemas(&schemas)
+        .map_err(|e| PyValueError::new_err(format!("Validation failed: {e}")))?;
+
+    let errors = report
+        .documents
+        .iter()
+        .map(|d| {
+            let entry = PyDict::new(py);
+            entry.set_item("id", &d.id)?;
+            entry.set_item("doc_type", &d.doc_type)?;
+            entry.set_item("errors", &d.errors)?;
+            entry.set_item("warnings", &d.warnings)?;
+            Ok(entry)
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+
+    let dict = PyDict::new(py);
+    dict.set_item("valid", report.valid(strict))?;
+    dict.set_item("invalid", report.invalid(strict))?;
+    dict.set_item("warnings", report.with_warnings())?;
+    dict.set_item("errors", errors)?;
+    Ok(dict.into())
+}
+
+/// Export every vault document to `output_path` as JSON Lines.
+///
+/// Each line has the same keys as the dicts returned by `read_document`,
+/// plus `fields`, `links` and provenance metadata. Returns a dict with
+/// `exported`, `failed` and `elapsed_secs`.
+#[pyfunction]
+#[pyo3(signature = (vault_path, output_path, include_body=true))]
+fn export_vault(
+    py: Python<'_>,
+    vault_path: &str,
+    output_path: &str,
+    include_body: bool,
+) -> PyResult<Py<PyDict>> {
+    let vault = Vault::open(Path::new(vault_path))
+        .map_err(|e| PyValueError::new_err(format!("Vault error: {e}")))?;
+    let file = fs::File::create(output_path)
+        .map_err(|e| PyValueError::new_err(format!("Export failed: {e}")))?;
+
+    let stats = vault
+        .export_jsonl(std::io::BufWriter::new(file), include_body)
+        .map_err(|e| PyValueError::new_err(format!("Export failed: {e}")))?;
+
+    let dict = PyDict::new(py);
+    dict.set_item("exported", stats.exported)?;
+    dict.set_item("failed", stats.failed)?;
+    dict.set_item("elapsed_secs", stats.elapsed.as_secs_f64())?;
+    Ok(dict.into())
+}
+
+/// Run an MKQL query and write the rows to `output_path` as CSV.
+///
+/// Columns are sorted by name; `body` is left out unless `include_body`.
+/// Line breaks in values are written as a literal `\n`. Returns the
+/// number of rows written.
+#[pyfunction]
+#[pyo3(signature = (vault_path, mkql, output_path, delimiter=',', include_body=false))]
+fn export_csv(
+    vault_path: &str,
+    mkql: &str,
+    output_path: &str,
+    delimiter: char,
+    include_body: bool,
+) -> PyResult<usize> {
+    let index = open_index(Path::new(vault_path))?;
+    let compiled = compile_mkql(vault_path, mkql, false)?;
+    let start = std::time::Instant::now();
+    let result = mkb_query::execute(&index, &compiled)
+        .map_err(|e| PyValueError::new_err(format!("Execution error: {e}")))?;
+    index
+        .record_query(mkql, start.elapsed(), result.total)
+        .map_err(|e| PyRuntimeError::new_err(format!("Failed to record query: {e}")))?;
+
+    let opts = mkb_query::CsvOpts {
+        delimiter,
+        include_body,
+        ..mkb_query::CsvOpts::default()
+    };
+    fs::write(output_path, mkb_query::format_csv(&result, &opts))
+        .map_err(|e| PyValueError::new_err(format!("Export failed: {e}")))?;
+    Ok(result.rows.len())
+}
+
+// === Saved Views ===
+
+fn open_vault(vault_path: &str) -> PyResult<Vault> {
+    Vault::open(Path::new(vault_path))
+        .map_err(|e| PyValueError::new_err(format!("Vault error: {e}")))
+}
+
+fn view_dict(py: Python<'_>, view: &mkb_core::view::SavedView) -> PyResult<Py<PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("name", &view.name)?;
+    dict.set_item("query", &view.query)?;
+    dict.set_item("description", view.description.as_deref())?;
+    dict.set_item("created_at", &view.created_at)?;
+    Ok(dict.into())
+}
+
+/// Save an MKQL query as a named view, replacing any view of that name.
+///
+/// The query must parse and compile, and every field it names must be a
+/// document column or defined by its type's schema; invalid MKQL raises
+/// `ValueError`.
+#[pyfunction]
+#[pyo3(signature = (vault_path, name, mkql, description=None))]
+fn save_view(
+    py: Python<'_>,
+    vault_path: &str,
+    name: &str,
+    mkql: &str,
+    description: Option<&str>,
+) -> PyResult<Py<PyDict>> {
+    let vault = open_vault(vault_path)?;
+    let ast = mkb_parser::parse_mkql(mkql)
+        .map_err(|e| PyValueError::new_err(format!("Parse error: {e}")))?;
+    let options = compile_options(vault_path, true)?;
+    mkb_query::validate_identifiers(&ast, &options.schemas)
+        .map_err(|e| PyValueError::new_err(format!("Invalid identifier: {e}")))?;
+    mkb_query::compile_with_options(&ast, options)
+        .map_err(|e| PyValueError::new_err(format!("Compile error: {e}")))?;
+
+    let view = mkb_core::view::SavedView {
+        name: name.to_string(),
+        description: description.map(str::to_string),
+        query: mkql.to_string(),
+        created_at: Utc::now().to_rfc3339(),
+    };
+    vault
+        .save_view(&view)
+        .map_err(|e| PyValueError::new_err(format!("Save failed: {e}")))?;
+    view_dict(py, &view)
+}
+
+/// Load a saved view as a `{name, query, description, created_at}` dict.
+#[pyfunction]
+fn load_view(py: Python<'_>, vault_path: &str, name: &str) -> PyResult<Py<PyDict>> {
+    let view = open_vault(vault_path)?
+        .load_view(name)
+        .map_err(|e| PyValueError::new_err(format!("Load failed: {e}")))?;
+    view_dict(py, &view)
+}
+
+/// List every saved view, sorted by name.
+#[pyfunction]
+fn list_views(py: Python<'_>, vault_path: &str) -> PyResult<Vec<Py<PyDict>>> {
+    let vault = open_vault(vault_path)?;
+    let names = vault
+        .list_views()
+        .map_err(|e| PyValueError::new_err(format!("List failed: {e}")))?;
+    names
+        .iter()
+        .map(|name| {
+            let view = vault
+                .load_view(name)
+                .map_err(|e| PyValueError::new_err(format!("Load failed: {e}")))?;
+            view_dict(py, &view)
+        })
+        .collect()
+}
+
+/// Delete a saved view. Raises `ValueError` if it doesn't exist.
+#[pyfunction]
+fn delete_view(vault_path: &str, name: &str) -> PyResult<()> {
+    open_vault(vault_path)?
+        .delete_view(name)
+        .map_err(|e| PyValueError::new_err(format!("Delete failed: {e}")))
+}
+
+/// Run a saved view; equivalent to `query_mkql(vault_path, view.query, format)`.
+#[pyfunction]
+#[pyo3(signature = (vault_path, name, format="json"))]
+fn run_view(vault_path: &str, name: &str, format: &str) -> PyResult<String> {
+    let view = open_vault(vault_path)?
+        .load_view(name)
+        .map_err(|e| PyValueError::new_err(format!("Load failed: {e}")))?;
+    query_mkql(vault_path, &view.query, format, None, false)
+}
+
 // === Embedding Operations (T-410) ===
 
 /// Store an embedding vector for a document.
@@ -365,20 +1495,24 @@ fn store_embedding(
         .map_err(|e| PyValueError::new_err(format!("Store embedding failed: {e}")))
 }
 
-/// Search for similar documents using vector similarity.
+/// Search for similar documents using vector similarity, optionally only
+/// among documents of `doc_type`.
 #[pyfunction]
-#[pyo3(signature = (vault_path, query_embedding, limit=10))]
+#[pyo3(signature = (vault_path, query_embedding, limit=10, doc_type=None))]
 fn search_semantic(
     py: Python<'_>,
     vault_path: &str,
     query_embedding: Vec<f32>,
     limit: usize,
+    doc_type: Option<&str>,
 ) -> PyResult<Vec<Py<PyDict>>> {
     let index = open_index(Path::new(vault_path))?;
 
-    let results = index
-        .search_semantic(&query_embedding, limit)
-        .map_err(|e| PyValueError::new_err(format!("Semantic search failed: {e}")))?;
+    let results = match doc_type {
+        Some(dtype) => index.search_semantic_by_type(&query_embedding, dtype, limit),
+        None => index.search_semantic(&query_embedding, limit),
+    }
+    .map_err(|e| PyValueError::new_err(format!("Semantic search failed: {e}")))?;
 
     results
         .iter()
@@ -411,42 +1545,328 @@ fn embedding_count(vault_path: &str) -> PyResult<u64> {
         .map_err(|e| PyValueError::new_err(format!("Embedding count failed: {e}")))
 }
 
+/// Embedding counts per model, with the oldest and newest `created_at`.
+#[pyfunction]
+fn get_embedding_stats(py: Python<'_>, vault_path: &str) -> PyResult<Vec<Py<PyDict>>> {
+    let index = open_index(Path::new(vault_path))?;
+    let stats = index
+        .get_embedding_model_stats()
+        .map_err(|e| PyValueError::new_err(format!("Embedding stats failed: {e}")))?;
+
+    stats
+        .iter()
+        .map(|s| {
+            let dict = PyDict::new(py);
+            dict.set_item("model", &s.model)?;
+            dict.set_item("count", s.count)?;
+            dict.set_item("oldest_created_at", &s.oldest_created_at)?;
+            dict.set_item("newest_created_at", &s.newest_created_at)?;
+            Ok(dict.into())
+        })
+        .collect()
+}
+
+/// Indexed documents that have no stored embedding.
+#[pyfunction]
+fn get_documents_without_embedding(py: Python<'_>, vault_path: &str) -> PyResult<Vec<Py<PyDict>>> {
+    let index = open_index(Path::new(vault_path))?;
+    let docs = index
+        .documents_without_embedding()
+        .map_err(|e| PyValueError::new_err(format!("Query failed: {e}")))?;
+    docs.iter().map(|d| indexed_to_dict(py, d)).collect()
+}
+
 /// Get the expected embedding dimension.
 #[pyfunction]
 fn embedding_dim() -> usize {
     mkb_index::EMBEDDING_DIM
 }
 
+// === Transactions ===
+
+/// All-or-nothing batch of document creates and links.
+///
+/// Operations are buffered in memory; nothing touches the vault or index
+/// until `commit()`. If any write fails during commit, the index transaction
+/// is rolled back and every file written so far is removed. Used as a
+/// context manager, the batch commits on a clean exit and is discarded when
+/// the block raises.
+#[pyclass(unsendable)]
+struct VaultTransaction {
+    vault_root: PathBuf,
+    vault: Vault,
+    index: IndexManager,
+    staged: Vec<Document>,
+    finished: bool,
+}
+
+impl VaultTransaction {
+    fn ensure_open(&self) -> PyResult<()> {
+        if self.finished {
+            return Err(PyValueError::new_err("Transaction already finished"));
+        }
+        Ok(())
+    }
+
+    /// Write staged documents, recording each created file and directory
+    /// so a failure can undo them.
+    fn write_staged(
+        &self,
+        written: &mut Vec<PathBuf>,
+        created_dirs: &mut Vec<PathBuf>,
+    ) -> Result<(), MkbError> {
+        for doc in &self.staged {
+            let type_dir = self
+                .vault_root
+                .join(mkb_vault::type_to_directory(&doc.doc_type));
+            if !type_dir.exists() && !created_dirs.contains(&type_dir) {
+                created_dirs.push(type_dir);
+            }
+            written.push(self.vault.create(doc)?);
+            self.index.index_document(doc)?;
+            if !doc.links.is_empty() {
+                self.index.store_links(&doc.id, &doc.links)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl VaultTransaction {
+    #[new]
+    fn new(vault_path: &str) -> PyResult<Self> {
+        let vpath = Path::new(vault_path);
+        let vault =
+            Vault::open(vpath).map_err(|e| PyValueError::new_err(format!("Vault error: {e}")))?;
+        let index = open_index(vpath)?;
+        Ok(Self {
+            vault_root: vpath.to_path_buf(),
+            vault,
+            index,
+            staged: Vec::new(),
+            finished: false,
+        })
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    #[pyo3(signature = (exc_type=None, _exc_value=None, _traceback=None))]
+    fn __exit__(
+        &mut self,
+        exc_type: Option<&Bound<'_, PyAny>>,
+        _exc_value: Option<&Bound<'_, PyAny>>,
+        _traceback: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<bool> {
+        if self.finished {
+            return Ok(false);
+        }
+        if exc_type.is_some_and(|t| !t.is_none()) {
+            self.rollback();
+        } else {
+            self.commit()?;
+        }
+        // Never swallow the caller's exception
+        Ok(false)
+    }
+
+    /// Stage a new document. Returns the document dict, including its ID.
+    #[pyo3(signature = (doc_type, title, observed_at, body="", tags=None, precision="day", valid_until=None, confidence=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn create_document(
+        &mut self,
+        py: Python<'_>,
+        doc_type: &str,
+        title: &str,
+        observed_at: &str,
+        body: &str,
+        tags: Option<Vec<String>>,
+        precision: &str,
+        valid_until: Option<&str>,
+        confidence: Option<f64>,
+    ) -> PyResult<Py<PyDict>> {
+        self.ensure_open()?;
+
+        // Counters on disk don't see staged documents; skip past them
+        let mut counter =
+            mkb_vault::next_counter(&self.vault_root, doc_type, &mkb_vault::slugify(title));
+        let mut id = Document::generate_id(doc_type, title, counter);
+        while self.staged.iter().any(|d| d.id == id) {
+            counter += 1;
+            id = Document::generate_id(doc_type, title, counter);
+        }
+
+        let doc = build_document(
+            id,
+            doc_type,
+            title,
+            observed_at,
+            body,
+            tags,
+            precision,
+            valid_until,
+            confidence,
+        )?;
+        let dict = doc_to_dict(py, &doc, &self.vault)?;
+        self.staged.push(doc);
+        Ok(dict)
+    }
+
+    /// Stage a link from a document created in this transaction.
+    ///
+    /// `observed_at` defaults to the source document's `observed_at`.
+    #[pyo3(signature = (source_id, rel, target, observed_at=None))]
+    fn link(
+        &mut self,
+        source_id: &str,
+        rel: &str,
+        target: &str,
+        observed_at: Option<&str>,
+    ) -> PyResult<()> {
+        self.ensure_open()?;
+        let observed = observed_at.map(parse_datetime).transpose()?;
+
+        let source = self
+            .staged
+            .iter_mut()
+            .find(|d| d.id == source_id)
+            .ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "Link source '{source_id}' was not created in this transaction"
+                ))
+            })?;
+        source.links.push(Link {
+            rel: rel.to_string(),
+            target: target.to_string(),
+            observed_at: observed.map_or(source.temporal.observed_at, |o| o.utc),
+            metadata: None,
+        });
+        Ok(())
+    }
+
+    /// Write all staged operations atomically. Returns the number of
+    /// documents written.
+    fn commit(&mut self) -> PyResult<usize> {
+        self.ensure_open()?;
+        self.finished = true;
+
+        let mut written = Vec::new();
+        let mut created_dirs = Vec::new();
+        let result = self
+            .index
+            .with_transaction(|_| self.write_staged(&mut written, &mut created_dirs));
+
+        if let Err(e) = result {
+            for path in &written {
+                let _ = fs::remove_file(path);
+            }
+            for dir in &created_dirs {
+                let _ = fs::remove_dir(dir);
+            }
+            self.staged.clear();
+            return Err(PyValueError::new_err(format!(
+                "Transaction rolled back: {e}"
+            )));
+        }
+
+        let count = self.staged.len();
+        self.staged.clear();
+        Ok(count)
+    }
+
+    /// Discard all staged operations without writing anything.
+    fn rollback(&mut self) {
+        self.staged.clear();
+        self.finished = true;
+    }
+}
+
 /// MKB Python module.
 #[pymodule]
 fn _mkb_core(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add("__version__", env!("CARGO_PKG_VERSION"))?;
 
+    // Exceptions
+    let py = m.py();
+    m.add(
+        "DocumentNotFoundError",
+        py.get_type::<DocumentNotFoundError>(),
+    )?;
+    m.add(
+        "DuplicateDocumentError",
+        py.get_type::<DuplicateDocumentError>(),
+    )?;
+    m.add(
+        "SchemaViolationError",
+        py.get_type::<SchemaViolationError>(),
+    )?;
+    m.add("IndexCorruptedError", py.get_type::<IndexCorruptedError>())?;
+
     // Vault CRUD (T-400.1)
     m.add_function(wrap_pyfunction!(init_vault, m)?)?;
     m.add_function(wrap_pyfunction!(create_document, m)?)?;
     m.add_function(wrap_pyfunction!(read_document, m)?)?;
+    m.add_function(wrap_pyfunction!(append_body, m)?)?;
+    m.add_function(wrap_pyfunction!(update_document, m)?)?;
     m.add_function(wrap_pyfunction!(delete_document, m)?)?;
 
     // Index operations (T-400.2)
     m.add_function(wrap_pyfunction!(search_fts, m)?)?;
     m.add_function(wrap_pyfunction!(query_mkql, m)?)?;
+    m.add_function(wrap_pyfunction!(query_mkql_params, m)?)?;
+    m.add_function(wrap_pyfunction!(query_mkql_with_transforms, m)?)?;
+    m.add_function(wrap_pyfunction!(stream_mkql, m)?)?;
+    m.add_function(wrap_pyfunction!(explain_mkql, m)?)?;
+    m.add_function(wrap_pyfunction!(assemble_context, m)?)?;
+    m.add_function(wrap_pyfunction!(query_to_arrow, m)?)?;
     m.add_function(wrap_pyfunction!(query_all, m)?)?;
+    m.add_function(wrap_pyfunction!(get_newest_per_type, m)?)?;
+    m.add_function(wrap_pyfunction!(snapshot_at, m)?)?;
     m.add_function(wrap_pyfunction!(query_by_type, m)?)?;
+    m.add_function(wrap_pyfunction!(get_supersession_chain, m)?)?;
+    m.add_function(wrap_pyfunction!(query_by_confidence, m)?)?;
+    m.add_function(wrap_pyfunction!(detect_dangling_links, m)?)?;
+    m.add_function(wrap_pyfunction!(rebuild_links_from_vault, m)?)?;
+    m.add_function(wrap_pyfunction!(get_link_graph, m)?)?;
+    m.add_function(wrap_pyfunction!(rebuild_index, m)?)?;
 
     // Temporal gate (T-400.3)
     m.add_function(wrap_pyfunction!(validate_temporal, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_all, m)?)?;
+
+    // Saved views
+    m.add_function(wrap_pyfunction!(save_view, m)?)?;
+    m.add_function(wrap_pyfunction!(load_view, m)?)?;
+    m.add_function(wrap_pyfunction!(list_views, m)?)?;
+    m.add_function(wrap_pyfunction!(delete_view, m)?)?;
+    m.add_function(wrap_pyfunction!(run_view, m)?)?;
 
     // Embedding operations (T-410)
     m.add_function(wrap_pyfunction!(store_embedding, m)?)?;
     m.add_function(wrap_pyfunction!(search_semantic, m)?)?;
     m.add_function(wrap_pyfunction!(has_embedding, m)?)?;
     m.add_function(wrap_pyfunction!(embedding_count, m)?)?;
+    m.add_function(wrap_pyfunction!(get_embedding_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(get_documents_without_embedding, m)?)?;
+    m.add_function(wrap_pyfunction!(set_query_history, m)?)?;
+    m.add_function(wrap_pyfunction!(get_query_history, m)?)?;
+    m.add_function(wrap_pyfunction!(add_annotation, m)?)?;
+    m.add_function(wrap_pyfunction!(get_annotations, m)?)?;
     m.add_function(wrap_pyfunction!(embedding_dim, m)?)?;
 
+    // Transactions
+    m.add_class::<VaultTransaction>()?;
+    m.add_class::<MkqlStream>()?;
+
     // Utility
     m.add_function(wrap_pyfunction!(document_count, m)?)?;
     m.add_function(wrap_pyfunction!(vault_status, m)?)?;
+    m.add_function(wrap_pyfunction!(vault_diff, m)?)?;
+    m.add_function(wrap_pyfunction!(export_vault, m)?)?;
+    m.add_function(wrap_pyfunction!(export_csv, m)?)?;
+    m.add_function(wrap_pyfunction!(build_graph, m)?)?;
 
     Ok(())
 }
@@ -0,0 +1,189 @@
+//! Compiled queries cached by their canonical MKQL text and compile options.
+//!
+//! The key is the query's normalized form (its `Display`) plus the
+//! future-observation settings it was compiled under, so spellings that
+//! differ only in case, spacing, redundant parentheses or `IN` list order
+//! share one entry.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use mkb_parser::ast::MkqlQuery;
+
+use crate::compiler::{compile_with_options, CompileOptions, CompiledQuery};
+
+/// A bounded cache of compiled queries. When full, the oldest entry is
+/// dropped to make room.
+///
+/// Clones share one cache.
+#[derive(Debug, Clone)]
+pub struct QueryCache {
+    capacity: usize,
+    entries: Arc<Mutex<Entries>>,
+}
+
+/// Normalized MKQL and the [`CompileOptions`] flags that change its SQL.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    mkql: String,
+    future_in_current: bool,
+    future_in_fresh: bool,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    compiled: HashMap<CacheKey, CompiledQuery>,
+    /// Keys in insertion order, oldest first.
+    order: VecDeque<CacheKey>,
+}
+
+impl QueryCache {
+    /// Entries kept by [`QueryCache::default`].
+    pub const DEFAULT_CAPACITY: usize = 256;
+
+    /// Create a cache holding up to `capacity` queries. A capacity of 0 is
+    /// treated as 1.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: Arc::new(Mutex::new(Entries::default())),
+        }
+    }
+
+    /// The compiled form of `query` under `options`, compiling and caching
+    /// it on a miss.
+    ///
+    /// Queries compiled with [`CompileOptions::validate_fields`] are checked
+    /// against `options.schemas` every time and never cached.
+    ///
+    /// # Errors
+    ///
+    /// Returns a string error if the query does not compile; failures are
+    /// not cached.
+    pub fn get_or_compile(
+        &self,
+        query: &MkqlQuery,
+        options: &CompileOptions,
+    ) -> Result<CompiledQuery, String> {
+        if options.validate_fields {
+            return compile_with_options(query, options.clone()).map_err(|e| e.to_string());
+        }
+        let key = CacheKey {
+            mkql: query.to_string(),
+            future_in_current: options.future_in_current,
+            future_in_fresh: options.future_in_fresh,
+        };
+        if let Some(compiled) = self.lock().compiled.get(&key) {
+            return Ok(compiled.clone());
+        }
+
+        let compiled = compile_with_options(query, options.clone()).map_err(|e| e.to_string())?;
+        let mut entries = self.lock();
+        if !entries.compiled.contains_key(&key) {
+            while entries.order.len() >= self.capacity {
+                if let Some(oldest) = entries.order.pop_front() {
+                    entries.compiled.remove(&oldest);
+                }
+            }
+            entries.order.push_back(key.clone());
+            entries.compiled.insert(key, compiled.clone());
+        }
+        Ok(compiled)
+    }
+
+    /// Number of cached queries.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lock().compiled.len()
+    }
+
+    /// Whether nothing is cached.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for QueryCache {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mkb_parser::parse_mkql;
+
+    #[test]
+    fn equivalent_spellings_share_an_entry() {
+        let cache = QueryCache::default();
+        let a = cache
+            .get_or_compile(
+                &parse_mkql("SELECT * FROM project WHERE status IN ('a', 'b')").unwrap(),
+                &CompileOptions::default(),
+            )
+            .unwrap();
+        let b = cache
+            .get_or_compile(
+                &parse_mkql("select  *  from project where (status in ('b','a'))").unwrap(),
+                &CompileOptions::default(),
+            )
+            .unwrap();
+        assert_eq!(cache.len(), 1);
+        assert_eq!(a.sql, b.sql);
+    }
+
+    #[test]
+    fn oldest_entry_is_evicted_when_full() {
+        let cache = QueryCache::new(2);
+        for doc_type in ["project", "meeting", "decision"] {
+            let query = parse_mkql(&format!("SELECT * FROM {doc_type}")).unwrap();
+            cache
+                .get_or_compile(&query, &CompileOptions::default())
+                .unwrap();
+        }
+        assert_eq!(cache.len(), 2);
+        let entries = cache.lock();
+        let cached: Vec<&str> = entries.order.iter().map(|key| key.mkql.as_str()).collect();
+        assert_eq!(cached, ["SELECT * FROM meeting", "SELECT * FROM decision"]);
+    }
+
+    #[test]
+    fn options_are_part_of_the_key() {
+        let cache = QueryCache::default();
+        let query = parse_mkql("SELECT * FROM project WHERE CURRENT()").unwrap();
+        let default = cache
+            .get_or_compile(&query, &CompileOptions::default())
+            .unwrap();
+        let no_future = CompileOptions {
+            future_in_current: false,
+            ..CompileOptions::default()
+        };
+        let no_future_sql = cache.get_or_compile(&query, &no_future).unwrap().sql;
+        assert_eq!(cache.len(), 2);
+        assert_ne!(default.sql, no_future_sql);
+        assert_eq!(
+            cache.get_or_compile(&query, &no_future).unwrap().sql,
+            no_future_sql
+        );
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn validated_compiles_bypass_the_cache() {
+        let cache = QueryCache::default();
+        let query = parse_mkql("SELECT * FROM project").unwrap();
+        let options = CompileOptions {
+            validate_fields: true,
+            ..CompileOptions::default()
+        };
+        cache.get_or_compile(&query, &options).unwrap();
+        assert!(cache.is_empty());
+    }
+}
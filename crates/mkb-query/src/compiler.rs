@@ -3,10 +3,29 @@
 //! Compiles an MKQL query AST into a SQL query with bound parameters.
 //! All values are parameterized to prevent SQL injection.
 
+use std::sync::Arc;
+
+use mkb_core::schema::SchemaDefinition;
+use mkb_core::temporal::DecayProfile;
 use mkb_parser::ast::{
-    CompOp, LinkedFunction, MkqlQuery, Predicate, SelectClause, SortDirection, TemporalFunction,
-    Value, WhereClause,
+    AggregateFunction, CommonTableExpr, CompOp, DateBucket, DateExpr, DurationUnit, HavingClause,
+    HavingPredicate, LinkedFunction, MkqlQuery, OrderKey, PhraseTarget, PivotClause, PivotKey,
+    Predicate, SelectClause, SelectField, SetOp, SortDirection, TagMatch, TemporalFunction, Value,
+    WhereClause,
 };
+use mkb_vault::QuerySettings;
+
+use crate::transform::ResultTransformer;
+
+/// Column alias carrying the document ID for similarity ranking; stripped
+/// from result rows by the executor.
+pub const SIMILARITY_ID_COLUMN: &str = "__similarity_id";
+
+/// Column aliases of a compiled PIVOT's grouped rows, before the executor
+/// transposes them.
+pub const PIVOT_ROW_KEY: &str = "__pivot_row";
+pub const PIVOT_COL_KEY: &str = "__pivot_col";
+pub const PIVOT_VALUE: &str = "__pivot_value";
 
 /// A compiled SQL query with bound parameters.
 #[derive(Debug, Clone)]
@@ -23,6 +42,105 @@ pub struct CompiledQuery {
     pub uses_semantic: bool,
     /// Semantic search parameters: (query_text, threshold).
     pub near_params: Option<(String, f64)>,
+    /// Human-readable description of each WHERE predicate, in compile order.
+    pub where_descriptions: Vec<String>,
+    /// `ORDER BY SIMILARITY(...)`, applied by the executor after the SQL phase.
+    pub similarity_order: Option<SimilarityOrder>,
+    /// The aggregate of a PIVOT query, whose grouped rows the executor
+    /// transposes into one row per row key.
+    pub pivot: Option<AggregateFunction>,
+    /// Post-processing the executor applies to the rows, in order.
+    pub transformers: Vec<Arc<dyn ResultTransformer>>,
+}
+
+impl CompiledQuery {
+    /// Append `transformer` to the post-processing chain.
+    #[must_use]
+    pub fn with_transformer(mut self, transformer: impl ResultTransformer + 'static) -> Self {
+        self.transformers.push(Arc::new(transformer));
+        self
+    }
+}
+
+/// Post-SQL ranking by semantic similarity to a reference text.
+///
+/// LIMIT/OFFSET move here from the SQL so they apply after ranking.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimilarityOrder {
+    pub text: String,
+    pub descending: bool,
+    pub limit: Option<u64>,
+    pub offset: Option<u64>,
+}
+
+/// Compiler settings that change how predicates are evaluated.
+#[derive(Debug, Clone)]
+pub struct CompileOptions {
+    /// Let `CURRENT()` match documents whose `observed_at` is in the future.
+    pub future_in_current: bool,
+    /// Let `FRESH()` match documents whose `observed_at` is in the future.
+    pub future_in_fresh: bool,
+    /// Reject fields that are neither document columns nor defined by the
+    /// schema for the queried type (see [`validate_identifiers`]). Has no
+    /// effect while `schemas` is empty.
+    pub validate_fields: bool,
+    /// Schemas consulted by `validate_fields`; `extends` chains are resolved
+    /// against the same list.
+    pub schemas: Vec<SchemaDefinition>,
+}
+
+impl Default for CompileOptions {
+    /// The default [`QuerySettings`]; fields are not validated.
+    fn default() -> Self {
+        QuerySettings::default().into()
+    }
+}
+
+impl From<QuerySettings> for CompileOptions {
+    /// A vault's `.mkb/query.yaml` settings, without field validation.
+    fn from(settings: QuerySettings) -> Self {
+        Self {
+            future_in_current: settings.future_in_current,
+            future_in_fresh: settings.future_in_fresh,
+            validate_fields: false,
+            schemas: Vec::new(),
+        }
+    }
+}
+
+/// Why a query failed to compile.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CompileError {
+    /// A field is not known for the queried type. Reported by
+    /// [`validate_identifiers`], and by compilation when
+    /// [`CompileOptions::validate_fields`] is set.
+    #[error(
+        "Unknown field '{field}' for type '{doc_type}'{}",
+        did_you_mean(suggestions)
+    )]
+    UnknownField {
+        field: String,
+        doc_type: String,
+        /// Known fields with similar spelling, closest first.
+        suggestions: Vec<String>,
+    },
+    /// Any other problem with the query.
+    #[error("{0}")]
+    Invalid(String),
+}
+
+impl From<String> for CompileError {
+    fn from(message: String) -> Self {
+        Self::Invalid(message)
+    }
+}
+
+fn did_you_mean(suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        String::new()
+    } else {
+        format!(" (did you mean: {}?)", suggestions.join(", "))
+    }
 }
 
 /// A SQL parameter value.
@@ -32,38 +150,278 @@ pub enum SqlParam {
     Integer(i64),
     Float(f64),
     Null,
+    /// A `:name` placeholder, bound by [`crate::execute_with_params`].
+    Named {
+        name: String,
+        expected: ParamKind,
+    },
 }
 
-/// Compile an MKQL AST into a parameterized SQL query.
+/// Storage class of the column a named parameter is compared against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamKind {
+    Text,
+    Real,
+}
+
+impl ParamKind {
+    fn for_field(field: &str) -> Self {
+        match field {
+            "confidence" => Self::Real,
+            _ => Self::Text,
+        }
+    }
+}
+
+/// Compile an MKQL AST into a parameterized SQL query with default
+/// [`CompileOptions`].
 ///
 /// # Errors
 ///
 /// Returns a string error if the query cannot be compiled.
 pub fn compile(query: &MkqlQuery) -> Result<CompiledQuery, String> {
-    let mut ctx = CompileCtx::new();
+    compile_with_options(query, CompileOptions::default()).map_err(|e| e.to_string())
+}
+
+/// Compile an MKQL AST into a parameterized SQL query.
+///
+/// # Errors
+///
+/// Returns [`CompileError::UnknownField`] if field validation is enabled and
+/// a field is unknown, or [`CompileError::Invalid`] if the query
+/// cannot be compiled.
+pub fn compile_with_options(
+    query: &MkqlQuery,
+    options: CompileOptions,
+) -> Result<CompiledQuery, CompileError> {
+    if options.validate_fields && !options.schemas.is_empty() {
+        validate_identifiers(query, &options.schemas)?;
+    }
+    let mut ctx = CompileCtx::new(options);
+
+    let with_sql = compile_ctes(&query.ctes, &mut ctx)?;
+    let (sql, similarity) = if query.set_ops.is_empty() {
+        compile_select_stmt(query, &mut ctx)?
+    } else {
+        (compile_set_ops(query, &mut ctx)?, None)
+    };
+
+    Ok(CompiledQuery {
+        sql: format!("{with_sql}{sql}"),
+        params: ctx.params,
+        uses_fts: ctx.uses_fts,
+        uses_links: ctx.uses_links,
+        uses_semantic: ctx.uses_semantic,
+        near_params: ctx.near_params,
+        where_descriptions: ctx.where_descriptions,
+        similarity_order: similarity,
+        pivot: query.pivot.as_ref().map(|p| p.agg_func),
+        transformers: Vec::new(),
+    })
+}
+
+/// Index tables a `WITH` name must not shadow; compiled predicates query
+/// them directly.
+const RESERVED_TABLES: &[&str] = &[
+    "documents",
+    "documents_fts",
+    "links",
+    "document_embeddings",
+    "vec_documents",
+    "vec_meta",
+    "annotations",
+];
+
+/// Compile `WITH a AS (...), b AS (...)` into a SQL prefix, or `""` if there
+/// are none.
+///
+/// Each name is in scope for the subqueries after it and for the main query,
+/// where `FROM name` reads the subquery's rows instead of a document type.
+/// Recursive CTEs are not supported.
+fn compile_ctes(ctes: &[CommonTableExpr], ctx: &mut CompileCtx) -> Result<String, String> {
+    let mut parts = Vec::with_capacity(ctes.len());
+    for cte in ctes {
+        let lower = cte.name.to_ascii_lowercase();
+        if RESERVED_TABLES.contains(&lower.as_str()) {
+            return Err(format!("WITH name '{}' is reserved", cte.name));
+        }
+        if ctx.ctes.iter().any(|n| n.eq_ignore_ascii_case(&cte.name)) {
+            return Err(format!("WITH name '{}' is defined twice", cte.name));
+        }
+        if !cte.query.ctes.is_empty() {
+            return Err("WITH is only supported at the start of a query".to_string());
+        }
+        let body = if cte.query.set_ops.is_empty() {
+            compile_set_operand(&cte.query, ctx)?
+        } else {
+            compile_set_ops(&cte.query, ctx)?
+        };
+        parts.push(format!("\"{}\" AS ({body})", cte.name));
+        ctx.ctes.push(cte.name.clone());
+    }
+
+    if parts.is_empty() {
+        Ok(String::new())
+    } else {
+        Ok(format!("WITH {} ", parts.join(", ")))
+    }
+}
+
+/// Compile `left UNION right INTERSECT ...` as a compound of subqueries.
+///
+/// Each operand keeps its own `doc_type`, WHERE, ORDER BY and LIMIT. SQLite
+/// applies compound operators left to right, matching MKQL. `UNION` keeps
+/// duplicates; `INTERSECT`/`EXCEPT` compare whole rows, which for `SELECT *`
+/// means comparing documents by id.
+fn compile_set_ops(query: &MkqlQuery, ctx: &mut CompileCtx) -> Result<String, String> {
+    let mut sql = format!("SELECT * FROM ({})", compile_set_operand(query, ctx)?);
+    for set_op in &query.set_ops {
+        if set_op.query.select != query.select {
+            return Err(format!(
+                "{} operands must select the same fields",
+                set_op_sql(set_op.op)
+            ));
+        }
+        let operand = compile_set_operand(&set_op.query, ctx)?;
+        sql.push_str(&format!(
+            " {} SELECT * FROM ({operand})",
+            set_op_sql(set_op.op)
+        ));
+    }
+    Ok(sql)
+}
+
+fn compile_set_operand(query: &MkqlQuery, ctx: &mut CompileCtx) -> Result<String, String> {
+    match compile_select_stmt(query, ctx)? {
+        (sql, None) => Ok(sql),
+        (_, Some(_)) => Err(
+            "ORDER BY SIMILARITY() is not supported in set operations or WITH subqueries"
+                .to_string(),
+        ),
+    }
+}
+
+fn set_op_sql(op: SetOp) -> &'static str {
+    match op {
+        SetOp::Union => "UNION ALL",
+        SetOp::Intersect => "INTERSECT",
+        SetOp::Except => "EXCEPT",
+    }
+}
+
+/// Compile a single SELECT, ignoring `query.set_ops`.
+fn compile_select_stmt(
+    query: &MkqlQuery,
+    ctx: &mut CompileCtx,
+) -> Result<(String, Option<SimilarityOrder>), String> {
+    // ORDER BY SIMILARITY() can only lead: it re-sorts rows after SQL ordering
+    let similarity = match query.order_by.as_deref() {
+        Some(items) => {
+            if items
+                .iter()
+                .skip(1)
+                .any(|i| matches!(i.key, OrderKey::Similarity(_)))
+            {
+                return Err("SIMILARITY() must be the first ORDER BY item".to_string());
+            }
+            match items.first() {
+                Some(first) => match &first.key {
+                    OrderKey::Similarity(text) => Some(SimilarityOrder {
+                        text: text.clone(),
+                        descending: first.direction == SortDirection::Desc,
+                        limit: query.limit,
+                        offset: query.offset,
+                    }),
+                    OrderKey::Field(_) => None,
+                },
+                None => None,
+            }
+        }
+        None => None,
+    };
+
+    // Aggregates collapse each group (or every row, without GROUP BY) to one row
+    let aggregates: Vec<&SelectField> = match &query.select {
+        SelectClause::Fields(fields) => fields.iter().filter(|f| f.aggregate.is_some()).collect(),
+        SelectClause::Star => Vec::new(),
+    };
+    let grouped = !query.group_by.is_empty() || !aggregates.is_empty();
+    if grouped {
+        check_grouping(query, similarity.is_some())?;
+    }
 
     // SELECT clause
-    let select_sql = compile_select(&query.select);
+    let mut select_sql = match query.pivot {
+        Some(ref pivot) => compile_pivot_select(pivot),
+        None => compile_select(&query.select),
+    };
+    if similarity.is_some() {
+        select_sql.push_str(&format!(", d.id AS {SIMILARITY_ID_COLUMN}"));
+    }
+
+    // FROM clause: a WITH name reads that subquery, anything else is a type
+    let cte_source = ctx
+        .ctes
+        .iter()
+        .find(|n| n.eq_ignore_ascii_case(&query.from));
+    let from_sql = match cte_source {
+        Some(name) => format!("\"{name}\" d"),
+        None => "documents d".to_string(),
+    };
+
+    let reads_cte = cte_source.is_some();
 
-    // FROM clause
-    let from_sql = "documents d";
+    // Reserve doc_type as first parameter; a PIVOT without FROM spans every type
+    let doc_type_idx = match cte_source {
+        Some(_) => None,
+        None if query.from.is_empty() => None,
+        None => Some(ctx.next_param_for_type(&query.from)),
+    };
 
-    // Reserve doc_type as first parameter
-    let doc_type_idx = ctx.next_param_for_type(&query.from);
+    // Joins are per SELECT; other operands of a set operation may differ
+    let (prior_fts, prior_links) = (ctx.uses_fts, ctx.uses_links);
+    ctx.uses_fts = false;
+    ctx.uses_links = false;
 
     // WHERE clause
-    let where_sql = if let Some(ref wc) = query.where_clause {
-        let (sql, _) = compile_where(wc, &mut ctx)?;
-        format!(" WHERE d.doc_type = ?{doc_type_idx} AND {sql}")
+    let mut conditions = Vec::new();
+    if let Some(idx) = doc_type_idx {
+        conditions.push(format!("d.doc_type = ?{idx}"));
+        ctx.where_descriptions.push(format!(
+            "Document type filter: doc_type = ?{idx} ('{}')",
+            query.from
+        ));
+    }
+    if let Some(ref wc) = query.where_clause {
+        conditions.push(compile_where(wc, ctx)?.0);
+    }
+    let where_sql = if conditions.is_empty() {
+        String::new()
     } else {
-        format!(" WHERE d.doc_type = ?{doc_type_idx}")
+        format!(" WHERE {}", conditions.join(" AND "))
     };
 
-    // JOIN for FTS5
-    let fts_join = if ctx.uses_fts {
-        " JOIN documents_fts f ON d.rowid = f.rowid"
+    // GROUP BY / HAVING
+    let group_sql = if query.pivot.is_some() {
+        format!(" GROUP BY {PIVOT_ROW_KEY}, {PIVOT_COL_KEY}")
+    } else if query.group_by.is_empty() {
+        String::new()
     } else {
-        ""
+        let fields: Vec<String> = query.group_by.iter().map(|f| format!("d.{f}")).collect();
+        format!(" GROUP BY {}", fields.join(", "))
+    };
+    let having_sql = match query.having {
+        Some(ref having) => format!(" HAVING {}", compile_having(having, ctx)?),
+        None => String::new(),
+    };
+
+    // JOIN for FTS5; subquery rows have no rowid, so look it up by id
+    let fts_join = match (ctx.uses_fts, reads_cte) {
+        (false, _) => "",
+        (true, false) => " JOIN documents_fts f ON d.rowid = f.rowid",
+        (true, true) => {
+            " JOIN documents_fts f ON f.rowid = (SELECT rowid FROM documents WHERE id = d.id)"
+        }
     };
 
     // JOIN for links
@@ -74,62 +432,337 @@ pub fn compile(query: &MkqlQuery) -> Result<CompiledQuery, String> {
     };
 
     // ORDER BY
-    let order_sql = if let Some(ref items) = query.order_by {
-        let parts: Vec<String> = items
-            .iter()
-            .map(|item| {
+    let field_orders: Vec<String> = query
+        .order_by
+        .iter()
+        .flatten()
+        .filter_map(|item| match &item.key {
+            OrderKey::Field(field) => {
                 let dir = match item.direction {
                     SortDirection::Asc => "ASC",
                     SortDirection::Desc => "DESC",
                 };
-                format!("d.{} {dir}", item.field)
-            })
-            .collect();
-        format!(" ORDER BY {}", parts.join(", "))
-    } else {
+                // Aggregates are sorted by their output column
+                if aggregates.iter().any(|f| output_column(f) == *field) {
+                    Some(format!("{field} {dir}"))
+                } else {
+                    Some(format!("d.{field} {dir}"))
+                }
+            }
+            OrderKey::Similarity(_) => None,
+        })
+        .collect();
+    // A PIVOT is sorted when it is transposed
+    let order_sql = if query.pivot.is_some() {
+        String::new()
+    } else if !field_orders.is_empty() {
+        format!(" ORDER BY {}", field_orders.join(", "))
+    } else if !grouped {
         " ORDER BY d.observed_at DESC".to_string()
+    } else if query.group_by.is_empty() {
+        String::new()
+    } else {
+        let fields: Vec<String> = query.group_by.iter().map(|f| format!("d.{f}")).collect();
+        format!(" ORDER BY {}", fields.join(", "))
     };
 
-    // LIMIT / OFFSET
-    let limit_sql = match query.limit {
+    // LIMIT / OFFSET (deferred to the executor when ranking by similarity)
+    let (limit, offset) = if similarity.is_some() {
+        (None, None)
+    } else {
+        (query.limit, query.offset)
+    };
+    let limit_sql = match limit {
         Some(n) => format!(" LIMIT {n}"),
         None => String::new(),
     };
-    let offset_sql = match query.offset {
+    let offset_sql = match offset {
         Some(n) => format!(" OFFSET {n}"),
         None => String::new(),
     };
 
+    ctx.uses_fts |= prior_fts;
+    ctx.uses_links |= prior_links;
+
     let sql = format!(
-        "SELECT {select_sql} FROM {from_sql}{fts_join}{link_join}{where_sql}{order_sql}{limit_sql}{offset_sql}"
+        "SELECT {select_sql} FROM {from_sql}{fts_join}{link_join}{where_sql}{group_sql}{having_sql}{order_sql}{limit_sql}{offset_sql}"
     );
 
-    Ok(CompiledQuery {
-        sql,
-        params: ctx.params,
-        uses_fts: ctx.uses_fts,
-        uses_links: ctx.uses_links,
-        uses_semantic: ctx.uses_semantic,
-        near_params: ctx.near_params,
-    })
+    Ok((sql, similarity))
+}
+
+/// Reject grouped queries whose rows would not be one per group.
+fn check_grouping(query: &MkqlQuery, by_similarity: bool) -> Result<(), String> {
+    if by_similarity {
+        return Err("ORDER BY SIMILARITY() is not supported with GROUP BY or aggregates".into());
+    }
+    match &query.select {
+        SelectClause::Star => Err("SELECT * cannot be used with GROUP BY".to_string()),
+        SelectClause::Fields(fields) => match fields
+            .iter()
+            .find(|f| f.aggregate.is_none() && !query.group_by.contains(&f.name))
+        {
+            Some(field) => Err(format!(
+                "Field '{}' must appear in GROUP BY or be aggregated",
+                field.name
+            )),
+            None => Ok(()),
+        },
+    }
+}
+
+/// Name of the result column a SELECT field produces: its alias, else the
+/// field name, or e.g. `count` for `COUNT(*)` and `avg_confidence` for
+/// `AVG(confidence)`.
+pub fn output_column(field: &SelectField) -> String {
+    if let Some(ref alias) = field.alias {
+        return alias.clone();
+    }
+    match field.aggregate {
+        None => field.name.clone(),
+        Some(function) if field.name == "*" => function.to_string().to_lowercase(),
+        Some(function) => format!("{}_{}", function.to_string().to_lowercase(), field.name),
+    }
+}
+
+/// The grouped columns of a PIVOT: row key, column key and cell value.
+fn compile_pivot_select(pivot: &PivotClause) -> String {
+    format!(
+        "{} AS {PIVOT_ROW_KEY}, {} AS {PIVOT_COL_KEY}, {} AS {PIVOT_VALUE}",
+        compile_pivot_key(&pivot.row_field),
+        compile_pivot_key(&pivot.col_field),
+        compile_aggregate(pivot.agg_func, &pivot.agg_field)
+    )
+}
+
+fn compile_pivot_key(key: &PivotKey) -> String {
+    match key.bucket {
+        None => format!("d.{}", key.field),
+        Some(DateBucket::Month) => format!("strftime('%Y-%m', d.{})", key.field),
+        Some(DateBucket::Year) => format!("strftime('%Y', d.{})", key.field),
+    }
+}
+
+fn compile_aggregate(function: AggregateFunction, field: &str) -> String {
+    if field == "*" {
+        format!("{function}(*)")
+    } else {
+        format!("{function}(d.{field})")
+    }
+}
+
+/// Columns of the `documents` table that MKQL field references compile to.
+pub const DOCUMENT_FIELDS: &[&str] = &[
+    "id",
+    "doc_type",
+    "title",
+    "observed_at",
+    "valid_until",
+    "temporal_precision",
+    "occurred_at",
+    "occurred_until",
+    "created_at",
+    "modified_at",
+    "confidence",
+    "source",
+    "provenance_type",
+    "supersedes",
+    "superseded_by",
+    "tags",
+    "body",
+    "effective_confidence",
+];
+
+/// Check that every field a query names is a document column or is defined
+/// by the schema for its `FROM` type.
+///
+/// [`compile`] passes field names through to SQL, so an unknown field only
+/// fails once the query runs. This catches it statically, in the query
+/// itself, its `WITH` subqueries and set operands. `extends` chains are
+/// resolved against `schemas`; with no schemas only [`DOCUMENT_FIELDS`] are
+/// known. Selecting from a `WITH` name is not checked: its columns are
+/// whatever the subquery selects.
+///
+/// # Errors
+///
+/// Returns [`CompileError::UnknownField`] naming the first unknown field.
+pub fn validate_identifiers(
+    query: &MkqlQuery,
+    schemas: &[SchemaDefinition],
+) -> Result<(), CompileError> {
+    check_fields(query, schemas, &[])
+}
+
+fn check_fields(
+    query: &MkqlQuery,
+    schemas: &[SchemaDefinition],
+    outer_ctes: &[&str],
+) -> Result<(), CompileError> {
+    let mut ctes = outer_ctes.to_vec();
+    for cte in &query.ctes {
+        check_fields(&cte.query, schemas, &ctes)?;
+        ctes.push(&cte.name);
+    }
+    for set_op in &query.set_ops {
+        check_fields(&set_op.query, schemas, &ctes)?;
+    }
+    if ctes.iter().any(|c| c.eq_ignore_ascii_case(&query.from)) {
+        return Ok(());
+    }
+
+    // A PIVOT without FROM spans every type
+    let mut known: Vec<String> = DOCUMENT_FIELDS.iter().map(ToString::to_string).collect();
+    for schema in schemas
+        .iter()
+        .filter(|s| query.from.is_empty() || s.name == query.from)
+    {
+        let resolved = schema.resolve(schemas).unwrap_or_else(|_| schema.clone());
+        known.extend(resolved.fields.into_keys());
+    }
+
+    match query_fields(query)
+        .into_iter()
+        .find(|f| !known.iter().any(|k| k == f))
+    {
+        Some(unknown) => Err(CompileError::UnknownField {
+            field: unknown.to_string(),
+            doc_type: query.from.clone(),
+            suggestions: suggest_fields(unknown, &known),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Every field `query` names outside its `WITH` subqueries and set operands.
+fn query_fields(query: &MkqlQuery) -> Vec<&str> {
+    let mut fields: Vec<&str> = Vec::new();
+    let mut aggregate_aliases = Vec::new();
+    if let SelectClause::Fields(selected) = &query.select {
+        fields.extend(
+            selected
+                .iter()
+                .map(|f| f.name.as_str())
+                .filter(|name| *name != "*"),
+        );
+        aggregate_aliases.extend(
+            selected
+                .iter()
+                .filter(|f| f.aggregate.is_some())
+                .map(output_column),
+        );
+    }
+    if let Some(ref wc) = query.where_clause {
+        collect_where_fields(wc, &mut fields);
+    }
+    fields.extend(query.group_by.iter().map(String::as_str));
+    if let Some(ref pivot) = query.pivot {
+        fields.push(&pivot.row_field.field);
+        fields.push(&pivot.col_field.field);
+        if pivot.agg_field != "*" {
+            fields.push(&pivot.agg_field);
+        }
+    }
+    if let Some(ref having) = query.having {
+        collect_having_fields(having, &mut fields);
+    }
+    for item in query.order_by.iter().flatten() {
+        if let OrderKey::Field(field) = &item.key {
+            if !aggregate_aliases.contains(field) {
+                fields.push(field);
+            }
+        }
+    }
+    fields
+}
+
+/// Up to three of `known` within edit distance 2 of `field`, closest first.
+fn suggest_fields(field: &str, known: &[String]) -> Vec<String> {
+    let mut close: Vec<(usize, &String)> = known
+        .iter()
+        .map(|k| (levenshtein(field, k), k))
+        .filter(|(distance, _)| *distance <= 2)
+        .collect();
+    close.sort();
+    close.dedup_by(|a, b| a.1 == b.1);
+    close.into_iter().take(3).map(|(_, k)| k.clone()).collect()
+}
+
+/// Character-level edit distance between two strings.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+fn collect_where_fields<'a>(wc: &'a WhereClause, fields: &mut Vec<&'a str>) {
+    match wc {
+        WhereClause::Predicate(pred) => match pred {
+            Predicate::Comparison { field, .. }
+            | Predicate::InList { field, .. }
+            | Predicate::Like { field, .. }
+            | Predicate::Regex { field, .. } => fields.push(field),
+            Predicate::BodyContains { .. }
+            | Predicate::MatchPhrase { .. }
+            | Predicate::TagsContain { .. }
+            | Predicate::TagsEmpty
+            | Predicate::Temporal(_)
+            | Predicate::Linked(_)
+            | Predicate::LinkCount { .. }
+            | Predicate::Near { .. }
+            | Predicate::Annotated { .. } => {}
+        },
+        WhereClause::And(left, right) | WhereClause::Or(left, right) => {
+            collect_where_fields(left, fields);
+            collect_where_fields(right, fields);
+        }
+        WhereClause::Not(inner) => collect_where_fields(inner, fields),
+    }
+}
+
+fn collect_having_fields<'a>(having: &'a HavingClause, fields: &mut Vec<&'a str>) {
+    match having {
+        HavingClause::Predicate(pred) if pred.field != "*" => fields.push(&pred.field),
+        HavingClause::Predicate(_) => {}
+        HavingClause::And(left, right) | HavingClause::Or(left, right) => {
+            collect_having_fields(left, fields);
+            collect_having_fields(right, fields);
+        }
+        HavingClause::Not(inner) => collect_having_fields(inner, fields),
+    }
 }
 
 struct CompileCtx {
+    options: CompileOptions,
     params: Vec<SqlParam>,
     uses_fts: bool,
     uses_links: bool,
     uses_semantic: bool,
     near_params: Option<(String, f64)>,
+    where_descriptions: Vec<String>,
+    /// `WITH` names compiled so far, usable in `FROM`.
+    ctes: Vec<String>,
 }
 
 impl CompileCtx {
-    fn new() -> Self {
+    fn new(options: CompileOptions) -> Self {
         Self {
+            options,
             params: Vec::new(),
             uses_fts: false,
             uses_links: false,
             uses_semantic: false,
             near_params: None,
+            where_descriptions: Vec::new(),
+            ctes: Vec::new(),
         }
     }
 
@@ -149,9 +782,14 @@ fn compile_select(select: &SelectClause) -> String {
         SelectClause::Fields(fields) => {
             let parts: Vec<String> = fields
                 .iter()
-                .map(|f| match &f.alias {
-                    Some(alias) => format!("d.{} AS {alias}", f.name),
-                    None => format!("d.{}", f.name),
+                .map(|f| match (f.aggregate, &f.alias) {
+                    (Some(function), _) => format!(
+                        "{} AS {}",
+                        compile_aggregate(function, &f.name),
+                        output_column(f)
+                    ),
+                    (None, Some(alias)) => format!("d.{} AS {alias}", f.name),
+                    (None, None) => format!("d.{}", f.name),
                 })
                 .collect();
             parts.join(", ")
@@ -179,34 +817,150 @@ fn compile_where(wc: &WhereClause, ctx: &mut CompileCtx) -> Result<(String, bool
     }
 }
 
+fn compile_having(having: &HavingClause, ctx: &mut CompileCtx) -> Result<String, String> {
+    match having {
+        HavingClause::Predicate(pred) => compile_having_predicate(pred, ctx),
+        HavingClause::And(left, right) => {
+            let l = compile_having(left, ctx)?;
+            let r = compile_having(right, ctx)?;
+            Ok(format!("({l} AND {r})"))
+        }
+        HavingClause::Or(left, right) => {
+            let l = compile_having(left, ctx)?;
+            let r = compile_having(right, ctx)?;
+            Ok(format!("({l} OR {r})"))
+        }
+        HavingClause::Not(inner) => Ok(format!("NOT ({})", compile_having(inner, ctx)?)),
+    }
+}
+
+/// `AVG(confidence) >= 0.8` → `AVG(d.confidence) >= ?N`.
+fn compile_having_predicate(
+    pred: &HavingPredicate,
+    ctx: &mut CompileCtx,
+) -> Result<String, String> {
+    let aggregate = compile_aggregate(pred.function, &pred.field);
+    let op = compile_comp_op(&pred.op);
+    let rhs = match &pred.value {
+        // Stored timestamps are RFC 3339 and `datetime()` is not, so compare
+        // as day numbers rather than text, as in WHERE
+        Value::Date(expr) => {
+            let date = compile_date_expr(expr, ctx);
+            return Ok(format!("julianday({aggregate}) {op} julianday({date})"));
+        }
+        value => {
+            let mut param = value_to_param(value, &pred.field)?;
+            // A count is a number whatever field it counts
+            if let SqlParam::Named { expected, .. } = &mut param {
+                if pred.function == AggregateFunction::Count {
+                    *expected = ParamKind::Real;
+                }
+            }
+            format!("?{}", ctx.next_param(param))
+        }
+    };
+    Ok(format!("{aggregate} {op} {rhs}"))
+}
+
 fn compile_predicate(pred: &Predicate, ctx: &mut CompileCtx) -> Result<(String, bool), String> {
+    ctx.where_descriptions.push(describe_predicate(pred));
     match pred {
         Predicate::Comparison { field, op, value } => {
             let op_str = compile_comp_op(op);
-            let idx = ctx.next_param(value_to_param(value));
+            if let Value::Date(expr) = value {
+                let date = compile_date_expr(expr, ctx);
+                return Ok((
+                    format!("julianday(d.{field}) {op_str} julianday({date})"),
+                    false,
+                ));
+            }
+            let idx = ctx.next_param(value_to_param(value, field)?);
             Ok((format!("d.{field} {op_str} ?{idx}"), false))
         }
         Predicate::InList { field, values } => {
-            let placeholders: Vec<String> = values
+            let placeholders = values
                 .iter()
                 .map(|v| {
-                    let idx = ctx.next_param(value_to_param(v));
-                    format!("?{idx}")
+                    let idx = ctx.next_param(value_to_param(v, field)?);
+                    Ok(format!("?{idx}"))
                 })
-                .collect();
+                .collect::<Result<Vec<_>, String>>()?;
             Ok((format!("d.{field} IN ({})", placeholders.join(", ")), false))
         }
         Predicate::Like { field, pattern } => {
             let idx = ctx.next_param(SqlParam::Text(pattern.clone()));
             Ok((format!("d.{field} LIKE ?{idx}"), false))
         }
+        Predicate::Regex { field, pattern } => {
+            // Validate here so bad patterns fail at compile time, not per row
+            regex::Regex::new(pattern).map_err(|e| format!("Invalid regex '{pattern}': {e}"))?;
+            let idx = ctx.next_param(SqlParam::Text(pattern.clone()));
+            Ok((format!("d.{field} REGEXP ?{idx}"), false))
+        }
         Predicate::BodyContains { term } => {
             ctx.uses_fts = true;
             let idx = ctx.next_param(SqlParam::Text(term.clone()));
             Ok((format!("documents_fts MATCH ?{idx}"), true))
         }
+        Predicate::MatchPhrase { target, phrase } => {
+            // FTS5 phrase syntax: a double-quoted string, quotes doubled
+            let quoted = format!("\"{}\"", phrase.replace('"', "\"\""));
+            let query = match target {
+                PhraseTarget::Body => quoted,
+                PhraseTarget::Title => format!("title : {quoted}"),
+            };
+            ctx.uses_fts = true;
+            let idx = ctx.next_param(SqlParam::Text(query));
+            Ok((format!("documents_fts MATCH ?{idx}"), true))
+        }
+        Predicate::TagsContain { tags, mode } => {
+            // Tags are stored as "a, b, c"; wrapping the normalized list in
+            // commas makes each tag match only as a whole entry
+            let conditions: Vec<String> = tags
+                .iter()
+                .map(|tag| {
+                    let pattern = format!("%,{},%", escape_like(tag));
+                    let idx = ctx.next_param(SqlParam::Text(pattern));
+                    format!(
+                        "(',' || REPLACE(COALESCE(d.tags, ''), ', ', ',') || ',') \
+                         LIKE ?{idx} ESCAPE '\\'"
+                    )
+                })
+                .collect();
+            let joiner = match mode {
+                TagMatch::Any => " OR ",
+                TagMatch::All => " AND ",
+            };
+            Ok((format!("({})", conditions.join(joiner)), false))
+        }
+        Predicate::TagsEmpty => Ok(("COALESCE(d.tags, '') = ''".to_string(), false)),
         Predicate::Temporal(tf) => compile_temporal(tf, ctx),
         Predicate::Linked(lf) => compile_linked(lf, ctx),
+        Predicate::LinkCount {
+            reverse,
+            rel,
+            op,
+            count,
+        } => {
+            // Correlated subquery: the outer row's id is the link source
+            // (or target, for REVERSE_LINK_COUNT)
+            let column = if *reverse { "target_id" } else { "source_id" };
+            let rel_filter = match rel {
+                Some(r) => {
+                    let idx = ctx.next_param(SqlParam::Text(r.clone()));
+                    format!(" AND rel = ?{idx}")
+                }
+                None => String::new(),
+            };
+            let op_str = compile_comp_op(op);
+            let idx = ctx.next_param(SqlParam::Integer(*count));
+            Ok((
+                format!(
+                    "(SELECT COUNT(*) FROM links WHERE {column} = d.id{rel_filter}) {op_str} ?{idx}"
+                ),
+                false,
+            ))
+        }
         Predicate::Near { query, threshold } => {
             ctx.uses_semantic = true;
             ctx.near_params = Some((query.clone(), *threshold));
@@ -215,9 +969,145 @@ fn compile_predicate(pred: &Predicate, ctx: &mut CompileCtx) -> Result<(String,
             // then inject d.id IN (...) into the SQL)
             Ok(("1=1 /* NEAR placeholder */".to_string(), false))
         }
+        Predicate::Annotated { annotation_type } => {
+            let idx = ctx.next_param(SqlParam::Text(annotation_type.clone()));
+            Ok((
+                format!("d.id IN (SELECT doc_id FROM annotations WHERE annotation_type = ?{idx})"),
+                false,
+            ))
+        }
+    }
+}
+
+/// Describe a predicate in plain words for `explain` output.
+fn describe_predicate(pred: &Predicate) -> String {
+    match pred {
+        Predicate::Comparison { field, op, value } => {
+            format!("Field comparison: {field} {op} {value}")
+        }
+        Predicate::InList { field, values } => {
+            let list: Vec<String> = values.iter().map(ToString::to_string).collect();
+            format!("Field membership: {field} IN ({})", list.join(", "))
+        }
+        Predicate::Like { field, pattern } => format!("Pattern match: {field} LIKE '{pattern}'"),
+        Predicate::Regex { field, pattern } => {
+            format!("Regex match: {field} REGEXP '{pattern}'")
+        }
+        Predicate::BodyContains { term } => format!("Full-text search (FTS5): '{term}'"),
+        Predicate::MatchPhrase { target, phrase } => match target {
+            PhraseTarget::Body => format!("Phrase search (FTS5): \"{phrase}\""),
+            PhraseTarget::Title => format!("Title phrase search (FTS5): \"{phrase}\""),
+        },
+        Predicate::TagsContain { tags, mode } => {
+            let list: Vec<String> = tags.iter().map(|t| format!("'{t}'")).collect();
+            let quantifier = match mode {
+                TagMatch::Any => "any of",
+                TagMatch::All => "all of",
+            };
+            format!("Tag membership: {quantifier} {}", list.join(", "))
+        }
+        Predicate::TagsEmpty => "Tag membership: no tags".to_string(),
+        Predicate::Temporal(tf) => match tf {
+            TemporalFunction::Fresh { duration } => {
+                format!("Freshness: observed within the last {duration}")
+            }
+            TemporalFunction::Stale { duration } => {
+                format!("Staleness: not observed within the last {duration}")
+            }
+            TemporalFunction::Changed { duration } => {
+                format!("Recently changed: file modified within the last {duration}")
+            }
+            TemporalFunction::Expired => "Expired: valid_until is in the past".to_string(),
+            TemporalFunction::Current => {
+                "Current: not superseded and valid_until not yet reached".to_string()
+            }
+            TemporalFunction::Latest => "Latest: not superseded".to_string(),
+            TemporalFunction::AsOf { datetime } => {
+                format!("Point in time: observed by and still valid at {datetime}")
+            }
+            TemporalFunction::AsOfRange { start, end } => {
+                format!("Occurred range: overlaps {start} to {end}")
+            }
+            TemporalFunction::Overlaps { start, end } => {
+                format!("Validity overlap: valid at some point from {start} to {end}")
+            }
+            TemporalFunction::OverlapsNow => {
+                "Valid now: observed_at reached and valid_until not yet reached".to_string()
+            }
+            TemporalFunction::EffConfidence { op, threshold } => {
+                format!("Effective confidence: decayed confidence {op} {threshold}")
+            }
+        },
+        Predicate::Linked(lf) => match lf {
+            LinkedFunction::Forward { rel, target } => match target {
+                Some(t) => format!("Forward link: '{rel}' to '{t}'"),
+                None => format!("Forward link: '{rel}' to any document"),
+            },
+            LinkedFunction::Transitive {
+                rel_pattern,
+                max_depth,
+                target,
+            } => match target {
+                Some(t) => {
+                    format!("Transitive link: '{rel_pattern}' within {max_depth} hops to '{t}'")
+                }
+                None => format!("Transitive link: '{rel_pattern}' within {max_depth} hops"),
+            },
+            LinkedFunction::Reverse { rel, source } => match source {
+                Some(s) => format!("Reverse link: '{rel}' from '{s}'"),
+                None => format!("Reverse link: '{rel}' from any document"),
+            },
+        },
+        Predicate::LinkCount {
+            reverse,
+            rel,
+            op,
+            count,
+        } => {
+            let direction = if *reverse { "Incoming" } else { "Outgoing" };
+            match rel {
+                Some(r) => format!("{direction} link count: '{r}' links {op} {count}"),
+                None => format!("{direction} link count: all links {op} {count}"),
+            }
+        }
+        Predicate::Near { query, threshold } => {
+            format!("Semantic similarity: NEAR '{query}' with threshold {threshold}")
+        }
+        Predicate::Annotated { annotation_type } => {
+            format!("Annotation: has a '{annotation_type}' annotation")
+        }
     }
 }
 
+/// Escape `%`, `_` and `\` so `value` matches literally in `LIKE ... ESCAPE '\'`.
+fn escape_like(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Parse a `YYYY-MM-DD` or RFC 3339 literal into the form the index stores
+/// timestamps in, so it compares correctly as text. Dates mean midnight UTC.
+fn stored_timestamp(literal: &str, function: &str) -> Result<String, String> {
+    use chrono::{DateTime, NaiveDate, Utc};
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(literal) {
+        return Ok(dt.with_timezone(&Utc).to_rfc3339());
+    }
+    NaiveDate::parse_from_str(literal, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|midnight| midnight.and_utc().to_rfc3339())
+        .ok_or_else(|| {
+            format!("{function}: invalid date '{literal}' (expected YYYY-MM-DD or RFC 3339)")
+        })
+}
+
 /// Convert MKQL duration string (e.g. "7d", "24h", "30m") to SQLite modifier ("-7 days").
 fn duration_to_sqlite_modifier(duration: &str) -> Result<String, String> {
     let s = duration.trim();
@@ -240,23 +1130,44 @@ fn duration_to_sqlite_modifier(duration: &str) -> Result<String, String> {
     Ok(format!("-{n} {sqlite_unit}"))
 }
 
+/// `observed_at` is not after now. Compared as Julian days because stored
+/// RFC 3339 timestamps don't sort against SQLite's `datetime()` text.
+const NOT_FUTURE: &str = "julianday(d.observed_at) <= julianday('now')";
+
 fn compile_temporal(tf: &TemporalFunction, ctx: &mut CompileCtx) -> Result<(String, bool), String> {
     match tf {
         TemporalFunction::Fresh { duration } => {
             let cutoff = duration_to_sqlite_modifier(duration)?;
             let idx = ctx.next_param(SqlParam::Text(cutoff));
-            Ok((format!("d.observed_at >= datetime('now', ?{idx})"), false))
+            let fresh = format!("d.observed_at >= datetime('now', ?{idx})");
+            if ctx.options.future_in_fresh {
+                Ok((fresh, false))
+            } else {
+                Ok((format!("({fresh} AND {NOT_FUTURE})"), false))
+            }
         }
         TemporalFunction::Stale { duration } => {
             let cutoff = duration_to_sqlite_modifier(duration)?;
             let idx = ctx.next_param(SqlParam::Text(cutoff));
             Ok((format!("d.observed_at < datetime('now', ?{idx})"), false))
         }
+        TemporalFunction::Changed { duration } => {
+            let cutoff = duration_to_sqlite_modifier(duration)?;
+            let idx = ctx.next_param(SqlParam::Text(cutoff));
+            Ok((
+                format!("julianday(d.modified_at) >= julianday('now', ?{idx})"),
+                false,
+            ))
+        }
         TemporalFunction::Expired => Ok(("d.valid_until < datetime('now')".to_string(), false)),
-        TemporalFunction::Current => Ok((
-            "(d.superseded_by IS NULL AND d.valid_until >= datetime('now'))".to_string(),
-            false,
-        )),
+        TemporalFunction::Current => {
+            let current = "d.superseded_by IS NULL AND d.valid_until >= datetime('now')";
+            if ctx.options.future_in_current {
+                Ok((format!("({current})"), false))
+            } else {
+                Ok((format!("({current} AND {NOT_FUTURE})"), false))
+            }
+        }
         TemporalFunction::Latest => {
             // Latest: not superseded
             Ok(("d.superseded_by IS NULL".to_string(), false))
@@ -275,10 +1186,51 @@ fn compile_temporal(tf: &TemporalFunction, ctx: &mut CompileCtx) -> Result<(Stri
                 false,
             ))
         }
+        TemporalFunction::AsOfRange { start, end } => {
+            // Events without occurred_until are points; without occurred_at they
+            // happened when observed. The bounds are normalized to the stored
+            // RFC 3339 form so the columns compare as text, and the end
+            // expression matches idx_documents_type.
+            let start_idx = ctx.next_param(SqlParam::Text(stored_timestamp(start, "AS_OF_RANGE")?));
+            let end_idx = ctx.next_param(SqlParam::Text(stored_timestamp(end, "AS_OF_RANGE")?));
+            Ok((
+                format!(
+                    "(COALESCE(d.occurred_at, d.observed_at) <= ?{end_idx} \
+                     AND COALESCE(d.occurred_until, d.occurred_at, d.observed_at) >= ?{start_idx})"
+                ),
+                false,
+            ))
+        }
+        TemporalFunction::Overlaps { start, end } => {
+            let start_idx = ctx.next_param(SqlParam::Text(start.clone()));
+            let end_idx = ctx.next_param(SqlParam::Text(end.clone()));
+            Ok((
+                format!(
+                    "(julianday(d.observed_at) <= julianday(?{end_idx}) \
+                     AND julianday(d.valid_until) >= julianday(?{start_idx}))"
+                ),
+                false,
+            ))
+        }
+        TemporalFunction::OverlapsNow => Ok((
+            format!("({NOT_FUTURE} AND d.valid_until >= datetime('now'))"),
+            false,
+        )),
         TemporalFunction::EffConfidence { op, threshold } => {
+            // Decay stored confidence by age: confidence × e^(−ln 2 × age / half_life)
             let op_str = compile_comp_op(op);
+            let half_life_days =
+                DecayProfile::default_profile().half_life().num_seconds() as f64 / 86_400.0;
+            let hl_idx = ctx.next_param(SqlParam::Float(half_life_days));
             let idx = ctx.next_param(SqlParam::Float(*threshold));
-            Ok((format!("d.confidence {op_str} ?{idx}"), false))
+            Ok((
+                format!(
+                    "(d.confidence * exp(-0.6931471805599453 * \
+                     max(0.0, julianday('now') - julianday(d.observed_at)) / ?{hl_idx})) \
+                     {op_str} ?{idx}"
+                ),
+                false,
+            ))
         }
     }
 }
@@ -302,6 +1254,11 @@ fn compile_linked(lf: &LinkedFunction, ctx: &mut CompileCtx) -> Result<(String,
                 ))
             }
         }
+        LinkedFunction::Transitive {
+            rel_pattern,
+            max_depth,
+            target,
+        } => compile_transitive_link(rel_pattern, *max_depth, target.as_deref(), ctx),
         LinkedFunction::Reverse { rel, source } => {
             let idx_rel = ctx.next_param(SqlParam::Text(rel.clone()));
             if let Some(s) = source {
@@ -322,6 +1279,60 @@ fn compile_linked(lf: &LinkedFunction, ctx: &mut CompileCtx) -> Result<(String,
     }
 }
 
+/// Deepest chain `LINKED_DEPTH` may follow. Links may form cycles, so the
+/// recursion needs a bound.
+const MAX_LINK_DEPTH: u32 = 10;
+
+/// `d.id IN (...)` over a recursive CTE collecting every document that starts
+/// a chain of at most `max_depth` links whose relations match `rel_pattern`,
+/// ending at `target` when given.
+fn compile_transitive_link(
+    rel_pattern: &str,
+    max_depth: u32,
+    target: Option<&str>,
+    ctx: &mut CompileCtx,
+) -> Result<(String, bool), String> {
+    if !(1..=MAX_LINK_DEPTH).contains(&max_depth) {
+        return Err(format!(
+            "LINKED_DEPTH depth must be between 1 and {MAX_LINK_DEPTH}, got {max_depth}"
+        ));
+    }
+    let rels: Vec<&str> = rel_pattern.split('|').map(str::trim).collect();
+    if rels.iter().any(|r| r.is_empty()) {
+        return Err(format!(
+            "Empty relation in LINKED_DEPTH pattern '{rel_pattern}'"
+        ));
+    }
+
+    let rel_list = rels
+        .iter()
+        .map(|r| format!("?{}", ctx.next_param(SqlParam::Text((*r).to_string()))))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let target_filter = match target {
+        Some(t) => format!(
+            " AND target_id = ?{}",
+            ctx.next_param(SqlParam::Text(t.to_string()))
+        ),
+        None => String::new(),
+    };
+    let idx_depth = ctx.next_param(SqlParam::Integer(i64::from(max_depth)));
+
+    // UNION (not UNION ALL) drops repeated (id, depth) pairs, so cycles
+    // cost at most one row per document and depth
+    Ok((
+        format!(
+            "d.id IN (WITH RECURSIVE reachable(id, depth) AS (\
+             SELECT source_id, 1 FROM links WHERE rel IN ({rel_list}){target_filter} \
+             UNION \
+             SELECT l.source_id, r.depth + 1 FROM links l JOIN reachable r ON l.target_id = r.id \
+             WHERE l.rel IN ({rel_list}) AND r.depth < ?{idx_depth}) \
+             SELECT id FROM reachable)"
+        ),
+        false,
+    ))
+}
+
 fn compile_comp_op(op: &CompOp) -> &'static str {
     match op {
         CompOp::Eq => "=",
@@ -333,14 +1344,53 @@ fn compile_comp_op(op: &CompOp) -> &'static str {
     }
 }
 
-fn value_to_param(value: &Value) -> SqlParam {
-    match value {
+fn value_to_param(value: &Value, field: &str) -> Result<SqlParam, String> {
+    Ok(match value {
         Value::String(s) => SqlParam::Text(s.clone()),
         Value::Integer(i) => SqlParam::Integer(*i),
         Value::Float(f) => SqlParam::Float(*f),
         Value::Boolean(b) => SqlParam::Integer(i64::from(*b)),
         Value::Null => SqlParam::Null,
+        Value::Param(name) => SqlParam::Named {
+            name: name.clone(),
+            expected: ParamKind::for_field(field),
+        },
+        Value::Date(expr) => {
+            return Err(format!(
+                "Date expression '{expr}' is only supported in comparisons"
+            ))
+        }
+    })
+}
+
+/// Compile `NOW() - 7d` to `datetime('now', ?N)`, one modifier parameter
+/// per interval, applied in source order.
+fn compile_date_expr(expr: &DateExpr, ctx: &mut CompileCtx) -> String {
+    fn modifiers(expr: &DateExpr, out: &mut Vec<String>) {
+        let (base, sign, d) = match expr {
+            DateExpr::Now => return,
+            DateExpr::Add(base, d) => (base, '+', d),
+            DateExpr::Sub(base, d) => (base, '-', d),
+        };
+        modifiers(base, out);
+        let (amount, unit) = match d.unit {
+            DurationUnit::Days => (u64::from(d.amount), "days"),
+            DurationUnit::Weeks => (u64::from(d.amount) * 7, "days"),
+            DurationUnit::Months => (u64::from(d.amount), "months"),
+            DurationUnit::Years => (u64::from(d.amount), "years"),
+        };
+        out.push(format!("{sign}{amount} {unit}"));
+    }
+
+    let mut mods = Vec::new();
+    modifiers(expr, &mut mods);
+    let mut sql = "datetime('now'".to_string();
+    for m in mods {
+        let idx = ctx.next_param(SqlParam::Text(m));
+        sql.push_str(&format!(", ?{idx}"));
     }
+    sql.push(')');
+    sql
 }
 
 #[cfg(test)]
@@ -360,6 +1410,32 @@ mod tests {
         assert!(matches!(&compiled.params[1], SqlParam::Text(s) if s == "active"));
     }
 
+    #[test]
+    fn compile_named_param_to_placeholder() {
+        let query = parse_mkql("SELECT * FROM project WHERE confidence > :min").unwrap();
+        let compiled = compile(&query).unwrap();
+        assert!(compiled.sql.contains("d.confidence > ?2"));
+        assert!(matches!(
+            &compiled.params[1],
+            SqlParam::Named { name, expected: ParamKind::Real } if name == "min"
+        ));
+    }
+
+    #[test]
+    fn compile_date_arithmetic_to_modifiers() {
+        let query =
+            parse_mkql("SELECT * FROM project WHERE observed_at >= NOW() - 2w + 1mo").unwrap();
+        let compiled = compile(&query).unwrap();
+        assert!(compiled
+            .sql
+            .contains("julianday(d.observed_at) >= julianday(datetime('now', ?2, ?3))"));
+        assert!(matches!(&compiled.params[1], SqlParam::Text(s) if s == "-14 days"));
+        assert!(matches!(&compiled.params[2], SqlParam::Text(s) if s == "+1 months"));
+
+        let query = parse_mkql("SELECT * FROM project WHERE status IN (NOW())").unwrap();
+        assert!(compile(&query).is_err());
+    }
+
     #[test]
     fn compile_in_list_to_sql() {
         let query =
@@ -379,6 +1455,51 @@ mod tests {
         assert!(compiled.sql.contains("JOIN documents_fts"));
     }
 
+    #[test]
+    fn compile_match_phrase_binds_quoted_fts5_phrase() {
+        let query = parse_mkql(
+            "SELECT * FROM meeting WHERE BODY MATCH_PHRASE 'say \"hi\" now' \
+             AND TITLE MATCH_PHRASE 'Sprint Review'",
+        )
+        .unwrap();
+        let compiled = compile(&query).unwrap();
+        assert!(compiled.uses_fts);
+        assert!(compiled.sql.contains("JOIN documents_fts"));
+        let texts: Vec<&str> = compiled
+            .params
+            .iter()
+            .filter_map(|p| match p {
+                SqlParam::Text(t) => Some(t.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert!(texts.contains(&"\"say \"\"hi\"\" now\""), "{texts:?}");
+        assert!(texts.contains(&"title : \"Sprint Review\""), "{texts:?}");
+    }
+
+    #[test]
+    fn compile_tags_contains_binds_escaped_whole_tag_patterns() {
+        let query =
+            parse_mkql("SELECT * FROM project WHERE TAGS CONTAINS ANY ('rust', '100%')").unwrap();
+        let compiled = compile(&query).unwrap();
+        assert!(compiled.sql.contains(" OR "));
+        assert!(matches!(&compiled.params[1], SqlParam::Text(s) if s == "%,rust,%"));
+        assert!(matches!(&compiled.params[2], SqlParam::Text(s) if s == "%,100\\%,%"));
+    }
+
+    #[test]
+    fn compile_tags_in_binds_one_pattern_per_tag() {
+        let query = parse_mkql("SELECT * FROM project WHERE TAGS IN ('rust', 'ai')").unwrap();
+        let compiled = compile(&query).unwrap();
+        assert!(!compiled.sql.contains("rust"));
+        assert!(compiled.sql.contains(" OR "));
+        assert!(matches!(&compiled.params[2], SqlParam::Text(s) if s == "%,ai,%"));
+
+        let query =
+            parse_mkql("SELECT * FROM project WHERE TAGS INCLUDES ALL ('rust', 'ai')").unwrap();
+        assert!(compile(&query).unwrap().sql.contains(" AND "));
+    }
+
     #[test]
     fn compile_parameterizes_values() {
         let query = parse_mkql("SELECT * FROM project WHERE status = 'active'").unwrap();
@@ -397,6 +1518,19 @@ mod tests {
         assert!(compiled.sql.contains("d.observed_at >= datetime('now'"));
     }
 
+    #[test]
+    fn compile_changed_to_modified_at_range() {
+        let query = parse_mkql("SELECT * FROM project WHERE CHANGED('7d')").unwrap();
+        let compiled = compile(&query).unwrap();
+        assert!(compiled
+            .sql
+            .contains("julianday(d.modified_at) >= julianday('now', ?"));
+        assert!(compiled
+            .params
+            .iter()
+            .any(|p| matches!(p, SqlParam::Text(t) if t == "-7 days")));
+    }
+
     #[test]
     fn compile_current_excludes_superseded_and_expired() {
         let query = parse_mkql("SELECT * FROM project WHERE CURRENT()").unwrap();
@@ -405,15 +1539,221 @@ mod tests {
         assert!(compiled.sql.contains("valid_until >= datetime('now')"));
     }
 
+    #[test]
+    fn compile_as_of_range_to_overlap_check() {
+        let query = parse_mkql("SELECT * FROM event WHERE AS_OF_RANGE('2025-01-01', '2025-01-07')")
+            .unwrap();
+        let compiled = compile(&query).unwrap();
+        assert!(compiled
+            .sql
+            .contains("COALESCE(d.occurred_until, d.occurred_at, d.observed_at) >= ?2"));
+        assert!(!compiled.sql.contains("julianday"));
+        assert!(
+            matches!(&compiled.params[1], SqlParam::Text(s) if s == "2025-01-01T00:00:00+00:00")
+        );
+        assert!(
+            matches!(&compiled.params[2], SqlParam::Text(s) if s == "2025-01-07T00:00:00+00:00")
+        );
+
+        let offset = parse_mkql(
+            "SELECT * FROM event WHERE AS_OF_RANGE('2025-01-01T02:00:00+02:00', '2025-01-07')",
+        )
+        .unwrap();
+        assert!(
+            matches!(&compile(&offset).unwrap().params[1], SqlParam::Text(s) if s == "2025-01-01T00:00:00+00:00")
+        );
+
+        for bad in ["next week", "2025-13-01", "2025-01-01 10:00"] {
+            let query = parse_mkql(&format!(
+                "SELECT * FROM event WHERE AS_OF_RANGE('{bad}', '2025-01-07')"
+            ))
+            .unwrap();
+            let err = compile(&query).unwrap_err();
+            assert!(err.contains("invalid date"), "{bad}: {err}");
+        }
+    }
+
+    #[test]
+    fn compile_overlaps_to_validity_window_check() {
+        let compiled = compile(
+            &parse_mkql("SELECT * FROM project WHERE OVERLAPS('2025-01-01', '2025-03-31')")
+                .unwrap(),
+        )
+        .unwrap();
+        assert!(compiled.sql.contains(
+            "(julianday(d.observed_at) <= julianday(?3) AND julianday(d.valid_until) >= julianday(?2))"
+        ));
+        assert!(matches!(&compiled.params[1], SqlParam::Text(s) if s == "2025-01-01"));
+        assert!(matches!(&compiled.params[2], SqlParam::Text(s) if s == "2025-03-31"));
+
+        let now =
+            compile(&parse_mkql("SELECT * FROM project WHERE OVERLAPS_NOW()").unwrap()).unwrap();
+        assert!(now.sql.contains(
+            "(julianday(d.observed_at) <= julianday('now') AND d.valid_until >= datetime('now'))"
+        ));
+        assert!(!now.sql.contains("superseded_by"));
+    }
+
+    #[test]
+    fn compile_future_observation_options() {
+        let fresh = parse_mkql("SELECT * FROM project WHERE FRESH('7d')").unwrap();
+        let current = parse_mkql("SELECT * FROM project WHERE CURRENT()").unwrap();
+
+        // Default: future observations are current but not fresh
+        assert!(compile(&fresh).unwrap().sql.contains(NOT_FUTURE));
+        assert!(!compile(&current).unwrap().sql.contains(NOT_FUTURE));
+
+        let flipped = CompileOptions {
+            future_in_current: false,
+            future_in_fresh: true,
+            ..CompileOptions::default()
+        };
+        let compiled = compile_with_options(&fresh, flipped.clone()).unwrap();
+        assert!(!compiled.sql.contains(NOT_FUTURE));
+        let compiled = compile_with_options(&current, flipped).unwrap();
+        assert!(compiled.sql.contains(NOT_FUTURE));
+    }
+
+    fn strict() -> CompileOptions {
+        CompileOptions {
+            validate_fields: true,
+            schemas: mkb_core::schema::built_in_schemas(),
+            ..CompileOptions::default()
+        }
+    }
+
+    #[test]
+    fn compile_strict_accepts_columns_and_schema_fields() {
+        for mkql in [
+            "SELECT * FROM project WHERE status = 'active' AND title LIKE '%api%'",
+            "SELECT * FROM meeting WHERE attendees LIKE '%ana%'",
+            "WITH a AS (SELECT * FROM project WHERE confidence > 0.5) \
+             SELECT * FROM a WHERE anything = 'x'",
+        ] {
+            let query = parse_mkql(mkql).unwrap();
+            assert!(compile_with_options(&query, strict()).is_ok(), "{mkql}");
+        }
+    }
+
+    #[test]
+    fn compile_strict_rejects_unknown_fields_with_suggestions() {
+        let query = parse_mkql("SELECT * FROM project WHERE stauts = 'active'").unwrap();
+        let err = compile_with_options(&query, strict()).unwrap_err();
+        assert_eq!(
+            err,
+            CompileError::UnknownField {
+                field: "stauts".to_string(),
+                doc_type: "project".to_string(),
+                suggestions: vec!["status".to_string()],
+            }
+        );
+        assert!(err.to_string().contains("did you mean: status?"));
+
+        // Set operands are checked against their own type
+        let query = parse_mkql(
+            "SELECT * FROM project WHERE status = 'active' \
+             UNION SELECT * FROM meeting WHERE status = 'active'",
+        )
+        .unwrap();
+        assert!(matches!(
+            compile_with_options(&query, strict()),
+            Err(CompileError::UnknownField { doc_type, .. }) if doc_type == "meeting"
+        ));
+    }
+
+    #[test]
+    fn compile_without_strict_ignores_unknown_fields() {
+        let query = parse_mkql("SELECT * FROM project WHERE nonexistent = 'x'").unwrap();
+        assert!(compile(&query).is_ok());
+        let no_schemas = CompileOptions {
+            validate_fields: true,
+            ..CompileOptions::default()
+        };
+        assert!(compile_with_options(&query, no_schemas).is_ok());
+    }
+
+    #[test]
+    fn compile_regex_to_regexp_operator() {
+        let query = parse_mkql("SELECT * FROM meeting WHERE title REGEX '^Sprint [0-9]+'").unwrap();
+        let compiled = compile(&query).unwrap();
+        assert!(compiled.sql.contains("d.title REGEXP ?2"));
+        assert!(matches!(&compiled.params[1], SqlParam::Text(p) if p == "^Sprint [0-9]+"));
+    }
+
+    #[test]
+    fn compile_invalid_regex_fails_at_compile_time() {
+        let query = parse_mkql("SELECT * FROM meeting WHERE title REGEX 'Sprint (['").unwrap();
+        let err = compile(&query).unwrap_err();
+        assert!(err.contains("Invalid regex"), "got: {err}");
+    }
+
+    #[test]
+    fn compile_similarity_order_defers_limit_to_executor() {
+        let query = parse_mkql(
+            "SELECT title FROM project ORDER BY SIMILARITY('machine learning') DESC LIMIT 5",
+        )
+        .unwrap();
+        let compiled = compile(&query).unwrap();
+        assert!(!compiled.sql.contains("LIMIT"));
+        assert!(compiled
+            .sql
+            .contains(&format!("d.id AS {SIMILARITY_ID_COLUMN}")));
+        assert_eq!(
+            compiled.similarity_order,
+            Some(SimilarityOrder {
+                text: "machine learning".to_string(),
+                descending: true,
+                limit: Some(5),
+                offset: None,
+            })
+        );
+    }
+
+    #[test]
+    fn compile_similarity_must_lead_order_by() {
+        let query =
+            parse_mkql("SELECT * FROM project ORDER BY title, SIMILARITY('ml') DESC").unwrap();
+        assert!(compile(&query).is_err());
+    }
+
     #[test]
     fn compile_eff_confidence_with_decay() {
         let query = parse_mkql("SELECT * FROM project WHERE EFF_CONFIDENCE(> 0.5)").unwrap();
         let compiled = compile(&query).unwrap();
-        assert!(compiled.sql.contains("d.confidence >"));
+        assert!(compiled.sql.contains("d.confidence * exp("));
+        assert!(compiled.sql.contains("julianday(d.observed_at)"));
+        // doc_type, half-life (days), threshold
+        assert_eq!(compiled.params.len(), 3);
+        assert!(matches!(compiled.params[1], SqlParam::Float(hl) if (hl - 90.0).abs() < 1e-9));
     }
 
     // === T-210.3: Link clause compilation ===
 
+    #[test]
+    fn compile_link_count_to_correlated_subquery() {
+        let query = parse_mkql("SELECT * FROM project WHERE LINK_COUNT() > 5").unwrap();
+        let compiled = compile(&query).unwrap();
+        assert!(compiled
+            .sql
+            .contains("(SELECT COUNT(*) FROM links WHERE source_id = d.id) > ?2"));
+        assert!(matches!(compiled.params[1], SqlParam::Integer(5)));
+        assert!(!compiled.uses_links);
+
+        let query =
+            parse_mkql("SELECT * FROM person WHERE REVERSE_LINK_COUNT('owner') >= 2").unwrap();
+        let compiled = compile(&query).unwrap();
+        assert!(compiled
+            .sql
+            .contains("(SELECT COUNT(*) FROM links WHERE target_id = d.id AND rel = ?2) >= ?3"));
+        assert_eq!(
+            compiled.where_descriptions,
+            vec![
+                "Document type filter: doc_type = ?1 ('person')",
+                "Incoming link count: 'owner' links >= 2"
+            ]
+        );
+    }
+
     #[test]
     fn compile_forward_link_to_join() {
         let query = parse_mkql("SELECT * FROM project WHERE LINKED('owner')").unwrap();
@@ -441,6 +1781,89 @@ mod tests {
         assert_eq!(compiled.params.len(), 1);
     }
 
+    #[test]
+    fn compile_set_ops_number_params_across_operands() {
+        let query = parse_mkql(
+            "SELECT * FROM project WHERE status = 'active' \
+             UNION SELECT * FROM decision WHERE BODY CONTAINS 'api'",
+        )
+        .unwrap();
+        let compiled = compile(&query).unwrap();
+        assert!(compiled
+            .sql
+            .starts_with("SELECT * FROM (SELECT d.* FROM documents d"));
+        assert!(compiled.sql.contains(
+            ") UNION ALL SELECT * FROM (SELECT d.* FROM documents d JOIN documents_fts f"
+        ));
+        assert_eq!(compiled.sql.matches("JOIN documents_fts").count(), 1);
+        assert!(compiled.sql.contains("d.doc_type = ?1"));
+        assert!(compiled.sql.contains("d.doc_type = ?3"));
+        assert_eq!(compiled.params.len(), 4);
+    }
+
+    #[test]
+    fn compile_set_ops_reject_mismatched_operands() {
+        let err = compile(
+            &parse_mkql("SELECT id FROM project INTERSECT SELECT * FROM decision").unwrap(),
+        )
+        .unwrap_err();
+        assert!(err.contains("same fields"), "{err}");
+
+        let err = compile(
+            &parse_mkql(
+                "SELECT * FROM project EXCEPT SELECT * FROM project ORDER BY SIMILARITY('x')",
+            )
+            .unwrap(),
+        )
+        .unwrap_err();
+        assert!(err.contains("SIMILARITY"), "{err}");
+    }
+
+    #[test]
+    fn compile_with_clause_reads_cte_instead_of_type() {
+        let query = parse_mkql(
+            "WITH active AS (SELECT * FROM project WHERE status = 'active') \
+             SELECT * FROM active WHERE BODY CONTAINS 'api'",
+        )
+        .unwrap();
+        let compiled = compile(&query).unwrap();
+        assert!(compiled.sql.starts_with(
+            "WITH \"active\" AS (SELECT d.* FROM documents d WHERE d.doc_type = ?1 AND d.status = ?2"
+        ));
+        assert!(compiled.sql.contains(
+            ") SELECT d.* FROM \"active\" d JOIN documents_fts f ON f.rowid = \
+             (SELECT rowid FROM documents WHERE id = d.id) WHERE documents_fts MATCH ?3"
+        ));
+        assert_eq!(compiled.params.len(), 3);
+        assert!(compiled.uses_fts);
+    }
+
+    #[test]
+    fn compile_with_clause_rejects_bad_names() {
+        for (mkql, expected) in [
+            (
+                "WITH links AS (SELECT * FROM project) SELECT * FROM links",
+                "reserved",
+            ),
+            (
+                "WITH annotations AS (SELECT * FROM project) \
+                 SELECT * FROM project WHERE ANNOTATED('reviewed')",
+                "reserved",
+            ),
+            (
+                "WITH a AS (SELECT * FROM project), A AS (SELECT * FROM meeting) SELECT * FROM a",
+                "defined twice",
+            ),
+            (
+                "WITH a AS (SELECT * FROM project ORDER BY SIMILARITY('x')) SELECT * FROM a",
+                "SIMILARITY",
+            ),
+        ] {
+            let err = compile(&parse_mkql(mkql).unwrap()).unwrap_err();
+            assert!(err.contains(expected), "{mkql}: {err}");
+        }
+    }
+
     #[test]
     fn compile_select_specific_fields() {
         let query = parse_mkql("SELECT title, status FROM project").unwrap();
@@ -448,6 +1871,98 @@ mod tests {
         assert!(compiled.sql.contains("d.title, d.status"));
     }
 
+    #[test]
+    fn compile_group_by_with_having() {
+        let query = parse_mkql(
+            "SELECT status, COUNT(*), AVG(confidence) AS conf FROM project \
+             WHERE CURRENT() GROUP BY status HAVING COUNT(*) > 1 OR AVG(confidence) >= :min",
+        )
+        .unwrap();
+        let compiled = compile(&query).unwrap();
+        assert!(compiled.sql.starts_with(
+            "SELECT d.status, COUNT(*) AS count, AVG(d.confidence) AS conf FROM documents d"
+        ));
+        assert!(compiled.sql.contains(
+            " GROUP BY d.status HAVING (COUNT(*) > ?2 OR AVG(d.confidence) >= ?3) ORDER BY d.status"
+        ));
+        assert!(matches!(compiled.params[1], SqlParam::Integer(1)));
+        assert!(matches!(
+            compiled.params[2],
+            SqlParam::Named {
+                expected: ParamKind::Real,
+                ..
+            }
+        ));
+
+        let recent = compile(
+            &parse_mkql(
+                "SELECT doc_type, MAX(observed_at) FROM project GROUP BY doc_type \
+                 HAVING MAX(observed_at) >= NOW() - 7d",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(recent
+            .sql
+            .contains(" HAVING julianday(MAX(d.observed_at)) >= julianday(datetime('now', ?2))"));
+
+        let ordered = compile(
+            &parse_mkql("SELECT source, COUNT(*) FROM signal GROUP BY source ORDER BY count DESC")
+                .unwrap(),
+        )
+        .unwrap();
+        assert!(ordered
+            .sql
+            .ends_with(" GROUP BY d.source ORDER BY count DESC"));
+    }
+
+    #[test]
+    fn compile_group_by_rejects_ungrouped_selects() {
+        for (mkql, expected) in [
+            ("SELECT * FROM project GROUP BY status", "SELECT *"),
+            (
+                "SELECT title, COUNT(*) FROM project GROUP BY status",
+                "Field 'title' must appear in GROUP BY",
+            ),
+            (
+                "SELECT status, COUNT(*) FROM project GROUP BY status ORDER BY SIMILARITY('x')",
+                "SIMILARITY",
+            ),
+        ] {
+            let err = compile(&parse_mkql(mkql).unwrap()).unwrap_err();
+            assert!(err.contains(expected), "{mkql}: {err}");
+        }
+    }
+
+    #[test]
+    fn compile_pivot_groups_by_both_keys() {
+        let compiled =
+            compile(&parse_mkql("SELECT PIVOT doc_type BY month(observed_at) COUNT(*)").unwrap())
+                .unwrap();
+        assert_eq!(
+            compiled.sql,
+            "SELECT d.doc_type AS __pivot_row, strftime('%Y-%m', d.observed_at) AS __pivot_col, \
+             COUNT(*) AS __pivot_value FROM documents d GROUP BY __pivot_row, __pivot_col"
+        );
+        assert!(compiled.params.is_empty());
+        assert_eq!(compiled.pivot, Some(AggregateFunction::Count));
+
+        let typed = compile(
+            &parse_mkql("SELECT PIVOT status BY year(observed_at) SUM(confidence) FROM project")
+                .unwrap(),
+        )
+        .unwrap();
+        assert!(typed.sql.contains("SUM(d.confidence) AS __pivot_value"));
+        assert!(typed.sql.contains("WHERE d.doc_type = ?1 GROUP BY"));
+
+        let err = validate_identifiers(
+            &parse_mkql("SELECT PIVOT doc_type BY month(bogus) COUNT(*)").unwrap(),
+            &[],
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("'bogus'"), "{err}");
+    }
+
     // === T-210.4: NEAR compilation ===
 
     #[test]
@@ -472,6 +1987,23 @@ mod tests {
         assert!(compiled.sql.contains("d.status ="));
     }
 
+    #[test]
+    fn compile_records_where_descriptions() {
+        let query = parse_mkql("SELECT * FROM project WHERE status = 'active' AND LINKED('owner')")
+            .unwrap();
+        let compiled = compile(&query).unwrap();
+        assert_eq!(compiled.where_descriptions.len(), 3);
+        assert_eq!(
+            compiled.where_descriptions[0],
+            "Document type filter: doc_type = ?1 ('project')"
+        );
+        assert_eq!(
+            compiled.where_descriptions[1],
+            "Field comparison: status = 'active'"
+        );
+        assert!(compiled.where_descriptions[2].contains("Forward link"));
+    }
+
     #[test]
     fn compile_order_by_and_limit() {
         let query = parse_mkql("SELECT * FROM project ORDER BY observed_at DESC LIMIT 10").unwrap();
@@ -479,4 +2011,56 @@ mod tests {
         assert!(compiled.sql.contains("ORDER BY d.observed_at DESC"));
         assert!(compiled.sql.contains("LIMIT 10"));
     }
+
+    #[test]
+    fn validate_identifiers_accepts_document_columns() {
+        let query = parse_mkql(
+            "SELECT title, confidence AS c FROM project \
+             WHERE tags LIKE '%rust%' AND NOT source IN ('a', 'b') AND FRESH('7d') \
+             ORDER BY observed_at DESC",
+        )
+        .unwrap();
+        assert!(validate_identifiers(&query, &[]).is_ok());
+    }
+
+    #[test]
+    fn validate_identifiers_rejects_unknown_fields() {
+        for mkql in [
+            "SELECT titel FROM project",
+            "SELECT * FROM project WHERE status = 'active'",
+            "SELECT * FROM project WHERE confidence > 0.5 OR owner REGEX 'a'",
+            "SELECT * FROM project ORDER BY priority",
+        ] {
+            let err = validate_identifiers(&parse_mkql(mkql).unwrap(), &[]).unwrap_err();
+            assert!(
+                matches!(err, CompileError::UnknownField { .. }),
+                "{mkql}: {err}"
+            );
+        }
+    }
+
+    #[test]
+    fn validate_identifiers_accepts_schema_fields_for_the_queried_type() {
+        let schemas = mkb_core::schema::built_in_schemas();
+        for mkql in [
+            "SELECT title, status FROM project WHERE status = 'active' ORDER BY status",
+            "SELECT status, COUNT(*) FROM project GROUP BY status",
+            "WITH a AS (SELECT * FROM project WHERE status = 'active') \
+             SELECT * FROM a WHERE anything = 'x'",
+        ] {
+            let query = parse_mkql(mkql).unwrap();
+            assert!(validate_identifiers(&query, &schemas).is_ok(), "{mkql}");
+        }
+
+        let query = parse_mkql("SELECT * FROM project ORDER BY stauts").unwrap();
+        let err = validate_identifiers(&query, &schemas).unwrap_err();
+        assert_eq!(
+            err,
+            CompileError::UnknownField {
+                field: "stauts".to_string(),
+                doc_type: "project".to_string(),
+                suggestions: vec!["status".to_string()],
+            }
+        );
+    }
 }
@@ -3,15 +3,64 @@
 //! Assembles query results into a format suitable for LLM consumption,
 //! prioritizing high-confidence fresh documents and respecting token budgets.
 
+use chrono::{DateTime, Utc};
+use mkb_core::document::Document;
+
 use crate::formatter::{QueryResult, ResultRow};
 
+/// Counts tokens in text the way the target model will see it.
+pub trait Tokenizer {
+    /// Number of tokens `text` encodes to.
+    fn count_tokens(&self, text: &str) -> usize;
+}
+
+/// The `cl100k_base` byte-pair encoding used by GPT-4 class models.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Cl100kTokenizer;
+
+impl Tokenizer for Cl100kTokenizer {
+    fn count_tokens(&self, text: &str) -> usize {
+        tiktoken_rs::cl100k_base_singleton()
+            .encode_with_special_tokens(text)
+            .len()
+    }
+}
+
+/// Counts whitespace-separated words. A cheap fallback that undercounts
+/// real BPE tokens, so leave headroom in the budget when using it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WhitespaceTokenizer;
+
+impl Tokenizer for WhitespaceTokenizer {
+    fn count_tokens(&self, text: &str) -> usize {
+        text.split_whitespace().count()
+    }
+}
+
+/// Look up a tokenizer by name: `cl100k_base` or `whitespace`.
+#[must_use]
+pub fn tokenizer_by_name(name: &str) -> Option<&'static dyn Tokenizer> {
+    match name {
+        "cl100k_base" => Some(&Cl100kTokenizer),
+        "whitespace" => Some(&WhitespaceTokenizer),
+        _ => None,
+    }
+}
+
 /// Options for context assembly.
 #[derive(Debug, Clone)]
 pub struct ContextOpts {
-    /// Maximum number of tokens (estimated at ~4 chars per token).
+    /// Maximum number of tokens, as counted by the assembler's tokenizer.
     pub max_tokens: usize,
-    /// Whether to use summary format when budget is tight.
+    /// Whether to use summary format when not even one full document fits.
     pub allow_summary: bool,
+    /// Leave out documents whose `valid_until` has passed. When included,
+    /// their sections are marked `[EXPIRED]`.
+    pub exclude_expired: bool,
+    /// Leave out documents that have a `superseded_by`.
+    pub exclude_superseded: bool,
+    /// Leave out documents with a lower confidence.
+    pub min_confidence: f64,
 }
 
 impl Default for ContextOpts {
@@ -19,29 +68,142 @@ impl Default for ContextOpts {
         Self {
             max_tokens: 4000,
             allow_summary: true,
+            exclude_expired: true,
+            exclude_superseded: true,
+            min_confidence: 0.0,
+        }
+    }
+}
+
+impl ContextOpts {
+    /// Whether a document passes the `exclude_*` and `min_confidence`
+    /// filters. A document of unknown confidence passes.
+    fn admits(&self, expired: bool, superseded: bool, confidence: Option<f64>) -> bool {
+        if (expired && self.exclude_expired) || (superseded && self.exclude_superseded) {
+            return false;
         }
+        confidence.is_none_or(|c| c >= self.min_confidence)
     }
 }
 
+/// Days after which a document's recency score halves.
+const RECENCY_HALF_LIFE_DAYS: f64 = 30.0;
+
+/// How much each signal counts towards a document's context priority.
+///
+/// Every signal is scaled to `[0, 1]` before weighting, so the weights
+/// compare directly.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriorityWeights {
+    /// Weight of how recently the document was observed.
+    pub recency_weight: f64,
+    /// Weight of the document's stated confidence.
+    pub confidence_weight: f64,
+    /// Weight of closeness to the seed document in the link graph.
+    pub link_proximity_weight: f64,
+    /// Weight of the full-text search rank.
+    pub fts_rank_weight: f64,
+}
+
+impl Default for PriorityWeights {
+    fn default() -> Self {
+        Self {
+            recency_weight: 0.3,
+            confidence_weight: 0.5,
+            link_proximity_weight: 0.1,
+            fts_rank_weight: 0.1,
+        }
+    }
+}
+
+impl PriorityWeights {
+    /// Weighted priority of `doc` as of `now`.
+    ///
+    /// - Recency is `1 / (1 + days / 30)` for the days since `observed_at`.
+    /// - Confidence is used as is.
+    /// - Link proximity is `1 / hops` from the seed document (the seed itself
+    ///   counts as one hop); `None` means unreachable and scores 0.
+    /// - `fts_rank` should already be in `[0, 1]`, e.g. from
+    ///   [`normalize_fts_ranks`]; `None` scores 0.
+    #[must_use]
+    pub fn score(
+        &self,
+        doc: &Document,
+        hops: Option<usize>,
+        fts_rank: Option<f64>,
+        now: DateTime<Utc>,
+    ) -> f64 {
+        let age_days = (now - doc.temporal.observed_at).num_days().max(0) as f64;
+        let recency = 1.0 / (1.0 + age_days / RECENCY_HALF_LIFE_DAYS);
+        let proximity = hops.map_or(0.0, |h| 1.0 / h.max(1) as f64);
+        let fts = fts_rank.unwrap_or(0.0).clamp(0.0, 1.0);
+
+        self.recency_weight * recency
+            + self.confidence_weight * doc.confidence
+            + self.link_proximity_weight * proximity
+            + self.fts_rank_weight * fts
+    }
+}
+
+/// Scale FTS5 `bm25()` ranks to `[0, 1]`, best match first.
+///
+/// FTS5 ranks are negative and lower is better, so the lowest rank maps to
+/// 1 and the highest to 0. If all ranks are equal, every entry gets 1.
+#[must_use]
+pub fn normalize_fts_ranks(ranks: &[f64]) -> Vec<f64> {
+    let best = ranks.iter().copied().fold(f64::INFINITY, f64::min);
+    let worst = ranks.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let spread = worst - best;
+    ranks
+        .iter()
+        .map(|&r| {
+            if spread > 0.0 {
+                (worst - r) / spread
+            } else {
+                1.0
+            }
+        })
+        .collect()
+}
+
 /// Assembles query results into LLM-consumable context.
 pub struct ContextAssembler;
 
 impl ContextAssembler {
-    /// Assemble results into a context string, respecting the token budget.
+    /// Assemble results into a context string of at most `opts.max_tokens`
+    /// tokens.
     ///
     /// Documents are prioritized by:
     /// 1. Higher confidence first
     /// 2. More recent `observed_at` first
     ///
-    /// If the full format exceeds the budget, falls back to summary format.
+    /// Rows are first filtered by the `exclude_*` and `min_confidence`
+    /// options, judged from their `valid_until`, `superseded_by` and
+    /// `confidence` columns; a row without the column is kept.
+    ///
+    /// Whole documents are added in that order until the next one doesn't
+    /// fit. That document's body is then cut at the last sentence boundary
+    /// that fits, and assembly stops. If not even the first document fits
+    /// whole, the summary format is used instead when allowed.
     #[must_use]
-    pub fn assemble(result: &QueryResult, opts: &ContextOpts) -> String {
-        if result.rows.is_empty() {
+    pub fn assemble(result: &QueryResult, opts: &ContextOpts, tokenizer: &dyn Tokenizer) -> String {
+        let now = Utc::now();
+        let mut sorted: Vec<&ResultRow> = result
+            .rows
+            .iter()
+            .filter(|row| {
+                opts.admits(
+                    row_is_expired(row, now),
+                    !str_field(row, "superseded_by").is_empty(),
+                    row.fields.get("confidence").and_then(|v| v.as_f64()),
+                )
+            })
+            .collect();
+        if sorted.is_empty() {
             return String::new();
         }
 
         // Sort rows by confidence (desc), then by observed_at (desc)
-        let mut sorted: Vec<&ResultRow> = result.rows.iter().collect();
         sorted.sort_by(|a, b| {
             let conf_a = a
                 .fields
@@ -56,69 +218,126 @@ impl ContextAssembler {
             conf_b
                 .partial_cmp(&conf_a)
                 .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| str_field(b, "observed_at").cmp(str_field(a, "observed_at")))
         });
 
-        let max_chars = opts.max_tokens * 4; // rough token estimate
+        Self::assemble_ordered(&sorted, opts, tokenizer, now)
+    }
 
-        // Try full format first
-        let full = Self::format_full(&sorted);
-        if full.len() <= max_chars {
-            return full;
-        }
+    /// Assemble documents in descending order of their paired priority
+    /// score, e.g. from [`PriorityWeights::score`], within
+    /// `opts.max_tokens` tokens.
+    ///
+    /// Documents are filtered by the `exclude_*` and `min_confidence`
+    /// options first. Budgeting, sentence-boundary truncation and the
+    /// summary fallback work as in [`assemble`](Self::assemble). Documents
+    /// with equal scores keep their input order.
+    #[must_use]
+    pub fn assemble_with_priority(
+        docs_with_scores: &[(Document, f64)],
+        opts: &ContextOpts,
+        tokenizer: &dyn Tokenizer,
+    ) -> String {
+        let now = Utc::now();
+        let mut ranked: Vec<&(Document, f64)> = docs_with_scores
+            .iter()
+            .filter(|(doc, _)| {
+                opts.admits(
+                    doc.is_stale(now),
+                    doc.superseded_by.is_some(),
+                    Some(doc.confidence),
+                )
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let rows: Vec<ResultRow> = ranked.iter().map(|(doc, _)| document_row(doc)).collect();
+        let rows: Vec<&ResultRow> = rows.iter().collect();
+        Self::assemble_ordered(&rows, opts, tokenizer, now)
+    }
 
-        // Fall back to summary format if allowed
-        if opts.allow_summary {
-            return Self::format_summary(&sorted, max_chars);
+    fn assemble_ordered(
+        sorted: &[&ResultRow],
+        opts: &ContextOpts,
+        tokenizer: &dyn Tokenizer,
+        now: DateTime<Utc>,
+    ) -> String {
+        if sorted.is_empty() {
+            return String::new();
         }
 
-        // Truncate full format
-        full[..max_chars.min(full.len())].to_string()
-    }
-
-    fn format_full(rows: &[&ResultRow]) -> String {
+        // Each section is tokenized once and the counts summed. Sections
+        // end in a blank line, which the tokenizer keeps apart from the
+        // next section's heading, so the sum is the count of the whole.
         let mut output = String::new();
-        for row in rows {
-            let title = row
-                .fields
-                .get("title")
-                .and_then(|v| v.as_str())
-                .unwrap_or("Untitled");
-            let doc_type = row
-                .fields
-                .get("doc_type")
-                .and_then(|v| v.as_str())
-                .unwrap_or("unknown");
-            let body = row
-                .fields
-                .get("body")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-            let confidence = row
-                .fields
-                .get("confidence")
-                .and_then(|v| v.as_f64())
-                .unwrap_or(1.0);
-            let observed_at = row
-                .fields
-                .get("observed_at")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-
-            output.push_str(&format!("## [{doc_type}] {title}\n"));
-            output.push_str(&format!(
-                "*Observed: {observed_at} | Confidence: {confidence:.2}*\n\n"
-            ));
-            if !body.is_empty() {
-                output.push_str(body);
-                output.push_str("\n\n");
+        let mut used = 0;
+        for (i, row) in sorted.iter().enumerate() {
+            let body = str_field(row, "body");
+            let expired = row_is_expired(row, now);
+            let section = Self::format_section(row, body, expired);
+            let cost = tokenizer.count_tokens(&section);
+            if used + cost <= opts.max_tokens {
+                output.push_str(&section);
+                used += cost;
+                continue;
+            }
+
+            if i == 0 && opts.allow_summary {
+                return Self::format_summary(sorted, tokenizer, opts.max_tokens);
             }
-            output.push_str("---\n\n");
+
+            // Longest sentence-aligned prefix of the body that still fits;
+            // longer prefixes never take fewer tokens
+            let remaining = opts.max_tokens - used;
+            let ends = sentence_ends(body);
+            let fitting = ends.partition_point(|&end| {
+                tokenizer.count_tokens(&Self::format_section(row, &body[..end], expired))
+                    <= remaining
+            });
+            if let Some(&end) = fitting.checked_sub(1).and_then(|last| ends.get(last)) {
+                output.push_str(&Self::format_section(row, &body[..end], expired));
+            }
+            break;
+        }
+        output
+    }
+
+    fn format_section(row: &ResultRow, body: &str, expired: bool) -> String {
+        let title = row
+            .fields
+            .get("title")
+            .and_then(|v| v.as_str())
+            .unwrap_or("Untitled");
+        let doc_type = row
+            .fields
+            .get("doc_type")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown");
+        let confidence = row
+            .fields
+            .get("confidence")
+            .and_then(|v| v.as_f64())
+            .unwrap_or(1.0);
+        let observed_at = str_field(row, "observed_at");
+
+        let warning = if expired { "[EXPIRED] " } else { "" };
+        let mut output = format!("## {warning}[{doc_type}] {title}\n");
+        output.push_str(&format!(
+            "*Observed: {observed_at} | Confidence: {confidence:.2}*\n\n"
+        ));
+        if !body.is_empty() {
+            output.push_str(body);
+            output.push_str("\n\n");
         }
+        output.push_str("---\n\n");
         output
     }
 
-    fn format_summary(rows: &[&ResultRow], max_chars: usize) -> String {
+    fn format_summary(rows: &[&ResultRow], tokenizer: &dyn Tokenizer, max_tokens: usize) -> String {
         let mut output = String::from("# Summary (truncated for context budget)\n\n");
+        let mut used = tokenizer.count_tokens(&output);
+        if used > max_tokens {
+            return String::new();
+        }
 
         for row in rows {
             let title = row
@@ -139,23 +358,68 @@ impl ContextAssembler {
 
             let line = format!("- **[{doc_type}] {title}** (confidence: {confidence:.2})\n");
 
-            if output.len() + line.len() > max_chars {
+            let cost = tokenizer.count_tokens(&line);
+            if used + cost > max_tokens {
                 break;
             }
             output.push_str(&line);
+            used += cost;
         }
 
         output
     }
 }
 
+/// The fields of `doc` that sections and summaries show.
+fn document_row(doc: &Document) -> ResultRow {
+    let mut fields = std::collections::BTreeMap::new();
+    fields.insert("title".to_string(), serde_json::json!(doc.title));
+    fields.insert("doc_type".to_string(), serde_json::json!(doc.doc_type));
+    fields.insert("confidence".to_string(), serde_json::json!(doc.confidence));
+    fields.insert(
+        "observed_at".to_string(),
+        serde_json::json!(doc.temporal.observed_at.to_rfc3339()),
+    );
+    fields.insert(
+        "valid_until".to_string(),
+        serde_json::json!(doc.temporal.valid_until.to_rfc3339()),
+    );
+    fields.insert("body".to_string(), serde_json::json!(doc.body));
+    ResultRow { fields }
+}
+
+/// Whether the row's `valid_until` column holds a time before `now`.
+fn row_is_expired(row: &ResultRow, now: DateTime<Utc>) -> bool {
+    DateTime::parse_from_rfc3339(str_field(row, "valid_until"))
+        .is_ok_and(|valid_until| valid_until < now)
+}
+
+fn str_field<'a>(row: &'a ResultRow, name: &str) -> &'a str {
+    row.fields.get(name).and_then(|v| v.as_str()).unwrap_or("")
+}
+
+/// Byte offsets just past each sentence in `text`: after `.`, `!` or `?`
+/// followed by whitespace, and at paragraph breaks.
+fn sentence_ends(text: &str) -> Vec<usize> {
+    let bytes = text.as_bytes();
+    (1..bytes.len())
+        .filter(|&i| {
+            let next_is_space = bytes[i].is_ascii_whitespace();
+            let terminal = matches!(bytes[i - 1], b'.' | b'!' | b'?');
+            let paragraph = bytes[i] == b'\n' && bytes[i - 1] == b'\n';
+            next_is_space && (terminal || paragraph)
+        })
+        .map(|i| if bytes[i - 1] == b'\n' { i - 1 } else { i })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
+    use std::collections::BTreeMap;
 
     fn make_row(title: &str, confidence: f64, body: &str) -> ResultRow {
-        let mut fields = HashMap::new();
+        let mut fields = BTreeMap::new();
         fields.insert("title".to_string(), serde_json::json!(title));
         fields.insert("doc_type".to_string(), serde_json::json!("project"));
         fields.insert("confidence".to_string(), serde_json::json!(confidence));
@@ -181,8 +445,9 @@ mod tests {
         let opts = ContextOpts {
             max_tokens: 10000,
             allow_summary: false,
+            ..ContextOpts::default()
         };
-        let output = ContextAssembler::assemble(&result, &opts);
+        let output = ContextAssembler::assemble(&result, &opts, &Cl100kTokenizer);
 
         // High confidence should appear first
         let high_pos = output.find("High Confidence").unwrap();
@@ -207,12 +472,13 @@ mod tests {
         let opts = ContextOpts {
             max_tokens: 100, // Very small budget = ~400 chars
             allow_summary: true,
+            ..ContextOpts::default()
         };
-        let output = ContextAssembler::assemble(&result, &opts);
+        let output = ContextAssembler::assemble(&result, &opts, &Cl100kTokenizer);
 
         // Should fall back to summary format
         assert!(output.contains("Summary"));
-        assert!(output.len() <= 500); // Within budget range
+        assert!(Cl100kTokenizer.count_tokens(&output) <= 100);
     }
 
     #[test]
@@ -229,8 +495,9 @@ mod tests {
         let opts = ContextOpts {
             max_tokens: 50, // Tiny budget
             allow_summary: true,
+            ..ContextOpts::default()
         };
-        let output = ContextAssembler::assemble(&result, &opts);
+        let output = ContextAssembler::assemble(&result, &opts, &Cl100kTokenizer);
         assert!(output.contains("Summary"));
         assert!(output.contains("Doc A")); // Highest confidence should still appear
     }
@@ -241,7 +508,298 @@ mod tests {
             rows: vec![],
             total: 0,
         };
-        let output = ContextAssembler::assemble(&result, &ContextOpts::default());
+        let output = ContextAssembler::assemble(&result, &ContextOpts::default(), &Cl100kTokenizer);
         assert!(output.is_empty());
     }
+
+    #[test]
+    fn assembler_truncates_last_document_at_sentence_boundary() {
+        let body = "First sentence here. Second sentence follows. Third one ends it.";
+        let result = QueryResult {
+            rows: vec![
+                make_row("Whole", 0.9, "Short body."),
+                make_row("Partial", 0.8, body),
+                make_row("Dropped", 0.7, "Never reached."),
+            ],
+            total: 3,
+        };
+        let whole = ContextAssembler::assemble(
+            &QueryResult {
+                rows: result.rows[..1].to_vec(),
+                total: 1,
+            },
+            &ContextOpts::default(),
+            &WhitespaceTokenizer,
+        );
+        let budget = WhitespaceTokenizer.count_tokens(&whole) + 16;
+
+        let opts = ContextOpts {
+            max_tokens: budget,
+            allow_summary: false,
+            ..ContextOpts::default()
+        };
+        let output = ContextAssembler::assemble(&result, &opts, &WhitespaceTokenizer);
+        assert!(output.starts_with(&whole));
+        assert!(output.contains("First sentence here. Second sentence follows.\n"));
+        assert!(!output.contains("Third"));
+        assert!(!output.contains("Dropped"));
+    }
+
+    #[test]
+    fn assembler_never_exceeds_max_tokens() {
+        let body = "The migration is on track. Latency dropped by half! Is the cache warm? \
+                    Yes, mostly.\n\nA new paragraph starts here and keeps going for a while.";
+        let rows: Vec<ResultRow> = (0..12)
+            .map(|i| make_row(&format!("Doc {i}"), 1.0 - f64::from(i) / 20.0, body))
+            .collect();
+        let result = QueryResult {
+            total: rows.len(),
+            rows,
+        };
+
+        let tokenizers: [&dyn Tokenizer; 2] = [&Cl100kTokenizer, &WhitespaceTokenizer];
+        for tokenizer in tokenizers {
+            for max_tokens in [5, 30, 60, 97, 150, 400, 5000] {
+                for allow_summary in [true, false] {
+                    let opts = ContextOpts {
+                        max_tokens,
+                        allow_summary,
+                        ..ContextOpts::default()
+                    };
+                    let output = ContextAssembler::assemble(&result, &opts, tokenizer);
+                    assert!(
+                        tokenizer.count_tokens(&output) <= max_tokens,
+                        "{max_tokens} tokens exceeded: {output}"
+                    );
+                }
+            }
+        }
+    }
+
+    /// Counts calls and the bytes tokenized across them.
+    #[derive(Default)]
+    struct MeteredTokenizer {
+        calls: std::cell::Cell<usize>,
+        bytes: std::cell::Cell<usize>,
+    }
+
+    impl Tokenizer for MeteredTokenizer {
+        fn count_tokens(&self, text: &str) -> usize {
+            self.calls.set(self.calls.get() + 1);
+            self.bytes.set(self.bytes.get() + text.len());
+            WhitespaceTokenizer.count_tokens(text)
+        }
+    }
+
+    #[test]
+    fn assembler_tokenizes_each_section_once() {
+        let body = "One short sentence. ".repeat(200);
+        let rows: Vec<ResultRow> = (0..200)
+            .map(|i| make_row(&format!("Doc {i}"), 0.9, &body))
+            .collect();
+        let input_bytes: usize = rows.iter().map(|r| str_field(r, "body").len()).sum();
+        let result = QueryResult {
+            total: rows.len(),
+            rows,
+        };
+        let opts = ContextOpts {
+            max_tokens: 50_000,
+            allow_summary: false,
+            ..ContextOpts::default()
+        };
+
+        let tokenizer = MeteredTokenizer::default();
+        let output = ContextAssembler::assemble(&result, &opts, &tokenizer);
+        assert!(output.contains("Doc 0"));
+        // One call per whole section plus a binary search over ~200
+        // sentence ends in the one that is cut
+        assert!(
+            tokenizer.calls.get() < 200 + 16,
+            "{}",
+            tokenizer.calls.get()
+        );
+        assert!(
+            tokenizer.bytes.get() < 2 * input_bytes,
+            "{} bytes tokenized",
+            tokenizer.bytes.get()
+        );
+    }
+
+    fn make_doc(title: &str, confidence: f64, observed_at: DateTime<Utc>) -> Document {
+        use mkb_core::temporal::{DecayProfile, RawTemporalInput};
+
+        let input = RawTemporalInput {
+            observed_at: Some(observed_at),
+            ..Default::default()
+        };
+        let mut doc = Document::new(
+            title.to_lowercase(),
+            "project".to_string(),
+            title.to_string(),
+            input,
+            &DecayProfile::default_profile(),
+        )
+        .unwrap();
+        doc.confidence = confidence;
+        doc.body = format!("Body of {title}.");
+        doc
+    }
+
+    #[test]
+    fn priority_weights_trade_recency_against_confidence() {
+        use chrono::TimeZone;
+
+        let now = Utc.with_ymd_and_hms(2025, 6, 1, 0, 0, 0).unwrap();
+        let fresh = make_doc("Fresh", 0.3, now - chrono::Duration::days(1));
+        let old = make_doc("Old", 0.95, now - chrono::Duration::days(365));
+        let order = |weights: PriorityWeights| {
+            let scored: Vec<(Document, f64)> = [&old, &fresh]
+                .into_iter()
+                .map(|doc| (doc.clone(), weights.score(doc, None, None, now)))
+                .collect();
+            // Both documents are past valid_until by today
+            let opts = ContextOpts {
+                exclude_expired: false,
+                ..ContextOpts::default()
+            };
+            let output =
+                ContextAssembler::assemble_with_priority(&scored, &opts, &WhitespaceTokenizer);
+            output.find("Fresh").unwrap() < output.find("Old").unwrap()
+        };
+
+        let recency_first = PriorityWeights {
+            recency_weight: 1.0,
+            confidence_weight: 0.2,
+            link_proximity_weight: 0.0,
+            fts_rank_weight: 0.0,
+        };
+        assert!(order(recency_first), "fresh document should lead");
+
+        let confidence_first = PriorityWeights {
+            recency_weight: 0.2,
+            confidence_weight: 1.0,
+            ..recency_first
+        };
+        assert!(!order(confidence_first), "confident document should lead");
+    }
+
+    #[test]
+    fn priority_score_counts_link_proximity_and_fts_rank() {
+        let now = Utc::now();
+        let doc = make_doc("Doc", 1.0, now);
+        let weights = PriorityWeights {
+            recency_weight: 0.0,
+            confidence_weight: 0.0,
+            link_proximity_weight: 1.0,
+            fts_rank_weight: 1.0,
+        };
+
+        assert_eq!(weights.score(&doc, None, None, now), 0.0);
+        assert_eq!(weights.score(&doc, Some(2), None, now), 0.5);
+        assert_eq!(weights.score(&doc, Some(0), Some(0.25), now), 1.25);
+        assert_eq!(
+            normalize_fts_ranks(&[-4.0, -1.0, -2.5]),
+            vec![1.0, 0.0, 0.5]
+        );
+        assert_eq!(normalize_fts_ranks(&[-3.0, -3.0]), vec![1.0, 1.0]);
+    }
+
+    #[test]
+    fn assembler_filters_expired_superseded_and_low_confidence_rows() {
+        let expired_row = |title: &str| {
+            let mut row = make_row(title, 0.9, "old news");
+            row.fields.insert(
+                "valid_until".to_string(),
+                serde_json::json!("2020-01-01T00:00:00Z"),
+            );
+            row
+        };
+        let mut current = make_row("Current", 0.9, "fresh");
+        current.fields.insert(
+            "valid_until".to_string(),
+            serde_json::json!("2999-01-01T00:00:00Z"),
+        );
+        let mut superseded = make_row("Superseded", 0.9, "replaced");
+        superseded
+            .fields
+            .insert("superseded_by".to_string(), serde_json::json!("proj-new"));
+        let result = QueryResult {
+            rows: vec![
+                current,
+                expired_row("Expired"),
+                superseded,
+                make_row("Doubtful", 0.2, "maybe"),
+            ],
+            total: 4,
+        };
+
+        let output = ContextAssembler::assemble(
+            &result,
+            &ContextOpts {
+                min_confidence: 0.5,
+                ..ContextOpts::default()
+            },
+            &WhitespaceTokenizer,
+        );
+        assert!(output.contains("Current"));
+        for dropped in ["Expired", "Superseded", "Doubtful"] {
+            assert!(!output.contains(dropped), "{dropped} kept: {output}");
+        }
+
+        let output = ContextAssembler::assemble(
+            &result,
+            &ContextOpts {
+                exclude_expired: false,
+                exclude_superseded: false,
+                ..ContextOpts::default()
+            },
+            &WhitespaceTokenizer,
+        );
+        assert!(output.contains("## [EXPIRED] [project] Expired\n"));
+        assert!(output.contains("## [project] Current\n"));
+        assert!(output.contains("## [project] Superseded\n"));
+        assert!(output.contains("Doubtful"));
+    }
+
+    #[test]
+    fn assemble_with_priority_filters_documents() {
+        let now = Utc::now();
+        let current = make_doc("Current", 0.9, now);
+        let expired = make_doc("Expired", 0.9, now - chrono::Duration::days(400));
+        let mut superseded = make_doc("Superseded", 0.9, now);
+        superseded.superseded_by = Some("current".to_string());
+        let scored: Vec<(Document, f64)> = [current, expired, superseded]
+            .into_iter()
+            .map(|doc| (doc, 1.0))
+            .collect();
+
+        let output = ContextAssembler::assemble_with_priority(
+            &scored,
+            &ContextOpts::default(),
+            &WhitespaceTokenizer,
+        );
+        assert!(output.contains("Current"));
+        assert!(!output.contains("Expired"));
+        assert!(!output.contains("Superseded"));
+
+        let output = ContextAssembler::assemble_with_priority(
+            &scored,
+            &ContextOpts {
+                exclude_expired: false,
+                ..ContextOpts::default()
+            },
+            &WhitespaceTokenizer,
+        );
+        assert!(output.contains("## [EXPIRED] [project] Expired\n"));
+        assert!(!output.contains("[EXPIRED] [project] Current"));
+    }
+
+    #[test]
+    fn tokenizers_by_name() {
+        let cl100k = tokenizer_by_name("cl100k_base").unwrap();
+        assert_eq!(cl100k.count_tokens("hello world"), 2);
+        let whitespace = tokenizer_by_name("whitespace").unwrap();
+        assert_eq!(whitespace.count_tokens("  one two\nthree "), 3);
+        assert!(tokenizer_by_name("gpt2").is_none());
+    }
 }
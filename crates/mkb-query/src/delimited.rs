@@ -0,0 +1,249 @@
+//! Delimiter-separated (CSV, TSV, ...) output of query results.
+//!
+//! Columns are the union of every row's columns in name order, so rows
+//! missing a column get an empty cell. Quoting follows RFC 4180: a field is
+//! quoted when it contains the delimiter, the quote character or a line
+//! break, and quote characters inside it are doubled.
+
+use crate::formatter::QueryResult;
+
+/// Byte order mark some spreadsheet programs need to read UTF-8.
+const BOM: char = '\u{feff}';
+
+/// Options for [`OutputFormat::Csv`](crate::OutputFormat::Csv).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvOpts {
+    pub delimiter: char,
+    pub quote: char,
+    /// Include the `body` column. Bodies are often large and unwanted in
+    /// tabular exports, so it is dropped by default.
+    pub include_body: bool,
+    /// Keep line breaks inside quoted fields. Otherwise they are written
+    /// as a literal `\n` so every row is one line.
+    pub multiline_body: bool,
+    /// Start the output with a UTF-8 byte order mark, for Excel.
+    pub bom: bool,
+}
+
+impl Default for CsvOpts {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            quote: '"',
+            include_body: false,
+            multiline_body: false,
+            bom: false,
+        }
+    }
+}
+
+impl CsvOpts {
+    /// Tab-separated values.
+    #[must_use]
+    pub fn tsv() -> Self {
+        Self {
+            delimiter: '\t',
+            ..Self::default()
+        }
+    }
+}
+
+/// Render `result` as a header line followed by one line per row.
+///
+/// An empty result renders as nothing at all (apart from the BOM), since
+/// it has no columns to name.
+#[must_use]
+pub fn format_csv(result: &QueryResult, opts: &CsvOpts) -> String {
+    let mut columns: Vec<&str> = result
+        .rows
+        .iter()
+        .flat_map(|row| row.fields.keys().map(String::as_str))
+        .filter(|col| opts.include_body || *col != "body")
+        .collect();
+    columns.sort_unstable();
+    columns.dedup();
+
+    let mut out = String::new();
+    if opts.bom {
+        out.push(BOM);
+    }
+    if columns.is_empty() {
+        return out;
+    }
+    push_record(&mut out, columns.iter().map(|c| (*c).to_string()), opts);
+    for row in &result.rows {
+        let cells = columns
+            .iter()
+            .map(|col| row.fields.get(*col).map(cell_text).unwrap_or_default());
+        push_record(&mut out, cells, opts);
+    }
+    out
+}
+
+/// A cell's text: strings unquoted, arrays joined with `, `, null empty.
+fn cell_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::Array(items) => {
+            items.iter().map(cell_text).collect::<Vec<_>>().join(", ")
+        }
+        other => other.to_string(),
+    }
+}
+
+fn push_record(out: &mut String, cells: impl Iterator<Item = String>, opts: &CsvOpts) {
+    for (n, cell) in cells.enumerate() {
+        if n > 0 {
+            out.push(opts.delimiter);
+        }
+        push_field(out, &cell, opts);
+    }
+    out.push('\n');
+}
+
+fn push_field(out: &mut String, field: &str, opts: &CsvOpts) {
+    let field = if opts.multiline_body {
+        field.to_string()
+    } else {
+        field.replace("\r\n", "\\n").replace(['\n', '\r'], "\\n")
+    };
+    let needs_quotes = field
+        .chars()
+        .any(|c| c == opts.delimiter || c == opts.quote || c == '\n' || c == '\r');
+    if !needs_quotes {
+        out.push_str(&field);
+        return;
+    }
+    out.push(opts.quote);
+    for c in field.chars() {
+        if c == opts.quote {
+            out.push(c);
+        }
+        out.push(c);
+    }
+    out.push(opts.quote);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formatter::ResultRow;
+    use std::collections::BTreeMap;
+
+    fn result(rows: &[&[(&str, serde_json::Value)]]) -> QueryResult {
+        let rows: Vec<ResultRow> = rows
+            .iter()
+            .map(|fields| ResultRow {
+                fields: fields
+                    .iter()
+                    .map(|(k, v)| ((*k).to_string(), v.clone()))
+                    .collect::<BTreeMap<_, _>>(),
+            })
+            .collect();
+        QueryResult {
+            total: rows.len(),
+            rows,
+        }
+    }
+
+    /// Parse `text` back with the `csv` crate, header included.
+    fn parse(text: &str, opts: &CsvOpts) -> Vec<Vec<String>> {
+        let mut reader = ::csv::ReaderBuilder::new()
+            .has_headers(false)
+            .delimiter(opts.delimiter as u8)
+            .quote(opts.quote as u8)
+            .from_reader(text.as_bytes());
+        reader
+            .records()
+            .map(|record| record.unwrap().iter().map(str::to_string).collect())
+            .collect()
+    }
+
+    fn tricky() -> QueryResult {
+        result(&[
+            &[
+                ("id", serde_json::json!("a-001")),
+                ("title", serde_json::json!("Alpha, \"the first\"")),
+                ("tags", serde_json::json!(["x", "y"])),
+                ("body", serde_json::json!("line one\nline two")),
+            ],
+            &[
+                ("id", serde_json::json!("b-001")),
+                ("title", serde_json::json!("Beta;\ttabbed")),
+                ("confidence", serde_json::json!(0.5)),
+                ("body", serde_json::Value::Null),
+            ],
+        ])
+    }
+
+    #[test]
+    fn round_trips_through_csv_parser() {
+        let opts = CsvOpts {
+            include_body: true,
+            multiline_body: true,
+            ..CsvOpts::default()
+        };
+        let records = parse(&format_csv(&tricky(), &opts), &opts);
+        assert_eq!(
+            records,
+            vec![
+                vec!["body", "confidence", "id", "tags", "title"],
+                vec![
+                    "line one\nline two",
+                    "",
+                    "a-001",
+                    "x, y",
+                    "Alpha, \"the first\""
+                ],
+                vec!["", "0.5", "b-001", "", "Beta;\ttabbed"],
+            ]
+        );
+    }
+
+    #[test]
+    fn custom_delimiter_and_quote_round_trip() {
+        for opts in [
+            CsvOpts::tsv(),
+            CsvOpts {
+                delimiter: ';',
+                quote: '\'',
+                ..CsvOpts::default()
+            },
+        ] {
+            let records = parse(&format_csv(&tricky(), &opts), &opts);
+            assert_eq!(records[0], vec!["confidence", "id", "tags", "title"]);
+            assert_eq!(records[1][3], "Alpha, \"the first\"");
+            assert_eq!(records[2][3], "Beta;\ttabbed");
+        }
+    }
+
+    #[test]
+    fn body_is_dropped_unless_requested() {
+        let text = format_csv(&tricky(), &CsvOpts::default());
+        assert!(text.starts_with("confidence,id,tags,title\n"));
+        assert!(!text.contains("line one"));
+    }
+
+    #[test]
+    fn line_breaks_are_escaped_unless_multiline() {
+        let opts = CsvOpts {
+            include_body: true,
+            ..CsvOpts::default()
+        };
+        let text = format_csv(&tricky(), &opts);
+        assert_eq!(text.lines().count(), 3);
+        assert_eq!(parse(&text, &opts)[1][0], "line one\\nline two");
+    }
+
+    #[test]
+    fn bom_and_empty_result() {
+        let opts = CsvOpts {
+            bom: true,
+            ..CsvOpts::default()
+        };
+        assert!(format_csv(&tricky(), &opts).starts_with("\u{feff}confidence,"));
+        assert_eq!(format_csv(&result(&[]), &opts), "\u{feff}");
+        assert_eq!(format_csv(&result(&[]), &CsvOpts::default()), "");
+    }
+}
@@ -3,34 +3,163 @@
 //! Takes a `CompiledQuery` and an `IndexManager`, executes the SQL,
 //! and returns a `QueryResult`.
 
-use mkb_index::IndexManager;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use mkb_core::error::MkbError;
+use mkb_index::{EmbeddingProvider, IndexManager, QueryPlanRow};
 use rusqlite::types::Value as SqlValue;
 
-use crate::compiler::{CompiledQuery, SqlParam};
-use crate::formatter::{QueryResult, ResultRow};
+use mkb_parser::ast::AggregateFunction;
+
+use crate::compiler::{
+    CompiledQuery, ParamKind, SimilarityOrder, SqlParam, PIVOT_COL_KEY, PIVOT_ROW_KEY, PIVOT_VALUE,
+    SIMILARITY_ID_COLUMN,
+};
+use crate::formatter::{QueryResult, ResultRow, PIVOT_ROW_COLUMN};
 
 /// Execute a compiled query against the index.
 ///
-/// For queries with `NEAR()` predicate, uses a two-phase approach:
-/// 1. Generate mock embedding, run KNN search to get candidate IDs
-/// 2. Filter by distance threshold, inject matching IDs into SQL
+/// Collects [`execute_streaming`] into memory; prefer the stream for
+/// queries that may return very many rows.
 ///
 /// # Errors
 ///
 /// Returns a string error if execution fails.
 pub fn execute(index: &IndexManager, compiled: &CompiledQuery) -> Result<QueryResult, String> {
+    let rows = execute_streaming(index, compiled)
+        .and_then(|stream| stream.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| match e {
+            MkbError::Query(msg) => msg,
+            other => other.to_string(),
+        })?;
+    let total = rows.len();
+    Ok(QueryResult { rows, total })
+}
+
+/// Result rows of [`execute_streaming`], read from SQLite as the iterator
+/// is advanced.
+pub struct QueryStream<'a> {
+    rows: Box<dyn Iterator<Item = Result<ResultRow, MkbError>> + 'a>,
+}
+
+impl Iterator for QueryStream<'_> {
+    type Item = Result<ResultRow, MkbError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.rows.next()
+    }
+}
+
+/// Execute a compiled query, yielding result rows one at a time.
+///
+/// For queries with `NEAR()` predicate, uses a two-phase approach:
+/// 1. Embed the NEAR text, run KNN search to get candidate IDs
+/// 2. Filter by distance threshold, bind matching IDs into the SQL
+///
+/// For `ORDER BY SIMILARITY()`, rows returned by SQL are re-ranked by cosine
+/// similarity to the reference text, then LIMIT/OFFSET are applied. Ranking
+/// needs every row, so those queries are buffered before the first is yielded,
+/// as are queries with [`transformers`](CompiledQuery::transformers), which
+/// run last.
+///
+/// Query text is embedded with [`mkb_index::mock_embedding`]; use
+/// [`execute_streaming_with_provider`] to embed it with a real model.
+///
+/// # Errors
+///
+/// Returns [`MkbError::Query`] if the query cannot be started. Failures
+/// while reading rows are yielded by the stream.
+pub fn execute_streaming<'a>(
+    index: &'a IndexManager,
+    compiled: &CompiledQuery,
+) -> Result<QueryStream<'a>, MkbError> {
+    stream_query(index, compiled, &|text| Ok(mkb_index::mock_embedding(text)))
+}
+
+/// Execute a compiled query, embedding `NEAR()` and `SIMILARITY()` text
+/// with `provider`. Otherwise identical to [`execute`].
+///
+/// # Errors
+///
+/// Returns a string error if embedding or execution fails.
+///
+/// # Panics
+///
+/// The provider is driven on its own single-threaded Tokio runtime, so this
+/// panics if called from within an async runtime.
+pub fn execute_with_provider<P: EmbeddingProvider>(
+    index: &IndexManager,
+    compiled: &CompiledQuery,
+    provider: &P,
+) -> Result<QueryResult, String> {
+    let rows = execute_streaming_with_provider(index, compiled, provider)
+        .and_then(|stream| stream.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| match e {
+            MkbError::Query(msg) => msg,
+            other => other.to_string(),
+        })?;
+    let total = rows.len();
+    Ok(QueryResult { rows, total })
+}
+
+/// Streaming form of [`execute_with_provider`].
+///
+/// # Errors
+///
+/// Returns [`MkbError::Query`] if embedding fails or the query cannot be
+/// started. Failures while reading rows are yielded by the stream.
+///
+/// # Panics
+///
+/// Panics if called from within an async runtime.
+pub fn execute_streaming_with_provider<'a, P: EmbeddingProvider>(
+    index: &'a IndexManager,
+    compiled: &CompiledQuery,
+    provider: &P,
+) -> Result<QueryStream<'a>, MkbError> {
+    stream_query(index, compiled, &|text| embed_blocking(provider, text))
+}
+
+/// Embed one text with `provider`, blocking until the vector is ready.
+fn embed_blocking<P: EmbeddingProvider>(provider: &P, text: &str) -> Result<Vec<f32>, MkbError> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| MkbError::Query(format!("Failed to start embedding runtime: {e}")))?;
+    runtime
+        .block_on(provider.embed(&[text]))
+        .map_err(|e| MkbError::Query(format!("Embedding failed: {e}")))?
+        .pop()
+        .ok_or_else(|| MkbError::Query("Embedding provider returned no vector".to_string()))
+}
+
+/// How many nearest neighbours a `NEAR()` lookup considers before the
+/// threshold is applied.
+const NEAR_CANDIDATES: usize = 1000;
+
+type Embed<'e> = dyn Fn(&str) -> Result<Vec<f32>, MkbError> + 'e;
+
+fn stream_query<'a>(
+    index: &'a IndexManager,
+    compiled: &CompiledQuery,
+    embed: &Embed<'_>,
+) -> Result<QueryStream<'a>, MkbError> {
     let mut sql = compiled.sql.clone();
+    let mut sql_params: Vec<SqlValue> = compiled
+        .params
+        .iter()
+        .map(sql_value)
+        .collect::<Result<_, _>>()?;
 
     // Phase 1: If NEAR() is used, resolve semantic candidates first
     if compiled.uses_semantic {
         if let Some((ref query_text, threshold)) = compiled.near_params {
-            let embedding = mkb_index::mock_embedding(query_text);
-            // Fetch a generous number of candidates (100)
+            let embedding = embed(query_text)?;
             let candidates = index
-                .search_semantic(&embedding, 100)
-                .map_err(|e| format!("Semantic search failed: {e}"))?;
+                .search_semantic(&embedding, NEAR_CANDIDATES)
+                .map_err(|e| MkbError::Query(format!("Semantic search failed: {e}")))?;
 
-            // Filter by distance threshold (lower distance = more similar)
+            // Similarity threshold → distance bound (lower distance = more similar)
             let matching_ids: Vec<String> = candidates
                 .into_iter()
                 .filter(|r| r.distance <= (1.0 - threshold))
@@ -38,56 +167,282 @@ pub fn execute(index: &IndexManager, compiled: &CompiledQuery) -> Result<QueryRe
                 .collect();
 
             if matching_ids.is_empty() {
-                return Ok(QueryResult {
-                    rows: Vec::new(),
-                    total: 0,
+                return Ok(QueryStream {
+                    rows: Box::new(std::iter::empty()),
                 });
             }
 
-            // Replace the NEAR placeholder with an ID filter
-            let id_list = matching_ids
-                .iter()
-                .map(|id| format!("'{id}'"))
+            // Replace the NEAR placeholder with an ID filter, binding the IDs
+            // after the compiled parameters
+            let first = sql_params.len() + 1;
+            let placeholders = (first..first + matching_ids.len())
+                .map(|n| format!("?{n}"))
                 .collect::<Vec<_>>()
                 .join(", ");
             sql = sql.replace(
                 "1=1 /* NEAR placeholder */",
-                &format!("d.id IN ({id_list})"),
+                &format!("d.id IN ({placeholders})"),
             );
+            sql_params.extend(matching_ids.into_iter().map(SqlValue::Text));
         }
     }
 
-    let sql_params: Vec<SqlValue> = compiled
+    let exec_err = |e: MkbError| MkbError::Query(format!("Query execution failed: {e}"));
+    let rows = index
+        .stream_sql(&sql, &sql_params)
+        .map_err(exec_err)?
+        .map(move |row| {
+            let mut fields = row.map_err(exec_err)?;
+            expand_tags(&mut fields);
+            Ok(ResultRow { fields })
+        });
+
+    let rows: Vec<ResultRow> = if let Some(function) = compiled.pivot {
+        transpose_pivot(rows.collect::<Result<Vec<_>, _>>()?, function)
+    } else if let Some(ref order) = compiled.similarity_order {
+        rank_by_similarity(index, rows.collect::<Result<Vec<_>, _>>()?, order, embed)?
+    } else if !compiled.transformers.is_empty() {
+        rows.collect::<Result<Vec<_>, _>>()?
+    } else {
+        return Ok(QueryStream {
+            rows: Box::new(rows),
+        });
+    };
+
+    let rows = compiled
+        .transformers
+        .iter()
+        .fold(rows, |rows, transformer| transformer.transform(rows));
+    Ok(QueryStream {
+        rows: Box::new(rows.into_iter().map(Ok)),
+    })
+}
+
+/// The value bound for `param`.
+///
+/// # Errors
+///
+/// Returns [`MkbError::Query`] for a `:name` parameter that was never bound.
+fn sql_value(param: &SqlParam) -> Result<SqlValue, MkbError> {
+    match param {
+        SqlParam::Text(s) => Ok(SqlValue::Text(s.clone())),
+        SqlParam::Integer(i) => Ok(SqlValue::Integer(*i)),
+        SqlParam::Float(f) => Ok(SqlValue::Real(*f)),
+        SqlParam::Null => Ok(SqlValue::Null),
+        SqlParam::Named { name, .. } => Err(MkbError::Query(format!(
+            "Unbound parameter :{name} (use execute_with_params)"
+        ))),
+    }
+}
+
+/// SQLite's plan for a compiled query, without running it.
+///
+/// `:name` parameters need not be bound, and a `NEAR()` filter is
+/// planned as its placeholder, since neither changes which indexes SQLite
+/// picks.
+///
+/// # Errors
+///
+/// Returns [`MkbError::Index`] if SQLite cannot prepare the query.
+pub fn explain_plan(
+    index: &IndexManager,
+    compiled: &CompiledQuery,
+) -> Result<Vec<QueryPlanRow>, MkbError> {
+    let params: Vec<SqlValue> = compiled
         .params
         .iter()
-        .map(|p| match p {
-            SqlParam::Text(s) => SqlValue::Text(s.clone()),
-            SqlParam::Integer(i) => SqlValue::Integer(*i),
-            SqlParam::Float(f) => SqlValue::Real(*f),
-            SqlParam::Null => SqlValue::Null,
-        })
+        .map(|p| sql_value(p).unwrap_or(SqlValue::Null))
         .collect();
+    index.explain_query(&compiled.sql, &params)
+}
 
-    let rows = index
-        .execute_sql(&sql, &sql_params)
-        .map_err(|e| format!("Query execution failed: {e}"))?;
+/// Execute a compiled query, binding its `:name` parameters from `params`.
+///
+/// Values are bound as SQL parameters, never spliced into the SQL text.
+/// A value whose JSON type doesn't match the compared column (e.g. a
+/// string for `confidence`) is coerced with a warning.
+///
+/// # Errors
+///
+/// Returns [`MkbError::Query`] if a parameter is missing, a value cannot
+/// be coerced, or execution fails.
+pub fn execute_with_params(
+    index: &IndexManager,
+    compiled: &CompiledQuery,
+    params: &HashMap<&str, serde_json::Value>,
+) -> Result<QueryResult, MkbError> {
+    let mut bound = compiled.clone();
+    for param in &mut bound.params {
+        if let SqlParam::Named { name, expected } = param {
+            let value = params
+                .get(name.as_str())
+                .ok_or_else(|| MkbError::Query(format!("missing value for parameter :{name}")))?;
+            *param = bind_param(name, *expected, value)?;
+        }
+    }
+    execute(index, &bound).map_err(MkbError::Query)
+}
 
-    let total = rows.len();
-    let result_rows: Vec<ResultRow> = rows
+fn bind_param(
+    name: &str,
+    expected: ParamKind,
+    value: &serde_json::Value,
+) -> Result<SqlParam, MkbError> {
+    use serde_json::Value as Json;
+
+    let mismatch = || {
+        tracing::warn!("parameter :{name} expects {expected:?}, got {value}; coercing");
+    };
+    match (expected, value) {
+        (_, Json::Null) => Ok(SqlParam::Null),
+        (ParamKind::Text, Json::String(s)) => Ok(SqlParam::Text(s.clone())),
+        (ParamKind::Text, Json::Number(_) | Json::Bool(_)) => {
+            mismatch();
+            Ok(SqlParam::Text(value.to_string()))
+        }
+        (ParamKind::Real, Json::Number(n)) => match n.as_i64() {
+            Some(i) => Ok(SqlParam::Integer(i)),
+            None => Ok(SqlParam::Float(n.as_f64().unwrap_or(f64::NAN))),
+        },
+        (ParamKind::Real, Json::String(s)) => {
+            mismatch();
+            s.trim().parse().map(SqlParam::Float).map_err(|_| {
+                MkbError::Query(format!(
+                    "parameter :{name}: cannot coerce {value} to a number"
+                ))
+            })
+        }
+        (ParamKind::Real, Json::Bool(b)) => {
+            mismatch();
+            Ok(SqlParam::Integer(i64::from(*b)))
+        }
+        (_, Json::Array(_) | Json::Object(_)) => Err(MkbError::Query(format!(
+            "parameter :{name}: expected a scalar, got {value}"
+        ))),
+    }
+}
+
+/// Replace the stored comma-separated `tags` string with a JSON array.
+fn expand_tags(fields: &mut std::collections::BTreeMap<String, serde_json::Value>) {
+    if let Some(serde_json::Value::String(stored)) = fields.get("tags") {
+        let tags = mkb_index::parse_tags(stored);
+        fields.insert("tags".to_string(), serde_json::json!(tags));
+    }
+}
+
+/// Turn a PIVOT's grouped `(row key, column key, value)` rows into one row
+/// per row key with a column per column key, both sorted. A missing
+/// `COUNT` cell is 0; other missing cells are null.
+fn transpose_pivot(rows: Vec<ResultRow>, function: AggregateFunction) -> Vec<ResultRow> {
+    let mut cells: HashMap<String, HashMap<String, serde_json::Value>> = HashMap::new();
+    let mut columns = BTreeSet::new();
+    for mut row in rows {
+        let row_key = pivot_key(row.fields.remove(PIVOT_ROW_KEY));
+        let col_key = pivot_key(row.fields.remove(PIVOT_COL_KEY));
+        let value = row
+            .fields
+            .remove(PIVOT_VALUE)
+            .unwrap_or(serde_json::Value::Null);
+        columns.insert(col_key.clone());
+        cells.entry(row_key).or_default().insert(col_key, value);
+    }
+
+    let missing = match function {
+        AggregateFunction::Count => serde_json::json!(0),
+        _ => serde_json::Value::Null,
+    };
+    let mut row_keys: Vec<String> = cells.keys().cloned().collect();
+    row_keys.sort();
+    row_keys
         .into_iter()
-        .map(|fields| ResultRow { fields })
-        .collect();
+        .map(|row_key| {
+            let mut values = cells.remove(&row_key).unwrap_or_default();
+            let mut fields: BTreeMap<String, serde_json::Value> = columns
+                .iter()
+                .map(|col| {
+                    let value = values.remove(col).unwrap_or_else(|| missing.clone());
+                    (col.clone(), value)
+                })
+                .collect();
+            fields.insert(PIVOT_ROW_COLUMN.to_string(), serde_json::json!(row_key));
+            ResultRow { fields }
+        })
+        .collect()
+}
 
-    Ok(QueryResult {
-        rows: result_rows,
-        total,
-    })
+/// A pivot axis value as a row or column key; documents without one are
+/// grouped under `(none)`.
+fn pivot_key(value: Option<serde_json::Value>) -> String {
+    match value {
+        Some(serde_json::Value::String(s)) => s,
+        None | Some(serde_json::Value::Null) => "(none)".to_string(),
+        Some(other) => other.to_string(),
+    }
+}
+
+/// Sort rows by similarity to `order.text`, then apply LIMIT/OFFSET.
+///
+/// Rows whose document has no stored embedding always sort last.
+fn rank_by_similarity(
+    index: &IndexManager,
+    rows: Vec<ResultRow>,
+    order: &SimilarityOrder,
+    embed: &Embed<'_>,
+) -> Result<Vec<ResultRow>, MkbError> {
+    let reference = embed(&order.text)?;
+
+    let mut scored = Vec::with_capacity(rows.len());
+    for mut row in rows {
+        let id = row
+            .fields
+            .remove(SIMILARITY_ID_COLUMN)
+            .and_then(|v| v.as_str().map(String::from))
+            .unwrap_or_default();
+        let score = index
+            .get_embedding(&id)
+            .map_err(|e| MkbError::Query(format!("Embedding lookup failed: {e}")))?
+            .map(|emb| cosine_similarity(&reference, &emb));
+        scored.push((score, row));
+    }
+
+    // Stable sort keeps the SQL ORDER BY as tie-breaker
+    scored.sort_by(|(a, _), (b, _)| match (a, b) {
+        (Some(a), Some(b)) if order.descending => b.total_cmp(a),
+        (Some(a), Some(b)) => a.total_cmp(b),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+
+    let offset = order.offset.unwrap_or(0) as usize;
+    let limit = order.limit.map_or(usize::MAX, |n| n as usize);
+    Ok(scored
+        .into_iter()
+        .skip(offset)
+        .take(limit)
+        .map(|(_, row)| row)
+        .collect())
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
+    let dot: f64 = a
+        .iter()
+        .zip(b)
+        .map(|(x, y)| f64::from(*x) * f64::from(*y))
+        .sum();
+    let norm_a: f64 = a.iter().map(|x| f64::from(*x).powi(2)).sum::<f64>().sqrt();
+    let norm_b: f64 = b.iter().map(|x| f64::from(*x).powi(2)).sum::<f64>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::compiler::compile;
+    use crate::transform::{ConfidenceFilterTransformer, DeduplicateTransformer};
     use chrono::{TimeZone, Utc};
     use mkb_core::document::Document;
     use mkb_core::temporal::{DecayProfile, RawTemporalInput, TemporalPrecision};
@@ -102,6 +457,7 @@ mod tests {
             valid_until: Some(utc(2025, 8, 10)),
             temporal_precision: Some(TemporalPrecision::Day),
             occurred_at: None,
+            occurred_until: None,
         };
         let profile = DecayProfile::default_profile();
         let mut doc = Document::new(
@@ -176,6 +532,43 @@ mod tests {
         assert!(titles.contains(&"Beta Project"));
     }
 
+    #[test]
+    fn execute_returns_tags_as_parsed_array() {
+        let index = IndexManager::in_memory().unwrap();
+        let mut doc = make_doc("proj-tags-001", "project", "Tagged", "");
+        doc.tags = vec![" rust ".to_string(), String::new(), "cli".to_string()];
+        index.index_document(&doc).unwrap();
+
+        let query = mkb_parser::parse_mkql("SELECT id, tags FROM project").unwrap();
+        let result = execute(&index, &compile(&query).unwrap()).unwrap();
+
+        assert_eq!(
+            result.rows[0].fields["tags"],
+            serde_json::json!(["rust", "cli"])
+        );
+        assert_eq!(
+            index.query_by_id("proj-tags-001").unwrap().unwrap().tags,
+            vec!["rust", "cli"]
+        );
+    }
+
+    #[test]
+    fn execute_same_query_twice_serializes_identically() {
+        let index = setup_index();
+        let query = mkb_parser::parse_mkql("SELECT * FROM project ORDER BY title").unwrap();
+        let compiled = compile(&query).unwrap();
+
+        let first = execute(&index, &compiled).unwrap();
+        let second = execute(&index, &compiled).unwrap();
+        let first_json = serde_json::to_string(&first).unwrap();
+        assert_eq!(first_json, serde_json::to_string(&second).unwrap());
+
+        let keys: Vec<&String> = first.rows[0].fields.keys().collect();
+        let mut sorted = keys.clone();
+        sorted.sort();
+        assert_eq!(keys, sorted);
+    }
+
     #[test]
     fn execute_with_where_clause() {
         let index = setup_index();
@@ -205,40 +598,1245 @@ mod tests {
     }
 
     #[test]
-    fn execute_with_limit() {
-        let index = setup_index();
-        let query = mkb_parser::parse_mkql("SELECT * FROM project LIMIT 1").unwrap();
-        let compiled = compile(&query).unwrap();
-        let result = execute(&index, &compiled).unwrap();
+    fn execute_match_phrase_respects_word_order() {
+        let index = IndexManager::in_memory().unwrap();
+        for (id, title, body) in [
+            (
+                "note-ordered-001",
+                "Sprint Review",
+                "Deep learning is powerful",
+            ),
+            ("note-reversed-001", "Review of Sprint", "Learning is deep"),
+        ] {
+            index
+                .index_document(&make_doc(id, "note", title, body))
+                .unwrap();
+        }
+        let ids = |mkql: &str| -> Vec<String> {
+            let query = mkb_parser::parse_mkql(mkql).unwrap();
+            let result = execute(&index, &compile(&query).unwrap()).unwrap();
+            result
+                .rows
+                .iter()
+                .map(|r| r.fields["id"].as_str().unwrap().to_string())
+                .collect()
+        };
 
-        assert_eq!(result.total, 1);
+        // Both bodies hold both words; only one has them as a phrase
+        assert_eq!(
+            ids("SELECT * FROM note WHERE BODY CONTAINS 'deep learning'").len(),
+            2
+        );
+        assert_eq!(
+            ids("SELECT * FROM note WHERE BODY MATCH_PHRASE 'deep learning'"),
+            ["note-ordered-001"]
+        );
+        // Case-insensitive, like the rest of FTS5
+        assert_eq!(
+            ids("SELECT * FROM note WHERE BODY MATCH_PHRASE 'DEEP Learning'"),
+            ["note-ordered-001"]
+        );
+        assert_eq!(
+            ids("SELECT * FROM note WHERE TITLE MATCH_PHRASE 'sprint review'"),
+            ["note-ordered-001"]
+        );
+        // A title phrase does not match body text
+        assert!(ids("SELECT * FROM note WHERE TITLE MATCH_PHRASE 'deep learning'").is_empty());
     }
 
     #[test]
-    fn execute_near_returns_results() {
+    fn execute_link_count_filters_by_degree() {
         let index = setup_index();
-        // Store embeddings for the test documents
-        let emb1 = mkb_index::mock_embedding("Rust systems programming");
+        let link = |rel: &str, target: &str| mkb_core::link::Link {
+            rel: rel.to_string(),
+            target: target.to_string(),
+            observed_at: utc(2025, 2, 10),
+            metadata: None,
+        };
         index
-            .store_embedding("proj-alpha-001", &emb1, "mock")
+            .store_links(
+                "proj-alpha-001",
+                &[
+                    link("owner", "pers-jane-001"),
+                    link("depends_on", "proj-beta-001"),
+                    link("discussed_in", "meet-standup-001"),
+                ],
+            )
+            .unwrap();
+        index
+            .store_links("proj-beta-001", &[link("owner", "pers-jane-001")])
             .unwrap();
 
-        let emb2 = mkb_index::mock_embedding("Python data pipeline");
+        let ids = |mkql: &str| -> Vec<String> {
+            let compiled = compile(&mkb_parser::parse_mkql(mkql).unwrap()).unwrap();
+            let mut ids: Vec<String> = execute(&index, &compiled)
+                .unwrap()
+                .rows
+                .iter()
+                .filter_map(|r| {
+                    r.fields
+                        .get("id")
+                        .and_then(|v| v.as_str())
+                        .map(String::from)
+                })
+                .collect();
+            ids.sort();
+            ids
+        };
+
+        assert_eq!(
+            ids("SELECT * FROM project WHERE LINK_COUNT() > 2"),
+            vec!["proj-alpha-001"]
+        );
+        assert_eq!(
+            ids("SELECT * FROM project WHERE LINK_COUNT('owner') = 1"),
+            vec!["proj-alpha-001", "proj-beta-001"]
+        );
+        assert_eq!(
+            ids("SELECT * FROM project WHERE REVERSE_LINK_COUNT() >= 1"),
+            vec!["proj-beta-001"]
+        );
+        assert!(ids("SELECT * FROM meeting WHERE LINK_COUNT() > 0").is_empty());
+    }
+
+    #[test]
+    fn execute_as_of_range_matches_overlapping_occurrences() {
+        let index = setup_index();
+        // Observed 2025-02-10 with no occurrence: treated as happening then
+        let mut conference = make_doc("proj-conf-001", "project", "Conference", "");
+        conference.temporal.occurred_at = Some(utc(2025, 1, 5));
+        conference.temporal.occurred_until = Some(utc(2025, 1, 8));
+        index.index_document(&conference).unwrap();
+        let mut launch = make_doc("proj-launch-001", "project", "Launch", "");
+        launch.temporal.occurred_at = Some(utc(2025, 1, 20));
+        index.index_document(&launch).unwrap();
+
+        let ids = |start: &str, end: &str| {
+            let mkql = format!("SELECT id FROM project WHERE AS_OF_RANGE('{start}', '{end}')");
+            let compiled = compile(&mkb_parser::parse_mkql(&mkql).unwrap()).unwrap();
+            let mut ids: Vec<String> = execute(&index, &compiled)
+                .unwrap()
+                .rows
+                .iter()
+                .map(|r| r.fields["id"].as_str().unwrap().to_string())
+                .collect();
+            ids.sort();
+            ids
+        };
+
+        assert_eq!(ids("2025-01-01", "2025-01-05"), vec!["proj-conf-001"]);
+        assert_eq!(
+            ids("2025-01-07", "2025-01-31"),
+            vec!["proj-conf-001", "proj-launch-001"]
+        );
+        assert!(ids("2025-01-09", "2025-01-19").is_empty());
+        assert_eq!(ids("2025-01-20", "2025-01-20"), vec!["proj-launch-001"]);
+        assert_eq!(
+            ids("2025-02-01", "2025-02-28"),
+            vec!["proj-alpha-001", "proj-beta-001"]
+        );
+
+        let compiled = compile(
+            &mkb_parser::parse_mkql(
+                "SELECT id FROM project WHERE AS_OF_RANGE('2025-01-01', '2025-01-05')",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let plan = explain_plan(&index, &compiled).unwrap();
+        assert!(
+            plan.iter().any(|row| row
+                .detail
+                .contains("idx_documents_type (doc_type=? AND <expr>>?)")),
+            "{plan:?}"
+        );
+    }
+
+    #[test]
+    fn execute_overlaps_matches_validity_windows() {
+        let index = IndexManager::in_memory().unwrap();
+        // Default window: observed 2025-02-10, valid until 2025-08-10
         index
-            .store_embedding("proj-beta-001", &emb2, "mock")
+            .index_document(&make_doc("proj-spanning-001", "project", "Spanning", ""))
             .unwrap();
+        for (id, observed, valid_until) in [
+            ("proj-exact-001", utc(2025, 3, 1), utc(2025, 3, 31)),
+            ("proj-expired-001", utc(2024, 12, 1), utc(2025, 2, 28)),
+            ("proj-later-001", utc(2025, 4, 15), utc(2025, 10, 1)),
+        ] {
+            let mut doc = make_doc(id, "project", id, "");
+            doc.temporal.observed_at = observed;
+            doc.temporal.valid_until = valid_until;
+            index.index_document(&doc).unwrap();
+        }
 
-        // Query with NEAR - should find documents semantically
-        let query = mkb_parser::parse_mkql(
-            "SELECT * FROM project WHERE NEAR('Rust systems programming', 0.0)",
+        let compiled = compile(
+            &mkb_parser::parse_mkql(
+                "SELECT id FROM project WHERE OVERLAPS('2025-03-01', '2025-03-31')",
+            )
+            .unwrap(),
         )
         .unwrap();
+        let mut ids: Vec<String> = execute(&index, &compiled)
+            .unwrap()
+            .rows
+            .iter()
+            .map(|r| r.fields["id"].as_str().unwrap().to_string())
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec!["proj-exact-001", "proj-spanning-001"]);
+    }
+
+    #[test]
+    fn execute_overlaps_now_is_current_without_supersession() {
+        let index = IndexManager::in_memory().unwrap();
+        let now = Utc::now();
+        for (id, observed, valid_until, superseded) in [
+            (
+                "proj-live-001",
+                now - chrono::Duration::days(1),
+                now + chrono::Duration::days(30),
+                false,
+            ),
+            (
+                "proj-old-001",
+                now - chrono::Duration::days(60),
+                now - chrono::Duration::days(1),
+                false,
+            ),
+            (
+                "proj-replaced-001",
+                now - chrono::Duration::days(2),
+                now + chrono::Duration::days(30),
+                true,
+            ),
+        ] {
+            let mut doc = make_doc(id, "project", id, "");
+            doc.temporal.observed_at = observed;
+            doc.temporal.valid_until = valid_until;
+            if superseded {
+                doc.superseded_by = Some("proj-live-001".to_string());
+            }
+            index.index_document(&doc).unwrap();
+        }
+
+        let ids = |mkql: &str| {
+            let compiled = compile(&mkb_parser::parse_mkql(mkql).unwrap()).unwrap();
+            let mut ids: Vec<String> = execute(&index, &compiled)
+                .unwrap()
+                .rows
+                .iter()
+                .map(|r| r.fields["id"].as_str().unwrap().to_string())
+                .collect();
+            ids.sort();
+            ids
+        };
+
+        assert_eq!(
+            ids("SELECT id FROM project WHERE OVERLAPS_NOW()"),
+            vec!["proj-live-001", "proj-replaced-001"]
+        );
+        assert_eq!(
+            ids("SELECT id FROM project WHERE OVERLAPS_NOW() AND LATEST()"),
+            ids("SELECT id FROM project WHERE CURRENT()")
+        );
+    }
+
+    #[test]
+    fn execute_future_observation_under_both_settings() {
+        use crate::compiler::{compile_with_options, CompileOptions};
+
+        let index = IndexManager::in_memory().unwrap();
+        // An hour ago is never "future", whatever the settings
+        for (id, offset) in [
+            ("proj-planned-001", chrono::Duration::days(2)),
+            ("proj-recent-001", chrono::Duration::hours(-1)),
+        ] {
+            let input = RawTemporalInput {
+                observed_at: Some(Utc::now() + offset),
+                valid_until: None,
+                temporal_precision: Some(TemporalPrecision::Day),
+                occurred_at: None,
+                occurred_until: None,
+            };
+            let doc = Document::new(
+                id.to_string(),
+                "project".to_string(),
+                id.to_string(),
+                input,
+                &DecayProfile::default_profile(),
+            )
+            .unwrap();
+            index.index_document(&doc).unwrap();
+        }
+
+        let count = |mkql: &str, options: &CompileOptions| {
+            let query = mkb_parser::parse_mkql(mkql).unwrap();
+            let compiled = compile_with_options(&query, options.clone()).unwrap();
+            execute(&index, &compiled).unwrap().total
+        };
+        let fresh = "SELECT * FROM project WHERE FRESH('7d')";
+        let current = "SELECT * FROM project WHERE CURRENT()";
+
+        let defaults = CompileOptions::default();
+        assert_eq!(count(fresh, &defaults), 1);
+        assert_eq!(count(current, &defaults), 2);
+
+        let include_all = CompileOptions {
+            future_in_current: true,
+            future_in_fresh: true,
+            ..CompileOptions::default()
+        };
+        assert_eq!(count(fresh, &include_all), 2);
+        assert_eq!(count(current, &include_all), 2);
+
+        let exclude_all = CompileOptions {
+            future_in_current: false,
+            future_in_fresh: false,
+            ..CompileOptions::default()
+        };
+        assert_eq!(count(fresh, &exclude_all), 1);
+        assert_eq!(count(current, &exclude_all), 1);
+    }
+
+    #[test]
+    fn execute_changed_matches_recently_edited_documents() {
+        let index = IndexManager::in_memory().unwrap();
+        let mut edited = make_doc("proj-edited-001", "project", "Edited", "");
+        let mut untouched = make_doc("proj-untouched-001", "project", "Untouched", "");
+        for doc in [&mut edited, &mut untouched] {
+            doc.created_at = utc(2025, 2, 10);
+            doc.modified_at = utc(2025, 2, 10);
+            index.index_document(doc).unwrap();
+        }
+
+        edited.modified_at = Utc::now();
+        index.index_document(&edited).unwrap();
+
+        let query = mkb_parser::parse_mkql("SELECT * FROM project WHERE CHANGED('1d')").unwrap();
+        let result = execute(&index, &compile(&query).unwrap()).unwrap();
+        assert_eq!(result.total, 1);
+        assert_eq!(result.rows[0].fields["id"], "proj-edited-001");
+    }
+
+    #[test]
+    fn execute_streaming_yields_rows_on_demand() {
+        let index = setup_index();
+        let query = mkb_parser::parse_mkql("SELECT id FROM project ORDER BY id").unwrap();
         let compiled = compile(&query).unwrap();
-        assert!(compiled.uses_semantic);
 
-        let result = execute(&index, &compiled).unwrap();
-        // With threshold 0.0, both should match (very permissive)
-        assert!(result.total >= 1);
+        let mut stream = execute_streaming(&index, &compiled).unwrap();
+        let first = stream.next().unwrap().unwrap();
+        assert_eq!(first.fields["id"], "proj-alpha-001");
+
+        let streamed: Vec<_> = execute_streaming(&index, &compiled)
+            .unwrap()
+            .map(|row| row.unwrap().fields)
+            .collect();
+        let collected: Vec<_> = execute(&index, &compiled)
+            .unwrap()
+            .rows
+            .into_iter()
+            .map(|row| row.fields)
+            .collect();
+        assert_eq!(streamed, collected);
+    }
+
+    fn set_op_ids(index: &IndexManager, mkql: &str) -> Vec<(String, String)> {
+        let compiled = compile(&mkb_parser::parse_mkql(mkql).unwrap()).unwrap();
+        let mut rows: Vec<(String, String)> = execute(index, &compiled)
+            .unwrap()
+            .rows
+            .into_iter()
+            .map(|row| {
+                let field = |name: &str| row.fields[name].as_str().unwrap().to_string();
+                (field("doc_type"), field("id"))
+            })
+            .collect();
+        rows.sort();
+        rows
+    }
+
+    #[test]
+    fn execute_union_keeps_each_row_doc_type() {
+        let index = setup_index();
+        let rows = set_op_ids(
+            &index,
+            "SELECT * FROM project WHERE title LIKE 'Alpha%' \
+             UNION SELECT * FROM meeting WHERE title LIKE 'Daily%'",
+        );
+        assert_eq!(
+            rows,
+            vec![
+                ("meeting".to_string(), "meet-standup-001".to_string()),
+                ("project".to_string(), "proj-alpha-001".to_string()),
+            ]
+        );
+    }
+
+    fn tagged_index() -> IndexManager {
+        let index = IndexManager::in_memory().unwrap();
+        for (id, tags) in [
+            ("proj-rust-001", vec!["rust", "cli"]),
+            ("proj-rusty-001", vec!["rusty", "trustworthy"]),
+            ("proj-ai-001", vec!["rust", "ai"]),
+            ("proj-none-001", vec![]),
+        ] {
+            let mut doc = make_doc(id, "project", id, "");
+            doc.tags = tags.into_iter().map(str::to_string).collect();
+            index.index_document(&doc).unwrap();
+        }
+        index
+    }
+
+    fn matching_ids(index: &IndexManager, mkql: &str) -> Vec<String> {
+        let query = mkb_parser::parse_mkql(mkql).unwrap();
+        let result = execute(index, &compile(&query).unwrap()).unwrap();
+        let mut ids: Vec<String> = result
+            .rows
+            .iter()
+            .map(|r| r.fields["id"].as_str().unwrap().to_string())
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn execute_tags_contains_matches_whole_tags_only() {
+        let index = tagged_index();
+
+        assert_eq!(
+            matching_ids(&index, "SELECT id FROM project WHERE TAGS CONTAINS 'rust'"),
+            vec!["proj-ai-001", "proj-rust-001"]
+        );
+        assert_eq!(
+            matching_ids(&index, "SELECT id FROM project WHERE TAGS CONTAINS 'cli'"),
+            vec!["proj-rust-001"]
+        );
+        assert!(
+            matching_ids(&index, "SELECT id FROM project WHERE TAGS CONTAINS 'rus%'").is_empty()
+        );
+    }
+
+    #[test]
+    fn execute_tags_contains_any_and_all() {
+        let index = tagged_index();
+
+        assert_eq!(
+            matching_ids(
+                &index,
+                "SELECT id FROM project WHERE TAGS CONTAINS ANY ('cli', 'ai')"
+            ),
+            vec!["proj-ai-001", "proj-rust-001"]
+        );
+        assert!(matching_ids(
+            &index,
+            "SELECT id FROM project WHERE TAGS CONTAINS ANY ('go', 'java')"
+        )
+        .is_empty());
+        assert_eq!(
+            matching_ids(
+                &index,
+                "SELECT id FROM project WHERE TAGS CONTAINS ALL ('rust', 'ai')"
+            ),
+            vec!["proj-ai-001"]
+        );
+        assert_eq!(
+            matching_ids(
+                &index,
+                "SELECT id FROM project WHERE TAGS CONTAINS 'rusty' OR TAGS CONTAINS 'ai'"
+            ),
+            vec!["proj-ai-001", "proj-rusty-001"]
+        );
+    }
+
+    #[test]
+    fn execute_tags_in_and_includes_all() {
+        let index = tagged_index();
+        let mut spaced = make_doc("proj-ml-001", "project", "ML", "");
+        spaced.tags = vec!["machine learning".to_string(), "ai".to_string()];
+        index.index_document(&spaced).unwrap();
+
+        assert_eq!(
+            matching_ids(&index, "SELECT id FROM project WHERE TAGS IN ('cli')"),
+            vec!["proj-rust-001"]
+        );
+        // Documents matching more than one tag are returned once
+        assert_eq!(
+            matching_ids(
+                &index,
+                "SELECT id FROM project WHERE TAGS IN ('rust', 'ai', 'database')"
+            ),
+            vec!["proj-ai-001", "proj-ml-001", "proj-rust-001"]
+        );
+        assert_eq!(
+            matching_ids(
+                &index,
+                "SELECT id FROM project WHERE TAGS IN ('machine learning')"
+            ),
+            vec!["proj-ml-001"]
+        );
+        assert!(
+            matching_ids(&index, "SELECT id FROM project WHERE TAGS IN ('machine')").is_empty()
+        );
+        assert_eq!(
+            matching_ids(
+                &index,
+                "SELECT id FROM project WHERE TAGS INCLUDES ALL ('ai', 'machine learning')"
+            ),
+            vec!["proj-ml-001"]
+        );
+    }
+
+    #[test]
+    fn execute_tags_is_empty() {
+        let index = tagged_index();
+
+        assert_eq!(
+            matching_ids(&index, "SELECT id FROM project WHERE TAGS IS EMPTY"),
+            vec!["proj-none-001"]
+        );
+        assert_eq!(
+            matching_ids(&index, "SELECT id FROM project WHERE NOT TAGS IS EMPTY").len(),
+            3
+        );
+    }
+
+    #[test]
+    fn execute_linked_depth_follows_chains_and_survives_cycles() {
+        let index = IndexManager::in_memory().unwrap();
+        let link = |rel: &str, target: &str| mkb_core::link::Link {
+            rel: rel.to_string(),
+            target: target.to_string(),
+            observed_at: utc(2025, 2, 10),
+            metadata: None,
+        };
+        for id in ["p1", "p2", "p3", "p4"] {
+            index
+                .index_document(&make_doc(id, "project", id, ""))
+                .unwrap();
+        }
+        index
+            .store_links("p1", &[link("blocked_by", "p2")])
+            .unwrap();
+        index
+            .store_links("p2", &[link("blocked_by", "p3")])
+            .unwrap();
+        index
+            .store_links("p3", &[link("blocked_by", "p1"), link("depends_on", "p4")])
+            .unwrap();
+
+        let ids = |predicate: &str| {
+            matching_ids(&index, &format!("SELECT id FROM project WHERE {predicate}"))
+        };
+
+        assert_eq!(ids("LINKED_DEPTH('blocked_by', 1, 'p3')"), vec!["p2"]);
+        assert_eq!(
+            ids("LINKED_DEPTH('blocked_by', 1, 'p3')"),
+            ids("LINKED('blocked_by', 'p3')")
+        );
+        assert_eq!(
+            ids("LINKED_DEPTH('blocked_by', 1)"),
+            ids("LINKED('blocked_by')")
+        );
+        assert_eq!(ids("LINKED_DEPTH('blocked_by', 2, 'p3')"), vec!["p1", "p2"]);
+        assert_eq!(
+            ids("LINKED_DEPTH('blocked_by', 10, 'p3')"),
+            vec!["p1", "p2", "p3"]
+        );
+        assert_eq!(
+            ids("LINKED_DEPTH_ANY('blocked_by|depends_on', 2, 'p4')"),
+            vec!["p2", "p3"]
+        );
+
+        let too_deep =
+            mkb_parser::parse_mkql("SELECT id FROM project WHERE LINKED_DEPTH('blocked_by', 11)")
+                .unwrap();
+        assert!(compile(&too_deep).unwrap_err().contains("between 1 and 10"));
+    }
+
+    #[test]
+    fn execute_intersect_of_type_and_tag_filter() {
+        let index = setup_index();
+        let mut tagged = make_doc("proj-gamma-001", "project", "Gamma", "");
+        tagged.tags = vec!["rust".to_string()];
+        index.index_document(&tagged).unwrap();
+        let mut other_type = make_doc("meet-rust-001", "meeting", "Rust sync", "");
+        other_type.tags = vec!["rust".to_string()];
+        index.index_document(&other_type).unwrap();
+
+        let rows = set_op_ids(
+            &index,
+            "SELECT id, doc_type FROM project \
+             INTERSECT SELECT id, doc_type FROM project WHERE tags LIKE '%rust%'",
+        );
+        assert_eq!(
+            rows,
+            vec![("project".to_string(), "proj-gamma-001".to_string())]
+        );
+    }
+
+    #[test]
+    fn execute_except_drops_superseded_documents() {
+        let index = setup_index();
+        let mut old = make_doc("proj-alpha-000", "project", "Alpha v0", "");
+        old.superseded_by = Some("proj-alpha-001".to_string());
+        index.index_document(&old).unwrap();
+
+        let rows = set_op_ids(
+            &index,
+            "SELECT * FROM project \
+             EXCEPT SELECT * FROM project WHERE superseded_by LIKE '%'",
+        );
+        assert_eq!(
+            rows,
+            vec![
+                ("project".to_string(), "proj-alpha-001".to_string()),
+                ("project".to_string(), "proj-beta-001".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn execute_single_cte() {
+        let index = setup_index();
+        let rows = set_op_ids(
+            &index,
+            "WITH confident AS (SELECT * FROM project WHERE confidence > 0.9) \
+             SELECT * FROM confident WHERE BODY CONTAINS 'rust'",
+        );
+        assert_eq!(
+            rows,
+            vec![("project".to_string(), "proj-alpha-001".to_string())]
+        );
+    }
+
+    #[test]
+    fn execute_chained_ctes() {
+        let index = setup_index();
+        let rows = set_op_ids(
+            &index,
+            "WITH everything AS (SELECT * FROM project UNION SELECT * FROM meeting), \
+                  titled AS (SELECT * FROM everything WHERE title LIKE '%Project') \
+             SELECT * FROM titled WHERE confidence < 0.9",
+        );
+        assert_eq!(
+            rows,
+            vec![("project".to_string(), "proj-beta-001".to_string())]
+        );
+    }
+
+    #[test]
+    fn execute_cte_used_twice() {
+        let index = setup_index();
+        let rows = set_op_ids(
+            &index,
+            "WITH p AS (SELECT * FROM project) \
+             SELECT * FROM p WHERE title LIKE 'Alpha%' \
+             UNION SELECT * FROM p WHERE confidence < 0.9",
+        );
+        assert_eq!(
+            rows,
+            vec![
+                ("project".to_string(), "proj-alpha-001".to_string()),
+                ("project".to_string(), "proj-beta-001".to_string()),
+            ]
+        );
+    }
+
+    /// `(doc_type, aggregate)` pairs of a query grouped by `doc_type`.
+    fn grouped_rows(index: &IndexManager, mkql: &str, column: &str) -> Vec<(String, f64)> {
+        let compiled = compile(&mkb_parser::parse_mkql(mkql).unwrap()).unwrap();
+        execute(index, &compiled)
+            .unwrap()
+            .rows
+            .iter()
+            .map(|r| {
+                (
+                    r.fields["doc_type"].as_str().unwrap().to_string(),
+                    r.fields[column].as_f64().unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn execute_group_by_having_count() {
+        let index = setup_index();
+        let everything = "WITH docs AS (SELECT * FROM project UNION SELECT * FROM meeting) ";
+
+        let all = grouped_rows(
+            &index,
+            &format!("{everything}SELECT doc_type, COUNT(*) FROM docs GROUP BY doc_type"),
+            "count",
+        );
+        assert_eq!(
+            all,
+            vec![("meeting".to_string(), 1.0), ("project".to_string(), 2.0)]
+        );
+
+        let several = grouped_rows(
+            &index,
+            &format!(
+                "{everything}SELECT doc_type, COUNT(*) AS n FROM docs \
+                 GROUP BY doc_type HAVING COUNT(*) > 1"
+            ),
+            "n",
+        );
+        assert_eq!(several, vec![("project".to_string(), 2.0)]);
+    }
+
+    #[test]
+    fn execute_having_avg_filters_groups() {
+        let index = setup_index();
+        let mut gamma = make_doc("proj-gamma-001", "project", "Gamma Project", "Draft");
+        gamma.confidence = 0.3;
+        index.index_document(&gamma).unwrap();
+
+        let confident = grouped_rows(
+            &index,
+            "WITH docs AS (SELECT * FROM project UNION SELECT * FROM meeting) \
+             SELECT doc_type, AVG(confidence) FROM docs \
+             GROUP BY doc_type HAVING AVG(confidence) >= 0.8 ORDER BY avg_confidence DESC",
+            "avg_confidence",
+        );
+        assert_eq!(confident, vec![("meeting".to_string(), 0.95)]);
+    }
+
+    #[test]
+    fn execute_having_compares_aggregate_dates_as_dates() {
+        let index = IndexManager::in_memory().unwrap();
+        let mut doc = make_doc("sign-ping-001", "signal", "Ping", "Just now");
+        // Earlier today: as text, the RFC 3339 `T` sorts after the space in
+        // `datetime('now')`, so only a date comparison keeps this group
+        doc.temporal.observed_at = Utc::now() - chrono::Duration::minutes(1);
+        index.index_document(&doc).unwrap();
+
+        let rows = grouped_rows(
+            &index,
+            "SELECT doc_type, COUNT(*) FROM signal GROUP BY doc_type \
+             HAVING MAX(observed_at) <= NOW()",
+            "count",
+        );
+        assert_eq!(rows, vec![("signal".to_string(), 1.0)]);
+    }
+
+    #[test]
+    fn execute_pivot_doc_type_by_month() {
+        let index = setup_index();
+        let mut gamma = make_doc("proj-gamma-001", "project", "Gamma Project", "Later");
+        gamma.temporal.observed_at = utc(2025, 3, 5);
+        index.index_document(&gamma).unwrap();
+
+        let query =
+            mkb_parser::parse_mkql("SELECT PIVOT doc_type BY month(observed_at) COUNT(*)").unwrap();
+        let result = execute(&index, &compile(&query).unwrap()).unwrap();
+        let table: Vec<(String, i64, i64)> = result
+            .rows
+            .iter()
+            .map(|r| {
+                (
+                    r.fields[PIVOT_ROW_COLUMN].as_str().unwrap().to_string(),
+                    r.fields["2025-02"].as_i64().unwrap(),
+                    r.fields["2025-03"].as_i64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            table,
+            vec![("meeting".to_string(), 1, 0), ("project".to_string(), 2, 1),]
+        );
+
+        let query = mkb_parser::parse_mkql(
+            "SELECT PIVOT doc_type BY month(observed_at) COUNT(*) FROM project \
+             WHERE confidence > 0.9",
+        )
+        .unwrap();
+        let result = execute(&index, &compile(&query).unwrap()).unwrap();
+        assert_eq!(result.total, 1);
+        assert_eq!(result.rows[0].fields[PIVOT_ROW_COLUMN], "project");
+        assert_eq!(result.rows[0].fields["2025-02"], 1);
+        assert_eq!(result.rows[0].fields["2025-03"], 1);
+    }
+
+    #[test]
+    fn execute_date_arithmetic_relative_to_now() {
+        let index = IndexManager::in_memory().unwrap();
+        for (id, observed_days_ago, valid_for_days) in [
+            ("proj-recent-001", 2, 10),
+            ("proj-older-001", 20, 30),
+            ("proj-ancient-001", 400, 600),
+        ] {
+            let observed_at = Utc::now() - chrono::Duration::days(observed_days_ago);
+            let input = RawTemporalInput {
+                observed_at: Some(observed_at),
+                valid_until: Some(observed_at + chrono::Duration::days(valid_for_days)),
+                temporal_precision: Some(TemporalPrecision::Day),
+                occurred_at: None,
+                occurred_until: None,
+            };
+            let doc = Document::new(
+                id.to_string(),
+                "project".to_string(),
+                id.to_string(),
+                input,
+                &DecayProfile::default_profile(),
+            )
+            .unwrap();
+            index.index_document(&doc).unwrap();
+        }
+
+        let ids = |mkql: &str| {
+            let query = mkb_parser::parse_mkql(mkql).unwrap();
+            let result = execute(&index, &compile(&query).unwrap()).unwrap();
+            result
+                .rows
+                .iter()
+                .map(|r| r.fields["id"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            ids("SELECT id FROM project WHERE observed_at >= NOW() - 7d"),
+            vec!["proj-recent-001"]
+        );
+        // Expiring within two weeks: recent (8 days left) and older (10 days
+        // left); ancient expired long ago and is also before the cutoff
+        assert_eq!(
+            ids("SELECT id FROM project WHERE valid_until < NOW() + 14d AND valid_until >= NOW() ORDER BY id"),
+            vec!["proj-older-001", "proj-recent-001"]
+        );
+        assert_eq!(
+            ids("SELECT id FROM project WHERE observed_at < NOW() - 1y"),
+            vec!["proj-ancient-001"]
+        );
+    }
+
+    #[test]
+    fn execute_eff_confidence_decays_old_documents() {
+        let index = setup_index();
+        // setup_index docs were observed 2025-02-10 and have long since decayed
+        let input = RawTemporalInput {
+            observed_at: Some(Utc::now()),
+            valid_until: None,
+            temporal_precision: Some(TemporalPrecision::Day),
+            occurred_at: None,
+            occurred_until: None,
+        };
+        let mut fresh = Document::new(
+            "proj-fresh-001".to_string(),
+            "project".to_string(),
+            "Fresh Project".to_string(),
+            input,
+            &DecayProfile::default_profile(),
+        )
+        .unwrap();
+        fresh.confidence = 0.9;
+        index.index_document(&fresh).unwrap();
+
+        let query =
+            mkb_parser::parse_mkql("SELECT * FROM project WHERE EFF_CONFIDENCE(> 0.5)").unwrap();
+        let compiled = compile(&query).unwrap();
+        let result = execute(&index, &compiled).unwrap();
+
+        assert_eq!(result.total, 1);
+        assert_eq!(
+            result.rows[0].fields.get("id").and_then(|v| v.as_str()),
+            Some("proj-fresh-001")
+        );
+    }
+
+    fn regex_ids(index: &IndexManager, mkql: &str) -> Vec<String> {
+        let compiled = compile(&mkb_parser::parse_mkql(mkql).unwrap()).unwrap();
+        let mut ids: Vec<String> = execute(index, &compiled)
+            .unwrap()
+            .rows
+            .iter()
+            .filter_map(|r| {
+                r.fields
+                    .get("id")
+                    .and_then(|v| v.as_str())
+                    .map(String::from)
+            })
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn execute_regex_is_case_sensitive_by_default() {
+        let index = setup_index();
+        assert!(regex_ids(&index, "SELECT * FROM project WHERE title REGEX 'alpha'").is_empty());
+        assert_eq!(
+            regex_ids(
+                &index,
+                "SELECT * FROM project WHERE title REGEX '(?i)alpha'"
+            ),
+            vec!["proj-alpha-001"]
+        );
+    }
+
+    #[test]
+    fn execute_regex_anchors_and_character_classes() {
+        let index = setup_index();
+        index
+            .index_document(&make_doc("meet-s12-001", "meeting", "Sprint 12", ""))
+            .unwrap();
+        index
+            .index_document(&make_doc("meet-sx-001", "meeting", "Sprint X", ""))
+            .unwrap();
+        index
+            .index_document(&make_doc("meet-pre-001", "meeting", "Pre Sprint 3", ""))
+            .unwrap();
+
+        assert_eq!(
+            regex_ids(
+                &index,
+                "SELECT * FROM meeting WHERE title REGEX '^Sprint [0-9]+$'"
+            ),
+            vec!["meet-s12-001"]
+        );
+        assert_eq!(
+            regex_ids(
+                &index,
+                "SELECT * FROM meeting WHERE title REGEX 'Sprint \\d'"
+            ),
+            vec!["meet-pre-001", "meet-s12-001"]
+        );
+        assert_eq!(
+            regex_ids(
+                &index,
+                "SELECT * FROM project WHERE title REGEX '^[AB][a-z]+ Project$'"
+            ),
+            vec!["proj-alpha-001", "proj-beta-001"]
+        );
+    }
+
+    #[test]
+    fn execute_annotated_matches_index_annotations() {
+        let index = setup_index();
+        index
+            .add_annotation("proj-beta-001", "reviewed", "Verified", "tester")
+            .unwrap();
+        let ids = |mkql: &str| -> Vec<String> {
+            let compiled = compile(&mkb_parser::parse_mkql(mkql).unwrap()).unwrap();
+            execute(&index, &compiled)
+                .unwrap()
+                .rows
+                .iter()
+                .map(|r| r.fields["id"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        assert_eq!(
+            ids("SELECT id FROM project WHERE ANNOTATED('reviewed')"),
+            vec!["proj-beta-001"]
+        );
+        assert_eq!(
+            ids("SELECT id FROM project WHERE NOT ANNOTATED('reviewed')"),
+            vec!["proj-alpha-001"]
+        );
+        assert!(ids("SELECT id FROM project WHERE ANNOTATED('needs-update')").is_empty());
+    }
+
+    #[test]
+    fn explain_plan_reports_type_index_without_binding_params() {
+        let index = setup_index();
+        let query = mkb_parser::parse_mkql("SELECT * FROM project WHERE title = :title").unwrap();
+        let plan = explain_plan(&index, &compile(&query).unwrap()).unwrap();
+        assert!(
+            plan.iter()
+                .any(|row| row.detail.contains("idx_documents_type")),
+            "{plan:?}"
+        );
+    }
+
+    #[test]
+    fn execute_applies_transformers_in_order() {
+        let index = setup_index();
+        let query = mkb_parser::parse_mkql(
+            "SELECT id, confidence FROM project UNION SELECT id, confidence FROM project",
+        )
+        .unwrap();
+        let ids = |compiled: &CompiledQuery| -> Vec<String> {
+            execute(&index, compiled)
+                .unwrap()
+                .rows
+                .iter()
+                .map(|r| r.fields["id"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        let compiled = compile(&query).unwrap();
+        assert_eq!(ids(&compiled).len(), 4);
+
+        let deduped = compiled.clone().with_transformer(DeduplicateTransformer);
+        let mut unique = ids(&deduped);
+        unique.sort();
+        assert_eq!(unique, vec!["proj-alpha-001", "proj-beta-001"]);
+
+        let filtered = deduped
+            .clone()
+            .with_transformer(ConfidenceFilterTransformer { min: 0.9 });
+        assert_eq!(ids(&filtered), vec!["proj-alpha-001"]);
+
+        // Streaming applies the same chain
+        let streamed: Vec<ResultRow> = execute_streaming(&index, &filtered)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(streamed.len(), 1);
+    }
+
+    fn run_with_params(
+        index: &IndexManager,
+        mkql: &str,
+        params: &[(&'static str, serde_json::Value)],
+    ) -> Result<Vec<String>, MkbError> {
+        let compiled = compile(&mkb_parser::parse_mkql(mkql).unwrap()).unwrap();
+        let params: HashMap<&str, serde_json::Value> = params.iter().cloned().collect();
+        let result = execute_with_params(index, &compiled, &params)?;
+        Ok(result
+            .rows
+            .iter()
+            .map(|r| r.fields["id"].as_str().unwrap().to_string())
+            .collect())
+    }
+
+    #[test]
+    fn execute_with_params_binds_named_values() {
+        let index = setup_index();
+        let ids = run_with_params(
+            &index,
+            "SELECT id FROM project WHERE title = :title",
+            &[("title", serde_json::json!("Beta Project"))],
+        )
+        .unwrap();
+        assert_eq!(ids, vec!["proj-beta-001"]);
+    }
+
+    #[test]
+    fn execute_with_params_missing_param_errors() {
+        let index = setup_index();
+        let err = run_with_params(&index, "SELECT id FROM project WHERE title = :title", &[])
+            .unwrap_err();
+        assert!(matches!(err, MkbError::Query(ref m) if m.contains(":title")));
+
+        // Plain execute refuses to run with unbound parameters
+        let query = mkb_parser::parse_mkql("SELECT id FROM project WHERE title = :t").unwrap();
+        assert!(execute(&index, &compile(&query).unwrap()).is_err());
+    }
+
+    #[test]
+    fn execute_with_params_coerces_mismatched_types() {
+        let index = setup_index();
+        // confidence is REAL; a numeric string is coerced, not rejected
+        let ids = run_with_params(
+            &index,
+            "SELECT id FROM project WHERE confidence < :max",
+            &[("max", serde_json::json!("0.9"))],
+        )
+        .unwrap();
+        assert_eq!(ids, vec!["proj-beta-001"]);
+
+        let err = run_with_params(
+            &index,
+            "SELECT id FROM project WHERE confidence < :max",
+            &[("max", serde_json::json!("high"))],
+        )
+        .unwrap_err();
+        assert!(matches!(err, MkbError::Query(_)));
+    }
+
+    #[test]
+    fn execute_with_params_does_not_inject_sql() {
+        let index = setup_index();
+        for hostile in [
+            "x' OR '1'='1",
+            "'; DROP TABLE documents; --",
+            "Alpha Project' --",
+        ] {
+            let ids = run_with_params(
+                &index,
+                "SELECT id FROM project WHERE title = :title",
+                &[("title", serde_json::json!(hostile))],
+            )
+            .unwrap();
+            assert!(ids.is_empty(), "{hostile} matched {ids:?}");
+        }
+        assert_eq!(index.query_all().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn execute_with_limit() {
+        let index = setup_index();
+        let query = mkb_parser::parse_mkql("SELECT * FROM project LIMIT 1").unwrap();
+        let compiled = compile(&query).unwrap();
+        let result = execute(&index, &compiled).unwrap();
+
+        assert_eq!(result.total, 1);
+    }
+
+    #[test]
+    fn execute_near_returns_results() {
+        let index = setup_index();
+        // Store embeddings for the test documents
+        let emb1 = mkb_index::mock_embedding("Rust systems programming");
+        index
+            .store_embedding("proj-alpha-001", &emb1, "mock")
+            .unwrap();
+
+        let emb2 = mkb_index::mock_embedding("Python data pipeline");
+        index
+            .store_embedding("proj-beta-001", &emb2, "mock")
+            .unwrap();
+
+        // Query with NEAR - should find documents semantically
+        let query = mkb_parser::parse_mkql(
+            "SELECT * FROM project WHERE NEAR('Rust systems programming', 0.0)",
+        )
+        .unwrap();
+        let compiled = compile(&query).unwrap();
+        assert!(compiled.uses_semantic);
+
+        let result = execute(&index, &compiled).unwrap();
+        // With threshold 0.0, both should match (very permissive)
+        assert!(result.total >= 1);
+    }
+
+    #[test]
+    fn execute_order_by_similarity_matches_hand_computed_ranking() {
+        let index = setup_index();
+        index
+            .index_document(&make_doc("proj-gamma-001", "project", "Gamma", ""))
+            .unwrap();
+        index
+            .index_document(&make_doc("proj-delta-001", "project", "Delta", ""))
+            .unwrap();
+
+        // q is the reference; o is made orthogonal to it (Gram-Schmidt)
+        let q = mkb_index::mock_embedding("machine learning");
+        let r = mkb_index::mock_embedding("gardening");
+        let r_dot_q: f32 = r.iter().zip(&q).map(|(a, b)| a * b).sum();
+        let o: Vec<f32> = r.iter().zip(&q).map(|(a, b)| a - r_dot_q * b).collect();
+        let blend: Vec<f32> = q.iter().zip(&o).map(|(a, b)| a + b).collect();
+        index.store_embedding("proj-alpha-001", &o, "mock").unwrap();
+        index.store_embedding("proj-beta-001", &q, "mock").unwrap();
+        index
+            .store_embedding("proj-gamma-001", &blend, "mock")
+            .unwrap();
+        // proj-delta-001 has no embedding
+
+        // Expected: beta (cos = 1), gamma (cos ≈ 0.71), alpha (cos ≈ 0), delta last
+        let ids = |mkql: &str| -> Vec<String> {
+            let compiled = compile(&mkb_parser::parse_mkql(mkql).unwrap()).unwrap();
+            execute(&index, &compiled)
+                .unwrap()
+                .rows
+                .iter()
+                .map(|r| r.fields["id"].as_str().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(
+            ids("SELECT * FROM project ORDER BY SIMILARITY('machine learning') DESC"),
+            vec![
+                "proj-beta-001",
+                "proj-gamma-001",
+                "proj-alpha-001",
+                "proj-delta-001"
+            ]
+        );
+        assert_eq!(
+            ids("SELECT * FROM project ORDER BY SIMILARITY('machine learning') DESC LIMIT 2"),
+            vec!["proj-beta-001", "proj-gamma-001"]
+        );
+        assert_eq!(
+            ids("SELECT * FROM project ORDER BY SIMILARITY('machine learning') ASC"),
+            vec![
+                "proj-alpha-001",
+                "proj-gamma-001",
+                "proj-beta-001",
+                "proj-delta-001"
+            ]
+        );
+    }
+
+    #[test]
+    fn execute_order_by_similarity_strips_helper_column() {
+        let index = setup_index();
+        let compiled = compile(
+            &mkb_parser::parse_mkql("SELECT title FROM project ORDER BY SIMILARITY('rust')")
+                .unwrap(),
+        )
+        .unwrap();
+        let result = execute(&index, &compiled).unwrap();
+        assert_eq!(result.total, 2);
+        assert!(result
+            .rows
+            .iter()
+            .all(|r| r.fields.keys().eq(std::iter::once("title"))));
+    }
+
+    /// Embeds text as one of two orthogonal unit vectors, depending on
+    /// whether it mentions Rust.
+    struct KeywordProvider;
+
+    impl KeywordProvider {
+        fn vector(text: &str) -> Vec<f32> {
+            let mut v = vec![0.0; mkb_index::EMBEDDING_DIM];
+            v[usize::from(!text.to_lowercase().contains("rust"))] = 1.0;
+            v
+        }
+    }
+
+    impl EmbeddingProvider for KeywordProvider {
+        fn model(&self) -> &str {
+            "keyword"
+        }
+
+        async fn embed(
+            &self,
+            texts: &[&str],
+        ) -> Result<Vec<Vec<f32>>, mkb_core::error::EmbeddingError> {
+            Ok(texts.iter().map(|t| Self::vector(t)).collect())
+        }
+    }
+
+    #[test]
+    fn execute_near_with_provider_combines_with_other_predicates() {
+        let index = IndexManager::in_memory().unwrap();
+        // Custom fields such as `status` are not indexed, so confidence
+        // stands in for the non-semantic predicate
+        for (id, body, confidence) in [
+            ("proj-rust-001", "Rust systems programming", 0.9),
+            ("proj-rustold-001", "Legacy Rust service", 0.3),
+            ("proj-python-001", "Python data pipeline", 0.9),
+        ] {
+            let mut doc = make_doc(id, "project", id, body);
+            doc.confidence = confidence;
+            index.index_document(&doc).unwrap();
+            index
+                .store_embedding(id, &KeywordProvider::vector(body), "keyword")
+                .unwrap();
+        }
+
+        let compiled = compile(
+            &mkb_parser::parse_mkql(
+                "SELECT id FROM project WHERE NEAR('rust', 0.9) AND confidence > 0.5",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let result = execute_with_provider(&index, &compiled, &KeywordProvider).unwrap();
+        let ids: Vec<&str> = result
+            .rows
+            .iter()
+            .map(|r| r.fields["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["proj-rust-001"]);
+
+        // The mock embedding of 'rust' is near neither stored vector
+        assert_eq!(execute(&index, &compiled).unwrap().total, 0);
     }
 
     #[test]
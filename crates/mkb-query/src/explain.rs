@@ -0,0 +1,232 @@
+//! Query plan explanation for compiled MKQL queries.
+//!
+//! Pure structural analysis of a [`CompiledQuery`]: no index access,
+//! no execution. Useful for debugging slow or surprising queries.
+
+use serde::Serialize;
+
+use crate::compiler::{CompiledQuery, SqlParam};
+
+/// A bound SQL parameter as shown in an explain plan.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExplainParam {
+    /// 1-based placeholder index (`?N`).
+    pub index: usize,
+    /// SQL type of the bound value (`text`, `integer`, `real`, `null`).
+    pub sql_type: String,
+    /// Display form of the bound value.
+    pub value: String,
+}
+
+/// A table access the query will perform.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TableScan {
+    /// Table name (e.g., `documents`, `links`).
+    pub table: String,
+    /// How the table is accessed.
+    pub access: String,
+}
+
+/// Structural explanation of a compiled query.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExplainOutput {
+    /// Final SQL string with `?N` placeholders.
+    pub sql: String,
+    /// Bound parameters in placeholder order.
+    pub params: Vec<ExplainParam>,
+    /// Whether the FTS5 full-text phase is active.
+    pub uses_fts: bool,
+    /// Whether the links table is consulted.
+    pub uses_links: bool,
+    /// Whether the semantic (vector KNN) phase is active.
+    pub uses_semantic: bool,
+    /// Estimated table scans, derived from the SQL structure.
+    pub table_scans: Vec<TableScan>,
+    /// Human-readable description of each WHERE clause component.
+    pub where_clause: Vec<String>,
+}
+
+impl ExplainOutput {
+    /// Render the plan as human-readable text.
+    #[must_use]
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        out.push_str("SQL:\n");
+        out.push_str(&format!("  {}\n", self.sql));
+
+        out.push_str("\nParameters:\n");
+        if self.params.is_empty() {
+            out.push_str("  (none)\n");
+        }
+        for p in &self.params {
+            out.push_str(&format!("  ?{} {} = {}\n", p.index, p.sql_type, p.value));
+        }
+
+        out.push_str("\nPhases:\n");
+        out.push_str(&format!("  fts:      {}\n", on_off(self.uses_fts)));
+        out.push_str(&format!("  links:    {}\n", on_off(self.uses_links)));
+        out.push_str(&format!("  semantic: {}\n", on_off(self.uses_semantic)));
+
+        out.push_str("\nTable scans:\n");
+        for scan in &self.table_scans {
+            out.push_str(&format!("  {} — {}\n", scan.table, scan.access));
+        }
+
+        out.push_str("\nWHERE:\n");
+        for desc in &self.where_clause {
+            out.push_str(&format!("  {desc}\n"));
+        }
+
+        out
+    }
+}
+
+fn on_off(active: bool) -> &'static str {
+    if active {
+        "active"
+    } else {
+        "inactive"
+    }
+}
+
+/// Explain a compiled query without executing it.
+#[must_use]
+pub fn explain(compiled: &CompiledQuery) -> ExplainOutput {
+    let params = compiled
+        .params
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let (sql_type, value) = match p {
+                SqlParam::Text(s) => ("text", format!("'{s}'")),
+                SqlParam::Integer(n) => ("integer", n.to_string()),
+                SqlParam::Float(f) => ("real", f.to_string()),
+                SqlParam::Null => ("null", "NULL".to_string()),
+                SqlParam::Named { name, .. } => ("param", format!(":{name}")),
+            };
+            ExplainParam {
+                index: i + 1,
+                sql_type: sql_type.to_string(),
+                value,
+            }
+        })
+        .collect();
+
+    let table_scans = estimate_table_scans(compiled);
+    let uses_links = compiled.uses_links || table_scans.iter().any(|s| s.table == "links");
+
+    ExplainOutput {
+        sql: compiled.sql.clone(),
+        params,
+        uses_fts: compiled.uses_fts,
+        uses_links,
+        uses_semantic: compiled.uses_semantic,
+        table_scans,
+        where_clause: compiled.where_descriptions.clone(),
+    }
+}
+
+/// Derive table accesses by walking `FROM`/`JOIN` targets in the SQL.
+fn estimate_table_scans(compiled: &CompiledQuery) -> Vec<TableScan> {
+    let mut scans = Vec::new();
+
+    if compiled.uses_semantic {
+        scans.push(TableScan {
+            table: "vec_documents".to_string(),
+            access: "KNN vector search (runs before the SQL phase)".to_string(),
+        });
+    }
+
+    let tokens: Vec<&str> = compiled.sql.split_whitespace().collect();
+    for pair in tokens.windows(2) {
+        let (kw, table) = (pair[0], pair[1].trim_matches(|c| c == '(' || c == ')'));
+        if kw != "FROM" && kw != "JOIN" {
+            continue;
+        }
+        let access = match table {
+            "documents" => "indexed lookup on doc_type (idx_documents_type)",
+            "documents_fts" => "FTS5 MATCH joined on rowid",
+            "links" => "subquery filtered by rel (idx_links_rel)",
+            _ => "full scan",
+        };
+        scans.push(TableScan {
+            table: table.to_string(),
+            access: access.to_string(),
+        });
+    }
+
+    scans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::compile;
+    use mkb_parser::parse_mkql;
+
+    #[test]
+    fn explain_simple_query() {
+        let compiled = compile(&parse_mkql("SELECT * FROM project").unwrap()).unwrap();
+        let plan = explain(&compiled);
+
+        assert_eq!(plan.sql, compiled.sql);
+        assert_eq!(plan.params.len(), 1);
+        assert_eq!(plan.params[0].sql_type, "text");
+        assert_eq!(plan.params[0].value, "'project'");
+        assert!(!plan.uses_fts && !plan.uses_links && !plan.uses_semantic);
+        assert_eq!(plan.table_scans.len(), 1);
+        assert_eq!(plan.table_scans[0].table, "documents");
+        assert_eq!(
+            plan.where_clause,
+            vec!["Document type filter: doc_type = ?1 ('project')".to_string()]
+        );
+    }
+
+    #[test]
+    fn explain_reports_active_phases_and_scans() {
+        let compiled = compile(
+            &parse_mkql(
+                "SELECT * FROM project WHERE BODY CONTAINS 'rust' AND LINKED('owner') AND NEAR('ml', 0.5)",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let plan = explain(&compiled);
+
+        assert!(plan.uses_fts);
+        assert!(plan.uses_links);
+        assert!(plan.uses_semantic);
+        let tables: Vec<&str> = plan.table_scans.iter().map(|s| s.table.as_str()).collect();
+        assert!(tables.contains(&"documents_fts"));
+        assert!(tables.contains(&"links"));
+        assert!(tables.contains(&"vec_documents"));
+        assert_eq!(plan.where_clause.len(), 4);
+    }
+
+    #[test]
+    fn explain_text_lists_params_with_types() {
+        let compiled =
+            compile(&parse_mkql("SELECT * FROM project WHERE confidence > 0.5").unwrap()).unwrap();
+        let text = explain(&compiled).to_text();
+
+        assert!(text.contains("?2 real = 0.5"));
+        assert!(text.contains("Field comparison: confidence > 0.5"));
+    }
+
+    #[test]
+    fn explain_text_numbers_type_filters_per_set_operand() {
+        let compiled = compile(
+            &parse_mkql(
+                "WITH a AS (SELECT * FROM project WHERE confidence > 0.5) \
+                 SELECT * FROM a UNION SELECT * FROM meeting",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let text = explain(&compiled).to_text();
+
+        assert!(text.contains("Document type filter: doc_type = ?1 ('project')"));
+        assert!(text.contains("Document type filter: doc_type = ?3 ('meeting')"));
+        assert!(!text.contains("('a')"));
+    }
+}
@@ -0,0 +1,268 @@
+//! Atom 1.0 feeds of query results.
+//!
+//! Each [`ResultRow`] becomes an `<entry>`, read from the row's `id`,
+//! `title`, `modified_at`, `body` and `doc_type` columns. Queries should
+//! select those columns (`SELECT *` does); missing ones fall back to
+//! something sensible rather than producing an invalid feed.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::formatter::{QueryResult, ResultRow};
+
+/// Characters of body text kept in each entry's `<summary>`.
+pub const FEED_SUMMARY_CHARS: usize = 200;
+
+/// Options for [`OutputFormat::Atom`](crate::OutputFormat::Atom).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedOpts {
+    /// Base URL of document links; an entry's ID is `{base_url}/{id}`.
+    /// Without one, entries are identified as `urn:mkb:{id}` and get no link.
+    pub base_url: Option<String>,
+    /// Feed `<title>`.
+    pub title: String,
+}
+
+impl Default for FeedOpts {
+    fn default() -> Self {
+        Self {
+            base_url: None,
+            title: "MKB query results".to_string(),
+        }
+    }
+}
+
+impl FeedOpts {
+    fn document_url(&self, id: &str) -> Option<String> {
+        self.base_url
+            .as_deref()
+            .map(|base| format!("{}/{id}", base.trim_end_matches('/')))
+    }
+}
+
+/// Render `result` as an Atom 1.0 feed.
+///
+/// The feed's `<updated>` is the newest entry's, or the current time for an
+/// empty feed.
+#[must_use]
+pub fn format_atom(result: &QueryResult, opts: &FeedOpts) -> String {
+    let now = Utc::now();
+    let entries: Vec<Entry> = result
+        .rows
+        .iter()
+        .enumerate()
+        .map(|(n, row)| Entry::from_row(row, n, now))
+        .collect();
+    let updated = entries.iter().map(|e| e.updated).max().unwrap_or(now);
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    let feed_id = opts
+        .base_url
+        .clone()
+        .unwrap_or_else(|| "urn:mkb:feed".to_string());
+    push_element(&mut xml, 1, "id", &feed_id);
+    push_element(&mut xml, 1, "title", &opts.title);
+    push_element(&mut xml, 1, "updated", &atom_date(updated));
+    xml.push_str("  <author><name>mkb</name></author>\n");
+    if let Some(ref base) = opts.base_url {
+        xml.push_str(&format!("  <link href=\"{}\"/>\n", escape(base)));
+    }
+    for entry in &entries {
+        entry.write(&mut xml, opts);
+    }
+    xml.push_str("</feed>\n");
+    xml
+}
+
+struct Entry {
+    id: String,
+    title: String,
+    updated: DateTime<Utc>,
+    summary: Option<String>,
+    category: Option<String>,
+}
+
+impl Entry {
+    /// `n` names rows that have no `id` column.
+    fn from_row(row: &ResultRow, n: usize, now: DateTime<Utc>) -> Self {
+        let text = |name: &str| row.fields.get(name).and_then(|v| v.as_str());
+        let id = text("id").map_or_else(|| format!("row-{}", n + 1), str::to_string);
+        let updated = ["modified_at", "observed_at"]
+            .iter()
+            .filter_map(|name| text(name))
+            .find_map(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map_or(now, |dt| dt.with_timezone(&Utc));
+        Self {
+            title: text("title").unwrap_or(&id).to_string(),
+            summary: text("body")
+                .map(|body| body.trim().chars().take(FEED_SUMMARY_CHARS).collect())
+                .filter(|summary: &String| !summary.is_empty()),
+            category: text("doc_type").map(str::to_string),
+            id,
+            updated,
+        }
+    }
+
+    fn write(&self, xml: &mut String, opts: &FeedOpts) {
+        let url = opts.document_url(&self.id);
+        xml.push_str("  <entry>\n");
+        let entry_id = url
+            .clone()
+            .unwrap_or_else(|| format!("urn:mkb:{}", self.id));
+        push_element(xml, 2, "id", &entry_id);
+        push_element(xml, 2, "title", &self.title);
+        push_element(xml, 2, "updated", &atom_date(self.updated));
+        if let Some(url) = url {
+            xml.push_str(&format!("    <link href=\"{}\"/>\n", escape(&url)));
+        }
+        if let Some(ref summary) = self.summary {
+            push_element(xml, 2, "summary", summary);
+        }
+        if let Some(ref category) = self.category {
+            xml.push_str(&format!("    <category term=\"{}\"/>\n", escape(category)));
+        }
+        xml.push_str("  </entry>\n");
+    }
+}
+
+fn push_element(xml: &mut String, depth: usize, name: &str, text: &str) {
+    xml.push_str(&format!(
+        "{}<{name}>{}</{name}>\n",
+        "  ".repeat(depth),
+        escape(text)
+    ));
+}
+
+fn atom_date(dt: DateTime<Utc>) -> String {
+    dt.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Escape `text` for XML content and attribute values, dropping characters
+/// XML 1.0 cannot represent.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            c if c < ' ' || matches!(c, '\u{fffe}' | '\u{ffff}') => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quick_xml::events::Event;
+    use std::collections::BTreeMap;
+
+    fn row(fields: &[(&str, &str)]) -> ResultRow {
+        ResultRow {
+            fields: fields
+                .iter()
+                .map(|(k, v)| ((*k).to_string(), serde_json::json!(v)))
+                .collect::<BTreeMap<_, _>>(),
+        }
+    }
+
+    fn result(rows: Vec<ResultRow>) -> QueryResult {
+        let total = rows.len();
+        QueryResult { rows, total }
+    }
+
+    /// Parse `xml` event by event, returning the text of every element
+    /// called `name` and the number of `<entry>` elements.
+    fn parse(xml: &str, name: &str) -> (Vec<String>, usize) {
+        let mut reader = quick_xml::Reader::from_str(xml);
+        let mut texts = Vec::new();
+        let mut entries = 0;
+        let mut inside = false;
+        loop {
+            match reader.read_event().expect("well-formed XML") {
+                Event::Start(e) => {
+                    entries += usize::from(e.name().as_ref() == b"entry");
+                    inside = e.name().as_ref() == name.as_bytes();
+                }
+                Event::Text(t) if inside => texts.push(t.unescape().unwrap().into_owned()),
+                Event::End(_) => inside = false,
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        (texts, entries)
+    }
+
+    #[test]
+    fn atom_feed_has_one_entry_per_row() {
+        let rows = vec![
+            row(&[
+                ("id", "proj-alpha-001"),
+                ("title", "Alpha & <Beta>"),
+                ("doc_type", "project"),
+                ("modified_at", "2025-03-01T10:00:00+00:00"),
+                ("body", "Alpha body"),
+            ]),
+            row(&[
+                ("id", "meet-sync-001"),
+                ("title", "Sync"),
+                ("doc_type", "meeting"),
+                ("modified_at", "2025-04-01T10:00:00+00:00"),
+            ]),
+        ];
+        let opts = FeedOpts {
+            base_url: Some("https://kb.example.com/docs/".to_string()),
+            ..FeedOpts::default()
+        };
+        let xml = format_atom(&result(rows), &opts);
+
+        let (ids, entries) = parse(&xml, "id");
+        assert_eq!(entries, 2);
+        assert_eq!(
+            ids,
+            vec![
+                "https://kb.example.com/docs/",
+                "https://kb.example.com/docs/proj-alpha-001",
+                "https://kb.example.com/docs/meet-sync-001",
+            ]
+        );
+        let (titles, _) = parse(&xml, "title");
+        assert_eq!(titles[1], "Alpha & <Beta>");
+        let (updated, _) = parse(&xml, "updated");
+        assert_eq!(
+            updated,
+            vec![
+                "2025-04-01T10:00:00Z",
+                "2025-03-01T10:00:00Z",
+                "2025-04-01T10:00:00Z"
+            ]
+        );
+        assert!(xml.contains("<category term=\"project\"/>"));
+    }
+
+    #[test]
+    fn atom_summary_truncates_body() {
+        let body = "é".repeat(FEED_SUMMARY_CHARS + 50);
+        let xml = format_atom(
+            &result(vec![row(&[("id", "a"), ("body", &body)])]),
+            &FeedOpts::default(),
+        );
+        let (summaries, _) = parse(&xml, "summary");
+        assert_eq!(summaries[0].chars().count(), FEED_SUMMARY_CHARS);
+        let (ids, _) = parse(&xml, "id");
+        assert_eq!(ids, vec!["urn:mkb:feed", "urn:mkb:a"]);
+    }
+
+    #[test]
+    fn empty_atom_feed_is_well_formed() {
+        let xml = format_atom(&result(Vec::new()), &FeedOpts::default());
+        let (titles, entries) = parse(&xml, "title");
+        assert_eq!(entries, 0);
+        assert_eq!(titles, vec!["MKB query results"]);
+    }
+}
@@ -1,20 +1,51 @@
-//! Result formatting: JSON, Table, and Markdown output.
+//! Result formatting: JSON, YAML, Table, Markdown, templated context,
+//! Atom feed, pivot table and CSV output.
 
+use mkb_core::error::MkbError;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+
+use crate::delimited::{format_csv, CsvOpts};
+use crate::feed::{format_atom, FeedOpts};
+
+/// Template used by [`OutputFormat::Context`] when none is given.
+pub const DEFAULT_CONTEXT_TEMPLATE: &str =
+    "## {{title}} ({{doc_type}}, observed {{observed_at}})\n\n{{body}}\n\n---\n";
+
+/// Column holding each row's key in the result of a PIVOT query; every
+/// other column is a column key.
+pub const PIVOT_ROW_COLUMN: &str = "row";
 
 /// Output format for query results.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum OutputFormat {
     Json,
+    /// One YAML document per row, separated by `---`.
+    Yaml,
     Table,
     Markdown,
+    /// Each row rendered through a template for LLM prompts. `{{field}}`
+    /// placeholders are replaced with the row's value for that column, or
+    /// nothing if the row lacks it. `None` uses [`DEFAULT_CONTEXT_TEMPLATE`].
+    Context {
+        template: Option<String>,
+    },
+    /// An Atom 1.0 feed with one entry per row; see [`format_atom`].
+    Atom(FeedOpts),
+    /// A PIVOT result as a cross table: row keys down the left, one column
+    /// per column key, both sorted.
+    PivotTable,
+    /// Delimiter-separated values with a header line; see [`format_csv`].
+    Csv(CsvOpts),
 }
 
 /// A single row in a query result.
+///
+/// Fields are kept sorted by name so serialized output is byte-stable.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResultRow {
-    pub fields: HashMap<String, serde_json::Value>,
+    pub fields: BTreeMap<String, serde_json::Value>,
 }
 
 /// A complete query result set.
@@ -29,15 +60,125 @@ pub struct QueryResult {
 pub fn format_results(result: &QueryResult, format: OutputFormat) -> String {
     match format {
         OutputFormat::Json => format_json(result),
+        OutputFormat::Yaml => format_yaml(result),
         OutputFormat::Table => format_table(result),
         OutputFormat::Markdown => format_markdown(result),
+        OutputFormat::Context { template } => result
+            .rows
+            .iter()
+            .map(|row| render_template(context_template(template.as_deref()), row))
+            .collect(),
+        OutputFormat::Atom(opts) => format_atom(result, &opts),
+        OutputFormat::PivotTable => format_pivot_table(result),
+        OutputFormat::Csv(opts) => format_csv(result, &opts),
+    }
+}
+
+fn context_template(template: Option<&str>) -> &str {
+    template.unwrap_or(DEFAULT_CONTEXT_TEMPLATE)
+}
+
+/// Substitute `{{name}}` placeholders in `template` with `row`'s values.
+/// Whitespace inside the braces is ignored; an unclosed `{{` is kept as is.
+fn render_template(template: &str, row: &ResultRow) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        output.push_str(&rest[..start]);
+        let name = rest[start + 2..start + 2 + len].trim();
+        match row.fields.get(name) {
+            None | Some(serde_json::Value::Null) => {}
+            Some(value) => output.push_str(&value_to_display(value)),
+        }
+        rest = &rest[start + 2 + len + 2..];
     }
+    output.push_str(rest);
+    output
 }
 
 fn format_json(result: &QueryResult) -> String {
     serde_json::to_string_pretty(result).unwrap_or_else(|_| "[]".to_string())
 }
 
+fn format_yaml(result: &QueryResult) -> String {
+    if result.rows.is_empty() {
+        return YAML_NO_RESULTS.to_string();
+    }
+    result
+        .rows
+        .iter()
+        .map(yaml_document)
+        .collect::<Vec<_>>()
+        .join("---\n")
+}
+
+/// A YAML stream with no documents, readable at a terminal.
+const YAML_NO_RESULTS: &str = "# no results\n";
+
+/// One row as a YAML mapping, with `body` last as a `|` block literal.
+fn yaml_document(row: &ResultRow) -> String {
+    let mut fields = row.fields.clone();
+    let body = fields.remove("body");
+    let mut output = if fields.is_empty() && body.is_some() {
+        String::new()
+    } else {
+        serde_yaml::to_string(&fields).unwrap_or_else(|_| "{}\n".to_string())
+    };
+    match body {
+        Some(serde_json::Value::String(text)) if is_literal_safe(&text) => {
+            output.push_str(&yaml_block_literal("body", &text));
+        }
+        Some(value) => {
+            let single = BTreeMap::from([("body", value)]);
+            output.push_str(&serde_yaml::to_string(&single).unwrap_or_default());
+        }
+        None => {}
+    }
+    output
+}
+
+/// Whether `text` survives a block literal unchanged. Literals can't hold
+/// control characters, and `\r` or Unicode line separators would be
+/// normalized to `\n` on read.
+fn is_literal_safe(text: &str) -> bool {
+    !text.is_empty()
+        && !text.chars().any(|c| {
+            (c.is_control() && c != '\n' && c != '\t')
+                || matches!(c, '\u{2028}' | '\u{2029}' | '\u{feff}')
+        })
+}
+
+fn yaml_block_literal(key: &str, text: &str) -> String {
+    let content = text.trim_end_matches('\n');
+    let chomp = match text.len() - content.len() {
+        0 => "-",
+        1 => "",
+        _ => "+",
+    };
+    // Indentation is inferred from the first non-empty line unless given
+    let indent = if text.starts_with([' ', '\n']) {
+        "2"
+    } else {
+        ""
+    };
+
+    let mut output = format!("{key}: |{indent}{chomp}\n");
+    for line in content.split('\n') {
+        if !line.is_empty() {
+            output.push_str("  ");
+            output.push_str(line);
+        }
+        output.push('\n');
+    }
+    for _ in 1..text.len() - content.len() {
+        output.push('\n');
+    }
+    output
+}
+
 fn format_table(result: &QueryResult) -> String {
     if result.rows.is_empty() {
         return "(no results)".to_string();
@@ -77,25 +218,97 @@ fn format_table(result: &QueryResult) -> String {
 
     // Rows
     for row in &result.rows {
-        let vals: Vec<String> = columns
-            .iter()
-            .enumerate()
-            .map(|(i, col)| {
+        output.push_str(&table_line(row, &columns, &widths));
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Cross table of a PIVOT result. Row keys are left-aligned under a blank
+/// corner, cells right-aligned; a cell missing from a row is left blank.
+fn format_pivot_table(result: &QueryResult) -> String {
+    if result.rows.is_empty() {
+        return "(no results)".to_string();
+    }
+
+    let columns: BTreeSet<&String> = result
+        .rows
+        .iter()
+        .flat_map(|row| row.fields.keys())
+        .filter(|name| *name != PIVOT_ROW_COLUMN)
+        .collect();
+    let mut rows: Vec<(String, &ResultRow)> = result
+        .rows
+        .iter()
+        .map(|row| {
+            let key = row
+                .fields
+                .get(PIVOT_ROW_COLUMN)
+                .map(value_to_display)
+                .unwrap_or_default();
+            (key, row)
+        })
+        .collect();
+    rows.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let key_width = rows.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+    let widths: Vec<usize> = columns
+        .iter()
+        .map(|col| {
+            rows.iter()
+                .filter_map(|(_, row)| row.fields.get(*col).map(value_display_len))
+                .fold(col.len(), usize::max)
+        })
+        .collect();
+
+    let mut output = String::new();
+    let header: Vec<String> = std::iter::once(" ".repeat(key_width))
+        .chain(columns.iter().zip(&widths).map(|(c, w)| format!("{c:>w$}")))
+        .collect();
+    output.push_str(&header.join(" | "));
+    output.push('\n');
+    let sep: Vec<String> = std::iter::once(key_width)
+        .chain(widths.iter().copied())
+        .map(|w| "-".repeat(w))
+        .collect();
+    output.push_str(&sep.join("-+-"));
+    output.push('\n');
+
+    for (key, row) in &rows {
+        let cells: Vec<String> = std::iter::once(format!("{key:key_width$}"))
+            .chain(columns.iter().zip(&widths).map(|(col, w)| {
                 let val = row
                     .fields
-                    .get(col)
+                    .get(*col)
                     .map(value_to_display)
-                    .unwrap_or_else(|| "null".to_string());
-                format!("{:width$}", val, width = widths[i])
-            })
+                    .unwrap_or_default();
+                format!("{val:>w$}")
+            }))
             .collect();
-        output.push_str(&vals.join(" | "));
+        output.push_str(&cells.join(" | "));
         output.push('\n');
     }
 
     output
 }
 
+fn table_line(row: &ResultRow, columns: &[String], widths: &[usize]) -> String {
+    let vals: Vec<String> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, col)| {
+            let val = row
+                .fields
+                .get(col)
+                .map(value_to_display)
+                .unwrap_or_else(|| "null".to_string());
+            format!("{:width$}", val, width = widths[i])
+        })
+        .collect();
+    vals.join(" | ")
+}
+
 fn format_markdown(result: &QueryResult) -> String {
     if result.rows.is_empty() {
         return "*No results*\n".to_string();
@@ -122,27 +335,149 @@ fn format_markdown(result: &QueryResult) -> String {
 
     // Rows
     for row in &result.rows {
-        output.push_str("| ");
-        let vals: Vec<String> = columns
-            .iter()
-            .map(|col| {
-                row.fields
-                    .get(col)
-                    .map(value_to_display)
-                    .unwrap_or_else(|| "null".to_string())
-            })
-            .collect();
-        output.push_str(&vals.join(" | "));
-        output.push_str(" |\n");
+        output.push_str(&markdown_line(row, &columns));
+        output.push('\n');
     }
 
     output
 }
 
+fn markdown_line(row: &ResultRow, columns: &[String]) -> String {
+    let vals: Vec<String> = columns
+        .iter()
+        .map(|col| {
+            row.fields
+                .get(col)
+                .map(value_to_display)
+                .unwrap_or_else(|| "null".to_string())
+        })
+        .collect();
+    format!("| {} |", vals.join(" | "))
+}
+
+/// Write results to `out` as they are read, without collecting them.
+///
+/// `head` is a prefix already taken from the stream. Table column widths
+/// are sized from it, so a wider value later on overflows its column.
+/// JSON keeps the `{"rows": [...], "total": N}` shape, with `total`
+/// written last. Returns the number of rows written.
+///
+/// # Errors
+///
+/// Returns the first error yielded by `rest`, or [`MkbError::Io`] if
+/// writing fails.
+pub fn write_results_streaming<W, I>(
+    out: &mut W,
+    head: Vec<ResultRow>,
+    rest: I,
+    format: OutputFormat,
+) -> Result<usize, MkbError>
+where
+    W: Write,
+    I: Iterator<Item = Result<ResultRow, MkbError>>,
+{
+    let columns: Vec<String> = head
+        .first()
+        .map(|row| row.fields.keys().cloned().collect())
+        .unwrap_or_default();
+    let widths: Vec<usize> = columns
+        .iter()
+        .map(|col| {
+            head.iter()
+                .filter_map(|row| row.fields.get(col).map(value_display_len))
+                .fold(col.len(), usize::max)
+        })
+        .collect();
+    let rows = head.into_iter().map(Ok).chain(rest);
+    let mut total = 0;
+
+    match format {
+        OutputFormat::Json => {
+            write!(out, "{{\n  \"rows\": [")?;
+            for row in rows {
+                let json = serde_json::to_string(&row?)
+                    .map_err(|e| MkbError::Serialization(e.to_string()))?;
+                let sep = if total == 0 { "" } else { "," };
+                write!(out, "{sep}\n    {json}")?;
+                total += 1;
+            }
+            let close = if total == 0 { "" } else { "\n  " };
+            writeln!(out, "{close}],\n  \"total\": {total}\n}}")?;
+        }
+        OutputFormat::Yaml => {
+            for row in rows {
+                let sep = if total == 0 { "" } else { "---\n" };
+                write!(out, "{sep}{}", yaml_document(&row?))?;
+                total += 1;
+            }
+            if total == 0 {
+                write!(out, "{YAML_NO_RESULTS}")?;
+            }
+        }
+        OutputFormat::Table if columns.is_empty() => writeln!(out, "(no results)")?,
+        OutputFormat::Table => {
+            let header: Vec<String> = columns
+                .iter()
+                .zip(&widths)
+                .map(|(c, w)| format!("{c:w$}"))
+                .collect();
+            writeln!(out, "{}", header.join(" | "))?;
+            let sep: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+            writeln!(out, "{}", sep.join("-+-"))?;
+            for row in rows {
+                writeln!(out, "{}", table_line(&row?, &columns, &widths))?;
+                total += 1;
+            }
+        }
+        OutputFormat::Markdown if columns.is_empty() => writeln!(out, "*No results*")?,
+        OutputFormat::Markdown => {
+            writeln!(out, "| {} |", columns.join(" | "))?;
+            let seps: Vec<&str> = columns.iter().map(|_| "---").collect();
+            writeln!(out, "| {} |", seps.join(" | "))?;
+            for row in rows {
+                writeln!(out, "{}", markdown_line(&row?, &columns))?;
+                total += 1;
+            }
+        }
+        OutputFormat::Context { template } => {
+            let template = context_template(template.as_deref());
+            for row in rows {
+                write!(out, "{}", render_template(template, &row?))?;
+                total += 1;
+            }
+        }
+        // The feed's <updated> depends on every entry, so collect first
+        OutputFormat::Atom(opts) => {
+            let rows = rows.collect::<Result<Vec<_>, _>>()?;
+            total = rows.len();
+            write!(out, "{}", format_atom(&QueryResult { rows, total }, &opts))?;
+        }
+        OutputFormat::PivotTable if columns.is_empty() => writeln!(out, "(no results)")?,
+        // Columns are only known once every row is in
+        OutputFormat::PivotTable => {
+            let rows = rows.collect::<Result<Vec<_>, _>>()?;
+            total = rows.len();
+            write!(out, "{}", format_pivot_table(&QueryResult { rows, total }))?;
+        }
+        // The header is the union of every row's columns
+        OutputFormat::Csv(opts) => {
+            let rows = rows.collect::<Result<Vec<_>, _>>()?;
+            total = rows.len();
+            write!(out, "{}", format_csv(&QueryResult { rows, total }, &opts))?;
+        }
+    }
+    Ok(total)
+}
+
 fn value_to_display(v: &serde_json::Value) -> String {
     match v {
         serde_json::Value::String(s) => s.clone(),
         serde_json::Value::Null => "null".to_string(),
+        serde_json::Value::Array(items) => items
+            .iter()
+            .map(value_to_display)
+            .collect::<Vec<_>>()
+            .join(", "),
         other => other.to_string(),
     }
 }
@@ -156,12 +491,12 @@ mod tests {
     use super::*;
 
     fn sample_result() -> QueryResult {
-        let mut row1 = HashMap::new();
+        let mut row1 = BTreeMap::new();
         row1.insert("id".to_string(), serde_json::json!("proj-alpha-001"));
         row1.insert("title".to_string(), serde_json::json!("Alpha Project"));
         row1.insert("status".to_string(), serde_json::json!("active"));
 
-        let mut row2 = HashMap::new();
+        let mut row2 = BTreeMap::new();
         row2.insert("id".to_string(), serde_json::json!("proj-beta-001"));
         row2.insert("title".to_string(), serde_json::json!("Beta Project"));
         row2.insert("status".to_string(), serde_json::json!("paused"));
@@ -184,6 +519,135 @@ mod tests {
         assert_eq!(parsed["total"], 2);
     }
 
+    #[test]
+    fn format_as_pivot_table_sorts_rows_and_columns() {
+        let row = |key: &str, cells: &[(&str, i64)]| {
+            let mut fields: BTreeMap<String, serde_json::Value> = cells
+                .iter()
+                .map(|(col, n)| (col.to_string(), serde_json::json!(n)))
+                .collect();
+            fields.insert(PIVOT_ROW_COLUMN.to_string(), serde_json::json!(key));
+            ResultRow { fields }
+        };
+        let result = QueryResult {
+            rows: vec![
+                row("project", &[("2025-03", 1), ("2025-02", 12)]),
+                row("meeting", &[("2025-02", 1)]),
+            ],
+            total: 2,
+        };
+        assert_eq!(
+            format_results(&result, OutputFormat::PivotTable),
+            "        | 2025-02 | 2025-03\n\
+             --------+---------+--------\n\
+             meeting |       1 |        \n\
+             project |      12 |       1\n"
+        );
+
+        let mut out = Vec::new();
+        write_results_streaming(
+            &mut out,
+            Vec::new(),
+            std::iter::empty(),
+            OutputFormat::PivotTable,
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "(no results)\n");
+    }
+
+    #[test]
+    fn streaming_output_matches_buffered_shape() {
+        let result = sample_result();
+        let (head, rest) = result.rows.split_at(1);
+        let write = |format| {
+            let mut out = Vec::new();
+            let rest = rest.iter().cloned().map(Ok);
+            let total = write_results_streaming(&mut out, head.to_vec(), rest, format).unwrap();
+            assert_eq!(total, 2);
+            String::from_utf8(out).unwrap()
+        };
+
+        let json: serde_json::Value = serde_json::from_str(&write(OutputFormat::Json)).unwrap();
+        let buffered: serde_json::Value =
+            serde_json::from_str(&format_results(&result, OutputFormat::Json)).unwrap();
+        assert_eq!(json, buffered);
+
+        assert_eq!(
+            write(OutputFormat::Markdown),
+            format_results(&result, OutputFormat::Markdown)
+        );
+        assert_eq!(
+            write(OutputFormat::Table),
+            format_results(&result, OutputFormat::Table)
+        );
+        assert_eq!(
+            write(OutputFormat::Yaml),
+            format_results(&result, OutputFormat::Yaml)
+        );
+        let context = OutputFormat::Context { template: None };
+        assert_eq!(write(context.clone()), format_results(&result, context));
+
+        let mut out = Vec::new();
+        write_results_streaming(&mut out, Vec::new(), std::iter::empty(), OutputFormat::Json)
+            .unwrap();
+        let empty: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(empty, serde_json::json!({"rows": [], "total": 0}));
+    }
+
+    fn yaml_documents(yaml: &str) -> Vec<serde_json::Value> {
+        use serde::Deserialize as _;
+        serde_yaml::Deserializer::from_str(yaml)
+            .map(|doc| serde_json::Value::deserialize(doc).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn format_as_yaml_round_trips() {
+        let mut result = sample_result();
+        let bodies = [
+            "## Notes\n\n- one\n- two\n",
+            "single line",
+            "  indented first line\nsecond",
+            "\n\nleading blank lines",
+            "trailing blanks\n\n\n",
+            "carriage\r\nreturn",
+            "",
+        ];
+        result.rows = bodies
+            .iter()
+            .enumerate()
+            .map(|(i, body)| {
+                let mut fields = result.rows[0].fields.clone();
+                fields.insert("id".to_string(), serde_json::json!(format!("proj-{i}")));
+                fields.insert("body".to_string(), serde_json::json!(body));
+                fields.insert("tags".to_string(), serde_json::json!(["a", "b"]));
+                fields.insert("confidence".to_string(), serde_json::json!(0.9));
+                ResultRow { fields }
+            })
+            .collect();
+
+        let output = format_results(&result, OutputFormat::Yaml);
+        assert!(output.contains("body: |\n  ## Notes\n"), "{output}");
+        assert!(output.contains("body: |-\n  single line\n"), "{output}");
+        assert_eq!(output.matches("---\n").count(), bodies.len() - 1);
+
+        let original: Vec<serde_json::Value> = result
+            .rows
+            .iter()
+            .map(|row| serde_json::to_value(&row.fields).unwrap())
+            .collect();
+        assert_eq!(yaml_documents(&output), original);
+
+        let single = QueryResult {
+            rows: result.rows[..1].to_vec(),
+            total: 1,
+        };
+        let output = format_results(&single, OutputFormat::Yaml);
+        assert!(!output.contains("---"));
+        let parsed: serde_json::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(parsed, original[0]);
+    }
+
     #[test]
     fn format_as_table() {
         let result = sample_result();
@@ -205,6 +669,40 @@ mod tests {
         assert!(output.contains("|\n"));
     }
 
+    #[test]
+    fn format_as_context_with_default_template() {
+        let mut fields = BTreeMap::new();
+        fields.insert("title".to_string(), serde_json::json!("Alpha Project"));
+        fields.insert("doc_type".to_string(), serde_json::json!("project"));
+        fields.insert("observed_at".to_string(), serde_json::json!("2025-02-10"));
+        fields.insert("body".to_string(), serde_json::json!("Migrating the API."));
+        let result = QueryResult {
+            rows: vec![ResultRow { fields }],
+            total: 1,
+        };
+
+        assert_eq!(
+            format_results(&result, OutputFormat::Context { template: None }),
+            "## Alpha Project (project, observed 2025-02-10)\n\nMigrating the API.\n\n---\n"
+        );
+    }
+
+    #[test]
+    fn format_as_context_with_custom_template() {
+        let template = "- {{ id }}: {{title}} [{{confidence}}]{{missing}} {{unclosed\n";
+        let output = format_results(
+            &sample_result(),
+            OutputFormat::Context {
+                template: Some(template.to_string()),
+            },
+        );
+        assert_eq!(
+            output,
+            "- proj-alpha-001: Alpha Project [] {{unclosed\n\
+             - proj-beta-001: Beta Project [] {{unclosed\n"
+        );
+    }
+
     #[test]
     fn format_empty_result() {
         let result = QueryResult {
@@ -216,5 +714,13 @@ mod tests {
             format_results(&result, OutputFormat::Markdown),
             "*No results*\n"
         );
+        assert_eq!(
+            format_results(&result, OutputFormat::Yaml),
+            "# no results\n"
+        );
+        assert_eq!(
+            format_results(&result, OutputFormat::Context { template: None }),
+            ""
+        );
     }
 }
@@ -2,11 +2,16 @@
 //!
 //! Supports DOT, Mermaid, and JSON output formats.
 //! Uses BFS traversal from a center node or collects all documents of a type.
+//! Links are traversed in memory over a [`LinkGraph`] loaded once per build.
 
 use std::collections::{HashMap, HashSet, VecDeque};
 
+use mkb_core::link::{LinkEdge, LinkGraph};
 use mkb_index::IndexManager;
 
+/// Edge `rel` linking a document to the earlier version it supersedes.
+pub const SUPERSEDES_REL: &str = "supersedes";
+
 /// A node in the document graph.
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct GraphNode {
@@ -36,6 +41,196 @@ pub struct DocumentGraph {
 /// Builds document relationship graphs from the index.
 pub struct GraphBuilder;
 
+impl DocumentGraph {
+    /// Render as a Graphviz DOT digraph.
+    ///
+    /// Node shapes follow the document type (see [`dot_shape`]) and edges
+    /// of well-known relations are colored (see [`dot_edge_color`]). Each
+    /// node is labelled `title\n(type)` with `observed_at` as its tooltip.
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph mkb {\n  rankdir=LR;\n  node [shape=box];\n\n");
+
+        for node in &self.nodes {
+            out.push_str(&format!(
+                "  \"{}\" [label=\"{}\\n({})\" shape={} tooltip=\"{}\"];\n",
+                dot_escape(&node.id),
+                dot_escape(&node.title),
+                dot_escape(&node.doc_type),
+                dot_shape(&node.doc_type),
+                node.observed_at
+            ));
+        }
+
+        out.push('\n');
+
+        for edge in &self.edges {
+            let color = dot_edge_color(&edge.rel)
+                .map(|c| format!(" color={c}"))
+                .unwrap_or_default();
+            out.push_str(&format!(
+                "  \"{}\" -> \"{}\" [label=\"{}\"{color}];\n",
+                dot_escape(&edge.source),
+                dot_escape(&edge.target),
+                dot_escape(&edge.rel)
+            ));
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    /// Serialize the nodes and edges as pretty-printed JSON.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Render as a Mermaid `graph LR` diagram.
+    ///
+    /// Node IDs are rewritten to Mermaid-safe identifiers (characters other
+    /// than ASCII alphanumerics and `_` become `_`, with a numeric suffix on
+    /// collision). Each node is labelled `title (type)`; the original ID
+    /// does not appear in the output.
+    #[must_use]
+    pub fn to_mermaid(&self) -> String {
+        let mut ids: HashMap<String, String> = HashMap::new();
+        let mut taken: HashSet<String> = HashSet::new();
+        let mut safe_id = |id: &str| -> String {
+            if let Some(existing) = ids.get(id) {
+                return existing.clone();
+            }
+            let base: String = id
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || c == '_' {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect();
+            let mut candidate = base.clone();
+            let mut n = 2;
+            while !taken.insert(candidate.clone()) {
+                candidate = format!("{base}_{n}");
+                n += 1;
+            }
+            ids.insert(id.to_string(), candidate.clone());
+            candidate
+        };
+
+        let mut out = String::from("graph LR\n");
+
+        for node in &self.nodes {
+            let label = mermaid_escape(&format!("{} ({})", node.title, node.doc_type));
+            out.push_str(&format!("  {}[\"{label}\"]\n", safe_id(&node.id)));
+        }
+
+        out.push('\n');
+
+        for edge in &self.edges {
+            let source = safe_id(&edge.source);
+            let target = safe_id(&edge.target);
+            out.push_str(&format!(
+                "  {source} -->|\"{}\"| {target}\n",
+                mermaid_escape(&edge.rel)
+            ));
+        }
+
+        out
+    }
+}
+
+impl From<&LinkEdge> for GraphEdge {
+    fn from(link: &LinkEdge) -> Self {
+        Self {
+            source: link.source.clone(),
+            target: link.target.clone(),
+            rel: link.rel.clone(),
+            observed_at: link.observed_at.clone(),
+        }
+    }
+}
+
+fn load_links(index: &IndexManager) -> Result<LinkGraph, String> {
+    index
+        .link_graph()
+        .map_err(|e| format!("Failed to load links: {e}"))
+}
+
+/// [`SUPERSEDES_REL`] edges touching `id`, each pointing from the newer
+/// version to the older one and dated by the newer version's `observed_at`.
+///
+/// `observed_at` is `id`'s own. An edge to a newer version that is not
+/// indexed is dropped.
+fn supersession_edges(
+    index: &IndexManager,
+    id: &str,
+    observed_at: &str,
+) -> Result<Vec<GraphEdge>, String> {
+    let links = index
+        .query_supersession(id)
+        .map_err(|e| format!("Failed to query supersession of {id}: {e}"))?;
+
+    let mut edges = Vec::new();
+    if let Some(older) = links.supersedes {
+        edges.push(GraphEdge {
+            source: id.to_string(),
+            target: older,
+            rel: SUPERSEDES_REL.to_string(),
+            observed_at: observed_at.to_string(),
+        });
+    }
+    if let Some(newer) = links.superseded_by {
+        let newer_doc = index
+            .query_by_id(&newer)
+            .map_err(|e| format!("Failed to query document {newer}: {e}"))?;
+        if let Some(newer_doc) = newer_doc {
+            edges.push(GraphEdge {
+                source: newer,
+                target: id.to_string(),
+                rel: SUPERSEDES_REL.to_string(),
+                observed_at: newer_doc.observed_at,
+            });
+        }
+    }
+    Ok(edges)
+}
+
+/// Graphviz node shape for a document type: ellipses for people, diamonds
+/// for decisions and boxes for everything else.
+#[must_use]
+pub fn dot_shape(doc_type: &str) -> &'static str {
+    match doc_type {
+        "person" => "ellipse",
+        "decision" => "diamond",
+        _ => "box",
+    }
+}
+
+/// Graphviz edge color for a relation, if it has one: red for `blocked_by`,
+/// blue for `owner` and green for `depends_on`.
+#[must_use]
+pub fn dot_edge_color(rel: &str) -> Option<&'static str> {
+    match rel {
+        "blocked_by" => Some("red"),
+        "owner" => Some("blue"),
+        "depends_on" => Some("green"),
+        _ => None,
+    }
+}
+
+/// Escape text for use inside a quoted DOT string.
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Escape text for use inside a quoted Mermaid label.
+fn mermaid_escape(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
 impl GraphBuilder {
     /// Build a graph centered on a document, traversing links up to `depth` hops (BFS).
     ///
@@ -47,69 +242,121 @@ impl GraphBuilder {
         center_id: &str,
         depth: u32,
     ) -> Result<DocumentGraph, String> {
-        let mut nodes_map: HashMap<String, GraphNode> = HashMap::new();
+        let links = load_links(index)?;
+        Self::bfs(index, &links, &[center_id.to_string()], depth)
+    }
+
+    /// Build a graph seeded from every document of a type, traversing links
+    /// up to `link_depth` hops from any of them (BFS). Subgraphs are merged.
+    ///
+    /// At depth 0 the graph holds just the documents of the type and the
+    /// links among them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error string if index queries fail.
+    pub fn from_type(
+        index: &IndexManager,
+        doc_type: &str,
+        link_depth: u32,
+    ) -> Result<DocumentGraph, String> {
+        let docs = index
+            .query_by_type(doc_type)
+            .map_err(|e| format!("Failed to query type {doc_type}: {e}"))?;
+        let seeds: Vec<String> = docs.into_iter().map(|d| d.id).collect();
+
+        let links = load_links(index)?;
+        let mut graph = Self::bfs(index, &links, &seeds, link_depth)?;
+
+        // BFS only records edges while expanding, so depth 0 needs the
+        // links between seeds added explicitly
+        if link_depth == 0 {
+            let seed_ids: HashSet<&String> = seeds.iter().collect();
+            for id in &seeds {
+                for link in links.outgoing(id) {
+                    if seed_ids.contains(&link.target) {
+                        graph.edges.push(GraphEdge::from(link));
+                    }
+                }
+            }
+            for node in &graph.nodes {
+                for edge in supersession_edges(index, &node.id, &node.observed_at)? {
+                    // Seen from both ends; keep the copy found at the newer one
+                    if edge.source == node.id && seed_ids.contains(&edge.target) {
+                        graph.edges.push(edge);
+                    }
+                }
+            }
+        }
+
+        Ok(graph)
+    }
+
+    /// Multi-source BFS over forward and reverse links. Nodes are returned in
+    /// discovery order; edges are deduplicated.
+    fn bfs(
+        index: &IndexManager,
+        links: &LinkGraph,
+        seeds: &[String],
+        depth: u32,
+    ) -> Result<DocumentGraph, String> {
+        let mut nodes = Vec::new();
         let mut edges = Vec::new();
         let mut visited: HashSet<String> = HashSet::new();
         let mut queue: VecDeque<(String, u32)> = VecDeque::new();
 
-        queue.push_back((center_id.to_string(), 0));
-        visited.insert(center_id.to_string());
+        for seed in seeds {
+            if visited.insert(seed.clone()) {
+                queue.push_back((seed.clone(), 0));
+            }
+        }
 
         while let Some((current_id, current_depth)) = queue.pop_front() {
-            // Add node if not already present
-            if !nodes_map.contains_key(&current_id) {
-                if let Some(doc) = index
-                    .query_by_id(&current_id)
-                    .map_err(|e| format!("Failed to query document {current_id}: {e}"))?
-                {
-                    nodes_map.insert(
-                        current_id.clone(),
-                        GraphNode {
-                            id: doc.id,
-                            doc_type: doc.doc_type,
-                            title: doc.title,
-                            observed_at: doc.observed_at,
-                            confidence: doc.confidence,
-                        },
-                    );
-                }
+            let mut current_observed_at = None;
+            if let Some(doc) = index
+                .query_by_id(&current_id)
+                .map_err(|e| format!("Failed to query document {current_id}: {e}"))?
+            {
+                current_observed_at = Some(doc.observed_at.clone());
+                nodes.push(GraphNode {
+                    id: doc.id,
+                    doc_type: doc.doc_type,
+                    title: doc.title,
+                    observed_at: doc.observed_at,
+                    confidence: doc.confidence,
+                });
             }
 
             if current_depth >= depth {
                 continue;
             }
 
-            // Forward links
-            let forward = index
-                .query_forward_links(&current_id)
-                .map_err(|e| format!("Failed to query forward links: {e}"))?;
-            for link in &forward {
-                edges.push(GraphEdge {
-                    source: link.source_id.clone(),
-                    target: link.target_id.clone(),
-                    rel: link.rel.clone(),
-                    observed_at: link.observed_at.clone(),
-                });
-                if !visited.contains(&link.target_id) {
-                    visited.insert(link.target_id.clone());
-                    queue.push_back((link.target_id.clone(), current_depth + 1));
+            for link in links.outgoing(&current_id) {
+                edges.push(GraphEdge::from(link));
+                if visited.insert(link.target.clone()) {
+                    queue.push_back((link.target.clone(), current_depth + 1));
                 }
             }
 
-            // Reverse links
-            let reverse = index
-                .query_reverse_links(&current_id)
-                .map_err(|e| format!("Failed to query reverse links: {e}"))?;
-            for link in &reverse {
-                edges.push(GraphEdge {
-                    source: link.source_id.clone(),
-                    target: link.target_id.clone(),
-                    rel: link.rel.clone(),
-                    observed_at: link.observed_at.clone(),
-                });
-                if !visited.contains(&link.source_id) {
-                    visited.insert(link.source_id.clone());
-                    queue.push_back((link.source_id.clone(), current_depth + 1));
+            for link in links.incoming(&current_id) {
+                edges.push(GraphEdge::from(link));
+                if visited.insert(link.source.clone()) {
+                    queue.push_back((link.source.clone(), current_depth + 1));
+                }
+            }
+
+            // Supersession, in either direction
+            if let Some(observed_at) = current_observed_at {
+                for edge in supersession_edges(index, &current_id, &observed_at)? {
+                    let neighbour = if edge.source == current_id {
+                        edge.target.clone()
+                    } else {
+                        edge.source.clone()
+                    };
+                    edges.push(edge);
+                    if visited.insert(neighbour.clone()) {
+                        queue.push_back((neighbour, current_depth + 1));
+                    }
                 }
             }
         }
@@ -125,107 +372,27 @@ impl GraphBuilder {
             .collect();
 
         Ok(DocumentGraph {
-            nodes: nodes_map.into_values().collect(),
+            nodes,
             edges: unique_edges,
         })
     }
 
-    /// Build a graph of all documents of a given type.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error string if index queries fail.
-    pub fn from_type(index: &IndexManager, doc_type: &str) -> Result<DocumentGraph, String> {
-        let docs = index
-            .query_by_type(doc_type)
-            .map_err(|e| format!("Failed to query type {doc_type}: {e}"))?;
-
-        let mut nodes = Vec::new();
-        let mut edges = Vec::new();
-        let node_ids: HashSet<String> = docs.iter().map(|d| d.id.clone()).collect();
-
-        for doc in &docs {
-            nodes.push(GraphNode {
-                id: doc.id.clone(),
-                doc_type: doc.doc_type.clone(),
-                title: doc.title.clone(),
-                observed_at: doc.observed_at.clone(),
-                confidence: doc.confidence,
-            });
-
-            // Get links between nodes of this type
-            let forward = index
-                .query_forward_links(&doc.id)
-                .map_err(|e| format!("Failed to query links: {e}"))?;
-            for link in forward {
-                if node_ids.contains(&link.target_id) {
-                    edges.push(GraphEdge {
-                        source: link.source_id,
-                        target: link.target_id,
-                        rel: link.rel,
-                        observed_at: link.observed_at,
-                    });
-                }
-            }
-        }
-
-        Ok(DocumentGraph { nodes, edges })
-    }
-
-    /// Format a graph as DOT (Graphviz) output.
+    /// Format a graph as DOT (Graphviz) output. See [`DocumentGraph::to_dot`].
     #[must_use]
     pub fn format_dot(graph: &DocumentGraph) -> String {
-        let mut out = String::from("digraph mkb {\n  rankdir=LR;\n  node [shape=box];\n\n");
-
-        for node in &graph.nodes {
-            let label = node.title.replace('"', "\\\"");
-            out.push_str(&format!(
-                "  \"{}\" [label=\"{}\\n({})\" tooltip=\"{}\"];\n",
-                node.id, label, node.doc_type, node.observed_at
-            ));
-        }
-
-        out.push('\n');
-
-        for edge in &graph.edges {
-            out.push_str(&format!(
-                "  \"{}\" -> \"{}\" [label=\"{}\"];\n",
-                edge.source, edge.target, edge.rel
-            ));
-        }
-
-        out.push_str("}\n");
-        out
+        graph.to_dot()
     }
 
-    /// Format a graph as Mermaid diagram.
+    /// Format a graph as Mermaid diagram. See [`DocumentGraph::to_mermaid`].
     #[must_use]
     pub fn format_mermaid(graph: &DocumentGraph) -> String {
-        let mut out = String::from("graph LR\n");
-
-        for node in &graph.nodes {
-            let label = node.title.replace('"', "'");
-            out.push_str(&format!("  {}[\"{}\"]\n", node.id.replace('-', "_"), label));
-        }
-
-        out.push('\n');
-
-        for edge in &graph.edges {
-            out.push_str(&format!(
-                "  {} -->|{}| {}\n",
-                edge.source.replace('-', "_"),
-                edge.rel,
-                edge.target.replace('-', "_")
-            ));
-        }
-
-        out
+        graph.to_mermaid()
     }
 
-    /// Format a graph as JSON.
+    /// Format a graph as JSON. See [`DocumentGraph::to_json`].
     #[must_use]
     pub fn format_json(graph: &DocumentGraph) -> String {
-        serde_json::to_string_pretty(graph).unwrap_or_else(|_| "{}".to_string())
+        graph.to_json()
     }
 }
 
@@ -247,6 +414,7 @@ mod tests {
             valid_until: Some(utc(2025, 8, 10)),
             temporal_precision: Some(TemporalPrecision::Day),
             occurred_at: None,
+            occurred_until: None,
         };
         let profile = DecayProfile::default_profile();
         let mut doc = Document::new(
@@ -357,6 +525,60 @@ mod tests {
         assert!(dot.ends_with("}\n"));
     }
 
+    #[test]
+    fn fixture_graph_has_exact_node_and_edge_counts() {
+        let index = setup_graph_index();
+
+        let graph = GraphBuilder::from_center(&index, "proj-alpha-001", 1).unwrap();
+        assert_eq!(graph.nodes.len(), 4);
+        assert_eq!(graph.edges.len(), 3);
+
+        let graph = GraphBuilder::from_center(&index, "pers-jane-001", 1).unwrap();
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.edges.len(), 1);
+
+        let json: serde_json::Value = serde_json::from_str(
+            &GraphBuilder::from_type(&index, "project", 0)
+                .unwrap()
+                .to_json(),
+        )
+        .unwrap();
+        assert_eq!(json["nodes"].as_array().unwrap().len(), 2);
+        assert_eq!(json["edges"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn to_dot_shapes_nodes_by_type_and_colors_edges_by_rel() {
+        let index = setup_graph_index();
+        index
+            .index_document(&make_doc("dec-adopt-001", "decision", "Adopt \"Rust\""))
+            .unwrap();
+        index
+            .store_links(
+                "dec-adopt-001",
+                &[Link {
+                    rel: "blocked_by".to_string(),
+                    target: "proj-alpha-001".to_string(),
+                    observed_at: utc(2025, 2, 10),
+                    metadata: None,
+                }],
+            )
+            .unwrap();
+        let dot = GraphBuilder::from_center(&index, "proj-alpha-001", 1)
+            .unwrap()
+            .to_dot();
+
+        let line = |needle: &str| dot.lines().find(|l| l.contains(needle)).unwrap();
+        assert!(line("\"proj-beta-001\" [").contains("shape=box"));
+        assert!(line("\"pers-jane-001\" [").contains("shape=ellipse"));
+        assert!(line("\"dec-adopt-001\" [").contains("shape=diamond"));
+        assert!(line("\"dec-adopt-001\" [").contains("Adopt \\\"Rust\\\""));
+        assert!(line("[label=\"owner\"").contains("color=blue"));
+        assert!(line("[label=\"depends_on\"").contains("color=green"));
+        assert!(line("[label=\"blocked_by\"").contains("color=red"));
+        assert!(!line("[label=\"discussed\"").contains("color="));
+    }
+
     #[test]
     fn format_mermaid_output() {
         let index = setup_graph_index();
@@ -381,7 +603,7 @@ mod tests {
     #[test]
     fn graph_by_type() {
         let index = setup_graph_index();
-        let graph = GraphBuilder::from_type(&index, "project").unwrap();
+        let graph = GraphBuilder::from_type(&index, "project", 0).unwrap();
 
         assert_eq!(graph.nodes.len(), 2); // Alpha + Beta
                                           // Should include the depends_on edge between them
@@ -390,4 +612,113 @@ mod tests {
             "Expected at least 1 edge between projects"
         );
     }
+
+    #[test]
+    fn graph_by_type_with_depth_merges_subgraphs() {
+        let index = setup_graph_index();
+        let graph = GraphBuilder::from_type(&index, "project", 1).unwrap();
+
+        let mut ids: Vec<&str> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+        ids.sort_unstable();
+        // Both projects plus Jane (owner of Alpha) and the standup (discusses Alpha)
+        assert_eq!(
+            ids,
+            vec![
+                "meet-standup-001",
+                "pers-jane-001",
+                "proj-alpha-001",
+                "proj-beta-001"
+            ]
+        );
+        // depends_on is reachable from both seeds but appears once
+        assert_eq!(
+            graph.edges.iter().filter(|e| e.rel == "depends_on").count(),
+            1
+        );
+    }
+
+    #[test]
+    fn to_mermaid_sanitizes_ids_and_labels_edges() {
+        let index = setup_graph_index();
+        let graph = GraphBuilder::from_type(&index, "project", 1).unwrap();
+        let mermaid = graph.to_mermaid();
+
+        assert!(mermaid.starts_with("graph LR\n"));
+        assert!(mermaid.contains("proj_alpha_001[\"Alpha (project)\"]"));
+        assert!(mermaid.contains("proj_alpha_001 -->|\"depends_on\"| proj_beta_001"));
+        assert!(!mermaid.contains("proj-alpha-001"));
+    }
+
+    #[test]
+    fn to_mermaid_disambiguates_colliding_ids() {
+        let node = |id: &str, title: &str| GraphNode {
+            id: id.to_string(),
+            doc_type: "note".to_string(),
+            title: title.to_string(),
+            observed_at: String::new(),
+            confidence: 1.0,
+        };
+        let graph = DocumentGraph {
+            nodes: vec![node("a-b", "Dash"), node("a/b", "Slash \"quoted\"")],
+            edges: vec![GraphEdge {
+                source: "a-b".to_string(),
+                target: "a/b".to_string(),
+                rel: "see_also".to_string(),
+                observed_at: String::new(),
+            }],
+        };
+        let mermaid = graph.to_mermaid();
+
+        assert!(mermaid.contains("a_b[\"Dash (note)\"]"));
+        assert!(mermaid.contains("a_b_2[\"Slash #quot;quoted#quot; (note)\"]"));
+        assert!(mermaid.contains("a_b -->|\"see_also\"| a_b_2"));
+    }
+
+    fn setup_supersession_index() -> IndexManager {
+        let index = IndexManager::in_memory().unwrap();
+        for (id, supersedes, superseded_by) in [
+            ("dec-v1-001", None, Some("dec-v2-001")),
+            ("dec-v2-001", Some("dec-v1-001"), Some("dec-v3-001")),
+            ("dec-v3-001", Some("dec-v2-001"), None),
+        ] {
+            let mut doc = make_doc(id, "decision", id);
+            doc.supersedes = supersedes.map(str::to_string);
+            doc.superseded_by = superseded_by.map(str::to_string);
+            index.index_document(&doc).unwrap();
+        }
+        index
+    }
+
+    fn supersedes_pairs(graph: &DocumentGraph) -> Vec<(&str, &str)> {
+        let mut pairs: Vec<(&str, &str)> = graph
+            .edges
+            .iter()
+            .filter(|e| e.rel == SUPERSEDES_REL)
+            .map(|e| (e.source.as_str(), e.target.as_str()))
+            .collect();
+        pairs.sort_unstable();
+        pairs
+    }
+
+    #[test]
+    fn graph_follows_supersession_both_ways() {
+        let index = setup_supersession_index();
+        let graph = GraphBuilder::from_center(&index, "dec-v2-001", 1).unwrap();
+        assert_eq!(graph.nodes.len(), 3);
+        assert_eq!(
+            supersedes_pairs(&graph),
+            [("dec-v2-001", "dec-v1-001"), ("dec-v3-001", "dec-v2-001")]
+        );
+    }
+
+    #[test]
+    fn graph_by_type_at_depth_0_includes_supersession() {
+        let index = setup_supersession_index();
+        let graph = GraphBuilder::from_type(&index, "decision", 0).unwrap();
+        assert_eq!(graph.nodes.len(), 3);
+        assert_eq!(
+            supersedes_pairs(&graph),
+            [("dec-v2-001", "dec-v1-001"), ("dec-v3-001", "dec-v2-001")]
+        );
+    }
 }
@@ -4,17 +4,44 @@
 //! that combine SQLite queries with vector similarity search.
 //!
 //! Includes:
-//! - MKQL-to-SQL compiler
-//! - Result formatter (JSON, Table, Markdown, Context)
+//! - MKQL-to-SQL compiler, with a [`QueryCache`] keyed on normalized MKQL
+//! - Query plan explanation (no execution)
+//! - Result post-processing with [`ResultTransformer`] chains
+//! - Result formatter (JSON, Table, Markdown, Context, PivotTable)
 //! - Context assembler for LLM token budgets
 
+mod cache;
 mod compiler;
 mod context;
+mod delimited;
 mod executor;
+mod explain;
+mod feed;
 mod formatter;
 pub mod graph;
+mod transform;
 
-pub use compiler::{compile, CompiledQuery};
-pub use context::{ContextAssembler, ContextOpts};
-pub use executor::execute;
-pub use formatter::{format_results, OutputFormat, QueryResult, ResultRow};
+pub use cache::QueryCache;
+pub use compiler::{
+    compile, compile_with_options, output_column, validate_identifiers, CompileError,
+    CompileOptions, CompiledQuery, DOCUMENT_FIELDS,
+};
+pub use context::{
+    normalize_fts_ranks, tokenizer_by_name, Cl100kTokenizer, ContextAssembler, ContextOpts,
+    PriorityWeights, Tokenizer, WhitespaceTokenizer,
+};
+pub use delimited::{format_csv, CsvOpts};
+pub use executor::{
+    execute, execute_streaming, execute_streaming_with_provider, execute_with_params,
+    execute_with_provider, explain_plan, QueryStream,
+};
+pub use explain::{explain, ExplainOutput, ExplainParam, TableScan};
+pub use feed::{format_atom, FeedOpts, FEED_SUMMARY_CHARS};
+pub use formatter::{
+    format_results, write_results_streaming, OutputFormat, QueryResult, ResultRow,
+    DEFAULT_CONTEXT_TEMPLATE, PIVOT_ROW_COLUMN,
+};
+pub use transform::{
+    ConfidenceFilterTransformer, DeduplicateTransformer, FieldProjectionTransformer,
+    LimitTransformer, ResultTransformer, SortTransformer, TransformSpec,
+};
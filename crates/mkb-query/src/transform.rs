@@ -0,0 +1,252 @@
+//! Post-processing of query results.
+//!
+//! A [`CompiledQuery`](crate::CompiledQuery) carries a chain of
+//! [`ResultTransformer`]s that the executor applies, in order, to the rows
+//! SQL returned. Transformers see every row at once, so a query with any
+//! is buffered before its first row is yielded.
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use serde::Deserialize;
+
+use crate::formatter::ResultRow;
+
+/// One step of result post-processing.
+pub trait ResultTransformer: Debug + Send + Sync {
+    /// The rows to pass on, in order.
+    fn transform(&self, rows: Vec<ResultRow>) -> Vec<ResultRow>;
+}
+
+/// Stable sort on one field. Numbers compare numerically and everything
+/// else by its text; rows missing the field (or holding null) go last in
+/// either direction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortTransformer {
+    pub field: String,
+    pub descending: bool,
+}
+
+impl ResultTransformer for SortTransformer {
+    fn transform(&self, mut rows: Vec<ResultRow>) -> Vec<ResultRow> {
+        rows.sort_by(|a, b| {
+            let key = |row: &ResultRow| {
+                row.fields
+                    .get(&self.field)
+                    .filter(|v| !v.is_null())
+                    .cloned()
+            };
+            match (key(a), key(b)) {
+                (Some(a), Some(b)) if self.descending => compare_values(&b, &a),
+                (Some(a), Some(b)) => compare_values(&a, &b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            }
+        });
+        rows
+    }
+}
+
+fn compare_values(a: &serde_json::Value, b: &serde_json::Value) -> Ordering {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        _ => match (a.as_str(), b.as_str()) {
+            (Some(a), Some(b)) => a.cmp(b),
+            _ => a.to_string().cmp(&b.to_string()),
+        },
+    }
+}
+
+/// Keep the first row of each `id`. Rows without an `id` are all kept.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeduplicateTransformer;
+
+impl ResultTransformer for DeduplicateTransformer {
+    fn transform(&self, rows: Vec<ResultRow>) -> Vec<ResultRow> {
+        let mut seen = HashSet::new();
+        rows.into_iter()
+            .filter(|row| match row.fields.get("id") {
+                Some(id) if !id.is_null() => seen.insert(id.to_string()),
+                _ => true,
+            })
+            .collect()
+    }
+}
+
+/// Drop rows whose `confidence` is below `min`. Rows without a numeric
+/// `confidence` are kept, as the query may not have selected it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConfidenceFilterTransformer {
+    pub min: f64,
+}
+
+impl ResultTransformer for ConfidenceFilterTransformer {
+    fn transform(&self, rows: Vec<ResultRow>) -> Vec<ResultRow> {
+        rows.into_iter()
+            .filter(|row| {
+                row.fields
+                    .get("confidence")
+                    .and_then(serde_json::Value::as_f64)
+                    .is_none_or(|confidence| confidence >= self.min)
+            })
+            .collect()
+    }
+}
+
+/// Keep the first `limit` rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LimitTransformer {
+    pub limit: usize,
+}
+
+impl ResultTransformer for LimitTransformer {
+    fn transform(&self, mut rows: Vec<ResultRow>) -> Vec<ResultRow> {
+        rows.truncate(self.limit);
+        rows
+    }
+}
+
+/// Keep only the named fields of each row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldProjectionTransformer {
+    pub fields: Vec<String>,
+}
+
+impl ResultTransformer for FieldProjectionTransformer {
+    fn transform(&self, mut rows: Vec<ResultRow>) -> Vec<ResultRow> {
+        for row in &mut rows {
+            row.fields.retain(|name, _| self.fields.contains(name));
+        }
+        rows
+    }
+}
+
+/// A transformer described as data, e.g. `{"type": "dedup"}` or
+/// `{"type": "confidence_filter", "min": 0.7}`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TransformSpec {
+    Sort {
+        field: String,
+        #[serde(default)]
+        descending: bool,
+    },
+    Dedup,
+    ConfidenceFilter {
+        min: f64,
+    },
+    Limit {
+        limit: usize,
+    },
+    Project {
+        fields: Vec<String>,
+    },
+}
+
+impl TransformSpec {
+    /// Parse a spec from JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns a string error if `value` names no known transformer or
+    /// lacks one of its fields.
+    pub fn from_json(value: &serde_json::Value) -> Result<Self, String> {
+        Self::deserialize(value).map_err(|e| format!("Invalid transform {value}: {e}"))
+    }
+
+    /// The transformer this spec describes.
+    #[must_use]
+    pub fn build(self) -> Arc<dyn ResultTransformer> {
+        match self {
+            Self::Sort { field, descending } => Arc::new(SortTransformer { field, descending }),
+            Self::Dedup => Arc::new(DeduplicateTransformer),
+            Self::ConfidenceFilter { min } => Arc::new(ConfidenceFilterTransformer { min }),
+            Self::Limit { limit } => Arc::new(LimitTransformer { limit }),
+            Self::Project { fields } => Arc::new(FieldProjectionTransformer { fields }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(id: &str, confidence: f64) -> ResultRow {
+        ResultRow {
+            fields: [
+                ("id".to_string(), serde_json::json!(id)),
+                ("confidence".to_string(), serde_json::json!(confidence)),
+            ]
+            .into_iter()
+            .collect(),
+        }
+    }
+
+    fn ids(rows: &[ResultRow]) -> Vec<&str> {
+        rows.iter()
+            .map(|r| r.fields["id"].as_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn dedup_keeps_first_row_per_id() {
+        let rows = vec![row("a", 0.9), row("b", 0.5), row("a", 0.1)];
+        let out = DeduplicateTransformer.transform(rows);
+        assert_eq!(ids(&out), vec!["a", "b"]);
+        assert_eq!(out[0].fields["confidence"], 0.9);
+    }
+
+    #[test]
+    fn confidence_filter_drops_low_confidence_rows() {
+        let mut unknown = row("c", 0.0);
+        unknown.fields.remove("confidence");
+        let rows = vec![row("a", 0.9), row("b", 0.5), unknown, row("d", 0.7)];
+        let out = ConfidenceFilterTransformer { min: 0.7 }.transform(rows);
+        assert_eq!(ids(&out), vec!["a", "c", "d"]);
+    }
+
+    #[test]
+    fn sort_limit_and_projection() {
+        let mut missing = row("z", 0.0);
+        missing.fields.remove("confidence");
+        let rows = vec![row("a", 0.5), missing, row("b", 0.9), row("c", 0.7)];
+
+        let sorted = SortTransformer {
+            field: "confidence".to_string(),
+            descending: true,
+        }
+        .transform(rows);
+        assert_eq!(ids(&sorted), vec!["b", "c", "a", "z"]);
+
+        let limited = LimitTransformer { limit: 2 }.transform(sorted);
+        let projected = FieldProjectionTransformer {
+            fields: vec!["id".to_string()],
+        }
+        .transform(limited);
+        assert_eq!(ids(&projected), vec!["b", "c"]);
+        assert!(projected.iter().all(|r| r.fields.len() == 1));
+    }
+
+    #[test]
+    fn spec_parses_from_json() {
+        let spec =
+            TransformSpec::from_json(&serde_json::json!({"type": "confidence_filter", "min": 0.7}))
+                .unwrap();
+        assert_eq!(spec, TransformSpec::ConfidenceFilter { min: 0.7 });
+        assert_eq!(
+            TransformSpec::from_json(&serde_json::json!({"type": "sort", "field": "title"}))
+                .unwrap(),
+            TransformSpec::Sort {
+                field: "title".to_string(),
+                descending: false
+            }
+        );
+
+        let err = TransformSpec::from_json(&serde_json::json!({"type": "shuffle"})).unwrap_err();
+        assert!(err.contains("shuffle"), "{err}");
+        assert!(TransformSpec::from_json(&serde_json::json!({"type": "limit"})).is_err());
+    }
+}
@@ -0,0 +1,342 @@
+//! Bulk import of a directory of markdown files.
+//!
+//! Files are read, parsed and written to the vault on a `rayon` pool.
+//! Each written document is sent over a channel back to the calling
+//! thread, so a single-writer store such as the SQLite index can be
+//! updated from there without locking.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use mkb_core::document::{Document, ProvenanceRecord, ProvenanceType};
+use mkb_core::error::MkbError;
+use mkb_core::frontmatter::parse_document;
+use mkb_core::temporal::{DecayProfile, RawTemporalInput, TemporalPrecision};
+use rayon::prelude::*;
+
+use crate::Vault;
+
+/// Outcome of [`Vault::import_directory`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportStats {
+    pub imported: usize,
+    pub rejected: usize,
+    pub elapsed: Duration,
+    /// `imported` per second of `elapsed`.
+    pub throughput_docs_per_sec: f64,
+    /// Why each rejected file was rejected, ordered by path.
+    pub rejections: Vec<RejectedFile>,
+}
+
+/// A file left out of an import.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RejectedFile {
+    pub path: PathBuf,
+    pub error: String,
+}
+
+/// Hands out `<stem>-<counter>` IDs to concurrent workers.
+///
+/// Each stem's counter starts after the highest ID already in the vault
+/// and is only advanced under the lock, so no two files get the same ID.
+struct IdAllocator<'a> {
+    vault: &'a Vault,
+    doc_type: &'a str,
+    next: Mutex<HashMap<String, u32>>,
+}
+
+impl<'a> IdAllocator<'a> {
+    fn new(vault: &'a Vault, doc_type: &'a str) -> Self {
+        Self {
+            vault,
+            doc_type,
+            next: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn allocate(&self, title: &str) -> String {
+        let mut next = self.next.lock().unwrap_or_else(PoisonError::into_inner);
+        let counter = next
+            .entry(Document::id_stem(self.doc_type, title))
+            .or_insert_with(|| {
+                let mut counter = 1;
+                while self
+                    .vault
+                    .document_path(
+                        self.doc_type,
+                        &Document::generate_id(self.doc_type, title, counter),
+                    )
+                    .exists()
+                {
+                    counter += 1;
+                }
+                counter
+            });
+        let id = Document::generate_id(self.doc_type, title, *counter);
+        *counter += 1;
+        id
+    }
+}
+
+impl Vault {
+    /// Import every `.md` file directly inside `dir` using `parallelism`
+    /// worker threads (0 picks one per CPU).
+    ///
+    /// See [`Vault::import_directory_with`].
+    ///
+    /// # Errors
+    ///
+    /// As [`Vault::import_directory_with`].
+    pub fn import_directory(
+        &self,
+        dir: &Path,
+        doc_type: &str,
+        parallelism: usize,
+    ) -> Result<ImportStats, MkbError> {
+        self.import_directory_with(dir, doc_type, parallelism, |_, _| Ok(()))
+    }
+
+    /// Import every `.md` file directly inside `dir`, calling `on_imported`
+    /// for each document written to the vault.
+    ///
+    /// A file with valid MKB frontmatter is imported as-is. Any other file
+    /// becomes a `doc_type` document observed now, titled by its first
+    /// `# ` heading, with the file content as body. Files are processed on
+    /// `parallelism` worker threads (0 picks one per CPU), but
+    /// `on_imported` always runs on the calling thread, one document at a
+    /// time.
+    ///
+    /// A file that cannot be read, parsed or written, or whose document
+    /// `on_imported` fails on, is rejected without stopping the import. A
+    /// document `on_imported` fails on is removed from the vault again, so
+    /// the vault holds exactly the documents counted as imported. Rejection
+    /// logs are written together once every file is processed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Io`] if `dir` cannot be read or a rejection log
+    /// cannot be written, or [`MkbError::Vault`] if the worker pool cannot
+    /// be started.
+    pub fn import_directory_with(
+        &self,
+        dir: &Path,
+        doc_type: &str,
+        parallelism: usize,
+        mut on_imported: impl FnMut(&Path, &Document) -> Result<(), MkbError>,
+    ) -> Result<ImportStats, MkbError> {
+        let start = Instant::now();
+        let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                path.is_file() && path.extension().and_then(|e| e.to_str()) == Some("md")
+            })
+            .collect();
+        files.sort();
+
+        let profile = self.load_decay_profile()?;
+        let ids = IdAllocator::new(self, doc_type);
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(parallelism)
+            .build()
+            .map_err(|e| MkbError::Vault(format!("Failed to start import workers: {e}")))?;
+
+        let mut imported = 0;
+        let mut rejections = Vec::new();
+        let (tx, rx) = mpsc::channel();
+        std::thread::scope(|scope| {
+            let (files, profile, ids, pool) = (&files, &profile, &ids, &pool);
+            scope.spawn(move || {
+                pool.install(|| {
+                    files.par_iter().for_each_with(tx, |tx, path| {
+                        let _ = tx.send((path, self.import_file(path, doc_type, profile, ids)));
+                    });
+                });
+            });
+
+            for (path, outcome) in rx {
+                let outcome = outcome.and_then(|doc| {
+                    on_imported(path, &doc).inspect_err(|_| {
+                        let _ = fs::remove_file(self.document_path(&doc.doc_type, &doc.id));
+                    })
+                });
+                match outcome {
+                    Ok(()) => imported += 1,
+                    Err(e) => rejections.push(RejectedFile {
+                        path: path.clone(),
+                        error: e.to_string(),
+                    }),
+                }
+            }
+        });
+
+        rejections.sort_by(|a, b| a.path.cmp(&b.path));
+        for rejection in &rejections {
+            let filename = rejection
+                .path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("unknown");
+            let content = fs::read_to_string(&rejection.path).unwrap_or_default();
+            self.write_rejection(filename, &content, &rejection.error, &[])?;
+        }
+
+        let elapsed = start.elapsed();
+        Ok(ImportStats {
+            imported,
+            rejected: rejections.len(),
+            elapsed,
+            throughput_docs_per_sec: imported as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+            rejections,
+        })
+    }
+
+    /// Turn one file into a document and write it to the vault.
+    fn import_file(
+        &self,
+        path: &Path,
+        doc_type: &str,
+        profile: &DecayProfile,
+        ids: &IdAllocator<'_>,
+    ) -> Result<Document, MkbError> {
+        let content = fs::read_to_string(path)?;
+        let doc = match parse_document(&content) {
+            Ok(doc) => doc,
+            Err(_) => {
+                let title = content
+                    .lines()
+                    .find(|l| l.starts_with("# "))
+                    .map_or("Untitled", |l| l.trim_start_matches("# "))
+                    .to_string();
+                let input = RawTemporalInput {
+                    observed_at: Some(Utc::now()),
+                    valid_until: None,
+                    temporal_precision: Some(TemporalPrecision::Day),
+                    occurred_at: None,
+                    occurred_until: None,
+                };
+                let mut doc = Document::new(
+                    ids.allocate(&title),
+                    doc_type.to_string(),
+                    title,
+                    input,
+                    profile,
+                )?;
+                doc.body = content;
+                doc
+            }
+        };
+        let mut doc = doc.sanitize(Document::MAX_BODY_BYTES, Document::MAX_FIELD_BYTES);
+        doc.provenance.get_or_insert_with(|| ProvenanceRecord {
+            original_filename: path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned()),
+            ..ProvenanceRecord::new(ProvenanceType::Import, "bulk_import")
+        });
+        self.create(&doc)?;
+        Ok(doc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn import_directory_imports_every_file_once() {
+        let vault_dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(vault_dir.path()).unwrap();
+        let source = tempfile::tempdir().unwrap();
+        // Shared titles make every worker race for the same ID counter
+        for i in 0..100 {
+            let title = if i % 2 == 0 {
+                "Weekly Notes"
+            } else {
+                "Standup"
+            };
+            fs::write(
+                source.path().join(format!("note-{i:03}.md")),
+                format!("# {title}\n\nEntry {i}\n"),
+            )
+            .unwrap();
+        }
+        fs::write(source.path().join("skip.txt"), "not markdown").unwrap();
+
+        let mut ids = HashSet::new();
+        let stats = vault
+            .import_directory_with(source.path(), "note", 8, |path, doc| {
+                let provenance = doc.provenance.as_ref().unwrap();
+                assert_eq!(provenance.source_type, ProvenanceType::Import);
+                assert_eq!(
+                    provenance.original_filename.as_deref(),
+                    path.file_name().and_then(|n| n.to_str())
+                );
+                ids.insert(doc.id.clone());
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(stats.imported, 100);
+        assert_eq!(stats.rejected, 0);
+        assert!(stats.throughput_docs_per_sec > 0.0);
+        assert_eq!(ids.len(), 100);
+        assert!(ids.contains("note-weekly-notes-050"));
+        assert!(ids.contains("note-standup-050"));
+        assert_eq!(
+            fs::read_dir(vault_dir.path().join("notes"))
+                .unwrap()
+                .count(),
+            100
+        );
+
+        // A second import continues after the IDs already taken
+        let again = vault.import_directory(source.path(), "note", 4).unwrap();
+        assert_eq!(again.imported, 100);
+        assert!(vault.document_path("note", "note-standup-100").exists());
+    }
+
+    #[test]
+    fn import_directory_rejects_failed_files_and_logs_them() {
+        let vault_dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(vault_dir.path()).unwrap();
+        let source = tempfile::tempdir().unwrap();
+        fs::write(source.path().join("a.md"), "# Alpha\n").unwrap();
+        fs::write(source.path().join("b.md"), "# Beta\n").unwrap();
+
+        let stats = vault
+            .import_directory_with(source.path(), "note", 2, |path, _| {
+                if path.ends_with("b.md") {
+                    Err(MkbError::Index("disk full".to_string()))
+                } else {
+                    Ok(())
+                }
+            })
+            .unwrap();
+
+        assert_eq!(stats.imported, 1);
+        assert_eq!(
+            stats.rejections,
+            vec![RejectedFile {
+                path: source.path().join("b.md"),
+                error: "index error: disk full".to_string(),
+            }]
+        );
+        assert_eq!(vault.rejection_count().unwrap(), 1);
+        // Only the document counted as imported stays in the vault
+        let titles: Vec<String> = vault
+            .list_documents()
+            .unwrap()
+            .iter()
+            .map(|p| {
+                parse_document(&fs::read_to_string(p).unwrap())
+                    .unwrap()
+                    .title
+            })
+            .collect();
+        assert_eq!(titles, vec!["Alpha".to_string()]);
+    }
+}
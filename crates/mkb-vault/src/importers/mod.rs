@@ -0,0 +1,3 @@
+//! Importers that convert notes from other tools into vault documents.
+
+pub mod obsidian;
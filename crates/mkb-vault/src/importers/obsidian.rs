@@ -0,0 +1,435 @@
+//! Import an Obsidian vault.
+//!
+//! Every `.md` file under the source directory becomes one document.
+//! Hidden directories such as `.obsidian/` and `.trash/` are skipped.
+//! `[[WikiLink]]`s that resolve to another imported note become
+//! [`Link`]s with rel [`WIKILINK_REL`]. Links to notes that were not
+//! imported are reported as dangling.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use mkb_core::document::{Document, ProvenanceRecord, ProvenanceType};
+use mkb_core::error::MkbError;
+use mkb_core::frontmatter::split_frontmatter;
+use mkb_core::link::{extract_wikilinks, Link};
+use mkb_core::temporal::{parse_datetime, DecayProfile, RawTemporalInput, TemporalPrecision};
+
+use crate::Vault;
+
+pub use mkb_core::link::WIKILINK_REL;
+
+/// Frontmatter keys mapped onto document fields rather than copied as-is.
+const MAPPED_KEYS: [&str; 5] = ["title", "date", "created", "tags", "aliases"];
+
+/// Converts an Obsidian vault into MKB documents.
+#[derive(Debug, Clone)]
+pub struct ObsidianImporter {
+    source: PathBuf,
+    doc_type: String,
+}
+
+/// Outcome of [`ObsidianImporter::import`].
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    /// Documents written to the vault, with their resolved links.
+    pub documents: Vec<Document>,
+    /// WikiLinks that resolved to an imported note.
+    pub links_resolved: usize,
+    /// WikiLinks whose target is not among the imported notes.
+    pub dangling_links: Vec<DanglingWikiLink>,
+    /// Notes that could not be converted.
+    pub skipped: Vec<SkippedNote>,
+}
+
+/// A WikiLink that did not resolve.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DanglingWikiLink {
+    pub source_id: String,
+    /// Link target as written, e.g. `Missing Note`.
+    pub target: String,
+}
+
+/// A note left out of the import.
+#[derive(Debug, Clone)]
+pub struct SkippedNote {
+    /// Path relative to the Obsidian vault.
+    pub path: PathBuf,
+    pub error: String,
+}
+
+/// A parsed note waiting for an ID and link resolution.
+struct Note {
+    rel_path: PathBuf,
+    aliases: Vec<String>,
+    wikilinks: Vec<String>,
+    doc: Document,
+}
+
+impl ObsidianImporter {
+    /// Import from the Obsidian vault at `source` as `document`-type docs.
+    pub fn new(source: impl Into<PathBuf>) -> Self {
+        Self {
+            source: source.into(),
+            doc_type: "document".to_string(),
+        }
+    }
+
+    /// Use `doc_type` for imported documents.
+    #[must_use]
+    pub fn with_doc_type(mut self, doc_type: impl Into<String>) -> Self {
+        self.doc_type = doc_type.into();
+        self
+    }
+
+    /// Convert every note and write the documents into `vault`.
+    ///
+    /// Notes whose frontmatter cannot be parsed are reported in
+    /// [`ImportReport::skipped`] and do not stop the import.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Io`] if the source cannot be read, or any error
+    /// from [`Vault::create`] while writing a document.
+    pub fn import(&self, vault: &Vault) -> Result<ImportReport, MkbError> {
+        let mut files = Vec::new();
+        collect_notes(&self.source, &mut files)?;
+        files.sort();
+
+        let mut report = ImportReport::default();
+        let mut notes = Vec::with_capacity(files.len());
+        for path in files {
+            let rel_path = path
+                .strip_prefix(&self.source)
+                .unwrap_or(&path)
+                .to_path_buf();
+            match self.parse_note(&path, rel_path.clone()) {
+                Ok(note) => notes.push(note),
+                Err(e) => report.skipped.push(SkippedNote {
+                    path: rel_path,
+                    error: e.to_string(),
+                }),
+            }
+        }
+
+        self.assign_ids(vault, &mut notes);
+        let lookup = build_lookup(&notes);
+
+        for note in &mut notes {
+            let mut seen = HashSet::new();
+            for target in &note.wikilinks {
+                match lookup.get(&target.to_lowercase()) {
+                    Some(id) if seen.insert(id.clone()) => {
+                        note.doc.links.push(Link {
+                            rel: WIKILINK_REL.to_string(),
+                            target: id.clone(),
+                            observed_at: note.doc.temporal.observed_at,
+                            metadata: None,
+                        });
+                        report.links_resolved += 1;
+                    }
+                    Some(_) => {}
+                    None => {
+                        let dangling = DanglingWikiLink {
+                            source_id: note.doc.id.clone(),
+                            target: target.clone(),
+                        };
+                        if !report.dangling_links.contains(&dangling) {
+                            report.dangling_links.push(dangling);
+                        }
+                    }
+                }
+            }
+        }
+
+        for note in notes {
+            vault.create(&note.doc)?;
+            report.documents.push(note.doc);
+        }
+        Ok(report)
+    }
+
+    fn parse_note(&self, path: &Path, rel_path: PathBuf) -> Result<Note, MkbError> {
+        let content = fs::read_to_string(path)?;
+        let (frontmatter, body) = match split_frontmatter(&content) {
+            Ok((yaml, body)) => {
+                let value: serde_yaml::Value = serde_yaml::from_str(yaml)
+                    .map_err(|e| MkbError::Serialization(e.to_string()))?;
+                let map = match value {
+                    serde_yaml::Value::Mapping(map) => map,
+                    serde_yaml::Value::Null => serde_yaml::Mapping::new(),
+                    _ => return Err(MkbError::Parse("frontmatter is not a mapping".to_string())),
+                };
+                (map, body)
+            }
+            Err(_) => (serde_yaml::Mapping::new(), content.as_str()),
+        };
+        let get = |key: &str| frontmatter.get(key);
+
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("Untitled");
+        let title = get("title")
+            .and_then(serde_yaml::Value::as_str)
+            .unwrap_or(stem)
+            .to_string();
+
+        // Obsidian has no observation time; the note's date stands in,
+        // falling back to when the file was last written
+        let dated = ["date", "created"]
+            .iter()
+            .filter_map(|key| get(key).and_then(serde_yaml::Value::as_str))
+            .find_map(|s| parse_datetime(s).ok());
+        let (observed_at, precision) = match dated {
+            Some(parsed) => (
+                parsed.utc,
+                parsed.coerce_precision(TemporalPrecision::Exact),
+            ),
+            None => {
+                let modified = fs::metadata(path)?.modified()?;
+                (DateTime::<Utc>::from(modified), TemporalPrecision::Exact)
+            }
+        };
+
+        let input = RawTemporalInput {
+            observed_at: Some(observed_at),
+            valid_until: None,
+            temporal_precision: Some(precision),
+            occurred_at: None,
+            occurred_until: None,
+        };
+        let mut doc = Document::new(
+            String::new(),
+            self.doc_type.clone(),
+            title,
+            input,
+            &DecayProfile::default_profile(),
+        )?;
+        doc.body = body.to_string();
+        doc.provenance = Some(ProvenanceRecord {
+            source_uri: Some(format!("obsidian:{}", rel_path.display())),
+            original_filename: Some(rel_path.display().to_string()),
+            ..ProvenanceRecord::new(ProvenanceType::Import, "obsidian")
+        });
+        doc.tags = get("tags")
+            .map(string_list)
+            .unwrap_or_default()
+            .iter()
+            .map(|t| t.trim_start_matches('#').to_string())
+            .filter(|t| !t.is_empty())
+            .collect();
+
+        let aliases = get("aliases").map(string_list).unwrap_or_default();
+        if !aliases.is_empty() {
+            doc.fields
+                .insert("aliases".to_string(), serde_json::json!(aliases));
+        }
+        for (key, value) in &frontmatter {
+            let Some(key) = key.as_str() else { continue };
+            if MAPPED_KEYS.contains(&key) {
+                continue;
+            }
+            let value =
+                serde_json::to_value(value).map_err(|e| MkbError::Serialization(e.to_string()))?;
+            doc.fields.insert(key.to_string(), value);
+        }
+
+        Ok(Note {
+            rel_path,
+            aliases,
+            wikilinks: extract_wikilinks(body),
+            doc,
+        })
+    }
+
+    /// Give each note an ID not already used in the vault or by another note.
+    fn assign_ids(&self, vault: &Vault, notes: &mut [Note]) {
+        let mut taken = HashSet::new();
+        for note in notes {
+            let mut counter = 1;
+            let id = loop {
+                let id = Document::generate_id(&self.doc_type, &note.doc.title, counter);
+                if !taken.contains(&id) && !vault.document_path(&self.doc_type, &id).exists() {
+                    break id;
+                }
+                counter += 1;
+            };
+            taken.insert(id.clone());
+            note.doc.id = id;
+        }
+    }
+}
+
+/// Map every name a note can be linked by (lowercased) to its ID: file
+/// name, path within the vault, and aliases. Earlier notes win ties.
+fn build_lookup(notes: &[Note]) -> HashMap<String, String> {
+    let mut lookup = HashMap::new();
+    for note in notes {
+        let without_ext = note.rel_path.with_extension("");
+        let path_key = without_ext.to_string_lossy().replace('\\', "/");
+        let stem = without_ext
+            .file_name()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        for key in [path_key, stem]
+            .into_iter()
+            .chain(note.aliases.iter().cloned())
+        {
+            lookup
+                .entry(key.to_lowercase())
+                .or_insert_with(|| note.doc.id.clone());
+        }
+    }
+    lookup
+}
+
+/// Read a frontmatter value written either as a YAML list or as one
+/// string separated by commas or spaces.
+fn string_list(value: &serde_yaml::Value) -> Vec<String> {
+    match value {
+        serde_yaml::Value::Sequence(items) => items
+            .iter()
+            .filter_map(|v| v.as_str().map(str::to_string))
+            .collect(),
+        serde_yaml::Value::String(s) => s
+            .split([',', ' '])
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Recursively gather `.md` files, skipping hidden directories.
+fn collect_notes(dir: &Path, out: &mut Vec<PathBuf>) -> Result<(), MkbError> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let hidden = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.starts_with('.'));
+        if hidden {
+            continue;
+        }
+        if path.is_dir() {
+            collect_notes(&path, out)?;
+        } else if path.extension().and_then(|e| e.to_str()) == Some("md") {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn fixture() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../../fixtures/obsidian")
+    }
+
+    fn by_source<'a>(report: &'a ImportReport, rel: &str) -> &'a Document {
+        let source = format!("obsidian:{rel}");
+        report
+            .documents
+            .iter()
+            .find(|d| {
+                d.provenance.as_ref().and_then(|p| p.source_uri.as_deref()) == Some(source.as_str())
+            })
+            .unwrap_or_else(|| panic!("no document imported from {rel}"))
+    }
+
+    fn targets(doc: &Document) -> Vec<&str> {
+        doc.links.iter().map(|l| l.target.as_str()).collect()
+    }
+
+    #[test]
+    fn import_fixture_vault() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+
+        let report = ObsidianImporter::new(fixture()).import(&vault).unwrap();
+
+        // .obsidian/ and .trash/ are skipped
+        assert_eq!(report.documents.len(), 4);
+        assert!(report.skipped.is_empty());
+        assert_eq!(report.links_resolved, 6);
+
+        let home = by_source(&report, "Home.md");
+        let beta = by_source(&report, "Beta.md");
+        let alpha = by_source(&report, "Projects/Alpha.md");
+        let daily = by_source(&report, "Daily/2025-03-06.md");
+
+        assert_eq!(targets(home), [alpha.id.as_str(), beta.id.as_str()]);
+        // [[Start]] resolves through Home's alias
+        assert_eq!(targets(beta), [home.id.as_str()]);
+        // [[Beta#Plan]] and [[beta]] collapse into one link; the image is ignored
+        assert_eq!(targets(alpha), [beta.id.as_str(), home.id.as_str()]);
+        assert_eq!(targets(daily), [alpha.id.as_str()]);
+        assert!(home.links.iter().all(|l| l.rel == WIKILINK_REL));
+
+        let mut dangling: Vec<(&str, &str)> = report
+            .dangling_links
+            .iter()
+            .map(|d| (d.source_id.as_str(), d.target.as_str()))
+            .collect();
+        dangling.sort_unstable();
+        assert_eq!(
+            dangling,
+            [
+                (beta.id.as_str(), "Ghost"),
+                (home.id.as_str(), "Missing Note")
+            ]
+        );
+
+        // Frontmatter mapping
+        assert_eq!(alpha.title, "Project Alpha");
+        assert_eq!(alpha.tags, ["project", "rust"]);
+        assert_eq!(
+            alpha.temporal.observed_at,
+            Utc.with_ymd_and_hms(2025, 3, 5, 7, 30, 0).unwrap()
+        );
+        assert_eq!(home.temporal.temporal_precision, TemporalPrecision::Day);
+        assert_eq!(home.fields["aliases"], serde_json::json!(["Start"]));
+        assert_eq!(beta.tags, ["research", "planning"]);
+        assert_eq!(
+            beta.temporal.observed_at,
+            Utc.with_ymd_and_hms(2025, 2, 15, 10, 0, 0).unwrap()
+        );
+        assert_eq!(beta.fields["status"], "draft");
+
+        // No date in frontmatter: the file's modification time
+        let mtime = fs::metadata(fixture().join("Daily/2025-03-06.md"))
+            .unwrap()
+            .modified()
+            .unwrap();
+        assert_eq!(daily.temporal.observed_at, DateTime::<Utc>::from(mtime));
+
+        // Documents and their links were written to the vault
+        let stored = vault.read("document", &alpha.id).unwrap();
+        assert_eq!(targets(&stored), targets(alpha));
+    }
+
+    #[test]
+    fn reimport_does_not_reuse_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+
+        let first = ObsidianImporter::new(fixture()).import(&vault).unwrap();
+        let second = ObsidianImporter::new(fixture())
+            .with_doc_type("document")
+            .import(&vault)
+            .unwrap();
+
+        let first_ids: HashSet<&str> = first.documents.iter().map(|d| d.id.as_str()).collect();
+        assert!(second
+            .documents
+            .iter()
+            .all(|d| !first_ids.contains(d.id.as_str())));
+    }
+}
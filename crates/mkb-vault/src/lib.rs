@@ -6,24 +6,259 @@
 //! lives as markdown files in the vault directory. The index layer
 //! is a derived cache that can be rebuilt from vault files.
 
+pub mod bulk;
+pub mod importers;
+pub mod lock;
 pub mod watcher;
 
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
-use chrono::Utc;
-use mkb_core::document::Document;
+use chrono::{DateTime, SecondsFormat, Utc};
+use mkb_core::document::{Document, ProvenanceRecord};
 use mkb_core::error::MkbError;
-use mkb_core::frontmatter::{parse_document, write_document};
-use mkb_core::temporal::TemporalGate;
+use mkb_core::frontmatter::{parse_document, write_document, FrontmatterVersion};
+use mkb_core::schema::{self, SchemaDefinition, SchemaRegistry};
+use mkb_core::temporal::{DecayModel, DecayProfile, TemporalGate};
 use mkb_core::view::SavedView;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::lock::VaultLock;
 
 /// Standard vault directory structure.
 const ARCHIVE_DIR: &str = ".archive";
+
+/// Appended to a file's path to name the scratch file [`atomic_write`] uses.
+const TMP_SUFFIX: &str = ".tmp";
+
+/// Names of the scratch files [`atomic_write`] leaves behind for the files it
+/// writes: documents and rejections (`.md`) and saved views (`.yaml`).
+const SCRATCH_SUFFIXES: [&str; 2] = [".md.tmp", ".yaml.tmp"];
+
+/// File name suffix of inferred schema drafts in `.mkb/schemas`.
+const INFERRED_SCHEMA_SUFFIX: &str = "-inferred.yaml";
+
+/// Contents of `.mkb/decay.yaml`.
+#[derive(Deserialize)]
+struct DecayConfig {
+    model: DecayModel,
+}
+
+/// Contents of `.mkb/ids.yaml`.
+#[derive(Deserialize)]
+struct IdConfig {
+    pattern: String,
+}
+
+/// How queries treat documents observed in the future, from
+/// `.mkb/query.yaml`. Missing keys take their defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuerySettings {
+    /// `CURRENT()` matches documents whose `observed_at` is in the future.
+    pub future_in_current: bool,
+    /// `FRESH()` matches documents whose `observed_at` is in the future.
+    pub future_in_fresh: bool,
+}
+
+impl Default for QuerySettings {
+    /// Documents observed in the future match `CURRENT()` but not `FRESH()`.
+    fn default() -> Self {
+        Self {
+            future_in_current: true,
+            future_in_fresh: false,
+        }
+    }
+}
+
+/// How [`Vault::append_body`] joins a new entry onto an existing body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppendStyle {
+    /// Inserted between the existing body and the new entry.
+    pub separator: String,
+    /// Start each entry with a `### <timestamp>` heading.
+    pub timestamp_header: bool,
+}
+
+impl Default for AppendStyle {
+    fn default() -> Self {
+        Self {
+            separator: "\n\n".to_string(),
+            timestamp_header: true,
+        }
+    }
+}
+
+/// Outcome of [`Vault::export_jsonl`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportStats {
+    pub exported: usize,
+    /// Files that could not be read or parsed; they are skipped.
+    pub failed: usize,
+    pub elapsed: Duration,
+}
+
+/// Outcome of [`Vault::migrate_format`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigrationStats {
+    /// Files rewritten in the target format.
+    pub migrated: usize,
+    /// Files not in the source format; left as they are.
+    pub skipped: usize,
+    /// Files that could not be read, parsed or written; left as they are.
+    pub failed: usize,
+    pub elapsed: Duration,
+}
+
+/// One document's findings in a [`BatchValidationReport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentValidation {
+    /// Document ID, or the vault-relative path for files that fail to parse.
+    pub id: String,
+    /// Document type; empty for files that fail to parse.
+    pub doc_type: String,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Outcome of [`Vault::validate_all_against_schemas`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchValidationReport {
+    /// Documents checked, including those whose type has no schema.
+    pub checked: usize,
+    /// Documents with at least one error or warning: unparseable files
+    /// first, then schema findings, each in path order.
+    pub documents: Vec<DocumentValidation>,
+}
+
+impl BatchValidationReport {
+    /// Documents that fail validation. With `strict`, warnings fail too.
+    #[must_use]
+    pub fn invalid(&self, strict: bool) -> usize {
+        self.documents
+            .iter()
+            .filter(|d| !d.errors.is_empty() || (strict && !d.warnings.is_empty()))
+            .count()
+    }
+
+    /// Documents that pass validation under the same rules as
+    /// [`invalid`](Self::invalid).
+    #[must_use]
+    pub fn valid(&self, strict: bool) -> usize {
+        self.checked - self.invalid(strict)
+    }
+
+    /// Documents with at least one warning.
+    #[must_use]
+    pub fn with_warnings(&self) -> usize {
+        self.documents
+            .iter()
+            .filter(|d| !d.warnings.is_empty())
+            .count()
+    }
+}
+
+/// How serious a [`VaultIssue`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IssueSeverity {
+    /// Worth a look; the vault still works.
+    Warning,
+    /// Data is unreadable or inconsistent.
+    Error,
+}
+
+impl std::fmt::Display for IssueSeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Warning => "warning",
+            Self::Error => "error",
+        })
+    }
+}
+
+/// Which check of [`Vault::validate`] raised a [`VaultIssue`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VaultIssueKind {
+    /// A document file cannot be read or parsed.
+    ParseError,
+    /// A document's temporal fields break the temporal gate's invariants.
+    InvalidTemporal,
+    /// The index holds a different number of documents than the vault.
+    IndexMismatch,
+    /// Two files hold documents with the same ID.
+    IdCollision,
+    /// `supersedes` or `superseded_by` names a document that doesn't exist.
+    BrokenSupersession,
+    /// The archive or rejection directory is missing or malformed.
+    DirectoryStructure,
+    /// A scratch file left by a write interrupted by a crash.
+    InterruptedWrite,
+}
+
+/// One finding of [`Vault::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaultIssue {
+    pub severity: IssueSeverity,
+    pub kind: VaultIssueKind,
+    pub description: String,
+    /// The file the issue is about, if it is about one.
+    pub path: Option<PathBuf>,
+}
+
+/// Outcome of [`Vault::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaultHealthReport {
+    /// No issue is an [`IssueSeverity::Error`].
+    pub healthy: bool,
+    /// Document files found.
+    pub documents: usize,
+    pub issues: Vec<VaultIssue>,
+}
+
+impl VaultHealthReport {
+    fn push(
+        &mut self,
+        severity: IssueSeverity,
+        kind: VaultIssueKind,
+        description: String,
+        path: Option<PathBuf>,
+    ) {
+        self.healthy &= severity < IssueSeverity::Error;
+        self.issues.push(VaultIssue {
+            severity,
+            kind,
+            description,
+            path,
+        });
+    }
+
+    /// Record a warning if `indexed`, the index's document count, differs
+    /// from the number of document files. The vault cannot read the index
+    /// itself, so callers that have one pass its count here.
+    pub fn check_index_count(&mut self, indexed: u64) {
+        if indexed != self.documents as u64 {
+            self.push(
+                IssueSeverity::Warning,
+                VaultIssueKind::IndexMismatch,
+                format!(
+                    "Index holds {indexed} documents but the vault has {} files; run `mkb reindex`",
+                    self.documents
+                ),
+                None,
+            );
+        }
+    }
+}
+
 /// The Vault manages file-system storage of knowledge documents.
 #[derive(Debug)]
 pub struct Vault {
     root: PathBuf,
+    append_style: AppendStyle,
 }
 
 impl Vault {
@@ -43,6 +278,7 @@ impl Vault {
         }
         Ok(Self {
             root: root.to_path_buf(),
+            append_style: AppendStyle::default(),
         })
     }
 
@@ -64,15 +300,50 @@ impl Vault {
 
         Ok(Self {
             root: root.to_path_buf(),
+            append_style: AppendStyle::default(),
         })
     }
 
+    /// Set how [`Vault::append_body`] formats appended entries.
+    #[must_use]
+    pub fn with_append_style(mut self, style: AppendStyle) -> Self {
+        self.append_style = style;
+        self
+    }
+
     /// Return the vault root directory.
     #[must_use]
     pub fn root(&self) -> &Path {
         &self.root
     }
 
+    /// Take the vault write lock (`.mkb/vault.lock`) without waiting.
+    ///
+    /// The operating system releases the lock if its owner exits, so a
+    /// crashed writer never blocks the vault. The lock is released when the
+    /// returned guard is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Vault`] if another live process holds the lock,
+    /// or [`MkbError::Io`] if the lock file cannot be written.
+    pub fn lock(&self) -> Result<VaultLock, MkbError> {
+        lock::acquire(&self.lock_path())
+    }
+
+    /// Take the vault write lock, polling until `timeout` elapses.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Vault::lock`], once the timeout has passed.
+    pub fn try_lock(&self, timeout: Duration) -> Result<VaultLock, MkbError> {
+        lock::acquire_with_timeout(&self.lock_path(), timeout)
+    }
+
+    fn lock_path(&self) -> PathBuf {
+        self.root.join(".mkb").join(lock::LOCK_FILE)
+    }
+
     /// Resolve the file path for a document based on its type and id.
     #[must_use]
     pub fn document_path(&self, doc_type: &str, id: &str) -> PathBuf {
@@ -86,20 +357,30 @@ impl Vault {
     ///
     /// # Errors
     ///
+    /// Returns [`MkbError::Validation`] if the document ID is unsafe or does
+    /// not match the pattern in `.mkb/ids.yaml` (see
+    /// [`load_id_pattern`](Self::load_id_pattern)).
     /// Returns [`MkbError::Temporal`] if temporal validation fails.
-    /// Returns [`MkbError::Vault`] if a document with the same ID already exists.
+    /// Returns [`MkbError::DuplicateDocument`] if a document with the same ID already exists.
     /// Returns [`MkbError::Io`] if file writing fails.
     pub fn create(&self, doc: &Document) -> Result<PathBuf, MkbError> {
+        Document::validate_id(&doc.id)?;
+        if let Some(pattern) = self.load_id_pattern()? {
+            if !pattern.is_match(&doc.id) {
+                return Err(MkbError::Validation(format!(
+                    "Document ID '{}' does not match required pattern '{pattern}'",
+                    doc.id
+                )));
+            }
+        }
+
         // Validate temporal fields (re-validate even though Document::new does it)
         TemporalGate::validate_fields(&doc.temporal)?;
 
         let path = self.document_path(&doc.doc_type, &doc.id);
 
         if path.exists() {
-            return Err(MkbError::Vault(format!(
-                "Document already exists: {}",
-                path.display()
-            )));
+            return Err(MkbError::DuplicateDocument { id: doc.id.clone() });
         }
 
         // Ensure the type directory exists
@@ -108,7 +389,7 @@ impl Vault {
         }
 
         let content = write_document(doc)?;
-        fs::write(&path, content)?;
+        atomic_write(&path, &content)?;
 
         Ok(path)
     }
@@ -117,17 +398,17 @@ impl Vault {
     ///
     /// # Errors
     ///
-    /// Returns [`MkbError::Vault`] if the document does not exist.
+    /// Returns [`MkbError::DocumentNotFound`] if the document does not exist.
     /// Returns [`MkbError::Io`] if file reading fails.
     /// Returns [`MkbError::Parse`] or [`MkbError::Serialization`] if parsing fails.
     pub fn read(&self, doc_type: &str, id: &str) -> Result<Document, MkbError> {
         let path = self.document_path(doc_type, id);
 
         if !path.exists() {
-            return Err(MkbError::Vault(format!(
-                "Document not found: {}",
-                path.display()
-            )));
+            return Err(MkbError::DocumentNotFound {
+                id: id.to_string(),
+                path,
+            });
         }
 
         let content = fs::read_to_string(&path)?;
@@ -140,16 +421,16 @@ impl Vault {
     ///
     /// # Errors
     ///
-    /// Returns [`MkbError::Vault`] if the document does not exist.
+    /// Returns [`MkbError::DocumentNotFound`] if the document does not exist.
     /// Returns [`MkbError::Temporal`] if temporal validation fails.
     pub fn update(&self, doc: &mut Document) -> Result<PathBuf, MkbError> {
         let path = self.document_path(&doc.doc_type, &doc.id);
 
         if !path.exists() {
-            return Err(MkbError::Vault(format!(
-                "Document not found for update: {}",
-                path.display()
-            )));
+            return Err(MkbError::DocumentNotFound {
+                id: doc.id.clone(),
+                path,
+            });
         }
 
         // Validate temporal fields
@@ -161,25 +442,92 @@ impl Vault {
         doc.modified_at = Utc::now();
 
         let content = write_document(doc)?;
-        fs::write(&path, content)?;
+        atomic_write(&path, &content)?;
 
         Ok(path)
     }
 
+    /// Append an entry to a document's body and bump `_modified_at`.
+    ///
+    /// The entry is joined to the existing body with the configured
+    /// [`AppendStyle`]. Returns the updated document; the caller is
+    /// responsible for reindexing it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::DocumentNotFound`] if the document does not exist.
+    /// Returns [`MkbError::Io`] if reading or writing the file fails.
+    pub fn append_body(&self, doc_type: &str, id: &str, text: &str) -> Result<Document, MkbError> {
+        self.append_entry(doc_type, id, text, None)
+    }
+
+    /// [`append_body`](Self::append_body), also recording the append in the
+    /// document's provenance.
+    ///
+    /// The `transformation_log` gains an `appended via <interface> at <time>`
+    /// entry, taking the interface from `appender.ingested_via`. A document
+    /// without provenance gets `appender` as its record first.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`append_body`](Self::append_body).
+    pub fn append_body_with_provenance(
+        &self,
+        doc_type: &str,
+        id: &str,
+        text: &str,
+        appender: &ProvenanceRecord,
+    ) -> Result<Document, MkbError> {
+        self.append_entry(doc_type, id, text, Some(appender))
+    }
+
+    fn append_entry(
+        &self,
+        doc_type: &str,
+        id: &str,
+        text: &str,
+        appender: Option<&ProvenanceRecord>,
+    ) -> Result<Document, MkbError> {
+        let mut doc = self.read(doc_type, id)?;
+        let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+
+        let mut entry = String::new();
+        if self.append_style.timestamp_header {
+            entry.push_str(&format!("### {now}\n\n"));
+        }
+        entry.push_str(text.trim_end());
+
+        let existing = doc.body.trim_end();
+        doc.body = if existing.is_empty() {
+            entry
+        } else {
+            format!("{existing}{}{entry}", self.append_style.separator)
+        };
+        if let Some(appender) = appender {
+            let record = doc.provenance.get_or_insert_with(|| appender.clone());
+            record
+                .transformation_log
+                .push(format!("appended via {} at {now}", appender.ingested_via));
+        }
+
+        self.update(&mut doc)?;
+        Ok(doc)
+    }
+
     /// Soft-delete a document by moving it to the archive directory.
     ///
     /// # Errors
     ///
-    /// Returns [`MkbError::Vault`] if the document does not exist.
+    /// Returns [`MkbError::DocumentNotFound`] if the document does not exist.
     /// Returns [`MkbError::Io`] if the move fails.
     pub fn delete(&self, doc_type: &str, id: &str) -> Result<PathBuf, MkbError> {
         let path = self.document_path(doc_type, id);
 
         if !path.exists() {
-            return Err(MkbError::Vault(format!(
-                "Document not found for deletion: {}",
-                path.display()
-            )));
+            return Err(MkbError::DocumentNotFound {
+                id: id.to_string(),
+                path,
+            });
         }
 
         let archive_type_dir = self
@@ -194,6 +542,57 @@ impl Vault {
         Ok(archive_path)
     }
 
+    /// Restore a soft-deleted document from the archive directory.
+    ///
+    /// Moves `.archive/<type_dir>/<id>.md` back to `<type_dir>/<id>.md`.
+    /// The caller is responsible for reindexing the restored document.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Vault`] if the document is not in the archive, or
+    /// if a document with the same ID has been created since it was deleted.
+    /// Returns [`MkbError::Io`] if the move fails.
+    pub fn restore_from_archive(&self, doc_type: &str, id: &str) -> Result<PathBuf, MkbError> {
+        let archive_path = self
+            .root
+            .join(ARCHIVE_DIR)
+            .join(type_to_directory(doc_type))
+            .join(format!("{id}.md"));
+
+        if !archive_path.exists() {
+            return Err(MkbError::Vault(format!(
+                "Document not in archive: {}",
+                archive_path.display()
+            )));
+        }
+
+        let path = self.document_path(doc_type, id);
+        if path.exists() {
+            return Err(MkbError::Vault(format!(
+                "Cannot restore over existing document: {}",
+                path.display()
+            )));
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(&archive_path, &path)?;
+
+        Ok(path)
+    }
+
+    /// Count soft-deleted documents in the archive directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Io`] if directory reading fails.
+    pub fn archived_count(&self) -> Result<usize, MkbError> {
+        let mut docs = Vec::new();
+        self.scan_directory(&self.root.join(ARCHIVE_DIR), &mut docs)?;
+        Ok(docs.len())
+    }
+
     /// List all document files in the vault (recursively scans type directories).
     ///
     /// # Errors
@@ -205,6 +604,375 @@ impl Vault {
         Ok(docs)
     }
 
+    /// Write every document as one JSON object per line to `output`.
+    ///
+    /// Each line is a [`document_record`], the same record the Python bridge
+    /// returns, with `effective_confidence` computed under the vault's decay
+    /// profile. Files are exported in path order; unreadable or unparseable
+    /// files are logged and counted in [`ExportStats::failed`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Io`] if scanning the vault or writing to `output`
+    /// fails, or [`MkbError::Serialization`] if `.mkb/decay.yaml` is invalid.
+    pub fn export_jsonl(
+        &self,
+        mut output: impl Write,
+        include_body: bool,
+    ) -> Result<ExportStats, MkbError> {
+        let start = Instant::now();
+        let mut files = self.list_documents()?;
+        files.sort();
+
+        let profile = self.load_decay_profile()?;
+        let now = Utc::now();
+        let (mut exported, mut failed) = (0, 0);
+        for path in &files {
+            let doc = match fs::read_to_string(path)
+                .map_err(MkbError::from)
+                .and_then(|content| parse_document(&content))
+            {
+                Ok(doc) => doc,
+                Err(e) => {
+                    tracing::warn!("Skipping {} in export: {e}", path.display());
+                    failed += 1;
+                    continue;
+                }
+            };
+            let line = serde_json::to_string(&document_record(&doc, &profile, now, include_body))
+                .map_err(|e| MkbError::Serialization(e.to_string()))?;
+            writeln!(output, "{line}")?;
+            exported += 1;
+        }
+        output.flush()?;
+
+        Ok(ExportStats {
+            exported,
+            failed,
+            elapsed: start.elapsed(),
+        })
+    }
+
+    /// Rewrite every document file written in the `from` layout in the `to`
+    /// layout.
+    ///
+    /// Only the layout changes: `_modified_at` and every other field are
+    /// kept. Each file is replaced atomically. Files in another layout are
+    /// skipped, so `from == to` rewrites nothing; unreadable or unparseable
+    /// files are logged and counted in [`MigrationStats::failed`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Io`] if the vault cannot be scanned.
+    pub fn migrate_format(
+        &self,
+        from: FrontmatterVersion,
+        to: FrontmatterVersion,
+    ) -> Result<MigrationStats, MkbError> {
+        let start = Instant::now();
+        let mut files = self.list_documents()?;
+        files.sort();
+
+        let (mut migrated, mut skipped, mut failed) = (0, 0, 0);
+        for path in &files {
+            let content = match fs::read_to_string(path) {
+                Ok(content) => content,
+                Err(e) => {
+                    tracing::warn!("Skipping {} in format migration: {e}", path.display());
+                    failed += 1;
+                    continue;
+                }
+            };
+            match FrontmatterVersion::detect(&content) {
+                Ok(version) if version == from && from != to => {}
+                Ok(_) => {
+                    skipped += 1;
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!("Skipping {} in format migration: {e}", path.display());
+                    failed += 1;
+                    continue;
+                }
+            }
+            let rewritten = parse_document(&content)
+                .and_then(|doc| doc.to_frontmatter_string(to))
+                .and_then(|output| atomic_write(path, &output));
+            match rewritten {
+                Ok(()) => migrated += 1,
+                Err(e) => {
+                    tracing::warn!("Skipping {} in format migration: {e}", path.display());
+                    failed += 1;
+                }
+            }
+        }
+
+        Ok(MigrationStats {
+            migrated,
+            skipped,
+            failed,
+            elapsed: start.elapsed(),
+        })
+    }
+
+    /// Validate every document's fields and links against the schema for its
+    /// type.
+    ///
+    /// Documents whose type has no schema in `schemas` pass unchecked. Link
+    /// target types are looked up among the vault's own documents. Files
+    /// that cannot be read or parsed are reported as invalid.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Io`] if the vault cannot be scanned.
+    pub fn validate_all_against_schemas(
+        &self,
+        schemas: &[SchemaDefinition],
+    ) -> Result<BatchValidationReport, MkbError> {
+        let mut files = self.list_documents()?;
+        files.sort();
+
+        let mut parsed = Vec::with_capacity(files.len());
+        let mut report = BatchValidationReport {
+            checked: files.len(),
+            documents: Vec::new(),
+        };
+        for path in &files {
+            match fs::read_to_string(path)
+                .map_err(MkbError::from)
+                .and_then(|content| parse_document(&content))
+            {
+                Ok(doc) => parsed.push(doc),
+                Err(e) => report.documents.push(DocumentValidation {
+                    id: path
+                        .strip_prefix(&self.root)
+                        .unwrap_or(path)
+                        .display()
+                        .to_string(),
+                    doc_type: String::new(),
+                    errors: vec![e.to_string()],
+                    warnings: Vec::new(),
+                }),
+            }
+        }
+
+        let types: HashMap<&str, &str> = parsed
+            .iter()
+            .map(|doc| (doc.id.as_str(), doc.doc_type.as_str()))
+            .collect();
+        for doc in &parsed {
+            let Some(schema_def) = schemas.iter().find(|s| s.name == doc.doc_type) else {
+                continue;
+            };
+            let fields = schema_def.validate(&doc.doc_type, &doc.fields);
+            let links =
+                schema_def.validate_links(&doc.links, |id| types.get(id).map(|t| (*t).to_string()));
+            let errors: Vec<String> = fields
+                .errors
+                .iter()
+                .chain(&links.errors)
+                .map(ToString::to_string)
+                .collect();
+            let warnings: Vec<String> = fields.warnings.into_iter().chain(links.warnings).collect();
+            if !errors.is_empty() || !warnings.is_empty() {
+                report.documents.push(DocumentValidation {
+                    id: doc.id.clone(),
+                    doc_type: doc.doc_type.clone(),
+                    errors,
+                    warnings,
+                });
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Check the vault for damage and inconsistencies.
+    ///
+    /// Every document file must parse and pass
+    /// [`TemporalGate::validate_fields`], no two files may share an ID, and
+    /// `supersedes`/`superseded_by` must name live or archived documents. The
+    /// archive and rejection directories must be directories, with archived
+    /// documents filed as `.archive/<type_dir>/<id>.md`. The index is not
+    /// checked; see [`VaultHealthReport::check_index_count`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Io`] if the vault cannot be scanned.
+    pub fn validate(&self) -> Result<VaultHealthReport, MkbError> {
+        use IssueSeverity::{Error, Warning};
+
+        let mut files = self.list_documents()?;
+        files.sort();
+        let mut report = VaultHealthReport {
+            healthy: true,
+            documents: files.len(),
+            issues: Vec::new(),
+        };
+        let relative = |path: &Path| {
+            path.strip_prefix(&self.root)
+                .unwrap_or(path)
+                .display()
+                .to_string()
+        };
+
+        let mut docs: Vec<(Document, &PathBuf)> = Vec::with_capacity(files.len());
+        for path in &files {
+            match fs::read_to_string(path)
+                .map_err(MkbError::from)
+                .and_then(|content| parse_document(&content))
+            {
+                Ok(doc) => docs.push((doc, path)),
+                Err(e) => report.push(
+                    Error,
+                    VaultIssueKind::ParseError,
+                    format!("{}: {e}", relative(path)),
+                    Some(path.clone()),
+                ),
+            }
+        }
+
+        let mut paths_by_id: HashMap<&str, Vec<&PathBuf>> = HashMap::new();
+        for (doc, path) in &docs {
+            paths_by_id.entry(doc.id.as_str()).or_default().push(path);
+        }
+        // Superseded documents are often archived, and archived files are
+        // named after their ID.
+        let mut archived = Vec::new();
+        self.scan_directory(&self.root.join(ARCHIVE_DIR), &mut archived)?;
+        let archived_ids: HashSet<String> = archived
+            .iter()
+            .filter_map(|p| p.file_stem().and_then(|s| s.to_str()).map(String::from))
+            .collect();
+        for (doc, path) in &docs {
+            if let Err(e) = TemporalGate::validate_fields(&doc.temporal) {
+                report.push(
+                    Error,
+                    VaultIssueKind::InvalidTemporal,
+                    format!("{}: {e}", doc.id),
+                    Some((*path).clone()),
+                );
+            }
+            for (field, target) in [
+                ("supersedes", &doc.supersedes),
+                ("superseded_by", &doc.superseded_by),
+            ] {
+                if let Some(target) = target.as_deref() {
+                    if !paths_by_id.contains_key(target) && !archived_ids.contains(target) {
+                        report.push(
+                            Warning,
+                            VaultIssueKind::BrokenSupersession,
+                            format!("{}: {field} '{target}' does not exist", doc.id),
+                            Some((*path).clone()),
+                        );
+                    }
+                }
+            }
+        }
+
+        let mut collisions: Vec<_> = paths_by_id.iter().filter(|(_, p)| p.len() > 1).collect();
+        collisions.sort();
+        for (id, paths) in collisions {
+            let listed: Vec<String> = paths.iter().map(|p| relative(p)).collect();
+            report.push(
+                Error,
+                VaultIssueKind::IdCollision,
+                format!("ID '{id}' is used by {}", listed.join(", ")),
+                None,
+            );
+        }
+
+        self.check_archive_structure(&mut report);
+        for path in self.interrupted_writes() {
+            report.push(
+                Warning,
+                VaultIssueKind::InterruptedWrite,
+                format!("{} is left over from an interrupted write", relative(&path)),
+                Some(path),
+            );
+        }
+        let rejected = self.rejected_dir();
+        if rejected.exists() && !rejected.is_dir() {
+            report.push(
+                Error,
+                VaultIssueKind::DirectoryStructure,
+                format!("{} is not a directory", relative(&rejected)),
+                Some(rejected),
+            );
+        }
+
+        Ok(report)
+    }
+
+    /// Add issues for an archive that is not `.archive/<type_dir>/<id>.md`.
+    fn check_archive_structure(&self, report: &mut VaultHealthReport) {
+        let archive = self.root.join(ARCHIVE_DIR);
+        if !archive.exists() {
+            return;
+        }
+        let mut misplaced = Vec::new();
+        match fs::read_dir(&archive) {
+            Ok(entries) => {
+                for entry in entries.filter_map(Result::ok) {
+                    let path = entry.path();
+                    if !path.is_dir() {
+                        misplaced.push(path);
+                        continue;
+                    }
+                    for file in fs::read_dir(&path)
+                        .into_iter()
+                        .flatten()
+                        .filter_map(Result::ok)
+                    {
+                        let file = file.path();
+                        if file.is_dir() || file.extension().and_then(|e| e.to_str()) != Some("md")
+                        {
+                            misplaced.push(file);
+                        }
+                    }
+                }
+            }
+            Err(_) => {
+                report.push(
+                    IssueSeverity::Error,
+                    VaultIssueKind::DirectoryStructure,
+                    format!("{ARCHIVE_DIR} is not a readable directory"),
+                    Some(archive),
+                );
+                return;
+            }
+        }
+        misplaced.sort();
+        for path in misplaced {
+            report.push(
+                IssueSeverity::Warning,
+                VaultIssueKind::DirectoryStructure,
+                format!(
+                    "{} is not an archived document",
+                    path.strip_prefix(&self.root).unwrap_or(&path).display()
+                ),
+                Some(path),
+            );
+        }
+    }
+
+    /// Move a damaged document file into the rejection log, recording
+    /// `error` as the reason. Returns the rejection log entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Io`] if the file cannot be read, logged or removed.
+    pub fn reject_file(&self, path: &Path, error: &str) -> Result<PathBuf, MkbError> {
+        let content = fs::read_to_string(path)?;
+        let filename = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown");
+        let rejected = self.write_rejection(filename, &content, error, &[])?;
+        fs::remove_file(path)?;
+        Ok(rejected)
+    }
+
     // === Saved Views ===
 
     /// Return the views directory path.
@@ -224,7 +992,7 @@ impl Vault {
         let path = dir.join(format!("{}.yaml", view.name));
         let yaml =
             serde_yaml::to_string(view).map_err(|e| MkbError::Serialization(e.to_string()))?;
-        fs::write(&path, yaml)?;
+        atomic_write(&path, &yaml)?;
         Ok(path)
     }
 
@@ -282,6 +1050,159 @@ impl Vault {
         Ok(())
     }
 
+    /// Return the user schema directory path.
+    #[must_use]
+    pub fn schemas_dir(&self) -> PathBuf {
+        self.root.join(".mkb").join("schemas")
+    }
+
+    /// Where `mkb schema infer` saves the schema inferred for `doc_type`.
+    ///
+    /// Inferred schemas are drafts: [`load_schemas`](Self::load_schemas)
+    /// skips them until they are renamed to `<type>.yaml`.
+    #[must_use]
+    pub fn inferred_schema_path(&self, doc_type: &str) -> PathBuf {
+        self.schemas_dir()
+            .join(format!("{doc_type}{INFERRED_SCHEMA_SUFFIX}"))
+    }
+
+    /// Load user schemas from `.mkb/schemas/*.yaml` and resolve their
+    /// `extends` chains.
+    ///
+    /// A schema may extend another user schema or a built-in one; a user
+    /// schema named like a built-in replaces it. Schemas are returned in
+    /// file name order. Inferred drafts (`*-inferred.yaml`) are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Serialization`] if a schema file fails to parse.
+    /// Returns [`MkbError::Schema`] if inheritance cannot be resolved.
+    pub fn load_schemas(&self) -> Result<Vec<SchemaDefinition>, MkbError> {
+        let dir = self.schemas_dir();
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut paths: Vec<PathBuf> = fs::read_dir(&dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<_, _>>()?;
+        paths.retain(|p| {
+            p.extension().and_then(|e| e.to_str()) == Some("yaml")
+                && !p.to_string_lossy().ends_with(INFERRED_SCHEMA_SUFFIX)
+        });
+        paths.sort();
+
+        let mut user = Vec::with_capacity(paths.len());
+        for path in &paths {
+            let content = fs::read_to_string(path)?;
+            let def: SchemaDefinition = serde_yaml::from_str(&content)
+                .map_err(|e| MkbError::Serialization(format!("{}: {e}", path.display())))?;
+            user.push(def);
+        }
+
+        let mut known = user.clone();
+        known.extend(
+            schema::built_in_schemas()
+                .into_iter()
+                .filter(|b| !user.iter().any(|u| u.name == b.name)),
+        );
+        user.iter()
+            .map(|def| def.resolve(&known).map_err(MkbError::from))
+            .collect()
+    }
+
+    /// Build a [`SchemaRegistry`] from the built-in schemas and this vault's
+    /// user schemas.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`load_schemas`](Self::load_schemas).
+    pub fn schema_registry(&self) -> Result<SchemaRegistry, MkbError> {
+        Ok(SchemaRegistry::new(self.load_schemas()?))
+    }
+
+    /// Return the decay configuration path.
+    #[must_use]
+    pub fn decay_config_path(&self) -> PathBuf {
+        self.root.join(".mkb").join("decay.yaml")
+    }
+
+    /// Load the decay profile configured under `model:` in
+    /// `.mkb/decay.yaml`, or the default profile if the file is absent.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Serialization`] if the file fails to parse.
+    pub fn load_decay_profile(&self) -> Result<DecayProfile, MkbError> {
+        let path = self.decay_config_path();
+        if !path.exists() {
+            return Ok(DecayProfile::default_profile());
+        }
+        let content = fs::read_to_string(&path)?;
+        let config: DecayConfig = serde_yaml::from_str(&content)
+            .map_err(|e| MkbError::Serialization(format!("{}: {e}", path.display())))?;
+        Ok(DecayProfile::with_model(config.model))
+    }
+
+    /// Return the document ID configuration path.
+    #[must_use]
+    pub fn id_config_path(&self) -> PathBuf {
+        self.root.join(".mkb").join("ids.yaml")
+    }
+
+    /// Load the regex under `pattern:` in `.mkb/ids.yaml` that new document
+    /// IDs must match on top of [`Document::validate_id`], or `None` if the
+    /// file is absent.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Serialization`] if the file fails to parse or the
+    /// pattern is not a valid regex.
+    pub fn load_id_pattern(&self) -> Result<Option<Regex>, MkbError> {
+        let path = self.id_config_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path)?;
+        let config: IdConfig = serde_yaml::from_str(&content)
+            .map_err(|e| MkbError::Serialization(format!("{}: {e}", path.display())))?;
+        Regex::new(&config.pattern)
+            .map(Some)
+            .map_err(|e| MkbError::Serialization(format!("{}: {e}", path.display())))
+    }
+
+    /// Return the query settings path.
+    #[must_use]
+    pub fn query_config_path(&self) -> PathBuf {
+        self.root.join(".mkb").join("query.yaml")
+    }
+
+    /// Load the settings in `.mkb/query.yaml`, or the defaults if the file
+    /// is absent.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Serialization`] if the file fails to parse.
+    pub fn load_query_settings(&self) -> Result<QuerySettings, MkbError> {
+        let path = self.query_config_path();
+        if !path.exists() {
+            return Ok(QuerySettings::default());
+        }
+        let content = fs::read_to_string(&path)?;
+        serde_yaml::from_str(&content)
+            .map_err(|e| MkbError::Serialization(format!("{}: {e}", path.display())))
+    }
+
+    /// Write `settings` to `.mkb/query.yaml`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Io`] if the file cannot be written.
+    pub fn save_query_settings(&self, settings: &QuerySettings) -> Result<(), MkbError> {
+        let yaml =
+            serde_yaml::to_string(settings).map_err(|e| MkbError::Serialization(e.to_string()))?;
+        atomic_write(&self.query_config_path(), &yaml)
+    }
+
     /// Return the rejected directory path.
     #[must_use]
     pub fn rejected_dir(&self) -> PathBuf {
@@ -323,7 +1244,7 @@ impl Vault {
         content.push_str("---\n\n");
         content.push_str(raw_content);
 
-        fs::write(&path, content)?;
+        atomic_write(&path, &content)?;
         Ok(path)
     }
 
@@ -344,6 +1265,42 @@ impl Vault {
         Ok(count)
     }
 
+    /// Scratch files left by an [`atomic_write`] interrupted by a crash, in
+    /// document directories, saved views and the rejection log.
+    ///
+    /// Only the exact names [`atomic_write`] uses count, so unrelated
+    /// `*.tmp` files in the vault are never reported.
+    #[must_use]
+    pub fn interrupted_writes(&self) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        collect_tmp_files(&self.root, true, &mut files);
+        collect_tmp_files(&self.views_dir(), false, &mut files);
+        collect_tmp_files(&self.rejected_dir(), false, &mut files);
+        files.sort();
+        files
+    }
+
+    /// Delete the files [`Vault::interrupted_writes`] finds and return the
+    /// ones removed.
+    ///
+    /// Callers must hold the vault lock, since a live writer owns its
+    /// scratch file until the rename. Failures are logged, not returned.
+    pub fn remove_interrupted_writes(&self) -> Vec<PathBuf> {
+        self.interrupted_writes()
+            .into_iter()
+            .filter(|path| match fs::remove_file(path) {
+                Ok(()) => {
+                    tracing::info!("Removed interrupted write {}", path.display());
+                    true
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to remove {}: {e}", path.display());
+                    false
+                }
+            })
+            .collect()
+    }
+
     fn scan_directory(&self, dir: &Path, docs: &mut Vec<PathBuf>) -> Result<(), MkbError> {
         if !dir.exists() {
             return Ok(());
@@ -371,6 +1328,97 @@ impl Vault {
     }
 }
 
+/// Serialize `doc` as the record shared by [`Vault::export_jsonl`] and the
+/// Python bridge's document dicts.
+///
+/// Alongside the frontmatter it carries values derived at `now`:
+/// `effective_confidence` under `profile`, `age_label`, and the provenance
+/// type and channel flattened to `provenance_type` and `ingested_via`.
+#[must_use]
+pub fn document_record(
+    doc: &Document,
+    profile: &DecayProfile,
+    now: DateTime<Utc>,
+    include_body: bool,
+) -> serde_json::Value {
+    let rfc3339 = |t: &DateTime<Utc>| t.to_rfc3339();
+    let mut record = serde_json::json!({
+        "id": doc.id,
+        "type": doc.doc_type,
+        "title": doc.title,
+        "observed_at": rfc3339(&doc.temporal.observed_at),
+        "valid_until": rfc3339(&doc.temporal.valid_until),
+        "temporal_precision": doc.temporal.temporal_precision,
+        "occurred_at": doc.temporal.occurred_at.as_ref().map(rfc3339),
+        "occurred_until": doc.temporal.occurred_until.as_ref().map(rfc3339),
+        "confidence": doc.confidence,
+        "effective_confidence": doc.temporal.effective_confidence(doc.confidence, profile, now),
+        "age_label": doc.temporal.age_in_precision_units(now),
+        "created_at": rfc3339(&doc.created_at),
+        "modified_at": rfc3339(&doc.modified_at),
+        "tags": doc.tags,
+        "source_hash": doc.source_hash,
+        "provenance_type": doc.provenance.as_ref().map(|p| p.source_type.as_str()),
+        "ingested_via": doc.provenance.as_ref().map(|p| p.ingested_via.as_str()),
+        "provenance": doc.provenance,
+        "supersedes": doc.supersedes,
+        "superseded_by": doc.superseded_by,
+        "superseded_at": doc.superseded_at.as_ref().map(rfc3339),
+        "fields": doc.fields,
+        "links": doc.links,
+    });
+    if include_body {
+        record["body"] = serde_json::Value::String(doc.body.clone());
+    }
+    record
+}
+
+/// Write `content` to `path` so readers see either the old file or the new
+/// one, never a partial write.
+///
+/// Writes `<path>.tmp`, fsyncs it and renames it over `path`. The rename is
+/// atomic on POSIX; on Windows it replaces the target in a single
+/// `MoveFileEx` call, which is as close as the platform gets.
+fn atomic_write(path: &Path, content: &str) -> Result<(), MkbError> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(TMP_SUFFIX);
+    let tmp = PathBuf::from(tmp);
+
+    let result = fs::File::create(&tmp)
+        .and_then(|mut file| {
+            file.write_all(content.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    Ok(result?)
+}
+
+/// Collect [`atomic_write`] scratch files under `dir`, descending into
+/// non-hidden subdirectories when `recursive` is set.
+fn collect_tmp_files(dir: &Path, recursive: bool, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if path.is_dir() {
+            if recursive && !name.starts_with('.') {
+                collect_tmp_files(&path, true, out);
+            }
+        } else if SCRATCH_SUFFIXES
+            .iter()
+            .any(|suffix| name.len() > suffix.len() && name.ends_with(suffix))
+        {
+            out.push(path);
+        }
+    }
+}
+
 /// Find the next available counter for a document ID to avoid collisions.
 ///
 /// Scans the type directory for existing files matching the pattern
@@ -450,6 +1498,7 @@ mod tests {
             valid_until: Some(utc(2025, 8, 10)),
             temporal_precision: Some(TemporalPrecision::Day),
             occurred_at: None,
+            occurred_until: None,
         };
         let profile = DecayProfile::default_profile();
         let mut doc = Document::new(
@@ -522,16 +1571,85 @@ mod tests {
     }
 
     #[test]
-    fn create_rejects_duplicate_document() {
+    fn create_rejects_duplicate_document() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+
+        let doc = make_doc("proj-alpha-001", "project", "Alpha");
+        vault.create(&doc).unwrap();
+
+        let result = vault.create(&doc);
+        assert!(matches!(
+            result,
+            Err(MkbError::DuplicateDocument { ref id }) if id == "proj-alpha-001"
+        ));
+    }
+
+    #[test]
+    fn missing_document_is_matched_by_variant() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        let expected = vault.document_path("project", "proj-missing-001");
+
+        match vault.read("project", "proj-missing-001") {
+            Err(MkbError::DocumentNotFound { id, path }) => {
+                assert_eq!(id, "proj-missing-001");
+                assert_eq!(path, expected);
+            }
+            other => panic!("expected DocumentNotFound, got {other:?}"),
+        }
+        assert!(matches!(
+            vault.delete("project", "proj-missing-001"),
+            Err(MkbError::DocumentNotFound { .. })
+        ));
+        let mut doc = make_doc("proj-missing-001", "project", "Missing");
+        assert!(matches!(
+            vault.update(&mut doc),
+            Err(MkbError::DocumentNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn create_rejects_path_escaping_ids() {
         let dir = tempfile::tempdir().unwrap();
         let vault = Vault::init(dir.path()).unwrap();
 
-        let doc = make_doc("proj-alpha-001", "project", "Alpha");
-        vault.create(&doc).unwrap();
+        for id in ["../escape-001", "proj/alpha-001"] {
+            let err = vault
+                .create(&make_doc(id, "project", "Escape"))
+                .unwrap_err();
+            assert!(matches!(err, MkbError::Validation(_)), "{id}: {err}");
+        }
+        assert!(!dir.path().join("escape-001.md").exists());
+        assert!(vault
+            .create(&make_doc("proj-alpha-001", "project", "Alpha"))
+            .is_ok());
+    }
 
-        let result = vault.create(&doc);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("already exists"));
+    #[test]
+    fn create_enforces_configured_id_pattern() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        assert!(vault.load_id_pattern().unwrap().is_none());
+        fs::write(
+            vault.id_config_path(),
+            "pattern: '^[a-z]+-[a-z0-9-]+-\\d{3}$'\n",
+        )
+        .unwrap();
+
+        let err = vault
+            .create(&make_doc("Alpha_1", "project", "Alpha"))
+            .unwrap_err();
+        assert!(err.to_string().contains("does not match required pattern"));
+        assert!(vault
+            .create(&make_doc("proj-alpha-001", "project", "Alpha"))
+            .is_ok());
+
+        fs::write(vault.id_config_path(), "pattern: '^[a-z'\n").unwrap();
+        let err = vault
+            .create(&make_doc("proj-beta-001", "project", "Beta"))
+            .unwrap_err();
+        assert!(matches!(err, MkbError::Serialization(_)), "{err}");
     }
 
     #[test]
@@ -573,6 +1691,107 @@ mod tests {
         assert!(reloaded.modified_at >= original_created);
     }
 
+    #[test]
+    fn append_body_keeps_existing_content_and_adds_entry_after_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+
+        let doc = make_doc("proj-alpha-001", "project", "Alpha");
+        vault.create(&doc).unwrap();
+
+        let appended = vault
+            .append_body("project", "proj-alpha-001", "First note.")
+            .unwrap();
+        assert!(appended.modified_at >= doc.modified_at);
+
+        let reloaded = vault.read("project", "proj-alpha-001").unwrap();
+        let original = doc.body.trim_end();
+        assert!(reloaded.body.starts_with(original));
+        let entry = &reloaded.body[original.len()..];
+        assert!(entry.starts_with("\n\n### "), "entry: {entry:?}");
+        assert!(entry.trim_end().ends_with("First note."));
+        assert_eq!(reloaded.created_at, doc.created_at);
+    }
+
+    #[test]
+    fn append_body_uses_configured_style() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path())
+            .unwrap()
+            .with_append_style(AppendStyle {
+                separator: "\n---\n".to_string(),
+                timestamp_header: false,
+            });
+
+        let mut doc = make_doc("proj-alpha-001", "project", "Alpha");
+        doc.body = "Line one".to_string();
+        vault.create(&doc).unwrap();
+
+        vault
+            .append_body("project", "proj-alpha-001", "Line two")
+            .unwrap();
+        vault
+            .append_body("project", "proj-alpha-001", "Line three")
+            .unwrap();
+
+        let reloaded = vault.read("project", "proj-alpha-001").unwrap();
+        assert_eq!(
+            reloaded.body.trim_end(),
+            "Line one\n---\nLine two\n---\nLine three"
+        );
+    }
+
+    #[test]
+    fn append_body_with_provenance_logs_each_append() {
+        use mkb_core::document::ProvenanceType;
+
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        let mut doc = make_doc("proj-alpha-001", "project", "Alpha");
+        doc.provenance = Some(ProvenanceRecord::new(ProvenanceType::Manual, "cli"));
+        vault.create(&doc).unwrap();
+        vault
+            .create(&make_doc("proj-beta-001", "project", "Beta"))
+            .unwrap();
+
+        let appender = ProvenanceRecord::new(ProvenanceType::LlmGenerated, "mcp");
+        for _ in 0..2 {
+            vault
+                .append_body_with_provenance("project", "proj-alpha-001", "note", &appender)
+                .unwrap();
+        }
+        let record = vault
+            .read("project", "proj-alpha-001")
+            .unwrap()
+            .provenance
+            .unwrap();
+        // The document's origin is kept; only the log grows
+        assert_eq!(record.source_type, ProvenanceType::Manual);
+        assert_eq!(record.transformation_log.len(), 2);
+        assert!(record.transformation_log[1].starts_with("appended via mcp at "));
+
+        vault
+            .append_body_with_provenance("project", "proj-beta-001", "note", &appender)
+            .unwrap();
+        let record = vault
+            .read("project", "proj-beta-001")
+            .unwrap()
+            .provenance
+            .unwrap();
+        assert_eq!(record.source_type, ProvenanceType::LlmGenerated);
+        assert_eq!(record.transformation_log.len(), 1);
+    }
+
+    #[test]
+    fn append_body_missing_document_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        assert!(matches!(
+            vault.append_body("project", "proj-missing-001", "note"),
+            Err(MkbError::DocumentNotFound { .. })
+        ));
+    }
+
     #[test]
     fn delete_soft_moves_to_archive() {
         let dir = tempfile::tempdir().unwrap();
@@ -591,6 +1810,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn restore_from_archive_undoes_delete() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+
+        vault
+            .create(&make_doc("proj-alpha-001", "project", "Alpha"))
+            .unwrap();
+        vault.delete("project", "proj-alpha-001").unwrap();
+        assert_eq!(vault.archived_count().unwrap(), 1);
+
+        let path = vault
+            .restore_from_archive("project", "proj-alpha-001")
+            .unwrap();
+        assert_eq!(path, vault.document_path("project", "proj-alpha-001"));
+        assert_eq!(
+            vault.read("project", "proj-alpha-001").unwrap().title,
+            "Alpha"
+        );
+        assert_eq!(vault.archived_count().unwrap(), 0);
+    }
+
+    #[test]
+    fn restore_from_archive_rejects_missing_and_colliding_documents() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+
+        vault
+            .create(&make_doc("proj-alpha-001", "project", "Alpha"))
+            .unwrap();
+        let err = vault
+            .restore_from_archive("project", "proj-alpha-001")
+            .unwrap_err();
+        assert!(matches!(err, MkbError::Vault(ref msg) if msg.contains("not in archive")));
+
+        vault.delete("project", "proj-alpha-001").unwrap();
+        vault
+            .create(&make_doc("proj-alpha-001", "project", "Alpha v2"))
+            .unwrap();
+        assert!(matches!(
+            vault.restore_from_archive("project", "proj-alpha-001"),
+            Err(MkbError::Vault(_))
+        ));
+        assert_eq!(vault.archived_count().unwrap(), 1);
+    }
+
     #[test]
     fn list_documents_finds_all_markdown_files() {
         let dir = tempfile::tempdir().unwrap();
@@ -729,6 +1994,355 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn query_settings_default_and_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        assert_eq!(
+            vault.load_query_settings().unwrap(),
+            QuerySettings::default()
+        );
+
+        fs::write(vault.query_config_path(), "future_in_fresh: true\n").unwrap();
+        let mut settings = vault.load_query_settings().unwrap();
+        assert!(settings.future_in_current);
+        assert!(settings.future_in_fresh);
+
+        settings.future_in_current = false;
+        vault.save_query_settings(&settings).unwrap();
+        assert_eq!(vault.load_query_settings().unwrap(), settings);
+
+        fs::write(vault.query_config_path(), "future_in_fresh: maybe\n").unwrap();
+        assert!(matches!(
+            vault.load_query_settings(),
+            Err(MkbError::Serialization(_))
+        ));
+    }
+
+    #[test]
+    fn load_decay_profile_reads_model_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        assert_eq!(
+            vault.load_decay_profile().unwrap(),
+            DecayProfile::default_profile()
+        );
+
+        fs::write(
+            vault.decay_config_path(),
+            "model:\n  kind: linear\n  half_life_days: 30\n",
+        )
+        .unwrap();
+        assert_eq!(
+            vault.load_decay_profile().unwrap().model,
+            DecayModel::Linear {
+                half_life: chrono::Duration::days(30)
+            }
+        );
+
+        fs::write(vault.decay_config_path(), "model:\n  kind: sigmoid\n").unwrap();
+        assert!(matches!(
+            vault.load_decay_profile(),
+            Err(MkbError::Serialization(_))
+        ));
+    }
+
+    #[test]
+    fn load_schemas_resolves_inheritance() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        assert!(vault.load_schemas().unwrap().is_empty());
+
+        fs::create_dir_all(vault.schemas_dir()).unwrap();
+        fs::write(
+            vault.schemas_dir().join("epic.yaml"),
+            "name: epic\nextends: project\nfields:\n  points:\n    type: integer\n",
+        )
+        .unwrap();
+        fs::write(
+            vault.schemas_dir().join("spike.yaml"),
+            "name: spike\nextends: epic\nfields:\n  status:\n    type: string\n",
+        )
+        .unwrap();
+
+        let schemas = vault.load_schemas().unwrap();
+        let names: Vec<&str> = schemas.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["epic", "spike"]);
+        let spike = &schemas[1];
+        assert!(spike.fields.contains_key("points"));
+        assert!(spike.fields.contains_key("owner"));
+        assert_eq!(
+            spike.fields["status"].field_type,
+            mkb_core::schema::FieldType::String
+        );
+
+        fs::write(
+            vault.schemas_dir().join("orphan.yaml"),
+            "name: orphan\nextends: missing\n",
+        )
+        .unwrap();
+        let err = vault.load_schemas().unwrap_err();
+        assert!(matches!(
+            err,
+            MkbError::Schema(mkb_core::error::SchemaError::MissingSchema { .. })
+        ));
+    }
+
+    #[test]
+    fn load_schemas_skips_inferred_drafts() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        fs::create_dir_all(vault.schemas_dir()).unwrap();
+        let draft = vault.inferred_schema_path("project");
+        assert_eq!(draft, vault.schemas_dir().join("project-inferred.yaml"));
+        fs::write(&draft, "name: project\nfields: {}\n").unwrap();
+
+        assert!(vault.load_schemas().unwrap().is_empty());
+    }
+
+    #[test]
+    fn schema_registry_includes_user_schemas() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        fs::create_dir_all(vault.schemas_dir()).unwrap();
+        fs::write(
+            vault.schemas_dir().join("project.yaml"),
+            "name: project\nversion: 3\nfields:\n  status:\n    type: string\n",
+        )
+        .unwrap();
+
+        let registry = vault.schema_registry().unwrap();
+        assert_eq!(registry.version("project"), Some(3));
+        assert_eq!(registry.version("meeting"), Some(1));
+    }
+
+    /// Rebuild a document from a [`document_record`] line.
+    fn document_from_record(line: &str) -> Document {
+        let mut record: serde_json::Value = serde_json::from_str(line).unwrap();
+        let obj = record.as_object_mut().unwrap();
+        let body = obj.remove("body").unwrap_or_default();
+        for key in ["created_at", "modified_at"] {
+            let value = obj.remove(key).unwrap();
+            obj.insert(format!("_{key}"), value);
+        }
+        let mut doc: Document = serde_json::from_value(record).unwrap();
+        doc.body = body.as_str().unwrap().to_string();
+        doc
+    }
+
+    #[test]
+    fn export_jsonl_roundtrips_documents() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+
+        let mut alpha = make_doc("proj-alpha-001", "project", "Alpha");
+        alpha.body = "Line one.\n\n\"Quoted\" line two.\n".to_string();
+        alpha.tags = vec!["infra".to_string(), "q1".to_string()];
+        alpha.provenance = Some(mkb_core::document::ProvenanceRecord {
+            source_uri: Some("notes.md".to_string()),
+            ..mkb_core::document::ProvenanceRecord::new(
+                mkb_core::document::ProvenanceType::Import,
+                "cli",
+            )
+        });
+        alpha
+            .fields
+            .insert("status".to_string(), serde_json::json!("active"));
+        alpha.links.push(mkb_core::link::Link {
+            rel: "owner".to_string(),
+            target: "pers-jane-001".to_string(),
+            observed_at: utc(2025, 2, 10),
+            metadata: None,
+        });
+        let beta = make_doc("meet-sync-001", "meeting", "Sync");
+        vault.create(&alpha).unwrap();
+        vault.create(&beta).unwrap();
+        fs::write(
+            dir.path().join("projects").join("broken.md"),
+            "no frontmatter",
+        )
+        .unwrap();
+
+        let mut out = Vec::new();
+        let stats = vault.export_jsonl(&mut out, true).unwrap();
+        assert_eq!((stats.exported, stats.failed), (2, 1));
+
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        for line in lines {
+            let restored = document_from_record(line);
+            let original = vault.read(&restored.doc_type, &restored.id).unwrap();
+            assert_eq!(
+                write_document(&restored).unwrap(),
+                write_document(&original).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn migrate_format_tags_unversioned_files_and_keeps_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+
+        let mut doc = make_doc("proj-alpha-001", "project", "Alpha Project");
+        doc.tags = vec!["rust".to_string(), "infra".to_string()];
+        doc.provenance = Some(mkb_core::document::ProvenanceRecord {
+            source_uri: Some("standup".to_string()),
+            ..mkb_core::document::ProvenanceRecord::new(
+                mkb_core::document::ProvenanceType::Manual,
+                "cli",
+            )
+        });
+        doc.fields
+            .insert("status".to_string(), serde_json::json!("active"));
+        doc.links.push(mkb_core::link::Link {
+            rel: "owner".to_string(),
+            target: "people/jane-smith".to_string(),
+            observed_at: utc(2025, 2, 10),
+            metadata: None,
+        });
+        let path = vault.create(&doc).unwrap();
+        let legacy = doc
+            .to_frontmatter_string(FrontmatterVersion::Unversioned)
+            .unwrap();
+        fs::write(&path, &legacy).unwrap();
+        let current = vault
+            .create(&make_doc("proj-beta-001", "project", "Beta"))
+            .unwrap();
+
+        let stats = vault
+            .migrate_format(FrontmatterVersion::Unversioned, FrontmatterVersion::V1)
+            .unwrap();
+        assert_eq!((stats.migrated, stats.skipped, stats.failed), (1, 1, 0));
+
+        let migrated = fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            FrontmatterVersion::detect(&migrated).unwrap(),
+            FrontmatterVersion::V1
+        );
+        assert_eq!(migrated, doc.to_frontmatter_v1().unwrap());
+        assert_eq!(
+            FrontmatterVersion::detect(&fs::read_to_string(current).unwrap()).unwrap(),
+            FrontmatterVersion::V1
+        );
+
+        // And back again, byte for byte
+        let stats = vault
+            .migrate_format(FrontmatterVersion::V1, FrontmatterVersion::Unversioned)
+            .unwrap();
+        assert_eq!(stats.migrated, 2);
+        assert_eq!(fs::read_to_string(&path).unwrap(), legacy);
+    }
+
+    #[test]
+    fn export_jsonl_can_omit_body() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        let mut doc = make_doc("proj-alpha-001", "project", "Alpha");
+        doc.body = "secret".to_string();
+        vault.create(&doc).unwrap();
+
+        let mut out = Vec::new();
+        vault.export_jsonl(&mut out, false).unwrap();
+        let record: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(record["id"], "proj-alpha-001");
+        assert_eq!(record["type"], "project");
+        assert!(record.get("body").is_none());
+        assert!(record["effective_confidence"].is_number());
+    }
+
+    #[test]
+    fn export_jsonl_matches_document_record_under_vault_decay_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        let mut doc = make_doc("proj-alpha-001", "project", "Alpha");
+        doc.provenance = Some(mkb_core::document::ProvenanceRecord::new(
+            mkb_core::document::ProvenanceType::Manual,
+            "cli",
+        ));
+        vault.create(&doc).unwrap();
+        // Linear decay reaches zero after two half-lives; the default
+        // profile still leaves some confidence at this age
+        fs::write(
+            vault.decay_config_path(),
+            "model:\n  kind: linear\n  half_life_days: 30\n",
+        )
+        .unwrap();
+
+        let mut out = Vec::new();
+        vault.export_jsonl(&mut out, true).unwrap();
+        let record: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(record["effective_confidence"], 0.0);
+        assert_eq!(record["provenance_type"], "manual");
+        assert_eq!(record["ingested_via"], "cli");
+        assert!(record["age_label"].as_str().unwrap().ends_with("ago"));
+
+        let doc = vault.read("project", "proj-alpha-001").unwrap();
+        let profile = vault.load_decay_profile().unwrap();
+        let mut expected = document_record(&doc, &profile, Utc::now(), true);
+        // Only the derived values can move between the two calls
+        expected["age_label"] = record["age_label"].clone();
+        assert_eq!(record, expected);
+        assert!(
+            document_record(&doc, &DecayProfile::default_profile(), Utc::now(), false)
+                ["effective_confidence"]
+                .as_f64()
+                .unwrap()
+                > 0.0
+        );
+    }
+
+    #[test]
+    fn validate_all_against_schemas_reports_each_violation() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+
+        let with_status = |id: &str, status: &str| {
+            let mut doc = make_doc(id, "project", id);
+            doc.fields
+                .insert("status".to_string(), serde_json::json!(status));
+            doc
+        };
+        let mut dangling = with_status("proj-gamma-001", "active");
+        dangling.links.push(mkb_core::link::Link {
+            rel: "owner".to_string(),
+            target: "pers-missing-001".to_string(),
+            observed_at: utc(2025, 2, 10),
+            metadata: None,
+        });
+        for doc in [
+            with_status("proj-alpha-001", "active"),
+            with_status("proj-beta-001", "bogus"),
+            dangling,
+            make_doc("note-free-001", "note", "Free"),
+        ] {
+            vault.create(&doc).unwrap();
+        }
+        fs::write(
+            dir.path().join("projects").join("broken.md"),
+            "no frontmatter",
+        )
+        .unwrap();
+
+        let report = vault
+            .validate_all_against_schemas(&schema::built_in_schemas())
+            .unwrap();
+        assert_eq!(report.checked, 5);
+        assert_eq!((report.valid(false), report.invalid(false)), (3, 2));
+        assert_eq!((report.valid(true), report.invalid(true)), (2, 3));
+        assert_eq!(report.with_warnings(), 1);
+
+        let ids: Vec<&str> = report.documents.iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            ["projects/broken.md", "proj-beta-001", "proj-gamma-001"]
+        );
+        assert!(report.documents[1].errors[0].contains("bogus"));
+        assert!(report.documents[2].errors.is_empty());
+        assert_eq!(report.documents[2].warnings.len(), 1);
+    }
+
     // === T-110.5 tests: rejection log ===
 
     #[test]
@@ -798,6 +2412,67 @@ mod tests {
         assert_eq!(vault.rejection_count().unwrap(), 2);
     }
 
+    #[test]
+    fn atomic_write_leaves_no_tmp_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        let mut doc = make_doc("proj-alpha-001", "project", "Alpha");
+        let path = vault.create(&doc).unwrap();
+        vault.update(&mut doc).unwrap();
+        vault
+            .save_view(&SavedView {
+                name: "active".to_string(),
+                description: None,
+                query: "SELECT * FROM project".to_string(),
+                created_at: "2025-02-10T00:00:00Z".to_string(),
+            })
+            .unwrap();
+
+        let mut leftovers = Vec::new();
+        collect_tmp_files(dir.path(), true, &mut leftovers);
+        collect_tmp_files(&vault.views_dir(), false, &mut leftovers);
+        assert!(leftovers.is_empty(), "{leftovers:?}");
+        assert!(fs::read_to_string(path).unwrap().contains("Alpha"));
+    }
+
+    #[test]
+    fn interrupted_writes_are_reported_and_removed_on_request() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        let path = vault
+            .create(&make_doc("proj-alpha-001", "project", "Alpha"))
+            .unwrap();
+
+        // Simulate a crash between writing the scratch file and the rename
+        let torn_doc = dir.path().join("projects/proj-alpha-001.md.tmp");
+        let torn_view = vault.views_dir().join("active.yaml.tmp");
+        fs::write(&torn_doc, "---\nid: proj-al").unwrap();
+        fs::write(&torn_view, "name: act").unwrap();
+        // Files the user keeps in the vault are not ours to delete
+        let own_tmp = dir.path().join("projects/draft.tmp");
+        fs::write(&own_tmp, "notes").unwrap();
+
+        let vault = Vault::open(dir.path()).unwrap();
+        assert!(torn_doc.exists(), "open must not clean up");
+
+        let report = vault.validate().unwrap();
+        let flagged: Vec<_> = report
+            .issues
+            .iter()
+            .filter(|i| i.kind == VaultIssueKind::InterruptedWrite)
+            .filter_map(|i| i.path.clone())
+            .collect();
+        assert_eq!(flagged, vec![torn_view.clone(), torn_doc.clone()]);
+        assert!(report.healthy);
+
+        let _lock = vault.lock().unwrap();
+        assert_eq!(vault.remove_interrupted_writes().len(), 2);
+        assert!(!torn_doc.exists());
+        assert!(!torn_view.exists());
+        assert!(own_tmp.exists());
+        assert!(path.exists());
+    }
+
     // === T-110.6 tests: file path resolution ===
 
     #[test]
@@ -824,4 +2499,106 @@ mod tests {
         let counter = next_counter(dir.path(), "project", "alpha-project");
         assert_eq!(counter, 3);
     }
+
+    // === Vault health ===
+
+    #[test]
+    fn validate_reports_healthy_vault() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        let mut old = make_doc("proj-old-001", "project", "Old");
+        old.superseded_by = Some("proj-new-001".to_string());
+        let mut new = make_doc("proj-new-001", "project", "New");
+        new.supersedes = Some("proj-old-001".to_string());
+        vault.create(&old).unwrap();
+        vault.create(&new).unwrap();
+        vault.delete("project", "proj-old-001").unwrap();
+
+        let mut report = vault.validate().unwrap();
+        assert!(report.healthy, "{:?}", report.issues);
+        assert!(report.issues.is_empty());
+        assert_eq!(report.documents, 1);
+
+        report.check_index_count(1);
+        assert!(report.issues.is_empty());
+        report.check_index_count(3);
+        assert!(report.healthy);
+        assert_eq!(report.issues[0].kind, VaultIssueKind::IndexMismatch);
+        assert_eq!(report.issues[0].severity, IssueSeverity::Warning);
+    }
+
+    #[test]
+    fn validate_flags_broken_documents() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        let mut doc = make_doc("proj-alpha-001", "project", "Alpha");
+        doc.supersedes = Some("proj-missing-001".to_string());
+        vault.create(&doc).unwrap();
+
+        let mut twin = make_doc("proj-alpha-001", "project", "Alpha Twin");
+        twin.body = "copy".to_string();
+        let content = mkb_core::frontmatter::write_document(&twin).unwrap();
+        fs::write(dir.path().join("projects/alpha-copy.md"), content).unwrap();
+        let garbage = dir.path().join("projects/garbage.md");
+        fs::write(&garbage, "no frontmatter here").unwrap();
+
+        let mut bad_temporal = make_doc("proj-late-001", "project", "Late");
+        bad_temporal.temporal.valid_until = utc(2020, 1, 1);
+        let content = mkb_core::frontmatter::write_document(&bad_temporal).unwrap();
+        fs::write(dir.path().join("projects/late.md"), content).unwrap();
+
+        let report = vault.validate().unwrap();
+        assert!(!report.healthy);
+        assert_eq!(report.documents, 4);
+        let kinds: Vec<_> = report.issues.iter().map(|i| (i.kind, i.severity)).collect();
+        assert!(kinds.contains(&(VaultIssueKind::ParseError, IssueSeverity::Error)));
+        assert!(kinds.contains(&(VaultIssueKind::InvalidTemporal, IssueSeverity::Error)));
+        assert!(kinds.contains(&(VaultIssueKind::IdCollision, IssueSeverity::Error)));
+        assert!(kinds.contains(&(VaultIssueKind::BrokenSupersession, IssueSeverity::Warning)));
+        let parse = report
+            .issues
+            .iter()
+            .find(|i| i.kind == VaultIssueKind::ParseError)
+            .unwrap();
+        assert_eq!(parse.path.as_deref(), Some(garbage.as_path()));
+    }
+
+    #[test]
+    fn validate_checks_archive_and_rejection_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        fs::create_dir_all(dir.path().join(ARCHIVE_DIR)).unwrap();
+        fs::write(dir.path().join(ARCHIVE_DIR).join("stray.md"), "x").unwrap();
+        let rejected = vault.rejected_dir();
+        fs::create_dir_all(rejected.parent().unwrap()).unwrap();
+        let _ = fs::remove_dir_all(&rejected);
+        fs::write(&rejected, "not a directory").unwrap();
+
+        let report = vault.validate().unwrap();
+        assert!(!report.healthy);
+        let structure: Vec<_> = report
+            .issues
+            .iter()
+            .filter(|i| i.kind == VaultIssueKind::DirectoryStructure)
+            .map(|i| i.severity)
+            .collect();
+        assert_eq!(
+            structure,
+            vec![IssueSeverity::Warning, IssueSeverity::Error]
+        );
+    }
+
+    #[test]
+    fn reject_file_moves_file_to_rejection_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        let garbage = dir.path().join("projects/garbage.md");
+        fs::create_dir_all(garbage.parent().unwrap()).unwrap();
+        fs::write(&garbage, "no frontmatter here").unwrap();
+
+        let logged = vault.reject_file(&garbage, "unparseable").unwrap();
+        assert!(!garbage.exists());
+        assert!(logged.starts_with(vault.rejected_dir()));
+        assert!(vault.validate().unwrap().healthy);
+    }
 }
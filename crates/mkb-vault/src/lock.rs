@@ -0,0 +1,239 @@
+//! Cooperative write lock for a vault.
+//!
+//! The lock is an OS advisory lock (`flock` on Unix, `LockFileEx` on
+//! Windows) on `.mkb/vault.lock`. The operating system releases it when the
+//! owner exits, so a crashed writer never leaves a stale lock behind. While
+//! held, the file records the owner's PID and acquisition time for error
+//! messages. Writers that respect it are serialized; nothing stops a
+//! process that ignores it from writing anyway.
+//!
+//! The file itself is never deleted: a process that opened it just before
+//! a delete could lock the orphaned file while another locks a new one.
+
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{SecondsFormat, Utc};
+use fs2::FileExt;
+use mkb_core::error::MkbError;
+
+/// Lock file name inside the `.mkb/` directory.
+pub const LOCK_FILE: &str = "vault.lock";
+
+/// Delay between attempts in [`crate::Vault::try_lock`].
+const POLL_INTERVAL: Duration = Duration::from_millis(25);
+
+/// RAII guard for the vault write lock. Dropping it releases the lock.
+#[derive(Debug)]
+pub struct VaultLock {
+    path: PathBuf,
+    file: File,
+    released: bool,
+}
+
+impl VaultLock {
+    /// Path of the lock file this guard owns.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Release the lock, reporting any error clearing the owner record or
+    /// unlocking the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MkbError::Io`] if the lock file cannot be cleared or
+    /// unlocked.
+    pub fn unlock(mut self) -> Result<(), MkbError> {
+        self.released = true;
+        self.release()
+    }
+
+    fn release(&self) -> Result<(), MkbError> {
+        let cleared = self.file.set_len(0);
+        FileExt::unlock(&self.file)?;
+        Ok(cleared?)
+    }
+}
+
+impl Drop for VaultLock {
+    fn drop(&mut self) {
+        if !self.released {
+            if let Err(e) = self.release() {
+                tracing::warn!("Failed to release vault lock {}: {e}", self.path.display());
+            }
+        }
+    }
+}
+
+/// Try once to take the lock at `path`.
+pub(crate) fn acquire(path: &Path) -> Result<VaultLock, MkbError> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)?;
+    if let Err(e) = file.try_lock_exclusive() {
+        if e.kind() != fs2::lock_contended_error().kind() {
+            return Err(e.into());
+        }
+        // The owner may not have written its PID yet.
+        return Err(MkbError::Vault(match read_owner(&mut file) {
+            Some(pid) => format!("vault is locked by PID {pid}"),
+            None => "vault is locked by another process".to_string(),
+        }));
+    }
+
+    let guard = VaultLock {
+        path: path.to_path_buf(),
+        file,
+        released: false,
+    };
+    let stamp = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let mut file = &guard.file;
+    file.set_len(0)?;
+    file.rewind()?;
+    writeln!(file, "{}\n{stamp}", std::process::id())?;
+    Ok(guard)
+}
+
+/// Poll [`acquire`] until it succeeds or `timeout` elapses.
+pub(crate) fn acquire_with_timeout(path: &Path, timeout: Duration) -> Result<VaultLock, MkbError> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match acquire(path) {
+            Err(MkbError::Vault(msg)) if Instant::now() < deadline => {
+                tracing::debug!("{msg}; retrying");
+                thread::sleep(POLL_INTERVAL);
+            }
+            other => return other,
+        }
+    }
+}
+
+/// PID recorded in the lock file, or `None` if it is empty or malformed.
+fn read_owner(file: &mut File) -> Option<u32> {
+    let mut content = String::new();
+    file.read_to_string(&mut content).ok()?;
+    content.lines().next().and_then(|l| l.trim().parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Vault;
+    use std::fs;
+
+    #[test]
+    fn lock_writes_pid_and_drop_releases() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+
+        let guard = vault.lock().unwrap();
+        let content = fs::read_to_string(guard.path()).unwrap();
+        assert_eq!(
+            content.lines().next(),
+            Some(std::process::id().to_string().as_str())
+        );
+
+        let path = guard.path().to_path_buf();
+        drop(guard);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "");
+        vault.lock().unwrap().unlock().unwrap();
+    }
+
+    #[test]
+    fn empty_lock_file_of_a_live_holder_is_respected() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+        let path = dir.path().join(".mkb").join(LOCK_FILE);
+
+        // A holder that has locked the file but not yet written its PID
+        let holder = File::create(&path).unwrap();
+        holder.try_lock_exclusive().unwrap();
+
+        let err = vault.lock().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "vault error: vault is locked by another process"
+        );
+        assert!(path.exists());
+        FileExt::unlock(&holder).unwrap();
+        vault.lock().unwrap();
+    }
+
+    #[test]
+    fn lock_held_by_live_pid_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        let _guard = Vault::init(&root).unwrap().lock().unwrap();
+
+        let err = thread::spawn(move || Vault::open(&root).unwrap().lock().unwrap_err())
+            .join()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            format!("vault error: vault is locked by PID {}", std::process::id())
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stale_lock_is_cleared() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::init(dir.path()).unwrap();
+
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let dead_pid = child.id();
+        child.wait().unwrap();
+        let path = dir.path().join(".mkb").join(LOCK_FILE);
+        fs::write(&path, format!("{dead_pid}\n2025-01-01T00:00:00Z\n")).unwrap();
+
+        let guard = vault.lock().unwrap();
+        let content = fs::read_to_string(guard.path()).unwrap();
+        assert!(content.starts_with(&std::process::id().to_string()));
+    }
+
+    #[test]
+    fn try_lock_waits_for_release() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        let vault = Vault::init(&root).unwrap();
+        let guard = vault.lock().unwrap();
+
+        let waiter = thread::spawn(move || {
+            Vault::open(&root)
+                .unwrap()
+                .try_lock(Duration::from_secs(5))
+                .map(|_| ())
+        });
+        thread::sleep(Duration::from_millis(100));
+        drop(guard);
+
+        assert!(waiter.join().unwrap().is_ok());
+    }
+
+    #[test]
+    fn try_lock_times_out_while_held() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        let _guard = Vault::init(&root).unwrap().lock().unwrap();
+
+        let started = Instant::now();
+        let result = thread::spawn(move || {
+            Vault::open(&root)
+                .unwrap()
+                .try_lock(Duration::from_millis(100))
+                .map(|_| ())
+        })
+        .join()
+        .unwrap();
+
+        assert!(matches!(result, Err(MkbError::Vault(_))));
+        assert!(started.elapsed() >= Duration::from_millis(100));
+    }
+}
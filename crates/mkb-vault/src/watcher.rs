@@ -3,14 +3,34 @@
 //! Uses the `notify` crate for cross-platform file system events
 //! (FSEvents on macOS, inotify on Linux, ReadDirectoryChanges on Windows).
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::mpsc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 
 use mkb_core::error::MkbError;
 
+/// Quiet period that ends a burst of events in [`VaultWatcher::next_batch`].
+///
+/// Editors often save a file as several writes (truncate, write, rename);
+/// waiting this long after the last one collapses them into one change.
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Longest a [`VaultWatcher::next_batch`] window stays open, however
+/// steadily events keep arriving.
+///
+/// A file rewritten every few hundred milliseconds (a log, a build output)
+/// would otherwise keep resetting the quiet period and nothing would ever
+/// be indexed.
+pub const DEFAULT_MAX_BATCH_AGE: Duration = Duration::from_secs(5);
+
+/// Events in one [`VaultWatcher::next_batch`] window beyond which the batch
+/// is flagged [`BatchFileEvent::overflowed`] and should be handled by
+/// rescanning the vault rather than file by file.
+pub const MAX_PENDING_EVENTS: usize = 1000;
+
 /// Events emitted by the vault watcher.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum VaultEvent {
@@ -20,10 +40,117 @@ pub enum VaultEvent {
     Removed(PathBuf),
 }
 
+impl VaultEvent {
+    /// The file this event is about.
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Changed(path) | Self::Removed(path) => path,
+        }
+    }
+}
+
+/// Every change seen in one debounce window, one entry per file.
+///
+/// A file created and then edited within the window is only in `created`;
+/// a file deleted and then written again is in `modified`; a file whose
+/// last event was a deletion is only in `deleted`. Paths keep the order in
+/// which they were first seen.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchFileEvent {
+    pub created: Vec<PathBuf>,
+    pub modified: Vec<PathBuf>,
+    pub deleted: Vec<PathBuf>,
+    /// More than [`MAX_PENDING_EVENTS`] events arrived in the window, e.g.
+    /// during a `git checkout`; a full reindex is cheaper than applying
+    /// them one by one.
+    pub overflowed: bool,
+}
+
+impl BatchFileEvent {
+    /// Number of files in the batch.
+    pub fn len(&self) -> usize {
+        self.created.len() + self.modified.len() + self.deleted.len()
+    }
+
+    /// Whether nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The batch as per-file events: creations and modifications first,
+    /// then deletions.
+    pub fn events(&self) -> impl Iterator<Item = VaultEvent> + '_ {
+        self.created
+            .iter()
+            .chain(&self.modified)
+            .map(|path| VaultEvent::Changed(path.clone()))
+            .chain(
+                self.deleted
+                    .iter()
+                    .map(|path| VaultEvent::Removed(path.clone())),
+            )
+    }
+}
+
+/// What happened to a path, as sent from the `notify` callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileChange {
+    Created,
+    Modified,
+    Deleted,
+}
+
+/// Folds the raw changes of one debounce window into a [`BatchFileEvent`].
+#[derive(Debug, Default)]
+struct BatchBuilder {
+    paths: Vec<(PathBuf, FileChange)>,
+    positions: HashMap<PathBuf, usize>,
+    received: usize,
+}
+
+impl BatchBuilder {
+    fn push(&mut self, change: FileChange, path: PathBuf) {
+        self.received += 1;
+        if self.received == MAX_PENDING_EVENTS + 1 {
+            tracing::warn!(
+                "More than {MAX_PENDING_EVENTS} file events pending; switching to a full reindex"
+            );
+        }
+        let Some(&i) = self.positions.get(&path) else {
+            self.positions.insert(path.clone(), self.paths.len());
+            self.paths.push((path, change));
+            return;
+        };
+        let seen = &mut self.paths[i].1;
+        *seen = match (*seen, change) {
+            (_, FileChange::Deleted) => FileChange::Deleted,
+            (FileChange::Created, _) => FileChange::Created,
+            _ => FileChange::Modified,
+        };
+    }
+
+    fn finish(self) -> BatchFileEvent {
+        let mut batch = BatchFileEvent {
+            overflowed: self.received > MAX_PENDING_EVENTS,
+            ..BatchFileEvent::default()
+        };
+        for (path, change) in self.paths {
+            match change {
+                FileChange::Created => batch.created.push(path),
+                FileChange::Modified => batch.modified.push(path),
+                FileChange::Deleted => batch.deleted.push(path),
+            }
+        }
+        batch
+    }
+}
+
 /// Watches a vault directory for file changes and emits events.
 pub struct VaultWatcher {
     _watcher: RecommendedWatcher,
-    receiver: mpsc::Receiver<VaultEvent>,
+    receiver: mpsc::Receiver<(FileChange, PathBuf)>,
+    debounce: Duration,
+    max_batch_age: Duration,
 }
 
 impl VaultWatcher {
@@ -56,14 +183,13 @@ impl VaultWatcher {
                         continue;
                     }
 
-                    let vault_event = match event.kind {
-                        EventKind::Create(_) | EventKind::Modify(_) => {
-                            VaultEvent::Changed(path.clone())
-                        }
-                        EventKind::Remove(_) => VaultEvent::Removed(path.clone()),
+                    let change = match event.kind {
+                        EventKind::Create(_) => FileChange::Created,
+                        EventKind::Modify(_) => FileChange::Modified,
+                        EventKind::Remove(_) => FileChange::Deleted,
                         _ => continue,
                     };
-                    let _ = tx_clone.send(vault_event);
+                    let _ = tx_clone.send((change, path.clone()));
                 }
             }
         })
@@ -76,21 +202,88 @@ impl VaultWatcher {
         Ok(Self {
             _watcher: watcher,
             receiver: rx,
+            debounce: DEFAULT_DEBOUNCE,
+            max_batch_age: DEFAULT_MAX_BATCH_AGE,
         })
     }
 
+    /// Use `delay` as the quiet period that ends a [`next_batch`] window
+    /// instead of [`DEFAULT_DEBOUNCE`].
+    ///
+    /// [`next_batch`]: Self::next_batch
+    #[must_use]
+    pub fn with_debounce(mut self, delay: Duration) -> Self {
+        self.debounce = delay;
+        self
+    }
+
+    /// The quiet period that ends a [`next_batch`](Self::next_batch) window.
+    pub fn debounce(&self) -> Duration {
+        self.debounce
+    }
+
+    /// Close a [`next_batch`](Self::next_batch) window `age` after its first
+    /// event even if events are still arriving (default
+    /// [`DEFAULT_MAX_BATCH_AGE`]).
+    #[must_use]
+    pub fn with_max_batch_age(mut self, age: Duration) -> Self {
+        self.max_batch_age = age;
+        self
+    }
+
     /// Try to receive the next event with a timeout.
     ///
     /// Returns `None` if no event is available within the timeout.
     pub fn recv_timeout(&self, timeout: Duration) -> Option<VaultEvent> {
-        self.receiver.recv_timeout(timeout).ok()
+        self.receiver
+            .recv_timeout(timeout)
+            .ok()
+            .map(|(change, path)| to_vault_event(change, path))
+    }
+
+    /// Wait up to `timeout` for a burst of events and return all of it as
+    /// one batch.
+    ///
+    /// After the first event, keeps collecting until no new event arrives
+    /// for the configured [`debounce`](Self::debounce), so a `git checkout`
+    /// touching a hundred files yields one batch rather than a hundred. A
+    /// window never stays open longer than the maximum batch age (see
+    /// [`with_max_batch_age`](Self::with_max_batch_age)); events after that
+    /// go into the next batch. An empty batch means nothing changed within
+    /// `timeout`.
+    pub fn next_batch(&self, timeout: Duration) -> BatchFileEvent {
+        let mut builder = BatchBuilder::default();
+        let Ok((change, path)) = self.receiver.recv_timeout(timeout) else {
+            return builder.finish();
+        };
+        builder.push(change, path);
+        let flush_at = Instant::now() + self.max_batch_age;
+        let mut quiet_until = (Instant::now() + self.debounce).min(flush_at);
+        while let Some(wait) = quiet_until.checked_duration_since(Instant::now()) {
+            let Ok((change, path)) = self.receiver.recv_timeout(wait) else {
+                break;
+            };
+            builder.push(change, path);
+            quiet_until = (Instant::now() + self.debounce).min(flush_at);
+        }
+        builder.finish()
     }
 
     /// Try to receive the next event without blocking.
     ///
     /// Returns `None` if no event is immediately available.
     pub fn try_recv(&self) -> Option<VaultEvent> {
-        self.receiver.try_recv().ok()
+        self.receiver
+            .try_recv()
+            .ok()
+            .map(|(change, path)| to_vault_event(change, path))
+    }
+}
+
+fn to_vault_event(change: FileChange, path: PathBuf) -> VaultEvent {
+    match change {
+        FileChange::Created | FileChange::Modified => VaultEvent::Changed(path),
+        FileChange::Deleted => VaultEvent::Removed(path),
     }
 }
 
@@ -187,6 +380,125 @@ mod tests {
         assert!(found_removed, "Expected watcher to emit Removed event");
     }
 
+    #[test]
+    fn next_batch_collapses_rapid_saves_into_one_event() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_root = dir.path();
+        fs::create_dir_all(vault_root.join("projects")).unwrap();
+
+        let watcher = VaultWatcher::start(vault_root)
+            .unwrap()
+            .with_debounce(Duration::from_millis(300));
+
+        let file = vault_root.join("projects").join("busy.md");
+        for i in 0..5 {
+            fs::write(&file, format!("# Busy\nrevision {i}")).unwrap();
+            std::thread::sleep(Duration::from_millis(20));
+        }
+
+        let batch = watcher.next_batch(Duration::from_secs(2));
+        assert_eq!(batch.len(), 1, "{batch:?}");
+        let events: Vec<VaultEvent> = batch.events().collect();
+        assert!(matches!(&events[0], VaultEvent::Changed(p) if p.ends_with("busy.md")));
+        assert!(watcher.next_batch(Duration::from_millis(400)).is_empty());
+    }
+
+    #[test]
+    fn next_batch_collects_rapid_writes_into_one_batch() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_root = dir.path();
+        fs::create_dir_all(vault_root.join("projects")).unwrap();
+
+        let watcher = VaultWatcher::start(vault_root)
+            .unwrap()
+            .with_debounce(Duration::from_millis(300));
+        assert_eq!(watcher.debounce(), Duration::from_millis(300));
+
+        for i in 0..100 {
+            let file = vault_root.join("projects").join(format!("doc-{i}.md"));
+            fs::write(&file, format!("# Doc {i}")).unwrap();
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        let batch = watcher.next_batch(Duration::from_secs(2));
+        assert_eq!(batch.len(), 100, "{batch:?}");
+        assert!(batch.deleted.is_empty());
+        assert!(!batch.overflowed);
+        assert!(watcher.next_batch(Duration::from_millis(200)).is_empty());
+    }
+
+    #[test]
+    fn next_batch_flushes_at_max_age_despite_steady_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_root = dir.path();
+        fs::create_dir_all(vault_root.join("projects")).unwrap();
+
+        let watcher = VaultWatcher::start(vault_root)
+            .unwrap()
+            .with_debounce(Duration::from_millis(300))
+            .with_max_batch_age(Duration::from_millis(600));
+
+        // Writes every 50 ms never leave a 300 ms quiet period
+        let file = vault_root.join("projects").join("log.md");
+        let writer = std::thread::spawn(move || {
+            for i in 0..60 {
+                fs::write(&file, format!("# Log\nline {i}")).unwrap();
+                std::thread::sleep(Duration::from_millis(50));
+            }
+        });
+
+        let start = Instant::now();
+        let batch = watcher.next_batch(Duration::from_secs(2));
+        let elapsed = start.elapsed();
+        assert_eq!(batch.len(), 1, "{batch:?}");
+        assert!(elapsed < Duration::from_millis(2000), "{elapsed:?}");
+
+        let next = watcher.next_batch(Duration::from_secs(2));
+        assert_eq!(next.len(), 1, "{next:?}");
+        writer.join().unwrap();
+    }
+
+    #[test]
+    fn batch_builder_keeps_one_entry_per_path() {
+        let mut builder = BatchBuilder::default();
+        builder.push(FileChange::Created, PathBuf::from("new.md"));
+        builder.push(FileChange::Modified, PathBuf::from("new.md"));
+        builder.push(FileChange::Modified, PathBuf::from("edited.md"));
+        builder.push(FileChange::Deleted, PathBuf::from("replaced.md"));
+        builder.push(FileChange::Created, PathBuf::from("replaced.md"));
+        builder.push(FileChange::Created, PathBuf::from("gone.md"));
+        builder.push(FileChange::Deleted, PathBuf::from("gone.md"));
+
+        let batch = builder.finish();
+        assert_eq!(batch.created, vec![PathBuf::from("new.md")]);
+        assert_eq!(
+            batch.modified,
+            vec![PathBuf::from("edited.md"), PathBuf::from("replaced.md")]
+        );
+        assert_eq!(batch.deleted, vec![PathBuf::from("gone.md")]);
+        assert_eq!(
+            batch.events().last(),
+            Some(VaultEvent::Removed(PathBuf::from("gone.md")))
+        );
+    }
+
+    #[test]
+    fn batch_builder_flags_overflow() {
+        let mut builder = BatchBuilder::default();
+        for _ in 0..MAX_PENDING_EVENTS {
+            builder.push(FileChange::Modified, PathBuf::from("busy.md"));
+        }
+        let mut over = BatchBuilder::default();
+        for _ in 0..=MAX_PENDING_EVENTS {
+            over.push(FileChange::Modified, PathBuf::from("busy.md"));
+        }
+
+        assert!(!builder.finish().overflowed);
+        let batch = over.finish();
+        assert!(batch.overflowed);
+        assert_eq!(batch.len(), 1);
+    }
+
     #[test]
     fn watcher_ignores_non_markdown() {
         let dir = tempfile::tempdir().unwrap();
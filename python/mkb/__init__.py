@@ -4,31 +4,81 @@ Python interface to the Rust-powered MKB core. All vault operations
 go through the native `_mkb_core` extension module built via PyO3.
 """
 
+from __future__ import annotations
+
+from typing import TYPE_CHECKING
+
 from mkb._mkb_core import (  # type: ignore[import-untyped]
     __version__,
+    DocumentNotFoundError,
+    DuplicateDocumentError,
+    IndexCorruptedError,
+    SchemaViolationError,
+    VaultTransaction,
+    add_annotation,
+    append_body,
+    assemble_context,
+    build_graph,
     create_document,
     delete_document,
+    delete_view,
+    detect_dangling_links,
     document_count,
     embedding_count,
     embedding_dim,
+    explain_mkql,
+    export_csv,
+    export_vault,
+    get_annotations,
+    get_documents_without_embedding,
+    get_embedding_stats,
+    get_link_graph,
+    get_newest_per_type,
+    get_query_history,
+    get_supersession_chain,
     has_embedding,
     init_vault,
+    list_views,
+    load_view,
     query_all,
+    query_by_confidence,
     query_by_type,
     query_mkql,
+    query_mkql_params,
+    query_mkql_with_transforms,
+    query_to_arrow,
     read_document,
+    rebuild_index,
+    rebuild_links_from_vault,
+    run_view,
+    save_view,
     search_fts,
     search_semantic,
+    set_query_history,
+    snapshot_at,
     store_embedding,
+    stream_mkql,
+    update_document,
+    validate_all,
     validate_temporal,
+    vault_diff,
     vault_status,
 )
 
+if TYPE_CHECKING:
+    import pandas as pd
+
 __all__ = [
     "__version__",
+    "DocumentNotFoundError",
+    "DuplicateDocumentError",
+    "SchemaViolationError",
+    "IndexCorruptedError",
     "init_vault",
     "create_document",
     "read_document",
+    "append_body",
+    "update_document",
     "delete_document",
     "search_fts",
     "search_semantic",
@@ -36,10 +86,50 @@ __all__ = [
     "has_embedding",
     "embedding_count",
     "embedding_dim",
+    "get_embedding_stats",
+    "get_documents_without_embedding",
     "query_mkql",
+    "query_mkql_params",
+    "query_mkql_with_transforms",
+    "set_query_history",
+    "get_query_history",
+    "add_annotation",
+    "get_annotations",
+    "stream_mkql",
+    "explain_mkql",
+    "assemble_context",
+    "query_to_arrow",
+    "query_to_dataframe",
     "query_all",
+    "get_newest_per_type",
+    "snapshot_at",
     "query_by_type",
+    "get_supersession_chain",
+    "build_graph",
+    "query_by_confidence",
+    "detect_dangling_links",
+    "rebuild_links_from_vault",
+    "get_link_graph",
+    "rebuild_index",
     "validate_temporal",
+    "validate_all",
+    "save_view",
+    "load_view",
+    "list_views",
+    "delete_view",
+    "run_view",
     "document_count",
     "vault_status",
+    "vault_diff",
+    "export_vault",
+    "export_csv",
+    "VaultTransaction",
 ]
+
+
+def query_to_dataframe(vault_path: str, mkql: str) -> pd.DataFrame:
+    """Run an MKQL query and return the results as a pandas DataFrame.
+
+    Column dtypes follow :func:`query_to_arrow`. Requires pyarrow and pandas.
+    """
+    return query_to_arrow(vault_path, mkql).to_pandas()
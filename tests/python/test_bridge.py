@@ -6,12 +6,17 @@ including vault CRUD, index operations, temporal gate, and vector search.
 
 from __future__ import annotations
 
+import csv
 import hashlib
+import importlib.util
 import json
+import sqlite3
 import struct
 import tempfile
 from pathlib import Path
 
+import pytest
+
 import mkb
 
 # === T-400.1: Vault CRUD ===
@@ -38,6 +43,68 @@ class TestVaultCRUD:
             assert doc["id"].startswith("proj-")
             assert "observed_at" in doc
             assert "valid_until" in doc
+            assert doc["age_label"].endswith("ago")
+            assert mkb.query_all(d)[0]["age_label"] == doc["age_label"]
+
+    def test_create_document_records_provenance(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            doc = mkb.create_document(
+                d, "project", "Alpha Project", "2025-02-10T00:00:00Z"
+            )
+            read = mkb.read_document(d, "project", doc["id"])
+            assert read["provenance_type"] == "api_ingestion"
+            assert read["ingested_via"] == "python"
+            result = json.loads(
+                mkb.query_mkql(
+                    d, "SELECT id FROM project WHERE provenance_type = 'api_ingestion'"
+                )
+            )
+            assert result["total"] == 1
+
+    def test_create_document_sanitizes_content(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            doc = mkb.create_document(
+                d,
+                "project",
+                "Cafe\u0301\x00",
+                "2025-02-10T00:00:00Z",
+                tags=[f"tag-{i}" for i in range(30)],
+            )
+            assert doc["title"] == "Caf\u00e9"
+            assert len(doc["tags"]) == 20
+
+    def test_document_includes_effective_confidence(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            doc = mkb.create_document(
+                d, "project", "Alpha", "2025-02-10T00:00:00Z"
+            )
+            # Observed well over a half-life ago, so decay is visible
+            assert doc["effective_confidence"] < doc["confidence"]
+            assert doc["effective_confidence"] > 0.0
+
+    def test_create_document_weights_confidence_by_precision(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            exact = mkb.create_document(
+                d, "project", "Exact", "2025-02-10T09:30:00Z", precision="exact"
+            )
+            inferred = mkb.create_document(
+                d, "project", "Inferred", "2025-02-10T00:00:00Z", precision="inferred"
+            )
+            explicit = mkb.create_document(
+                d,
+                "project",
+                "Explicit",
+                "2025-02-10T00:00:00Z",
+                precision="inferred",
+                confidence=1.0,
+            )
+            assert exact["confidence"] == 1.0
+            assert inferred["confidence"] == pytest.approx(0.4)
+            assert explicit["confidence"] == 1.0
 
     def test_create_document_with_body_and_tags(self) -> None:
         with tempfile.TemporaryDirectory() as d:
@@ -63,6 +130,52 @@ class TestVaultCRUD:
             assert doc["id"] == created["id"]
             assert doc["title"] == "Read Test"
 
+    def test_append_body(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            created = mkb.create_document(
+                d, "project", "Journal", "2025-02-10T00:00:00Z", body="Day one."
+            )
+            doc = mkb.append_body(d, "project", created["id"], "Day two.")
+            assert doc["body"].startswith("Day one.\n\n### ")
+            assert doc["body"].endswith("Day two.")
+
+            doc = mkb.append_body(
+                d, "project", created["id"], "Day three.", separator="\n", timestamp_header=False
+            )
+            assert doc["body"].endswith("Day two.\nDay three.")
+            assert mkb.search_fts(d, "three")[0]["id"] == created["id"]
+
+    def test_update_document(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            created = mkb.create_document(
+                d, "project", "Draft", "2025-02-10T00:00:00Z", body="Old body.", tags=["a", "b"]
+            )
+            doc = mkb.update_document(d, "project", created["id"], title="Final")
+            assert doc["title"] == "Final"
+            assert doc["body"].rstrip() == "Old body."
+            assert doc["created_at"] == created["created_at"]
+
+            doc = mkb.update_document(d, "project", created["id"], body="New body.")
+            assert doc["body"] == "New body."
+            assert mkb.search_fts(d, "New")[0]["id"] == created["id"]
+
+            doc = mkb.update_document(d, "project", created["id"], tags=["c"])
+            assert doc["tags"] == ["c"]
+            assert mkb.read_document(d, "project", created["id"])["tags"] == ["c"]
+
+    def test_update_document_rejects_valid_until_before_observed_at(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            created = mkb.create_document(d, "project", "Dated", "2025-02-10T00:00:00Z")
+            with pytest.raises(ValueError):
+                mkb.update_document(
+                    d, "project", created["id"], valid_until="2025-01-01T00:00:00Z"
+                )
+            doc = mkb.read_document(d, "project", created["id"])
+            assert doc["valid_until"] == created["valid_until"]
+
     def test_delete_document(self) -> None:
         with tempfile.TemporaryDirectory() as d:
             mkb.init_vault(d)
@@ -72,6 +185,23 @@ class TestVaultCRUD:
             archive_path = mkb.delete_document(d, "project", created["id"])
             assert "archive" in archive_path
 
+    def test_missing_document_raises_document_not_found(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            with pytest.raises(mkb.DocumentNotFoundError, match="proj-missing-001"):
+                mkb.read_document(d, "project", "proj-missing-001")
+            # Still a ValueError for callers that catch broadly
+            with pytest.raises(ValueError):
+                mkb.delete_document(d, "project", "proj-missing-001")
+
+    def test_corrupted_index_raises_index_corrupted(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            index_path = Path(d) / ".mkb" / "index" / "mkb.db"
+            index_path.write_text("not a database" * 100)
+            with pytest.raises(mkb.IndexCorruptedError):
+                mkb.document_count(d)
+
 
 # === T-400.2: Index Operations ===
 
@@ -92,6 +222,20 @@ class TestIndexOperations:
             results = mkb.search_fts(d, "machine learning")
             assert len(results) >= 1
             assert results[0]["title"] == "ML Project"
+            assert "snippet" not in results[0]
+
+    def test_search_fts_with_snippets(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            mkb.create_document(
+                d,
+                "project",
+                "ML Project",
+                "2025-02-10T00:00:00Z",
+                body="We trained neural networks on the quarterly sales data.",
+            )
+            results = mkb.search_fts(d, "neural", include_snippets=True)
+            assert "<b>neural</b>" in results[0]["snippet"]
 
     def test_query_mkql_json(self) -> None:
         with tempfile.TemporaryDirectory() as d:
@@ -106,6 +250,45 @@ class TestIndexOperations:
             parsed = json.loads(result)
             assert len(parsed) == 2
 
+    def test_query_results_carry_tags_as_lists(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            mkb.create_document(
+                d, "project", "Alpha", "2025-02-10T00:00:00Z", tags=["rust", "machine learning"]
+            )
+            mkb.create_document(d, "project", "Beta", "2025-02-11T00:00:00Z")
+            mkql = "SELECT title, tags FROM project ORDER BY title"
+
+            rows = json.loads(mkb.query_mkql(d, mkql))["rows"]
+            assert [r["fields"]["tags"] for r in rows] == [["rust", "machine learning"], []]
+            assert [r["tags"] for r in mkb.stream_mkql(d, mkql)] == [
+                ["rust", "machine learning"],
+                [],
+            ]
+
+    def test_query_mkql_tags_in(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            mkb.create_document(d, "project", "Alpha", "2025-02-10T00:00:00Z", tags=["rust"])
+            mkb.create_document(
+                d, "project", "Beta", "2025-02-10T00:00:00Z", tags=["ai", "machine learning"]
+            )
+            mkb.create_document(d, "project", "Gamma", "2025-02-10T00:00:00Z", tags=["go"])
+
+            def titles(mkql: str) -> list[str]:
+                rows = json.loads(mkb.query_mkql(d, mkql))["rows"]
+                return sorted(row["fields"]["title"] for row in rows)
+
+            assert titles("SELECT title FROM project WHERE TAGS IN ('rust', 'ai')") == [
+                "Alpha",
+                "Beta",
+            ]
+            assert titles(
+                "SELECT title FROM project WHERE TAGS INCLUDES ALL ('ai', 'machine learning')"
+            ) == ["Beta"]
+            with pytest.raises(ValueError):
+                mkb.query_mkql(d, "SELECT title FROM project WHERE TAGS IN ()")
+
     def test_query_mkql_table_format(self) -> None:
         with tempfile.TemporaryDirectory() as d:
             mkb.init_vault(d)
@@ -115,6 +298,246 @@ class TestIndexOperations:
             result = mkb.query_mkql(d, "SELECT * FROM project", format="table")
             assert "---" in result  # Table separator
 
+    def test_query_mkql_pivot(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            mkb.create_document(d, "project", "Alpha", "2025-02-10T00:00:00Z")
+            mkb.create_document(d, "project", "Beta", "2025-03-03T00:00:00Z")
+            mkb.create_document(d, "meeting", "Sync", "2025-03-04T00:00:00Z")
+            mkql = "SELECT PIVOT doc_type BY month(observed_at) COUNT(*)"
+
+            rows = json.loads(mkb.query_mkql(d, mkql))["rows"]
+            assert [r["fields"] for r in rows] == [
+                {"row": "meeting", "2025-02": 0, "2025-03": 1},
+                {"row": "project", "2025-02": 1, "2025-03": 1},
+            ]
+            table = mkb.query_mkql(d, mkql, format="pivot")
+            assert table.splitlines()[0].split("|")[1].strip() == "2025-02"
+
+    def test_query_history_is_opt_in(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            mkb.create_document(d, "project", "Alpha", "2025-02-10T00:00:00Z")
+            mkb.query_mkql(d, "SELECT * FROM project")
+            assert mkb.get_query_history(d) == []
+
+            mkb.set_query_history(d, True)
+            mkb.query_mkql(d, "SELECT * FROM project")
+            mkb.query_mkql(d, "SELECT * FROM meeting")
+
+            history = mkb.get_query_history(d)
+            assert [h["mkql"] for h in history] == [
+                "SELECT * FROM meeting",
+                "SELECT * FROM project",
+            ]
+            assert history[1]["result_count"] == 1
+            assert history[1]["duration_ms"] >= 0
+            assert len(mkb.get_query_history(d, limit=1)) == 1
+
+    def test_annotations_mark_documents_for_mkql(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            created = mkb.create_document(d, "project", "Alpha", "2025-02-10T00:00:00Z")
+            mkb.create_document(d, "project", "Beta", "2025-02-11T00:00:00Z")
+
+            annotation_id = mkb.add_annotation(
+                d, created["id"], "reviewed", content="Checked in Q2 review"
+            )
+            annotations = mkb.get_annotations(d, created["id"])
+            assert [a["id"] for a in annotations] == [annotation_id]
+            assert annotations[0]["content"] == "Checked in Q2 review"
+            assert annotations[0]["created_by"] == "python"
+
+            rows = json.loads(
+                mkb.query_mkql(d, "SELECT title FROM project WHERE ANNOTATED('reviewed')")
+            )["rows"]
+            assert [r["fields"]["title"] for r in rows] == ["Alpha"]
+
+    def test_query_mkql_yaml_format(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            mkb.create_document(
+                d, "project", "Alpha", "2025-02-10T00:00:00Z", body="Line one.\nLine two."
+            )
+            result = mkb.query_mkql(d, "SELECT title, body FROM project", format="yaml")
+            assert result == "title: Alpha\nbody: |-\n  Line one.\n  Line two.\n"
+
+    def test_query_mkql_context_format(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            created = mkb.create_document(
+                d, "project", "Context Test", "2025-02-10T00:00:00Z", body="Details."
+            )
+            result = mkb.query_mkql(d, "SELECT * FROM project", format="context")
+            assert result.startswith("## Context Test (project, observed 2025-02-10")
+            assert "Details." in result
+
+            result = mkb.query_mkql(
+                d, "SELECT * FROM project", format="context", template_str="{{id}}: {{title}}\n"
+            )
+            assert result == f"{created['id']}: Context Test\n"
+
+            with pytest.raises(ValueError):
+                mkb.query_mkql(d, "SELECT * FROM project", template_str="{{id}}")
+
+    def test_query_mkql_strict_rejects_unknown_fields(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            mkb.create_document(
+                d, "project", "Alpha", "2025-02-10T00:00:00Z"
+            )
+            query = "SELECT * FROM project WHERE titel = 'Alpha'"
+            with pytest.raises(ValueError, match="did you mean: title"):
+                mkb.query_mkql(d, query, strict=True)
+
+            result = mkb.query_mkql(
+                d, "SELECT * FROM project WHERE title = 'Alpha'", strict=True
+            )
+            assert len(json.loads(result)) == 1
+
+    def test_query_mkql_params(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            mkb.create_document(
+                d, "project", "Alpha", "2025-02-10T00:00:00Z"
+            )
+            mkb.create_document(
+                d, "project", "Beta", "2025-02-10T00:00:00Z"
+            )
+            result = mkb.query_mkql_params(
+                d,
+                "SELECT title FROM project WHERE title = :title",
+                {"title": "Beta"},
+            )
+            rows = json.loads(result)["rows"]
+            assert [r["fields"]["title"] for r in rows] == ["Beta"]
+
+            with pytest.raises(ValueError):
+                mkb.query_mkql_params(
+                    d, "SELECT title FROM project WHERE title = :title", {}
+                )
+
+    def test_query_mkql_with_transforms(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            mkb.create_document(
+                d, "project", "Alpha", "2025-02-10T00:00:00Z", confidence=0.9
+            )
+            mkb.create_document(
+                d, "project", "Beta", "2025-02-10T00:00:00Z", confidence=0.5
+            )
+            mkql = (
+                "SELECT id, title, confidence FROM project "
+                "UNION SELECT id, title, confidence FROM project"
+            )
+            deduped = json.loads(
+                mkb.query_mkql_with_transforms(d, mkql, transforms=[{"type": "dedup"}])
+            )
+            assert deduped["total"] == 2
+
+            filtered = json.loads(
+                mkb.query_mkql_with_transforms(
+                    d,
+                    mkql,
+                    transforms=[
+                        {"type": "dedup"},
+                        {"type": "confidence_filter", "min": 0.7},
+                        {"type": "project", "fields": ["title"]},
+                    ],
+                )
+            )
+            assert [r["fields"] for r in filtered["rows"]] == [{"title": "Alpha"}]
+
+            with pytest.raises(ValueError):
+                mkb.query_mkql_with_transforms(d, mkql, transforms=[{"type": "shuffle"}])
+
+    def test_stream_mkql_yields_rows_lazily(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            for title in ("Alpha", "Beta", "Gamma"):
+                mkb.create_document(
+                    d, "project", title, "2025-02-10T00:00:00Z", tags=["x"]
+                )
+            stream = mkb.stream_mkql(
+                d, "SELECT title, tags FROM project ORDER BY title"
+            )
+            assert iter(stream) is stream
+            first = next(stream)
+            assert first == {"title": "Alpha", "tags": ["x"]}
+            assert [r["title"] for r in stream] == ["Beta", "Gamma"]
+            with pytest.raises(StopIteration):
+                next(stream)
+
+            with pytest.raises(ValueError):
+                mkb.stream_mkql(d, "SELECT FROM")
+
+    def test_assemble_context_stays_within_budget(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            for title in ("Alpha", "Beta", "Gamma"):
+                mkb.create_document(
+                    d,
+                    "project",
+                    title,
+                    "2025-02-10T00:00:00Z",
+                    body="One sentence here. " * 40,
+                    valid_until="2999-01-01T00:00:00Z",
+                )
+            full = mkb.assemble_context(d, "SELECT * FROM project", 100_000)
+            assert full.count("## [project]") == 3
+
+            for tokenizer in ("cl100k_base", "whitespace"):
+                context = mkb.assemble_context(
+                    d, "SELECT * FROM project", 300, tokenizer
+                )
+                # Whole documents, then the last one cut after a sentence
+                assert context.count("## [project]") >= 2
+                assert context != full
+                assert len(context.split()) <= 300
+                assert context.rstrip().endswith("here.\n\n---")
+
+            with pytest.raises(ValueError):
+                mkb.assemble_context(d, "SELECT * FROM project", 100, "gpt2")
+
+    def test_assemble_context_excludes_expired_documents(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            mkb.create_document(
+                d, "project", "Current", "2025-02-10T00:00:00Z",
+                valid_until="2999-01-01T00:00:00Z",
+            )
+            mkb.create_document(
+                d, "project", "Stale", "2020-02-10T00:00:00Z",
+                valid_until="2020-03-01T00:00:00Z",
+            )
+            context = mkb.assemble_context(d, "SELECT * FROM project")
+            assert "Current" in context
+            assert "Stale" not in context
+
+            context = mkb.assemble_context(
+                d, "SELECT * FROM project", exclude_expired=False
+            )
+            assert "## [EXPIRED] [project] Stale" in context
+
+    def test_explain_mkql(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            result = mkb.explain_mkql(d, "SELECT * FROM project")
+            plan = json.loads(result)
+            assert "FROM documents" in plan["sql"]
+            assert plan["params"][0]["value"] == "'project'"
+
+    def test_rebuild_index_reports_progress(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            mkb.create_document(d, "project", "P1", "2025-02-10T00:00:00Z")
+            mkb.create_document(d, "project", "P2", "2025-02-10T00:00:00Z")
+            calls: list[tuple[int, int]] = []
+            stats = mkb.rebuild_index(d, lambda done, total: calls.append((done, total)))
+            assert stats["indexed"] == 2
+            assert calls == [(1, 2), (2, 2)]
+            assert mkb.document_count(d) == 2
+
     def test_query_all(self) -> None:
         with tempfile.TemporaryDirectory() as d:
             mkb.init_vault(d)
@@ -127,6 +550,31 @@ class TestIndexOperations:
             results = mkb.query_all(d)
             assert len(results) == 2
 
+    def test_get_newest_per_type(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            assert mkb.get_newest_per_type(d) == {}
+            mkb.create_document(d, "project", "Old", "2025-01-10T00:00:00Z")
+            mkb.create_document(d, "project", "New", "2025-02-10T00:00:00Z")
+            mkb.create_document(d, "meeting", "Standup", "2025-01-20T00:00:00Z")
+            newest = mkb.get_newest_per_type(d)
+            assert sorted(newest) == ["meeting", "project"]
+            assert newest["project"]["title"] == "New"
+            assert newest["meeting"]["title"] == "Standup"
+
+    def test_snapshot_at(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            mkb.create_document(d, "project", "Early", "2025-01-10T00:00:00Z")
+            mkb.create_document(d, "project", "Later", "2025-03-10T00:00:00Z")
+            assert mkb.snapshot_at(d, "2025-01-01") == []
+            titles = [doc["title"] for doc in mkb.snapshot_at(d, "2025-02-01")]
+            assert titles == ["Early"]
+            titles = [doc["title"] for doc in mkb.snapshot_at(d, "2025-04-01")]
+            assert titles == ["Later", "Early"]
+            with pytest.raises(ValueError):
+                mkb.snapshot_at(d, "not a date")
+
     def test_query_by_type(self) -> None:
         with tempfile.TemporaryDirectory() as d:
             mkb.init_vault(d)
@@ -140,6 +588,139 @@ class TestIndexOperations:
             assert len(results) == 1
             assert results[0]["type"] == "project"
 
+    def test_get_supersession_chain(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            v1 = mkb.create_document(d, "project", "Plan v1", "2025-02-10T00:00:00Z")
+            v2 = mkb.create_document(d, "project", "Plan v2", "2025-03-10T00:00:00Z")
+            path = Path(d) / "projects" / f"{v2['id']}.md"
+            content = path.read_text()
+            path.write_text(
+                content.replace("confidence:", f"supersedes: {v1['id']}\nconfidence:", 1)
+            )
+            mkb.rebuild_index(d)
+
+            chain = mkb.get_supersession_chain(d, v2["id"])
+            assert [c["id"] for c in chain] == [v1["id"], v2["id"]]
+            assert [c["id"] for c in mkb.get_supersession_chain(d, v1["id"])] == [v1["id"]]
+
+    def test_build_graph(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            v1 = mkb.create_document(d, "project", "Plan v1", "2025-02-10T00:00:00Z")
+            v2 = mkb.create_document(d, "project", "Plan v2", "2025-03-10T00:00:00Z")
+            mkb.create_document(d, "meeting", "Sync", "2025-03-11T00:00:00Z")
+            path = Path(d) / "projects" / f"{v2['id']}.md"
+            content = path.read_text()
+            path.write_text(
+                content.replace("confidence:", f"supersedes: {v1['id']}\nconfidence:", 1)
+            )
+            mkb.rebuild_index(d)
+
+            graph = json.loads(mkb.build_graph(d, center_id=v2["id"], depth=1))
+            assert len(graph["nodes"]) == 2
+            assert [(e["source"], e["target"]) for e in graph["edges"]] == [
+                (v2["id"], v1["id"])
+            ]
+            by_type = json.loads(mkb.build_graph(d, doc_type="meeting", depth=0))
+            assert [n["title"] for n in by_type["nodes"]] == ["Sync"]
+
+            with pytest.raises(ValueError, match="exactly one"):
+                mkb.build_graph(d)
+
+    def test_query_by_confidence(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            mkb.create_document(
+                d, "project", "P1", "2025-02-10T00:00:00Z"
+            )
+            mkb.create_document(
+                d, "meeting", "M1", "2025-02-10T00:00:00Z"
+            )
+            assert len(mkb.query_by_confidence(d, 0.5)) == 2
+            assert len(mkb.query_by_confidence(d, 0.0, 0.9)) == 0
+            results = mkb.query_by_confidence(d, 1.0, doc_type="meeting")
+            assert [r["type"] for r in results] == ["meeting"]
+
+            with pytest.raises(ValueError, match="confidence out of range"):
+                mkb.query_by_confidence(d, -0.1)
+            with pytest.raises(ValueError, match="confidence out of range"):
+                mkb.query_by_confidence(d, 0.0, 1.5, doc_type="project")
+
+    def test_detect_dangling_links(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            projects = Path(d) / "projects"
+            projects.mkdir(exist_ok=True)
+            (projects / "proj-linked-001.md").write_text(
+                "---\n"
+                "id: proj-linked-001\n"
+                "type: project\n"
+                "title: Linked\n"
+                'observed_at: "2025-02-10T00:00:00Z"\n'
+                'valid_until: "2025-08-10T00:00:00Z"\n'
+                "temporal_precision: day\n"
+                '_created_at: "2025-02-10T00:00:00Z"\n'
+                '_modified_at: "2025-02-10T00:00:00Z"\n'
+                "confidence: 1.0\n"
+                "links:\n"
+                "  - rel: owner\n"
+                "    target: people/nobody\n"
+                '    observed_at: "2025-02-10T00:00:00Z"\n'
+                "---\n"
+                "Body\n"
+            )
+            mkb.rebuild_index(d)
+            assert mkb.detect_dangling_links(d) == [
+                {
+                    "source_id": "proj-linked-001",
+                    "target_id": "people/nobody",
+                    "rel": "owner",
+                }
+            ]
+
+    def test_rebuild_links_from_vault(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            beta = mkb.create_document(d, "project", "Beta", "2025-02-10T00:00:00Z")
+            alpha = mkb.create_document(
+                d,
+                "project",
+                "Alpha",
+                "2025-02-10T00:00:00Z",
+                body=f"Blocked on [[{beta['id']}]] and [[proj-ghost-001|Ghost]].",
+            )
+            sync = mkb.create_document(
+                d,
+                "meeting",
+                "Sync",
+                "2025-02-11T00:00:00Z",
+                body=f"Discussed [[{alpha['id']}]] and [[{alpha['id']}#Plan]].",
+            )
+
+            assert mkb.rebuild_links_from_vault(d) == {"rebuilt": 3, "dangling": 1}
+            assert mkb.detect_dangling_links(d) == [
+                {
+                    "source_id": alpha["id"],
+                    "target_id": "proj-ghost-001",
+                    "rel": "links_to",
+                }
+            ]
+
+            graph = mkb.get_link_graph(d)
+            nodes = {n["id"]: n for n in graph["nodes"]}
+            assert set(nodes) == {alpha["id"], beta["id"], sync["id"], "proj-ghost-001"}
+            assert nodes[sync["id"]]["title"] == "Sync"
+            assert nodes["proj-ghost-001"]["type"] is None
+            assert sorted((e["source"], e["target"]) for e in graph["edges"]) == sorted(
+                [
+                    (alpha["id"], beta["id"]),
+                    (alpha["id"], "proj-ghost-001"),
+                    (sync["id"], alpha["id"]),
+                ]
+            )
+            assert all(e["rel"] == "links_to" for e in graph["edges"])
+
     def test_document_count(self) -> None:
         with tempfile.TemporaryDirectory() as d:
             mkb.init_vault(d)
@@ -174,6 +755,30 @@ class TestTemporalGate:
         assert result["valid"] is True
         assert result["temporal_precision"] == "month"
 
+    def test_validate_temporal_offsets(self) -> None:
+        cases = [
+            ("2025-06-15T10:00:00+00:00", 0, "2025-06-15T10:00:00+00:00"),
+            ("2025-06-15T10:00:00+02:00", 120, "2025-06-15T08:00:00+00:00"),
+            ("2025-06-15T10:00:00-05:00", -300, "2025-06-15T15:00:00+00:00"),
+        ]
+        for observed_at, offset, utc in cases:
+            result = mkb.validate_temporal(observed_at=observed_at, precision="exact")
+            assert result["valid"] is True
+            assert result["tz_offset_minutes"] == offset
+            assert result["observed_at"] == utc
+            assert result["temporal_precision"] == "exact"
+
+    def test_validate_temporal_date_only(self) -> None:
+        result = mkb.validate_temporal(observed_at="2025-06-15", precision="exact")
+        assert result["valid"] is True
+        assert result["observed_at"] == "2025-06-15T00:00:00+00:00"
+        assert result["tz_offset_minutes"] == 0
+        assert result["temporal_precision"] == "day"
+
+    def test_validate_temporal_invalid_datetime(self) -> None:
+        with pytest.raises(ValueError, match="invalid datetime"):
+            mkb.validate_temporal(observed_at="next thursday")
+
     def test_validate_temporal_invalid_precision(self) -> None:
         try:
             mkb.validate_temporal(
@@ -185,6 +790,95 @@ class TestTemporalGate:
             pass
 
 
+# === Transactions ===
+
+
+class TestVaultTransaction:
+    """All-or-nothing multi-document writes through PyO3 bridge."""
+
+    def test_commit_on_clean_exit(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            with mkb.VaultTransaction(d) as tx:
+                alpha = tx.create_document(
+                    "project", "Alpha", "2025-02-10T00:00:00Z"
+                )
+                owner = tx.create_document(
+                    "person", "Jane", "2025-02-10T00:00:00Z"
+                )
+                tx.link(alpha["id"], "owner", owner["id"])
+            assert mkb.document_count(d) == 2
+            doc = mkb.read_document(d, "project", alpha["id"])
+            assert doc["title"] == "Alpha"
+
+    def test_exception_leaves_vault_unchanged(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            mkb.create_document(
+                d, "project", "Existing", "2025-02-10T00:00:00Z"
+            )
+            try:
+                with mkb.VaultTransaction(d) as tx:
+                    tx.create_document(
+                        "project", "Alpha", "2025-02-10T00:00:00Z"
+                    )
+                    tx.create_document(
+                        "meeting", "Sync", "2025-02-10T00:00:00Z"
+                    )
+                    msg = "ingestion failed"
+                    raise RuntimeError(msg)
+            except RuntimeError:
+                pass
+            status = mkb.vault_status(d)
+            assert status["indexed_documents"] == 1
+            assert status["vault_files"] == 1
+            assert not (Path(d) / "meetings").exists()
+
+    def test_failure_during_commit_undoes_written_files(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            mkb.create_document(d, "project", "Existing", "2025-02-10T00:00:00Z")
+            tx = mkb.VaultTransaction(d)
+            tx.create_document("meeting", "Sync", "2025-02-10T00:00:00Z")
+            staged = tx.create_document("project", "Beta", "2025-02-10T00:00:00Z")
+            # Another writer takes the staged ID before the commit, so the
+            # second write fails after the first has landed
+            taken = mkb.create_document(d, "project", "Beta", "2025-02-10T00:00:00Z")
+            assert taken["id"] == staged["id"]
+
+            with pytest.raises(ValueError, match="rolled back"):
+                tx.commit()
+
+            assert not (Path(d) / "meetings").exists()
+            status = mkb.vault_status(d)
+            assert status["indexed_documents"] == 2
+            assert status["vault_files"] == 2
+            assert [doc["title"] for doc in mkb.query_by_type(d, "meeting")] == []
+            assert mkb.read_document(d, "project", taken["id"])["title"] == "Beta"
+
+    def test_same_title_gets_distinct_ids(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            with mkb.VaultTransaction(d) as tx:
+                a = tx.create_document("project", "Dup", "2025-02-10T00:00:00Z")
+                b = tx.create_document("project", "Dup", "2025-02-10T00:00:00Z")
+            assert a["id"] != b["id"]
+            assert mkb.document_count(d) == 2
+
+    def test_link_requires_staged_source(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            tx = mkb.VaultTransaction(d)
+            try:
+                tx.link("proj-missing-001", "owner", "people/jane")
+                msg = "Should have raised ValueError"
+                raise AssertionError(msg)
+            except ValueError:
+                pass
+            tx.rollback()
+            assert mkb.document_count(d) == 0
+
+
 # === Utility ===
 
 
@@ -202,11 +896,201 @@ class TestUtility:
             assert status["vault_files"] == 1
             assert status["index_synced"] is True
             assert status["rejection_count"] == 0
+            assert status["archived_documents"] == 0
+            assert status["newest_per_type"]["project"]["title"] == "P1"
+
+    def test_vault_diff_finds_unindexed_document(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            doc = mkb.create_document(d, "project", "P1", "2025-02-10T00:00:00Z")
+            assert mkb.vault_diff(d) == {
+                "in_vault_not_index": [],
+                "in_index_not_vault": [],
+                "modified_since_index": [],
+            }
+
+            conn = sqlite3.connect(Path(d) / ".mkb" / "index" / "mkb.db")
+            with conn:
+                conn.execute("DELETE FROM documents WHERE id = ?", (doc["id"],))
+            conn.close()
+
+            assert mkb.vault_diff(d)["in_vault_not_index"] == [doc["id"]]
+
+    def test_validate_all(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            good = mkb.create_document(d, "project", "Good", "2025-02-10T00:00:00Z")
+            bad = mkb.create_document(d, "project", "Bad", "2025-02-10T00:00:00Z")
+            mkb.update_document(d, "project", good["id"], fields={"status": "active"})
+            mkb.update_document(d, "project", bad["id"], fields={"status": "bogus"})
+            mkb.create_document(d, "note", "Untyped", "2025-02-10T00:00:00Z")
+
+            report = mkb.validate_all(d)
+            assert report["valid"] == 2
+            assert report["invalid"] == 1
+            assert report["warnings"] == 0
+            assert [e["id"] for e in report["errors"]] == [bad["id"]]
+            assert mkb.validate_all(d, strict=True)["invalid"] == 1
+
+    def test_export_vault_matches_read_document(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            p1 = mkb.create_document(
+                d, "project", "P1", "2025-02-10T00:00:00Z", body="Hello", tags=["a"]
+            )
+            mkb.create_document(d, "meeting", "M1", "2025-02-10T00:00:00Z")
+            out = Path(d) / "vault.jsonl"
+
+            stats = mkb.export_vault(d, str(out))
+            assert stats["exported"] == 2
+            assert stats["failed"] == 0
+
+            records = [json.loads(line) for line in out.read_text().splitlines()]
+            exported = next(r for r in records if r["id"] == p1["id"])
+            doc = mkb.read_document(d, "project", p1["id"])
+            assert exported.keys() == doc.keys()
+            assert exported["provenance_type"] == "api_ingestion"
+            for key, value in doc.items():
+                if key == "effective_confidence":
+                    assert exported[key] == pytest.approx(value)
+                else:
+                    assert exported[key] == value, key
+
+            mkb.export_vault(d, str(out), include_body=False)
+            assert all("body" not in json.loads(line) for line in out.read_text().splitlines())
+
+    def test_export_csv_round_trips(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            mkb.create_document(
+                d, "project", 'Alpha, "the first"', "2025-02-10T00:00:00Z", body="a\nb"
+            )
+            out = Path(d) / "projects.csv"
+
+            written = mkb.export_csv(d, "SELECT id, title, body FROM project", str(out))
+            assert written == 1
+            with out.open(newline="") as f:
+                rows = list(csv.DictReader(f))
+            assert rows[0]["title"] == 'Alpha, "the first"'
+            assert "body" not in rows[0]
+
+            mkb.export_csv(
+                d, "SELECT title, body FROM project", str(out), delimiter=";", include_body=True
+            )
+            with out.open(newline="") as f:
+                rows = list(csv.DictReader(f, delimiter=";"))
+            assert rows[0]["body"].startswith("a\\nb")
 
     def test_version_exposed(self) -> None:
         assert mkb.__version__ == "0.2.0"
 
 
+# === Saved views ===
+
+
+class TestSavedViews:
+    """Saved view CRUD through the PyO3 bridge."""
+
+    def test_view_round_trip(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            mkb.create_document(d, "project", "Alpha", "2025-02-10T00:00:00Z")
+            mkb.create_document(d, "meeting", "Sync", "2025-02-10T00:00:00Z")
+            query = "SELECT id, title FROM project"
+
+            saved = mkb.save_view(d, "projects", query, description="All projects")
+            assert saved["name"] == "projects"
+            assert saved["query"] == query
+            assert saved["description"] == "All projects"
+            assert saved["created_at"]
+            assert mkb.load_view(d, "projects") == saved
+
+            mkb.save_view(d, "all", "SELECT * FROM document")
+            views = mkb.list_views(d)
+            assert [v["name"] for v in views] == ["all", "projects"]
+            assert views[0]["description"] is None
+            assert set(views[1]) == {"name", "query", "description", "created_at"}
+
+            assert mkb.run_view(d, "projects") == mkb.query_mkql(d, query)
+            rows = json.loads(mkb.run_view(d, "projects"))["rows"]
+            assert [r["title"] for r in rows] == ["Alpha"]
+
+            mkb.delete_view(d, "projects")
+            assert [v["name"] for v in mkb.list_views(d)] == ["all"]
+            with pytest.raises(ValueError):
+                mkb.load_view(d, "projects")
+            with pytest.raises(ValueError):
+                mkb.delete_view(d, "projects")
+
+    def test_save_view_rejects_invalid_mkql(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            with pytest.raises(ValueError):
+                mkb.save_view(d, "broken", "SELEKT * FROM project")
+            with pytest.raises(ValueError, match="stauts"):
+                mkb.save_view(d, "typo", "SELECT * FROM project WHERE stauts = 'x'")
+            assert mkb.list_views(d) == []
+            mkb.save_view(d, "active", "SELECT * FROM project WHERE status = 'active'")
+            assert [v["name"] for v in mkb.list_views(d)] == ["active"]
+
+
+# === Arrow / pandas ===
+
+
+class TestArrow:
+    """Typed pyarrow tables and pandas DataFrames from MKQL queries."""
+
+    def _vault_with_docs(self, d: str) -> None:
+        mkb.init_vault(d)
+        mkb.create_document(
+            d, "project", "Alpha", "2025-02-10T00:00:00Z", tags=["rust", "core"]
+        )
+        mkb.create_document(d, "project", "Beta", "2025-03-01T12:30:00Z")
+
+    def test_query_to_arrow_column_types(self) -> None:
+        pa = pytest.importorskip("pyarrow")
+        with tempfile.TemporaryDirectory() as d:
+            self._vault_with_docs(d)
+            table = mkb.query_to_arrow(d, "SELECT * FROM project ORDER BY observed_at")
+
+            assert table.num_rows == 2
+            assert table.schema.field("id").type == pa.string()
+            assert table.schema.field("confidence").type == pa.float64()
+            assert table.schema.field("observed_at").type == pa.timestamp("us", tz="UTC")
+            assert table.schema.field("valid_until").type == pa.timestamp("us", tz="UTC")
+            assert table.schema.field("tags").type == pa.list_(pa.string())
+            assert table.column("tags").to_pylist() == [["rust", "core"], []]
+
+    def test_query_to_arrow_selected_fields_and_aliases(self) -> None:
+        pa = pytest.importorskip("pyarrow")
+        with tempfile.TemporaryDirectory() as d:
+            self._vault_with_docs(d)
+            table = mkb.query_to_arrow(d, "SELECT title AS name, observed_at FROM project")
+
+            assert table.column_names == ["name", "observed_at"]
+            assert table.schema.field("name").type == pa.string()
+            assert table.schema.field("observed_at").type == pa.timestamp("us", tz="UTC")
+
+    def test_query_to_dataframe_dtypes(self) -> None:
+        pytest.importorskip("pyarrow")
+        pytest.importorskip("pandas")
+        with tempfile.TemporaryDirectory() as d:
+            self._vault_with_docs(d)
+            df = mkb.query_to_dataframe(d, "SELECT * FROM project")
+
+            assert len(df) == 2
+            assert str(df["confidence"].dtype) == "float64"
+            assert str(df["observed_at"].dtype) == "datetime64[us, UTC]"
+
+    def test_query_to_arrow_without_pyarrow_raises_import_error(self) -> None:
+        if importlib.util.find_spec("pyarrow") is not None:
+            pytest.skip("pyarrow is installed")
+        with tempfile.TemporaryDirectory() as d:
+            self._vault_with_docs(d)
+            with pytest.raises(ImportError, match="pip install"):
+                mkb.query_to_arrow(d, "SELECT * FROM project")
+
+
 # === T-410: Embedding / Vector Search ===
 
 
@@ -268,6 +1152,39 @@ class TestEmbeddings:
             assert results[0]["id"] == ids[0]
             assert results[0]["distance"] < results[1]["distance"]
 
+    def test_semantic_search_by_doc_type(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            for doc_type, name in [("project", "Alpha"), ("meeting", "Sync")]:
+                doc = mkb.create_document(d, doc_type, name, "2025-02-10T00:00:00Z")
+                mkb.store_embedding(d, doc["id"], _test_embedding(name), "test-model")
+
+            results = mkb.search_semantic(
+                d, _test_embedding("Alpha"), limit=1, doc_type="meeting"
+            )
+            assert [r["type"] for r in results] == ["meeting"]
+
+    def test_embedding_stats_by_model(self) -> None:
+        with tempfile.TemporaryDirectory() as d:
+            mkb.init_vault(d)
+            ids = []
+            for name in ["Alpha", "Beta", "Gamma", "Delta"]:
+                doc = mkb.create_document(d, "project", name, "2025-02-10T00:00:00Z")
+                ids.append(doc["id"])
+            mkb.store_embedding(d, ids[0], _test_embedding("Alpha"), "model-a")
+            mkb.store_embedding(d, ids[1], _test_embedding("Beta"), "model-a")
+            mkb.store_embedding(d, ids[2], _test_embedding("Gamma"), "model-b")
+
+            stats = mkb.get_embedding_stats(d)
+            assert [(s["model"], s["count"]) for s in stats] == [
+                ("model-a", 2),
+                ("model-b", 1),
+            ]
+            assert stats[0]["oldest_created_at"] <= stats[0]["newest_created_at"]
+
+            missing = mkb.get_documents_without_embedding(d)
+            assert [doc["id"] for doc in missing] == [ids[3]]
+
     def test_embedding_dimension_mismatch(self) -> None:
         with tempfile.TemporaryDirectory() as d:
             mkb.init_vault(d)